@@ -0,0 +1,139 @@
+// Language runtime usage analytics
+// Rolls the worker's daily per-language usage buckets up into one report
+
+use optimus_common::types::{Language, LanguageUsageDay};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use utoipa::ToSchema;
+
+/// Default and maximum look-back for the report (buckets expire after 90 days)
+pub const DEFAULT_DAYS: u32 = 7;
+pub const MAX_DAYS: u32 = 90;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LanguageAnalyticsReport {
+    /// First and last day covered (YYYY-MM-DD, UTC)
+    pub from: String,
+    pub to: String,
+    pub languages: Vec<LanguageAnalytics>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LanguageAnalytics {
+    pub language: Language,
+    pub jobs: u64,
+    /// Jobs per runtime version
+    pub versions: BTreeMap<String, u64>,
+    /// Jobs per resource limit tier (e.g. "512mb/1cpu")
+    pub tiers: BTreeMap<String, u64>,
+    /// Absent when no jobs ran in the window
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_bytes: Option<Percentiles>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub execution_ms: Option<Percentiles>,
+    /// Jobs per day, oldest first
+    pub daily: Vec<DailyJobs>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DailyJobs {
+    pub date: String,
+    pub jobs: u64,
+}
+
+/// Nearest-rank percentiles over the sampled jobs
+///
+/// Each day keeps at most its most recent 1000 samples per language, so
+/// busy days are approximated by their latest jobs.
+#[derive(Debug, PartialEq, Eq, Serialize, ToSchema)]
+pub struct Percentiles {
+    pub p50: u64,
+    pub p90: u64,
+    pub p99: u64,
+    pub max: u64,
+}
+
+fn percentiles(samples: &mut [u64]) -> Option<Percentiles> {
+    if samples.is_empty() {
+        return None;
+    }
+    samples.sort_unstable();
+    let rank = |p: usize| samples[(samples.len() * p).div_ceil(100).max(1) - 1];
+    Some(Percentiles {
+        p50: rank(50),
+        p90: rank(90),
+        p99: rank(99),
+        max: samples[samples.len() - 1],
+    })
+}
+
+/// Combine a language's daily buckets (oldest first) into one summary
+pub fn summarize(language: Language, days: Vec<(String, LanguageUsageDay)>) -> LanguageAnalytics {
+    let mut summary = LanguageAnalytics {
+        language,
+        jobs: 0,
+        versions: BTreeMap::new(),
+        tiers: BTreeMap::new(),
+        source_bytes: None,
+        execution_ms: None,
+        daily: Vec::with_capacity(days.len()),
+    };
+    let mut source_bytes = Vec::new();
+    let mut execution_ms = Vec::new();
+
+    for (date, day) in days {
+        summary.jobs += day.jobs;
+        for (version, count) in day.versions {
+            *summary.versions.entry(version).or_default() += count;
+        }
+        for (tier, count) in day.tiers {
+            *summary.tiers.entry(tier).or_default() += count;
+        }
+        source_bytes.extend(day.source_bytes);
+        execution_ms.extend(day.execution_ms);
+        summary.daily.push(DailyJobs { date, jobs: day.jobs });
+    }
+
+    summary.source_bytes = percentiles(&mut source_bytes);
+    summary.execution_ms = percentiles(&mut execution_ms);
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentiles_nearest_rank() {
+        assert_eq!(percentiles(&mut []), None);
+
+        let mut samples: Vec<u64> = (1..=100).rev().collect();
+        assert_eq!(percentiles(&mut samples), Some(Percentiles { p50: 50, p90: 90, p99: 99, max: 100 }));
+        assert_eq!(percentiles(&mut [7]), Some(Percentiles { p50: 7, p90: 7, p99: 7, max: 7 }));
+    }
+
+    #[test]
+    fn test_summarize_merges_days() {
+        let day = |jobs: u64, version: &str, samples: Vec<u64>| LanguageUsageDay {
+            jobs,
+            versions: [(version.to_string(), jobs)].into_iter().collect(),
+            tiers: [("256mb/0.5cpu".to_string(), jobs)].into_iter().collect(),
+            source_bytes: samples.clone(),
+            execution_ms: samples,
+        };
+        let summary = summarize(
+            Language::Python,
+            vec![
+                ("2026-10-15".to_string(), day(2, "3.11-slim", vec![10, 20])),
+                ("2026-10-16".to_string(), day(1, "3.12-slim", vec![30])),
+            ],
+        );
+
+        assert_eq!(summary.jobs, 3);
+        assert_eq!(summary.versions["3.11-slim"], 2);
+        assert_eq!(summary.versions["3.12-slim"], 1);
+        assert_eq!(summary.tiers["256mb/0.5cpu"], 3);
+        assert_eq!(summary.execution_ms.unwrap().max, 30);
+        assert_eq!(summary.daily.len(), 2);
+    }
+}
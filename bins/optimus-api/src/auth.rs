@@ -0,0 +1,253 @@
+// Authentication and role-based access control
+// Verifies JWT bearer tokens (HS256 or RS256) and enforces roles on admin routes
+
+use axum::{
+    extract::{Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Json, Response},
+};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use crate::handlers::{ErrorDetail, ErrorResponse};
+use crate::AppState;
+
+/// Caller role carried in the token's `role` claim
+/// Roles are ordered: each role can do everything the ones below it can
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    #[default]
+    User,
+    /// Human reviewer - may attach feedback to results
+    Grader,
+    Admin,
+}
+
+/// JWT claims accepted by the API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Claims {
+    pub sub: String,
+    #[serde(default)]
+    pub role: Role,
+    pub exp: u64,
+}
+
+/// Authenticated caller, inserted into request extensions by `authenticate`
+#[derive(Debug, Clone)]
+pub struct AuthContext {
+    pub subject: String,
+    pub role: Role,
+}
+
+/// Token verifier configured from environment
+///
+/// - `JWT_ALGORITHM`: HS256 (default) or RS256
+/// - `JWT_SECRET`: shared secret for HS256
+/// - `JWT_PUBLIC_KEY_PATH`: PEM public key for RS256
+/// - `JWT_ISSUER` / `JWT_AUDIENCE`: optional claim checks
+///
+/// With no key configured, bearer tokens are rejected and admin routes are closed.
+#[derive(Clone)]
+pub struct AuthConfig {
+    verifier: Option<(DecodingKey, Validation)>,
+}
+
+impl AuthConfig {
+    pub fn from_env() -> Result<Self, String> {
+        let algorithm = match std::env::var("JWT_ALGORITHM")
+            .unwrap_or_else(|_| "HS256".to_string())
+            .to_uppercase()
+            .as_str()
+        {
+            "HS256" => Algorithm::HS256,
+            "RS256" => Algorithm::RS256,
+            other => return Err(format!("Unsupported JWT_ALGORITHM '{}' (expected HS256 or RS256)", other)),
+        };
+
+        let key = match algorithm {
+            Algorithm::RS256 => match std::env::var("JWT_PUBLIC_KEY_PATH") {
+                Ok(path) => {
+                    let pem = std::fs::read(&path)
+                        .map_err(|e| format!("Failed to read JWT public key {}: {}", path, e))?;
+                    Some(DecodingKey::from_rsa_pem(&pem)
+                        .map_err(|e| format!("Invalid RS256 public key {}: {}", path, e))?)
+                }
+                Err(_) => None,
+            },
+            _ => std::env::var("JWT_SECRET")
+                .ok()
+                .filter(|s| !s.is_empty())
+                .map(|secret| DecodingKey::from_secret(secret.as_bytes())),
+        };
+
+        let verifier = key.map(|key| {
+            let mut validation = Validation::new(algorithm);
+            if let Ok(issuer) = std::env::var("JWT_ISSUER") {
+                validation.set_issuer(&[issuer]);
+            }
+            match std::env::var("JWT_AUDIENCE") {
+                Ok(audience) => validation.set_audience(&[audience]),
+                Err(_) => validation.validate_aud = false,
+            }
+            (key, validation)
+        });
+
+        match &verifier {
+            Some(_) => info!("JWT authentication enabled ({:?})", algorithm),
+            None => warn!("JWT authentication not configured - admin routes are disabled"),
+        }
+
+        Ok(Self { verifier })
+    }
+
+    /// Build a config from an explicit key (tests and embedding)
+    #[cfg(test)]
+    pub fn with_key(key: DecodingKey, algorithm: Algorithm) -> Self {
+        let mut validation = Validation::new(algorithm);
+        validation.validate_aud = false;
+        Self { verifier: Some((key, validation)) }
+    }
+
+    /// Verify a raw bearer token and return the caller context
+    pub fn verify(&self, token: &str) -> Result<AuthContext, String> {
+        let (key, validation) = self.verifier
+            .as_ref()
+            .ok_or_else(|| "Authentication is not configured".to_string())?;
+
+        let data = jsonwebtoken::decode::<Claims>(token, key, validation)
+            .map_err(|e| format!("Invalid token: {}", e))?;
+
+        Ok(AuthContext {
+            subject: data.claims.sub,
+            role: data.claims.role,
+        })
+    }
+}
+
+fn auth_error(status: StatusCode, code: &str, message: String) -> Response {
+    (
+        status,
+        Json(ErrorResponse {
+            error: ErrorDetail {
+                code: code.to_string(),
+                message,
+                params: vec![],
+            },
+        }),
+    ).into_response()
+}
+
+/// Middleware: parse an optional `Authorization: Bearer` token
+///
+/// - No header → request continues anonymously
+/// - Valid token → `AuthContext` inserted into request extensions
+/// - Invalid token → 401
+pub async fn authenticate(
+    State(state): State<Arc<AppState>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let token = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(|t| t.trim().to_string());
+
+    if let Some(token) = token {
+        match state.auth.verify(&token) {
+            Ok(ctx) => {
+                request.extensions_mut().insert(ctx);
+            }
+            Err(message) => {
+                warn!(error = %message, "Rejected request with invalid bearer token");
+                return auth_error(StatusCode::UNAUTHORIZED, "INVALID_TOKEN", message);
+            }
+        }
+    }
+
+    next.run(request).await
+}
+
+/// Middleware: require an authenticated caller with the admin role
+/// Must run after `authenticate`
+pub async fn require_admin(request: Request, next: Next) -> Response {
+    match request.extensions().get::<AuthContext>() {
+        Some(ctx) if ctx.role >= Role::Admin => next.run(request).await,
+        Some(ctx) => {
+            warn!(subject = %ctx.subject, role = ?ctx.role, "Rejected non-admin caller on admin route");
+            auth_error(
+                StatusCode::FORBIDDEN,
+                "FORBIDDEN",
+                "Admin role required".to_string(),
+            )
+        }
+        None => auth_error(
+            StatusCode::UNAUTHORIZED,
+            "UNAUTHENTICATED",
+            "Admin bearer token required".to_string(),
+        ),
+    }
+}
+
+/// Middleware: require an authenticated caller with at least the grader role
+/// Must run after `authenticate`
+pub async fn require_grader(request: Request, next: Next) -> Response {
+    match request.extensions().get::<AuthContext>() {
+        Some(ctx) if ctx.role >= Role::Grader => next.run(request).await,
+        Some(ctx) => {
+            warn!(subject = %ctx.subject, role = ?ctx.role, "Rejected non-grader caller on grader route");
+            auth_error(
+                StatusCode::FORBIDDEN,
+                "FORBIDDEN",
+                "Grader role required".to_string(),
+            )
+        }
+        None => auth_error(
+            StatusCode::UNAUTHORIZED,
+            "UNAUTHENTICATED",
+            "Grader bearer token required".to_string(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::{EncodingKey, Header};
+
+    fn token(secret: &str, role: Role) -> String {
+        let claims = Claims {
+            sub: "tester".to_string(),
+            role,
+            exp: (chrono::Utc::now().timestamp() + 3600) as u64,
+        };
+        jsonwebtoken::encode(&Header::default(), &claims, &EncodingKey::from_secret(secret.as_bytes())).unwrap()
+    }
+
+    #[test]
+    fn test_hs256_roundtrip() {
+        let auth = AuthConfig::with_key(DecodingKey::from_secret(b"s3cret"), Algorithm::HS256);
+
+        let ctx = auth.verify(&token("s3cret", Role::Admin)).unwrap();
+        assert_eq!(ctx.subject, "tester");
+        assert_eq!(ctx.role, Role::Admin);
+
+        assert!(auth.verify(&token("wrong", Role::Admin)).is_err());
+    }
+
+    #[test]
+    fn test_role_defaults_to_user() {
+        let claims: Claims = serde_json::from_str(r#"{"sub":"a","exp":1}"#).unwrap();
+        assert_eq!(claims.role, Role::User);
+        assert!(Role::Admin > Role::User);
+        assert!(Role::Admin > Role::Grader && Role::Grader > Role::User);
+
+        let claims: Claims = serde_json::from_str(r#"{"sub":"a","role":"grader","exp":1}"#).unwrap();
+        assert_eq!(claims.role, Role::Grader);
+    }
+}
@@ -0,0 +1,111 @@
+// Worker availability for submissions
+// Rejects or parks jobs for languages with no live workers and announces when they return
+
+use optimus_common::redis;
+use optimus_common::types::{Language, WebhookDelivery};
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+use crate::metrics;
+
+/// How often worker presence is re-checked for every language
+const WATCH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// What to do with a submission when its language has no live workers
+///
+/// - `NO_WORKERS_POLICY=park` (default): queue it, flag the response `parked`
+///   and announce when workers come back
+/// - `NO_WORKERS_POLICY=reject`: 503 `NO_WORKERS`
+/// - `WORKERS_RETURNED_WEBHOOK_URL`: optional `workers.returned` callback
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoWorkersPolicy {
+    Park,
+    Reject,
+}
+
+impl NoWorkersPolicy {
+    pub fn from_env() -> Result<Self, String> {
+        match std::env::var("NO_WORKERS_POLICY") {
+            Err(_) => Ok(Self::Park),
+            Ok(value) => Self::parse(&value),
+        }
+    }
+
+    fn parse(value: &str) -> Result<Self, String> {
+        match value.trim().to_lowercase().as_str() {
+            "" | "park" => Ok(Self::Park),
+            "reject" => Ok(Self::Reject),
+            other => Err(format!("NO_WORKERS_POLICY must be 'park' or 'reject', got '{}'", other)),
+        }
+    }
+}
+
+/// Background task: export live worker counts and, once a language with
+/// parked jobs has workers again, announce that its backlog is draining
+pub async fn watch_loop(mut conn: ::redis::aio::ConnectionManager, webhook_url: Option<String>) {
+    info!("Worker availability watcher started");
+
+    let mut ticker = tokio::time::interval(WATCH_INTERVAL);
+    loop {
+        ticker.tick().await;
+        for language in Language::all_variants() {
+            let live = match redis::count_live_workers(&mut conn, language).await {
+                Ok(live) => live,
+                Err(e) => {
+                    error!(language = %language, error = %e, "Failed to count live workers");
+                    continue;
+                }
+            };
+            metrics::record_live_workers(&language.to_string(), live);
+            if live == 0 {
+                continue;
+            }
+
+            // Exactly one replica takes the count, so the event fires once
+            let parked = match redis::take_parked_jobs(&mut conn, language).await {
+                Ok(parked) => parked,
+                Err(e) => {
+                    error!(language = %language, error = %e, "Failed to read parked jobs");
+                    continue;
+                }
+            };
+            if parked == 0 {
+                continue;
+            }
+
+            info!(language = %language, parked_jobs = parked, live_workers = live, "Workers returned - parked backlog draining");
+            metrics::record_workers_returned(&language.to_string());
+
+            if let Some(url) = &webhook_url {
+                let body = serde_json::json!({
+                    "event": "workers.returned",
+                    "language": language,
+                    "parked_jobs": parked,
+                    "live_workers": live,
+                    "at": chrono::Utc::now().to_rfc3339(),
+                });
+                let delivery = WebhookDelivery {
+                    url: url.clone(),
+                    event: "workers.returned".to_string(),
+                    body: body.to_string(),
+                };
+                if let Err(e) = redis::push_webhook(&mut conn, &delivery).await {
+                    warn!(language = %language, error = %e, "Failed to queue workers.returned webhook");
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_parsing() {
+        assert_eq!(NoWorkersPolicy::parse("park"), Ok(NoWorkersPolicy::Park));
+        assert_eq!(NoWorkersPolicy::parse(" Reject "), Ok(NoWorkersPolicy::Reject));
+        assert_eq!(NoWorkersPolicy::parse(""), Ok(NoWorkersPolicy::Park));
+        assert!(NoWorkersPolicy::parse("drop").is_err());
+    }
+}
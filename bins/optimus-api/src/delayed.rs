@@ -0,0 +1,39 @@
+// Delayed job release
+// Moves jobs held back by a batch's spread/jitter onto their queues once due
+
+use optimus_common::redis;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// How often due jobs are looked for (bounds how late a job is released)
+const RELEASE_INTERVAL: Duration = Duration::from_millis(500);
+/// Jobs moved per Redis call; a larger backlog is drained over consecutive calls
+const RELEASE_BATCH: usize = 500;
+
+/// Background task: release due delayed jobs
+/// Safe to run on every API replica - each release is atomic in Redis
+pub async fn release_loop(mut conn: ::redis::aio::ConnectionManager) {
+    info!("Delayed job releaser started");
+
+    let mut ticker = tokio::time::interval(RELEASE_INTERVAL);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        ticker.tick().await;
+        loop {
+            match redis::release_delayed_jobs(&mut conn, RELEASE_BATCH).await {
+                Ok(released) => {
+                    if released > 0 {
+                        debug!(released, "Released delayed jobs");
+                    }
+                    if released < RELEASE_BATCH {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    warn!(error = %e, "Failed to release delayed jobs");
+                    break;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,90 @@
+// Submission rate forecast for autoscalers
+// Smooths per-language submissions/minute so workers can scale before queue depth spikes
+
+use optimus_common::redis;
+use optimus_common::types::{Language, SubmissionForecast};
+use std::time::Duration;
+use tracing::{error, info};
+
+use crate::metrics;
+
+/// Weight of the most recent minute (higher reacts faster, smooths less)
+pub const ALPHA: f64 = 0.3;
+/// Completed minutes fed into the EWMA
+pub const WINDOW_MINUTES: u32 = 15;
+/// How often the forecast is recomputed and published
+const REFRESH_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Exponentially weighted moving average of per-minute counts (oldest first)
+/// Seeded with the oldest count; an empty window forecasts zero
+pub fn ewma(counts: &[u64]) -> f64 {
+    let mut iter = counts.iter().map(|&c| c as f64);
+    let Some(first) = iter.next() else {
+        return 0.0;
+    };
+    iter.fold(first, |avg, count| ALPHA * count + (1.0 - ALPHA) * avg)
+}
+
+/// Compute a language's forecast from its per-minute submission counters
+pub async fn compute(
+    conn: &mut ::redis::aio::ConnectionManager,
+    language: &Language,
+) -> ::redis::RedisResult<SubmissionForecast> {
+    let now = chrono::Utc::now();
+    let current = now.timestamp() / 60;
+    let window = WINDOW_MINUTES as i64;
+
+    // Completed minutes plus the in-progress one in a single MGET
+    let mut counts = redis::get_submission_counts(conn, language, current - window, WINDOW_MINUTES as usize + 1).await?;
+    let current_minute = counts.pop().unwrap_or(0);
+
+    Ok(SubmissionForecast {
+        language: *language,
+        ewma_per_minute: ewma(&counts),
+        current_minute,
+        window_minutes: WINDOW_MINUTES,
+        updated_at: now.to_rfc3339(),
+    })
+}
+
+/// Background task: recompute every language's forecast, export it as
+/// gauges and publish it to Redis for external scalers
+pub async fn refresh_loop(mut conn: ::redis::aio::ConnectionManager) {
+    info!("Submission forecast refresher started");
+
+    let mut ticker = tokio::time::interval(REFRESH_INTERVAL);
+    loop {
+        ticker.tick().await;
+        for language in Language::all_variants() {
+            let forecast = match compute(&mut conn, language).await {
+                Ok(forecast) => forecast,
+                Err(e) => {
+                    error!(language = %language, error = %e, "Failed to compute submission forecast");
+                    continue;
+                }
+            };
+            metrics::record_submission_forecast(&forecast);
+            if let Err(e) = redis::store_forecast(&mut conn, &forecast).await {
+                error!(language = %language, error = %e, "Failed to publish submission forecast");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ewma_tracks_recent_minutes() {
+        assert_eq!(ewma(&[]), 0.0);
+        assert_eq!(ewma(&[10, 10, 10]), 10.0);
+
+        // A burst in the last minute pulls the average up by ALPHA of the jump
+        let rate = ewma(&[10, 10, 20]);
+        assert!((rate - 13.0).abs() < 1e-9);
+
+        // Older minutes weigh less than newer ones
+        assert!(ewma(&[0, 0, 30]) > ewma(&[0, 30, 0]));
+    }
+}
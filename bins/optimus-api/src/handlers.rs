@@ -1,35 +1,122 @@
 // HTTP route handlers for the Optimus API
 
 use axum::{
-    extract::{State, Path},
+    extract::{ConnectInfo, Extension, State, Path, Query},
     http::{StatusCode, HeaderMap},
     response::{IntoResponse, Json},
 };
-use optimus_common::types::{JobRequest, Language};
+use optimus_common::types::{ArchiveFormat, Comparison, ExpectedKind, FixtureFile, IdempotencyRecord, JobRequest, Language, Placement, Priority, ProjectArchive, ScoringPolicy, SubmissionForecast, TimeLimitMode, TimeScoring, WorkerInfo};
 use optimus_common::redis;
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
 use std::sync::Arc;
 use uuid::Uuid;
 use tracing::{info, error, warn};
 
+use crate::analytics;
+use crate::availability::NoWorkersPolicy;
+use crate::forecast;
 use crate::AppState;
+use crate::auth::AuthContext;
 use crate::metrics;
+use crate::rate_limit;
+use crate::result_store;
+use crate::validation;
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
 pub struct SubmitRequest {
     pub language: Language,
     pub source_code: String,
+    /// May be omitted when the problem or batch has a `test_suite`
+    #[serde(default)]
     pub test_cases: Vec<TestCaseInput>,
     #[serde(default = "default_timeout")]
     pub timeout_ms: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_scoring: Option<TimeScoring>,
+    /// `cpu` applies `timeout_ms` to CPU time instead of wall time
+    #[serde(default, skip_serializing_if = "TimeLimitMode::is_wall")]
+    pub time_limit_mode: TimeLimitMode,
+    #[serde(default)]
+    pub priority: Priority,
+    /// Batch created via `POST /batches` that this job counts towards
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub batch_id: Option<Uuid>,
+    /// Default output comparison for test cases that don't set their own
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comparison: Option<Comparison>,
+    /// Score presentation errors (right answer, wrong whitespace) as passes
+    /// (default: the language's `evaluation.accept_presentation_errors`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accept_presentation_errors: Option<bool>,
+    /// `all_or_nothing` awards max_score only when every test passes (default: weighted)
+    #[serde(default, skip_serializing_if = "ScoringPolicy::is_weighted")]
+    pub scoring: ScoringPolicy,
+    /// Server-side problem (`POST /admin/problems`); its limits replace
+    /// `timeout_ms`, `time_limit_mode`, `memory_limit_mb`, `cpu_limit` and the comparison settings
+    /// (including `accept_presentation_errors`) in this request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub problem_id: Option<String>,
+    /// Container memory for this job (clamped to the language's `max_memory_limit_mb`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_limit_mb: Option<u32>,
+    /// Container CPU cores for this job (clamped to the language's `max_cpu_limit`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_limit: Option<f64>,
+    /// Answer from the result of an identical earlier submission (same language
+    /// version, source, tests, limits and scoring) instead of executing again
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub cache: bool,
+    /// Run only on workers matching these constraints (a pool declared in
+    /// the language's `placements`); default: any worker of the language
+    #[serde(default, skip_serializing_if = "Placement::is_any")]
+    pub placement: Placement,
+    /// Attach GPU devices to the job's containers (the language must have `gpu` configured)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub gpu: bool,
+    /// Test containers run at once (clamped to the language's `concurrency.max_parallel_tests`;
+    /// 1 runs tests sequentially)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_parallel_tests: Option<u32>,
+    /// Set only by `POST /execute/archive`
+    #[serde(skip_deserializing, default, skip_serializing_if = "Option::is_none")]
+    #[schema(ignore)]
+    pub archive: Option<ProjectArchive>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
 pub struct TestCaseInput {
+    /// Program stdin, at most 10 MiB (inputs beyond the 2 MB request body
+    /// limit need a chunked test suite upload)
     pub input: String,
-    pub expected_output: String,
+    /// May be omitted when `expected_outputs` is given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_output: Option<String>,
+    /// Additional acceptable outputs (any-of)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub expected_outputs: Vec<String>,
     #[serde(default = "default_weight")]
     pub weight: u32,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fixtures: Vec<FixtureFile>,
+    /// Command-line arguments passed to the program
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    /// Extra environment variables (ordered, so the idempotency fingerprint is stable)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    /// Output comparison for this test (overrides the job-level `comparison`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comparison: Option<Comparison>,
+    /// Compare output case-insensitively (e.g. "YES" matches "Yes");
+    /// default: the language's `evaluation.ignore_case`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ignore_case: Option<bool>,
+    /// `regex` treats the expected outputs as patterns matching the whole output
+    #[serde(default, skip_serializing_if = "ExpectedKind::is_literal")]
+    pub expected_kind: ExpectedKind,
 }
 
 fn default_timeout() -> u64 {
@@ -40,45 +127,315 @@ fn default_weight() -> u32 {
     10
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct SubmitResponse {
     pub job_id: String,
+    /// Queued while no worker for the language was alive; runs once workers return
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub parked: bool,
+    /// Result cache hit (`cache: true`) - the job is already complete
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<optimus_common::types::ExecutionResult>,
+    /// Notes attached by the deployment's validation plugins
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub annotations: BTreeMap<String, String>,
+    /// Held back until this time (RFC 3339) by its batch's `spread_ms` / `jitter_ms`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub release_at: Option<String>,
 }
 
 // Safety limits (per specification)
 const MAX_TEST_CASES: usize = 100;
 const MAX_SOURCE_CODE_SIZE: usize = 256_000; // 256 KB
-const MAX_STDIN_SIZE: usize = 64_000; // 64 KB per test case input
+const MAX_STDIN_SIZE: usize = optimus_common::types::MAX_TEST_INPUT_BYTES; // 10 MB per test case input, as workers accept
 const MAX_EXPECTED_OUTPUT_SIZE: usize = 64_000; // 64 KB per expected output
 const MAX_TIMEOUT_MS: u64 = 60_000; // 60 seconds
 const MIN_TIMEOUT_MS: u64 = 1; // 1 millisecond
+const MAX_EXPECTED_OUTPUTS: usize = 16;
+const MAX_FIXTURES_PER_TEST: usize = 16;
+const MAX_FIXTURE_BYTES_PER_TEST: usize = 1_000_000; // 1 MB decoded, all fixtures of a test
+const MAX_IDEMPOTENCY_KEY_LEN: usize = 255;
+const MAX_ARCHIVE_SIZE: usize = 5_000_000; // 5 MB compressed
+const MAX_ENTRY_COMMAND_LEN: usize = 4096;
+const MIN_MEMORY_LIMIT_MB: u32 = 16;
+const MIN_CPU_LIMIT: f64 = 0.1;
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ErrorResponse {
     pub error: ErrorDetail,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ErrorDetail {
     pub code: String,
     pub message: String,
+    /// Values interpolated into `message`, in order (localized messages are
+    /// rendered from these)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub params: Vec<String>,
+}
+
+/// SHA-256 of the canonical request body, used to detect Idempotency-Key reuse
+fn payload_fingerprint(payload: &SubmitRequest) -> String {
+    use sha2::{Digest, Sha256};
+
+    let json = serde_json::to_vec(payload).unwrap_or_default();
+    format!("{:x}", Sha256::digest(&json))
+}
+
+/// SHA-256 of everything that decides a job's outcome, keying the result cache
+/// Identity, scheduling and bookkeeping fields are blanked out first
+fn result_cache_fingerprint(job: &JobRequest, language_version: &str) -> String {
+    use sha2::{Digest, Sha256};
+
+    let mut canonical = job.clone();
+    canonical.id = Uuid::nil();
+    canonical.metadata = Default::default();
+    canonical.priority = Default::default();
+    canonical.batch_id = None;
+    canonical.problem_id = None;
+    canonical.cache_fingerprint = None;
+    canonical.max_parallel_tests = None;
+
+    let mut hasher = Sha256::new();
+    hasher.update(language_version.as_bytes());
+    hasher.update([0]);
+    hasher.update(serde_json::to_vec(&canonical).unwrap_or_default());
+    format!("{:x}", hasher.finalize())
+}
+
+/// A test case that breaks the submission limits
+struct TestCaseRejection {
+    status: StatusCode,
+    code: &'static str,
+    /// Reason label for `optimus_jobs_rejected_total`
+    reason: &'static str,
+    message: String,
+    params: Vec<String>,
+}
+
+impl TestCaseRejection {
+    fn into_response(self) -> axum::response::Response {
+        (
+            self.status,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: self.code.to_string(),
+                    message: self.message,
+                    params: self.params,
+                },
+            }),
+        ).into_response()
+    }
+}
+
+/// Per-test-case checks shared by submissions and test suite uploads: input
+/// and expected output sizes, fixtures, comparison settings, regex patterns
+/// and args/env
+fn validate_test_cases(test_cases: &[TestCaseInput], comparison: Option<Comparison>) -> Result<(), TestCaseRejection> {
+    let reject = |status, code, reason, message, params| Err(TestCaseRejection { status, code, reason, message, params });
+    for (idx, tc) in test_cases.iter().enumerate() {
+        if tc.input.len() > MAX_STDIN_SIZE {
+            return reject(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                "TEST_CASE_INPUT_TOO_LARGE",
+                "test_case_input_too_large",
+                format!("Test case {} input exceeds {} bytes", idx + 1, MAX_STDIN_SIZE),
+                vec![(idx + 1).to_string(), MAX_STDIN_SIZE.to_string()],
+            );
+        }
+        
+        if tc.expected_output.is_none() && tc.expected_outputs.is_empty() {
+            return reject(
+                StatusCode::BAD_REQUEST,
+                "MISSING_EXPECTED_OUTPUT",
+                "missing_expected_output",
+                format!("Test case {} must set expected_output or expected_outputs", idx + 1),
+                vec![(idx + 1).to_string()],
+            );
+        }
+        
+        if tc.expected_outputs.len() > MAX_EXPECTED_OUTPUTS {
+            return reject(
+                StatusCode::BAD_REQUEST,
+                "TOO_MANY_EXPECTED_OUTPUTS",
+                "too_many_expected_outputs",
+                format!("Test case {} has more than {} expected_outputs", idx + 1, MAX_EXPECTED_OUTPUTS),
+                vec![(idx + 1).to_string(), MAX_EXPECTED_OUTPUTS.to_string()],
+            );
+        }
+        
+        let largest_expected = tc.expected_output.iter().chain(&tc.expected_outputs).map(String::len).max().unwrap_or(0);
+        if largest_expected > MAX_EXPECTED_OUTPUT_SIZE {
+            return reject(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                "TEST_CASE_OUTPUT_TOO_LARGE",
+                "test_case_output_too_large",
+                format!("Test case {} expected output exceeds {} bytes", idx + 1, MAX_EXPECTED_OUTPUT_SIZE),
+                vec![(idx + 1).to_string(), MAX_EXPECTED_OUTPUT_SIZE.to_string()],
+            );
+        }
+        
+        // Fixture files: count, decoded size, and path safety
+        if tc.fixtures.len() > MAX_FIXTURES_PER_TEST {
+            return reject(
+                StatusCode::BAD_REQUEST,
+                "TOO_MANY_FIXTURES",
+                "too_many_fixtures",
+                format!(
+                    "Test case {} has {} fixtures, maximum is {}",
+                    idx + 1,
+                    tc.fixtures.len(),
+                    MAX_FIXTURES_PER_TEST
+                ),
+                vec![(idx + 1).to_string(), tc.fixtures.len().to_string(), MAX_FIXTURES_PER_TEST.to_string()],
+            );
+        }
+        
+        let mut fixture_bytes = 0usize;
+        for fixture in &tc.fixtures {
+            match fixture.validate() {
+                Ok(bytes) => fixture_bytes += bytes.len(),
+                Err(reason) => {
+                    return reject(
+                        StatusCode::BAD_REQUEST,
+                        "INVALID_FIXTURE",
+                        "invalid_fixture",
+                        format!("Test case {}: {}", idx + 1, reason),
+                        vec![(idx + 1).to_string()],
+                    );
+                }
+            }
+        }
+        
+        if fixture_bytes > MAX_FIXTURE_BYTES_PER_TEST {
+            return reject(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                "FIXTURES_TOO_LARGE",
+                "fixtures_too_large",
+                format!("Test case {} fixtures exceed {} bytes", idx + 1, MAX_FIXTURE_BYTES_PER_TEST),
+                vec![(idx + 1).to_string(), MAX_FIXTURE_BYTES_PER_TEST.to_string()],
+            );
+        }
+        
+        // Comparison tolerances must be usable numbers
+        if let Some(Err(reason)) = tc.comparison.or(comparison).map(|c| c.validate()) {
+            return reject(
+                StatusCode::BAD_REQUEST,
+                "INVALID_COMPARISON",
+                "invalid_comparison",
+                format!("Test case {}: {}", idx + 1, reason),
+                vec![(idx + 1).to_string()],
+            );
+        }
+        
+        // Regex expected outputs must compile (the worker would otherwise fail them silently)
+        if tc.expected_kind == ExpectedKind::Regex {
+            let patterns = tc.expected_output.iter().chain(tc.expected_outputs.iter());
+            for pattern in patterns {
+                if let Err(e) = optimus_common::types::compile_expected_regex(pattern, tc.ignore_case.unwrap_or_default()) {
+                    return reject(
+                        StatusCode::BAD_REQUEST,
+                        "INVALID_EXPECTED_REGEX",
+                        "invalid_expected_regex",
+                        format!("Test case {}: {}", idx + 1, e),
+                        vec![(idx + 1).to_string()],
+                    );
+                }
+            }
+        }
+        
+        // Program args and env: counts, sizes, and no shadowing the runner's variables
+        let env: std::collections::HashMap<String, String> = tc.env.clone().into_iter().collect();
+        if let Err(reason) = optimus_common::types::validate_args_env(&tc.args, &env) {
+            return reject(
+                StatusCode::BAD_REQUEST,
+                "INVALID_ARGS_ENV",
+                "invalid_args_env",
+                format!("Test case {}: {}", idx + 1, reason),
+                vec![(idx + 1).to_string()],
+            );
+        }
+    }
+    Ok(())
 }
 
 /// POST /execute - Submit a job for execution
 /// 
 /// Supports idempotency via Idempotency-Key header
-/// - Same key + same payload → returns same job_id
+/// - Same key + same payload → returns same job_id (nothing re-queued)
 /// - Same key + different payload → returns 409 Conflict
+/// - Keys are scoped per authenticated caller and expire after 24 hours
+#[utoipa::path(
+    post,
+    path = "/execute",
+    tag = "jobs",
+    request_body = SubmitRequest,
+    params(("Idempotency-Key" = Option<String>, Header, description = "Deduplicates retried submissions (max 255 chars)")),
+    responses(
+        (status = 200, description = "Answered from the result cache", body = SubmitResponse),
+        (status = 202, description = "Job queued", body = SubmitResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 403, description = "Batch owned by another caller", body = ErrorResponse),
+        (status = 404, description = "Batch or problem not found", body = ErrorResponse),
+        (status = 409, description = "Idempotency-Key reused with a different payload, or batch full", body = ErrorResponse),
+        (status = 413, description = "Payload too large", body = ErrorResponse),
+        (status = 422, description = "Unsupported language, no matching placement pool or GPUs, or rejected by a validation plugin", body = ErrorResponse),
+        (status = 429, description = "Rate limit exceeded", body = ErrorResponse),
+        (status = 503, description = "No live workers for the language (NO_WORKERS_POLICY=reject)", body = ErrorResponse),
+    )
+)]
 pub async fn submit_job(
     State(state): State<Arc<AppState>>,
+    auth: Option<Extension<AuthContext>>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
     headers: HeaderMap,
-    Json(payload): Json<SubmitRequest>,
+    Json(mut payload): Json<SubmitRequest>,
 ) -> impl IntoResponse {
+    // Per-caller rate limit (authenticated callers keyed by token subject)
+    if let (Some(config), Some(Extension(ctx))) = (&state.rate_limit, &auth) {
+        let mut conn = state.redis.clone();
+        if let Some(rejection) = rate_limit::check(&mut conn, config, "key", &ctx.subject).await {
+            return rejection;
+        }
+    }
+
+    // Anonymous callers are limited per client IP instead (if configured)
+    if let (Some(config), None) = (&state.ip_rate_limit, &auth) {
+        let peer = connect_info.map(|ConnectInfo(addr)| addr);
+        if let Some(ip) = rate_limit::client_ip(&headers, peer, config.trusted_proxy_hops) {
+            let mut conn = state.redis.clone();
+            if let Some(rejection) = rate_limit::check(&mut conn, &config.bucket, "ip", &ip.to_string()).await {
+                return rejection;
+            }
+        }
+    }
+    
     // Extract idempotency key if provided
     let idempotency_key = headers
         .get("idempotency-key")
         .and_then(|v| v.to_str().ok())
-        .map(|s| s.to_string());
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty());
+    
+    if let Some(ref key) = idempotency_key {
+        if key.len() > MAX_IDEMPOTENCY_KEY_LEN {
+            metrics::record_job_rejected("invalid_idempotency_key");
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "INVALID_IDEMPOTENCY_KEY".to_string(),
+                        message: format!(
+                            "Idempotency-Key must be at most {} characters",
+                            MAX_IDEMPOTENCY_KEY_LEN
+                        ),
+                        params: vec![],
+                    },
+                }),
+            ).into_response();
+        }
+    }
     
     // 0. Validate language is enabled
     if !state.language_registry.is_enabled(payload.language) {
@@ -96,78 +453,190 @@ pub async fn submit_job(
                         "Language '{}' is not enabled or supported",
                         payload.language
                     ),
+                    params: vec![payload.language.to_string()],
                 },
             }),
         ).into_response();
     }
     
-    // Handle idempotency if key is provided
-    if let Some(ref key) = idempotency_key {
-        let mut conn = state.redis.clone();
-        let idempotency_redis_key = format!("optimus:idempotency:{}", key);
-        
-        // Check if this key was used before using redis commands
-        match ::redis::cmd("GET")
-            .arg(&idempotency_redis_key)
-            .query_async::<_, Option<String>>(&mut conn)
-            .await
-        {
-            Ok(Some(stored_data)) => {
-                // Key exists - check if payload matches
-                let payload_json = serde_json::to_string(&payload).unwrap_or_default();
-                
-                if let Ok(stored) = serde_json::from_str::<serde_json::Value>(&stored_data) {
-                    if let Some(stored_payload) = stored.get("payload").and_then(|p| p.as_str()) {
-                        if stored_payload == payload_json {
-                            // Same payload - return existing job_id
-                            if let Some(job_id) = stored.get("job_id").and_then(|j| j.as_str()) {
-                                info!(
-                                    idempotency_key = %key,
-                                    job_id = %job_id,
-                                    "Idempotent request - returning existing job_id"
-                                );
-                                return (
-                                    StatusCode::ACCEPTED,
-                                    Json(SubmitResponse {
-                                        job_id: job_id.to_string(),
-                                    }),
-                                ).into_response();
-                            }
-                        } else {
-                            // Different payload with same key - conflict
-                            warn!(
-                                idempotency_key = %key,
-                                "Rejected: Same idempotency key with different payload"
-                            );
-                            metrics::record_job_rejected("idempotency_conflict");
-                            return (
-                                StatusCode::CONFLICT,
-                                Json(ErrorResponse {
-                                    error: ErrorDetail {
-                                        code: "IDEMPOTENCY_CONFLICT".to_string(),
-                                        message: "Same idempotency key used with different payload".to_string(),
-                                    },
-                                }),
-                            ).into_response();
-                        }
-                    }
-                }
-            }
+    // 0a. Placement must name a pool that workers serve
+    if let Err(message) = payload.placement.validate() {
+        metrics::record_job_rejected("invalid_placement");
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "INVALID_PLACEMENT".to_string(),
+                    message: format!("Invalid placement: {}", message),
+                    params: vec![],
+                },
+            }),
+        ).into_response();
+    }
+    if let Some(pool) = payload.placement.pool() {
+        if !state.language_registry.has_pool(payload.language, &pool) {
+            metrics::record_job_rejected("placement_unavailable");
+            return (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "PLACEMENT_UNAVAILABLE".to_string(),
+                        message: format!(
+                            "No '{}' worker pool matches the requested placement ({})",
+                            payload.language, pool
+                        ),
+                        params: vec![],
+                    },
+                }),
+            ).into_response();
+        }
+    }
+    
+    // Generate job ID
+    let job_id = Uuid::new_v4();
+    
+    // Fingerprint payload early for idempotency check (before moving fields)
+    let payload_hash = idempotency_key.as_ref().map(|_| payload_fingerprint(&payload));
+    
+    // 0b. Per-job resources, clamped to what the language allows
+    let memory_ok = payload.memory_limit_mb.is_none_or(|mb| mb >= MIN_MEMORY_LIMIT_MB);
+    let cpu_ok = payload.cpu_limit.is_none_or(|cpu| cpu.is_finite() && cpu >= MIN_CPU_LIMIT);
+    let parallel_ok = payload.max_parallel_tests.is_none_or(|n| n >= 1);
+    if !memory_ok || !cpu_ok || !parallel_ok {
+        metrics::record_job_rejected("invalid_resources");
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "INVALID_RESOURCES".to_string(),
+                    message: format!(
+                        "memory_limit_mb must be at least {}, cpu_limit at least {} and max_parallel_tests at least 1",
+                        MIN_MEMORY_LIMIT_MB, MIN_CPU_LIMIT
+                    ),
+                    params: vec![MIN_MEMORY_LIMIT_MB.to_string(), MIN_CPU_LIMIT.to_string(), "1".to_string()],
+                },
+            }),
+        ).into_response();
+    }
+    let ceiling = state.language_registry.resource_ceiling(payload.language);
+    let mut memory_limit_mb = payload
+        .memory_limit_mb
+        .map(|mb| ceiling.map_or(mb, |c| mb.min(c.memory_limit_mb)));
+    let mut cpu_limit = payload
+        .cpu_limit
+        .map(|cpu| ceiling.map_or(cpu, |c| cpu.min(c.cpu_limit)));
+    let mut max_parallel_tests = payload
+        .max_parallel_tests
+        .map(|n| ceiling.and_then(|c| c.max_parallel_tests).map_or(n, |max| n.min(max)));
+    
+    // 0c. Server-side problem limits override whatever the request asked for
+    if let Some(problem_id) = &payload.problem_id {
+        let problem = match redis::get_problem(&mut state.redis.clone(), problem_id).await {
+            Ok(Some(problem)) => problem,
             Ok(None) => {
-                // Key doesn't exist - will store after creating job
+                metrics::record_job_rejected("problem_not_found");
+                return (
+                    StatusCode::NOT_FOUND,
+                    Json(ErrorResponse {
+                        error: ErrorDetail {
+                            code: "PROBLEM_NOT_FOUND".to_string(),
+                            message: format!("Problem '{}' does not exist", problem_id),
+                            params: vec![problem_id.to_string()],
+                        },
+                    }),
+                ).into_response();
             }
             Err(e) => {
-                error!(error = %e, "Failed to check idempotency key");
-                // Continue without idempotency on Redis errors
+                error!(job_id = %job_id, problem_id = %problem_id, error = %e, "Failed to load problem");
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ErrorResponse {
+                        error: ErrorDetail {
+                            code: "INTERNAL_ERROR".to_string(),
+                            message: format!("Failed to load problem: {}", e),
+                            params: vec![],
+                        },
+                    }),
+                ).into_response();
+            }
+        };
+        
+        if let Some(suite) = &problem.test_suite {
+            // The suite is the problem's test set; client tests would replace it
+            if !payload.test_cases.is_empty() {
+                metrics::record_job_rejected("test_cases_with_test_suite");
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(ErrorResponse {
+                        error: ErrorDetail {
+                            code: "TEST_CASES_NOT_ALLOWED".to_string(),
+                            message: format!("Problem '{}' has a test suite; omit test_cases", problem_id),
+                            params: vec![],
+                        },
+                    }),
+                ).into_response();
+            }
+            match load_test_suite(&state, suite).await {
+                Ok(test_cases) => payload.test_cases = test_cases,
+                Err(response) => {
+                    metrics::record_job_rejected("test_suite_unavailable");
+                    return response;
+                }
+            }
+        }
+        
+        let limits = problem.limits;
+        payload.timeout_ms = limits.timeout_ms;
+        // Problems are checked against the global maximum only; the language still caps them
+        memory_limit_mb = limits
+            .memory_limit_mb
+            .map(|mb| ceiling.map_or(mb, |c| mb.min(c.memory_limit_mb)));
+        cpu_limit = limits
+            .cpu_limit
+            .map(|cpu| ceiling.map_or(cpu, |c| cpu.min(c.cpu_limit)));
+        payload.accept_presentation_errors = limits.accept_presentation_errors;
+        payload.time_limit_mode = limits.time_limit_mode;
+        payload.gpu |= limits.gpu;
+        if limits.max_parallel_tests.is_some() {
+            max_parallel_tests = limits.max_parallel_tests;
+        }
+        if let Some(comparison) = limits.comparison {
+            payload.comparison = Some(comparison);
+            for tc in &mut payload.test_cases {
+                tc.comparison = Some(comparison);
             }
         }
     }
     
-    // Generate job ID
-    let job_id = Uuid::new_v4();
+    // 0d. A batch's test suite fills in for missing tests (membership is checked in step 8)
+    if let (true, Some(batch_id)) = (payload.test_cases.is_empty(), payload.batch_id) {
+        if let Ok(Some(optimus_common::types::BatchRecord { test_suite: Some(suite), .. })) =
+            redis::get_batch(&mut state.redis.clone(), &batch_id).await
+        {
+            match load_test_suite(&state, &suite).await {
+                Ok(test_cases) => payload.test_cases = test_cases,
+                Err(response) => {
+                    metrics::record_job_rejected("test_suite_unavailable");
+                    return response;
+                }
+            }
+        }
+    }
     
-    // Serialize payload early for idempotency check (before moving fields)
-    let payload_json_for_idempotency = serde_json::to_string(&payload).unwrap_or_default();
+    // 0e. GPU jobs need a language whose workers have GPUs
+    if payload.gpu && !state.language_registry.has_gpu(payload.language) {
+        metrics::record_job_rejected("gpu_unavailable");
+        return (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "GPU_UNAVAILABLE".to_string(),
+                    message: format!("No GPU workers are configured for '{}'", payload.language),
+                    params: vec![],
+                },
+            }),
+        ).into_response();
+    }
     
     // Safety checks - validate request before queueing
     
@@ -181,6 +650,7 @@ pub async fn submit_job(
                 error: ErrorDetail {
                     code: "NO_TEST_CASES".to_string(),
                     message: "At least one test case is required".to_string(),
+                    params: vec![],
                 },
             }),
         ).into_response();
@@ -204,6 +674,7 @@ pub async fn submit_job(
                         MAX_TEST_CASES,
                         payload.test_cases.len()
                     ),
+                    params: vec![MAX_TEST_CASES.to_string(), payload.test_cases.len().to_string()],
                 },
             }),
         ).into_response();
@@ -228,77 +699,87 @@ pub async fn submit_job(
                         MAX_SOURCE_CODE_SIZE,
                         payload.source_code.len()
                     ),
+                    params: vec![MAX_SOURCE_CODE_SIZE.to_string(), payload.source_code.len().to_string()],
                 },
             }),
         ).into_response();
     }
     
-    // 3. Validate source code is not empty
-    if payload.source_code.trim().is_empty() {
-        metrics::record_job_rejected("empty_source_code");
-        error!(job_id = %job_id, "Rejected: Empty source code");
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: ErrorDetail {
-                    code: "EMPTY_SOURCE_CODE".to_string(),
-                    message: "Source code cannot be empty".to_string(),
-                },
-            }),
-        ).into_response();
-    }
-    
-    // 4. Check test case input/output sizes
-    for (idx, tc) in payload.test_cases.iter().enumerate() {
-        if tc.input.len() > MAX_STDIN_SIZE {
-            metrics::record_job_rejected("test_case_input_too_large");
-            error!(
-                job_id = %job_id,
-                test_case = idx + 1,
-                size = tc.input.len(),
-                limit = MAX_STDIN_SIZE,
-                "Rejected: Test case input too large"
-            );
+    // 2b. Check project archive (contents are unpacked and vetted by the worker)
+    if let Some(ref archive) = payload.archive {
+        use base64::Engine as _;
+        let size = match base64::engine::general_purpose::STANDARD.decode(&archive.content_base64) {
+            Ok(bytes) => bytes.len(),
+            Err(e) => {
+                metrics::record_job_rejected("invalid_archive");
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(ErrorResponse {
+                        error: ErrorDetail {
+                            code: "INVALID_ARCHIVE".to_string(),
+                            message: format!("Archive is not valid base64: {}", e),
+                            params: vec![],
+                        },
+                    }),
+                ).into_response();
+            }
+        };
+        if size > MAX_ARCHIVE_SIZE {
+            metrics::record_job_rejected("archive_too_large");
+            error!(job_id = %job_id, size = size, limit = MAX_ARCHIVE_SIZE, "Rejected: Archive too large");
             return (
                 StatusCode::PAYLOAD_TOO_LARGE,
                 Json(ErrorResponse {
                     error: ErrorDetail {
-                        code: "TEST_CASE_INPUT_TOO_LARGE".to_string(),
+                        code: "ARCHIVE_TOO_LARGE".to_string(),
                         message: format!(
-                            "Test case {} input exceeds {} bytes",
-                            idx + 1,
-                            MAX_STDIN_SIZE
+                            "Maximum {} bytes allowed, got {} bytes",
+                            MAX_ARCHIVE_SIZE,
+                            size
                         ),
+                        params: vec![MAX_ARCHIVE_SIZE.to_string(), size.to_string()],
                     },
                 }),
             ).into_response();
         }
-        
-        if tc.expected_output.len() > MAX_EXPECTED_OUTPUT_SIZE {
-            metrics::record_job_rejected("test_case_output_too_large");
-            error!(
-                job_id = %job_id,
-                test_case = idx + 1,
-                size = tc.expected_output.len(),
-                limit = MAX_EXPECTED_OUTPUT_SIZE,
-                "Rejected: Test case expected output too large"
-            );
+        if archive.entry_command.as_ref().is_some_and(|c| c.len() > MAX_ENTRY_COMMAND_LEN) {
+            metrics::record_job_rejected("invalid_archive");
             return (
-                StatusCode::PAYLOAD_TOO_LARGE,
+                StatusCode::BAD_REQUEST,
                 Json(ErrorResponse {
                     error: ErrorDetail {
-                        code: "TEST_CASE_OUTPUT_TOO_LARGE".to_string(),
-                        message: format!(
-                            "Test case {} expected output exceeds {} bytes",
-                            idx + 1,
-                            MAX_EXPECTED_OUTPUT_SIZE
-                        ),
+                        code: "INVALID_ARCHIVE".to_string(),
+                        message: format!("entry_command must be at most {} characters", MAX_ENTRY_COMMAND_LEN),
+                        params: vec![],
                     },
                 }),
             ).into_response();
         }
     }
     
+    // 3. Validate source code is not empty (archive submissions carry no single source file)
+    if payload.archive.is_none() && payload.source_code.trim().is_empty() {
+        metrics::record_job_rejected("empty_source_code");
+        error!(job_id = %job_id, "Rejected: Empty source code");
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "EMPTY_SOURCE_CODE".to_string(),
+                    message: "Source code cannot be empty".to_string(),
+                    params: vec![],
+                },
+            }),
+        ).into_response();
+    }
+    
+    // 4. Check test case input/output sizes, fixtures, comparison and args/env
+    if let Err(rejection) = validate_test_cases(&payload.test_cases, payload.comparison) {
+        metrics::record_job_rejected(rejection.reason);
+        error!(job_id = %job_id, code = rejection.code, reason = %rejection.message, "Rejected: Invalid test case");
+        return rejection.into_response();
+    }
+    
     // 5. Validate timeout
     if payload.timeout_ms < MIN_TIMEOUT_MS || payload.timeout_ms > MAX_TIMEOUT_MS {
         metrics::record_job_rejected("invalid_timeout");
@@ -317,92 +798,414 @@ pub async fn submit_job(
                         MIN_TIMEOUT_MS,
                         MAX_TIMEOUT_MS
                     ),
+                    params: vec![MIN_TIMEOUT_MS.to_string(), MAX_TIMEOUT_MS.to_string()],
                 },
             }),
         ).into_response();
     }
 
-    // Convert test case inputs to internal format
+    // 6. Validate optional time-based scoring
+    if let Some(scoring) = &payload.time_scoring {
+        if let Err(reason) = scoring.validate() {
+            metrics::record_job_rejected("invalid_time_scoring");
+            error!(job_id = %job_id, reason = %reason, "Rejected: Invalid time scoring");
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "INVALID_TIME_SCORING".to_string(),
+                        message: reason,
+                        params: vec![],
+                    },
+                }),
+            ).into_response();
+        }
+        // A speed bonus has nothing to grade against when partial credit is all-or-nothing
+        if payload.scoring == ScoringPolicy::AllOrNothing {
+            metrics::record_job_rejected("invalid_time_scoring");
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "INVALID_TIME_SCORING".to_string(),
+                        message: "time_scoring cannot be combined with scoring: all_or_nothing".to_string(),
+                        params: vec![],
+                    },
+                }),
+            ).into_response();
+        }
+    }
+
+    // 7. Deployment validation plugins (see `validation`)
+    let submission = validation::Submission {
+        request: &payload,
+        caller: auth.as_ref().map(|Extension(ctx)| ctx),
+    };
+    let annotations = match state.validators.run(&submission) {
+        Ok(annotations) => annotations,
+        Err(rejection) => {
+            metrics::record_job_rejected("validation_plugin");
+            metrics::record_validation_rejection(&rejection.validator);
+            warn!(job_id = %job_id, validator = %rejection.validator, code = %rejection.code, "Rejected: Validation plugin");
+            return (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: rejection.code,
+                        message: rejection.message,
+                        params: vec![],
+                    },
+                }),
+            ).into_response();
+        }
+    };
+
+    // 8. Validate batch membership
+    let batch = match payload.batch_id {
+        Some(batch_id) => match redis::get_batch(&mut state.redis.clone(), &batch_id).await {
+            Ok(Some(batch)) => {
+                let caller = auth.as_ref().map(|Extension(ctx)| ctx.subject.as_str());
+                if batch.owner.is_some() && batch.owner.as_deref() != caller {
+                    metrics::record_job_rejected("batch_forbidden");
+                    warn!(job_id = %job_id, batch_id = %batch_id, "Rejected: Caller does not own batch");
+                    return (
+                        StatusCode::FORBIDDEN,
+                        Json(ErrorResponse {
+                            error: ErrorDetail {
+                                code: "BATCH_FORBIDDEN".to_string(),
+                                message: "Batch belongs to a different caller".to_string(),
+                                params: vec![],
+                            },
+                        }),
+                    ).into_response();
+                }
+                Some(batch)
+            }
+            Ok(None) => {
+                metrics::record_job_rejected("batch_not_found");
+                return (
+                    StatusCode::NOT_FOUND,
+                    Json(ErrorResponse {
+                        error: ErrorDetail {
+                            code: "BATCH_NOT_FOUND".to_string(),
+                            message: format!("Batch {} does not exist or has expired", batch_id),
+                            params: vec![],
+                        },
+                    }),
+                ).into_response();
+            }
+            Err(e) => {
+                error!(job_id = %job_id, batch_id = %batch_id, error = %e, "Failed to load batch");
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ErrorResponse {
+                        error: ErrorDetail {
+                            code: "INTERNAL_ERROR".to_string(),
+                            message: format!("Failed to load batch: {}", e),
+                            params: vec![],
+                        },
+                    }),
+                ).into_response();
+            }
+        },
+        None => None,
+    };
+
+    // Convert test case inputs to internal format (job-level comparison fills
+    // in per test, then the language's grading defaults)
+    let job_comparison = payload.comparison;
+    let defaults = state.language_registry.evaluation_defaults(payload.language);
     let test_cases: Vec<optimus_common::types::TestCase> = payload
         .test_cases
         .into_iter()
         .enumerate()
-        .map(|(idx, tc)| optimus_common::types::TestCase {
-            id: (idx + 1) as u32,
-            input: tc.input,
-            expected_output: tc.expected_output,
-            weight: tc.weight,
+        .map(|(idx, tc)| {
+            // Without a primary expected output, the first alternative takes its place
+            let mut alternatives = tc.expected_outputs.into_iter();
+            let expected_output = tc.expected_output.or_else(|| alternatives.next()).unwrap_or_default();
+            optimus_common::types::TestCase {
+                id: (idx + 1) as u32,
+                input: tc.input,
+                expected_output,
+                expected_outputs: alternatives.collect(),
+                weight: tc.weight,
+                fixtures: tc.fixtures,
+                args: tc.args,
+                env: tc.env.into_iter().collect(),
+                comparison: tc.comparison.or(job_comparison).or(defaults.comparison),
+                ignore_case: tc.ignore_case.unwrap_or(defaults.ignore_case),
+                expected_kind: tc.expected_kind,
+            }
         })
         .collect();
 
     // Create job request
-    let job = JobRequest {
+    let use_cache = payload.cache;
+    let mut job = JobRequest {
         id: job_id,
         language: payload.language,
         source_code: payload.source_code,
         test_cases,
         timeout_ms: payload.timeout_ms,
-        metadata: optimus_common::types::JobMetadata::default(),
+        metadata: optimus_common::types::JobMetadata {
+            submitted_at: Some(chrono::Utc::now().to_rfc3339()),
+            annotations: annotations.clone(),
+            ..Default::default()
+        },
+        priority: payload.priority,
+        batch_id: payload.batch_id,
+        time_scoring: payload.time_scoring,
+        time_limit_mode: payload.time_limit_mode,
+        archive: payload.archive,
+        accept_presentation_errors: payload.accept_presentation_errors.unwrap_or(defaults.accept_presentation_errors),
+        scoring: payload.scoring,
+        problem_id: payload.problem_id,
+        memory_limit_mb,
+        cpu_limit,
+        cache_fingerprint: None,
+        placement: payload.placement,
+        gpu: payload.gpu,
+        max_parallel_tests,
     };
 
-    // Push to Redis queue
     let mut conn = state.redis.clone();
-    match redis::push_job(&mut conn, &job).await {
-        Ok(_) => {
-            // Store idempotency key if provided
-            if let Some(ref key) = idempotency_key {
-                let idempotency_redis_key = format!("optimus:idempotency:{}", key);
-                let idempotency_data = serde_json::json!({
-                    "job_id": job_id.to_string(),
-                    "payload": payload_json_for_idempotency,
-                    "created_at": chrono::Utc::now().to_rfc3339(),
-                });
-                
-                // Store with 24 hour TTL using SETEX
-                let mut conn_for_idempotency = state.redis.clone();
-                if let Err(e) = ::redis::cmd("SETEX")
-                    .arg(&idempotency_redis_key)
-                    .arg(86400) // 24 hours
-                    .arg(idempotency_data.to_string())
-                    .query_async::<_, ()>(&mut conn_for_idempotency)
-                    .await
-                {
-                    error!(
-                        error = %e,
-                        idempotency_key = %key,
-                        "Failed to store idempotency key (job already queued)"
-                    );
-                    // Don't fail the request - job is already queued
-                }
-            }
-            
-            // Record metrics
-            metrics::record_job_submitted(&job.language.to_string());
-            
-            info!(
-                job_id = %job_id,
-                language = %job.language,
-                test_cases = job.test_cases.len(),
-                phase = "queued",
-                idempotency_key = ?idempotency_key,
-                "Job queued"
-            );
-            
-            (
-                StatusCode::ACCEPTED,
-                Json(SubmitResponse {
-                    job_id: job_id.to_string(),
+
+    // Opt-in result cache: an identical earlier submission answers right away
+    let mut cached = None;
+    if use_cache {
+        let version = state.language_registry.version(job.language).unwrap_or_default();
+        let fingerprint = result_cache_fingerprint(&job, version);
+        match redis::get_cached_result(&mut conn, &fingerprint).await {
+            Ok(hit) => cached = hit,
+            Err(e) => warn!(job_id = %job_id, error = %e, "Failed to read result cache"),
+        }
+        metrics::record_result_cache(if cached.is_some() { "hit" } else { "miss" });
+        job.cache_fingerprint = Some(fingerprint);
+    }
+
+    // A job for a language nobody consumes would sit in the queue forever
+    // Unknown presence (Redis error) is treated as alive
+    let no_workers = cached.is_none() && match redis::count_live_workers(&mut conn, &job.language).await {
+        Ok(live) => live == 0,
+        Err(e) => {
+            warn!(job_id = %job_id, error = %e, "Failed to check worker presence");
+            false
+        }
+    };
+    if no_workers && state.no_workers_policy == NoWorkersPolicy::Reject {
+        metrics::record_job_rejected("no_workers");
+        warn!(job_id = %job_id, language = %job.language, "Rejected: No live workers");
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "NO_WORKERS".to_string(),
+                    message: format!("No workers for '{}'", job.language),
+                    params: vec![job.language.to_string()],
+                },
+            }),
+        ).into_response();
+    }
+
+    // Claim the idempotency key before queueing so concurrent retries can't double-submit
+    let idempotency_redis_key = idempotency_key.as_ref().map(|key| {
+        let caller = auth.as_ref().map(|Extension(ctx)| ctx.subject.as_str());
+        redis::idempotency_key(caller, key)
+    });
+
+    if let (Some(redis_key), Some(payload_hash)) = (&idempotency_redis_key, payload_hash) {
+        let record = IdempotencyRecord {
+            job_id,
+            payload_hash,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        };
+
+        match redis::claim_idempotency_key(&mut conn, redis_key, &record).await {
+            Ok(None) => {
+                // Claimed - this request owns the key
+            }
+            Ok(Some(existing)) if existing.payload_hash == record.payload_hash => {
+                info!(
+                    idempotency_key = ?idempotency_key,
+                    job_id = %existing.job_id,
+                    "Idempotent request - returning existing job_id"
+                );
+                return (
+                    StatusCode::ACCEPTED,
+                    Json(SubmitResponse {
+                        job_id: existing.job_id.to_string(),
+                        parked: false,
+                        result: None,
+                        annotations: BTreeMap::new(),
+                        release_at: None,
+                    }),
+                ).into_response();
+            }
+            Ok(Some(_)) => {
+                warn!(
+                    idempotency_key = ?idempotency_key,
+                    "Rejected: Same idempotency key with different payload"
+                );
+                metrics::record_job_rejected("idempotency_conflict");
+                return (
+                    StatusCode::CONFLICT,
+                    Json(ErrorResponse {
+                        error: ErrorDetail {
+                            code: "IDEMPOTENCY_CONFLICT".to_string(),
+                            message: "Same idempotency key used with different payload".to_string(),
+                            params: vec![],
+                        },
+                    }),
+                ).into_response();
+            }
+            Err(e) => {
+                error!(error = %e, "Failed to claim idempotency key");
+                // Continue without idempotency on Redis errors
+            }
+        }
+    }
+
+    // Reserve a batch slot so a batch never holds more jobs than it declared
+    let mut batch_slot = None;
+    if let Some(batch) = &batch {
+        batch_slot = match redis::reserve_batch_slot(&mut conn, batch).await {
+            Ok(slot) => slot,
+            Err(e) => {
+                error!(job_id = %job_id, batch_id = %batch.id, error = %e, "Failed to reserve batch slot");
+                None
+            }
+        };
+
+        if batch_slot.is_none() {
+            if let Some(redis_key) = &idempotency_redis_key {
+                if let Err(e) = redis::release_idempotency_key(&mut conn, redis_key).await {
+                    error!(error = %e, "Failed to release idempotency key");
+                }
+            }
+            metrics::record_job_rejected("batch_full");
+            return (
+                StatusCode::CONFLICT,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "BATCH_FULL".to_string(),
+                        message: format!(
+                            "Batch {} already has its {} jobs",
+                            batch.id,
+                            batch.expected_jobs
+                        ),
+                        params: vec![],
+                    },
+                }),
+            ).into_response();
+        }
+    }
+
+    if let Some(mut result) = cached {
+        result.job_id = job_id;
+        result.cached = true;
+        result.feedback = None;
+        match redis::store_result(&mut conn, &result).await {
+            Ok(_) => {
+                if let Some(batch) = &batch {
+                    finish_batch_job(&mut conn, batch, &job_id).await;
+                }
+                info!(
+                    job_id = %job_id,
+                    language = %job.language,
+                    batch_id = ?job.batch_id,
+                    phase = "cached",
+                    "Job answered from result cache"
+                );
+                return (
+                    StatusCode::OK,
+                    Json(SubmitResponse {
+                        job_id: job_id.to_string(),
+                        parked: false,
+                        result: Some(result),
+                        annotations,
+                        release_at: None,
+                    }),
+                ).into_response();
+            }
+            Err(e) => {
+                // Fall back to executing it
+                error!(job_id = %job_id, error = %e, "Failed to store cached result");
+            }
+        }
+    }
+
+    // Spread batches release their jobs over the window instead of all at once
+    let release_at = batch
+        .as_ref()
+        .zip(batch_slot)
+        .and_then(|(batch, slot)| batch_release_at(batch, slot))
+        .filter(|release_at| *release_at > chrono::Utc::now());
+
+    // Push to Redis queue
+    let queued = match release_at {
+        Some(release_at) => redis::push_job_delayed(&mut conn, &mut job, release_at).await,
+        None => redis::push_job(&mut conn, &mut job).await,
+    };
+    match queued {
+        Ok(_) => {
+            // Record metrics
+            metrics::record_job_submitted(&job.language.to_string());
+            // Feeds the autoscaler forecast; losing a count must not fail the submission
+            if let Err(e) = redis::record_submission(&mut conn, &job.language).await {
+                warn!(job_id = %job_id, error = %e, "Failed to record submission for forecast");
+            }
+            if no_workers {
+                metrics::record_job_parked(&job.language.to_string());
+                if let Err(e) = redis::record_parked_job(&mut conn, &job.language).await {
+                    warn!(job_id = %job_id, error = %e, "Failed to record parked job");
+                }
+            }
+            
+            info!(
+                job_id = %job_id,
+                language = %job.language,
+                test_cases = job.test_cases.len(),
+                priority = %job.priority,
+                batch_id = ?job.batch_id,
+                phase = "queued",
+                idempotency_key = ?idempotency_key,
+                parked = no_workers,
+                release_at = ?release_at,
+                "Job queued"
+            );
+            
+            (
+                StatusCode::ACCEPTED,
+                Json(SubmitResponse {
+                    job_id: job_id.to_string(),
+                    parked: no_workers,
+                    result: None,
+                    annotations,
+                    release_at: release_at.map(|release_at| release_at.to_rfc3339()),
                 }),
             ).into_response()
         }
         Err(e) => {
             error!(job_id = %job_id, error = %e, "Failed to queue job");
+
+            // Free the key so the client's retry can actually queue the job
+            if let Some(redis_key) = &idempotency_redis_key {
+                if let Err(e) = redis::release_idempotency_key(&mut conn, redis_key).await {
+                    error!(error = %e, "Failed to release idempotency key");
+                }
+            }
+            if let Some(batch) = &batch {
+                if let Err(e) = redis::release_batch_slot(&mut conn, &batch.id).await {
+                    error!(batch_id = %batch.id, error = %e, "Failed to release batch slot");
+                }
+            }
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(ErrorResponse {
                     error: ErrorDetail {
                         code: "QUEUE_FAILURE".to_string(),
                         message: format!("Failed to queue job: {}", e),
+                        params: vec![],
                     },
                 }),
             ).into_response()
@@ -410,7 +1213,231 @@ pub async fn submit_job(
     }
 }
 
-#[derive(Debug, Serialize)]
+/// When the job in `slot` of a spread batch is due, measured from the batch's creation
+/// None for batches without spread or jitter
+fn batch_release_at(batch: &optimus_common::types::BatchRecord, slot: u32) -> Option<chrono::DateTime<chrono::Utc>> {
+    if batch.spread_ms == 0 && batch.jitter_ms == 0 {
+        return None;
+    }
+    let created_at = chrono::DateTime::parse_from_rfc3339(&batch.created_at).ok()?;
+    let offset_ms = batch.release_offset_ms(slot, Uuid::new_v4().as_u128() as u64);
+    Some(created_at.with_timezone(&chrono::Utc) + chrono::Duration::milliseconds(offset_ms as i64))
+}
+
+/// Count a job the API finished itself (cache hit, reaped orphan) towards its
+/// batch, completing the batch if it was the last
+/// Mirrors the worker's batch tracking; failures are logged since the result is stored
+pub(crate) async fn finish_batch_job(
+    conn: &mut ::redis::aio::ConnectionManager,
+    batch: &optimus_common::types::BatchRecord,
+    job_id: &Uuid,
+) {
+    match redis::mark_batch_job_finished(conn, batch, job_id).await {
+        Ok(true) => match redis::complete_batch(conn, batch).await {
+            Ok(_) => info!(batch_id = %batch.id, phase = "batch_completed", "Batch completed"),
+            Err(e) => error!(batch_id = %batch.id, error = %e, "Failed to publish batch completion"),
+        },
+        Ok(false) => {}
+        Err(e) => error!(job_id = %job_id, batch_id = %batch.id, error = %e, "Failed to record batch progress"),
+    }
+}
+
+/// Body of `POST /execute/archive` (JSON, or the `metadata` part of a multipart upload)
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ArchiveSubmitRequest {
+    pub language: Language,
+    pub test_cases: Vec<TestCaseInput>,
+    #[serde(default = "default_timeout")]
+    pub timeout_ms: u64,
+    #[serde(default)]
+    pub time_scoring: Option<TimeScoring>,
+    #[serde(default)]
+    pub time_limit_mode: TimeLimitMode,
+    #[serde(default)]
+    pub priority: Priority,
+    #[serde(default)]
+    pub batch_id: Option<Uuid>,
+    #[serde(default)]
+    pub comparison: Option<Comparison>,
+    #[serde(default)]
+    pub accept_presentation_errors: Option<bool>,
+    #[serde(default)]
+    pub scoring: ScoringPolicy,
+    #[serde(default)]
+    pub problem_id: Option<String>,
+    #[serde(default)]
+    pub memory_limit_mb: Option<u32>,
+    #[serde(default)]
+    pub cpu_limit: Option<f64>,
+    #[serde(default)]
+    pub cache: bool,
+    #[serde(default)]
+    pub placement: Placement,
+    #[serde(default)]
+    pub gpu: bool,
+    #[serde(default)]
+    pub max_parallel_tests: Option<u32>,
+    /// Required for JSON; inferred from the file name for multipart uploads
+    #[serde(default)]
+    pub format: Option<ArchiveFormat>,
+    /// Required for JSON; multipart uploads send the file as the `archive` part
+    #[serde(default)]
+    pub archive_base64: Option<String>,
+    /// Shell command run from the project root; defaults to the language's `project_command`
+    #[serde(default)]
+    pub entry_command: Option<String>,
+}
+
+impl ArchiveSubmitRequest {
+    fn into_submit_request(self, format: ArchiveFormat, content_base64: String) -> SubmitRequest {
+        SubmitRequest {
+            language: self.language,
+            source_code: String::new(),
+            test_cases: self.test_cases,
+            timeout_ms: self.timeout_ms,
+            time_scoring: self.time_scoring,
+            time_limit_mode: self.time_limit_mode,
+            priority: self.priority,
+            batch_id: self.batch_id,
+            comparison: self.comparison,
+            accept_presentation_errors: self.accept_presentation_errors,
+            scoring: self.scoring,
+            problem_id: self.problem_id,
+            memory_limit_mb: self.memory_limit_mb,
+            cpu_limit: self.cpu_limit,
+            cache: self.cache,
+            placement: self.placement,
+            gpu: self.gpu,
+            max_parallel_tests: self.max_parallel_tests,
+            archive: Some(ProjectArchive {
+                format,
+                content_base64,
+                entry_command: self.entry_command,
+            }),
+        }
+    }
+}
+
+/// Archive format from an uploaded file name
+fn format_from_filename(name: &str) -> Option<ArchiveFormat> {
+    let name = name.to_lowercase();
+    if name.ends_with(".zip") {
+        Some(ArchiveFormat::Zip)
+    } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        Some(ArchiveFormat::TarGz)
+    } else {
+        None
+    }
+}
+
+fn invalid_archive_request(message: String) -> axum::response::Response {
+    metrics::record_job_rejected("invalid_archive");
+    (
+        StatusCode::BAD_REQUEST,
+        Json(ErrorResponse {
+            error: ErrorDetail {
+                code: "INVALID_ARCHIVE".to_string(),
+                message,
+                params: vec![],
+            },
+        }),
+    ).into_response()
+}
+
+/// Split a multipart upload into its `metadata` JSON and `archive` file parts
+async fn read_archive_multipart(
+    mut multipart: axum::extract::Multipart,
+) -> Result<(ArchiveSubmitRequest, ArchiveFormat, String), String> {
+    use base64::Engine as _;
+
+    let mut metadata: Option<ArchiveSubmitRequest> = None;
+    let mut archive: Option<(Option<String>, Vec<u8>)> = None;
+
+    while let Some(field) = multipart.next_field().await.map_err(|e| e.to_string())? {
+        match field.name() {
+            Some("metadata") => {
+                let text = field.text().await.map_err(|e| e.to_string())?;
+                metadata = Some(serde_json::from_str(&text).map_err(|e| format!("Invalid metadata: {}", e))?);
+            }
+            Some("archive") => {
+                let file_name = field.file_name().map(str::to_string);
+                let bytes = field.bytes().await.map_err(|e| e.to_string())?;
+                archive = Some((file_name, bytes.to_vec()));
+            }
+            _ => {}
+        }
+    }
+
+    let metadata = metadata.ok_or("Missing 'metadata' part")?;
+    let (file_name, bytes) = archive.ok_or("Missing 'archive' part")?;
+    let format = metadata
+        .format
+        .or_else(|| file_name.as_deref().and_then(format_from_filename))
+        .ok_or("Archive format unknown - set 'format' or upload a .zip / .tar.gz file")?;
+
+    Ok((metadata, format, base64::engine::general_purpose::STANDARD.encode(bytes)))
+}
+
+/// POST /execute/archive - Submit a multi-file project
+///
+/// Accepts either JSON (`archive_base64` + `format`) or `multipart/form-data`
+/// with a `metadata` JSON part and an `archive` file part. The archive is
+/// unpacked into the container's working directory by the worker and
+/// `entry_command` is run there for each test case. Validation, rate limits
+/// and Idempotency-Key handling are shared with `POST /execute`.
+#[utoipa::path(
+    post,
+    path = "/execute/archive",
+    tag = "jobs",
+    request_body = ArchiveSubmitRequest,
+    params(("Idempotency-Key" = Option<String>, Header, description = "Deduplicates retried submissions (max 255 chars)")),
+    responses(
+        (status = 202, description = "Job queued", body = SubmitResponse),
+        (status = 400, description = "Invalid request or archive", body = ErrorResponse),
+        (status = 413, description = "Archive too large", body = ErrorResponse),
+        (status = 422, description = "Unsupported language, no matching placement pool or GPUs, or rejected by a validation plugin", body = ErrorResponse),
+        (status = 429, description = "Rate limit exceeded", body = ErrorResponse),
+        (status = 503, description = "No live workers for the language (NO_WORKERS_POLICY=reject)", body = ErrorResponse),
+    )
+)]
+pub async fn submit_archive_job(
+    State(state): State<Arc<AppState>>,
+    auth: Option<Extension<AuthContext>>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
+    request: axum::extract::Request,
+) -> axum::response::Response {
+    use axum::extract::FromRequest;
+
+    let is_multipart = headers
+        .get(axum::http::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("multipart/form-data"));
+
+    let payload = if is_multipart {
+        let multipart = match axum::extract::Multipart::from_request(request, &state).await {
+            Ok(multipart) => multipart,
+            Err(rejection) => return rejection.into_response(),
+        };
+        match read_archive_multipart(multipart).await {
+            Ok((metadata, format, content)) => metadata.into_submit_request(format, content),
+            Err(message) => return invalid_archive_request(message),
+        }
+    } else {
+        let Json(body) = match Json::<ArchiveSubmitRequest>::from_request(request, &state).await {
+            Ok(body) => body,
+            Err(rejection) => return rejection.into_response(),
+        };
+        let (Some(format), Some(content)) = (body.format, body.archive_base64.clone()) else {
+            return invalid_archive_request("JSON submissions must set 'format' and 'archive_base64'".to_string());
+        };
+        body.into_submit_request(format, content)
+    };
+
+    submit_job(State(state), auth, connect_info, headers, Json(payload)).await.into_response()
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 pub struct HealthResponse {
     pub status: String,
     pub uptime_seconds: u64,
@@ -434,6 +1461,12 @@ pub async fn metrics_handler(State(state): State<Arc<AppState>>) -> impl IntoRes
 
 /// GET /health - Liveness probe (process alive check)
 /// Returns 200 if the process is running
+#[utoipa::path(
+    get,
+    path = "/health",
+    tag = "health",
+    responses((status = 200, description = "Process is alive", body = HealthResponse))
+)]
 pub async fn health_check(State(state): State<Arc<AppState>>) -> impl IntoResponse {
     let uptime = state.start_time.elapsed().as_secs();
     
@@ -449,6 +1482,15 @@ pub async fn health_check(State(state): State<Arc<AppState>>) -> impl IntoRespon
 
 /// GET /ready - Readiness probe (Redis connectivity check)
 /// Returns 200 only if Redis is reachable
+#[utoipa::path(
+    get,
+    path = "/ready",
+    tag = "health",
+    responses(
+        (status = 200, description = "Redis reachable", body = HealthResponse),
+        (status = 503, description = "Redis unreachable", body = HealthResponse),
+    )
+)]
 pub async fn readiness_check(State(state): State<Arc<AppState>>) -> impl IntoResponse {
     let uptime = state.start_time.elapsed().as_secs();
     
@@ -478,10 +1520,115 @@ pub async fn readiness_check(State(state): State<Arc<AppState>>) -> impl IntoRes
     }
 }
 
+/// Upper bound for `?wait=` on job result long-polling
+const MAX_RESULT_WAIT: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Page size for `results` when `limit` isn't given, and the largest allowed
+const DEFAULT_RESULTS_LIMIT: usize = 100;
+const MAX_RESULTS_LIMIT: usize = 1000;
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ResultQuery {
+    /// Long-poll duration: "30s", "500ms" or plain seconds ("30")
+    pub wait: Option<String>,
+    /// Index of the first test result to return (default 0)
+    pub offset: Option<usize>,
+    /// Number of test results to return (default 100, max 1000)
+    pub limit: Option<usize>,
+}
+
+/// Position of the returned `results` window within the full result list
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ResultsPage {
+    pub offset: usize,
+    pub limit: usize,
+    pub total: usize,
+}
+
+/// GET /job/{job_id} response: the stored result with `results` windowed
+/// by `offset`/`limit`, plus a summary over every test
+#[derive(Debug, Serialize, ToSchema)]
+pub struct JobResultResponse {
+    #[serde(flatten)]
+    pub result: optimus_common::types::ExecutionResult,
+    pub summary: optimus_common::types::ResultSummary,
+    pub page: ResultsPage,
+}
+
+impl JobResultResponse {
+    fn paginate(mut result: optimus_common::types::ExecutionResult, offset: usize, limit: usize) -> Self {
+        let summary = result.summary();
+        let total = result.results.len();
+        result.results = result.results.into_iter().skip(offset).take(limit).collect();
+        Self {
+            result,
+            summary,
+            page: ResultsPage { offset, limit, total },
+        }
+    }
+}
+
+/// Parse a `wait` value, capped at `MAX_RESULT_WAIT`
+fn parse_wait(value: &str) -> Option<std::time::Duration> {
+    let value = value.trim();
+    let duration = if let Some(ms) = value.strip_suffix("ms") {
+        std::time::Duration::from_millis(ms.parse().ok()?)
+    } else {
+        let secs = value.strip_suffix('s').unwrap_or(value);
+        std::time::Duration::from_secs(secs.parse().ok()?)
+    };
+    Some(duration.min(MAX_RESULT_WAIT))
+}
+
+/// Block until the job's result is stored or `wait` elapses
+///
+/// Subscribes before re-checking so a result stored in between isn't missed.
+/// Any pub/sub failure just ends the wait early - the caller re-reads the result.
+async fn wait_for_result(state: &AppState, job_id: &Uuid, wait: std::time::Duration) {
+    use futures_util::StreamExt;
+
+    let mut pubsub = match state.redis_client.get_async_connection().await {
+        Ok(conn) => conn.into_pubsub(),
+        Err(e) => {
+            warn!(job_id = %job_id, error = %e, "Failed to open pubsub connection for long-poll");
+            return;
+        }
+    };
+    if let Err(e) = pubsub.subscribe(redis::job_events_channel(job_id)).await {
+        warn!(job_id = %job_id, error = %e, "Failed to subscribe to job events");
+        return;
+    }
+
+    if let Ok(Some(_)) = redis::get_result(&mut state.redis.clone(), job_id).await {
+        return;
+    }
+
+    let _ = tokio::time::timeout(wait, pubsub.on_message().next()).await;
+}
+
 /// GET /job/{job_id} - Query execution result
+///
+/// With `?wait=30s` the request is held open until the result exists or the
+/// wait elapses (max 60s), then answers exactly like a plain poll.
+/// `results` is paged with `?offset=&limit=` (default first 100); `summary`
+/// always covers every test. Finished results are streamed with chunked
+/// transfer encoding rather than buffered.
+#[utoipa::path(
+    get,
+    path = "/job/{job_id}",
+    tag = "jobs",
+    params(("job_id" = Uuid, Path, description = "Job id returned by /execute"), ResultQuery),
+    responses(
+        (status = 200, description = "Job finished", body = JobResultResponse),
+        (status = 202, description = "Job queued (`pending`) or executing (`running`, with `progress`)"),
+        (status = 400, description = "Invalid job id, wait or pagination", body = ErrorResponse),
+        (status = 500, description = "Redis error", body = ErrorResponse),
+    )
+)]
 pub async fn get_job_result(
     State(state): State<Arc<AppState>>,
     Path(job_id): Path<String>,
+    Query(query): Query<ResultQuery>,
 ) -> impl IntoResponse {
     // Parse job ID
     let job_uuid = match Uuid::parse_str(&job_id) {
@@ -493,21 +1640,99 @@ pub async fn get_job_result(
                     error: ErrorDetail {
                         code: "INVALID_JOB_ID".to_string(),
                         message: "Invalid job ID format".to_string(),
+                        params: vec![],
+                    },
+                }),
+            ).into_response();
+        }
+    };
+
+    let wait = match query.wait.as_deref().map(parse_wait) {
+        None => None,
+        Some(Some(wait)) => Some(wait),
+        Some(None) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "INVALID_WAIT".to_string(),
+                        message: "wait must be a duration like 30s or 500ms".to_string(),
+                        params: vec![],
                     },
                 }),
             ).into_response();
         }
     };
 
+    let offset = query.offset.unwrap_or(0);
+    let limit = query.limit.unwrap_or(DEFAULT_RESULTS_LIMIT);
+    if limit == 0 || limit > MAX_RESULTS_LIMIT {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "INVALID_PAGINATION".to_string(),
+                    message: format!("limit must be between 1 and {}", MAX_RESULTS_LIMIT),
+                    params: vec![],
+                },
+            }),
+        ).into_response();
+    }
+
     // Fetch result from Redis
     let mut conn = state.redis.clone();
-    match redis::get_result(&mut conn, &job_uuid).await {
-        Ok(Some(result)) => {
+    let mut lookup = result_store::get_result(&state, &mut conn, &job_uuid).await;
+
+    // Long-poll: hold the request until the worker stores the result
+    if let (Ok(None), Some(wait)) = (&lookup, wait) {
+        if !wait.is_zero() {
+            wait_for_result(&state, &job_uuid, wait).await;
+            lookup = result_store::get_result(&state, &mut conn, &job_uuid).await;
+        }
+    }
+
+    match lookup {
+        Ok(Some(mut result)) => {
             info!(job_id = %job_id, status = ?result.overall_status, "Job result retrieved");
-            // Result exists - return it
-            (StatusCode::OK, Json(result)).into_response()
+            // Grader feedback lives under its own key so worker writes can't clobber it
+            result.feedback = match redis::get_feedback(&mut conn, &job_uuid).await {
+                Ok(feedback) => feedback,
+                Err(e) => {
+                    warn!(job_id = %job_id, error = %e, "Failed to fetch grader feedback");
+                    None
+                }
+            };
+            // Result exists - return the requested window of it, streamed since
+            // a page of full test outputs can run to megabytes
+            crate::streaming::json(StatusCode::OK, JobResultResponse::paginate(result, offset, limit))
         }
         Ok(None) => {
+            // The worker holding the job publishes progress in its status key;
+            // without a live processing heartbeat that progress is stale
+            let progress = match redis::get_job_progress(&mut conn, &job_uuid).await {
+                Ok(Some(progress)) => match redis::processing_expired(&mut conn, &job_uuid).await {
+                    Ok(false) => Some(progress),
+                    _ => None,
+                },
+                Ok(None) => None,
+                Err(e) => {
+                    warn!(job_id = %job_id, error = %e, "Failed to fetch job progress");
+                    None
+                }
+            };
+            if let Some(progress) = progress {
+                info!(job_id = %job_id, tests_completed = progress.tests_completed, "Job still running");
+                return (
+                    StatusCode::ACCEPTED,
+                    Json(serde_json::json!({
+                        "job_id": job_id,
+                        "status": "running",
+                        "progress": progress,
+                        "message": "Job is executing"
+                    })),
+                ).into_response();
+            }
+
             info!(job_id = %job_id, "Job still pending or not found");
             // Result not found - job may still be queued/running (or doesn't exist)
             // We return 202 optimistically to avoid expensive queue scans
@@ -528,6 +1753,7 @@ pub async fn get_job_result(
                     error: ErrorDetail {
                         code: "INTERNAL_ERROR".to_string(),
                         message: format!("Failed to query job status: {}", e),
+                        params: vec![],
                     },
                 }),
             ).into_response()
@@ -535,26 +1761,38 @@ pub async fn get_job_result(
     }
 }
 
-#[derive(Debug, Serialize)]
-pub struct JobDebugInfo {
-    pub job_id: String,
-    pub status: String,
-    pub attempts: u8,
-    pub max_attempts: u8,
-    pub last_failure_reason: Option<String>,
-    pub in_main_queue: bool,
-    pub in_retry_queue: bool,
-    pub in_dlq: bool,
-    pub result: Option<optimus_common::types::ExecutionResult>,
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PartialResultsResponse {
+    pub job_id: Uuid,
+    /// The final result is stored; `results` are its tests
+    pub complete: bool,
+    /// Present while a worker is executing the job
+    pub progress: Option<optimus_common::types::JobProgress>,
+    /// Finished tests, ordered by test id
+    pub results: Vec<optimus_common::types::TestResult>,
 }
 
-/// GET /job/{job_id}/debug - Detailed debugging information for job
-/// Shows retry attempts, queue status, and failure reasons
-pub async fn get_job_debug(
+/// GET /job/{job_id}/partial - Tests finished so far
+///
+/// Workers publish each test's result as soon as it finishes, so long jobs
+/// can be followed test by test. Once the job completes this returns the
+/// final result's tests with `complete: true`. An unknown or queued job
+/// answers with no results.
+#[utoipa::path(
+    get,
+    path = "/job/{job_id}/partial",
+    tag = "jobs",
+    params(("job_id" = Uuid, Path, description = "Job id returned by /execute")),
+    responses(
+        (status = 200, description = "Tests finished so far", body = PartialResultsResponse),
+        (status = 400, description = "Invalid job id", body = ErrorResponse),
+        (status = 500, description = "Redis error", body = ErrorResponse),
+    )
+)]
+pub async fn get_partial_results(
     State(state): State<Arc<AppState>>,
     Path(job_id): Path<String>,
 ) -> impl IntoResponse {
-    // Parse job ID
     let job_uuid = match Uuid::parse_str(&job_id) {
         Ok(id) => id,
         Err(_) => {
@@ -564,43 +1802,316 @@ pub async fn get_job_debug(
                     error: ErrorDetail {
                         code: "INVALID_JOB_ID".to_string(),
                         message: "Invalid job ID format".to_string(),
+                        params: vec![],
                     },
                 }),
             ).into_response();
         }
     };
 
+    let redis_error = |e: ::redis::RedisError| {
+        error!(job_id = %job_uuid, error = %e, "Failed to fetch partial results");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "INTERNAL_ERROR".to_string(),
+                    message: format!("Failed to query partial results: {}", e),
+                    params: vec![],
+                },
+            }),
+        ).into_response()
+    };
+
     let mut conn = state.redis.clone();
-    
-    // Fetch result from Redis
-    let result = match redis::get_result(&mut conn, &job_uuid).await {
-        Ok(result) => result,
+    match result_store::get_result(&state, &mut conn, &job_uuid).await {
+        Ok(Some(result)) => {
+            return Json(PartialResultsResponse {
+                job_id: job_uuid,
+                complete: true,
+                progress: None,
+                results: result.results,
+            }).into_response();
+        }
+        Ok(None) => {}
+        Err(e) => return redis_error(e),
+    }
+
+    let published = match redis::get_partial_results(&mut conn, &job_uuid).await {
+        Ok(published) => published,
+        Err(e) => return redis_error(e),
+    };
+    // A test re-run after an infrastructure failure is published again; the
+    // latest run is the one the final result will keep
+    let mut latest = std::collections::BTreeMap::new();
+    for result in published {
+        latest.insert(result.test_id, result);
+    }
+
+    // Same staleness rule as GET /job/{job_id}
+    let progress = match redis::get_job_progress(&mut conn, &job_uuid).await {
+        Ok(Some(progress)) => match redis::processing_expired(&mut conn, &job_uuid).await {
+            Ok(false) => Some(progress),
+            _ => None,
+        },
+        Ok(None) => None,
         Err(e) => {
-            error!(job_id = %job_id, error = %e, "Failed to fetch job result");
+            warn!(job_id = %job_uuid, error = %e, "Failed to fetch job progress");
+            None
+        }
+    };
+
+    Json(PartialResultsResponse {
+        job_id: job_uuid,
+        complete: false,
+        progress,
+        results: latest.into_values().collect(),
+    }).into_response()
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct FeedbackRequest {
+    /// Free-form reviewer notes (replaces the previous comment)
+    #[serde(default)]
+    pub comment: Option<String>,
+    /// Points added to the automatic score; negative values deduct
+    #[serde(default)]
+    pub score_adjustment: i64,
+}
+
+const MAX_FEEDBACK_COMMENT_LEN: usize = 10_000;
+
+/// PUT /job/{job_id}/feedback - Attach grader comments and a manual score adjustment
+///
+/// Replaces the current feedback; every change is kept in `history` along
+/// with the grader who made it. The adjusted score must stay within
+/// 0..=max_score. Requires the grader (or admin) role.
+#[utoipa::path(
+    put,
+    path = "/job/{job_id}/feedback",
+    tag = "jobs",
+    request_body = FeedbackRequest,
+    params(("job_id" = Uuid, Path, description = "Job id returned by /execute")),
+    responses(
+        (status = 200, description = "Feedback stored", body = optimus_common::types::JobFeedback),
+        (status = 400, description = "Invalid job id, comment or adjustment", body = ErrorResponse),
+        (status = 403, description = "Grader role required", body = ErrorResponse),
+        (status = 404, description = "No result for this job", body = ErrorResponse),
+        (status = 500, description = "Redis error", body = ErrorResponse),
+    )
+)]
+pub async fn put_job_feedback(
+    State(state): State<Arc<AppState>>,
+    Extension(grader): Extension<AuthContext>,
+    Path(job_id): Path<String>,
+    Json(payload): Json<FeedbackRequest>,
+) -> impl IntoResponse {
+    use optimus_common::types::{FeedbackRevision, JobFeedback};
+
+    let job_uuid = match Uuid::parse_str(&job_id) {
+        Ok(id) => id,
+        Err(_) => {
             return (
-                StatusCode::INTERNAL_SERVER_ERROR,
+                StatusCode::BAD_REQUEST,
                 Json(ErrorResponse {
                     error: ErrorDetail {
-                        code: "INTERNAL_ERROR".to_string(),
-                        message: format!("Failed to query job: {}", e),
+                        code: "INVALID_JOB_ID".to_string(),
+                        message: "Invalid job ID format".to_string(),
+                        params: vec![],
                     },
                 }),
             ).into_response();
         }
     };
-    
-    // Check all queues for this job (search all languages)
-    let mut in_main_queue = false;
-    let mut in_retry_queue = false;
-    let mut in_dlq = false;
-    let mut job_metadata = None;
-    
-    for language in Language::all_variants() {
-        let lang = language.to_string();
-        // Check main queue
-        let main_queue = format!("optimus:queue:{}", lang);
-        if let Ok(items) = ::redis::cmd("LRANGE")
-            .arg(&main_queue)
+
+    let comment = payload.comment.map(|c| c.trim().to_string()).filter(|c| !c.is_empty());
+    if comment.as_ref().is_some_and(|c| c.len() > MAX_FEEDBACK_COMMENT_LEN) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "INVALID_COMMENT".to_string(),
+                    message: format!("comment must be at most {} characters", MAX_FEEDBACK_COMMENT_LEN),
+                    params: vec![],
+                },
+            }),
+        ).into_response();
+    }
+
+    let mut conn = state.redis.clone();
+
+    let result = match result_store::get_result(&state, &mut conn, &job_uuid).await {
+        Ok(Some(result)) => result,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "RESULT_NOT_FOUND".to_string(),
+                        message: "Job has no stored result to review".to_string(),
+                        params: vec![],
+                    },
+                }),
+            ).into_response();
+        }
+        Err(e) => {
+            error!(job_id = %job_id, error = %e, "Failed to fetch job result for feedback");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "INTERNAL_ERROR".to_string(),
+                        message: format!("Failed to query job result: {}", e),
+                        params: vec![],
+                    },
+                }),
+            ).into_response();
+        }
+    };
+
+    let adjusted_score = result.score as i64 + payload.score_adjustment;
+    if adjusted_score < 0 || adjusted_score > result.max_score as i64 {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "INVALID_SCORE_ADJUSTMENT".to_string(),
+                    message: format!(
+                        "Adjusted score must be between 0 and {} (automatic score is {})",
+                        result.max_score, result.score
+                    ),
+                    params: vec![],
+                },
+            }),
+        ).into_response();
+    }
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let revision = FeedbackRevision {
+        grader: grader.subject.clone(),
+        comment: comment.clone(),
+        score_adjustment: payload.score_adjustment,
+        at: now.clone(),
+    };
+    let mut feedback = JobFeedback {
+        comment,
+        score_adjustment: payload.score_adjustment,
+        adjusted_score: adjusted_score as u32,
+        updated_by: grader.subject.clone(),
+        updated_at: now,
+        history: vec![],
+    };
+
+    if let Err(e) = redis::store_feedback(&mut conn, &job_uuid, &feedback, &revision).await {
+        error!(job_id = %job_id, error = %e, "Failed to store feedback");
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "INTERNAL_ERROR".to_string(),
+                    message: format!("Failed to store feedback: {}", e),
+                    params: vec![],
+                },
+            }),
+        ).into_response();
+    }
+
+    info!(
+        job_id = %job_id,
+        grader = %grader.subject,
+        score_adjustment = payload.score_adjustment,
+        adjusted_score = feedback.adjusted_score,
+        "Grader feedback stored"
+    );
+
+    // Answer with the full audit trail, as GET /job/{id} would show it
+    if let Ok(Some(stored)) = redis::get_feedback(&mut conn, &job_uuid).await {
+        feedback = stored;
+    }
+    (StatusCode::OK, Json(feedback)).into_response()
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct JobDebugInfo {
+    pub job_id: String,
+    pub status: String,
+    pub attempts: u8,
+    pub max_attempts: u8,
+    pub last_failure_reason: Option<String>,
+    pub in_main_queue: bool,
+    pub in_retry_queue: bool,
+    pub in_dlq: bool,
+    /// Queue timeline: enqueue, first dequeue per attempt, retries, dead-lettering
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hops: Option<optimus_common::types::HopLog>,
+    pub result: Option<optimus_common::types::ExecutionResult>,
+}
+
+/// GET /job/{job_id}/debug - Detailed debugging information for job
+/// Shows retry attempts, queue status, failure reasons and the queue timeline
+#[utoipa::path(
+    get,
+    path = "/job/{job_id}/debug",
+    tag = "jobs",
+    params(("job_id" = Uuid, Path, description = "Job id")),
+    responses(
+        (status = 200, description = "Retry and queue state", body = JobDebugInfo),
+        (status = 400, description = "Invalid job id", body = ErrorResponse),
+    )
+)]
+pub async fn get_job_debug(
+    State(state): State<Arc<AppState>>,
+    Path(job_id): Path<String>,
+) -> impl IntoResponse {
+    // Parse job ID
+    let job_uuid = match Uuid::parse_str(&job_id) {
+        Ok(id) => id,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "INVALID_JOB_ID".to_string(),
+                        message: "Invalid job ID format".to_string(),
+                        params: vec![],
+                    },
+                }),
+            ).into_response();
+        }
+    };
+
+    let mut conn = state.redis.clone();
+    
+    // Fetch result from Redis
+    let result = match result_store::get_result(&state, &mut conn, &job_uuid).await {
+        Ok(result) => result,
+        Err(e) => {
+            error!(job_id = %job_id, error = %e, "Failed to fetch job result");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "INTERNAL_ERROR".to_string(),
+                        message: format!("Failed to query job: {}", e),
+                        params: vec![],
+                    },
+                }),
+            ).into_response();
+        }
+    };
+    
+    // Check all queues for this job (search all languages)
+    let mut in_main_queue = false;
+    let mut in_retry_queue = false;
+    let mut in_dlq = false;
+    let mut job_metadata = None;
+    
+    for language in Language::all_variants() {
+        let lang = language.to_string();
+        // Check main queue
+        let main_queue = format!("optimus:queue:{}", lang);
+        if let Ok(items) = ::redis::cmd("LRANGE")
+            .arg(&main_queue)
             .arg(0)
             .arg(-1)
             .query_async::<_, Vec<String>>(&mut conn)
@@ -662,6 +2173,19 @@ pub async fn get_job_debug(
         }
     }
     
+    // The stored copy is current even after a worker took the job off the queues
+    let hops = match redis::get_hop_log(&mut conn, &job_uuid).await {
+        Ok(hops) => hops,
+        Err(e) => {
+            warn!(job_id = %job_id, error = %e, "Failed to fetch hop log");
+            None
+        }
+    };
+    let hops = hops.or_else(|| job_metadata.as_ref().map(|m| m.hops.clone()).filter(|h| !h.is_empty()));
+    // A worker holds the job and is still refreshing its processing heartbeat
+    let processing = !in_main_queue && !in_retry_queue && !in_dlq
+        && matches!(redis::processing_expired(&mut conn, &job_uuid).await, Ok(false));
+    
     let debug_info = JobDebugInfo {
         job_id: job_id.clone(),
         status: if result.is_some() {
@@ -672,6 +2196,11 @@ pub async fn get_job_debug(
             "retrying".to_string()
         } else if in_main_queue {
             "queued".to_string()
+        } else if processing {
+            "running".to_string()
+        } else if hops.as_ref().is_some_and(|h| h.release_at.is_some() && h.first_dequeue_at.is_empty()) {
+            // Held back by its batch's spread/jitter
+            "scheduled".to_string()
         } else {
             "unknown".to_string()
         },
@@ -681,6 +2210,7 @@ pub async fn get_job_debug(
         in_main_queue,
         in_retry_queue,
         in_dlq,
+        hops,
         result,
     };
     
@@ -688,7 +2218,7 @@ pub async fn get_job_debug(
     (StatusCode::OK, Json(debug_info)).into_response()
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct CancelResponse {
     pub job_id: String,
     pub status: String,
@@ -703,6 +2233,17 @@ pub struct CancelResponse {
 /// - Returns 200 OK if cancelled
 /// - Returns 409 Conflict if already completed/failed
 /// - Returns 404 Not Found if job doesn't exist
+#[utoipa::path(
+    post,
+    path = "/job/{job_id}/cancel",
+    tag = "jobs",
+    params(("job_id" = Uuid, Path, description = "Job id")),
+    responses(
+        (status = 200, description = "Cancellation requested", body = CancelResponse),
+        (status = 400, description = "Invalid job id", body = ErrorResponse),
+        (status = 409, description = "Job already finished", body = ErrorResponse),
+    )
+)]
 pub async fn cancel_job(
     State(state): State<Arc<AppState>>,
     Path(job_id): Path<String>,
@@ -717,6 +2258,7 @@ pub async fn cancel_job(
                     error: ErrorDetail {
                         code: "INVALID_JOB_ID".to_string(),
                         message: "Invalid job ID format".to_string(),
+                        params: vec![],
                     },
                 }),
             ).into_response();
@@ -726,7 +2268,7 @@ pub async fn cancel_job(
     let mut conn = state.redis.clone();
     
     // Check if job already has a result (completed/failed)
-    match redis::get_result(&mut conn, &job_uuid).await {
+    match result_store::get_result(&state, &mut conn, &job_uuid).await {
         Ok(Some(result)) => {
             // Job already completed - cannot cancel
             let status = match result.overall_status {
@@ -763,6 +2305,7 @@ pub async fn cancel_job(
                     error: ErrorDetail {
                         code: "INTERNAL_ERROR".to_string(),
                         message: format!("Failed to query job: {}", e),
+                        params: vec![],
                     },
                 }),
             ).into_response();
@@ -792,9 +2335,2210 @@ pub async fn cancel_job(
                     error: ErrorDetail {
                         code: "INTERNAL_ERROR".to_string(),
                         message: format!("Failed to cancel job: {}", e),
+                        params: vec![],
                     },
                 }),
             ).into_response()
         }
     }
 }
+
+#[derive(Debug, Deserialize)]
+pub struct DryRunBody {
+    pub source_code: String,
+    #[serde(default)]
+    pub input: String,
+    #[serde(default = "default_timeout")]
+    pub timeout_ms: u64,
+}
+
+/// Extra time allowed on top of the snippet timeout for the worker to pick up
+/// the request, pull the image and create the container
+const DRY_RUN_GRACE_MS: u64 = 30_000;
+const DRY_RUN_POLL_INTERVAL_MS: u64 = 200;
+
+/// POST /admin/languages/{name}/dry-run - Run a snippet without scoring
+///
+/// Pushes the snippet to the language's admin dry-run queue, waits for a
+/// worker to execute it, and returns raw stdout/stderr/exit code.
+/// Returns 504 if no worker picks it up in time.
+pub async fn dry_run_language(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Json(payload): Json<DryRunBody>,
+) -> impl IntoResponse {
+    let language = match Language::from_str(&name) {
+        Some(lang) => lang,
+        None => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "LANGUAGE_NOT_FOUND".to_string(),
+                        message: format!("Unknown language '{}'", name),
+                        params: vec![],
+                    },
+                }),
+            ).into_response();
+        }
+    };
+
+    if !state.language_registry.is_enabled(language) {
+        return (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "LANGUAGE_NOT_SUPPORTED".to_string(),
+                    message: format!("Language '{}' is not enabled or supported", language),
+                    params: vec![language.to_string()],
+                },
+            }),
+        ).into_response();
+    }
+
+    if payload.source_code.trim().is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "EMPTY_SOURCE_CODE".to_string(),
+                    message: "Source code cannot be empty".to_string(),
+                    params: vec![],
+                },
+            }),
+        ).into_response();
+    }
+
+    if payload.source_code.len() > MAX_SOURCE_CODE_SIZE || payload.input.len() > MAX_STDIN_SIZE {
+        return (
+            StatusCode::PAYLOAD_TOO_LARGE,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "PAYLOAD_TOO_LARGE".to_string(),
+                    message: format!(
+                        "Source code is limited to {} bytes and input to {} bytes",
+                        MAX_SOURCE_CODE_SIZE,
+                        MAX_STDIN_SIZE
+                    ),
+                    params: vec![MAX_SOURCE_CODE_SIZE.to_string(), MAX_STDIN_SIZE.to_string()],
+                },
+            }),
+        ).into_response();
+    }
+
+    if payload.timeout_ms < MIN_TIMEOUT_MS || payload.timeout_ms > MAX_TIMEOUT_MS {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "INVALID_TIMEOUT".to_string(),
+                    message: format!(
+                        "Timeout must be between {}ms and {}ms",
+                        MIN_TIMEOUT_MS,
+                        MAX_TIMEOUT_MS
+                    ),
+                    params: vec![MIN_TIMEOUT_MS.to_string(), MAX_TIMEOUT_MS.to_string()],
+                },
+            }),
+        ).into_response();
+    }
+
+    let request = optimus_common::types::DryRunRequest {
+        id: Uuid::new_v4(),
+        language,
+        source_code: payload.source_code,
+        input: payload.input,
+        timeout_ms: payload.timeout_ms,
+    };
+
+    let mut conn = state.redis.clone();
+    if let Err(e) = redis::push_dry_run(&mut conn, &request).await {
+        error!(request_id = %request.id, error = %e, "Failed to queue dry-run");
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "QUEUE_FAILURE".to_string(),
+                    message: format!("Failed to queue dry-run: {}", e),
+                    params: vec![],
+                },
+            }),
+        ).into_response();
+    }
+
+    info!(
+        request_id = %request.id,
+        language = %language,
+        phase = "dry_run_queued",
+        "Dry-run queued"
+    );
+
+    // Poll for the result - a blocking read would stall the shared multiplexed connection
+    let deadline = std::time::Instant::now()
+        + std::time::Duration::from_millis(request.timeout_ms + DRY_RUN_GRACE_MS);
+
+    while std::time::Instant::now() < deadline {
+        match redis::get_dry_run_result(&mut conn, &request.id).await {
+            Ok(Some(result)) => {
+                info!(request_id = %request.id, exit_code = ?result.exit_code, "Dry-run completed");
+                return (StatusCode::OK, Json(result)).into_response();
+            }
+            Ok(None) => {}
+            Err(e) => {
+                error!(request_id = %request.id, error = %e, "Failed to fetch dry-run result");
+            }
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(DRY_RUN_POLL_INTERVAL_MS)).await;
+    }
+
+    warn!(request_id = %request.id, language = %language, "Dry-run timed out waiting for a worker");
+    (
+        StatusCode::GATEWAY_TIMEOUT,
+        Json(ErrorResponse {
+            error: ErrorDetail {
+                code: "DRY_RUN_TIMEOUT".to_string(),
+                message: format!("No {} worker completed the dry-run in time", language),
+                params: vec![],
+            },
+        }),
+    ).into_response()
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateBatchRequest {
+    /// Number of jobs that will be submitted with this `batch_id`
+    pub expected_jobs: u32,
+    /// Receives one `batch.completed` POST when every job has finished
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+    /// Webhook body: `summary` (default), `full`, or `{"projection": [dot paths]}`
+    #[serde(default)]
+    pub webhook_payload: optimus_common::types::WebhookPayload,
+    /// Finalized upload (`POST /uploads`) whose tests run for batch jobs
+    /// that send no `test_cases`
+    #[serde(default)]
+    pub test_suite: Option<Uuid>,
+    /// Release the batch's jobs evenly over this many milliseconds from batch
+    /// creation instead of queueing each one on submission
+    #[serde(default)]
+    pub spread_ms: u64,
+    /// Random extra delay of up to this many milliseconds per job
+    #[serde(default)]
+    pub jitter_ms: u64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CreateBatchResponse {
+    pub batch_id: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BatchStatusResponse {
+    pub batch_id: String,
+    pub expected_jobs: u32,
+    pub submitted_jobs: u32,
+    pub finished_jobs: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completion: Option<optimus_common::types::BatchCompletion>,
+}
+
+const MAX_BATCH_JOBS: u32 = 10_000;
+const MAX_WEBHOOK_URL_LEN: usize = 2048;
+const MAX_BATCH_SPREAD_MS: u64 = 3_600_000; // 1 hour
+const MAX_BATCH_JITTER_MS: u64 = 60_000; // 1 minute
+
+/// POST /batches - Create a batch that jobs can be submitted into
+///
+/// Jobs join the batch by passing `batch_id` to `/execute`. When the last
+/// of `expected_jobs` finishes, one aggregated completion event is sent to
+/// the webhook (if any) and to `GET /batches/{id}/events` subscribers.
+///
+/// With `spread_ms` / `jitter_ms`, submitted jobs are held back and released
+/// over the window so a whole-class regrade does not start every container at once.
+#[utoipa::path(
+    post,
+    path = "/batches",
+    tag = "batches",
+    request_body = CreateBatchRequest,
+    responses(
+        (status = 201, description = "Batch created", body = CreateBatchResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 403, description = "Test suite upload owned by another caller", body = ErrorResponse),
+        (status = 404, description = "Test suite upload not found", body = ErrorResponse),
+        (status = 409, description = "Test suite upload not finalized", body = ErrorResponse),
+    )
+)]
+pub async fn create_batch(
+    State(state): State<Arc<AppState>>,
+    auth: Option<Extension<AuthContext>>,
+    Json(payload): Json<CreateBatchRequest>,
+) -> impl IntoResponse {
+    if payload.expected_jobs == 0 || payload.expected_jobs > MAX_BATCH_JOBS {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "INVALID_BATCH_SIZE".to_string(),
+                    message: format!("expected_jobs must be between 1 and {}", MAX_BATCH_JOBS),
+                    params: vec![],
+                },
+            }),
+        ).into_response();
+    }
+
+    if let Some(url) = &payload.webhook_url {
+        let valid_scheme = url.starts_with("https://") || url.starts_with("http://");
+        if !valid_scheme || url.len() > MAX_WEBHOOK_URL_LEN {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "INVALID_WEBHOOK_URL".to_string(),
+                        message: format!(
+                            "webhook_url must be an http(s) URL of at most {} characters",
+                            MAX_WEBHOOK_URL_LEN
+                        ),
+                        params: vec![],
+                    },
+                }),
+            ).into_response();
+        }
+    }
+
+    if let Err(reason) = payload.webhook_payload.validate() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "INVALID_WEBHOOK_PAYLOAD".to_string(),
+                    message: format!("webhook_payload: {}", reason),
+                    params: vec![],
+                },
+            }),
+        ).into_response();
+    }
+
+    if payload.spread_ms > MAX_BATCH_SPREAD_MS || payload.jitter_ms > MAX_BATCH_JITTER_MS {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "INVALID_BATCH_SPREAD".to_string(),
+                    message: format!(
+                        "spread_ms must be at most {} and jitter_ms at most {}",
+                        MAX_BATCH_SPREAD_MS, MAX_BATCH_JITTER_MS
+                    ),
+                    params: vec![],
+                },
+            }),
+        ).into_response();
+    }
+
+    let owner = auth.map(|Extension(ctx)| ctx.subject);
+    let test_suite = match payload.test_suite {
+        Some(upload_id) => match finalized_test_suite(&state, &upload_id, owner.as_deref()).await {
+            Ok(suite) => Some(suite),
+            Err(response) => return response,
+        },
+        None => None,
+    };
+
+    let batch = optimus_common::types::BatchRecord {
+        id: Uuid::new_v4(),
+        expected_jobs: payload.expected_jobs,
+        webhook_url: payload.webhook_url,
+        webhook_payload: payload.webhook_payload,
+        owner,
+        test_suite,
+        spread_ms: payload.spread_ms,
+        jitter_ms: payload.jitter_ms,
+        created_at: chrono::Utc::now().to_rfc3339(),
+    };
+
+    let mut conn = state.redis.clone();
+    match redis::create_batch(&mut conn, &batch).await {
+        Ok(_) => {
+            info!(
+                batch_id = %batch.id,
+                expected_jobs = batch.expected_jobs,
+                webhook = batch.webhook_url.is_some(),
+                webhook_payload = ?batch.webhook_payload,
+                spread_ms = batch.spread_ms,
+                jitter_ms = batch.jitter_ms,
+                "Batch created"
+            );
+            (
+                StatusCode::CREATED,
+                Json(CreateBatchResponse {
+                    batch_id: batch.id.to_string(),
+                }),
+            ).into_response()
+        }
+        Err(e) => {
+            error!(error = %e, "Failed to create batch");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "INTERNAL_ERROR".to_string(),
+                        message: format!("Failed to create batch: {}", e),
+                        params: vec![],
+                    },
+                }),
+            ).into_response()
+        }
+    }
+}
+
+/// Load a batch for a read endpoint, enforcing ownership
+async fn load_batch_for_caller(
+    state: &AppState,
+    batch_id: &str,
+    auth: Option<&AuthContext>,
+) -> Result<optimus_common::types::BatchRecord, axum::response::Response> {
+    let error = |status: StatusCode, code: &str, message: String| {
+        (
+            status,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: code.to_string(),
+                    message,
+                    params: vec![],
+                },
+            }),
+        ).into_response()
+    };
+
+    let batch_uuid = Uuid::parse_str(batch_id)
+        .map_err(|_| error(StatusCode::BAD_REQUEST, "INVALID_BATCH_ID", "Invalid batch ID format".to_string()))?;
+
+    let batch = redis::get_batch(&mut state.redis.clone(), &batch_uuid)
+        .await
+        .map_err(|e| error(StatusCode::INTERNAL_SERVER_ERROR, "INTERNAL_ERROR", format!("Failed to load batch: {}", e)))?
+        .ok_or_else(|| error(StatusCode::NOT_FOUND, "BATCH_NOT_FOUND", format!("Batch {} does not exist or has expired", batch_id)))?;
+
+    if batch.owner.is_some() && batch.owner.as_deref() != auth.map(|ctx| ctx.subject.as_str()) {
+        return Err(error(StatusCode::FORBIDDEN, "BATCH_FORBIDDEN", "Batch belongs to a different caller".to_string()));
+    }
+
+    Ok(batch)
+}
+
+/// GET /batches/{id} - Batch progress and, once finished, the completion event
+#[utoipa::path(
+    get,
+    path = "/batches/{batch_id}",
+    tag = "batches",
+    params(("batch_id" = Uuid, Path, description = "Batch id")),
+    responses(
+        (status = 200, description = "Batch progress", body = BatchStatusResponse),
+        (status = 403, description = "Batch owned by another caller", body = ErrorResponse),
+        (status = 404, description = "Batch not found", body = ErrorResponse),
+    )
+)]
+pub async fn get_batch(
+    State(state): State<Arc<AppState>>,
+    auth: Option<Extension<AuthContext>>,
+    Path(batch_id): Path<String>,
+) -> impl IntoResponse {
+    let batch = match load_batch_for_caller(&state, &batch_id, auth.as_ref().map(|Extension(ctx)| ctx)).await {
+        Ok(batch) => batch,
+        Err(response) => return response,
+    };
+
+    let mut conn = state.redis.clone();
+    let (submitted_jobs, finished_jobs) = redis::get_batch_progress(&mut conn, &batch.id)
+        .await
+        .unwrap_or_else(|e| {
+            error!(batch_id = %batch.id, error = %e, "Failed to read batch progress");
+            (0, 0)
+        });
+    let completion = redis::get_batch_completion(&mut conn, &batch.id)
+        .await
+        .unwrap_or_else(|e| {
+            error!(batch_id = %batch.id, error = %e, "Failed to read batch completion");
+            None
+        });
+
+    (
+        StatusCode::OK,
+        Json(BatchStatusResponse {
+            batch_id: batch.id.to_string(),
+            expected_jobs: batch.expected_jobs,
+            submitted_jobs,
+            finished_jobs,
+            completion,
+        }),
+    ).into_response()
+}
+
+/// GET /batches/{id}/events - Server-Sent Events stream for batch completion
+///
+/// Emits a single `batch.completed` event (immediately if the batch already
+/// finished) and then closes the stream.
+#[utoipa::path(
+    get,
+    path = "/batches/{batch_id}/events",
+    tag = "batches",
+    params(("batch_id" = Uuid, Path, description = "Batch id")),
+    responses(
+        (status = 200, description = "SSE stream with one `batch.completed` event", body = optimus_common::types::BatchCompletion, content_type = "text/event-stream"),
+        (status = 404, description = "Batch not found", body = ErrorResponse),
+    )
+)]
+pub async fn batch_events(
+    State(state): State<Arc<AppState>>,
+    auth: Option<Extension<AuthContext>>,
+    Path(batch_id): Path<String>,
+) -> impl IntoResponse {
+    use axum::response::sse::{Event, KeepAlive, Sse};
+    use futures_util::StreamExt;
+
+    let batch = match load_batch_for_caller(&state, &batch_id, auth.as_ref().map(|Extension(ctx)| ctx)).await {
+        Ok(batch) => batch,
+        Err(response) => return response,
+    };
+
+    // Subscribe before checking for a stored completion so the event can't slip between the two
+    let mut pubsub = match state.redis_client.get_async_connection().await {
+        Ok(conn) => conn.into_pubsub(),
+        Err(e) => {
+            error!(batch_id = %batch.id, error = %e, "Failed to open pubsub connection");
+            return (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "EVENTS_UNAVAILABLE".to_string(),
+                        message: "Event stream is temporarily unavailable".to_string(),
+                        params: vec![],
+                    },
+                }),
+            ).into_response();
+        }
+    };
+    if let Err(e) = pubsub.subscribe(redis::batch_events_channel(&batch.id)).await {
+        error!(batch_id = %batch.id, error = %e, "Failed to subscribe to batch events");
+    }
+
+    let stored = redis::get_batch_completion(&mut state.redis.clone(), &batch.id)
+        .await
+        .ok()
+        .flatten()
+        .and_then(|completion| serde_json::to_string(&completion).ok());
+
+    let stream = futures_util::stream::once(async move {
+        match stored {
+            Some(payload) => Some(payload),
+            None => pubsub.on_message().next().await.and_then(|msg| msg.get_payload::<String>().ok()),
+        }
+    })
+    .filter_map(|payload| async move {
+        payload.map(|data| Ok::<_, std::convert::Infallible>(Event::default().event("batch.completed").data(data)))
+    });
+
+    Sse::new(stream).keep_alive(KeepAlive::default()).into_response()
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct AnalyticsQuery {
+    /// Days to cover, ending today (default 7, max 90)
+    pub days: Option<u32>,
+}
+
+/// GET /admin/analytics/languages - Language, version and limit-tier usage
+///
+/// Rolls up the daily buckets workers record for every finished job: job
+/// counts per language/version/tier plus source size and execution time
+/// percentiles. Languages are reported even when unused, so candidates for
+/// retirement show up as zero.
+#[utoipa::path(
+    get,
+    path = "/admin/analytics/languages",
+    tag = "admin",
+    params(AnalyticsQuery),
+    responses(
+        (status = 200, description = "Usage report", body = analytics::LanguageAnalyticsReport),
+        (status = 400, description = "Invalid days", body = ErrorResponse),
+        (status = 500, description = "Redis error", body = ErrorResponse),
+    )
+)]
+pub async fn get_language_analytics(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<AnalyticsQuery>,
+) -> impl IntoResponse {
+    let days = query.days.unwrap_or(analytics::DEFAULT_DAYS);
+    if days == 0 || days > analytics::MAX_DAYS {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "INVALID_DAYS".to_string(),
+                    message: format!("days must be between 1 and {}", analytics::MAX_DAYS),
+                    params: vec![],
+                },
+            }),
+        ).into_response();
+    }
+
+    let today = chrono::Utc::now().date_naive();
+    let dates: Vec<String> = (0..days)
+        .rev()
+        .map(|offset| (today - chrono::Duration::days(offset as i64)).format("%Y-%m-%d").to_string())
+        .collect();
+
+    let mut conn = state.redis.clone();
+    let mut languages = Vec::new();
+    for language in Language::all_variants() {
+        let mut buckets = Vec::with_capacity(dates.len());
+        for date in &dates {
+            match redis::get_language_usage_day(&mut conn, date, language).await {
+                Ok(day) => buckets.push((date.clone(), day)),
+                Err(e) => {
+                    error!(language = %language, date = %date, error = %e, "Failed to read usage analytics");
+                    return (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(ErrorResponse {
+                            error: ErrorDetail {
+                                code: "INTERNAL_ERROR".to_string(),
+                                message: format!("Failed to read usage analytics: {}", e),
+                                params: vec![],
+                            },
+                        }),
+                    ).into_response();
+                }
+            }
+        }
+        languages.push(analytics::summarize(*language, buckets));
+    }
+
+    let report = analytics::LanguageAnalyticsReport {
+        from: dates.first().cloned().unwrap_or_default(),
+        to: dates.last().cloned().unwrap_or_default(),
+        languages,
+    };
+    (StatusCode::OK, Json(report)).into_response()
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct JobDiagnostics {
+    pub job_id: Uuid,
+    /// One bundle per timed-out test, ordered by test id
+    pub bundles: Vec<optimus_common::types::DiagnosticBundle>,
+}
+
+/// GET /admin/job/{job_id}/diagnostics - Kill diagnostics for a job's timed-out tests
+///
+/// Bundles are only captured for languages with `kill_diagnostics` in
+/// languages.json, and are kept as long as the result. A job with no
+/// timeouts (or an unknown job) has none.
+#[utoipa::path(
+    get,
+    path = "/admin/job/{job_id}/diagnostics",
+    tag = "admin",
+    params(("job_id" = Uuid, Path, description = "Job id")),
+    responses(
+        (status = 200, description = "Captured bundles (possibly none)", body = JobDiagnostics),
+        (status = 400, description = "Invalid job id", body = ErrorResponse),
+        (status = 500, description = "Redis error", body = ErrorResponse),
+    )
+)]
+pub async fn get_job_diagnostics(
+    State(state): State<Arc<AppState>>,
+    Path(job_id): Path<String>,
+) -> impl IntoResponse {
+    let job_uuid = match Uuid::parse_str(&job_id) {
+        Ok(id) => id,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "INVALID_JOB_ID".to_string(),
+                        message: "Invalid job ID format".to_string(),
+                        params: vec![],
+                    },
+                }),
+            ).into_response();
+        }
+    };
+
+    let mut conn = state.redis.clone();
+    match redis::get_diagnostics(&mut conn, &job_uuid).await {
+        Ok(bundles) => (StatusCode::OK, Json(JobDiagnostics { job_id: job_uuid, bundles })).into_response(),
+        Err(e) => {
+            error!(job_id = %job_id, error = %e, "Failed to read kill diagnostics");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "INTERNAL_ERROR".to_string(),
+                        message: format!("Failed to read kill diagnostics: {}", e),
+                        params: vec![],
+                    },
+                }),
+            ).into_response()
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ForceCompleteRequest {
+    /// Terminal status to record: `cancelled` or `failed`
+    pub status: optimus_common::types::JobStatus,
+    pub reason: String,
+}
+
+const MAX_FORCE_COMPLETE_REASON_LEN: usize = 1024;
+
+/// POST /admin/job/{job_id}/force-complete - Write a terminal result for a stuck job
+///
+/// For jobs orphaned by a lost worker or lease. Atomically removes the job
+/// from every queue, stores a Cancelled/Failed result carrying the reason,
+/// and flags it cancelled. Refuses (409) if the job already has a result.
+pub async fn force_complete_job(
+    State(state): State<Arc<AppState>>,
+    Path(job_id): Path<String>,
+    Json(payload): Json<ForceCompleteRequest>,
+) -> impl IntoResponse {
+    use optimus_common::types::{ExecutionResult, JobStatus};
+
+    let job_uuid = match Uuid::parse_str(&job_id) {
+        Ok(id) => id,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "INVALID_JOB_ID".to_string(),
+                        message: "Invalid job ID format".to_string(),
+                        params: vec![],
+                    },
+                }),
+            ).into_response();
+        }
+    };
+
+    if !matches!(payload.status, JobStatus::Cancelled | JobStatus::Failed) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "INVALID_STATUS".to_string(),
+                    message: "status must be cancelled or failed".to_string(),
+                    params: vec![],
+                },
+            }),
+        ).into_response();
+    }
+
+    let reason = payload.reason.trim();
+    if reason.is_empty() || reason.len() > MAX_FORCE_COMPLETE_REASON_LEN {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "INVALID_REASON".to_string(),
+                    message: format!("reason must be 1-{} characters", MAX_FORCE_COMPLETE_REASON_LEN),
+                    params: vec![],
+                },
+            }),
+        ).into_response();
+    }
+
+    let mut conn = state.redis.clone();
+
+    // Best-effort lookup for scoring and batch bookkeeping - jobs lost mid-execution aren't queued
+    let queued_job = redis::find_queued_job(&mut conn, &job_uuid).await.ok().flatten();
+
+    let result = ExecutionResult {
+        job_id: job_uuid,
+        overall_status: payload.status,
+        score: 0,
+        max_score: queued_job
+            .as_ref()
+            .map(|job| job.test_cases.iter().map(|tc| tc.weight).sum())
+            .unwrap_or(0),
+        results: vec![],
+        scoring_formula: None,
+        reason: Some(reason.to_string()),
+        feedback: None,
+        timing_summary: None,
+        suspicious: false,
+        cached: false,
+        verdict_summary: None,
+    };
+
+    match redis::force_complete_job(&mut conn, &result).await {
+        Ok(Some(removed)) => {
+            warn!(
+                job_id = %job_id,
+                status = ?result.overall_status,
+                removed_from_queues = removed,
+                reason = %reason,
+                "Job force-completed by operator"
+            );
+            if result.overall_status == JobStatus::Cancelled {
+                metrics::record_job_cancelled("admin");
+            }
+
+            if let Some(batch_id) = queued_job.as_ref().and_then(|job| job.batch_id) {
+                if let Ok(Some(batch)) = redis::get_batch(&mut conn, &batch_id).await {
+                    let finished = redis::mark_batch_job_finished(&mut conn, &batch, &job_uuid).await;
+                    if matches!(finished, Ok(true)) {
+                        if let Err(e) = redis::complete_batch(&mut conn, &batch).await {
+                            error!(batch_id = %batch_id, error = %e, "Failed to publish batch completion");
+                        }
+                    }
+                }
+            }
+
+            (StatusCode::OK, Json(result)).into_response()
+        }
+        Ok(None) => (
+            StatusCode::CONFLICT,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "ALREADY_FINISHED".to_string(),
+                    message: "Job already has a result".to_string(),
+                    params: vec![],
+                },
+            }),
+        ).into_response(),
+        Err(e) => {
+            error!(job_id = %job_id, error = %e, "Failed to force-complete job");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "INTERNAL_ERROR".to_string(),
+                        message: format!("Failed to force-complete job: {}", e),
+                        params: vec![],
+                    },
+                }),
+            ).into_response()
+        }
+    }
+}
+
+/// Largest `max_attempts` an operator may give a queued job
+const MAX_QUEUED_JOB_ATTEMPTS: u8 = 10;
+
+#[derive(Debug, Deserialize)]
+pub struct QueuedJobsQuery {
+    /// Index of the first job to return, in dequeue order (default 0)
+    pub offset: Option<usize>,
+    /// Number of jobs to return (default 100, max 1000)
+    pub limit: Option<usize>,
+}
+
+/// One waiting job, without its source and test cases
+#[derive(Debug, Serialize)]
+pub struct QueuedJobSummary {
+    pub job_id: Uuid,
+    pub queue: String,
+    pub position: usize,
+    pub priority: Priority,
+    pub timeout_ms: u64,
+    pub attempts: u8,
+    pub max_attempts: u8,
+    pub test_cases: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub batch_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub submitted_at: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QueuedJobsResponse {
+    pub language: Language,
+    pub jobs: Vec<QueuedJobSummary>,
+    pub page: ResultsPage,
+}
+
+/// A waiting job in full, with where it sits
+#[derive(Debug, Serialize)]
+pub struct QueuedJobDetail {
+    pub queue: String,
+    pub position: usize,
+    pub job: JobRequest,
+}
+
+/// Fields an operator may change on a waiting job
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct QueuedJobEdit {
+    pub timeout_ms: Option<u64>,
+    pub max_attempts: Option<u8>,
+}
+
+fn queue_error(status: StatusCode, code: &str, message: String) -> axum::response::Response {
+    (
+        status,
+        Json(ErrorResponse {
+            error: ErrorDetail {
+                code: code.to_string(),
+                message,
+                params: vec![],
+            },
+        }),
+    ).into_response()
+}
+
+/// Find a job still waiting in one of a language's queues
+async fn load_queued_job(
+    state: &AppState,
+    language: &str,
+    job_id: &str,
+) -> Result<redis::QueuedJob, axum::response::Response> {
+    let language = Language::from_str(language)
+        .ok_or_else(|| queue_error(StatusCode::NOT_FOUND, "LANGUAGE_NOT_FOUND", format!("Unknown language '{}'", language)))?;
+    let job_uuid = Uuid::parse_str(job_id)
+        .map_err(|_| queue_error(StatusCode::BAD_REQUEST, "INVALID_JOB_ID", "Invalid job ID format".to_string()))?;
+
+    redis::list_queued_jobs(&mut state.redis.clone(), &language)
+        .await
+        .map_err(|e| queue_error(StatusCode::INTERNAL_SERVER_ERROR, "INTERNAL_ERROR", format!("Failed to read queue: {}", e)))?
+        .into_iter()
+        .find(|queued| queued.job.id == job_uuid)
+        .ok_or_else(|| {
+            queue_error(
+                StatusCode::NOT_FOUND,
+                "JOB_NOT_QUEUED",
+                format!("Job {} is not waiting in a {} queue", job_id, language),
+            )
+        })
+}
+
+/// GET /admin/queue/{language}/jobs - Jobs waiting for a worker, in dequeue order
+///
+/// Covers the high, normal, retry and low queues (not the DLQ). Source code
+/// and test cases are left out; fetch a single job for those.
+pub async fn list_queued_jobs(
+    State(state): State<Arc<AppState>>,
+    Path(language): Path<String>,
+    Query(query): Query<QueuedJobsQuery>,
+) -> impl IntoResponse {
+    let language = match Language::from_str(&language) {
+        Some(lang) => lang,
+        None => return queue_error(StatusCode::NOT_FOUND, "LANGUAGE_NOT_FOUND", format!("Unknown language '{}'", language)),
+    };
+
+    let queued = match redis::list_queued_jobs(&mut state.redis.clone(), &language).await {
+        Ok(queued) => queued,
+        Err(e) => {
+            error!(language = %language, error = %e, "Failed to list queued jobs");
+            return queue_error(StatusCode::INTERNAL_SERVER_ERROR, "INTERNAL_ERROR", format!("Failed to read queue: {}", e));
+        }
+    };
+
+    let offset = query.offset.unwrap_or(0);
+    let limit = query.limit.unwrap_or(DEFAULT_RESULTS_LIMIT).clamp(1, MAX_RESULTS_LIMIT);
+    let total = queued.len();
+    let jobs = queued
+        .into_iter()
+        .skip(offset)
+        .take(limit)
+        .map(|queued| QueuedJobSummary {
+            job_id: queued.job.id,
+            queue: queued.queue,
+            position: queued.position,
+            priority: queued.job.priority,
+            timeout_ms: queued.job.timeout_ms,
+            attempts: queued.job.metadata.attempts,
+            max_attempts: queued.job.metadata.max_attempts,
+            test_cases: queued.job.test_cases.len(),
+            batch_id: queued.job.batch_id,
+            submitted_at: queued.job.metadata.submitted_at,
+        })
+        .collect();
+
+    (
+        StatusCode::OK,
+        Json(QueuedJobsResponse {
+            language,
+            jobs,
+            page: ResultsPage { offset, limit, total },
+        }),
+    ).into_response()
+}
+
+/// GET /admin/queue/{language}/jobs/{job_id} - One waiting job in full
+pub async fn get_queued_job(
+    State(state): State<Arc<AppState>>,
+    Path((language, job_id)): Path<(String, String)>,
+) -> impl IntoResponse {
+    match load_queued_job(&state, &language, &job_id).await {
+        Ok(queued) => (
+            StatusCode::OK,
+            Json(QueuedJobDetail {
+                queue: queued.queue,
+                position: queued.position,
+                job: queued.job,
+            }),
+        ).into_response(),
+        Err(response) => response,
+    }
+}
+
+/// PATCH /admin/queue/{language}/jobs/{job_id} - Edit a waiting job in place
+///
+/// Only `timeout_ms` and `max_attempts` can change. The entry is swapped
+/// atomically where it sits, so the job keeps its place in line; if a
+/// worker picked it up in the meantime nothing changes (409).
+pub async fn edit_queued_job(
+    State(state): State<Arc<AppState>>,
+    Path((language, job_id)): Path<(String, String)>,
+    Json(edit): Json<QueuedJobEdit>,
+) -> impl IntoResponse {
+    if edit.timeout_ms.is_none() && edit.max_attempts.is_none() {
+        return queue_error(StatusCode::BAD_REQUEST, "EMPTY_EDIT", "Set timeout_ms and/or max_attempts".to_string());
+    }
+    if let Some(timeout_ms) = edit.timeout_ms {
+        if !(MIN_TIMEOUT_MS..=MAX_TIMEOUT_MS).contains(&timeout_ms) {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "INVALID_TIMEOUT".to_string(),
+                        message: format!("Timeout must be between {}ms and {}ms", MIN_TIMEOUT_MS, MAX_TIMEOUT_MS),
+                        params: vec![MIN_TIMEOUT_MS.to_string(), MAX_TIMEOUT_MS.to_string()],
+                    },
+                }),
+            ).into_response();
+        }
+    }
+    if let Some(max_attempts) = edit.max_attempts {
+        if !(1..=MAX_QUEUED_JOB_ATTEMPTS).contains(&max_attempts) {
+            return queue_error(
+                StatusCode::BAD_REQUEST,
+                "INVALID_MAX_ATTEMPTS",
+                format!("max_attempts must be between 1 and {}", MAX_QUEUED_JOB_ATTEMPTS),
+            );
+        }
+    }
+
+    let queued = match load_queued_job(&state, &language, &job_id).await {
+        Ok(queued) => queued,
+        Err(response) => return response,
+    };
+
+    let mut job = queued.job.clone();
+    if let Some(timeout_ms) = edit.timeout_ms {
+        job.timeout_ms = timeout_ms;
+    }
+    if let Some(max_attempts) = edit.max_attempts {
+        job.metadata.max_attempts = max_attempts;
+    }
+
+    match redis::replace_queued_job(&mut state.redis.clone(), &queued, &job).await {
+        Ok(true) => {
+            warn!(
+                job_id = %job.id,
+                queue = %queued.queue,
+                timeout_ms = job.timeout_ms,
+                previous_timeout_ms = queued.job.timeout_ms,
+                max_attempts = job.metadata.max_attempts,
+                previous_max_attempts = queued.job.metadata.max_attempts,
+                "Queued job edited by operator"
+            );
+            (
+                StatusCode::OK,
+                Json(QueuedJobDetail {
+                    queue: queued.queue,
+                    position: queued.position,
+                    job,
+                }),
+            ).into_response()
+        }
+        Ok(false) => queue_error(
+            StatusCode::CONFLICT,
+            "JOB_NOT_QUEUED",
+            "Job left the queue while being edited (picked up by a worker or moved)".to_string(),
+        ),
+        Err(e) => {
+            error!(job_id = %job_id, error = %e, "Failed to replace queued job");
+            queue_error(StatusCode::INTERNAL_SERVER_ERROR, "INTERNAL_ERROR", format!("Failed to replace queued job: {}", e))
+        }
+    }
+}
+
+/// Queued jobs of a language by kind, summed across worker pools
+#[derive(Debug, Serialize)]
+pub struct LanguageQueueDepths {
+    pub language: Language,
+    /// High, normal and low priority queues
+    pub main: u64,
+    pub retry: u64,
+    pub dlq: u64,
+    /// Every list by name
+    pub queues: BTreeMap<String, u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QueueDepthsResponse {
+    pub languages: Vec<LanguageQueueDepths>,
+}
+
+/// GET /admin/queues - Depth of the main, retry and dead-letter queues per language
+pub async fn get_queue_depths(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let mut conn = state.redis.clone();
+    let mut languages = Vec::new();
+    for language in Language::all_variants() {
+        let depths = match redis::queue_depths(&mut conn, language).await {
+            Ok(depths) => depths,
+            Err(e) => {
+                error!(language = %language, error = %e, "Failed to read queue depths");
+                return queue_error(StatusCode::INTERNAL_SERVER_ERROR, "INTERNAL_ERROR", format!("Failed to read queue depths: {}", e));
+            }
+        };
+        let mut summary = LanguageQueueDepths {
+            language: *language,
+            main: 0,
+            retry: 0,
+            dlq: 0,
+            queues: BTreeMap::new(),
+        };
+        for (queue, depth) in depths {
+            // Queue names end in ":retry" / ":dlq" for every pool
+            if queue.ends_with(":dlq") {
+                summary.dlq += depth;
+            } else if queue.ends_with(":retry") {
+                summary.retry += depth;
+            } else {
+                summary.main += depth;
+            }
+            summary.queues.insert(queue, depth);
+        }
+        languages.push(summary);
+    }
+
+    (StatusCode::OK, Json(QueueDepthsResponse { languages })).into_response()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PurgeQueuesQuery {
+    /// Also empty the dead-letter queue (default false)
+    #[serde(default)]
+    pub include_dlq: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PurgeQueuesResponse {
+    pub language: Language,
+    /// Entries removed, by queue (emptied queues only)
+    pub purged: BTreeMap<String, u64>,
+    pub total: u64,
+}
+
+/// Result reason stored for jobs dropped by a purge
+const PURGED_REASON: &str = "Purged from the queue by an operator";
+
+/// POST /admin/queues/{language}/purge - Drop every job waiting for a language
+///
+/// Empties the high, normal, retry and low queues of every pool in one
+/// step (the DLQ too with `?include_dlq=true`). Purged jobs that were still
+/// waiting get a `cancelled` result so clients stop polling, and count as
+/// finished in their batch. Jobs already held by a worker are not touched.
+pub async fn purge_queues(
+    State(state): State<Arc<AppState>>,
+    Path(language): Path<String>,
+    Query(query): Query<PurgeQueuesQuery>,
+) -> impl IntoResponse {
+    use optimus_common::types::{ExecutionResult, JobStatus};
+
+    let language = match Language::from_str(&language) {
+        Some(lang) => lang,
+        None => return queue_error(StatusCode::NOT_FOUND, "LANGUAGE_NOT_FOUND", format!("Unknown language '{}'", language)),
+    };
+
+    let mut conn = state.redis.clone();
+    let drained = match redis::drain_queues(&mut conn, &language, query.include_dlq).await {
+        Ok(drained) => drained,
+        Err(e) => {
+            error!(language = %language, error = %e, "Failed to purge queues");
+            return queue_error(StatusCode::INTERNAL_SERVER_ERROR, "INTERNAL_ERROR", format!("Failed to purge queues: {}", e));
+        }
+    };
+
+    let dlq = redis::dlq_name(&language);
+    let mut purged = BTreeMap::new();
+    for (queue, entries) in drained {
+        if entries.is_empty() {
+            continue;
+        }
+        purged.insert(queue.clone(), entries.len() as u64);
+        // Dead-lettered jobs already carry their failed result
+        if queue == dlq {
+            continue;
+        }
+        for job in entries.iter().filter_map(|entry| serde_json::from_str::<JobRequest>(entry).ok()) {
+            let result = ExecutionResult {
+                job_id: job.id,
+                overall_status: JobStatus::Cancelled,
+                score: 0,
+                max_score: job.test_cases.iter().map(|tc| tc.weight).sum(),
+                results: vec![],
+                scoring_formula: None,
+                reason: Some(PURGED_REASON.to_string()),
+                feedback: None,
+                timing_summary: None,
+                suspicious: false,
+                cached: false,
+                verdict_summary: None,
+            };
+            if let Err(e) = redis::store_result(&mut conn, &result).await {
+                error!(job_id = %job.id, error = %e, "Failed to store result for purged job");
+                continue;
+            }
+            metrics::record_job_cancelled("purge");
+            if let Some(batch_id) = job.batch_id {
+                if let Ok(Some(batch)) = redis::get_batch(&mut conn, &batch_id).await {
+                    finish_batch_job(&mut conn, &batch, &job.id).await;
+                }
+            }
+        }
+    }
+
+    let total = purged.values().sum();
+    warn!(
+        language = %language,
+        include_dlq = query.include_dlq,
+        purged = total,
+        "Queues purged by operator"
+    );
+    (StatusCode::OK, Json(PurgeQueuesResponse { language, purged, total })).into_response()
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ForecastReport {
+    pub languages: Vec<SubmissionForecast>,
+}
+
+/// GET /metrics/forecast - Predicted submission rate per language
+///
+/// EWMA of submissions per minute over the last completed minutes, plus the
+/// count for the current minute. Meant for autoscalers (e.g. the KEDA
+/// `metrics-api` scaler) that should add workers before queue depth climbs.
+#[utoipa::path(
+    get,
+    path = "/metrics/forecast",
+    tag = "health",
+    responses(
+        (status = 200, description = "Forecast per language", body = ForecastReport),
+        (status = 500, description = "Redis error", body = ErrorResponse),
+    )
+)]
+pub async fn get_submission_forecast(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let mut conn = state.redis.clone();
+    let mut languages = Vec::new();
+    for language in Language::all_variants() {
+        match forecast::compute(&mut conn, language).await {
+            Ok(forecast) => languages.push(forecast),
+            Err(e) => {
+                error!(language = %language, error = %e, "Failed to compute submission forecast");
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ErrorResponse {
+                        error: ErrorDetail {
+                            code: "INTERNAL_ERROR".to_string(),
+                            message: format!("Failed to compute submission forecast: {}", e),
+                            params: vec![],
+                        },
+                    }),
+                ).into_response();
+            }
+        }
+    }
+
+    (StatusCode::OK, Json(ForecastReport { languages })).into_response()
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LanguageWorkers {
+    pub language: Language,
+    /// Jobs executing across the language's live workers
+    pub active_jobs: usize,
+    /// Sum of the live workers' `max_parallel_jobs`
+    pub capacity: usize,
+    pub workers: Vec<WorkerInfo>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WorkersReport {
+    pub languages: Vec<LanguageWorkers>,
+}
+
+/// GET /workers - Live workers per language and their current load
+///
+/// Workers re-register with every heartbeat; one that stopped heartbeating
+/// drops out after `HEARTBEAT_STALE_SECS`. Languages without workers are
+/// listed with an empty `workers`.
+#[utoipa::path(
+    get,
+    path = "/workers",
+    tag = "health",
+    responses(
+        (status = 200, description = "Live workers per language", body = WorkersReport),
+        (status = 500, description = "Redis error", body = ErrorResponse),
+    )
+)]
+pub async fn list_workers(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let mut conn = state.redis.clone();
+    let mut languages = Vec::new();
+    for language in Language::all_variants() {
+        match redis::list_live_workers(&mut conn, language).await {
+            Ok(workers) => languages.push(LanguageWorkers {
+                language: *language,
+                active_jobs: workers.iter().map(|worker| worker.active_jobs).sum(),
+                capacity: workers.iter().map(|worker| worker.max_parallel_jobs).sum(),
+                workers,
+            }),
+            Err(e) => {
+                error!(language = %language, error = %e, "Failed to list live workers");
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ErrorResponse {
+                        error: ErrorDetail {
+                            code: "INTERNAL_ERROR".to_string(),
+                            message: format!("Failed to list live workers: {}", e),
+                            params: vec![],
+                        },
+                    }),
+                ).into_response();
+            }
+        }
+    }
+
+    (StatusCode::OK, Json(WorkersReport { languages })).into_response()
+}
+
+/// Largest memory limit a problem may set
+const MAX_PROBLEM_MEMORY_MB: u32 = 8192;
+const MAX_PROBLEM_ID_LEN: usize = 64;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateProblemRequest {
+    /// Letters, digits, `-` and `_` (max 64 characters)
+    pub id: String,
+    pub limits: optimus_common::types::ProblemLimits,
+    /// Finalized upload (`POST /uploads`) holding the problem's tests, run
+    /// for submissions that send no `test_cases`
+    #[serde(default)]
+    pub test_suite: Option<Uuid>,
+}
+
+/// POST /admin/problems - Define a problem with limits submissions can't override
+///
+/// Jobs submitted with `problem_id` run with the problem's timeout, memory
+/// and CPU limits (capped by the language's maximums), time limit mode, comparison and
+/// presentation error handling regardless of their own fields. Problems are
+/// write-once (409 if the id exists) so limits can't shift under submissions
+/// that were already judged.
+#[utoipa::path(
+    post,
+    path = "/admin/problems",
+    tag = "admin",
+    request_body = CreateProblemRequest,
+    responses(
+        (status = 201, description = "Problem created", body = optimus_common::types::Problem),
+        (status = 400, description = "Invalid id or limits", body = ErrorResponse),
+        (status = 403, description = "Test suite upload owned by another caller", body = ErrorResponse),
+        (status = 404, description = "Test suite upload not found", body = ErrorResponse),
+        (status = 409, description = "Problem id already taken, or test suite upload not finalized", body = ErrorResponse),
+        (status = 500, description = "Redis error", body = ErrorResponse),
+    )
+)]
+pub async fn create_problem(
+    State(state): State<Arc<AppState>>,
+    auth: Option<Extension<AuthContext>>,
+    Json(payload): Json<CreateProblemRequest>,
+) -> impl IntoResponse {
+    use optimus_common::types::Problem;
+
+    let invalid = |message: String| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "INVALID_PROBLEM".to_string(),
+                    message,
+                    params: vec![],
+                },
+            }),
+        ).into_response()
+    };
+
+    let id_ok = !payload.id.is_empty()
+        && payload.id.len() <= MAX_PROBLEM_ID_LEN
+        && payload.id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !id_ok {
+        return invalid(format!(
+            "id must be 1-{} characters of letters, digits, '-' or '_'",
+            MAX_PROBLEM_ID_LEN
+        ));
+    }
+
+    let limits = payload.limits;
+    if limits.timeout_ms < MIN_TIMEOUT_MS || limits.timeout_ms > MAX_TIMEOUT_MS {
+        return invalid(format!(
+            "timeout_ms must be between {}ms and {}ms",
+            MIN_TIMEOUT_MS, MAX_TIMEOUT_MS
+        ));
+    }
+    if let Some(memory) = limits.memory_limit_mb {
+        if !(MIN_MEMORY_LIMIT_MB..=MAX_PROBLEM_MEMORY_MB).contains(&memory) {
+            return invalid(format!(
+                "memory_limit_mb must be between {} and {}",
+                MIN_MEMORY_LIMIT_MB, MAX_PROBLEM_MEMORY_MB
+            ));
+        }
+    }
+    if limits.cpu_limit.is_some_and(|cpu| !(cpu.is_finite() && cpu >= MIN_CPU_LIMIT)) {
+        return invalid(format!("cpu_limit must be at least {}", MIN_CPU_LIMIT));
+    }
+    if limits.max_parallel_tests == Some(0) {
+        return invalid("max_parallel_tests must be at least 1".to_string());
+    }
+    if let Some(Err(reason)) = limits.comparison.map(|c| c.validate()) {
+        return invalid(reason);
+    }
+
+    let caller = auth.as_ref().map(|Extension(ctx)| ctx.subject.as_str());
+    let test_suite = match payload.test_suite {
+        Some(upload_id) => match finalized_test_suite(&state, &upload_id, caller).await {
+            Ok(suite) => Some(suite),
+            Err(response) => return response,
+        },
+        None => None,
+    };
+
+    let problem = Problem {
+        id: payload.id,
+        limits,
+        test_suite,
+        created_at: chrono::Utc::now().to_rfc3339(),
+    };
+
+    match redis::create_problem(&mut state.redis.clone(), &problem).await {
+        Ok(true) => {
+            info!(problem_id = %problem.id, timeout_ms = limits.timeout_ms, "Problem created");
+            (StatusCode::CREATED, Json(problem)).into_response()
+        }
+        Ok(false) => (
+            StatusCode::CONFLICT,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "PROBLEM_EXISTS".to_string(),
+                    message: format!("Problem '{}' already exists (problems are immutable)", problem.id),
+                    params: vec![],
+                },
+            }),
+        ).into_response(),
+        Err(e) => {
+            error!(problem_id = %problem.id, error = %e, "Failed to store problem");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "INTERNAL_ERROR".to_string(),
+                        message: format!("Failed to store problem: {}", e),
+                        params: vec![],
+                    },
+                }),
+            ).into_response()
+        }
+    }
+}
+
+/// GET /problems/{problem_id} - Limits a problem imposes on submissions
+#[utoipa::path(
+    get,
+    path = "/problems/{problem_id}",
+    tag = "jobs",
+    params(("problem_id" = String, Path, description = "Problem id")),
+    responses(
+        (status = 200, description = "Problem definition", body = optimus_common::types::Problem),
+        (status = 404, description = "Unknown problem", body = ErrorResponse),
+        (status = 500, description = "Redis error", body = ErrorResponse),
+    )
+)]
+pub async fn get_problem(
+    State(state): State<Arc<AppState>>,
+    Path(problem_id): Path<String>,
+) -> impl IntoResponse {
+    match redis::get_problem(&mut state.redis.clone(), &problem_id).await {
+        Ok(Some(problem)) => (StatusCode::OK, Json(problem)).into_response(),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "PROBLEM_NOT_FOUND".to_string(),
+                    message: format!("Problem '{}' does not exist", problem_id),
+                    params: vec![problem_id.to_string()],
+                },
+            }),
+        ).into_response(),
+        Err(e) => {
+            error!(problem_id = %problem_id, error = %e, "Failed to load problem");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "INTERNAL_ERROR".to_string(),
+                        message: format!("Failed to load problem: {}", e),
+                        params: vec![],
+                    },
+                }),
+            ).into_response()
+        }
+    }
+}
+
+/// Largest problem asset an upload may carry
+pub const MAX_ASSET_BYTES: usize = 10 * 1024 * 1024;
+const MAX_ASSET_NAME_LEN: usize = 128;
+/// How long clients and CDNs may reuse an asset before revalidating its ETag
+const ASSET_MAX_AGE_SECS: u64 = 300;
+
+fn asset_error(status: StatusCode, code: &str, message: String) -> axum::response::Response {
+    (
+        status,
+        Json(ErrorResponse {
+            error: ErrorDetail {
+                code: code.to_string(),
+                message,
+                params: vec![],
+            },
+        }),
+    ).into_response()
+}
+
+/// Letters, digits, `-`, `_` and `.`, not starting with a dot
+fn valid_asset_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= MAX_ASSET_NAME_LEN
+        && !name.starts_with('.')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// Object storage key of a problem asset
+fn asset_object_key(problem_id: &str, name: &str) -> String {
+    format!("problems/{}/assets/{}", problem_id, name)
+}
+
+/// Content type for an upload without a Content-Type header
+fn asset_content_type(name: &str) -> &'static str {
+    match name.rsplit_once('.').map(|(_, ext)| ext.to_ascii_lowercase()).as_deref() {
+        Some("pdf") => "application/pdf",
+        Some("md") => "text/markdown; charset=utf-8",
+        Some("txt" | "in" | "out" | "ans") => "text/plain; charset=utf-8",
+        Some("json") => "application/json",
+        Some("png") => "image/png",
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("svg") => "image/svg+xml",
+        Some("zip") => "application/zip",
+        _ => "application/octet-stream",
+    }
+}
+
+/// 503 for asset and upload requests when OBJECT_STORE_URL is unset
+fn object_store_disabled() -> axum::response::Response {
+    asset_error(
+        StatusCode::SERVICE_UNAVAILABLE,
+        "OBJECT_STORE_DISABLED",
+        "Problem assets and uploads need object storage (OBJECT_STORE_URL)".to_string(),
+    )
+}
+
+/// 404 unless the problem exists
+async fn require_problem(state: &AppState, problem_id: &str) -> Result<(), axum::response::Response> {
+    match redis::get_problem(&mut state.redis.clone(), problem_id).await {
+        Ok(Some(_)) => Ok(()),
+        Ok(None) => Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: ErrorDetail {
+                    code: "PROBLEM_NOT_FOUND".to_string(),
+                    message: format!("Problem '{}' does not exist", problem_id),
+                    params: vec![problem_id.to_string()],
+                },
+            }),
+        ).into_response()),
+        Err(e) => {
+            error!(problem_id = %problem_id, error = %e, "Failed to load problem");
+            Err(asset_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "INTERNAL_ERROR",
+                format!("Failed to load problem: {}", e),
+            ))
+        }
+    }
+}
+
+/// PUT /admin/problems/{problem_id}/assets/{name} - Upload a statement or sample file
+///
+/// The request body is stored as-is in object storage, replacing an earlier
+/// asset of the same name; its Content-Type is served back (guessed from the
+/// file extension when absent).
+#[utoipa::path(
+    put,
+    path = "/admin/problems/{problem_id}/assets/{name}",
+    tag = "admin",
+    params(
+        ("problem_id" = String, Path, description = "Problem id"),
+        ("name" = String, Path, description = "Asset file name (letters, digits, '-', '_', '.')"),
+    ),
+    request_body(content = Vec<u8>, content_type = "application/octet-stream"),
+    responses(
+        (status = 201, description = "Asset stored", body = optimus_common::types::ProblemAsset),
+        (status = 400, description = "Invalid asset name or empty body", body = ErrorResponse),
+        (status = 404, description = "Unknown problem", body = ErrorResponse),
+        (status = 413, description = "Asset larger than 10 MB"),
+        (status = 500, description = "Storage error", body = ErrorResponse),
+        (status = 503, description = "Object storage not configured", body = ErrorResponse),
+    )
+)]
+pub async fn put_problem_asset(
+    State(state): State<Arc<AppState>>,
+    Path((problem_id, name)): Path<(String, String)>,
+    headers: HeaderMap,
+    body: axum::body::Bytes,
+) -> impl IntoResponse {
+    use optimus_common::types::ProblemAsset;
+    use sha2::{Digest, Sha256};
+
+    let Some(store) = state.object_store.as_deref() else {
+        return object_store_disabled();
+    };
+    if !valid_asset_name(&name) {
+        return asset_error(
+            StatusCode::BAD_REQUEST,
+            "INVALID_ASSET",
+            format!(
+                "Asset names are 1-{} letters, digits, '-', '_' or '.' and don't start with '.'",
+                MAX_ASSET_NAME_LEN
+            ),
+        );
+    }
+    if body.is_empty() {
+        return asset_error(StatusCode::BAD_REQUEST, "INVALID_ASSET", "Asset body is empty".to_string());
+    }
+    if let Err(response) = require_problem(&state, &problem_id).await {
+        return response;
+    }
+
+    let content_type = headers
+        .get(axum::http::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .filter(|ct| !ct.is_empty())
+        .unwrap_or_else(|| asset_content_type(&name))
+        .to_string();
+    let asset = ProblemAsset {
+        name,
+        content_type,
+        size: body.len() as u64,
+        sha256: format!("{:x}", Sha256::digest(&body)),
+        uploaded_at: chrono::Utc::now().to_rfc3339(),
+    };
+
+    // Bytes first: metadata never points at an object that isn't there
+    let key = asset_object_key(&problem_id, &asset.name);
+    if let Err(e) = store.put(&key, body.to_vec(), &asset.content_type).await {
+        error!(problem_id = %problem_id, asset = %asset.name, error = %e, "Failed to store problem asset");
+        return asset_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "INTERNAL_ERROR",
+            format!("Failed to store asset: {}", e),
+        );
+    }
+    if let Err(e) = redis::put_problem_asset(&mut state.redis.clone(), &problem_id, &asset).await {
+        error!(problem_id = %problem_id, asset = %asset.name, error = %e, "Failed to record problem asset");
+        return asset_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "INTERNAL_ERROR",
+            format!("Failed to record asset: {}", e),
+        );
+    }
+
+    info!(problem_id = %problem_id, asset = %asset.name, size = asset.size, "Problem asset stored");
+    (StatusCode::CREATED, Json(asset)).into_response()
+}
+
+/// GET /problems/{problem_id}/assets - Files attached to a problem
+#[utoipa::path(
+    get,
+    path = "/problems/{problem_id}/assets",
+    tag = "jobs",
+    params(("problem_id" = String, Path, description = "Problem id")),
+    responses(
+        (status = 200, description = "Assets ordered by name", body = Vec<optimus_common::types::ProblemAsset>),
+        (status = 404, description = "Unknown problem", body = ErrorResponse),
+        (status = 500, description = "Redis error", body = ErrorResponse),
+    )
+)]
+pub async fn list_problem_assets(
+    State(state): State<Arc<AppState>>,
+    Path(problem_id): Path<String>,
+) -> impl IntoResponse {
+    if let Err(response) = require_problem(&state, &problem_id).await {
+        return response;
+    }
+    match redis::list_problem_assets(&mut state.redis.clone(), &problem_id).await {
+        Ok(assets) => (StatusCode::OK, Json(assets)).into_response(),
+        Err(e) => {
+            error!(problem_id = %problem_id, error = %e, "Failed to list problem assets");
+            asset_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "INTERNAL_ERROR",
+                format!("Failed to list assets: {}", e),
+            )
+        }
+    }
+}
+
+/// GET /problems/{problem_id}/assets/{name} - Download a problem asset
+///
+/// Served with its stored Content-Type, an ETag (the content's SHA-256) and
+/// `Cache-Control: public, max-age=300`; `If-None-Match` with the current
+/// ETag answers 304 without a body.
+#[utoipa::path(
+    get,
+    path = "/problems/{problem_id}/assets/{name}",
+    tag = "jobs",
+    params(
+        ("problem_id" = String, Path, description = "Problem id"),
+        ("name" = String, Path, description = "Asset file name"),
+    ),
+    responses(
+        (status = 200, description = "Asset content", content_type = "application/octet-stream"),
+        (status = 304, description = "Client copy is current"),
+        (status = 404, description = "Unknown problem or asset", body = ErrorResponse),
+        (status = 500, description = "Storage error", body = ErrorResponse),
+        (status = 503, description = "Object storage not configured", body = ErrorResponse),
+    )
+)]
+pub async fn get_problem_asset(
+    State(state): State<Arc<AppState>>,
+    Path((problem_id, name)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    use axum::http::header;
+
+    let Some(store) = state.object_store.as_deref() else {
+        return object_store_disabled();
+    };
+    let not_found = || {
+        asset_error(
+            StatusCode::NOT_FOUND,
+            "ASSET_NOT_FOUND",
+            format!("Problem '{}' has no asset '{}'", problem_id, name),
+        )
+    };
+    let asset = match redis::get_problem_asset(&mut state.redis.clone(), &problem_id, &name).await {
+        Ok(Some(asset)) => asset,
+        Ok(None) => return not_found(),
+        Err(e) => {
+            error!(problem_id = %problem_id, asset = %name, error = %e, "Failed to load problem asset");
+            return asset_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "INTERNAL_ERROR",
+                format!("Failed to load asset: {}", e),
+            );
+        }
+    };
+
+    let etag = format!("\"{}\"", asset.sha256);
+    let cache_control = format!("public, max-age={}", ASSET_MAX_AGE_SECS);
+    let fresh = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|tags| tags.split(',').any(|tag| tag.trim() == etag || tag.trim() == "*"));
+    if fresh {
+        return (
+            StatusCode::NOT_MODIFIED,
+            [(header::ETAG, etag), (header::CACHE_CONTROL, cache_control)],
+        ).into_response();
+    }
+
+    match store.get(&asset_object_key(&problem_id, &asset.name)).await {
+        Ok(Some(body)) => (
+            StatusCode::OK,
+            [
+                (header::CONTENT_TYPE, asset.content_type),
+                (header::ETAG, etag),
+                (header::CACHE_CONTROL, cache_control),
+                (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+            ],
+            body,
+        ).into_response(),
+        Ok(None) => {
+            warn!(problem_id = %problem_id, asset = %asset.name, "Problem asset missing from object storage");
+            not_found()
+        }
+        Err(e) => {
+            error!(problem_id = %problem_id, asset = %asset.name, error = %e, "Failed to read problem asset");
+            asset_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "INTERNAL_ERROR",
+                format!("Failed to read asset: {}", e),
+            )
+        }
+    }
+}
+
+/// Largest chunked upload (a test suite as a JSON array of test cases)
+const MAX_UPLOAD_BYTES: u64 = 512 * 1024 * 1024;
+// Every accepted size is within reach of a valid suite (its inputs alone can exceed it)
+const _: () = assert!(MAX_UPLOAD_BYTES <= (MAX_TEST_CASES * MAX_STDIN_SIZE) as u64);
+/// Largest chunk a single `PATCH /uploads/{id}` may carry
+pub const MAX_UPLOAD_CHUNK_BYTES: usize = 8 * 1024 * 1024;
+/// Cap on chunks per upload (keeps the session record small)
+const MAX_UPLOAD_CHUNKS: usize = 4096;
+
+/// Object storage key of a stored upload chunk (chunks are content-addressed,
+/// so a losing concurrent write never overwrites the chunk that won)
+fn upload_chunk_key(upload_id: &Uuid, sha256: &str) -> String {
+    format!("uploads/{}/{}", upload_id, sha256)
+}
+
+/// Object storage key of a finalized test suite
+fn test_suite_object_key(upload_id: &Uuid) -> String {
+    format!("test-suites/{}.json", upload_id)
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateUploadRequest {
+    /// Size of the complete upload in bytes (at most 512 MiB)
+    pub total_bytes: u64,
+    /// Hex SHA-256 of the complete upload, verified on finalize
+    #[serde(default)]
+    pub sha256: Option<String>,
+}
+
+/// Load an upload session, enforcing ownership
+async fn load_upload_for_caller(
+    state: &AppState,
+    upload_id: &str,
+    caller: Option<&str>,
+) -> Result<optimus_common::types::UploadSession, axum::response::Response> {
+    let Ok(upload_uuid) = Uuid::parse_str(upload_id) else {
+        return Err(asset_error(
+            StatusCode::BAD_REQUEST,
+            "INVALID_UPLOAD_ID",
+            "Invalid upload ID format".to_string(),
+        ));
+    };
+    let session = match redis::get_upload(&mut state.redis.clone(), &upload_uuid).await {
+        Ok(Some(session)) => session,
+        Ok(None) => {
+            return Err(asset_error(
+                StatusCode::NOT_FOUND,
+                "UPLOAD_NOT_FOUND",
+                format!("Upload {} does not exist or has expired", upload_id),
+            ))
+        }
+        Err(e) => {
+            error!(upload_id = %upload_id, error = %e, "Failed to load upload");
+            return Err(asset_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "INTERNAL_ERROR",
+                format!("Failed to load upload: {}", e),
+            ));
+        }
+    };
+
+    if session.owner.is_some() && session.owner.as_deref() != caller {
+        return Err(asset_error(
+            StatusCode::FORBIDDEN,
+            "UPLOAD_FORBIDDEN",
+            "Upload belongs to a different caller".to_string(),
+        ));
+    }
+    Ok(session)
+}
+
+/// The test suite of a finalized upload, for problems and batches that reference it
+async fn finalized_test_suite(
+    state: &AppState,
+    upload_id: &Uuid,
+    caller: Option<&str>,
+) -> Result<optimus_common::types::TestSuiteRef, axum::response::Response> {
+    let session = load_upload_for_caller(state, &upload_id.to_string(), caller).await?;
+    session.test_suite.ok_or_else(|| {
+        asset_error(
+            StatusCode::CONFLICT,
+            "UPLOAD_NOT_FINALIZED",
+            format!("Upload {} must be finalized before it can be used as a test suite", upload_id),
+        )
+    })
+}
+
+/// Read a finalized test suite's test cases from object storage
+async fn load_test_suite(
+    state: &AppState,
+    suite: &optimus_common::types::TestSuiteRef,
+) -> Result<Vec<TestCaseInput>, axum::response::Response> {
+    let Some(store) = state.object_store.as_deref() else {
+        return Err(object_store_disabled());
+    };
+    let unavailable = |reason: String| {
+        error!(upload_id = %suite.upload_id, reason = %reason, "Test suite unavailable");
+        asset_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "TEST_SUITE_UNAVAILABLE",
+            format!("Test suite {} could not be loaded: {}", suite.upload_id, reason),
+        )
+    };
+    match store.get(&test_suite_object_key(&suite.upload_id)).await {
+        Ok(Some(body)) => serde_json::from_slice(&body).map_err(|e| unavailable(e.to_string())),
+        Ok(None) => Err(unavailable("missing from object storage".to_string())),
+        Err(e) => Err(unavailable(e)),
+    }
+}
+
+/// POST /uploads - Start a chunked upload of a large test suite
+///
+/// For test suites above the request body limit: send the JSON array of
+/// test cases in chunks with `PATCH /uploads/{id}`, then finalize it and pass
+/// the upload id as `test_suite` when creating a problem or batch. Sessions
+/// expire 24 hours after their last change.
+#[utoipa::path(
+    post,
+    path = "/uploads",
+    tag = "uploads",
+    request_body = CreateUploadRequest,
+    responses(
+        (status = 201, description = "Upload started", body = optimus_common::types::UploadSession),
+        (status = 400, description = "Invalid size or checksum", body = ErrorResponse),
+        (status = 500, description = "Redis error", body = ErrorResponse),
+        (status = 503, description = "Object storage not configured", body = ErrorResponse),
+    )
+)]
+pub async fn create_upload(
+    State(state): State<Arc<AppState>>,
+    auth: Option<Extension<AuthContext>>,
+    Json(payload): Json<CreateUploadRequest>,
+) -> impl IntoResponse {
+    use optimus_common::types::UploadSession;
+
+    if state.object_store.is_none() {
+        return object_store_disabled();
+    }
+    if payload.total_bytes == 0 || payload.total_bytes > MAX_UPLOAD_BYTES {
+        return asset_error(
+            StatusCode::BAD_REQUEST,
+            "INVALID_UPLOAD",
+            format!("total_bytes must be between 1 and {}", MAX_UPLOAD_BYTES),
+        );
+    }
+    let sha256 = payload.sha256.map(|digest| digest.to_ascii_lowercase());
+    if sha256.as_ref().is_some_and(|digest| digest.len() != 64 || !digest.chars().all(|c| c.is_ascii_hexdigit())) {
+        return asset_error(
+            StatusCode::BAD_REQUEST,
+            "INVALID_UPLOAD",
+            "sha256 must be 64 hex characters".to_string(),
+        );
+    }
+
+    let session = UploadSession {
+        id: Uuid::new_v4(),
+        total_bytes: payload.total_bytes,
+        received_bytes: 0,
+        chunks: Vec::new(),
+        sha256,
+        owner: auth.map(|Extension(ctx)| ctx.subject),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        test_suite: None,
+    };
+    if let Err(e) = redis::create_upload(&mut state.redis.clone(), &session).await {
+        error!(error = %e, "Failed to create upload");
+        return asset_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "INTERNAL_ERROR",
+            format!("Failed to create upload: {}", e),
+        );
+    }
+
+    info!(upload_id = %session.id, total_bytes = session.total_bytes, "Upload started");
+    (StatusCode::CREATED, Json(session)).into_response()
+}
+
+/// GET /uploads/{id} - Upload progress (`received_bytes` is where to resume)
+#[utoipa::path(
+    get,
+    path = "/uploads/{upload_id}",
+    tag = "uploads",
+    params(("upload_id" = Uuid, Path, description = "Upload id")),
+    responses(
+        (status = 200, description = "Upload session", body = optimus_common::types::UploadSession),
+        (status = 400, description = "Invalid upload id", body = ErrorResponse),
+        (status = 403, description = "Upload owned by another caller", body = ErrorResponse),
+        (status = 404, description = "Upload not found or expired", body = ErrorResponse),
+    )
+)]
+pub async fn get_upload(
+    State(state): State<Arc<AppState>>,
+    auth: Option<Extension<AuthContext>>,
+    Path(upload_id): Path<String>,
+) -> impl IntoResponse {
+    let caller = auth.as_ref().map(|Extension(ctx)| ctx.subject.as_str());
+    match load_upload_for_caller(&state, &upload_id, caller).await {
+        Ok(session) => (StatusCode::OK, Json(session)).into_response(),
+        Err(response) => response,
+    }
+}
+
+/// PATCH /uploads/{id} - Append a chunk
+///
+/// The `Upload-Offset` header must equal the session's `received_bytes`;
+/// anything else (a retried or concurrent chunk) answers 409 and leaves the
+/// session unchanged, so clients resume from `GET /uploads/{id}`. Chunks are
+/// at most 8 MB.
+#[utoipa::path(
+    patch,
+    path = "/uploads/{upload_id}",
+    tag = "uploads",
+    params(
+        ("upload_id" = Uuid, Path, description = "Upload id"),
+        ("Upload-Offset" = u64, Header, description = "Byte offset of this chunk in the upload"),
+    ),
+    request_body(content = Vec<u8>, content_type = "application/octet-stream"),
+    responses(
+        (status = 200, description = "Chunk stored", body = optimus_common::types::UploadSession),
+        (status = 400, description = "Invalid upload id or offset, empty chunk or chunk past total_bytes", body = ErrorResponse),
+        (status = 403, description = "Upload owned by another caller", body = ErrorResponse),
+        (status = 404, description = "Upload not found or expired", body = ErrorResponse),
+        (status = 409, description = "Offset mismatch or upload already finalized", body = ErrorResponse),
+        (status = 413, description = "Chunk larger than 8 MB"),
+        (status = 500, description = "Storage error", body = ErrorResponse),
+        (status = 503, description = "Object storage not configured", body = ErrorResponse),
+    )
+)]
+pub async fn append_upload_chunk(
+    State(state): State<Arc<AppState>>,
+    auth: Option<Extension<AuthContext>>,
+    Path(upload_id): Path<String>,
+    headers: HeaderMap,
+    body: axum::body::Bytes,
+) -> impl IntoResponse {
+    use sha2::{Digest, Sha256};
+
+    let Some(store) = state.object_store.as_deref() else {
+        return object_store_disabled();
+    };
+    let Some(offset) = headers
+        .get("upload-offset")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<u64>().ok())
+    else {
+        return asset_error(
+            StatusCode::BAD_REQUEST,
+            "INVALID_UPLOAD_OFFSET",
+            "Upload-Offset header must be a byte offset".to_string(),
+        );
+    };
+    if body.is_empty() {
+        return asset_error(StatusCode::BAD_REQUEST, "INVALID_UPLOAD", "Chunk is empty".to_string());
+    }
+
+    let caller = auth.as_ref().map(|Extension(ctx)| ctx.subject.as_str());
+    let session = match load_upload_for_caller(&state, &upload_id, caller).await {
+        Ok(session) => session,
+        Err(response) => return response,
+    };
+    if session.test_suite.is_some() {
+        return asset_error(
+            StatusCode::CONFLICT,
+            "UPLOAD_FINALIZED",
+            format!("Upload {} is already finalized", upload_id),
+        );
+    }
+    let offset_mismatch = || {
+        asset_error(
+            StatusCode::CONFLICT,
+            "UPLOAD_OFFSET_MISMATCH",
+            format!("Upload {} expects the chunk at offset {}", upload_id, session.received_bytes),
+        )
+    };
+    if offset != session.received_bytes {
+        return offset_mismatch();
+    }
+    let end = offset + body.len() as u64;
+    if end > session.total_bytes {
+        return asset_error(
+            StatusCode::BAD_REQUEST,
+            "INVALID_UPLOAD",
+            format!("Chunk ends at byte {}, past total_bytes ({})", end, session.total_bytes),
+        );
+    }
+    if session.chunks.len() >= MAX_UPLOAD_CHUNKS {
+        return asset_error(
+            StatusCode::BAD_REQUEST,
+            "INVALID_UPLOAD",
+            format!("Uploads are limited to {} chunks; send larger chunks", MAX_UPLOAD_CHUNKS),
+        );
+    }
+
+    // Bytes first: the session never lists a chunk that isn't stored
+    let digest = format!("{:x}", Sha256::digest(&body));
+    if let Err(e) = store
+        .put(&upload_chunk_key(&session.id, &digest), body.to_vec(), "application/octet-stream")
+        .await
+    {
+        error!(upload_id = %upload_id, offset = offset, error = %e, "Failed to store upload chunk");
+        return asset_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "INTERNAL_ERROR",
+            format!("Failed to store chunk: {}", e),
+        );
+    }
+
+    let mut next = session.clone();
+    next.received_bytes = end;
+    next.chunks.push(digest);
+    match redis::replace_upload(&mut state.redis.clone(), &session, &next).await {
+        Ok(true) => (StatusCode::OK, Json(next)).into_response(),
+        Ok(false) => offset_mismatch(),
+        Err(e) => {
+            error!(upload_id = %upload_id, error = %e, "Failed to record upload chunk");
+            asset_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "INTERNAL_ERROR",
+                format!("Failed to record chunk: {}", e),
+            )
+        }
+    }
+}
+
+/// POST /uploads/{id}/finalize - Assemble and check a complete upload
+///
+/// The chunks must add up to `total_bytes`, match the declared `sha256` (if
+/// any) and form a JSON array of 1-100 test cases in the `/execute` shape.
+/// The response's `test_suite` is what problems and batches reference.
+/// Finalizing again returns the same session.
+#[utoipa::path(
+    post,
+    path = "/uploads/{upload_id}/finalize",
+    tag = "uploads",
+    params(("upload_id" = Uuid, Path, description = "Upload id")),
+    responses(
+        (status = 200, description = "Upload finalized", body = optimus_common::types::UploadSession),
+        (status = 400, description = "Invalid upload id", body = ErrorResponse),
+        (status = 403, description = "Upload owned by another caller", body = ErrorResponse),
+        (status = 404, description = "Upload not found or expired", body = ErrorResponse),
+        (status = 409, description = "Upload incomplete, or finalized concurrently", body = ErrorResponse),
+        (status = 422, description = "Checksum mismatch or not a valid test suite", body = ErrorResponse),
+        (status = 500, description = "Storage error", body = ErrorResponse),
+        (status = 503, description = "Object storage not configured", body = ErrorResponse),
+    )
+)]
+pub async fn finalize_upload(
+    State(state): State<Arc<AppState>>,
+    auth: Option<Extension<AuthContext>>,
+    Path(upload_id): Path<String>,
+) -> impl IntoResponse {
+    use optimus_common::types::TestSuiteRef;
+    use sha2::{Digest, Sha256};
+
+    let Some(store) = state.object_store.as_deref() else {
+        return object_store_disabled();
+    };
+    let caller = auth.as_ref().map(|Extension(ctx)| ctx.subject.as_str());
+    let session = match load_upload_for_caller(&state, &upload_id, caller).await {
+        Ok(session) => session,
+        Err(response) => return response,
+    };
+    if session.test_suite.is_some() {
+        return (StatusCode::OK, Json(session)).into_response();
+    }
+    if session.received_bytes != session.total_bytes {
+        return asset_error(
+            StatusCode::CONFLICT,
+            "UPLOAD_INCOMPLETE",
+            format!(
+                "Upload {} has {} of {} bytes",
+                upload_id, session.received_bytes, session.total_bytes
+            ),
+        );
+    }
+
+    let storage_error = |message: String| {
+        error!(upload_id = %upload_id, error = %message, "Failed to finalize upload");
+        asset_error(StatusCode::INTERNAL_SERVER_ERROR, "INTERNAL_ERROR", message)
+    };
+    let mut assembled = Vec::with_capacity(session.total_bytes as usize);
+    for digest in &session.chunks {
+        match store.get(&upload_chunk_key(&session.id, digest)).await {
+            Ok(Some(chunk)) => assembled.extend_from_slice(&chunk),
+            Ok(None) => return storage_error(format!("Chunk {} is missing from object storage", digest)),
+            Err(e) => return storage_error(format!("Failed to read chunk {}: {}", digest, e)),
+        }
+    }
+
+    let sha256 = format!("{:x}", Sha256::digest(&assembled));
+    if session.sha256.as_ref().is_some_and(|declared| *declared != sha256) {
+        return asset_error(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "UPLOAD_CHECKSUM_MISMATCH",
+            format!("Upload {} has SHA-256 {}, not the declared checksum", upload_id, sha256),
+        );
+    }
+    let test_cases = match serde_json::from_slice::<Vec<TestCaseInput>>(&assembled) {
+        Ok(test_cases) if (1..=MAX_TEST_CASES).contains(&test_cases.len()) => {
+            // Same per-test limits as /execute, so every job using the suite is accepted
+            if let Err(rejection) = validate_test_cases(&test_cases, None) {
+                return asset_error(StatusCode::UNPROCESSABLE_ENTITY, "INVALID_TEST_SUITE", rejection.message);
+            }
+            test_cases.len()
+        }
+        Ok(test_cases) => {
+            return asset_error(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "INVALID_TEST_SUITE",
+                format!("A test suite holds 1-{} test cases, got {}", MAX_TEST_CASES, test_cases.len()),
+            )
+        }
+        Err(e) => {
+            return asset_error(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "INVALID_TEST_SUITE",
+                format!("Upload is not a JSON array of test cases: {}", e),
+            )
+        }
+    };
+
+    if let Err(e) = store
+        .put(&test_suite_object_key(&session.id), assembled, "application/json")
+        .await
+    {
+        return storage_error(format!("Failed to store test suite: {}", e));
+    }
+    let mut next = session.clone();
+    next.test_suite = Some(TestSuiteRef {
+        upload_id: session.id,
+        test_cases: test_cases as u32,
+        size: session.total_bytes,
+        sha256,
+    });
+    match redis::replace_upload(&mut state.redis.clone(), &session, &next).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return asset_error(
+                StatusCode::CONFLICT,
+                "UPLOAD_CHANGED",
+                format!("Upload {} changed while finalizing; fetch it and retry", upload_id),
+            )
+        }
+        Err(e) => return storage_error(format!("Failed to record finalized upload: {}", e)),
+    }
+
+    // The assembled suite replaces the chunks; leftovers only cost storage
+    for digest in &next.chunks {
+        if let Err(e) = store.delete(&upload_chunk_key(&session.id, digest)).await {
+            warn!(upload_id = %upload_id, chunk = %digest, error = %e, "Failed to delete upload chunk");
+        }
+    }
+
+    info!(upload_id = %upload_id, size = session.total_bytes, test_cases = test_cases, "Upload finalized");
+    (StatusCode::OK, Json(next)).into_response()
+}
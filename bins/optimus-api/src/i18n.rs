@@ -0,0 +1,314 @@
+// Localized error messages
+// Rewrites `error.message` on JSON error responses for the caller's Accept-Language, filling in `error.params`; codes never change
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::Request,
+    http::{header, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use tracing::warn;
+
+/// Error bodies are tiny; anything larger is passed through untouched
+const MAX_ERROR_BODY_BYTES: usize = 64 * 1024;
+
+/// Locales with a message catalogue (English is the untranslated default)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Locale {
+    Es,
+    Fr,
+    Pt,
+}
+
+impl Locale {
+    /// Value for the Content-Language header
+    pub fn tag(&self) -> &'static str {
+        match self {
+            Locale::Es => "es",
+            Locale::Fr => "fr",
+            Locale::Pt => "pt",
+        }
+    }
+
+    fn from_primary_subtag(subtag: &str) -> Option<Self> {
+        match subtag {
+            "es" => Some(Locale::Es),
+            "fr" => Some(Locale::Fr),
+            "pt" => Some(Locale::Pt),
+            _ => None,
+        }
+    }
+}
+
+/// Pick the catalogue for an Accept-Language header (RFC 9110 q-values)
+/// Returns None when English (or a wildcard) ranks above every supported locale
+pub fn negotiate(accept_language: &str) -> Option<Locale> {
+    let mut ranges: Vec<(&str, f32)> = accept_language
+        .split(',')
+        .filter_map(|item| {
+            let mut parts = item.split(';').map(str::trim);
+            let range = parts.next().filter(|r| !r.is_empty())?;
+            let q = parts
+                .find_map(|p| p.strip_prefix("q="))
+                .map_or(Some(1.0), |q| q.parse::<f32>().ok())?;
+            Some((range, q))
+        })
+        .filter(|&(_, q)| q > 0.0)
+        .collect();
+    // Stable: equal weights keep the client's order
+    ranges.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    for (range, _) in ranges {
+        let primary = range.split('-').next().unwrap_or_default().to_ascii_lowercase();
+        if primary == "en" || primary == "*" {
+            return None;
+        }
+        if let Some(locale) = Locale::from_primary_subtag(&primary) {
+            return Some(locale);
+        }
+    }
+    None
+}
+
+/// Fill `{0}`, `{1}`, ... from `params`; None if the template wants more than the error carries
+fn render(template: &str, params: &[&str]) -> Option<String> {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+        let close = open + rest[open..].find('}')?;
+        let index: usize = rest[open + 1..close].parse().ok()?;
+        out.push_str(params.get(index)?);
+        rest = &rest[close + 1..];
+    }
+    out.push_str(rest);
+    Some(out)
+}
+
+/// Catalogue template for a code; placeholders index into the error's `params`
+fn template(locale: Locale, code: &str) -> Option<&'static str> {
+    let template = match (locale, code) {
+        (Locale::Es, "LANGUAGE_NOT_SUPPORTED") => "El lenguaje '{0}' no está habilitado o no es compatible",
+        (Locale::Fr, "LANGUAGE_NOT_SUPPORTED") => "Le langage '{0}' n'est pas activé ou pas pris en charge",
+        (Locale::Pt, "LANGUAGE_NOT_SUPPORTED") => "A linguagem '{0}' não está habilitada ou não é suportada",
+
+        (Locale::Es, "NO_WORKERS") => "No hay workers activos para '{0}'",
+        (Locale::Fr, "NO_WORKERS") => "Aucun worker actif pour '{0}'",
+        (Locale::Pt, "NO_WORKERS") => "Não há workers ativos para '{0}'",
+
+        (Locale::Es, "NO_TEST_CASES") => "Se requiere al menos un caso de prueba",
+        (Locale::Fr, "NO_TEST_CASES") => "Au moins un cas de test est requis",
+        (Locale::Pt, "NO_TEST_CASES") => "É necessário pelo menos um caso de teste",
+
+        (Locale::Es, "TOO_MANY_TEST_CASES") => "Se permiten como máximo {0} casos de prueba, se recibieron {1}",
+        (Locale::Fr, "TOO_MANY_TEST_CASES") => "{0} cas de test au maximum, {1} reçus",
+        (Locale::Pt, "TOO_MANY_TEST_CASES") => "São permitidos no máximo {0} casos de teste, recebidos {1}",
+
+        (Locale::Es, "SOURCE_CODE_TOO_LARGE" | "ARCHIVE_TOO_LARGE") => "Se permiten como máximo {0} bytes, se recibieron {1} bytes",
+        (Locale::Fr, "SOURCE_CODE_TOO_LARGE" | "ARCHIVE_TOO_LARGE") => "{0} octets au maximum, {1} octets reçus",
+        (Locale::Pt, "SOURCE_CODE_TOO_LARGE" | "ARCHIVE_TOO_LARGE") => "São permitidos no máximo {0} bytes, recebidos {1} bytes",
+
+        (Locale::Es, "PAYLOAD_TOO_LARGE") => "El código fuente está limitado a {0} bytes y la entrada a {1} bytes",
+        (Locale::Fr, "PAYLOAD_TOO_LARGE") => "Le code source est limité à {0} octets et l'entrée à {1} octets",
+        (Locale::Pt, "PAYLOAD_TOO_LARGE") => "O código-fonte é limitado a {0} bytes e a entrada a {1} bytes",
+
+        (Locale::Es, "EMPTY_SOURCE_CODE") => "El código fuente no puede estar vacío",
+        (Locale::Fr, "EMPTY_SOURCE_CODE") => "Le code source ne peut pas être vide",
+        (Locale::Pt, "EMPTY_SOURCE_CODE") => "O código-fonte não pode estar vazio",
+
+        (Locale::Es, "TEST_CASE_INPUT_TOO_LARGE") => "La entrada del caso de prueba {0} supera {1} bytes",
+        (Locale::Fr, "TEST_CASE_INPUT_TOO_LARGE") => "L'entrée du cas de test {0} dépasse {1} octets",
+        (Locale::Pt, "TEST_CASE_INPUT_TOO_LARGE") => "A entrada do caso de teste {0} excede {1} bytes",
+
+        (Locale::Es, "TEST_CASE_OUTPUT_TOO_LARGE") => "La salida esperada del caso de prueba {0} supera {1} bytes",
+        (Locale::Fr, "TEST_CASE_OUTPUT_TOO_LARGE") => "La sortie attendue du cas de test {0} dépasse {1} octets",
+        (Locale::Pt, "TEST_CASE_OUTPUT_TOO_LARGE") => "A saída esperada do caso de teste {0} excede {1} bytes",
+
+        (Locale::Es, "MISSING_EXPECTED_OUTPUT") => "El caso de prueba {0} debe definir expected_output o expected_outputs",
+        (Locale::Fr, "MISSING_EXPECTED_OUTPUT") => "Le cas de test {0} doit définir expected_output ou expected_outputs",
+        (Locale::Pt, "MISSING_EXPECTED_OUTPUT") => "O caso de teste {0} deve definir expected_output ou expected_outputs",
+
+        (Locale::Es, "TOO_MANY_EXPECTED_OUTPUTS") => "El caso de prueba {0} tiene más de {1} expected_outputs",
+        (Locale::Fr, "TOO_MANY_EXPECTED_OUTPUTS") => "Le cas de test {0} a plus de {1} expected_outputs",
+        (Locale::Pt, "TOO_MANY_EXPECTED_OUTPUTS") => "O caso de teste {0} tem mais de {1} expected_outputs",
+
+        (Locale::Es, "TOO_MANY_FIXTURES") => "El caso de prueba {0} tiene {1} archivos auxiliares, el máximo es {2}",
+        (Locale::Fr, "TOO_MANY_FIXTURES") => "Le cas de test {0} a {1} fichiers annexes, le maximum est {2}",
+        (Locale::Pt, "TOO_MANY_FIXTURES") => "O caso de teste {0} tem {1} arquivos auxiliares, o máximo é {2}",
+
+        (Locale::Es, "FIXTURES_TOO_LARGE") => "Los archivos auxiliares del caso de prueba {0} superan {1} bytes",
+        (Locale::Fr, "FIXTURES_TOO_LARGE") => "Les fichiers annexes du cas de test {0} dépassent {1} octets",
+        (Locale::Pt, "FIXTURES_TOO_LARGE") => "Os arquivos auxiliares do caso de teste {0} excedem {1} bytes",
+
+        (Locale::Es, "INVALID_FIXTURE") => "El caso de prueba {0} tiene un archivo auxiliar no válido",
+        (Locale::Fr, "INVALID_FIXTURE") => "Le cas de test {0} contient un fichier annexe invalide",
+        (Locale::Pt, "INVALID_FIXTURE") => "O caso de teste {0} tem um arquivo auxiliar inválido",
+
+        (Locale::Es, "INVALID_COMPARISON") => "El caso de prueba {0} tiene un modo de comparación no válido",
+        (Locale::Fr, "INVALID_COMPARISON") => "Le cas de test {0} a un mode de comparaison invalide",
+        (Locale::Pt, "INVALID_COMPARISON") => "O caso de teste {0} tem um modo de comparação inválido",
+
+        (Locale::Es, "INVALID_EXPECTED_REGEX") => "El caso de prueba {0} tiene una expresión regular esperada no válida",
+        (Locale::Fr, "INVALID_EXPECTED_REGEX") => "Le cas de test {0} a une expression régulière attendue invalide",
+        (Locale::Pt, "INVALID_EXPECTED_REGEX") => "O caso de teste {0} tem uma expressão regular esperada inválida",
+
+        (Locale::Es, "INVALID_ARGS_ENV") => "El caso de prueba {0} tiene argumentos o variables de entorno no válidos",
+        (Locale::Fr, "INVALID_ARGS_ENV") => "Le cas de test {0} a des arguments ou variables d'environnement invalides",
+        (Locale::Pt, "INVALID_ARGS_ENV") => "O caso de teste {0} tem argumentos ou variáveis de ambiente inválidos",
+
+        (Locale::Es, "INVALID_TIMEOUT") => "El tiempo límite debe estar entre {0}ms y {1}ms",
+        (Locale::Fr, "INVALID_TIMEOUT") => "Le délai doit être compris entre {0}ms et {1}ms",
+        (Locale::Pt, "INVALID_TIMEOUT") => "O tempo limite deve estar entre {0}ms e {1}ms",
+
+        (Locale::Es, "INVALID_TIME_SCORING") => "La configuración de puntuación por tiempo no es válida",
+        (Locale::Fr, "INVALID_TIME_SCORING") => "La configuration de notation au temps est invalide",
+        (Locale::Pt, "INVALID_TIME_SCORING") => "A configuração de pontuação por tempo é inválida",
+
+        (Locale::Es, "INVALID_RESOURCES") => "memory_limit_mb debe ser al menos {0}, cpu_limit al menos {1} y max_parallel_tests al menos {2}",
+        (Locale::Fr, "INVALID_RESOURCES") => "memory_limit_mb doit valoir au moins {0}, cpu_limit au moins {1} et max_parallel_tests au moins {2}",
+        (Locale::Pt, "INVALID_RESOURCES") => "memory_limit_mb deve ser pelo menos {0}, cpu_limit pelo menos {1} e max_parallel_tests pelo menos {2}",
+
+        (Locale::Es, "INVALID_ARCHIVE") => "El archivo del proyecto no es válido",
+        (Locale::Fr, "INVALID_ARCHIVE") => "L'archive du projet est invalide",
+        (Locale::Pt, "INVALID_ARCHIVE") => "O arquivo do projeto é inválido",
+
+        (Locale::Es, "PROBLEM_NOT_FOUND") => "El problema '{0}' no existe",
+        (Locale::Fr, "PROBLEM_NOT_FOUND") => "Le problème '{0}' n'existe pas",
+        (Locale::Pt, "PROBLEM_NOT_FOUND") => "O problema '{0}' não existe",
+
+        (Locale::Es, "RATE_LIMITED") => "Límite de {0} envíos por minuto superado, reintente en {1}s",
+        (Locale::Fr, "RATE_LIMITED") => "Limite de {0} soumissions par minute dépassée, réessayez dans {1}s",
+        (Locale::Pt, "RATE_LIMITED") => "Limite de {0} envios por minuto excedido, tente novamente em {1}s",
+
+        (Locale::Es, "INVALID_JOB_ID") => "Formato de ID de trabajo no válido",
+        (Locale::Fr, "INVALID_JOB_ID") => "Format d'identifiant de tâche invalide",
+        (Locale::Pt, "INVALID_JOB_ID") => "Formato de ID de tarefa inválido",
+
+        (Locale::Es, "INTERNAL_ERROR") => "Error interno del servidor, inténtelo de nuevo más tarde",
+        (Locale::Fr, "INTERNAL_ERROR") => "Erreur interne du serveur, réessayez plus tard",
+        (Locale::Pt, "INTERNAL_ERROR") => "Erro interno do servidor, tente novamente mais tarde",
+
+        _ => return None,
+    };
+    Some(template)
+}
+
+/// Localized message for an error, or None to keep the English one
+pub fn translate(locale: Locale, code: &str, params: &[&str]) -> Option<String> {
+    render(template(locale, code)?, params)
+}
+
+/// Middleware: localize `error.message` of JSON error responses per Accept-Language
+/// Must be the outermost layer so errors from other middleware are covered too
+pub async fn localize(request: Request, next: Next) -> Response {
+    let locale = request
+        .headers()
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(negotiate);
+
+    let mut response = next.run(request).await;
+
+    let status = response.status();
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with("application/json"));
+    if !(status.is_client_error() || status.is_server_error()) || !is_json {
+        return response;
+    }
+
+    // The message depends on the header even when it wasn't translated this time
+    response.headers_mut().append(header::VARY, HeaderValue::from_static("accept-language"));
+
+    let Some(locale) = locale else {
+        return response;
+    };
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, MAX_ERROR_BODY_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            // The body is gone at this point; the status and code headers still reach the client
+            warn!(error = %e, "Failed to buffer error response for localization");
+            return Response::from_parts(parts, Body::empty());
+        }
+    };
+
+    let Ok(mut json) = serde_json::from_slice::<serde_json::Value>(&bytes) else {
+        return Response::from_parts(parts, Body::from(bytes));
+    };
+    let localized = {
+        let error = &json["error"];
+        let params: Vec<&str> = error["params"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|param| param.as_str())
+            .collect();
+        error["code"].as_str().and_then(|code| translate(locale, code, &params))
+    };
+    let Some(localized) = localized else {
+        return Response::from_parts(parts, Body::from(bytes));
+    };
+
+    json["error"]["message"] = serde_json::Value::String(localized);
+    parts.headers.remove(header::CONTENT_LENGTH);
+    parts
+        .headers
+        .insert(header::CONTENT_LANGUAGE, HeaderValue::from_static(locale.tag()));
+    Response::from_parts(parts, Body::from(json.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiate_respects_quality() {
+        assert_eq!(negotiate("es-MX,es;q=0.9,en;q=0.8"), Some(Locale::Es));
+        assert_eq!(negotiate("en-US,fr;q=0.5"), None);
+        assert_eq!(negotiate("de, fr-CA;q=0.7, en;q=0.3"), Some(Locale::Fr));
+        assert_eq!(negotiate("en;q=0.2, pt-BR"), Some(Locale::Pt));
+        assert_eq!(negotiate("es;q=0, fr;q=0.1"), Some(Locale::Fr));
+        assert_eq!(negotiate("*"), None);
+        assert_eq!(negotiate(""), None);
+        assert_eq!(negotiate("de"), None);
+    }
+
+    #[test]
+    fn test_translate_renders_params() {
+        assert_eq!(
+            translate(Locale::Es, "TOO_MANY_FIXTURES", &["3", "20", "16"]).as_deref(),
+            Some("El caso de prueba 3 tiene 20 archivos auxiliares, el máximo es 16")
+        );
+        assert_eq!(
+            translate(Locale::Fr, "LANGUAGE_NOT_SUPPORTED", &["cobol"]).as_deref(),
+            Some("Le langage 'cobol' n'est pas activé ou pas pris en charge")
+        );
+        assert_eq!(
+            translate(Locale::Pt, "INVALID_RESOURCES", &["16", "0.1", "1"]).as_deref(),
+            Some("memory_limit_mb deve ser pelo menos 16, cpu_limit pelo menos 0.1 e max_parallel_tests pelo menos 1")
+        );
+        assert_eq!(
+            translate(Locale::Fr, "TEST_CASE_INPUT_TOO_LARGE", &["1", "10485760"]).as_deref(),
+            Some("L'entrée du cas de test 1 dépasse 10485760 octets")
+        );
+        // Names with quotes or digits are taken whole
+        assert_eq!(
+            translate(Locale::Es, "PROBLEM_NOT_FOUND", &["it's-2024"]).as_deref(),
+            Some("El problema 'it's-2024' no existe")
+        );
+        assert_eq!(translate(Locale::Pt, "NO_TEST_CASES", &[]).as_deref(), Some("É necessário pelo menos um caso de teste"));
+    }
+
+    #[test]
+    fn test_translate_falls_back_to_english() {
+        // No catalogue entry
+        assert_eq!(translate(Locale::Es, "INVALID_TOKEN", &[]), None);
+        // Error without the params the template needs
+        assert_eq!(translate(Locale::Es, "TOO_MANY_TEST_CASES", &["500"]), None);
+    }
+}
@@ -1,9 +1,9 @@
 // Language configuration management
 // Loads and validates languages from languages.json
 
-use optimus_common::types::Language;
+use optimus_common::types::{Comparison, Language, Placement};
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::Path;
 
@@ -17,6 +17,52 @@ pub struct LanguageConfig {
     pub queue_name: String,
     pub memory_limit_mb: u32,
     pub cpu_limit: f64,
+    /// Largest per-job `memory_limit_mb` a submission may ask for (default: `memory_limit_mb`)
+    #[serde(default)]
+    pub max_memory_limit_mb: Option<u32>,
+    /// Largest per-job `cpu_limit` a submission may ask for (default: `cpu_limit`)
+    #[serde(default)]
+    pub max_cpu_limit: Option<f64>,
+    /// Placement-constrained worker pools jobs may ask for
+    #[serde(default)]
+    pub placements: Vec<Placement>,
+    /// GPU device settings (worker-side; only their presence matters here)
+    #[serde(default)]
+    pub gpu: Option<serde_json::Value>,
+    /// Worker parallelism; `max_parallel_tests` caps per-job requests
+    #[serde(default)]
+    pub concurrency: Option<ConcurrencyConfig>,
+    /// Grading defaults for submissions that don't choose their own
+    #[serde(default)]
+    pub evaluation: EvaluationDefaults,
+}
+
+/// Course-wide grading conventions of one language (e.g. float tolerance
+/// for Python, strict for Java); each applies only where the submission
+/// leaves the setting unset, and a problem's `comparison` still wins
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct EvaluationDefaults {
+    #[serde(default)]
+    pub comparison: Option<Comparison>,
+    #[serde(default)]
+    pub ignore_case: bool,
+    #[serde(default)]
+    pub accept_presentation_errors: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConcurrencyConfig {
+    #[serde(default)]
+    pub max_parallel_tests: Option<u32>,
+}
+
+/// Upper bounds for per-job resource overrides of one language
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResourceCeiling {
+    pub memory_limit_mb: u32,
+    pub cpu_limit: f64,
+    /// None when the language sets no `concurrency.max_parallel_tests`
+    pub max_parallel_tests: Option<u32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,6 +82,11 @@ struct LanguagesFile {
 #[derive(Debug, Clone)]
 pub struct LanguageRegistry {
     enabled_languages: HashSet<Language>,
+    ceilings: HashMap<Language, ResourceCeiling>,
+    versions: HashMap<Language, String>,
+    pools: HashMap<Language, HashSet<String>>,
+    gpu_languages: HashSet<Language>,
+    evaluation: HashMap<Language, EvaluationDefaults>,
 }
 
 impl LanguageRegistry {
@@ -44,15 +95,51 @@ impl LanguageRegistry {
         let content = fs::read_to_string(path.as_ref())
             .map_err(|e| format!("Failed to read languages.json: {}", e))?;
         
-        let config: LanguagesFile = serde_json::from_str(&content)
+        Self::from_json(&content)
+    }
+    
+    /// Build the registry from languages.json content
+    pub fn from_json(content: &str) -> Result<Self, String> {
+        let config: LanguagesFile = serde_json::from_str(content)
             .map_err(|e| format!("Failed to parse languages.json: {}", e))?;
         
         let mut enabled_languages = HashSet::new();
+        let mut ceilings = HashMap::new();
+        let mut versions = HashMap::new();
+        let mut pools = HashMap::new();
+        let mut gpu_languages = HashSet::new();
+        let mut evaluation = HashMap::new();
         
         for lang_config in &config.languages {
             match Language::from_str(&lang_config.name) {
                 Some(lang) => {
                     enabled_languages.insert(lang);
+                    versions.insert(lang, lang_config.version.clone());
+                    for placement in &lang_config.placements {
+                        placement.validate().map_err(|e| {
+                            format!("Invalid placement for '{}' in languages.json: {}", lang_config.name, e)
+                        })?;
+                    }
+                    pools.insert(lang, lang_config.placements.iter().filter_map(Placement::pool).collect());
+                    if lang_config.gpu.is_some() {
+                        gpu_languages.insert(lang);
+                    }
+                    if let Some(Err(e)) = lang_config.evaluation.comparison.map(|c| c.validate()) {
+                        return Err(format!("Invalid evaluation.comparison for '{}' in languages.json: {}", lang_config.name, e));
+                    }
+                    evaluation.insert(lang, lang_config.evaluation);
+                    // A maximum below the default would shrink the default itself
+                    ceilings.insert(lang, ResourceCeiling {
+                        memory_limit_mb: lang_config.max_memory_limit_mb
+                            .unwrap_or(lang_config.memory_limit_mb)
+                            .max(lang_config.memory_limit_mb),
+                        cpu_limit: lang_config.max_cpu_limit
+                            .unwrap_or(lang_config.cpu_limit)
+                            .max(lang_config.cpu_limit),
+                        max_parallel_tests: lang_config.concurrency.as_ref()
+                            .and_then(|c| c.max_parallel_tests)
+                            .map(|n| n.max(1)),
+                    });
                 }
                 None => {
                     return Err(format!(
@@ -67,7 +154,7 @@ impl LanguageRegistry {
             return Err("No languages configured in languages.json".to_string());
         }
         
-        Ok(Self { enabled_languages })
+        Ok(Self { enabled_languages, ceilings, versions, pools, gpu_languages, evaluation })
     }
     
     /// Check if a language is enabled
@@ -75,6 +162,31 @@ impl LanguageRegistry {
         self.enabled_languages.contains(&language)
     }
     
+    /// Largest per-job resources a submission for this language may request
+    pub fn resource_ceiling(&self, language: Language) -> Option<ResourceCeiling> {
+        self.ceilings.get(&language).copied()
+    }
+    
+    /// Configured runtime version (part of the result cache fingerprint)
+    pub fn version(&self, language: Language) -> Option<&str> {
+        self.versions.get(&language).map(String::as_str)
+    }
+    
+    /// Whether the language has a worker pool for this placement (see `Placement::pool`)
+    pub fn has_pool(&self, language: Language, pool: &str) -> bool {
+        self.pools.get(&language).is_some_and(|pools| pools.contains(pool))
+    }
+    
+    /// Whether the language's workers attach GPUs (languages.json `gpu`)
+    pub fn has_gpu(&self, language: Language) -> bool {
+        self.gpu_languages.contains(&language)
+    }
+    
+    /// Grading defaults of a language (all unset when it configures none)
+    pub fn evaluation_defaults(&self, language: Language) -> EvaluationDefaults {
+        self.evaluation.get(&language).copied().unwrap_or_default()
+    }
+    
     /// Get all enabled languages
     pub fn enabled_languages(&self) -> Vec<Language> {
         self.enabled_languages.iter().copied().collect()
@@ -94,6 +206,80 @@ mod tests {
         if let Ok(reg) = registry {
             // Should have at least python
             assert!(reg.is_enabled(Language::Python));
+            
+            // Ceilings never fall below the language defaults
+            let ceiling = reg.resource_ceiling(Language::Python).unwrap();
+            assert!(ceiling.memory_limit_mb >= 256);
+            assert!(ceiling.cpu_limit >= 0.5);
         }
     }
+    
+    #[test]
+    fn test_default_config_is_python_only() {
+        let registry = LanguageRegistry::from_json(optimus_common::config::DEFAULT_LANGUAGES_JSON).unwrap();
+        assert_eq!(registry.enabled_languages(), vec![Language::Python]);
+        assert_eq!(registry.resource_ceiling(Language::Python).unwrap().memory_limit_mb, 128);
+    }
+    
+    #[test]
+    fn test_placement_pools() {
+        let with_pools = optimus_common::config::DEFAULT_LANGUAGES_JSON.replace(
+            r#""name": "python","#,
+            r#""name": "python", "placements": [{"arch": "arm64"}, {"arch": "arm64", "region": "eu-west-1"}],"#,
+        );
+        let registry = LanguageRegistry::from_json(&with_pools).unwrap();
+        assert!(registry.has_pool(Language::Python, "arch-arm64"));
+        assert!(registry.has_pool(Language::Python, "arch-arm64.region-eu-west-1"));
+        assert!(!registry.has_pool(Language::Python, "region-eu-west-1"));
+        assert!(!registry.has_pool(Language::Java, "arch-arm64"));
+        
+        let invalid = with_pools.replace("arm64", "ARM 64");
+        assert!(LanguageRegistry::from_json(&invalid).is_err());
+    }
+    
+    #[test]
+    fn test_gpu_languages() {
+        let registry = LanguageRegistry::from_json(optimus_common::config::DEFAULT_LANGUAGES_JSON).unwrap();
+        assert!(!registry.has_gpu(Language::Python));
+        
+        let with_gpu = optimus_common::config::DEFAULT_LANGUAGES_JSON
+            .replace(r#""name": "python","#, r#""name": "python", "gpu": {"count": 1},"#);
+        let registry = LanguageRegistry::from_json(&with_gpu).unwrap();
+        assert!(registry.has_gpu(Language::Python));
+    }
+    
+    #[test]
+    fn test_parallel_tests_ceiling() {
+        let registry = LanguageRegistry::from_json(optimus_common::config::DEFAULT_LANGUAGES_JSON).unwrap();
+        let ceiling = registry.resource_ceiling(Language::Python).unwrap();
+        assert_eq!(ceiling.max_parallel_tests, None);
+        
+        let with_concurrency = optimus_common::config::DEFAULT_LANGUAGES_JSON.replace(
+            r#""name": "python","#,
+            r#""name": "python", "concurrency": {"max_parallel_jobs": 2, "max_parallel_tests": 4},"#,
+        );
+        let registry = LanguageRegistry::from_json(&with_concurrency).unwrap();
+        let ceiling = registry.resource_ceiling(Language::Python).unwrap();
+        assert_eq!(ceiling.max_parallel_tests, Some(4));
+    }
+    
+    #[test]
+    fn test_evaluation_defaults() {
+        let registry = LanguageRegistry::from_json(optimus_common::config::DEFAULT_LANGUAGES_JSON).unwrap();
+        assert_eq!(registry.evaluation_defaults(Language::Python), EvaluationDefaults::default());
+        assert_eq!(registry.evaluation_defaults(Language::Java), EvaluationDefaults::default());
+        
+        let with_defaults = optimus_common::config::DEFAULT_LANGUAGES_JSON.replace(
+            r#""name": "python","#,
+            r#""name": "python", "evaluation": {"comparison": {"mode": "float", "abs_tol": 1e-6}, "ignore_case": true},"#,
+        );
+        let registry = LanguageRegistry::from_json(&with_defaults).unwrap();
+        let defaults = registry.evaluation_defaults(Language::Python);
+        assert_eq!(defaults.comparison, Some(Comparison::Float { abs_tol: 1e-6, rel_tol: 0.0 }));
+        assert!(defaults.ignore_case);
+        assert!(!defaults.accept_presentation_errors);
+        
+        let invalid = with_defaults.replace("1e-6", "-1");
+        assert!(LanguageRegistry::from_json(&invalid).is_err());
+    }
 }
@@ -1,20 +1,48 @@
+mod analytics;
+mod availability;
+mod auth;
+mod delayed;
+mod forecast;
 mod handlers;
+mod i18n;
 mod routes;
+mod streaming;
 mod metrics;
 mod language_config;
+mod object_store;
+mod rate_limit;
+mod reaper;
+mod result_store;
+mod validation;
+mod webhooks;
+mod openapi;
 
-use axum::Router;
+use axum::{middleware, Router};
 use futures_util::StreamExt;
 use redis::aio::ConnectionManager;
 use std::sync::Arc;
 use tokio::net::TcpListener;
-use tracing::info;
+use tracing::{info, warn};
 
 #[derive(Clone)]
 pub struct AppState {
     pub redis: ConnectionManager,
+    /// For dedicated connections (pub/sub streams)
+    pub redis_client: redis::Client,
     pub start_time: Arc<std::time::Instant>,
     pub language_registry: Arc<language_config::LanguageRegistry>,
+    pub auth: Arc<auth::AuthConfig>,
+    pub rate_limit: Option<rate_limit::RateLimitConfig>,
+    /// Per-IP limit for unauthenticated submissions
+    pub ip_rate_limit: Option<rate_limit::IpRateLimitConfig>,
+    /// Submissions for a language with no live workers
+    pub no_workers_policy: availability::NoWorkersPolicy,
+    /// Deployment-specific submission rules, run after the built-in checks
+    pub validators: Arc<validation::ValidatorChain>,
+    /// Problem asset, upload and result copy storage (None when OBJECT_STORE_URL is unset)
+    pub object_store: Option<Arc<object_store::ObjectStore>>,
+    /// Whether results are also persisted to object storage, and read from where
+    pub result_storage: result_store::ResultStorageMode,
 }
 
 #[tokio::main]
@@ -44,7 +72,7 @@ async fn main() {
     let client = redis::Client::open(redis_url.as_str())
         .expect("Failed to create Redis client");
     
-    let redis_conn = ConnectionManager::new(client).await
+    let redis_conn = ConnectionManager::new(client.clone()).await
         .expect("Failed to connect to Redis");
     
     info!("Connected to Redis: {}", redis_url);
@@ -53,10 +81,16 @@ async fn main() {
     let config_path = std::env::var("LANGUAGE_CONFIG_PATH")
         .unwrap_or_else(|_| "config/languages.json".to_string());
     
-    let language_registry = language_config::LanguageRegistry::load_from_file(&config_path)
-        .unwrap_or_else(|e| {
-            panic!("Failed to load language configuration from {}: {}", config_path, e);
-        });
+    let language_registry = if !std::path::Path::new(&config_path).exists() && optimus_common::config::allow_default_config() {
+        warn!("{} not found - using the built-in default configuration (python only, ALLOW_DEFAULT_CONFIG=true)", config_path);
+        language_config::LanguageRegistry::from_json(optimus_common::config::DEFAULT_LANGUAGES_JSON)
+            .expect("built-in default language configuration is valid")
+    } else {
+        language_config::LanguageRegistry::load_from_file(&config_path)
+            .unwrap_or_else(|e| {
+                panic!("Failed to load language configuration from {}: {}", config_path, e);
+            })
+    };
     
     let enabled_langs: Vec<String> = language_registry.enabled_languages()
         .iter()
@@ -64,18 +98,90 @@ async fn main() {
         .collect();
     info!("Loaded language configuration: enabled languages = {:?}", enabled_langs);
 
+    // Load JWT verifier (admin routes stay closed if unconfigured)
+    let auth_config = auth::AuthConfig::from_env()
+        .unwrap_or_else(|e| panic!("Invalid authentication configuration: {}", e));
+
+    let rate_limit_config = rate_limit::RateLimitConfig::from_env();
+    match &rate_limit_config {
+        Some(cfg) => info!("Submission rate limit: {}/min per caller (burst {})", cfg.per_minute, cfg.burst),
+        None => info!("Submission rate limit disabled"),
+    }
+    let ip_rate_limit_config = rate_limit::IpRateLimitConfig::from_env();
+    if let Some(cfg) = &ip_rate_limit_config {
+        info!(
+            "Unauthenticated submission rate limit: {}/min per IP (burst {}, {} trusted proxy hop(s))",
+            cfg.bucket.per_minute, cfg.bucket.burst, cfg.trusted_proxy_hops
+        );
+    }
+
+    let no_workers_policy = availability::NoWorkersPolicy::from_env()
+        .unwrap_or_else(|e| panic!("Invalid worker availability configuration: {}", e));
+    info!("Submissions with no live workers: {:?}", no_workers_policy);
+
+    // Register deployment-specific validators here with `.with(...)`
+    let validators = validation::ValidatorChain::from_env()
+        .unwrap_or_else(|e| panic!("Invalid submission validation configuration: {}", e));
+    info!("Submission validators: {:?}", validators.names());
+
+    let object_store = object_store::ObjectStore::from_env()
+        .unwrap_or_else(|e| panic!("Invalid object storage configuration: {}", e));
+    match &object_store {
+        Some(store) => info!("Object storage: {}", store.describe()),
+        None => info!("Object storage disabled (problem assets and uploads unavailable)"),
+    }
+
+    let result_storage = result_store::ResultStorageMode::from_env()
+        .unwrap_or_else(|e| panic!("Invalid result storage configuration: {}", e));
+    if result_storage.persists() && object_store.is_none() {
+        panic!("RESULT_STORAGE_MODE={:?} needs OBJECT_STORE_URL", result_storage);
+    }
+    info!("Result storage: {:?}", result_storage);
+
     let state = Arc::new(AppState {
         redis: redis_conn.clone(),
+        redis_client: client.clone(),
         start_time: Arc::new(std::time::Instant::now()),
         language_registry: Arc::new(language_registry),
+        auth: Arc::new(auth_config),
+        rate_limit: rate_limit_config,
+        ip_rate_limit: ip_rate_limit_config,
+        no_workers_policy,
+        validators: Arc::new(validators),
+        object_store: object_store.map(Arc::new),
+        result_storage,
     });
 
     // Start background metrics subscriber
     tokio::spawn(metrics_subscriber());
 
+    // Start submission forecast refresher (autoscaler signal)
+    tokio::spawn(forecast::refresh_loop(redis_conn.clone()));
+
+    // Start worker availability watcher (live worker gauges, parked backlog recovery)
+    let workers_returned_webhook = std::env::var("WORKERS_RETURNED_WEBHOOK_URL").ok().filter(|url| !url.is_empty());
+    tokio::spawn(availability::watch_loop(redis_conn.clone(), workers_returned_webhook));
+
+    // Start delayed job releaser (spread batch submissions)
+    tokio::spawn(delayed::release_loop(redis_conn.clone()));
+
+    // Start orphaned job reaper (in-flight jobs of crashed workers)
+    tokio::spawn(reaper::reap_loop(redis_conn.clone()));
+
+    // Start result persistence and consistency checker (dual-write result storage)
+    if let (true, Some(store)) = (result_storage.persists(), state.object_store.clone()) {
+        tokio::spawn(result_store::persist_loop(client.clone(), redis_conn.clone(), store.clone()));
+        tokio::spawn(result_store::check_loop(redis_conn.clone(), store));
+    }
+
+    // Start webhook dispatcher (batch completion callbacks)
+    tokio::spawn(webhooks::dispatch_loop(client));
+
     // Build router
     let app = Router::new()
         .merge(routes::routes())
+        .layer(middleware::from_fn_with_state(state.clone(), auth::authenticate))
+        .layer(middleware::from_fn(i18n::localize))
         .with_state(state);
 
     // Start server
@@ -88,7 +194,8 @@ async fn main() {
     info!("HTTP server listening on {}", addr);
     info!("Ready to accept jobs");
 
-    axum::serve(listener, app).await
+    // Peer addresses feed the per-IP rate limiter
+    axum::serve(listener, app.into_make_service_with_connect_info::<std::net::SocketAddr>()).await
         .expect("Server error");
 }
 
@@ -119,30 +226,25 @@ async fn metrics_subscriber() {
     
     info!("Metrics subscriber started - listening for job completions");
     
-    loop {
-        match pubsub.on_message().next().await {
-            Some(msg) => {
-                let payload: String = match msg.get_payload() {
-                    Ok(p) => p,
-                    Err(_) => continue,
-                };
-                
-                if let Ok(event) = serde_json::from_str::<serde_json::Value>(&payload) {
-                    let language = event["language"].as_str().unwrap_or("unknown");
-                    let status = event["status"].as_str().unwrap_or("unknown");
-                    let exec_time = event["execution_time_ms"].as_f64().unwrap_or(0.0);
-                    
-                    metrics::record_job_completed(language, status, exec_time);
-                    
-                    tracing::debug!(
-                        job_id = event["job_id"].as_str().unwrap_or("unknown"),
-                        language = language,
-                        status = status,
-                        "Recorded job completion metrics"
-                    );
-                }
-            }
-            None => break,
+    while let Some(msg) = pubsub.on_message().next().await {
+        let payload: String = match msg.get_payload() {
+            Ok(p) => p,
+            Err(_) => continue,
+        };
+        
+        if let Ok(event) = serde_json::from_str::<serde_json::Value>(&payload) {
+            let language = event["language"].as_str().unwrap_or("unknown");
+            let status = event["status"].as_str().unwrap_or("unknown");
+            let exec_time = event["execution_time_ms"].as_f64().unwrap_or(0.0);
+            
+            metrics::record_job_completed(language, status, exec_time);
+            
+            tracing::debug!(
+                job_id = event["job_id"].as_str().unwrap_or("unknown"),
+                language = language,
+                status = status,
+                "Recorded job completion metrics"
+            );
         }
     }
 }
@@ -2,7 +2,7 @@
 
 use lazy_static::lazy_static;
 use prometheus::{
-    CounterVec, HistogramOpts, HistogramVec, IntGaugeVec, Opts,
+    CounterVec, GaugeVec, HistogramOpts, HistogramVec, IntGaugeVec, Opts,
     Registry, TextEncoder, Encoder,
 };
 
@@ -56,12 +56,103 @@ lazy_static! {
     )
     .expect("metric can be created");
 
+    // Rate limiter outcomes (scope = key | ip; decision = allowed | throttled | fail_open)
+    pub static ref RATE_LIMIT_DECISIONS: CounterVec = CounterVec::new(
+        Opts::new("optimus_rate_limit_decisions_total", "Submission rate limiter decisions"),
+        &["scope", "decision"]
+    )
+    .expect("metric can be created");
+
     // Jobs cancelled counter
     pub static ref JOBS_CANCELLED: CounterVec = CounterVec::new(
         Opts::new("optimus_jobs_cancelled_total", "Total jobs cancelled"),
         &["source"]
     )
     .expect("metric can be created");
+
+    // Webhook deliveries (event + outcome: delivered / failed)
+    pub static ref WEBHOOK_DELIVERIES: CounterVec = CounterVec::new(
+        Opts::new("optimus_webhook_deliveries_total", "Total outbound webhook deliveries"),
+        &["event", "outcome"]
+    )
+    .expect("metric can be created");
+
+    // Smoothed submissions per minute (leading signal for autoscalers)
+    pub static ref SUBMISSION_RATE_EWMA: GaugeVec = GaugeVec::new(
+        Opts::new("optimus_submission_rate_ewma", "EWMA of submissions per minute per language"),
+        &["language"]
+    )
+    .expect("metric can be created");
+
+    // Submissions so far in the current minute
+    pub static ref SUBMISSIONS_CURRENT_MINUTE: IntGaugeVec = IntGaugeVec::new(
+        Opts::new("optimus_submissions_current_minute", "Submissions in the current minute per language"),
+        &["language"]
+    )
+    .expect("metric can be created");
+
+    // Result cache lookups for opt-in submissions
+    pub static ref RESULT_CACHE_LOOKUPS: CounterVec = CounterVec::new(
+        Opts::new("optimus_result_cache_total", "Result cache lookups by outcome"),
+        &["outcome"]
+    )
+    .expect("metric can be created");
+
+    // Workers with a recent heartbeat
+    pub static ref LIVE_WORKERS: IntGaugeVec = IntGaugeVec::new(
+        Opts::new("optimus_live_workers", "Workers with a recent heartbeat per language"),
+        &["language"]
+    )
+    .expect("metric can be created");
+
+    // Jobs accepted while their language had no workers
+    pub static ref JOBS_PARKED: CounterVec = CounterVec::new(
+        Opts::new("optimus_jobs_parked_total", "Jobs accepted while no workers were alive"),
+        &["language"]
+    )
+    .expect("metric can be created");
+
+    // Workers came back for a language with parked jobs
+    pub static ref WORKERS_RETURNED: CounterVec = CounterVec::new(
+        Opts::new("optimus_workers_returned_total", "Times workers returned to a parked backlog"),
+        &["language"]
+    )
+    .expect("metric can be created");
+
+    // In-flight jobs whose processing heartbeat lapsed (outcome = retry | dlq)
+    pub static ref ORPHANED_JOBS: CounterVec = CounterVec::new(
+        Opts::new("optimus_orphaned_jobs_total", "In-flight jobs requeued or dead-lettered after their worker stopped heartbeating"),
+        &["language", "outcome"]
+    )
+    .expect("metric can be created");
+
+    // Submissions vetoed by a deployment validation plugin
+    pub static ref VALIDATION_REJECTIONS: CounterVec = CounterVec::new(
+        Opts::new("optimus_validation_rejections_total", "Submissions rejected by validation plugins"),
+        &["validator"]
+    )
+    .expect("metric can be created");
+
+    // Result copies written to object storage (outcome = ok | error)
+    pub static ref RESULTS_PERSISTED: CounterVec = CounterVec::new(
+        Opts::new("optimus_results_persisted_total", "Results copied to object storage after being stored in Redis"),
+        &["outcome"]
+    )
+    .expect("metric can be created");
+
+    // Result reads by the backend that answered (redis | object_store | *_fallback)
+    pub static ref RESULT_READS: CounterVec = CounterVec::new(
+        Opts::new("optimus_result_reads_total", "Result reads by answering storage backend"),
+        &["source"]
+    )
+    .expect("metric can be created");
+
+    // Persisted results found diverging from Redis (kind = missing | mismatch)
+    pub static ref RESULT_DIVERGENCES: CounterVec = CounterVec::new(
+        Opts::new("optimus_result_divergences_total", "Persisted results found missing or different from Redis by the consistency checker"),
+        &["kind"]
+    )
+    .expect("metric can be created");
 }
 
 /// Initialize metrics registry
@@ -90,9 +181,60 @@ pub fn init_metrics() {
         .register(Box::new(JOBS_REJECTED.clone()))
         .expect("collector can be registered");
 
+    REGISTRY
+        .register(Box::new(RATE_LIMIT_DECISIONS.clone()))
+        .expect("collector can be registered");
+
     REGISTRY
         .register(Box::new(JOBS_CANCELLED.clone()))
         .expect("collector can be registered");
+
+    REGISTRY
+        .register(Box::new(WEBHOOK_DELIVERIES.clone()))
+        .expect("collector can be registered");
+
+    REGISTRY
+        .register(Box::new(SUBMISSION_RATE_EWMA.clone()))
+        .expect("collector can be registered");
+
+    REGISTRY
+        .register(Box::new(SUBMISSIONS_CURRENT_MINUTE.clone()))
+        .expect("collector can be registered");
+
+    REGISTRY
+        .register(Box::new(RESULT_CACHE_LOOKUPS.clone()))
+        .expect("collector can be registered");
+
+    REGISTRY
+        .register(Box::new(LIVE_WORKERS.clone()))
+        .expect("collector can be registered");
+
+    REGISTRY
+        .register(Box::new(JOBS_PARKED.clone()))
+        .expect("collector can be registered");
+
+    REGISTRY
+        .register(Box::new(WORKERS_RETURNED.clone()))
+        .expect("collector can be registered");
+
+    REGISTRY
+        .register(Box::new(ORPHANED_JOBS.clone()))
+        .expect("collector can be registered");
+    REGISTRY
+        .register(Box::new(VALIDATION_REJECTIONS.clone()))
+        .expect("collector can be registered");
+
+    REGISTRY
+        .register(Box::new(RESULTS_PERSISTED.clone()))
+        .expect("collector can be registered");
+
+    REGISTRY
+        .register(Box::new(RESULT_READS.clone()))
+        .expect("collector can be registered");
+
+    REGISTRY
+        .register(Box::new(RESULT_DIVERGENCES.clone()))
+        .expect("collector can be registered");
 }
 
 /// Render metrics in Prometheus text format
@@ -114,24 +256,33 @@ pub fn record_job_rejected(reason: &str) {
     JOBS_REJECTED.with_label_values(&[reason]).inc();
 }
 
+/// Record a rate limiter decision
+pub fn record_rate_limit_decision(scope: &str, decision: &str) {
+    RATE_LIMIT_DECISIONS.with_label_values(&[scope, decision]).inc();
+}
+
 /// Record job completion
 pub fn record_job_completed(language: &str, status: &str, execution_time_ms: f64) {
     JOBS_COMPLETED.with_label_values(&[language, status]).inc();
     JOB_EXECUTION_TIME.with_label_values(&[language]).observe(execution_time_ms);
 }
 
-/// Update queue depth for a language
+/// Update queue depth for a language (summed across priorities)
 pub async fn update_queue_depths(redis_conn: &mut redis::aio::ConnectionManager) {
     use redis::AsyncCommands;
-    use optimus_common::types::Language;
+    use optimus_common::types::{Language, Priority};
     
     for language in Language::all_variants() {
-        let queue_name = optimus_common::redis::queue_name(language);
-        if let Ok(depth) = redis_conn.llen::<_, i64>(&queue_name).await {
-            QUEUE_DEPTH
-                .with_label_values(&[&language.to_string()])
-                .set(depth);
+        let mut depth = 0i64;
+        for priority in [Priority::High, Priority::Normal, Priority::Low] {
+            let queue_name = optimus_common::redis::priority_queue_name(language, priority);
+            if let Ok(len) = redis_conn.llen::<_, i64>(&queue_name).await {
+                depth += len;
+            }
         }
+        QUEUE_DEPTH
+            .with_label_values(&[&language.to_string()])
+            .set(depth);
     }
 }
 
@@ -139,3 +290,62 @@ pub async fn update_queue_depths(redis_conn: &mut redis::aio::ConnectionManager)
 pub fn record_job_cancelled(source: &str) {
     JOBS_CANCELLED.with_label_values(&[source]).inc();
 }
+
+/// Record an outbound webhook delivery outcome
+pub fn record_webhook_delivery(event: &str, outcome: &str) {
+    WEBHOOK_DELIVERIES.with_label_values(&[event, outcome]).inc();
+}
+
+/// Export a language's submission forecast
+pub fn record_submission_forecast(forecast: &optimus_common::types::SubmissionForecast) {
+    let language = forecast.language.to_string();
+    SUBMISSION_RATE_EWMA.with_label_values(&[&language]).set(forecast.ewma_per_minute);
+    SUBMISSIONS_CURRENT_MINUTE
+        .with_label_values(&[&language])
+        .set(forecast.current_minute as i64);
+}
+
+/// Record a result cache lookup (hit / miss)
+pub fn record_result_cache(outcome: &str) {
+    RESULT_CACHE_LOOKUPS.with_label_values(&[outcome]).inc();
+}
+
+/// Export a language's live worker count
+pub fn record_live_workers(language: &str, live: u64) {
+    LIVE_WORKERS.with_label_values(&[language]).set(live as i64);
+}
+
+/// Record a job accepted with no live workers
+pub fn record_job_parked(language: &str) {
+    JOBS_PARKED.with_label_values(&[language]).inc();
+}
+
+/// Record an orphaned job handed back by the reaper
+pub fn record_orphaned_job(language: &str, outcome: &str) {
+    ORPHANED_JOBS.with_label_values(&[language, outcome]).inc();
+}
+
+/// Record workers returning to a parked backlog
+pub fn record_workers_returned(language: &str) {
+    WORKERS_RETURNED.with_label_values(&[language]).inc();
+}
+
+/// Record a submission vetoed by a validation plugin
+pub fn record_validation_rejection(validator: &str) {
+    VALIDATION_REJECTIONS.with_label_values(&[validator]).inc();
+}
+
+/// Record a result copy written to object storage
+pub fn record_result_persisted(outcome: &str) {
+    RESULTS_PERSISTED.with_label_values(&[outcome]).inc();
+}
+
+/// Record which storage backend answered a result read
+pub fn record_result_read(source: &str) {
+    RESULT_READS.with_label_values(&[source]).inc();
+}
+
+/// Record a persisted result diverging from Redis
+pub fn record_result_divergence(kind: &str) {
+    RESULT_DIVERGENCES.with_label_values(&[kind]).inc();
+}
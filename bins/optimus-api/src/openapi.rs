@@ -0,0 +1,105 @@
+// OpenAPI specification for the Optimus API
+// Generated from the handler annotations and Rust types, served at /openapi.json
+
+use axum::{
+    http::header,
+    response::{Html, IntoResponse, Json},
+};
+use utoipa::OpenApi;
+
+use crate::handlers;
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Optimus API", description = "Sandboxed code execution and grading"),
+    paths(
+        handlers::submit_job,
+        handlers::submit_archive_job,
+        handlers::get_job_result,
+        handlers::get_partial_results,
+        handlers::put_job_feedback,
+        handlers::get_job_debug,
+        handlers::cancel_job,
+        handlers::create_batch,
+        handlers::get_batch,
+        handlers::batch_events,
+        handlers::health_check,
+        handlers::readiness_check,
+        handlers::get_language_analytics,
+        handlers::get_job_diagnostics,
+        handlers::get_submission_forecast,
+        handlers::list_workers,
+        handlers::create_problem,
+        handlers::get_problem,
+        handlers::put_problem_asset,
+        handlers::list_problem_assets,
+        handlers::get_problem_asset,
+        handlers::create_upload,
+        handlers::get_upload,
+        handlers::append_upload_chunk,
+        handlers::finalize_upload,
+    ),
+    tags(
+        (name = "jobs", description = "Submit jobs and fetch results"),
+        (name = "batches", description = "Group jobs under one completion event"),
+        (name = "uploads", description = "Chunked uploads of large test suites"),
+        (name = "health", description = "Liveness and readiness probes"),
+        (name = "admin", description = "Operator endpoints (admin token required)"),
+    )
+)]
+pub struct ApiDoc;
+
+/// GET /openapi.json - OpenAPI 3 document
+pub async fn openapi_json() -> impl IntoResponse {
+    Json(ApiDoc::openapi())
+}
+
+/// GET /docs - Swagger UI for /openapi.json (assets loaded from unpkg)
+pub async fn swagger_ui() -> impl IntoResponse {
+    (
+        [(header::CACHE_CONTROL, "no-cache")],
+        Html(SWAGGER_UI_HTML),
+    )
+}
+
+const SWAGGER_UI_HTML: &str = r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+  <meta charset="utf-8" />
+  <title>Optimus API</title>
+  <link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@5/swagger-ui.css" />
+</head>
+<body>
+  <div id="swagger-ui"></div>
+  <script src="https://unpkg.com/swagger-ui-dist@5/swagger-ui-bundle.js" crossorigin></script>
+  <script>
+    window.onload = () => {
+      window.ui = SwaggerUIBundle({ url: "/openapi.json", dom_id: "#swagger-ui" });
+    };
+  </script>
+</body>
+</html>
+"##;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spec_covers_public_payloads() {
+        let spec = serde_json::to_value(ApiDoc::openapi()).unwrap();
+
+        let schemas = &spec["components"]["schemas"];
+        for name in ["SubmitRequest", "TestCaseInput", "ExecutionResult", "TestResult", "ErrorResponse", "JobStatus"] {
+            assert!(schemas.get(name).is_some(), "missing schema {}", name);
+        }
+
+        assert!(spec["paths"]["/execute"]["post"].is_object());
+        assert!(spec["paths"]["/job/{job_id}"]["get"].is_object());
+        assert!(spec["paths"]["/job/{job_id}/partial"]["get"].is_object());
+
+        // serde renames must carry through to the schema
+        let statuses = schemas["JobStatus"]["enum"].as_array().unwrap();
+        assert!(statuses.contains(&serde_json::json!("completed")));
+    }
+}
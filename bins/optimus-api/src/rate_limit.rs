@@ -0,0 +1,181 @@
+// Submission rate limiting
+// Redis-backed token buckets shared by all API replicas
+
+use axum::{
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use optimus_common::redis;
+use std::net::{IpAddr, SocketAddr};
+use tracing::{error, warn};
+
+use crate::handlers::{ErrorDetail, ErrorResponse};
+use crate::metrics;
+
+/// Rate limit settings from environment
+///
+/// - `RATE_LIMIT_PER_MINUTE`: sustained submissions per caller (unset = disabled)
+/// - `RATE_LIMIT_BURST`: bucket capacity (default: same as per-minute)
+#[derive(Debug, Clone)]
+pub struct RateLimitConfig {
+    pub per_minute: u32,
+    pub burst: u32,
+}
+
+impl RateLimitConfig {
+    pub fn from_env() -> Option<Self> {
+        Self::from_vars("RATE_LIMIT_PER_MINUTE", "RATE_LIMIT_BURST")
+    }
+
+    fn from_vars(per_minute_var: &str, burst_var: &str) -> Option<Self> {
+        let per_minute: u32 = std::env::var(per_minute_var)
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|&n| n > 0)?;
+
+        let burst = std::env::var(burst_var)
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|&n| n > 0)
+            .unwrap_or(per_minute);
+
+        Some(Self { per_minute, burst })
+    }
+}
+
+/// Per-IP limit for unauthenticated submissions
+///
+/// - `IP_RATE_LIMIT_PER_MINUTE` / `IP_RATE_LIMIT_BURST`: as above, per client IP
+///   (unset = disabled)
+/// - `TRUSTED_PROXY_HOPS`: reverse proxies in front of the API that append to
+///   `X-Forwarded-For` (default 0 = ignore the header, use the peer address)
+#[derive(Debug, Clone)]
+pub struct IpRateLimitConfig {
+    pub bucket: RateLimitConfig,
+    pub trusted_proxy_hops: usize,
+}
+
+impl IpRateLimitConfig {
+    pub fn from_env() -> Option<Self> {
+        let bucket = RateLimitConfig::from_vars("IP_RATE_LIMIT_PER_MINUTE", "IP_RATE_LIMIT_BURST")?;
+        let trusted_proxy_hops = std::env::var("TRUSTED_PROXY_HOPS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0);
+
+        Some(Self { bucket, trusted_proxy_hops })
+    }
+}
+
+/// The submitting client's IP
+///
+/// Each trusted proxy appends the address it received the request from, so
+/// the client is the `trusted_proxy_hops`-th entry from the right; anything
+/// further left is client-supplied and can't be trusted. Without trusted
+/// proxies (or a usable header) the TCP peer is the client.
+pub fn client_ip(headers: &HeaderMap, peer: Option<SocketAddr>, trusted_proxy_hops: usize) -> Option<IpAddr> {
+    if trusted_proxy_hops > 0 {
+        let forwarded: Vec<&str> = headers
+            .get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .collect();
+
+        if let Some(ip) = forwarded
+            .len()
+            .checked_sub(trusted_proxy_hops)
+            .and_then(|idx| forwarded[idx].parse().ok())
+        {
+            return Some(ip);
+        }
+    }
+
+    peer.map(|addr| addr.ip())
+}
+
+/// Seconds to advertise in `Retry-After` (rounded up, at least 1)
+fn retry_after_secs(retry_after_ms: u64) -> u64 {
+    retry_after_ms.div_ceil(1000).max(1)
+}
+
+/// Consume one submission token for `identity` within `scope`
+///
+/// Returns `Some(response)` with 429 + `Retry-After` when the caller is over
+/// its limit. Redis failures fail open so a Redis blip doesn't reject traffic.
+pub async fn check(
+    conn: &mut ::redis::aio::ConnectionManager,
+    config: &RateLimitConfig,
+    scope: &str,
+    identity: &str,
+) -> Option<Response> {
+    let key = redis::rate_limit_key(scope, identity);
+
+    match redis::take_rate_limit_token(conn, &key, config.per_minute, config.burst).await {
+        Ok((true, _)) => {
+            metrics::record_rate_limit_decision(scope, "allowed");
+            None
+        }
+        Ok((false, retry_after_ms)) => {
+            let retry_after = retry_after_secs(retry_after_ms);
+            metrics::record_rate_limit_decision(scope, "throttled");
+            metrics::record_job_rejected("rate_limited");
+            warn!(
+                scope = scope,
+                identity = %identity,
+                retry_after_secs = retry_after,
+                "Rejected: Rate limit exceeded"
+            );
+            Some((
+                StatusCode::TOO_MANY_REQUESTS,
+                [(header::RETRY_AFTER, retry_after.to_string())],
+                Json(ErrorResponse {
+                    error: ErrorDetail {
+                        code: "RATE_LIMITED".to_string(),
+                        message: format!(
+                            "Rate limit of {} submissions per minute exceeded, retry in {}s",
+                            config.per_minute,
+                            retry_after
+                        ),
+                        params: vec![config.per_minute.to_string(), retry_after.to_string()],
+                    },
+                }),
+            ).into_response())
+        }
+        Err(e) => {
+            metrics::record_rate_limit_decision(scope, "fail_open");
+            error!(error = %e, "Rate limiter unavailable - allowing request");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_after_rounds_up() {
+        assert_eq!(retry_after_secs(0), 1);
+        assert_eq!(retry_after_secs(1), 1);
+        assert_eq!(retry_after_secs(1000), 1);
+        assert_eq!(retry_after_secs(1001), 2);
+    }
+
+    #[test]
+    fn test_client_ip_respects_trusted_hops() {
+        let peer: Option<SocketAddr> = Some("10.0.0.2:4000".parse().unwrap());
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", "1.1.1.1, 203.0.113.7, 10.0.0.9".parse().unwrap());
+
+        // Header ignored unless proxies are trusted
+        assert_eq!(client_ip(&headers, peer, 0), Some("10.0.0.2".parse().unwrap()));
+        assert_eq!(client_ip(&headers, peer, 1), Some("10.0.0.9".parse().unwrap()));
+        assert_eq!(client_ip(&headers, peer, 2), Some("203.0.113.7".parse().unwrap()));
+        // More hops than entries, or no header: fall back to the peer
+        assert_eq!(client_ip(&headers, peer, 5), Some("10.0.0.2".parse().unwrap()));
+        assert_eq!(client_ip(&HeaderMap::new(), peer, 1), Some("10.0.0.2".parse().unwrap()));
+    }
+}
@@ -0,0 +1,122 @@
+// Orphaned job reaper
+// Hands back in-flight jobs whose worker stopped refreshing their processing heartbeat
+
+use optimus_common::redis::{self, LeasedJob};
+use optimus_common::types::{ExecutionResult, JobStatus};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::{handlers, metrics};
+
+/// How often workers' in-flight lists are checked
+const REAP_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Background task: requeue orphaned jobs, or dead-letter them on their last attempt
+/// Safe to run on every API replica - each hand-back is atomic in Redis
+pub async fn reap_loop(mut conn: ::redis::aio::ConnectionManager) {
+    info!("Orphaned job reaper started ({}s interval)", REAP_INTERVAL.as_secs());
+
+    let mut missing = HashMap::new();
+    let mut ticker = tokio::time::interval(REAP_INTERVAL);
+    loop {
+        ticker.tick().await;
+        if let Err(e) = reap(&mut conn, &mut missing).await {
+            warn!(error = %e, "Orphaned job reaper pass failed");
+        }
+    }
+}
+
+/// One pass over every worker's in-flight list
+///
+/// `missing` tracks when this replica first saw each in-flight job without
+/// a heartbeat. A job is handed back only once it has gone without one for
+/// `PROCESSING_TTL_SECS`, so a job a worker has just moved off a queue (its
+/// heartbeat is set right after the move) is not taken from it.
+async fn reap(
+    conn: &mut ::redis::aio::ConnectionManager,
+    missing: &mut HashMap<(String, Uuid), Instant>,
+) -> ::redis::RedisResult<()> {
+    let grace = Duration::from_secs(redis::PROCESSING_TTL_SECS);
+    let mut still_missing = HashMap::new();
+    for worker_id in redis::inflight_workers(conn).await? {
+        for leased in redis::list_leased_jobs(conn, &worker_id).await? {
+            if !redis::processing_expired(conn, &leased.job.id).await? {
+                continue;
+            }
+            let entry = (worker_id.clone(), leased.job.id);
+            let since = missing.get(&entry).copied().unwrap_or_else(Instant::now);
+            if since.elapsed() >= grace {
+                hand_back(conn, &worker_id, &leased).await;
+            } else {
+                still_missing.insert(entry, since);
+            }
+        }
+        redis::forget_idle_inflight_worker(conn, &worker_id).await?;
+    }
+    *missing = still_missing;
+    Ok(())
+}
+
+/// Move one orphaned job to the retry queue, counting the lost run as a
+/// failed attempt; on its last attempt it goes to the DLQ with a failed result
+async fn hand_back(conn: &mut ::redis::aio::ConnectionManager, worker_id: &str, leased: &LeasedJob) {
+    let mut job = leased.job.clone();
+    let job_id = job.id;
+    job.metadata.attempts += 1;
+    job.metadata.last_failure_reason = Some(format!(
+        "Worker {} stopped heartbeating while holding the job (crashed or lost)",
+        worker_id
+    ));
+    let dead_letter = job.metadata.attempts >= job.metadata.max_attempts;
+
+    match redis::requeue_leased_job(conn, worker_id, leased, &mut job, dead_letter, true).await {
+        Ok(true) => {}
+        // Acked, refreshed or handed back by another replica in the meantime
+        Ok(false) => return,
+        Err(e) => {
+            error!(job_id = %job_id, worker = %worker_id, error = %e, "Failed to hand back orphaned job");
+            return;
+        }
+    }
+
+    let outcome = if dead_letter { "dlq" } else { "retry" };
+    metrics::record_orphaned_job(&job.language.to_string(), outcome);
+    warn!(
+        job_id = %job_id,
+        worker = %worker_id,
+        attempts = job.metadata.attempts,
+        outcome = outcome,
+        "Orphaned job handed back"
+    );
+
+    if !dead_letter {
+        return;
+    }
+    let failed_result = ExecutionResult {
+        job_id,
+        overall_status: JobStatus::Failed,
+        score: 0,
+        max_score: job.test_cases.iter().map(|tc| tc.weight).sum(),
+        results: vec![],
+        scoring_formula: None,
+        reason: job.metadata.last_failure_reason.clone(),
+        feedback: None,
+        timing_summary: None,
+        suspicious: false,
+        cached: false,
+        verdict_summary: None,
+    };
+    if let Err(e) = redis::store_result_with_metrics(conn, &failed_result, &job.language).await {
+        error!(job_id = %job_id, error = %e, "Failed to store failed result for orphaned job");
+        return;
+    }
+    if let Some(batch_id) = job.batch_id {
+        match redis::get_batch(conn, &batch_id).await {
+            Ok(Some(batch)) => handlers::finish_batch_job(conn, &batch, &job_id).await,
+            Ok(None) => {}
+            Err(e) => error!(job_id = %job_id, batch_id = %batch_id, error = %e, "Failed to load batch"),
+        }
+    }
+}
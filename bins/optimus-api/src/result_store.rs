@@ -0,0 +1,315 @@
+// Result persistence beyond Redis
+// Copies stored results to object storage and reads them back once Redis has
+// expired them, so result storage can migrate without downtime
+
+use futures_util::StreamExt;
+use optimus_common::redis;
+use optimus_common::types::ExecutionResult;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::{metrics, object_store::ObjectStore, AppState};
+
+/// How often the consistency checker walks the results held in Redis
+const CHECK_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Result keys fetched per SCAN step during a consistency pass
+const CHECK_SCAN_COUNT: usize = 200;
+
+/// Where results live
+///
+/// Redis always receives every result first (workers write there) and stays
+/// the hot path; the other modes add an object storage copy, written
+/// asynchronously by the API after each result is stored:
+///
+/// - `RESULT_STORAGE_MODE=redis` (default): Redis only, results expire after 24 hours
+/// - `dual_write`: also persist to object storage; reads prefer Redis and fall
+///   back to the persisted copy once Redis has expired the result
+/// - `object_store`: also persist; reads prefer the persisted copy and fall
+///   back to Redis (results not yet copied, or object storage unreachable)
+///
+/// Both dual modes need `OBJECT_STORE_URL` and run the consistency checker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResultStorageMode {
+    Redis,
+    DualWrite,
+    ObjectStore,
+}
+
+impl ResultStorageMode {
+    pub fn from_env() -> Result<Self, String> {
+        match std::env::var("RESULT_STORAGE_MODE") {
+            Err(_) => Ok(Self::Redis),
+            Ok(value) => Self::parse(&value),
+        }
+    }
+
+    fn parse(value: &str) -> Result<Self, String> {
+        match value.trim().to_lowercase().as_str() {
+            "" | "redis" => Ok(Self::Redis),
+            "dual_write" => Ok(Self::DualWrite),
+            "object_store" => Ok(Self::ObjectStore),
+            other => Err(format!(
+                "RESULT_STORAGE_MODE must be 'redis', 'dual_write' or 'object_store', got '{}'",
+                other
+            )),
+        }
+    }
+
+    pub fn persists(self) -> bool {
+        self != Self::Redis
+    }
+}
+
+/// Object storage key of a job's persisted result
+pub fn result_object_key(job_id: &Uuid) -> String {
+    format!("results/{}.json", job_id)
+}
+
+/// Fetch a job's result honouring the configured read preference
+///
+/// Object storage failures never fail the read: they are logged and the
+/// Redis answer is used instead. A persisted copy is ignored while the job's
+/// result is marked reset (requeued from the DLQ and not re-persisted yet):
+/// it belongs to the previous run.
+pub async fn get_result(
+    state: &AppState,
+    conn: &mut ::redis::aio::ConnectionManager,
+    job_id: &Uuid,
+) -> ::redis::RedisResult<Option<ExecutionResult>> {
+    let store = match (state.result_storage, state.object_store.as_deref()) {
+        (mode, Some(store)) if mode.persists() => store,
+        _ => return redis::get_result(conn, job_id).await,
+    };
+    let stale = redis::result_was_reset(conn, job_id).await?;
+
+    if state.result_storage == ResultStorageMode::ObjectStore {
+        if let Some(result) = read_persisted(store, job_id, stale).await {
+            metrics::record_result_read("object_store");
+            return Ok(Some(result));
+        }
+        let result = redis::get_result(conn, job_id).await?;
+        if result.is_some() {
+            metrics::record_result_read("redis_fallback");
+        }
+        return Ok(result);
+    }
+
+    if let Some(result) = redis::get_result(conn, job_id).await? {
+        metrics::record_result_read("redis");
+        return Ok(Some(result));
+    }
+    let result = read_persisted(store, job_id, stale).await;
+    if result.is_some() {
+        metrics::record_result_read("object_store_fallback");
+    }
+    Ok(result)
+}
+
+async fn read_persisted(store: &ObjectStore, job_id: &Uuid, stale: bool) -> Option<ExecutionResult> {
+    if stale {
+        return None;
+    }
+    match store.get(&result_object_key(job_id)).await {
+        Ok(Some(body)) => match serde_json::from_slice(&body) {
+            Ok(result) => Some(result),
+            Err(e) => {
+                warn!(job_id = %job_id, error = %e, "Persisted result is not valid JSON");
+                None
+            }
+        },
+        Ok(None) => None,
+        Err(e) => {
+            warn!(job_id = %job_id, error = %e, "Failed to read persisted result");
+            None
+        }
+    }
+}
+
+/// Copy a job's current Redis result to object storage (no-op if it has none)
+async fn persist(
+    conn: &mut ::redis::aio::ConnectionManager,
+    store: &ObjectStore,
+    job_id: &Uuid,
+) -> Result<bool, String> {
+    let Some(result) = redis::get_result(conn, job_id).await.map_err(|e| e.to_string())? else {
+        return Ok(false);
+    };
+    write_persisted(conn, store, &result).await?;
+    Ok(true)
+}
+
+/// Overwrite a job's persisted copy with `result` (its current Redis result)
+/// The copy is current again, so a reset marker no longer applies
+async fn write_persisted(
+    conn: &mut ::redis::aio::ConnectionManager,
+    store: &ObjectStore,
+    result: &ExecutionResult,
+) -> Result<(), String> {
+    let body = serde_json::to_vec(result).map_err(|e| e.to_string())?;
+    store.put(&result_object_key(&result.job_id), body, "application/json").await?;
+    redis::clear_result_reset(conn, &result.job_id).await.map_err(|e| e.to_string())
+}
+
+/// Background task: persist every result as soon as it is stored in Redis
+///
+/// Listens for the "stored" event published on each job's event channel.
+/// Safe to run on every API replica - the copy is an idempotent overwrite,
+/// and results whose event was missed are picked up by the consistency checker.
+pub async fn persist_loop(client: ::redis::Client, mut conn: ::redis::aio::ConnectionManager, store: Arc<ObjectStore>) {
+    let mut pubsub = match client.get_async_connection().await {
+        Ok(conn) => conn.into_pubsub(),
+        Err(e) => {
+            error!(error = %e, "Failed to create pubsub connection for result persistence");
+            return;
+        }
+    };
+    let pattern = format!("{}:job:*", redis::EVENTS_PREFIX);
+    if let Err(e) = pubsub.psubscribe(&pattern).await {
+        error!(error = %e, "Failed to subscribe to job events for result persistence");
+        return;
+    }
+
+    info!("Result persistence started ({})", store.describe());
+
+    let mut messages = pubsub.on_message();
+    while let Some(msg) = messages.next().await {
+        let channel = msg.get_channel_name();
+        let Some(job_id) = channel.rsplit(':').next().and_then(|id| Uuid::parse_str(id).ok()) else {
+            continue;
+        };
+        match persist(&mut conn, &store, &job_id).await {
+            Ok(true) => metrics::record_result_persisted("ok"),
+            Ok(false) => {}
+            Err(e) => {
+                metrics::record_result_persisted("error");
+                warn!(job_id = %job_id, error = %e, "Failed to persist result to object storage");
+            }
+        }
+    }
+    warn!("Result persistence subscription ended");
+}
+
+/// Background task: compare every result in Redis with its persisted copy
+///
+/// Divergences (copy missing, or different from Redis) are counted, logged
+/// and repaired from Redis, which stays the source of truth while both hold
+/// a result.
+pub async fn check_loop(mut conn: ::redis::aio::ConnectionManager, store: Arc<ObjectStore>) {
+    info!("Result consistency checker started ({}s interval)", CHECK_INTERVAL.as_secs());
+
+    let mut ticker = tokio::time::interval(CHECK_INTERVAL);
+    loop {
+        ticker.tick().await;
+        match check(&mut conn, &store).await {
+            Ok(checked) => info!(checked = checked, "Result consistency pass finished"),
+            Err(e) => warn!(error = %e, "Result consistency pass failed"),
+        }
+    }
+}
+
+async fn check(conn: &mut ::redis::aio::ConnectionManager, store: &ObjectStore) -> Result<u64, String> {
+    let mut checked = 0;
+    let mut cursor = 0;
+    loop {
+        let (next, job_ids) = redis::scan_result_ids(conn, cursor, CHECK_SCAN_COUNT)
+            .await
+            .map_err(|e| e.to_string())?;
+        for job_id in job_ids {
+            // Expired between SCAN and GET
+            let Some(result) = redis::get_result(conn, &job_id).await.map_err(|e| e.to_string())? else {
+                continue;
+            };
+            checked += 1;
+            let persisted = match store.get(&result_object_key(&job_id)).await {
+                Ok(persisted) => persisted,
+                Err(e) => {
+                    warn!(job_id = %job_id, error = %e, "Failed to read persisted result for consistency check");
+                    continue;
+                }
+            };
+            let Some(kind) = divergence(&result, persisted.as_deref()) else {
+                continue;
+            };
+            metrics::record_result_divergence(kind);
+            warn!(job_id = %job_id, divergence = kind, "Persisted result diverges from Redis - repairing");
+            if let Err(e) = write_persisted(conn, store, &result).await {
+                warn!(job_id = %job_id, error = %e, "Failed to repair persisted result");
+            }
+        }
+        if next == 0 {
+            return Ok(checked);
+        }
+        cursor = next;
+    }
+}
+
+/// How a persisted copy differs from the Redis result (None if it matches)
+///
+/// Compared as JSON values, so field order and formatting don't count.
+fn divergence(result: &ExecutionResult, persisted: Option<&[u8]>) -> Option<&'static str> {
+    let Some(body) = persisted else {
+        return Some("missing");
+    };
+    let expected = serde_json::to_value(result).ok()?;
+    match serde_json::from_slice::<serde_json::Value>(body) {
+        Ok(actual) if actual == expected => None,
+        _ => Some("mismatch"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use optimus_common::types::JobStatus;
+
+    fn sample_result() -> ExecutionResult {
+        ExecutionResult {
+            job_id: Uuid::nil(),
+            overall_status: JobStatus::Completed,
+            score: 10,
+            max_score: 10,
+            results: vec![],
+            scoring_formula: None,
+            reason: None,
+            feedback: None,
+            timing_summary: None,
+            suspicious: false,
+            cached: false,
+            verdict_summary: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_mode() {
+        assert_eq!(ResultStorageMode::parse("redis"), Ok(ResultStorageMode::Redis));
+        assert_eq!(ResultStorageMode::parse(""), Ok(ResultStorageMode::Redis));
+        assert_eq!(ResultStorageMode::parse(" Dual_Write "), Ok(ResultStorageMode::DualWrite));
+        assert_eq!(ResultStorageMode::parse("object_store"), Ok(ResultStorageMode::ObjectStore));
+        assert!(ResultStorageMode::parse("postgres").is_err());
+    }
+
+    #[test]
+    fn test_result_object_key() {
+        assert_eq!(
+            result_object_key(&Uuid::nil()),
+            "results/00000000-0000-0000-0000-000000000000.json"
+        );
+    }
+
+    #[test]
+    fn test_divergence() {
+        let result = sample_result();
+        let same = serde_json::to_vec_pretty(&result).unwrap();
+        assert_eq!(divergence(&result, Some(&same)), None);
+        assert_eq!(divergence(&result, None), Some("missing"));
+
+        let mut changed = result.clone();
+        changed.score = 0;
+        let changed = serde_json::to_vec(&changed).unwrap();
+        assert_eq!(divergence(&result, Some(&changed)), Some("mismatch"));
+        assert_eq!(divergence(&result, Some(b"not json")), Some("mismatch"));
+    }
+}
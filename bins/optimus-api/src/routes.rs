@@ -1,20 +1,79 @@
 // Route definitions for the Optimus API
 
 use axum::{
-    routing::{get, post},
+    extract::DefaultBodyLimit,
+    middleware,
+    routing::{get, post, put},
     Router,
 };
 use std::sync::Arc;
 
-use crate::{handlers, AppState};
+use crate::{auth, handlers, openapi, AppState};
+
+/// Request body cap for archive uploads (base64 inflates the 5 MB archive limit)
+const ARCHIVE_BODY_LIMIT: usize = 8 * 1024 * 1024;
 
 pub fn routes() -> Router<Arc<AppState>> {
     Router::new()
         .route("/execute", post(handlers::submit_job))
+        .route(
+            "/execute/archive",
+            post(handlers::submit_archive_job).layer(DefaultBodyLimit::max(ARCHIVE_BODY_LIMIT)),
+        )
         .route("/health", get(handlers::health_check))
         .route("/ready", get(handlers::readiness_check))
         .route("/metrics", get(handlers::metrics_handler))
+        .route("/metrics/forecast", get(handlers::get_submission_forecast))
+        .route("/workers", get(handlers::list_workers))
+        .route("/openapi.json", get(openapi::openapi_json))
+        .route("/docs", get(openapi::swagger_ui))
         .route("/job/:job_id", get(handlers::get_job_result))
+        .route("/job/:job_id/partial", get(handlers::get_partial_results))
         .route("/job/:job_id/debug", get(handlers::get_job_debug))
         .route("/job/:job_id/cancel", post(handlers::cancel_job))
+        .route("/batches", post(handlers::create_batch))
+        .route("/batches/:batch_id", get(handlers::get_batch))
+        .route("/batches/:batch_id/events", get(handlers::batch_events))
+        .route("/problems/:problem_id", get(handlers::get_problem))
+        .route("/problems/:problem_id/assets", get(handlers::list_problem_assets))
+        .route("/problems/:problem_id/assets/:name", get(handlers::get_problem_asset))
+        .route("/uploads", post(handlers::create_upload))
+        .route(
+            "/uploads/:upload_id",
+            get(handlers::get_upload)
+                .patch(handlers::append_upload_chunk)
+                .layer(DefaultBodyLimit::max(handlers::MAX_UPLOAD_CHUNK_BYTES)),
+        )
+        .route("/uploads/:upload_id/finalize", post(handlers::finalize_upload))
+        .merge(grader_routes())
+        .merge(admin_routes())
+}
+
+/// Grader routes - require a bearer token with the grader (or admin) role
+fn grader_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/job/:job_id/feedback", put(handlers::put_job_feedback))
+        .route_layer(middleware::from_fn(auth::require_grader))
+}
+
+/// Admin-only routes - require a bearer token with the admin role
+fn admin_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/admin/languages/:name/dry-run", post(handlers::dry_run_language))
+        .route("/admin/job/:job_id/force-complete", post(handlers::force_complete_job))
+        .route("/admin/job/:job_id/diagnostics", get(handlers::get_job_diagnostics))
+        .route("/admin/queue/:language/jobs", get(handlers::list_queued_jobs))
+        .route(
+            "/admin/queue/:language/jobs/:job_id",
+            get(handlers::get_queued_job).patch(handlers::edit_queued_job),
+        )
+        .route("/admin/queues", get(handlers::get_queue_depths))
+        .route("/admin/queues/:language/purge", post(handlers::purge_queues))
+        .route("/admin/analytics/languages", get(handlers::get_language_analytics))
+        .route("/admin/problems", post(handlers::create_problem))
+        .route(
+            "/admin/problems/:problem_id/assets/:name",
+            put(handlers::put_problem_asset).layer(DefaultBodyLimit::max(handlers::MAX_ASSET_BYTES)),
+        )
+        .route_layer(middleware::from_fn(auth::require_admin))
 }
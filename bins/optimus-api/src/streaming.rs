@@ -0,0 +1,90 @@
+// Streamed JSON responses for large payloads
+// Serializes straight into a chunked body instead of building the whole string first
+
+use axum::body::{Body, Bytes};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use serde::Serialize;
+use std::io::{self, Write};
+use tokio::sync::mpsc;
+use tracing::warn;
+
+/// Bytes handed to the body per chunk
+const CHUNK_BYTES: usize = 64 * 1024;
+/// Chunks buffered ahead of a slow client before serialization pauses
+const CHUNKS_IN_FLIGHT: usize = 4;
+
+/// `io::Write` that forwards fixed-size chunks to the response body
+struct ChunkWriter {
+    buf: Vec<u8>,
+    tx: mpsc::Sender<Result<Bytes, io::Error>>,
+}
+
+impl ChunkWriter {
+    fn send(&mut self) -> io::Result<()> {
+        if self.buf.is_empty() {
+            return Ok(());
+        }
+        let chunk = Bytes::from(std::mem::replace(&mut self.buf, Vec::with_capacity(CHUNK_BYTES)));
+        // The client went away - stop serializing
+        self.tx
+            .blocking_send(Ok(chunk))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "response body dropped"))
+    }
+}
+
+impl Write for ChunkWriter {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(data);
+        if self.buf.len() >= CHUNK_BYTES {
+            self.send()?;
+        }
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.send()
+    }
+}
+
+/// Respond with `value` as JSON, serialized on a blocking thread while the
+/// body streams out with chunked transfer encoding
+///
+/// Peak memory is a few chunks rather than the full serialized document.
+/// The status is sent before serialization runs, so a serialization failure
+/// can only cut the body short (logged, and the client sees invalid JSON).
+pub fn json<T: Serialize + Send + 'static>(status: StatusCode, value: T) -> Response {
+    let (tx, rx) = mpsc::channel(CHUNKS_IN_FLIGHT);
+    tokio::task::spawn_blocking(move || {
+        let mut writer = ChunkWriter { buf: Vec::with_capacity(CHUNK_BYTES), tx };
+        let written = serde_json::to_writer(&mut writer, &value).map_err(io::Error::from).and_then(|_| writer.flush());
+        if let Err(e) = written {
+            if e.kind() != io::ErrorKind::BrokenPipe {
+                warn!(error = %e, "Failed to stream JSON response");
+                let _ = writer.tx.blocking_send(Err(e));
+            }
+        }
+    });
+
+    let chunks = futures_util::stream::unfold(rx, |mut rx| async move { rx.recv().await.map(|chunk| (chunk, rx)) });
+    (status, [(header::CONTENT_TYPE, "application/json")], Body::from_stream(chunks)).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_streamed_body_matches_buffered_json() {
+        // Several chunks' worth, with a partial last chunk
+        let value: Vec<String> = (0..20_000).map(|i| format!("output line {}", i)).collect();
+        let expected = serde_json::to_vec(&value).unwrap();
+        assert!(expected.len() > 3 * CHUNK_BYTES);
+
+        let response = json(StatusCode::OK, value);
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(body.as_ref(), expected.as_slice());
+    }
+}
@@ -0,0 +1,179 @@
+// Submission validation plugins
+// Deployment-specific rules that can veto or annotate a submission after the built-in checks
+
+use regex::Regex;
+use std::collections::BTreeMap;
+
+use crate::auth::AuthContext;
+use crate::handlers::SubmitRequest;
+
+/// What a validator sees: the request (after problem limits are applied) and its caller
+pub struct Submission<'a> {
+    pub request: &'a SubmitRequest,
+    /// None for anonymous submissions
+    pub caller: Option<&'a AuthContext>,
+}
+
+/// A validator's decision on one submission
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Accept,
+    /// Accept, attaching key/value notes to the job (`metadata.annotations`)
+    Annotate(BTreeMap<String, String>),
+    /// Refuse with 422 and this error code/message
+    Reject { code: String, message: String },
+}
+
+/// A deployment-specific submission rule
+///
+/// Runs on the request path, so it must be cheap and must not block.
+/// Register implementations on the `ValidatorChain` built in `main.rs`.
+pub trait SubmissionValidator: Send + Sync {
+    /// Used in logs and the `optimus_validation_rejections_total` metric
+    fn name(&self) -> &str;
+
+    fn validate(&self, submission: &Submission<'_>) -> Verdict;
+}
+
+/// First rejection raised by a chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rejection {
+    pub validator: String,
+    pub code: String,
+    pub message: String,
+}
+
+/// Validators run in registration order; the first rejection wins and
+/// annotations from later validators replace earlier ones with the same key
+#[derive(Default)]
+pub struct ValidatorChain {
+    validators: Vec<Box<dyn SubmissionValidator>>,
+}
+
+impl ValidatorChain {
+    /// Chain with the built-in validators enabled by the environment
+    ///
+    /// - `VALIDATION_DENY_SOURCE_PATTERN`: reject sources matching this regex
+    ///   (`SOURCE_REJECTED`, message from `VALIDATION_DENY_SOURCE_MESSAGE`)
+    pub fn from_env() -> Result<Self, String> {
+        let mut chain = Self::default();
+        if let Some(pattern) = std::env::var("VALIDATION_DENY_SOURCE_PATTERN").ok().filter(|p| !p.is_empty()) {
+            let message = std::env::var("VALIDATION_DENY_SOURCE_MESSAGE")
+                .unwrap_or_else(|_| "Source code is not allowed by this deployment".to_string());
+            chain = chain.with(DenySourcePattern::new(&pattern, message)?);
+        }
+        Ok(chain)
+    }
+
+    /// Append a validator (runs after the ones already registered)
+    pub fn with(mut self, validator: impl SubmissionValidator + 'static) -> Self {
+        self.validators.push(Box::new(validator));
+        self
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.validators.iter().map(|v| v.name()).collect()
+    }
+
+    /// Run every validator; the merged annotations on success
+    pub fn run(&self, submission: &Submission<'_>) -> Result<BTreeMap<String, String>, Rejection> {
+        let mut annotations = BTreeMap::new();
+        for validator in &self.validators {
+            match validator.validate(submission) {
+                Verdict::Accept => {}
+                Verdict::Annotate(notes) => annotations.extend(notes),
+                Verdict::Reject { code, message } => {
+                    return Err(Rejection {
+                        validator: validator.name().to_string(),
+                        code,
+                        message,
+                    });
+                }
+            }
+        }
+        Ok(annotations)
+    }
+}
+
+/// Built-in: reject source code matching a pattern (e.g. banned imports)
+pub struct DenySourcePattern {
+    pattern: Regex,
+    message: String,
+}
+
+impl DenySourcePattern {
+    pub fn new(pattern: &str, message: String) -> Result<Self, String> {
+        let pattern = Regex::new(pattern)
+            .map_err(|e| format!("VALIDATION_DENY_SOURCE_PATTERN is not a valid regex: {}", e))?;
+        Ok(Self { pattern, message })
+    }
+}
+
+impl SubmissionValidator for DenySourcePattern {
+    fn name(&self) -> &str {
+        "deny_source_pattern"
+    }
+
+    fn validate(&self, submission: &Submission<'_>) -> Verdict {
+        if self.pattern.is_match(&submission.request.source_code) {
+            Verdict::Reject {
+                code: "SOURCE_REJECTED".to_string(),
+                message: self.message.clone(),
+            }
+        } else {
+            Verdict::Accept
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Tag(&'static str, &'static str);
+
+    impl SubmissionValidator for Tag {
+        fn name(&self) -> &str {
+            "tag"
+        }
+
+        fn validate(&self, _submission: &Submission<'_>) -> Verdict {
+            Verdict::Annotate(BTreeMap::from([(self.0.to_string(), self.1.to_string())]))
+        }
+    }
+
+    fn request(source_code: &str) -> SubmitRequest {
+        serde_json::from_value(serde_json::json!({
+            "language": "python",
+            "source_code": source_code,
+            "test_cases": [{"input": "", "expected_output": "ok"}],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_chain_merges_annotations_and_stops_at_first_rejection() {
+        let chain = ValidatorChain::default()
+            .with(Tag("course", "cs101"))
+            .with(Tag("course", "cs102"))
+            .with(DenySourcePattern::new(r"import\s+os", "no os".to_string()).unwrap())
+            .with(Tag("never", "reached"));
+        assert_eq!(chain.names(), vec!["tag", "tag", "deny_source_pattern", "tag"]);
+
+        let allowed = request("print('ok')");
+        let annotations = chain.run(&Submission { request: &allowed, caller: None }).unwrap();
+        assert_eq!(annotations.get("course").map(String::as_str), Some("cs102"));
+        assert_eq!(annotations.get("never").map(String::as_str), Some("reached"));
+
+        let denied = request("import os\nprint('ok')");
+        let rejection = chain.run(&Submission { request: &denied, caller: None }).unwrap_err();
+        assert_eq!(rejection.validator, "deny_source_pattern");
+        assert_eq!(rejection.code, "SOURCE_REJECTED");
+        assert_eq!(rejection.message, "no os");
+    }
+
+    #[test]
+    fn test_invalid_deny_pattern() {
+        assert!(DenySourcePattern::new("(", String::new()).is_err());
+    }
+}
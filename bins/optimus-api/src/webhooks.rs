@@ -0,0 +1,100 @@
+// Outbound webhook delivery
+// Drains the shared webhook queue so each event is POSTed by exactly one API replica
+
+use optimus_common::redis;
+use optimus_common::types::WebhookDelivery;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+use crate::metrics;
+
+const MAX_ATTEMPTS: u32 = 4;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Backoff before retry `attempt` (1-based): 1s, 4s, 16s
+fn retry_delay(attempt: u32) -> Duration {
+    Duration::from_secs(4u64.pow(attempt.saturating_sub(1)))
+}
+
+/// Consume queued webhooks until the task is dropped
+pub async fn dispatch_loop(client: ::redis::Client) {
+    // Dedicated connection - BLPOP must not stall request handlers
+    let mut conn = match ::redis::aio::ConnectionManager::new(client).await {
+        Ok(conn) => conn,
+        Err(e) => {
+            error!(error = %e, "Failed to connect webhook dispatcher to Redis");
+            return;
+        }
+    };
+
+    let http = match reqwest::Client::builder().timeout(REQUEST_TIMEOUT).build() {
+        Ok(http) => http,
+        Err(e) => {
+            error!(error = %e, "Failed to build webhook HTTP client");
+            return;
+        }
+    };
+
+    info!("Webhook dispatcher started");
+
+    loop {
+        match redis::pop_webhook(&mut conn, 5.0).await {
+            Ok(Some(delivery)) => {
+                // Deliver in the background so one slow endpoint doesn't hold up the queue
+                let http = http.clone();
+                tokio::spawn(async move { deliver(&http, &delivery).await });
+            }
+            Ok(None) => continue,
+            Err(e) => {
+                error!(error = %e, "Redis error in webhook dispatcher");
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+        }
+    }
+}
+
+/// POST one webhook, retrying non-2xx responses and transport errors
+async fn deliver(http: &reqwest::Client, delivery: &WebhookDelivery) {
+    for attempt in 1..=MAX_ATTEMPTS {
+        let response = http
+            .post(&delivery.url)
+            .header("Content-Type", "application/json")
+            .header("X-Optimus-Event", &delivery.event)
+            .body(delivery.body.clone())
+            .send()
+            .await;
+
+        match response {
+            Ok(resp) if resp.status().is_success() => {
+                info!(url = %delivery.url, event = %delivery.event, attempt, "Webhook delivered");
+                metrics::record_webhook_delivery(&delivery.event, "delivered");
+                return;
+            }
+            Ok(resp) => {
+                warn!(url = %delivery.url, event = %delivery.event, attempt, status = %resp.status(), "Webhook rejected");
+            }
+            Err(e) => {
+                warn!(url = %delivery.url, event = %delivery.event, attempt, error = %e, "Webhook request failed");
+            }
+        }
+
+        if attempt < MAX_ATTEMPTS {
+            tokio::time::sleep(retry_delay(attempt)).await;
+        }
+    }
+
+    error!(url = %delivery.url, event = %delivery.event, "Webhook delivery abandoned after {} attempts", MAX_ATTEMPTS);
+    metrics::record_webhook_delivery(&delivery.event, "failed");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_delay_backoff() {
+        assert_eq!(retry_delay(1), Duration::from_secs(1));
+        assert_eq!(retry_delay(2), Duration::from_secs(4));
+        assert_eq!(retry_delay(3), Duration::from_secs(16));
+    }
+}
@@ -0,0 +1,270 @@
+// Resource preset calibration for a language image
+// Runs a standard workload under several memory/CPU limits in Docker and
+// recommends memory_limit_mb / cpu_limit (and the derived pod resources) for languages.json
+use anyhow::{Context, Result, bail};
+use optimus_common::types::Language;
+use std::fs;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use crate::commands::{calculate_resources, load_languages_config, save_languages_config};
+
+/// Container memory limits tried, in MB
+const MEMORY_PRESETS_MB: [u32; 4] = [128, 256, 512, 1024];
+/// Container CPU limits tried, in cores
+const CPU_PRESETS: [f32; 3] = [0.5, 1.0, 2.0];
+/// A run slower than this is killed and counted as a timeout
+const RUN_TIMEOUT: Duration = Duration::from_secs(60);
+/// The cheapest CPU preset within this factor of the fastest one is recommended
+const CPU_SLOWDOWN_TOLERANCE: f64 = 1.25;
+
+// The workload counts the primes up to INPUT with a sieve: compile, allocation and CPU bound
+const INPUT: &str = "5000000";
+const EXPECTED_OUTPUT: &str = "348513";
+
+fn workload(language: Language) -> &'static str {
+    match language {
+        Language::Python => "n = int(input())\nsieve = [True] * (n + 1)\nsieve[0] = sieve[1] = False\nfor i in range(2, int(n ** 0.5) + 1):\n    if sieve[i]:\n        sieve[i * i :: i] = [False] * len(range(i * i, n + 1, i))\nprint(sum(sieve))\n",
+        Language::Java => "import java.util.Scanner;\npublic class Main {\n    public static void main(String[] args) {\n        int n = new Scanner(System.in).nextInt();\n        boolean[] composite = new boolean[n + 1];\n        int count = 0;\n        for (int i = 2; i <= n; i++) {\n            if (!composite[i]) {\n                count++;\n                for (long j = (long) i * i; j <= n; j += i) composite[(int) j] = true;\n            }\n        }\n        System.out.println(count);\n    }\n}\n",
+        Language::Rust => "use std::io::Read;\nfn main() {\n    let mut s = String::new();\n    std::io::stdin().read_to_string(&mut s).unwrap();\n    let n: usize = s.trim().parse().unwrap();\n    let mut composite = vec![false; n + 1];\n    let mut count = 0;\n    for i in 2..=n {\n        if !composite[i] {\n            count += 1;\n            let mut j = i * i;\n            while j <= n {\n                composite[j] = true;\n                j += i;\n            }\n        }\n    }\n    println!(\"{}\", count);\n}\n",
+    }
+}
+
+/// How a single workload run ended
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Outcome {
+    Passed(Duration),
+    OutOfMemory,
+    TimedOut,
+    /// Wrong output or a non-zero exit that wasn't the OOM killer
+    Failed,
+}
+
+struct PresetResult {
+    memory_mb: u32,
+    cpu: f32,
+    outcomes: Vec<Outcome>,
+}
+
+impl PresetResult {
+    fn all_passed(&self) -> bool {
+        self.outcomes.iter().all(|o| matches!(o, Outcome::Passed(_)))
+    }
+
+    fn median(&self) -> Option<Duration> {
+        let mut times: Vec<Duration> = self
+            .outcomes
+            .iter()
+            .filter_map(|o| match o {
+                Outcome::Passed(time) => Some(*time),
+                _ => None,
+            })
+            .collect();
+        times.sort();
+        times.get(times.len() / 2).copied()
+    }
+
+    fn describe(&self) -> String {
+        let count = |wanted: fn(&Outcome) -> bool| self.outcomes.iter().filter(|o| wanted(o)).count();
+        let passed = count(|o| matches!(o, Outcome::Passed(_)));
+        let mut notes = Vec::new();
+        for (label, n) in [
+            ("oom", count(|o| *o == Outcome::OutOfMemory)),
+            ("timeout", count(|o| *o == Outcome::TimedOut)),
+            ("failed", count(|o| *o == Outcome::Failed)),
+        ] {
+            if n > 0 {
+                notes.push(format!("{} {}", n, label));
+            }
+        }
+        let summary = format!("{}/{} ok", passed, self.outcomes.len());
+        if notes.is_empty() {
+            summary
+        } else {
+            format!("{} ({})", summary, notes.join(", "))
+        }
+    }
+}
+
+/// Calibrate `language`'s image: `runs` runs per preset, then print (and with
+/// `apply`, save) the recommended limits
+pub fn run(language_name: &str, runs: u32, apply: bool) -> Result<()> {
+    let language = Language::from_str(language_name)
+        .with_context(|| format!("No calibration workload for language '{}'", language_name))?;
+    let mut languages_json = load_languages_config()?;
+    let index = languages_json
+        .languages
+        .iter()
+        .position(|l| l.name == language_name)
+        .with_context(|| format!("Language '{}' not found in config/languages.json", language_name))?;
+    let image = languages_json.languages[index].image.clone();
+    if runs == 0 {
+        bail!("--runs must be at least 1");
+    }
+
+    let workdir = std::env::temp_dir().join(format!("optimus-calibrate-{}", std::process::id()));
+    let job_dir = workdir.join("optimus");
+    fs::create_dir_all(&job_dir).context("Failed to create a temporary directory")?;
+    fs::write(job_dir.join("source"), workload(language)).context("Failed to write the workload")?;
+
+    println!(
+        "📏 Calibrating {} ({}): {} preset(s) x {} run(s)\n",
+        language_name,
+        image,
+        MEMORY_PRESETS_MB.len() * CPU_PRESETS.len(),
+        runs
+    );
+    println!("{:<8} {:<6} {:<12} Runs", "Memory", "CPU", "Median");
+    println!("{}", "─".repeat(50));
+
+    let mut results = Vec::new();
+    for memory_mb in MEMORY_PRESETS_MB {
+        for cpu in CPU_PRESETS {
+            let mut outcomes = Vec::new();
+            for _ in 0..runs {
+                let outcome = run_once(&image, language_name, &workdir, memory_mb, cpu);
+                outcomes.push(outcome.with_context(|| format!("Run at {}MB / {} CPU failed to start", memory_mb, cpu))?);
+            }
+            let result = PresetResult { memory_mb, cpu, outcomes };
+            println!(
+                "{:<8} {:<6} {:<12} {}",
+                format!("{}MB", memory_mb),
+                cpu,
+                result.median().map(|t| format!("{}ms", t.as_millis())).unwrap_or_else(|| "-".to_string()),
+                result.describe()
+            );
+            results.push(result);
+        }
+    }
+    let _ = fs::remove_dir_all(&workdir);
+
+    let Some((memory_mb, cpu)) = recommend(&results) else {
+        bail!("No memory preset passed every run - check the image with `optimus-cli smoke` first");
+    };
+    let (resources, concurrency) = calculate_resources(memory_mb, cpu);
+
+    println!("\n✅ Recommended for {}:", language_name);
+    println!("   memory_limit_mb: {}", memory_mb);
+    println!("   cpu_limit: {}", cpu);
+    println!(
+        "   resources: requests {} / {}, limits {} / {}",
+        resources.requests.memory, resources.requests.cpu, resources.limits.memory, resources.limits.cpu
+    );
+    println!(
+        "   concurrency: max_parallel_jobs {}, max_parallel_tests {}",
+        concurrency.max_parallel_jobs, concurrency.max_parallel_tests
+    );
+
+    if apply {
+        let config = &mut languages_json.languages[index];
+        config.memory_limit_mb = memory_mb;
+        config.cpu_limit = cpu;
+        config.resources = resources;
+        config.concurrency = concurrency;
+        save_languages_config(&languages_json)?;
+        println!("\n💾 Updated config/languages.json (republish or redeploy workers to apply)");
+    } else {
+        println!("\nRerun with --apply to write these into config/languages.json");
+    }
+    Ok(())
+}
+
+/// Smallest memory preset that passed every run at every CPU preset, and at
+/// that memory the cheapest CPU preset not much slower than the fastest
+fn recommend(results: &[PresetResult]) -> Option<(u32, f32)> {
+    let memory_mb = MEMORY_PRESETS_MB.into_iter().find(|memory_mb| {
+        results.iter().filter(|r| r.memory_mb == *memory_mb).all(PresetResult::all_passed)
+    })?;
+
+    let at_memory: Vec<(f32, Duration)> = results
+        .iter()
+        .filter(|r| r.memory_mb == memory_mb)
+        .filter_map(|r| Some((r.cpu, r.median()?)))
+        .collect();
+    let fastest = at_memory.iter().map(|(_, time)| *time).min()?;
+    let cpu = at_memory
+        .iter()
+        .filter(|(_, time)| time.as_secs_f64() <= fastest.as_secs_f64() * CPU_SLOWDOWN_TOLERANCE)
+        .map(|(cpu, _)| *cpu)
+        .fold(f32::MAX, f32::min);
+    Some((memory_mb, cpu))
+}
+
+/// One workload run in a fresh container with the given limits
+///
+/// Mirrors the worker: the source is copied to /tmp/optimus/source, the input
+/// goes to stdin, and the image's runner compiles and runs it. Err only when
+/// Docker itself fails.
+fn run_once(image: &str, language: &str, workdir: &std::path::Path, memory_mb: u32, cpu: f32) -> Result<Outcome> {
+    let output = docker(&[
+        "create",
+        "-i",
+        "--network",
+        "none",
+        "--memory",
+        &format!("{}m", memory_mb),
+        "--memory-swap",
+        &format!("{}m", memory_mb),
+        "--cpus",
+        &cpu.to_string(),
+        "-e",
+        &format!("LANGUAGE={}", language),
+        "--label",
+        "optimus.calibrate=true",
+        image,
+    ])?;
+    let container_id = output.trim().to_string();
+
+    let outcome = (|| -> Result<Outcome> {
+        docker(&["cp", &workdir.join("optimus").display().to_string(), &format!("{}:/tmp/", container_id)])?;
+
+        let start = Instant::now();
+        let mut child = Command::new("docker")
+            .args(["start", "-a", "-i", &container_id])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .context("Failed to execute docker start. Is Docker installed and running?")?;
+        if let Some(mut stdin) = child.stdin.take() {
+            let _ = stdin.write_all(INPUT.as_bytes());
+        }
+
+        while child.try_wait()?.is_none() {
+            if start.elapsed() > RUN_TIMEOUT {
+                let _ = docker(&["kill", &container_id]);
+                let _ = child.wait();
+                return Ok(Outcome::TimedOut);
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        let elapsed = start.elapsed();
+        let output = child.wait_with_output()?;
+
+        let oom_killed = docker(&["inspect", "-f", "{{.State.OOMKilled}}", &container_id])?;
+        Ok(if oom_killed.trim() == "true" {
+            Outcome::OutOfMemory
+        } else if output.status.success() && String::from_utf8_lossy(&output.stdout).trim() == EXPECTED_OUTPUT {
+            Outcome::Passed(elapsed)
+        } else {
+            Outcome::Failed
+        })
+    })();
+
+    let _ = docker(&["rm", "-f", &container_id]);
+    outcome
+}
+
+/// Run a docker command, returning its stdout
+fn docker(args: &[&str]) -> Result<String> {
+    let output = Command::new("docker")
+        .args(args)
+        .stdin(Stdio::null())
+        .output()
+        .context("Failed to execute docker. Is Docker installed and running?")?;
+    if !output.status.success() {
+        bail!("docker {} failed: {}", args[0], String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
@@ -1,6 +1,8 @@
 // CLI commands for managing Optimus
 use anyhow::{Context, Result, bail};
+use optimus_common::types::{Comparison, Placement};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
@@ -11,6 +13,9 @@ pub struct LanguageExecution {
     pub command: String,
     pub args: Vec<String>,
     pub file_extension: String,
+    /// Default entry command for project archive submissions (run from /code)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_command: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -47,20 +52,137 @@ pub struct LanguageConfig {
     pub queue_name: String,
     pub memory_limit_mb: u32,
     pub cpu_limit: f32,
+    /// Per-job override ceilings (defaults: memory_limit_mb / cpu_limit)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_memory_limit_mb: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_cpu_limit: Option<f32>,
     pub resources: Resources,
     pub concurrency: Concurrency,
+    /// Cap on live containers of this language per Docker host (worker-side)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_containers: Option<u32>,
+    /// Diagnostics kept from containers killed on timeout (worker-side)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kill_diagnostics: Option<KillDiagnostics>,
+    /// OCI runtime registered with dockerd, e.g. "kata" (worker-side)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub runtime: Option<String>,
+    /// Placement-constrained worker pools, each rendered as its own deployment
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub placements: Vec<Placement>,
+    /// NVIDIA GPUs attached to the language's containers (worker-side)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gpu: Option<GpuConfig>,
+    /// Seccomp profile ("default" or a profile JSON path) for Docker/Podman containers (worker-side)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seccomp_profile: Option<String>,
+    /// pids limit and nofile/fsize/nproc ulimits (worker-side)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub process_limits: Option<ProcessLimits>,
+    /// Read-only rootfs and its tmpfs sizes (worker-side)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filesystem: Option<FilesystemConfig>,
+    /// Capability hardening and user namespace mode (worker-side)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub security: Option<SecurityConfig>,
+    /// Grading defaults for submissions that don't set their own (API-side)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub evaluation: Option<EvaluationDefaults>,
+}
+
+/// Unset fields are left to the worker's defaults
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GpuConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub count: Option<u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub capabilities: Vec<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub all_jobs: bool,
+}
+
+/// Unset fields are left to the worker's defaults
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KillDiagnostics {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grace_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_tail_kb: Option<usize>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<String>,
+}
+
+/// Unset fields are left to the worker's defaults
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessLimits {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pids_limit: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nofile: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fsize_mb: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nproc: Option<i64>,
+}
+
+/// Unset fields are left to the worker's defaults
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilesystemConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub readonly_rootfs: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tmp_mb: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub work_dir_mb: Option<u64>,
+}
+
+/// Unset fields are left to the worker's defaults
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecurityConfig {
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub legacy_privileges: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub userns_mode: Option<String>,
+}
+
+/// Unset fields are left to the submission
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvaluationDefaults {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comparison: Option<Comparison>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub ignore_case: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub accept_presentation_errors: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct LanguagesJson {
     pub languages: Vec<LanguageConfig>,
+    /// Tombstones of languages removed with `remove-lang --archive`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub archived: Vec<ArchivedLanguage>,
+}
+
+/// A removed language whose config entry, Dockerfile and manifests were
+/// moved under `archive/` (brought back by `restore-lang`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedLanguage {
+    pub name: String,
+    pub archived_at: String,
+    /// Archive directory, relative to the repository root
+    pub path: String,
 }
 
+/// Config entry of an archived language, inside its archive directory
+const ARCHIVED_CONFIG_FILE: &str = "language.json";
+
 /// Load languages configuration
-fn load_languages_config() -> Result<LanguagesJson> {
+pub(crate) fn load_languages_config() -> Result<LanguagesJson> {
     let config_path = Path::new("config/languages.json");
     if !config_path.exists() {
-        return Ok(LanguagesJson { languages: vec![] });
+        return Ok(LanguagesJson { languages: vec![], archived: vec![] });
     }
 
     let content = fs::read_to_string(config_path)
@@ -70,7 +192,7 @@ fn load_languages_config() -> Result<LanguagesJson> {
 }
 
 /// Save languages configuration
-fn save_languages_config(config: &LanguagesJson) -> Result<()> {
+pub(crate) fn save_languages_config(config: &LanguagesJson) -> Result<()> {
     let config_path = Path::new("config/languages.json");
     
     // Ensure config directory exists
@@ -88,6 +210,7 @@ fn save_languages_config(config: &LanguagesJson) -> Result<()> {
 }
 
 /// Add a new language to Optimus
+#[allow(clippy::too_many_arguments)]
 pub async fn add_language(
     name: &str,
     ext: &str,
@@ -137,12 +260,25 @@ pub async fn add_language(
             command: exec_command,
             args: vec![],
             file_extension,
+            project_command: None,
         },
         queue_name,
         memory_limit_mb: memory,
         cpu_limit: cpu,
+        max_memory_limit_mb: None,
+        max_cpu_limit: None,
         resources,
         concurrency,
+        max_containers: None,
+        kill_diagnostics: None,
+        runtime: None,
+        gpu: None,
+        seccomp_profile: None,
+        process_limits: None,
+        filesystem: None,
+        security: None,
+        evaluation: None,
+        placements: Vec::new(),
     };
 
     // Add to languages
@@ -183,7 +319,7 @@ pub async fn add_language(
 }
 
 /// Calculate resource allocations based on memory and CPU
-fn calculate_resources(memory_mb: u32, cpu: f32) -> (Resources, Concurrency) {
+pub(crate) fn calculate_resources(memory_mb: u32, cpu: f32) -> (Resources, Concurrency) {
     // Resource requests are 50% of limits
     let memory_request = format!("{}Mi", memory_mb * 2);
     let memory_limit = format!("{}Gi", (memory_mb as f32 * 4.0 / 1024.0).ceil() as u32);
@@ -218,8 +354,16 @@ fn calculate_resources(memory_mb: u32, cpu: f32) -> (Resources, Concurrency) {
 }
 
 /// Remove a language from Optimus
-pub async fn remove_language(name: &str, yes: bool) -> Result<()> {
-    println!("🗑️  Removing language: {}", name);
+///
+/// With `archive`, the config entry, Dockerfile and manifests are moved under
+/// `archive/{name}/{timestamp}/` and a tombstone is left in languages.json,
+/// so `restore-lang` can bring the language back; the Docker image is kept.
+pub async fn remove_language(name: &str, yes: bool, archive: bool) -> Result<()> {
+    if archive {
+        println!("📦 Archiving language: {}", name);
+    } else {
+        println!("🗑️  Removing language: {}", name);
+    }
 
     // Load existing config
     let mut languages_json = load_languages_config()?;
@@ -231,13 +375,19 @@ pub async fn remove_language(name: &str, yes: bool) -> Result<()> {
 
     let lang_version = languages_json.languages[lang_index].version.clone();
     let lang_dockerfile_path = languages_json.languages[lang_index].dockerfile_path.clone();
+    let archived_at = chrono::Utc::now();
+    let archive_dir = PathBuf::from(format!("archive/{}/{}", name, archived_at.format("%Y%m%dT%H%M%SZ")));
 
     // Confirm deletion
     if !yes {
-        print!("⚠️  This will remove:\n");
-        print!("  - Config entry in languages.json\n");
-        print!("  - Dockerfile at {}\n", lang_dockerfile_path);
-        print!("  - K8s manifests (worker-deployment-{}.yaml, KEDA ScaledObjects)\n", name);
+        if archive {
+            println!("⚠️  This will move to {}:", archive_dir.display());
+        } else {
+            println!("⚠️  This will remove:");
+        }
+        println!("  - Config entry in languages.json");
+        println!("  - Dockerfile at {}", lang_dockerfile_path);
+        println!("  - K8s manifests (worker-deployment-{}.yaml, KEDA ScaledObjects)", name);
         print!("\nContinue? (y/N): ");
         io::stdout().flush()?;
 
@@ -251,32 +401,50 @@ pub async fn remove_language(name: &str, yes: bool) -> Result<()> {
     }
 
     // Remove from config
-    languages_json.languages.remove(lang_index);
-    println!("📝 Removing from config/languages.json...");
-    save_languages_config(&languages_json)?;
+    let removed = languages_json.languages.remove(lang_index);
+    if archive {
+        fs::create_dir_all(&archive_dir)
+            .with_context(|| format!("Failed to create {}", archive_dir.display()))?;
+        let entry = serde_json::to_string_pretty(&removed).context("Failed to serialize config entry")?;
+        fs::write(archive_dir.join(ARCHIVED_CONFIG_FILE), entry)
+            .with_context(|| format!("Failed to write {}", archive_dir.join(ARCHIVED_CONFIG_FILE).display()))?;
+    }
 
-    // Remove Dockerfile directory
+    // Remove (or archive) the Dockerfile directory and K8s manifests
     let dockerfile_dir = PathBuf::from(format!("dockerfiles/{}", name));
-    if dockerfile_dir.exists() {
-        println!("🐳 Removing {}...", dockerfile_dir.display());
-        fs::remove_dir_all(&dockerfile_dir)
-            .with_context(|| format!("Failed to remove {}", dockerfile_dir.display()))?;
+    let paths = std::iter::once(dockerfile_dir).chain(language_manifests(name).into_iter().map(PathBuf::from));
+    for path in paths.filter(|path| path.exists()) {
+        if archive {
+            println!("📦 Archiving {}...", path.display());
+            move_path(&path, &archive_dir.join(&path))?;
+        } else if path.is_dir() {
+            println!("🐳 Removing {}...", path.display());
+            fs::remove_dir_all(&path)
+                .with_context(|| format!("Failed to remove {}", path.display()))?;
+        } else {
+            println!("📊 Removing {}...", path.display());
+            fs::remove_file(&path)
+                .with_context(|| format!("Failed to remove {}", path.display()))?;
+        }
     }
 
-    // Remove K8s manifests
-    let manifests = vec![
-        format!("k8s/worker-deployment-{}.yaml", name),
-        format!("k8s/keda/scaled-object-{}.yaml", name),
-        format!("k8s/keda/scaled-object-{}-retry.yaml", name),
-    ];
+    if archive {
+        languages_json.archived.push(ArchivedLanguage {
+            name: name.to_string(),
+            archived_at: archived_at.to_rfc3339(),
+            path: archive_dir.display().to_string(),
+        });
+    }
+    println!("📝 Updating config/languages.json...");
+    save_languages_config(&languages_json)?;
 
-    for manifest_path in manifests {
-        let path = Path::new(&manifest_path);
-        if path.exists() {
-            println!("📊 Removing {}...", manifest_path);
-            fs::remove_file(path)
-                .with_context(|| format!("Failed to remove {}", manifest_path))?;
-        }
+    if archive {
+        println!("\n✅ Language '{}' archived to {}", name, archive_dir.display());
+        println!("   Docker image optimus-{}:{} was kept", name, lang_version);
+        println!("\n📋 Next steps:");
+        println!("  1. Apply changes to K8s cluster if deployed");
+        println!("  2. Undo with: optimus-cli restore-lang --name {}", name);
+        return Ok(());
     }
 
     // Remove Docker image
@@ -317,6 +485,97 @@ pub async fn remove_language(name: &str, yes: bool) -> Result<()> {
     Ok(())
 }
 
+/// Bring back the most recently archived copy of a language
+pub async fn restore_language(name: &str) -> Result<()> {
+    println!("♻️  Restoring language: {}", name);
+
+    let mut languages_json = load_languages_config()?;
+    if languages_json.languages.iter().any(|l| l.name == name) {
+        bail!("Language '{}' is already configured - remove it before restoring an archived copy", name);
+    }
+    let tombstone_index = languages_json.archived.iter()
+        .rposition(|archived| archived.name == name)
+        .ok_or_else(|| anyhow::anyhow!("No archived copy of '{}' in languages.json", name))?;
+    let archive_dir = PathBuf::from(&languages_json.archived[tombstone_index].path);
+
+    let entry_path = archive_dir.join(ARCHIVED_CONFIG_FILE);
+    let entry = fs::read_to_string(&entry_path)
+        .with_context(|| format!("Failed to read {}", entry_path.display()))?;
+    let config: LanguageConfig = serde_json::from_str(&entry)
+        .with_context(|| format!("Failed to parse {}", entry_path.display()))?;
+
+    // Refuse before moving anything if a file has been recreated since
+    let mut files = Vec::new();
+    archived_files(&archive_dir, Path::new(""), &mut files)?;
+    files.retain(|file| file.as_os_str() != ARCHIVED_CONFIG_FILE);
+    if let Some(existing) = files.iter().find(|file| file.exists()) {
+        bail!("{} already exists - move it away before restoring", existing.display());
+    }
+
+    for file in &files {
+        println!("📦 Restoring {}...", file.display());
+        move_path(&archive_dir.join(file), file)?;
+    }
+
+    languages_json.languages.push(config);
+    languages_json.archived.remove(tombstone_index);
+    println!("📝 Updating config/languages.json...");
+    save_languages_config(&languages_json)?;
+    fs::remove_dir_all(&archive_dir)
+        .with_context(|| format!("Failed to remove {}", archive_dir.display()))?;
+
+    println!("\n✅ Language '{}' restored", name);
+    println!("\n📋 Next steps:");
+    println!("  1. Rebuild the image if it was removed: optimus-cli build-image --name {}", name);
+    println!("  2. Apply the manifests: kubectl apply -f k8s/ -f k8s/keda/");
+
+    Ok(())
+}
+
+/// K8s manifests of a language, including placement pools rendered by render-k8s
+fn language_manifests(name: &str) -> Vec<String> {
+    let mut manifests = vec![
+        format!("k8s/worker-deployment-{}.yaml", name),
+        format!("k8s/keda/scaled-object-{}.yaml", name),
+        format!("k8s/keda/scaled-object-{}-retry.yaml", name),
+    ];
+    for (dir, prefix) in [("k8s", "worker-deployment"), ("k8s/keda", "scaled-object")] {
+        let pool_prefix = format!("{}-{}-pool-", prefix, name);
+        if let Ok(entries) = fs::read_dir(dir) {
+            manifests.extend(
+                entries
+                    .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+                    .filter(|file| file.starts_with(&pool_prefix) && file.ends_with(".yaml"))
+                    .map(|file| format!("{}/{}", dir, file)),
+            );
+        }
+    }
+    manifests
+}
+
+/// Move a file or directory, creating the destination's parents
+fn move_path(from: &Path, to: &Path) -> Result<()> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::rename(from, to).with_context(|| format!("Failed to move {} to {}", from.display(), to.display()))
+}
+
+/// Files under `root/relative`, as paths relative to `root`
+fn archived_files(root: &Path, relative: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    let dir = root.join(relative);
+    for entry in fs::read_dir(&dir).with_context(|| format!("Failed to read {}", dir.display()))? {
+        let entry = entry?;
+        let path = relative.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            archived_files(root, &path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
 /// List all configured languages
 pub async fn list_languages() -> Result<()> {
     let languages_json = load_languages_config()?;
@@ -347,263 +606,316 @@ pub async fn list_languages() -> Result<()> {
     Ok(())
 }
 
-/// Generate Dockerfile for the language
-fn generate_dockerfile(
-    dockerfile_path: &Path,
-    name: &str,
-    version: &str,
-    base_image: Option<&str>,
-) -> Result<()> {
-    // Create directory
-    if let Some(parent) = dockerfile_path.parent() {
-        fs::create_dir_all(parent)?;
+/// List sandbox containers on this Docker host, grouped by language
+///
+/// Reads the `optimus.*` labels the worker engine puts on every container,
+/// so it shows what is actually running whatever the workers believe
+pub fn list_containers(language: Option<&str>) -> Result<()> {
+    let mut args = vec![
+        "ps".to_string(),
+        "--all".to_string(),
+        "--filter".to_string(),
+        "label=optimus.managed=true".to_string(),
+    ];
+    if let Some(language) = language {
+        args.push("--filter".to_string());
+        args.push(format!("label=optimus.language={}", language));
     }
+    args.push("--format".to_string());
+    args.push(r#"{{.ID}}\t{{.Label "optimus.language"}}\t{{.Label "optimus.worker"}}\t{{.State}}\t{{.RunningFor}}"#.to_string());
 
-    let dockerfile_content = match name {
-        "python" => generate_python_dockerfile(version),
-        "java" => generate_java_dockerfile(version),
-        "rust" => generate_rust_dockerfile(version),
-        "cpp" => generate_cpp_dockerfile(version),
-        "go" => generate_go_dockerfile(version),
-        "javascript" | "node" => generate_node_dockerfile(version),
-        _ => {
-            // Generic Dockerfile
-            let default_base = format!("{}:{}", name, version);
-            let base = base_image.unwrap_or(&default_base);
-            format!(
-                r#"# GENERATED BY optimus-cli — DO NOT EDIT
-FROM {}
-
-WORKDIR /app
+    let output = Command::new("docker")
+        .args(&args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .context("Failed to execute docker ps. Is Docker installed and running?")?;
+    if !output.status.success() {
+        bail!("docker ps failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+    }
 
-# Copy runner script (if exists) from dockerfiles/{1}/ (build context is repo root)
-COPY dockerfiles/{1}/runner.* /app/
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut rows: Vec<Vec<&str>> = stdout
+        .lines()
+        .map(|line| line.split('\t').collect::<Vec<_>>())
+        .filter(|fields| fields.len() == 5)
+        .collect();
+    if rows.is_empty() {
+        println!("No Optimus containers on this host.");
+        return Ok(());
+    }
+    rows.sort_by(|a, b| a[1].cmp(b[1]).then(a[2].cmp(b[2])));
 
-# Set execution command
-CMD ["{1}"]
-"#,
-                base, name
-            )
-        }
-    };
+    println!("{:<14} {:<10} {:<36} {:<10} {:<20}", "Container", "Language", "Worker", "State", "Age");
+    println!("{}", "─".repeat(94));
+    for row in &rows {
+        println!("{:<14} {:<10} {:<36} {:<10} {:<20}", row[0], row[1], row[2], row[3], row[4]);
+    }
 
-    fs::write(dockerfile_path, dockerfile_content)
-        .context("Failed to write Dockerfile")?;
+    let mut live: BTreeMap<&str, usize> = BTreeMap::new();
+    for row in rows.iter().filter(|row| matches!(row[3], "created" | "running")) {
+        *live.entry(row[1]).or_default() += 1;
+    }
+    println!();
+    for (language, count) in &live {
+        println!("  {}: {} live", language, count);
+    }
+    println!("\nTotal: {} container(s)", rows.len());
 
     Ok(())
 }
 
-/// Generate Python Dockerfile
-fn generate_python_dockerfile(version: &str) -> String {
-    format!(
-        r#"# GENERATED BY optimus-cli — DO NOT EDIT
-# Python Execution Environment - Optimized for Cold Start
-FROM python:{}
-
-# Set environment variables for performance
-ENV PYTHONUNBUFFERED=1 \
-    PYTHONDONTWRITEBYTECODE=1 \
-    PIP_NO_CACHE_DIR=1 \
-    PIP_DISABLE_PIP_VERSION_CHECK=1 \
-    LANGUAGE=python
-
-WORKDIR /code
-
-# Install common packages (pre-installed at build time, not runtime)
-RUN pip install --no-cache-dir \
-    pytest==7.4.3 \
-    numpy==1.26.2 \
-    requests==2.31.0
-
-# Copy universal runner script (build context is repo root)
-COPY dockerfiles/runner.sh /runner.sh
-RUN chmod +x /runner.sh
-
-# Create non-root user for security
-RUN useradd -m -u 1000 optimus && \
-    chown -R optimus:optimus /code
-
-USER optimus
-
-# Use universal runner
-ENTRYPOINT ["/runner.sh"]
-"#,
-        version
-    )
-}
-
-/// Generate Java Dockerfile
-fn generate_java_dockerfile(version: &str) -> String {
-    format!(
-        r#"# GENERATED BY optimus-cli — DO NOT EDIT
-# Java Execution Environment - Optimized for Cold Start
-FROM eclipse-temurin:{}-jdk-alpine
+/// Publish config/languages.json to Redis
+/// Workers running with LANGUAGE_CONFIG_SOURCE=redis pick it up on their next refresh
+pub async fn publish_config(redis_url: Option<&str>) -> Result<()> {
+    let languages_json = load_languages_config()?;
+    if languages_json.languages.is_empty() {
+        bail!("config/languages.json has no languages - refusing to publish an empty config");
+    }
 
-# Set environment variables for performance
-ENV JAVA_TOOL_OPTIONS="-XX:+UseContainerSupport -XX:MaxRAMPercentage=75.0 -XX:+TieredCompilation -XX:TieredStopAtLevel=1" \
-    LANGUAGE=java
+    let body = serde_json::to_string_pretty(&languages_json)
+        .context("Failed to serialize languages.json")?;
 
-WORKDIR /code
+    let redis_url = redis_url
+        .map(|u| u.to_string())
+        .or_else(|| std::env::var("REDIS_URL").ok())
+        .unwrap_or_else(|| "redis://127.0.0.1:6379".to_string());
 
-# Install JUnit and bash at build time (bash needed for runner.sh)
-RUN apk add --no-cache wget bash && \
-    wget -q https://repo1.maven.org/maven2/junit/junit/4.13.2/junit-4.13.2.jar -P /opt/ && \
-    wget -q https://repo1.maven.org/maven2/org/hamcrest/hamcrest-core/1.3/hamcrest-core-1.3.jar -P /opt/ && \
-    apk del wget && \
-    rm -rf /var/cache/apk/*
+    println!("📡 Publishing {} language(s) to {}...", languages_json.languages.len(), redis_url);
 
-ENV CLASSPATH=/opt/junit-4.13.2.jar:/opt/hamcrest-core-1.3.jar
+    let client = redis::Client::open(redis_url.as_str())
+        .context("Invalid Redis URL")?;
+    let mut conn = redis::aio::ConnectionManager::new(client).await
+        .context("Failed to connect to Redis")?;
 
-# Copy universal runner script (build context is repo root)
-COPY dockerfiles/runner.sh /runner.sh
-RUN chmod +x /runner.sh
+    let version = optimus_common::redis::publish_language_config(&mut conn, &body).await
+        .context("Failed to publish language config")?;
 
-# Create non-root user for security
-RUN adduser -D -u 1000 optimus && \
-    chown -R optimus:optimus /code
-
-USER optimus
+    println!("✅ Published language config version {}", version);
+    println!("   Workers with LANGUAGE_CONFIG_SOURCE=redis will apply it on their next refresh");
 
-# Use universal runner
-ENTRYPOINT ["/runner.sh"]
-"#,
-        version
-    )
+    Ok(())
 }
 
-/// Generate C++ Dockerfile
-fn generate_cpp_dockerfile(version: &str) -> String {
-    format!(
-        r#"# GENERATED BY optimus-cli — DO NOT EDIT
-# C++ Execution Environment
-FROM gcc:{}
-
-# Set environment variables
-ENV LANGUAGE=cpp
-
-WORKDIR /code
-
-# Install necessary build tools
-RUN apt-get update && apt-get install -y --no-install-recommends \
-    build-essential \
+/// Shared base image every language image copies its runtime layer from
+pub const BASE_IMAGE: &str = "optimus-base:latest";
+const BASE_DOCKERFILE_PATH: &str = "dockerfiles/Dockerfile.base";
+
+/// Base image: runner.sh, a static tini and the Firecracker guest init
+/// (vm-init.sh) under /opt/optimus
+///
+/// Built `FROM scratch`, so language images pull in exactly these files.
+/// Because the COPY below is byte-identical in every language image, the
+/// layer has the same digest everywhere and worker nodes fetch it once.
+const BASE_DOCKERFILE: &str = r#"# syntax=docker/dockerfile:1
+# GENERATED BY optimus-cli — DO NOT EDIT
+# Optimus Base Layer - universal runner + init, shared by every language image
+FROM debian:bookworm-slim AS tini
+RUN apt-get update && apt-get install -y --no-install-recommends tini \
     && rm -rf /var/lib/apt/lists/*
 
-# Copy universal runner script (build context is repo root)
-COPY dockerfiles/runner.sh /runner.sh
-RUN chmod +x /runner.sh
-
-# Create non-root user for security
-RUN useradd -m -u 1000 optimus && \
-    chown -R optimus:optimus /code
-
-USER optimus
+FROM scratch
+COPY --from=tini --chmod=755 /usr/bin/tini-static /opt/optimus/tini
+COPY --chmod=755 dockerfiles/runner.sh /opt/optimus/runner.sh
+COPY --chmod=755 dockerfiles/vm-init.sh /opt/optimus/vm-init
+"#;
+
+/// Language-specific parts of a generated Dockerfile
+///
+/// Everything else (shared layer, non-root user, entrypoint) comes from
+/// `render_dockerfile`, so all images stay structurally identical.
+struct LanguageImage {
+    title: &'static str,
+    from: String,
+    /// `KEY=value` pairs, LANGUAGE included
+    env: Vec<String>,
+    /// Optional build-time setup block (comment + RUN/ENV lines)
+    setup: Option<&'static str>,
+    /// Alpine images use busybox `adduser` instead of `useradd`
+    alpine: bool,
+}
 
-# Use universal runner
-ENTRYPOINT ["/runner.sh"]
-"#,
-        version
-    )
+fn env(pairs: &[&str]) -> Vec<String> {
+    pairs.iter().map(|p| p.to_string()).collect()
 }
 
-/// Generate Go Dockerfile
-fn generate_go_dockerfile(version: &str) -> String {
+fn render_dockerfile(image: &LanguageImage) -> String {
+    let env = image.env.join(" \\\n    ");
+    let setup = image.setup.map(|s| format!("{}\n\n", s.trim_end())).unwrap_or_default();
+    let add_user = if image.alpine {
+        "adduser -D -u 1000 optimus"
+    } else {
+        "useradd -m -u 1000 optimus"
+    };
+
     format!(
-        r#"# GENERATED BY optimus-cli — DO NOT EDIT
-# Go Execution Environment
-FROM golang:{}
+        r#"# syntax=docker/dockerfile:1
+# GENERATED BY optimus-cli — DO NOT EDIT
+# {title}
+FROM {from}
 
-# Set environment variables
-ENV GO111MODULE=on \
-    CGO_ENABLED=0 \
-    LANGUAGE=go
+# Lets the worker image GC recognise Optimus images, dangling rebuilds included
+LABEL optimus.managed="true"
+
+ENV {env}
 
 WORKDIR /code
 
-# Copy universal runner script (build context is repo root)
-COPY dockerfiles/runner.sh /runner.sh
-RUN chmod +x /runner.sh
+{setup}# Shared Optimus layer: universal runner + tini (from {base})
+COPY --from={base} /opt/optimus/ /opt/optimus/
 
 # Create non-root user for security
-RUN useradd -m -u 1000 optimus && \
+RUN {add_user} && \
     chown -R optimus:optimus /code
 
 USER optimus
 
-# Use universal runner
-ENTRYPOINT ["/runner.sh"]
+# tini reaps zombies and forwards signals; the runner does the rest
+ENTRYPOINT ["/opt/optimus/tini", "--", "/opt/optimus/runner.sh"]
 "#,
-        version
+        title = image.title,
+        from = image.from,
+        env = env,
+        setup = setup,
+        base = BASE_IMAGE,
+        add_user = add_user,
     )
 }
 
-/// Generate Node.js Dockerfile
-fn generate_node_dockerfile(version: &str) -> String {
-    format!(
-        r#"# GENERATED BY optimus-cli — DO NOT EDIT
-# Node.js Execution Environment
-FROM node:{}
-
-# Set environment variables
-ENV LANGUAGE=javascript
+/// Generate Dockerfile for the language
+fn generate_dockerfile(
+    dockerfile_path: &Path,
+    name: &str,
+    version: &str,
+    base_image: Option<&str>,
+) -> Result<()> {
+    // Create directory
+    if let Some(parent) = dockerfile_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
 
-WORKDIR /code
+    let image = match name {
+        "python" => python_image(version),
+        "java" => java_image(version),
+        "rust" => rust_image(version),
+        "cpp" => cpp_image(version),
+        "go" => go_image(version),
+        "javascript" | "node" => node_image(version),
+        _ => {
+            // Generic image - the runner must already know how to run it
+            LanguageImage {
+                title: "Generic Execution Environment",
+                from: base_image.map(str::to_string).unwrap_or_else(|| format!("{}:{}", name, version)),
+                env: vec![format!("LANGUAGE={}", name)],
+                setup: None,
+                alpine: false,
+            }
+        }
+    };
 
-# Install necessary tools
-RUN npm install -g typescript ts-node
+    fs::write(dockerfile_path, render_dockerfile(&image))
+        .context("Failed to write Dockerfile")?;
 
-# Copy universal runner script (build context is repo root)
-COPY dockerfiles/runner.sh /runner.sh
-RUN chmod +x /runner.sh
+    Ok(())
+}
 
-# Create non-root user for security
-RUN useradd -m -u 1000 optimus && \
-    chown -R optimus:optimus /code
+fn python_image(version: &str) -> LanguageImage {
+    LanguageImage {
+        title: "Python Execution Environment - Optimized for Cold Start",
+        from: format!("python:{}", version),
+        env: env(&[
+            "PYTHONUNBUFFERED=1",
+            "PYTHONDONTWRITEBYTECODE=1",
+            "PIP_NO_CACHE_DIR=1",
+            "PIP_DISABLE_PIP_VERSION_CHECK=1",
+            "LANGUAGE=python",
+        ]),
+        setup: Some(
+            r#"# Install common packages (pre-installed at build time, not runtime)
+RUN pip install --no-cache-dir \
+    pytest==7.4.3 \
+    numpy==1.26.2 \
+    requests==2.31.0"#,
+        ),
+        alpine: false,
+    }
+}
 
-USER optimus
+fn java_image(version: &str) -> LanguageImage {
+    LanguageImage {
+        title: "Java Execution Environment - Optimized for Cold Start",
+        from: format!("eclipse-temurin:{}-jdk-alpine", version),
+        env: env(&[
+            r#"JAVA_TOOL_OPTIONS="-XX:+UseContainerSupport -XX:MaxRAMPercentage=75.0 -XX:+TieredCompilation -XX:TieredStopAtLevel=1""#,
+            "LANGUAGE=java",
+        ]),
+        setup: Some(
+            r#"# Install JUnit and bash at build time (bash needed for runner.sh)
+RUN apk add --no-cache wget bash && \
+    wget -q https://repo1.maven.org/maven2/junit/junit/4.13.2/junit-4.13.2.jar -P /opt/ && \
+    wget -q https://repo1.maven.org/maven2/org/hamcrest/hamcrest-core/1.3/hamcrest-core-1.3.jar -P /opt/ && \
+    apk del wget && \
+    rm -rf /var/cache/apk/*
 
-# Use universal runner
-ENTRYPOINT ["/runner.sh"]
-"#,
-        version
-    )
+ENV CLASSPATH=/opt/junit-4.13.2.jar:/opt/hamcrest-core-1.3.jar"#,
+        ),
+        alpine: true,
+    }
 }
 
-/// Generate Rust Dockerfile
-fn generate_rust_dockerfile(version: &str) -> String {
-    format!(
-        r#"# GENERATED BY optimus-cli — DO NOT EDIT
-# Rust Execution Environment - Optimized for Code Execution
-FROM rust:{}
+fn cpp_image(version: &str) -> LanguageImage {
+    // gcc images already ship the full toolchain
+    LanguageImage {
+        title: "C++ Execution Environment",
+        from: format!("gcc:{}", version),
+        env: env(&["LANGUAGE=cpp"]),
+        setup: None,
+        alpine: false,
+    }
+}
 
-# Set environment variables for performance
-ENV CARGO_HOME=/usr/local/cargo \
-    RUSTUP_HOME=/usr/local/rustup \
-    PATH=/usr/local/cargo/bin:$PATH \
-    RUSTFLAGS="-C opt-level=2 -C debuginfo=0" \
-    LANGUAGE=rust
+fn go_image(version: &str) -> LanguageImage {
+    LanguageImage {
+        title: "Go Execution Environment",
+        from: format!("golang:{}", version),
+        env: env(&["GO111MODULE=on", "CGO_ENABLED=0", "LANGUAGE=go"]),
+        setup: None,
+        alpine: false,
+    }
+}
 
-WORKDIR /code
+fn node_image(version: &str) -> LanguageImage {
+    LanguageImage {
+        title: "Node.js Execution Environment",
+        from: format!("node:{}", version),
+        env: env(&["LANGUAGE=javascript"]),
+        setup: Some(
+            r#"# Install necessary tools
+RUN npm install -g typescript ts-node"#,
+        ),
+        alpine: false,
+    }
+}
 
-# Install required packages
+fn rust_image(version: &str) -> LanguageImage {
+    LanguageImage {
+        title: "Rust Execution Environment - Optimized for Code Execution",
+        from: format!("rust:{}", version),
+        env: env(&[
+            "CARGO_HOME=/usr/local/cargo",
+            "RUSTUP_HOME=/usr/local/rustup",
+            "PATH=/usr/local/cargo/bin:$PATH",
+            r#"RUSTFLAGS="-C opt-level=2 -C debuginfo=0""#,
+            "LANGUAGE=rust",
+        ]),
+        setup: Some(
+            r#"# Install required packages
 RUN apt-get update && apt-get install -y --no-install-recommends \
     ca-certificates \
-    && rm -rf /var/lib/apt/lists/*
-
-# Copy universal runner script (build context is repo root)
-COPY dockerfiles/runner.sh /runner.sh
-RUN chmod +x /runner.sh
-
-# Create non-root user for security
-RUN useradd -m -u 1000 optimus && \
-    chown -R optimus:optimus /code
-
-USER optimus
-
-# Use universal runner
-ENTRYPOINT ["/runner.sh"]
-"#,
-        version
-    )
+    && rm -rf /var/lib/apt/lists/*"#,
+        ),
+        alpine: false,
+    }
 }
 
 /// Build Docker image for a language
@@ -624,6 +936,9 @@ pub async fn build_docker_image(name: &str, no_cache: bool) -> Result<()> {
         bail!("Dockerfile not found at {}. Generate it first with add-lang command.", dockerfile_path.display());
     }
     
+    // Language images copy their runtime layer from the base, so it goes first
+    build_base_image(no_cache)?;
+
     // Build image tag
     let image_tag = format!("optimus-{}:{}", name, lang_config.version);
     
@@ -658,6 +973,7 @@ pub async fn build_docker_image(name: &str, no_cache: bool) -> Result<()> {
     // Execute docker build
     let status = Command::new("docker")
         .args(&docker_args)
+        .env("DOCKER_BUILDKIT", "1")
         .stdout(Stdio::inherit())
         .stderr(Stdio::inherit())
         .status()
@@ -674,7 +990,7 @@ pub async fn build_docker_image(name: &str, no_cache: bool) -> Result<()> {
     // Verify image exists
     println!("\n🔍 Verifying image...");
     let verify_status = Command::new("docker")
-        .args(&["images", &image_tag, "--format", "{{.Repository}}:{{.Tag}}"])
+        .args(["images", &image_tag, "--format", "{{.Repository}}:{{.Tag}}"])
         .stdout(Stdio::inherit())
         .stderr(Stdio::inherit())
         .status();
@@ -685,3 +1001,34 @@ pub async fn build_docker_image(name: &str, no_cache: bool) -> Result<()> {
     
     Ok(())
 }
+
+/// Build (or refresh) the shared base image
+///
+/// The Dockerfile is regenerated every time so runner.sh changes and CLI
+/// upgrades land without manual steps; Docker's cache keeps no-op rebuilds cheap.
+fn build_base_image(no_cache: bool) -> Result<()> {
+    println!("🧱 Building base image: {}", BASE_IMAGE);
+    fs::write(BASE_DOCKERFILE_PATH, BASE_DOCKERFILE)
+        .context("Failed to write base Dockerfile")?;
+
+    let mut docker_args = vec!["build", "-t", BASE_IMAGE, "-f", BASE_DOCKERFILE_PATH];
+    if no_cache {
+        docker_args.push("--no-cache");
+    }
+    docker_args.push(".");
+
+    let status = Command::new("docker")
+        .args(&docker_args)
+        .env("DOCKER_BUILDKIT", "1")
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .status()
+        .context("Failed to execute docker build. Is Docker installed and running?")?;
+
+    if !status.success() {
+        bail!("Base image build failed with exit code: {:?}", status.code());
+    }
+
+    println!("✅ Base image ready\n");
+    Ok(())
+}
@@ -0,0 +1,130 @@
+// Dead letter queue inspection and recovery
+// Lists dead-lettered jobs with their failure reasons, moves a job back to its
+// queue for another set of attempts, or purges the DLQ
+use anyhow::{Context, Result, bail};
+use optimus_common::redis as keys;
+use optimus_common::types::Language;
+use redis::aio::ConnectionManager;
+use std::io::{self, Write};
+use uuid::Uuid;
+
+/// Failure reasons longer than this are cut in the table
+const REASON_WIDTH: usize = 60;
+
+async fn connect(redis_url: &str) -> Result<ConnectionManager> {
+    let client = redis::Client::open(redis_url).context("Invalid Redis URL")?;
+    ConnectionManager::new(client).await.context("Failed to connect to Redis")
+}
+
+/// The named language, or every language
+fn languages(lang: Option<&str>) -> Result<Vec<Language>> {
+    match lang {
+        Some(name) => match Language::from_str(name) {
+            Some(language) => Ok(vec![language]),
+            None => bail!("Unknown language '{}'", name),
+        },
+        None => Ok(Language::all_variants().to_vec()),
+    }
+}
+
+/// One table cell: first line only, cut to `width` characters
+fn cell(text: &str, width: usize) -> String {
+    let line = text.lines().next().unwrap_or_default();
+    if line.chars().count() > width {
+        format!("{}…", line.chars().take(width - 1).collect::<String>())
+    } else {
+        line.to_string()
+    }
+}
+
+/// Print dead-lettered jobs with their metadata and last failure reason
+pub async fn list(redis_url: &str, lang: Option<&str>) -> Result<()> {
+    let mut conn = connect(redis_url).await?;
+
+    let mut total = 0;
+    for language in languages(lang)? {
+        let jobs = keys::list_dlq_jobs(&mut conn, &language)
+            .await
+            .with_context(|| format!("Failed to read the {} DLQ", language))?;
+        if jobs.is_empty() {
+            continue;
+        }
+        if total == 0 {
+            println!("{:<36} {:<8} {:<8} {:<25} Last failure", "Job", "Language", "Attempts", "Dead-lettered");
+            println!("{}", "─".repeat(80 + REASON_WIDTH));
+        }
+        for dead in &jobs {
+            let metadata = &dead.job.metadata;
+            println!(
+                "{:<36} {:<8} {:<8} {:<25} {}",
+                dead.job.id,
+                language,
+                format!("{}/{}", metadata.attempts, metadata.max_attempts),
+                metadata.hops.dlq_at.as_deref().unwrap_or("-"),
+                cell(metadata.last_failure_reason.as_deref().unwrap_or("-"), REASON_WIDTH),
+            );
+        }
+        total += jobs.len();
+    }
+
+    if total == 0 {
+        println!("No dead-lettered jobs.");
+    } else {
+        println!("\nTotal: {} job(s)", total);
+    }
+    Ok(())
+}
+
+/// Move one dead-lettered job back to its queue
+pub async fn requeue(redis_url: &str, job_id: Uuid) -> Result<()> {
+    let mut conn = connect(redis_url).await?;
+
+    for language in Language::all_variants() {
+        let jobs = keys::list_dlq_jobs(&mut conn, language)
+            .await
+            .with_context(|| format!("Failed to read the {} DLQ", language))?;
+        let Some(dead) = jobs.into_iter().find(|dead| dead.job.id == job_id) else {
+            continue;
+        };
+        return match keys::requeue_dlq_job(&mut conn, &dead).await? {
+            Some(queue) => {
+                println!("✅ Requeued {} to {} (attempts reset)", job_id, queue);
+                Ok(())
+            }
+            None => bail!("Job {} left the DLQ while being requeued", job_id),
+        };
+    }
+    bail!("Job {} is not in any DLQ", job_id)
+}
+
+/// Drop the dead-lettered jobs of one language (or all), after confirmation
+pub async fn purge(redis_url: &str, lang: Option<&str>, yes: bool) -> Result<()> {
+    let languages = languages(lang)?;
+    let mut conn = connect(redis_url).await?;
+
+    if !yes {
+        let names: Vec<String> = languages.iter().map(|language| language.to_string()).collect();
+        print!("⚠️  This permanently deletes every dead-lettered job for {}. Continue? (y/N): ", names.join(", "));
+        io::stdout().flush()?;
+
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+        if !input.trim().eq_ignore_ascii_case("y") {
+            println!("❌ Aborted");
+            return Ok(());
+        }
+    }
+
+    let mut total = 0;
+    for language in &languages {
+        let purged = keys::purge_dlq(&mut conn, language)
+            .await
+            .with_context(|| format!("Failed to purge the {} DLQ", language))?;
+        if purged > 0 {
+            println!("  🗑  {}: {} job(s)", language, purged);
+        }
+        total += purged;
+    }
+    println!("✅ Purged {} dead-lettered job(s)", total);
+    Ok(())
+}
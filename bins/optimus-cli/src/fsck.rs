@@ -0,0 +1,229 @@
+// Redis consistency checker for job state
+// Finds orphaned status keys, results missing their status, jobs queued more than once
+// and stale cancellation flags, and optionally repairs them
+use anyhow::{Context, Result, bail};
+use optimus_common::redis as keys;
+use optimus_common::types::{ExecutionResult, JobControl, JobRequest, JobStatus, Language};
+use redis::AsyncCommands;
+use redis::aio::ConnectionManager;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Result TTL used when a repaired status key can't inherit the result's own
+const DEFAULT_TTL_SECS: i64 = 86400;
+/// Keys fetched per SCAN round trip
+const SCAN_COUNT: usize = 500;
+
+/// One inconsistency and what repairing it means
+enum Issue {
+    /// Status key left behind without its result - deleted on repair
+    StatusWithoutResult { job_id: Uuid },
+    /// Result whose status key is missing - status rewritten from the result on repair
+    ResultWithoutStatus { job_id: Uuid, status: JobStatus },
+    /// Extra queue entry for a job already queued elsewhere - removed on repair
+    DuplicateQueued { job_id: Uuid, queue: String, first_queue: String, payload: String },
+    /// Cancellation flag on a job that finished normally - deleted on repair
+    StaleCancellation { job_id: Uuid, status: JobStatus },
+}
+
+impl Issue {
+    fn describe(&self) -> String {
+        match self {
+            Issue::StatusWithoutResult { job_id } => {
+                format!("{}: status key without a result", job_id)
+            }
+            Issue::ResultWithoutStatus { job_id, status } => {
+                format!("{}: result ({:?}) without a status key", job_id, status)
+            }
+            Issue::DuplicateQueued { job_id, queue, first_queue, .. } => {
+                format!("{}: queued in {} and again in {}", job_id, first_queue, queue)
+            }
+            Issue::StaleCancellation { job_id, status } => {
+                format!("{}: cancellation flag on a job that finished as {:?}", job_id, status)
+            }
+        }
+    }
+
+    async fn repair(&self, conn: &mut ConnectionManager) -> Result<()> {
+        match self {
+            Issue::StatusWithoutResult { job_id } => {
+                // The result may have landed since the scan
+                let exists: bool = conn.exists(keys::result_key(job_id)).await?;
+                if !exists {
+                    let _: () = conn.del(keys::status_key(job_id)).await?;
+                }
+            }
+            Issue::ResultWithoutStatus { job_id, status } => {
+                // Expire together with the result it indexes
+                let ttl: i64 = conn.ttl(keys::result_key(job_id)).await?;
+                let ttl = if ttl > 0 { ttl } else { DEFAULT_TTL_SECS };
+                let _: () = conn.set_ex(keys::status_key(job_id), serde_json::to_string(status)?, ttl as u64).await?;
+            }
+            Issue::DuplicateQueued { queue, payload, .. } => {
+                let _: i64 = conn.lrem(queue, 1, payload).await?;
+            }
+            Issue::StaleCancellation { job_id, .. } => {
+                let _: () = conn.del(keys::control_key(job_id)).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Scan Redis for inconsistent job state; with `repair`, fix what was found
+pub async fn run(redis_url: &str, repair: bool) -> Result<()> {
+    let client = redis::Client::open(redis_url).context("Invalid Redis URL")?;
+    let mut conn = ConnectionManager::new(client).await
+        .context("Failed to connect to Redis")?;
+
+    println!("🔍 Checking job state in {}...", redis_url);
+
+    let mut issues = Vec::new();
+    issues.extend(check_status_keys(&mut conn).await?);
+    issues.extend(check_results(&mut conn).await?);
+    issues.extend(check_queues(&mut conn).await?);
+    issues.extend(check_cancellations(&mut conn).await?);
+
+    if issues.is_empty() {
+        println!("✅ No inconsistencies found");
+        return Ok(());
+    }
+
+    for issue in &issues {
+        println!("  ⚠️  {}", issue.describe());
+    }
+
+    if !repair {
+        bail!("{} inconsistenc(ies) found - rerun with --repair to fix them", issues.len());
+    }
+
+    let mut failed = 0;
+    for issue in &issues {
+        if let Err(e) = issue.repair(&mut conn).await {
+            failed += 1;
+            println!("  ❌ Failed to repair {}: {:#}", issue.describe(), e);
+        }
+    }
+    if failed > 0 {
+        bail!("{} of {} repair(s) failed", failed, issues.len());
+    }
+
+    println!("🔧 Repaired {} inconsistenc(ies)", issues.len());
+    Ok(())
+}
+
+/// Job IDs of every key under `prefix` (keys whose suffix isn't a job ID are skipped)
+async fn scan_job_ids(conn: &mut ConnectionManager, prefix: &str) -> Result<Vec<Uuid>> {
+    let mut ids = Vec::new();
+    let mut cursor: u64 = 0;
+    loop {
+        let (next, batch): (u64, Vec<String>) = redis::cmd("SCAN")
+            .arg(cursor)
+            .arg("MATCH")
+            .arg(format!("{}:*", prefix))
+            .arg("COUNT")
+            .arg(SCAN_COUNT)
+            .query_async(conn)
+            .await
+            .with_context(|| format!("Failed to scan {} keys", prefix))?;
+        ids.extend(
+            batch
+                .iter()
+                .filter_map(|key| key.strip_prefix(prefix)?.strip_prefix(':'))
+                .filter_map(|suffix| Uuid::parse_str(suffix).ok()),
+        );
+        if next == 0 {
+            break;
+        }
+        cursor = next;
+    }
+    // SCAN may return a key more than once
+    ids.sort();
+    ids.dedup();
+    Ok(ids)
+}
+
+async fn check_status_keys(conn: &mut ConnectionManager) -> Result<Vec<Issue>> {
+    let mut issues = Vec::new();
+    for job_id in scan_job_ids(conn, keys::STATUS_PREFIX).await? {
+        let exists: bool = conn.exists(keys::result_key(&job_id)).await?;
+        // A running job's progress lives in its status key until the result lands
+        if !exists && keys::get_job_progress(conn, &job_id).await?.is_none() {
+            issues.push(Issue::StatusWithoutResult { job_id });
+        }
+    }
+    Ok(issues)
+}
+
+async fn check_results(conn: &mut ConnectionManager) -> Result<Vec<Issue>> {
+    let mut issues = Vec::new();
+    for job_id in scan_job_ids(conn, keys::RESULT_PREFIX).await? {
+        let exists: bool = conn.exists(keys::status_key(&job_id)).await?;
+        if exists {
+            continue;
+        }
+        // Expired (or unreadable) between the scan and now - nothing to index
+        if let Ok(Some(result)) = keys::get_result(conn, &job_id).await {
+            issues.push(Issue::ResultWithoutStatus { job_id, status: result.overall_status });
+        }
+    }
+    Ok(issues)
+}
+
+async fn check_queues(conn: &mut ConnectionManager) -> Result<Vec<Issue>> {
+    let mut issues = Vec::new();
+    // First queue each job was seen in, walking queues in dequeue order
+    let mut seen: HashMap<Uuid, String> = HashMap::new();
+    for language in Language::all_variants() {
+        let queues = keys::all_job_queues(conn, language).await
+            .context("Failed to list worker pools")?;
+        for queue in queues {
+            let items: Vec<String> = conn.lrange(&queue, 0, -1).await
+                .with_context(|| format!("Failed to read {}", queue))?;
+            for payload in items {
+                let Ok(job) = serde_json::from_str::<JobRequest>(&payload) else {
+                    continue;
+                };
+                match seen.get(&job.id) {
+                    Some(first_queue) => issues.push(Issue::DuplicateQueued {
+                        job_id: job.id,
+                        queue: queue.clone(),
+                        first_queue: first_queue.clone(),
+                        payload,
+                    }),
+                    None => {
+                        seen.insert(job.id, queue.clone());
+                    }
+                }
+            }
+        }
+    }
+    Ok(issues)
+}
+
+async fn check_cancellations(conn: &mut ConnectionManager) -> Result<Vec<Issue>> {
+    let mut issues = Vec::new();
+    for job_id in scan_job_ids(conn, keys::CONTROL_PREFIX).await? {
+        let payload: Option<String> = conn.get(keys::control_key(&job_id)).await?;
+        let cancelled = payload
+            .and_then(|p| serde_json::from_str::<JobControl>(&p).ok())
+            .is_some_and(|control| control.cancelled);
+        if !cancelled {
+            continue;
+        }
+        let Ok(Some(result)) = keys::get_result(conn, &job_id).await else {
+            continue;
+        };
+        if is_stale_cancellation(&result) {
+            issues.push(Issue::StaleCancellation { job_id, status: result.overall_status });
+        }
+    }
+    Ok(issues)
+}
+
+/// A flag is stale once the job finished on its own. Cancelled jobs and
+/// operator force-completes (which carry a reason) keep theirs so a worker
+/// still holding the job won't start it.
+fn is_stale_cancellation(result: &ExecutionResult) -> bool {
+    result.overall_status != JobStatus::Cancelled && result.reason.is_none()
+}
@@ -1,7 +1,14 @@
+mod calibrate;
 mod commands;
+mod dlq;
+mod fsck;
+mod render;
+mod smoke;
+mod snapshot;
 
 use clap::{Parser, Subcommand};
 use anyhow::Result;
+use std::path::PathBuf;
 
 #[derive(Parser)]
 #[command(name = "optimus-cli")]
@@ -61,6 +68,17 @@ enum Commands {
         /// Skip confirmation prompt
         #[arg(short = 'y', long)]
         yes: bool,
+
+        /// Move the config entry, Dockerfile and manifests under archive/ instead of deleting them
+        #[arg(long)]
+        archive: bool,
+    },
+
+    /// Restore a language removed with `remove-lang --archive`
+    RestoreLang {
+        /// Language name to restore
+        #[arg(short, long)]
+        name: String,
     },
 
     /// List all configured languages
@@ -76,6 +94,135 @@ enum Commands {
         #[arg(long, default_value = "false")]
         no_cache: bool,
     },
+
+    /// Render worker Deployments and KEDA ScaledObjects (one per language and placement pool)
+    RenderK8s {
+        /// Output directory (ScaledObjects go in its keda/ subdirectory)
+        #[arg(long, default_value = "k8s")]
+        out_dir: PathBuf,
+    },
+
+    /// Publish config/languages.json to Redis for workers to pick up
+    PublishConfig {
+        /// Redis URL (defaults to REDIS_URL or redis://127.0.0.1:6379)
+        #[arg(long)]
+        redis_url: Option<String>,
+    },
+
+    /// Run a standard workload at several memory/CPU presets and recommend languages.json limits
+    Calibrate {
+        /// Language name (must be in config/languages.json, with its image built)
+        #[arg(short, long)]
+        lang: String,
+
+        /// Runs per preset
+        #[arg(long, default_value = "3")]
+        runs: u32,
+
+        /// Write the recommendation into config/languages.json
+        #[arg(long)]
+        apply: bool,
+    },
+
+    /// Post-deploy smoke test: submit known-good and known-bad programs and check verdicts
+    Smoke {
+        /// API base URL (defaults to OPTIMUS_API_URL or http://localhost:8080)
+        #[arg(long)]
+        api_url: Option<String>,
+
+        /// Bearer token (defaults to OPTIMUS_API_TOKEN)
+        #[arg(long)]
+        token: Option<String>,
+
+        /// Only test this language (default: every language in config/languages.json)
+        #[arg(short, long)]
+        language: Option<String>,
+
+        /// Overall time to wait for results, in seconds
+        #[arg(long, default_value = "180")]
+        timeout: u64,
+    },
+
+    /// List sandbox containers on this Docker host (labeled by the worker engine)
+    Containers {
+        /// Only list containers of this language
+        #[arg(short, long)]
+        language: Option<String>,
+    },
+
+    /// Check Redis for inconsistent job state (orphaned status keys, duplicate queue entries, ...)
+    Fsck {
+        /// Redis URL (defaults to REDIS_URL or redis://127.0.0.1:6379)
+        #[arg(long)]
+        redis_url: Option<String>,
+
+        /// Fix the inconsistencies found instead of only reporting them
+        #[arg(long)]
+        repair: bool,
+    },
+
+    /// Save queues, results and indices from Redis to a snapshot file (stop the API and workers first)
+    Backup {
+        /// Redis URL (defaults to REDIS_URL or redis://127.0.0.1:6379)
+        #[arg(long)]
+        redis_url: Option<String>,
+
+        /// Snapshot file (.json, gzip-compressed .gz or zstd-compressed .zst)
+        #[arg(short, long, default_value = "optimus-snapshot.json.gz")]
+        out: PathBuf,
+    },
+
+    /// Load a snapshot written by `backup` into Redis
+    Restore {
+        /// Redis URL (defaults to REDIS_URL or redis://127.0.0.1:6379)
+        #[arg(long)]
+        redis_url: Option<String>,
+
+        /// Snapshot file (.json, gzip-compressed .gz or zstd-compressed .zst)
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Restore even if Redis already holds optimus keys (snapshot keys replace them)
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Inspect, requeue or purge dead-lettered jobs
+    Dlq {
+        /// Redis URL (defaults to REDIS_URL or redis://127.0.0.1:6379)
+        #[arg(long, global = true)]
+        redis_url: Option<String>,
+
+        #[command(subcommand)]
+        action: DlqAction,
+    },
+}
+
+#[derive(Subcommand)]
+enum DlqAction {
+    /// List dead-lettered jobs with their last failure reason
+    List {
+        /// Only this language (default: every language)
+        #[arg(short, long)]
+        lang: Option<String>,
+    },
+
+    /// Move a job back to its queue with its attempts reset
+    Requeue {
+        #[arg(long)]
+        job_id: uuid::Uuid,
+    },
+
+    /// Delete dead-lettered jobs
+    Purge {
+        /// Only this language (default: every language)
+        #[arg(short, long)]
+        lang: Option<String>,
+
+        /// Skip confirmation prompt
+        #[arg(short = 'y', long)]
+        yes: bool,
+    },
 }
 
 #[tokio::main]
@@ -106,8 +253,11 @@ async fn main() -> Result<()> {
                 !skip_docker,
             ).await?;
         }
-        Commands::RemoveLang { name, yes } => {
-            commands::remove_language(&name, yes).await?;
+        Commands::RemoveLang { name, yes, archive } => {
+            commands::remove_language(&name, yes, archive).await?;
+        }
+        Commands::RestoreLang { name } => {
+            commands::restore_language(&name).await?;
         }
         Commands::ListLangs => {
             commands::list_languages().await?;
@@ -115,6 +265,53 @@ async fn main() -> Result<()> {
         Commands::BuildImage { name, no_cache } => {
             commands::build_docker_image(&name, no_cache).await?;
         }
+        Commands::RenderK8s { out_dir } => {
+            render::run(&out_dir)?;
+        }
+        Commands::PublishConfig { redis_url } => {
+            commands::publish_config(redis_url.as_deref()).await?;
+        }
+        Commands::Calibrate { lang, runs, apply } => {
+            calibrate::run(&lang, runs, apply)?;
+        }
+        Commands::Smoke { api_url, token, language, timeout } => {
+            let api_url = api_url
+                .or_else(|| std::env::var("OPTIMUS_API_URL").ok())
+                .unwrap_or_else(|| "http://localhost:8080".to_string());
+            let token = token.or_else(|| std::env::var("OPTIMUS_API_TOKEN").ok());
+            smoke::run(&api_url, token.as_deref(), language.as_deref(), timeout).await?;
+        }
+        Commands::Containers { language } => {
+            commands::list_containers(language.as_deref())?;
+        }
+        Commands::Fsck { redis_url, repair } => {
+            let redis_url = redis_url
+                .or_else(|| std::env::var("REDIS_URL").ok())
+                .unwrap_or_else(|| "redis://127.0.0.1:6379".to_string());
+            fsck::run(&redis_url, repair).await?;
+        }
+        Commands::Backup { redis_url, out } => {
+            let redis_url = redis_url
+                .or_else(|| std::env::var("REDIS_URL").ok())
+                .unwrap_or_else(|| "redis://127.0.0.1:6379".to_string());
+            snapshot::backup(&redis_url, &out).await?;
+        }
+        Commands::Restore { redis_url, input, force } => {
+            let redis_url = redis_url
+                .or_else(|| std::env::var("REDIS_URL").ok())
+                .unwrap_or_else(|| "redis://127.0.0.1:6379".to_string());
+            snapshot::restore(&redis_url, &input, force).await?;
+        }
+        Commands::Dlq { redis_url, action } => {
+            let redis_url = redis_url
+                .or_else(|| std::env::var("REDIS_URL").ok())
+                .unwrap_or_else(|| "redis://127.0.0.1:6379".to_string());
+            match action {
+                DlqAction::List { lang } => dlq::list(&redis_url, lang.as_deref()).await?,
+                DlqAction::Requeue { job_id } => dlq::requeue(&redis_url, job_id).await?,
+                DlqAction::Purge { lang, yes } => dlq::purge(&redis_url, lang.as_deref(), yes).await?,
+            }
+        }
     }
 
     Ok(())
@@ -0,0 +1,237 @@
+// Kubernetes manifests for worker deployments
+// One Deployment + KEDA ScaledObject per language, plus one pair per placement
+// pool (languages.json `placements`) pinned to matching nodes; GPU languages
+// are pinned to GPU nodes
+use anyhow::{Context, Result, bail};
+use handlebars::Handlebars;
+use optimus_common::redis as keys;
+use optimus_common::types::{Language, Placement};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::commands::{load_languages_config, LanguageConfig};
+
+const WORKER_DEPLOYMENT_TEMPLATE: &str = r#"# GENERATED BY optimus-cli render-k8s — DO NOT EDIT
+apiVersion: apps/v1
+kind: Deployment
+metadata:
+  name: {{name}}
+  namespace: optimus
+  labels:
+    app: optimus-worker
+    optimus.language: {{language}}
+{{#if pool}}
+    optimus.pool: {{pool}}
+{{/if}}
+spec:
+  replicas: 1  # Will be scaled by KEDA
+  selector:
+    matchLabels:
+      app: {{name}}
+  template:
+    metadata:
+      labels:
+        app: {{name}}
+        optimus.language: {{language}}
+    spec:
+{{#if node_selector}}
+      nodeSelector:
+{{#each node_selector}}
+        {{@key}}: "{{this}}"
+{{/each}}
+{{/if}}
+      # Longer than SHUTDOWN_DRAIN_SECS so in-flight jobs can finish on SIGTERM
+      terminationGracePeriodSeconds: 60
+      containers:
+      - name: optimus-worker
+        image: optimus-worker:latest
+        imagePullPolicy: IfNotPresent
+        env:
+        - name: REDIS_URL
+          value: "redis://redis:6379"
+        - name: RUST_LOG
+          value: "info"
+        - name: DOCKER_HOST
+          value: "unix:///var/run/docker.sock"
+        - name: OPTIMUS_LANGUAGE
+          value: "{{language}}"
+        - name: OPTIMUS_QUEUE
+          value: "{{queue}}"
+        - name: OPTIMUS_IMAGE
+          value: "{{image}}"
+{{#if pool}}
+        - name: OPTIMUS_POOL
+          value: "{{pool}}"
+{{/if}}
+        - name: MAX_PARALLEL_JOBS
+          value: "{{max_parallel_jobs}}"
+        - name: MAX_PARALLEL_TESTS
+          value: "{{max_parallel_tests}}"
+        - name: SHUTDOWN_DRAIN_SECS
+          value: "50"
+        livenessProbe:
+          httpGet:
+            path: /healthz
+            port: 8080
+          initialDelaySeconds: 15
+          periodSeconds: 15
+          timeoutSeconds: 5
+          failureThreshold: 4
+        readinessProbe:
+          httpGet:
+            path: /readyz
+            port: 8080
+          periodSeconds: 10
+          timeoutSeconds: 5
+        volumeMounts:
+        - name: docker-sock
+          mountPath: /var/run/docker.sock
+        resources:
+          requests:
+            memory: "{{requests.memory}}"
+            cpu: "{{requests.cpu}}"
+          limits:
+            memory: "{{limits.memory}}"
+            cpu: "{{limits.cpu}}"
+      volumes:
+      - name: docker-sock
+        hostPath:
+          path: /var/run/docker.sock
+          type: Socket
+"#;
+
+const SCALED_OBJECT_TEMPLATE: &str = r#"# GENERATED BY optimus-cli render-k8s — DO NOT EDIT
+apiVersion: keda.sh/v1alpha1
+kind: ScaledObject
+metadata:
+  name: {{name}}
+  namespace: optimus
+spec:
+  scaleTargetRef:
+    name: {{name}}
+  minReplicaCount: 0
+  maxReplicaCount: 10
+  triggers:
+{{#each queues}}
+  - type: redis
+    metadata:
+      address: redis:6379
+      listName: "{{this}}"
+      listLength: "5"
+{{/each}}
+"#;
+
+/// Template context for one worker deployment (a language's default pool or a placement pool)
+#[derive(Debug, Serialize)]
+struct WorkerContext {
+    /// Deployment name, also the ScaledObject name
+    name: String,
+    language: String,
+    pool: Option<String>,
+    queue: String,
+    /// Every queue the worker consumes, for KEDA
+    queues: Vec<String>,
+    image: String,
+    /// Node labels for the placement (well-known) and GPU nodes (GPU feature discovery)
+    node_selector: BTreeMap<String, String>,
+    max_parallel_jobs: u32,
+    max_parallel_tests: u32,
+    requests: crate::commands::ResourceRequests,
+    limits: crate::commands::ResourceLimits,
+}
+
+impl WorkerContext {
+    fn new(config: &LanguageConfig, language: &Language, placement: Option<&Placement>) -> Self {
+        let pool = placement.and_then(Placement::pool);
+        let name = match &pool {
+            Some(pool) => format!("optimus-worker-{}-{}", config.name, pool.replace('.', "-")),
+            None => format!("optimus-worker-{}", config.name),
+        };
+        let mut node_selector = BTreeMap::new();
+        // GPU jobs run in sibling containers on the node, so the pod itself requests no GPUs
+        if config.gpu.is_some() {
+            node_selector.insert("nvidia.com/gpu.present".to_string(), "true".to_string());
+        }
+        if let Some(placement) = placement {
+            if let Some(arch) = &placement.arch {
+                node_selector.insert("kubernetes.io/arch".to_string(), arch.clone());
+            }
+            if let Some(region) = &placement.region {
+                node_selector.insert("topology.kubernetes.io/region".to_string(), region.clone());
+            }
+        }
+        Self {
+            name,
+            language: config.name.clone(),
+            queue: match &pool {
+                Some(pool) => keys::pool_queue_name(language, Some(pool)),
+                None => config.queue_name.clone(),
+            },
+            queues: keys::pool_dequeue_order(language, pool.as_deref()).to_vec(),
+            pool,
+            image: config.image.clone(),
+            node_selector,
+            max_parallel_jobs: config.concurrency.max_parallel_jobs,
+            max_parallel_tests: config.concurrency.max_parallel_tests,
+            requests: config.resources.requests.clone(),
+            limits: config.resources.limits.clone(),
+        }
+    }
+
+    /// File name suffix: the language, plus the pool for placement pools
+    fn file_stem(&self) -> String {
+        match &self.pool {
+            Some(pool) => format!("{}-pool-{}", self.language, pool),
+            None => self.language.clone(),
+        }
+    }
+}
+
+/// Render worker Deployments and KEDA ScaledObjects for every configured
+/// language and placement pool into `out_dir`
+pub fn run(out_dir: &Path) -> Result<()> {
+    let languages_json = load_languages_config()?;
+    if languages_json.languages.is_empty() {
+        bail!("config/languages.json has no languages - nothing to render");
+    }
+
+    let mut handlebars = Handlebars::new();
+    handlebars.set_strict_mode(true);
+    handlebars.register_escape_fn(handlebars::no_escape);
+    handlebars.register_template_string("deployment", WORKER_DEPLOYMENT_TEMPLATE)?;
+    handlebars.register_template_string("scaled_object", SCALED_OBJECT_TEMPLATE)?;
+
+    let keda_dir = out_dir.join("keda");
+    fs::create_dir_all(&keda_dir).with_context(|| format!("Failed to create {}", keda_dir.display()))?;
+
+    println!("📝 Rendering worker manifests into {}...", out_dir.display());
+    let mut rendered = 0;
+    for config in &languages_json.languages {
+        let language = Language::from_str(&config.name)
+            .with_context(|| format!("Unknown language '{}' in languages.json", config.name))?;
+        for placement in &config.placements {
+            placement
+                .validate()
+                .map_err(|e| anyhow::anyhow!("Invalid placement for '{}': {}", config.name, e))?;
+        }
+
+        let pools = std::iter::once(None).chain(config.placements.iter().map(Some));
+        for placement in pools {
+            let context = WorkerContext::new(config, &language, placement);
+            let deployment = out_dir.join(format!("worker-deployment-{}.yaml", context.file_stem()));
+            let scaled_object = keda_dir.join(format!("scaled-object-{}.yaml", context.file_stem()));
+            fs::write(&deployment, handlebars.render("deployment", &context)?)
+                .with_context(|| format!("Failed to write {}", deployment.display()))?;
+            fs::write(&scaled_object, handlebars.render("scaled_object", &context)?)
+                .with_context(|| format!("Failed to write {}", scaled_object.display()))?;
+            println!("  ✓ {} ({})", context.name, context.queue);
+            rendered += 1;
+        }
+    }
+
+    println!("✅ Rendered {} worker deployment(s)", rendered);
+    println!("   Apply with: kubectl apply -f {} -f {}", out_dir.display(), keda_dir.display());
+    Ok(())
+}
@@ -0,0 +1,209 @@
+// Golden-path smoke test against a live deployment
+// Submits known-good and known-bad programs per language and checks the verdicts
+use anyhow::{Context, Result, bail};
+use optimus_common::types::{ExecutionResult, Language, TestStatus};
+use std::time::{Duration, Instant};
+
+use crate::commands::load_languages_config;
+
+/// One curated submission and the verdict it must produce
+struct Sample {
+    name: &'static str,
+    source: &'static str,
+    expected: TestStatus,
+}
+
+// Every sample reads two integers and should print their sum
+const INPUT: &str = "2 3";
+const EXPECTED_OUTPUT: &str = "5";
+const TIMEOUT_MS: u64 = 2000;
+
+fn samples(language: Language) -> Vec<Sample> {
+    match language {
+        Language::Python => vec![
+            Sample { name: "pass", source: "a, b = map(int, input().split())\nprint(a + b)\n", expected: TestStatus::Passed },
+            Sample { name: "wrong answer", source: "a, b = map(int, input().split())\nprint(a - b)\n", expected: TestStatus::Failed },
+            Sample { name: "time limit", source: "while True:\n    pass\n", expected: TestStatus::TimeLimitExceeded },
+            Sample { name: "runtime error", source: "raise RuntimeError('boom')\n", expected: TestStatus::RuntimeError },
+        ],
+        Language::Java => vec![
+            Sample {
+                name: "pass",
+                source: "import java.util.Scanner;\npublic class Main {\n    public static void main(String[] args) {\n        Scanner in = new Scanner(System.in);\n        System.out.println(in.nextInt() + in.nextInt());\n    }\n}\n",
+                expected: TestStatus::Passed,
+            },
+            Sample {
+                name: "wrong answer",
+                source: "public class Main {\n    public static void main(String[] args) {\n        System.out.println(4);\n    }\n}\n",
+                expected: TestStatus::Failed,
+            },
+            Sample {
+                name: "time limit",
+                source: "public class Main {\n    public static void main(String[] args) {\n        while (true) {}\n    }\n}\n",
+                expected: TestStatus::TimeLimitExceeded,
+            },
+            Sample {
+                name: "runtime error",
+                source: "public class Main {\n    public static void main(String[] args) {\n        throw new RuntimeException(\"boom\");\n    }\n}\n",
+                expected: TestStatus::RuntimeError,
+            },
+            Sample {
+                name: "compile error",
+                source: "public class Main {\n    public static void main(String[] args) {\n        int x = \n    }\n}\n",
+                expected: TestStatus::CompileError,
+            },
+        ],
+        Language::Rust => vec![
+            Sample {
+                name: "pass",
+                source: "use std::io::Read;\nfn main() {\n    let mut s = String::new();\n    std::io::stdin().read_to_string(&mut s).unwrap();\n    let n: i64 = s.split_whitespace().map(|x| x.parse::<i64>().unwrap()).sum();\n    println!(\"{}\", n);\n}\n",
+                expected: TestStatus::Passed,
+            },
+            Sample { name: "wrong answer", source: "fn main() {\n    println!(\"4\");\n}\n", expected: TestStatus::Failed },
+            Sample { name: "time limit", source: "fn main() {\n    loop {\n        std::hint::spin_loop();\n    }\n}\n", expected: TestStatus::TimeLimitExceeded },
+            Sample { name: "runtime error", source: "fn main() {\n    panic!(\"boom\");\n}\n", expected: TestStatus::RuntimeError },
+            Sample { name: "compile error", source: "fn main() {\n    let x: i32 = \"five\";\n}\n", expected: TestStatus::CompileError },
+        ],
+    }
+}
+
+/// Languages to check: `only` if given, otherwise every language in config/languages.json
+fn enabled_languages(only: Option<&str>) -> Result<Vec<Language>> {
+    if let Some(name) = only {
+        let language = Language::from_str(name)
+            .with_context(|| format!("No smoke samples for language '{}'", name))?;
+        return Ok(vec![language]);
+    }
+
+    let configured = load_languages_config()?;
+    Ok(configured
+        .languages
+        .iter()
+        .filter_map(|lang| Language::from_str(&lang.name))
+        .collect())
+}
+
+/// Run the smoke suite against `api_url`; fails if any verdict differs
+pub async fn run(api_url: &str, token: Option<&str>, only: Option<&str>, timeout_secs: u64) -> Result<()> {
+    let api_url = api_url.trim_end_matches('/');
+    let languages = enabled_languages(only)?;
+    if languages.is_empty() {
+        bail!("No languages to smoke test");
+    }
+
+    let http = reqwest::Client::builder()
+        .timeout(Duration::from_secs(60))
+        .build()
+        .context("Failed to build HTTP client")?;
+
+    println!("🔥 Smoke testing {} ({} language(s))\n", api_url, languages.len());
+
+    // Submit everything first so the samples run in parallel on the workers
+    let mut submitted = Vec::new();
+    for language in &languages {
+        for sample in samples(*language) {
+            let job_id = submit(&http, api_url, token, *language, &sample).await
+                .with_context(|| format!("Failed to submit {} / {}", language, sample.name))?;
+            submitted.push((*language, sample, job_id));
+        }
+    }
+
+    let deadline = Instant::now() + Duration::from_secs(timeout_secs);
+    let mut failures = 0;
+
+    println!("{:<8} {:<15} {:<20} {:<20}", "Lang", "Case", "Expected", "Got");
+    println!("{}", "─".repeat(70));
+
+    for (language, sample, job_id) in &submitted {
+        let verdict = match wait_for_result(&http, api_url, token, job_id, deadline).await {
+            Ok(result) => result.results.first().map(|r| r.status),
+            Err(e) => {
+                eprintln!("⚠ {} / {}: {:#}", language, sample.name, e);
+                None
+            }
+        };
+
+        let ok = verdict == Some(sample.expected);
+        if !ok {
+            failures += 1;
+        }
+        println!(
+            "{:<8} {:<15} {:<20} {:<20} {}",
+            language.to_string(),
+            sample.name,
+            format!("{:?}", sample.expected),
+            verdict.map(|v| format!("{:?}", v)).unwrap_or_else(|| "no result".to_string()),
+            if ok { "✅" } else { "❌" }
+        );
+    }
+
+    println!();
+    if failures > 0 {
+        bail!("{} of {} smoke checks failed", failures, submitted.len());
+    }
+
+    println!("✅ All {} smoke checks passed", submitted.len());
+    Ok(())
+}
+
+fn authorize(request: reqwest::RequestBuilder, token: Option<&str>) -> reqwest::RequestBuilder {
+    match token {
+        Some(token) => request.bearer_auth(token),
+        None => request,
+    }
+}
+
+async fn submit(
+    http: &reqwest::Client,
+    api_url: &str,
+    token: Option<&str>,
+    language: Language,
+    sample: &Sample,
+) -> Result<String> {
+    let body = serde_json::json!({
+        "language": language,
+        "source_code": sample.source,
+        "test_cases": [{"input": INPUT, "expected_output": EXPECTED_OUTPUT, "weight": 1}],
+        "timeout_ms": TIMEOUT_MS,
+        "priority": "high",
+    });
+
+    let response = authorize(http.post(format!("{}/execute", api_url)), token)
+        .json(&body)
+        .send()
+        .await?;
+    let status = response.status();
+    let payload: serde_json::Value = response.json().await.context("Invalid submit response")?;
+    if !status.is_success() {
+        bail!("API returned {}: {}", status, payload);
+    }
+
+    payload["job_id"]
+        .as_str()
+        .map(|id| id.to_string())
+        .context("Submit response has no job_id")
+}
+
+/// Long-poll `GET /job/{id}?wait=` until the result exists or the deadline passes
+async fn wait_for_result(
+    http: &reqwest::Client,
+    api_url: &str,
+    token: Option<&str>,
+    job_id: &str,
+    deadline: Instant,
+) -> Result<ExecutionResult> {
+    while Instant::now() < deadline {
+        let wait_secs = deadline.saturating_duration_since(Instant::now()).as_secs().clamp(1, 30);
+        let response = authorize(http.get(format!("{}/job/{}?wait={}s", api_url, job_id, wait_secs)), token)
+            .send()
+            .await?;
+
+        match response.status() {
+            reqwest::StatusCode::OK => return response.json().await.context("Invalid result payload"),
+            reqwest::StatusCode::ACCEPTED => continue,
+            status => bail!("API returned {} for job {}", status, job_id),
+        }
+    }
+
+    bail!("Timed out waiting for job {}", job_id)
+}
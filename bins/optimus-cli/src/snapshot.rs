@@ -0,0 +1,284 @@
+// Queue backup and restore across Redis maintenance
+// Dumps every optimus:* key (queues, results, status indices, batches, ...) to a
+// JSON snapshot, compressed according to the file extension (.gz or .zst), and
+// loads it back
+use anyhow::{Context, Result, bail};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use optimus_common::redis as keys;
+use redis::AsyncCommands;
+use redis::aio::ConnectionManager;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+/// Bumped when the snapshot layout changes incompatibly
+const SNAPSHOT_VERSION: u32 = 1;
+/// Keys fetched per SCAN round trip
+const SCAN_COUNT: usize = 500;
+
+/// Runtime state that must not outlive the processes that own it, and caches
+/// that rebuild themselves - restoring them would only do harm or waste space
+fn is_transient(key: &str) -> bool {
+    [
+        keys::CONCURRENCY_PREFIX,
+        keys::RATE_LIMIT_PREFIX,
+        keys::WORKERS_PREFIX,
+        keys::WORKER_STATUS_PREFIX,
+        keys::WORKER_PREFIX,
+        keys::PROCESSING_PREFIX,
+        keys::ARTIFACT_PREFIX,
+    ]
+        .iter()
+        .any(|prefix| key.strip_prefix(prefix).is_some_and(|rest| rest.starts_with(':')))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Snapshot {
+    version: u32,
+    /// Seconds since the Unix epoch
+    created_at: u64,
+    keys: Vec<Entry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Entry {
+    key: String,
+    /// Remaining time to live; None for keys without an expiry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ttl_ms: Option<i64>,
+    #[serde(flatten)]
+    value: Value,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "lowercase")]
+enum Value {
+    String(String),
+    List(Vec<String>),
+    Hash(BTreeMap<String, String>),
+    Set(Vec<String>),
+    Zset(Vec<(String, f64)>),
+}
+
+impl Value {
+    fn kind(&self) -> &'static str {
+        match self {
+            Value::String(_) => "string",
+            Value::List(_) => "list",
+            Value::Hash(_) => "hash",
+            Value::Set(_) => "set",
+            Value::Zset(_) => "zset",
+        }
+    }
+}
+
+async fn connect(redis_url: &str) -> Result<ConnectionManager> {
+    let client = redis::Client::open(redis_url).context("Invalid Redis URL")?;
+    ConnectionManager::new(client).await.context("Failed to connect to Redis")
+}
+
+/// Snapshot file compression, picked from the file extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Compression {
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    /// `.json` (or no extension) is plain JSON; anything else unknown is
+    /// refused rather than silently written uncompressed
+    fn from_path(path: &Path) -> Result<Self> {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            None | Some("json") => Ok(Compression::None),
+            Some("gz") => Ok(Compression::Gzip),
+            Some("zst") | Some("zstd") => Ok(Compression::Zstd),
+            Some(other) => bail!(
+                "Unrecognised snapshot extension '.{}' (use .json, .json.gz or .json.zst)",
+                other
+            ),
+        }
+    }
+}
+
+/// Write every optimus:* key to `out`
+///
+/// Keys are read one at a time, so stop the API and workers first for a
+/// consistent snapshot (nothing stops a worker popping a job mid-backup).
+pub async fn backup(redis_url: &str, out: &Path) -> Result<()> {
+    let compression = Compression::from_path(out)?;
+    let mut conn = connect(redis_url).await?;
+    println!("💾 Backing up {} to {}...", redis_url, out.display());
+
+    let mut entries = Vec::new();
+    let mut skipped = 0;
+    for key in scan_keys(&mut conn).await? {
+        if is_transient(&key) {
+            continue;
+        }
+        match read_entry(&mut conn, &key).await {
+            Ok(Some(entry)) => entries.push(entry),
+            // Expired since the scan
+            Ok(None) => {}
+            Err(e) => {
+                skipped += 1;
+                println!("  ⚠️  Skipping {}: {:#}", key, e);
+            }
+        }
+    }
+
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for entry in &entries {
+        *counts.entry(entry.value.kind()).or_default() += 1;
+    }
+
+    let snapshot = Snapshot {
+        version: SNAPSHOT_VERSION,
+        created_at: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs()),
+        keys: entries,
+    };
+    let file = BufWriter::new(File::create(out).with_context(|| format!("Failed to create {}", out.display()))?);
+    match compression {
+        Compression::None => {
+            let mut file = file;
+            serde_json::to_writer(&mut file, &snapshot).context("Failed to write snapshot")?;
+            file.flush()?;
+        }
+        Compression::Gzip => {
+            let mut encoder = GzEncoder::new(file, flate2::Compression::default());
+            serde_json::to_writer(&mut encoder, &snapshot).context("Failed to write snapshot")?;
+            encoder.finish().context("Failed to finish snapshot")?.flush()?;
+        }
+        Compression::Zstd => {
+            let mut encoder = zstd::Encoder::new(file, 0).context("Failed to start zstd stream")?;
+            serde_json::to_writer(&mut encoder, &snapshot).context("Failed to write snapshot")?;
+            encoder.finish().context("Failed to finish snapshot")?.flush()?;
+        }
+    }
+
+    println!("✅ Saved {} key(s) {:?}", snapshot.keys.len(), counts);
+    if skipped > 0 {
+        bail!("{} key(s) could not be read and are missing from the snapshot", skipped);
+    }
+    Ok(())
+}
+
+/// Load a snapshot into Redis
+///
+/// Refuses to touch a Redis that already holds optimus:* keys unless `force`,
+/// in which case snapshot keys replace existing ones of the same name.
+/// Expiries continue from where they stood when the snapshot was taken.
+pub async fn restore(redis_url: &str, input: &Path, force: bool) -> Result<()> {
+    let compression = Compression::from_path(input)?;
+    let file = BufReader::new(File::open(input).with_context(|| format!("Failed to open {}", input.display()))?);
+    let reader: Box<dyn Read> = match compression {
+        Compression::None => Box::new(file),
+        Compression::Gzip => Box::new(GzDecoder::new(file)),
+        Compression::Zstd => Box::new(zstd::Decoder::with_buffer(file).context("Failed to open zstd stream")?),
+    };
+    let snapshot: Snapshot = serde_json::from_reader(reader).context("Failed to parse snapshot")?;
+    if snapshot.version != SNAPSHOT_VERSION {
+        bail!("Unsupported snapshot version {} (expected {})", snapshot.version, SNAPSHOT_VERSION);
+    }
+
+    let mut conn = connect(redis_url).await?;
+    let existing = scan_keys(&mut conn).await?.into_iter().filter(|key| !is_transient(key)).count();
+    if existing > 0 && !force {
+        bail!("{} already holds {} optimus key(s) - rerun with --force to overwrite", redis_url, existing);
+    }
+
+    println!("📥 Restoring {} key(s) into {}...", snapshot.keys.len(), redis_url);
+    for entry in &snapshot.keys {
+        write_entry(&mut conn, entry)
+            .await
+            .with_context(|| format!("Failed to restore {}", entry.key))?;
+    }
+
+    println!("✅ Restored {} key(s)", snapshot.keys.len());
+    Ok(())
+}
+
+/// Every optimus:* key (deduplicated - SCAN may return a key more than once)
+async fn scan_keys(conn: &mut ConnectionManager) -> Result<Vec<String>> {
+    let mut found = Vec::new();
+    let mut cursor: u64 = 0;
+    loop {
+        let (next, batch): (u64, Vec<String>) = redis::cmd("SCAN")
+            .arg(cursor)
+            .arg("MATCH")
+            .arg("optimus:*")
+            .arg("COUNT")
+            .arg(SCAN_COUNT)
+            .query_async(conn)
+            .await
+            .context("Failed to scan keys")?;
+        found.extend(batch);
+        if next == 0 {
+            break;
+        }
+        cursor = next;
+    }
+    found.sort();
+    found.dedup();
+    Ok(found)
+}
+
+async fn read_entry(conn: &mut ConnectionManager, key: &str) -> Result<Option<Entry>> {
+    let kind: String = redis::cmd("TYPE").arg(key).query_async(conn).await?;
+    let value = match kind.as_str() {
+        "none" => return Ok(None),
+        "string" => Value::String(conn.get(key).await?),
+        "list" => Value::List(conn.lrange(key, 0, -1).await?),
+        "hash" => Value::Hash(conn.hgetall(key).await?),
+        "set" => Value::Set(conn.smembers(key).await?),
+        "zset" => Value::Zset(conn.zrange_withscores(key, 0, -1).await?),
+        other => bail!("unsupported key type '{}'", other),
+    };
+    // -1: no expiry, -2: expired since it was read (restoring it would make it permanent)
+    let ttl_ms: i64 = conn.pttl(key).await?;
+    if ttl_ms == -2 {
+        return Ok(None);
+    }
+    Ok(Some(Entry {
+        key: key.to_string(),
+        ttl_ms: (ttl_ms > 0).then_some(ttl_ms),
+        value,
+    }))
+}
+
+async fn write_entry(conn: &mut ConnectionManager, entry: &Entry) -> Result<()> {
+    let key = entry.key.as_str();
+    let mut pipe = redis::pipe();
+    pipe.atomic().del(key).ignore();
+    match &entry.value {
+        Value::String(value) => {
+            pipe.set(key, value).ignore();
+        }
+        Value::List(items) if !items.is_empty() => {
+            pipe.rpush(key, items).ignore();
+        }
+        Value::Hash(fields) if !fields.is_empty() => {
+            let fields: Vec<(&String, &String)> = fields.iter().collect();
+            pipe.hset_multiple(key, &fields).ignore();
+        }
+        Value::Set(members) if !members.is_empty() => {
+            pipe.sadd(key, members).ignore();
+        }
+        Value::Zset(members) if !members.is_empty() => {
+            let members: Vec<(f64, &String)> = members.iter().map(|(member, score)| (*score, member)).collect();
+            pipe.zadd_multiple(key, &members).ignore();
+        }
+        // Redis never stores empty collections
+        _ => {}
+    }
+    if let Some(ttl_ms) = entry.ttl_ms {
+        pipe.pexpire(key, ttl_ms).ignore();
+    }
+    let _: () = pipe.query_async(conn).await?;
+    Ok(())
+}
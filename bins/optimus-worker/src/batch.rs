@@ -0,0 +1,73 @@
+//! Batch Tracking - Aggregated Completion Events
+//!
+//! **Responsibility:**
+//! Count terminal jobs per batch and emit exactly one `batch.completed`
+//! event when the last one finishes.
+//!
+//! **Flow:**
+//! - Called after a job's terminal result is stored (completed, failed, cancelled)
+//! - Job id is added to the batch's finished set (idempotent)
+//! - The worker that finishes the batch builds per-job summaries from the
+//!   stored results and publishes the event (SSE channel + webhook queue)
+//!
+//! Webhook delivery itself happens in the API, so workers never make
+//! outbound HTTP calls.
+
+use optimus_common::redis;
+use optimus_common::types::{BatchRecord, JobRequest};
+use tracing::{error, info, warn};
+
+/// Record a finished job and emit the batch event if it was the last one
+///
+/// Failures are logged, never propagated - the job result is already stored.
+pub async fn on_job_finished(conn: &mut ::redis::aio::ConnectionManager, job: &JobRequest) {
+    let Some(batch_id) = job.batch_id else {
+        return;
+    };
+
+    let batch = match redis::get_batch(conn, &batch_id).await {
+        Ok(Some(batch)) => batch,
+        Ok(None) => {
+            warn!(job_id = %job.id, batch_id = %batch_id, "Batch expired or missing - skipping batch tracking");
+            return;
+        }
+        Err(e) => {
+            error!(job_id = %job.id, batch_id = %batch_id, error = %e, "Failed to load batch");
+            return;
+        }
+    };
+
+    match redis::mark_batch_job_finished(conn, &batch, &job.id).await {
+        Ok(true) => {
+            if let Err(e) = emit_completion(conn, &batch).await {
+                error!(batch_id = %batch_id, error = %e, "Failed to publish batch completion");
+            }
+        }
+        Ok(false) => {}
+        Err(e) => {
+            error!(job_id = %job.id, batch_id = %batch_id, error = %e, "Failed to record batch progress");
+        }
+    }
+}
+
+/// Publish the completion event and log it
+async fn emit_completion(
+    conn: &mut ::redis::aio::ConnectionManager,
+    batch: &BatchRecord,
+) -> ::redis::RedisResult<()> {
+    let completion = redis::complete_batch(conn, batch).await?;
+
+    if completion.jobs.len() < batch.expected_jobs as usize {
+        warn!(batch_id = %batch.id, summarized = completion.jobs.len(), "Some batch job results missing from summary");
+    }
+
+    info!(
+        batch_id = %batch.id,
+        total_jobs = batch.expected_jobs,
+        webhook = batch.webhook_url.is_some(),
+        phase = "batch_completed",
+        "Batch completed"
+    );
+
+    Ok(())
+}
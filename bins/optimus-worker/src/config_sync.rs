@@ -0,0 +1,202 @@
+//! Language Config Sync - Central languages.json Distribution
+//!
+//! **Responsibility:**
+//! Load languages.json from a central source at boot and keep it fresh,
+//! so config changes don't require rebuilding or re-mounting worker images.
+//!
+//! **Sources (`LANGUAGE_CONFIG_SOURCE`):**
+//! - `file` (default): local `config/languages.json` only, no refresh
+//! - `redis`: published via `optimus-cli publish-config`, checked by version
+//! - `http(s)://...`: fetched with `If-None-Match` / ETag
+//!
+//! **Safety:**
+//! - A remote config that fails to parse is ignored (last good config stays)
+//! - A config that drops this worker's language or moves its queue is
+//!   rejected - that is a routing change and needs a redeploy
+//! - If the remote source is unavailable at boot, the local file is used
+
+use optimus_exec::config::LanguageConfigManager;
+use anyhow::{bail, Context, Result};
+use optimus_common::redis;
+use optimus_common::types::Language;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+/// Where languages.json comes from
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigSource {
+    File,
+    Redis,
+    Url(String),
+}
+
+impl ConfigSource {
+    pub fn from_env() -> Self {
+        match std::env::var("LANGUAGE_CONFIG_SOURCE") {
+            Ok(v) if v.eq_ignore_ascii_case("redis") => ConfigSource::Redis,
+            Ok(v) if v.starts_with("http://") || v.starts_with("https://") => ConfigSource::Url(v),
+            _ => ConfigSource::File,
+        }
+    }
+
+    /// Refresh interval from `LANGUAGE_CONFIG_REFRESH_SECS` (default: 30)
+    pub fn refresh_interval() -> Duration {
+        let secs = std::env::var("LANGUAGE_CONFIG_REFRESH_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|&s: &u64| s > 0)
+            .unwrap_or(30);
+        Duration::from_secs(secs)
+    }
+}
+
+/// Fetches languages.json and remembers what it last saw
+pub struct ConfigFetcher {
+    source: ConfigSource,
+    client: ::redis::Client,
+    http: reqwest::Client,
+    /// Redis version or HTTP ETag of the last applied config
+    marker: Option<String>,
+}
+
+impl ConfigFetcher {
+    pub fn new(source: ConfigSource, client: ::redis::Client) -> Self {
+        Self {
+            source,
+            client,
+            http: reqwest::Client::new(),
+            marker: None,
+        }
+    }
+
+    /// Fetch the config if it changed since the last call
+    /// Returns `Ok(None)` when unchanged (or for the file source)
+    pub async fn fetch_if_changed(&mut self) -> Result<Option<LanguageConfigManager>> {
+        match self.source.clone() {
+            ConfigSource::File => Ok(None),
+            ConfigSource::Redis => self.fetch_redis().await,
+            ConfigSource::Url(url) => self.fetch_url(&url).await,
+        }
+    }
+
+    async fn fetch_redis(&mut self) -> Result<Option<LanguageConfigManager>> {
+        let mut conn = ::redis::aio::ConnectionManager::new(self.client.clone()).await?;
+
+        let version = redis::get_language_config_version(&mut conn).await?;
+        if version.map(|v| v.to_string()) == self.marker {
+            return Ok(None);
+        }
+
+        let Some((version, body)) = redis::get_language_config(&mut conn).await? else {
+            bail!("No language config published at {}", redis::language_config_key());
+        };
+
+        let manager = LanguageConfigManager::from_json(&body)?;
+        self.marker = Some(version.to_string());
+        Ok(Some(manager))
+    }
+
+    async fn fetch_url(&mut self, url: &str) -> Result<Option<LanguageConfigManager>> {
+        let mut request = self.http.get(url).timeout(Duration::from_secs(10));
+        if let Some(etag) = &self.marker {
+            request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+        }
+
+        let response = request.send().await.context("Language config request failed")?;
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            bail!("Language config request returned {}", response.status());
+        }
+
+        let etag = response
+            .headers()
+            .get(reqwest::header::ETAG)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.to_string());
+        let body = response.text().await.context("Failed to read language config body")?;
+
+        let manager = LanguageConfigManager::from_json(&body)?;
+        self.marker = etag;
+        Ok(Some(manager))
+    }
+}
+
+/// Check a fetched config is safe to apply to a worker bound to `language`
+pub fn validate_update(
+    current: &LanguageConfigManager,
+    incoming: &LanguageConfigManager,
+    language: &Language,
+) -> Result<()> {
+    let incoming_config = incoming
+        .get_config(language)
+        .context("Updated config no longer contains this worker's language")?;
+
+    let current_queue = current.get_queue_name(language)?;
+    if incoming_config.queue_name != current_queue {
+        bail!(
+            "Updated config moves {} from queue '{}' to '{}' - redeploy required",
+            language,
+            current_queue,
+            incoming_config.queue_name
+        );
+    }
+
+    Ok(())
+}
+
+/// Periodically refresh `manager` in place until the task is dropped
+pub async fn refresh_loop(mut fetcher: ConfigFetcher, manager: LanguageConfigManager, language: Language) {
+    let interval = ConfigSource::refresh_interval();
+    info!(source = ?fetcher.source, interval_secs = interval.as_secs(), "Language config refresh started");
+
+    loop {
+        tokio::time::sleep(interval).await;
+
+        match fetcher.fetch_if_changed().await {
+            Ok(Some(incoming)) => {
+                if let Err(e) = validate_update(&manager, &incoming, &language) {
+                    error!(error = %e, "Rejected language config update - keeping current config");
+                    continue;
+                }
+                if manager.replace_with(&incoming) {
+                    info!(
+                        marker = ?fetcher.marker,
+                        languages = ?manager.list_languages(),
+                        "✓ Language config updated"
+                    );
+                }
+            }
+            Ok(None) => {}
+            Err(e) => warn!(error = %e, "Language config refresh failed - keeping current config"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(queue: &str) -> LanguageConfigManager {
+        LanguageConfigManager::from_json(&format!(
+            r#"{{"languages": [{{
+                "name": "python", "version": "3.11", "image": "optimus-python:3.11-slim",
+                "dockerfile_path": "dockerfiles/python/Dockerfile",
+                "execution": {{"command": "python", "args": [], "file_extension": "py"}},
+                "queue_name": "{}", "memory_limit_mb": 256, "cpu_limit": 0.5
+            }}]}}"#,
+            queue
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_update_must_keep_language_and_queue() {
+        let current = config("optimus:queue:python");
+
+        assert!(validate_update(&current, &config("optimus:queue:python"), &Language::Python).is_ok());
+        assert!(validate_update(&current, &config("optimus:queue:other"), &Language::Python).is_err());
+        assert!(validate_update(&current, &current, &Language::Java).is_err());
+    }
+}
@@ -0,0 +1,93 @@
+//! Dry-Run Consumer - Admin Raw Execution
+//!
+//! **Responsibility:**
+//! Serve `POST /admin/languages/{name}/dry-run` requests by running the
+//! provided snippet through the same engine used for real jobs.
+//!
+//! **Differences from the job path:**
+//! - Consumes a dedicated admin queue (`optimus:queue:{lang}:dryrun`)
+//! - No evaluator, no score, no retries, no DLQ
+//! - Result is written to a short-lived key the API is polling
+//!
+//! Lets people adding a new language iterate on runner/image issues
+//! through the platform itself instead of shelling into worker nodes.
+
+use optimus_exec::config::LanguageConfigManager;
+use optimus_exec::engine::{self, ExecutionEngine};
+use optimus_common::redis;
+use optimus_common::types::{DryRunRequest, DryRunResult, Language, TestCase};
+use tracing::{error, info, warn};
+
+/// Consume dry-run requests for the worker's language until the task is dropped
+pub async fn dry_run_loop(
+    client: ::redis::Client,
+    language: Language,
+    config_manager: LanguageConfigManager,
+) -> anyhow::Result<()> {
+    // Dedicated connection - BLPOP here must not stall the job loop
+    let mut conn = ::redis::aio::ConnectionManager::new(client).await?;
+    let engine = engine::connect(&config_manager)?;
+
+    info!(
+        queue = %redis::dry_run_queue_name(&language),
+        "Dry-run consumer started"
+    );
+
+    loop {
+        match redis::pop_dry_run(&mut conn, &language, 5.0).await {
+            Ok(Some(request)) => {
+                let result = run_dry_run(engine.as_ref(), &request).await;
+
+                if let Err(e) = redis::store_dry_run_result(&mut conn, &result).await {
+                    error!(request_id = %request.id, error = %e, "Failed to store dry-run result");
+                }
+            }
+            Ok(None) => continue,
+            Err(e) => {
+                error!(error = %e, "Redis error in dry-run consumer");
+                tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+            }
+        }
+    }
+}
+
+/// Execute a single dry-run request and convert the raw output
+async fn run_dry_run(engine: &dyn ExecutionEngine, request: &DryRunRequest) -> DryRunResult {
+    info!(
+        request_id = %request.id,
+        language = %request.language,
+        timeout_ms = request.timeout_ms,
+        phase = "dry_run",
+        "Executing dry-run snippet"
+    );
+
+    let test_case = TestCase { input: request.input.clone(), ..Default::default() };
+    match engine
+        .execute_in_container(&request.language, &request.source_code, &test_case, None, request.timeout_ms, Default::default(), Default::default())
+        .await
+    {
+        Ok(output) => DryRunResult {
+            id: request.id,
+            language: request.language,
+            stdout: output.stdout,
+            stderr: output.stderr,
+            exit_code: output.exit_code,
+            execution_time_ms: output.execution_time_ms,
+            timed_out: output.timed_out,
+            engine_error: None,
+        },
+        Err(e) => {
+            warn!(request_id = %request.id, error = %e, "Dry-run engine failure");
+            DryRunResult {
+                id: request.id,
+                language: request.language,
+                stdout: String::new(),
+                stderr: String::new(),
+                exit_code: None,
+                execution_time_ms: 0,
+                timed_out: false,
+                engine_error: Some(format!("{:#}", e)),
+            }
+        }
+    }
+}
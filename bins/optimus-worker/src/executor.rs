@@ -1,47 +1,61 @@
-/// Job Executor - High-Level Orchestration
-///
-/// **Responsibility:**
-/// Coordinate execution engine and evaluator to produce final results.
-///
-/// **Architecture:**
-/// 1. Use DockerEngine to run code in sandboxed containers (engine.rs)
-/// 2. Use Evaluator to score outputs (evaluator.rs)
-/// 3. Return aggregated ExecutionResult
-///
-/// This module is the glue layer - it knows nothing about:
-/// - How code executes (engine's job)
-/// - How scoring works (evaluator's job)
+//! Job Executor - High-Level Orchestration
+//!
+//! **Responsibility:**
+//! Coordinate execution engine and evaluator to produce final results.
+//!
+//! **Architecture:**
+//! 1. Use an ExecutionEngine (Docker or Podman) to run code in sandboxed containers (engine.rs)
+//! 2. Use Evaluator to score outputs (evaluator.rs)
+//! 3. Return aggregated ExecutionResult
+//!
+//! This module is the glue layer - it knows nothing about:
+//! - How code executes (engine's job)
+//! - How scoring works (evaluator's job)
 
-use crate::engine::{execute_job_async, DockerEngine};
-use crate::evaluator;
-use crate::config::LanguageConfigManager;
+use optimus_exec::engine::{self, execute_job_async};
+use optimus_exec::evaluator;
+use optimus_exec::config::LanguageConfigManager;
+use optimus_exec::phases::{self, PhaseTimings};
 use optimus_common::types::{ExecutionResult, JobRequest};
 use anyhow::Result;
 
-/// Execute a job using Docker engine + evaluator
+/// Execute a job using the container engine + evaluator
 ///
 /// This is the production execution path:
-/// - DockerEngine runs code in sandboxed containers with language-specific configs
+/// - The selected engine (`OPTIMUS_CONTAINER_RUNTIME`) runs code in sandboxed
+///   containers with language-specific configs
 /// - Evaluator scores outputs
 /// - Results are aggregated
-/// - Cooperative cancellation is checked between test cases
+/// - Up to `max_parallel_tests` test containers run at once (lowered by the
+///   job's own `max_parallel_tests`), or one shared container runs them in
+///   turn (`per_job_container`)
+/// - Cooperative cancellation is checked before each test starts
+/// - Container and evaluation phases are added to `phases`
 pub async fn execute_docker(
     job: &JobRequest,
     config_manager: &LanguageConfigManager,
     redis_conn: &mut redis::aio::ConnectionManager,
+    phases: &mut PhaseTimings,
+    max_parallel_tests: usize,
+    per_job_container: bool,
 ) -> Result<ExecutionResult> {
     println!("→ Starting job execution: {}", job.id);
-    println!("  Using: DockerEngine + Evaluator");
+    println!("  Using: {} engine + Evaluator", optimus_exec::container_runtime::selected());
     println!();
 
-    // Step 1: Create Docker engine with config manager
-    let engine = DockerEngine::new_with_config(config_manager)?;
+    // Step 1: Create the engine with config manager
+    let engine = engine::connect(config_manager)?;
 
-    // Step 2: Execute with Docker engine (with cancellation support)
-    let outputs = execute_job_async(job, &engine, redis_conn).await;
+    // Step 2: Execute with the engine (with cancellation support)
+    let max_parallel_tests = job
+        .max_parallel_tests
+        .map_or(max_parallel_tests, |n| (n as usize).clamp(1, max_parallel_tests.max(1)));
+    let outputs = execute_job_async(job, engine.as_ref(), redis_conn, phases, max_parallel_tests, per_job_container).await;
 
     // Step 3: Evaluate outputs
+    let evaluate_start = std::time::Instant::now();
     let result = evaluator::evaluate(job, outputs);
+    phases.add(phases::EVALUATE, evaluate_start.elapsed());
 
     Ok(result)
 }
@@ -0,0 +1,44 @@
+//! Heartbeat - Worker Presence for the API
+//!
+//! **Responsibility:**
+//! Tell the API that this language has a live consumer, so submissions for a
+//! language with no workers are rejected or parked instead of silently
+//! sitting in the queue.
+//!
+//! **Protocol:**
+//! - Every `HEARTBEAT_INTERVAL_SECS` the worker scores its id with the current
+//!   time in `optimus:workers:{language}`
+//! - Ids older than `HEARTBEAT_STALE_SECS` don't count (crashed pods age out)
+//! - A clean shutdown removes the id right away
+//!
+//! **Registration:**
+//! Each heartbeat also rewrites `optimus:worker:{worker_id}` (version,
+//! capacity and current load) with the same staleness window, which the
+//! API lists at `GET /workers`.
+
+use optimus_common::redis;
+use optimus_common::types::WorkerInfo;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tracing::{info, warn};
+
+/// Refresh this worker's heartbeat until the task is dropped
+/// Load is read from the job semaphore (permits in use)
+pub async fn heartbeat_loop(
+    mut conn: ::redis::aio::ConnectionManager,
+    mut info: WorkerInfo,
+    semaphore: Arc<Semaphore>,
+) {
+    info!("Heartbeat started ({}s interval)", redis::HEARTBEAT_INTERVAL_SECS);
+
+    let mut ticker = tokio::time::interval(Duration::from_secs(redis::HEARTBEAT_INTERVAL_SECS));
+    loop {
+        ticker.tick().await;
+        info.active_jobs = info.max_parallel_jobs.saturating_sub(semaphore.available_permits());
+        info.last_heartbeat = chrono::Utc::now().to_rfc3339();
+        if let Err(e) = redis::record_worker_heartbeat(&mut conn, &info).await {
+            warn!(error = %e, "Failed to record worker heartbeat");
+        }
+    }
+}
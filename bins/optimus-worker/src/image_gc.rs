@@ -0,0 +1,322 @@
+//! Image GC - Keep Rotated Language Images From Filling the Node
+//!
+//! **Responsibility:**
+//! Every language version bump leaves the previous image (and any dangling
+//! rebuilds) on each worker node. Periodically remove Optimus images that
+//! nothing uses any more, once they are older than an age budget or the
+//! node's Optimus images exceed a size budget.
+//!
+//! **What counts as an Optimus image:**
+//! - Labeled `optimus.managed=true` (images generated by `optimus-cli`),
+//!   including dangling ones left behind by a rebuild
+//! - Tagged (or pulled by digest) under the repository of a configured
+//!   language image, e.g. `optimus-python:3.10-slim` once python moved to 3.11
+//!
+//! Images tagged outside those repositories are never touched.
+//!
+//! **Never removed:**
+//! - Any language's configured image
+//! - Images of existing containers (any worker, any state)
+//!
+//! **Age:**
+//! Measured from the later of the image's creation and the last time this
+//! worker saw it configured or in use (tracked in memory per worker). A
+//! restarted worker falls back to creation time, which is what rotated
+//! images need.
+//!
+//! **Size budget (`IMAGE_GC_MAX_GB`):**
+//! When the Optimus images on the node add up to more than the budget, the
+//! longest-idle removable images go first, whatever their age. Sizes are as
+//! Docker reports them, so layers shared between images count once per image.
+//!
+//! Removals never force: an image a container grabbed in the meantime stays.
+//! Several workers sharing a daemon each sweep; the loser of a race just sees
+//! the image gone.
+
+use crate::metrics;
+use bollard::container::ListContainersOptions;
+use bollard::image::{ListImagesOptions, RemoveImageOptions};
+use bollard::Docker;
+use optimus_common::types::Language;
+use optimus_exec::config::LanguageConfigManager;
+use optimus_exec::{container_labels, container_runtime, docker_api};
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// When and how aggressively to prune
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImageGcPolicy {
+    pub interval: Duration,
+    /// Unused images idle for longer are removed
+    pub max_age: Duration,
+    /// Total size of Optimus images to stay under (None: no size budget)
+    pub max_bytes: Option<u64>,
+}
+
+impl ImageGcPolicy {
+    /// `IMAGE_GC_INTERVAL_SECS` (default 3600, 0 disables),
+    /// `IMAGE_GC_MAX_AGE_HOURS` (default 168) and `IMAGE_GC_MAX_GB` (default unset)
+    pub fn from_env() -> Option<Self> {
+        let var = |name: &str| std::env::var(name).ok().and_then(|v| v.trim().parse::<f64>().ok());
+        let interval_secs = var("IMAGE_GC_INTERVAL_SECS").unwrap_or(3600.0);
+        if interval_secs <= 0.0 {
+            return None;
+        }
+        Some(Self {
+            interval: Duration::from_secs_f64(interval_secs),
+            max_age: Duration::from_secs_f64(var("IMAGE_GC_MAX_AGE_HOURS").unwrap_or(168.0).max(0.0) * 3600.0),
+            max_bytes: var("IMAGE_GC_MAX_GB").filter(|gb| *gb > 0.0).map(|gb| (gb * 1e9) as u64),
+        })
+    }
+}
+
+/// Why an image was removed (`reason` label)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reason {
+    Age,
+    Size,
+}
+
+impl Reason {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Age => "age",
+            Self::Size => "size",
+        }
+    }
+}
+
+/// One Optimus image on the node, as the planner sees it
+#[derive(Debug, Clone)]
+struct NodeImage {
+    id: String,
+    /// Tags to remove (empty for a dangling image, which is removed by id)
+    tags: Vec<String>,
+    size: u64,
+    /// Unix seconds of its creation or the last time this worker saw it used
+    last_active: i64,
+    /// Neither configured nor used by a container
+    removable: bool,
+}
+
+/// Images to remove, in removal order
+fn plan(images: &[NodeImage], now: i64, policy: &ImageGcPolicy) -> Vec<(usize, Reason)> {
+    let max_age = policy.max_age.as_secs() as i64;
+    let mut candidates: Vec<usize> = (0..images.len()).filter(|&i| images[i].removable).collect();
+    candidates.sort_by_key(|&i| images[i].last_active);
+
+    let mut total: u64 = images.iter().map(|image| image.size).sum();
+    let mut removals = Vec::new();
+    for i in candidates {
+        let reason = if now - images[i].last_active > max_age {
+            Reason::Age
+        } else if policy.max_bytes.is_some_and(|budget| total > budget) {
+            Reason::Size
+        } else {
+            continue;
+        };
+        total = total.saturating_sub(images[i].size);
+        removals.push((i, reason));
+    }
+    removals
+}
+
+/// Repository part of an image reference (`registry:5000/optimus-python:3.11` → `registry:5000/optimus-python`)
+fn repository(reference: &str) -> &str {
+    if let Some((repository, _digest)) = reference.split_once('@') {
+        return repository;
+    }
+    match reference.rsplit_once(':') {
+        Some((repository, tag)) if !tag.contains('/') => repository,
+        _ => reference,
+    }
+}
+
+/// Tags that name an image (Docker reports dangling images as `<none>:<none>`)
+fn real_tags(tags: &[String]) -> Vec<String> {
+    tags.iter().filter(|tag| tag.as_str() != "<none>:<none>").cloned().collect()
+}
+
+/// Prune images every `policy.interval` until the task is dropped
+pub async fn gc_loop(config_manager: LanguageConfigManager, policy: ImageGcPolicy) {
+    info!(
+        "Image GC started (every {}s, max age {}h, size budget {})",
+        policy.interval.as_secs(),
+        policy.max_age.as_secs() / 3600,
+        policy.max_bytes.map_or("none".to_string(), |bytes| format!("{:.1} GB", bytes as f64 / 1e9))
+    );
+
+    // Image id -> last unix second this worker saw it configured or in use
+    let mut last_seen: HashMap<String, i64> = HashMap::new();
+    let mut ticker = tokio::time::interval(policy.interval);
+    loop {
+        ticker.tick().await;
+        let docker = match container_runtime::connect() {
+            Ok(docker) => docker,
+            Err(e) => {
+                warn!(error = %e, "Image GC skipped - container engine unavailable");
+                continue;
+            }
+        };
+        if let Err(e) = sweep(&docker, &config_manager, &policy, &mut last_seen).await {
+            warn!(error = %e, "Image GC sweep failed");
+        }
+    }
+}
+
+/// One pass: find Optimus images, plan against the budgets and remove
+async fn sweep(
+    docker: &Docker,
+    config_manager: &LanguageConfigManager,
+    policy: &ImageGcPolicy,
+    last_seen: &mut HashMap<String, i64>,
+) -> anyhow::Result<()> {
+    let now = chrono::Utc::now().timestamp();
+
+    // Configured images, by tag and (when present locally) by id
+    let configured: Vec<String> = config_manager
+        .list_languages()
+        .iter()
+        .filter_map(|name| Language::from_str(name))
+        .filter_map(|language| config_manager.get_image(&language).ok())
+        .collect();
+    let repositories: HashSet<&str> = configured.iter().map(|image| repository(image)).collect();
+    let mut protected: HashSet<String> = HashSet::new();
+    for image in &configured {
+        if let Ok(inspect) = docker_api::call("inspect_image", || docker.inspect_image(image)).await {
+            protected.extend(inspect.id);
+        }
+    }
+
+    let containers = docker_api::call("list_containers", || {
+        docker.list_containers(Some(ListContainersOptions::<String> { all: true, ..Default::default() }))
+    })
+    .await?;
+    protected.extend(containers.into_iter().filter_map(|container| container.image_id));
+
+    let summaries = docker_api::call("list_images", || {
+        docker.list_images(Some(ListImagesOptions::<String> { all: false, ..Default::default() }))
+    })
+    .await?;
+
+    let mut images = Vec::new();
+    for summary in summaries {
+        let tags = real_tags(&summary.repo_tags);
+        let labeled = summary.labels.get(container_labels::LABEL_MANAGED).is_some_and(|v| v == "true");
+        let configured_repository = |reference: &String| repositories.contains(repository(reference));
+        // Unlabeled images only when every tag (or, untagged, a digest) is under a configured repository
+        let ours = labeled
+            || if tags.is_empty() {
+                summary.repo_digests.iter().any(configured_repository)
+            } else {
+                tags.iter().all(configured_repository)
+            };
+        if !ours {
+            continue;
+        }
+
+        let removable = !protected.contains(&summary.id);
+        if !removable {
+            last_seen.insert(summary.id.clone(), now);
+        }
+        let last_active = summary.created.max(last_seen.get(&summary.id).copied().unwrap_or(0));
+        images.push(NodeImage {
+            id: summary.id,
+            tags,
+            size: summary.size.max(0) as u64,
+            last_active,
+            removable,
+        });
+    }
+
+    let total: u64 = images.iter().map(|image| image.size).sum();
+    metrics::OPTIMUS_IMAGE_BYTES.set(total as i64);
+
+    let removals = plan(&images, now, policy);
+    if removals.is_empty() {
+        debug!(images = images.len(), total_bytes = total, "Image GC: nothing to remove");
+        return Ok(());
+    }
+
+    for (i, reason) in removals {
+        let image = &images[i];
+        let targets: Vec<&str> = if image.tags.is_empty() {
+            vec![image.id.as_str()]
+        } else {
+            image.tags.iter().map(String::as_str).collect()
+        };
+        let mut removed = true;
+        for target in targets {
+            let options = Some(RemoveImageOptions { force: false, noprune: false });
+            match docker_api::call("remove_image", || docker.remove_image(target, options, None)).await {
+                Ok(_) => {}
+                Err(bollard::errors::Error::DockerResponseServerError { status_code: 404, .. }) => {}
+                Err(e) => {
+                    // 409: a container started from it since the listing
+                    debug!(image = %target, error = %e, "Image GC: image not removed");
+                    removed = false;
+                    break;
+                }
+            }
+        }
+        if removed {
+            last_seen.remove(&image.id);
+            metrics::IMAGE_GC_REMOVED.with_label_values(&[reason.as_str()]).inc();
+            metrics::IMAGE_GC_RECLAIMED_BYTES.inc_by(image.size as f64);
+            info!(
+                image = %image.tags.first().map_or(image.id.as_str(), String::as_str),
+                size_bytes = image.size,
+                reason = reason.as_str(),
+                "Image GC: removed unused image"
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(id: &str, size: u64, last_active: i64, removable: bool) -> NodeImage {
+        NodeImage {
+            id: id.to_string(),
+            tags: vec![],
+            size,
+            last_active,
+            removable,
+        }
+    }
+
+    #[test]
+    fn test_plan_age_and_size_budgets() {
+        let day = 86400;
+        let now = 100 * day;
+        let images = vec![
+            image("current", 500, now, false),
+            image("old", 300, now - 10 * day, true),
+            image("recent", 200, now - day, true),
+            image("newest", 100, now - 60, true),
+        ];
+
+        let aged = ImageGcPolicy { interval: Duration::from_secs(60), max_age: Duration::from_secs(7 * day as u64), max_bytes: None };
+        assert_eq!(plan(&images, now, &aged), vec![(1, Reason::Age)]);
+
+        // 1100 bytes against a 700 budget: "old" ages out, then "recent" goes for size
+        let budget = ImageGcPolicy { max_bytes: Some(700), ..aged };
+        assert_eq!(plan(&images, now, &budget), vec![(1, Reason::Age), (2, Reason::Size)]);
+
+        // Protected images count towards the budget but are never removed
+        let tight = ImageGcPolicy { max_bytes: Some(1), ..aged };
+        assert_eq!(plan(&images, now, &tight).len(), 3);
+    }
+
+    #[test]
+    fn test_repository() {
+        assert_eq!(repository("optimus-python:3.11-slim"), "optimus-python");
+        assert_eq!(repository("registry:5000/optimus-python:3.11"), "registry:5000/optimus-python");
+        assert_eq!(repository("registry:5000/optimus-python"), "registry:5000/optimus-python");
+        assert_eq!(repository("optimus-python@sha256:abc"), "optimus-python");
+    }
+}
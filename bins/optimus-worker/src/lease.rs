@@ -0,0 +1,77 @@
+//! Leases - At-Least-Once Job Delivery
+//!
+//! **Responsibility:**
+//! A worker takes a job by moving it from its queue into its own in-flight
+//! list (`optimus:inflight:{worker_id}`) and acknowledges it once the job is
+//! fully handled (result stored, retried or dead-lettered). A crash in
+//! between leaves the job in the list instead of losing it.
+//!
+//! **Processing heartbeat:**
+//! `optimus:processing:{job_id}` is set when the job is taken and refreshed
+//! every `PROCESSING_HEARTBEAT_SECS` while this worker holds it. Once it
+//! lapses, the API's orphan reaper hands the job back to the retry queue
+//! (or the DLQ on its last attempt).
+//!
+//! On a clean shutdown the interrupted job goes back right away, without
+//! using up an attempt. Delivery is at-least-once: a worker lost after
+//! storing a result but before acknowledging it means the job runs again.
+
+use optimus_common::redis::{self, LeasedJob};
+use std::time::Duration;
+use tracing::{error, warn};
+use uuid::Uuid;
+
+/// Acknowledge a handled job
+/// Failures are logged: the job stays in the in-flight list and runs again
+/// once its heartbeat lapses
+pub async fn ack(conn: &mut ::redis::aio::ConnectionManager, worker_id: &str, leased: &LeasedJob) {
+    if let Err(e) = redis::ack_job(conn, worker_id, leased).await {
+        error!(job_id = %leased.job.id, error = %e, "Failed to acknowledge job - it will be redelivered");
+    }
+}
+
+/// Refreshes a job's processing heartbeat until dropped
+pub struct JobHeartbeat(tokio::task::JoinHandle<()>);
+
+impl JobHeartbeat {
+    pub fn start(mut conn: ::redis::aio::ConnectionManager, worker_id: &str, job_id: Uuid) -> Self {
+        let worker_id = worker_id.to_string();
+        Self(tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(Duration::from_secs(redis::PROCESSING_HEARTBEAT_SECS));
+            // The first tick is immediate and the heartbeat was just set with the lease
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                if let Err(e) = redis::touch_processing(&mut conn, &job_id, &worker_id).await {
+                    warn!(job_id = %job_id, error = %e, "Failed to refresh processing heartbeat");
+                }
+            }
+        }))
+    }
+}
+
+impl Drop for JobHeartbeat {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Put every job left in this worker's in-flight list back on its retry
+/// queue as it was (shutdown mid-job)
+pub async fn hand_back_interrupted(conn: &mut ::redis::aio::ConnectionManager, worker_id: &str) {
+    let leased = match redis::list_leased_jobs(conn, worker_id).await {
+        Ok(leased) => leased,
+        Err(e) => {
+            error!(error = %e, "Failed to read this worker's in-flight list");
+            return;
+        }
+    };
+    for leased in leased {
+        let mut job = leased.job.clone();
+        match redis::requeue_leased_job(conn, worker_id, &leased, &mut job, false, false).await {
+            Ok(true) => warn!(job_id = %job.id, "Interrupted job handed back to the retry queue"),
+            Ok(false) => {}
+            Err(e) => error!(job_id = %job.id, error = %e, "Failed to hand back interrupted job"),
+        }
+    }
+}
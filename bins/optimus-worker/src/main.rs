@@ -1,17 +1,27 @@
-mod engine;
-mod evaluator;
 mod executor;
-mod config;
+mod dry_run;
+mod batch;
+mod config_sync;
+mod heartbeat;
+mod image_gc;
+mod lease;
+mod metrics;
+mod probes;
+mod startup;
+mod warmup;
+mod watchdog;
 
 use optimus_common::redis;
-use optimus_common::types::Language;
+use optimus_common::types::{Language, WorkerInfo};
 use optimus_common::config::WorkerConfig;
+#[cfg(not(unix))]
 use tokio::signal;
 use tokio::sync::{Semaphore, RwLock};
 use std::sync::Arc;
-use config::LanguageConfigManager;
+use optimus_exec::config::LanguageConfigManager;
+use optimus_exec::{container_labels, container_runtime, containerd, docker_api, engine, evaluator, firecracker, kubernetes, output_budget, phases, sandbox};
 use tracing::{info, error, warn, debug, instrument};
-use bollard::{Docker, image::CreateImageOptions};
+use bollard::image::CreateImageOptions;
 use futures_util::stream::StreamExt;
 use axum::{
     extract::State,
@@ -23,11 +33,16 @@ use axum::{
 use tokio::net::TcpListener;
 use serde::Serialize;
 
+/// How long shutdown waits for dropped jobs' containers to be removed
+const CLEANUP_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
 /// Shared worker state for health checks
 #[derive(Clone)]
 struct WorkerState {
-    redis_url: String,
+    redis: ::redis::Client,
     is_executing: Arc<RwLock<bool>>,
+    config_manager: LanguageConfigManager,
+    language: Language,
 }
 
 #[derive(Serialize)]
@@ -48,29 +63,19 @@ async fn health_handler(State(state): State<WorkerState>) -> impl IntoResponse {
     )
 }
 
-/// Readiness probe - checks Redis connectivity and execution state
+/// Readiness probe - checks Redis connectivity, the container runtime and execution state
 async fn ready_handler(State(state): State<WorkerState>) -> impl IntoResponse {
-    // Check Redis connectivity
-    let redis_ok = match ::redis::Client::open(state.redis_url.as_str()) {
-        Ok(client) => {
-            match client.get_async_connection().await {
-                Ok(mut conn) => {
-                    ::redis::cmd("PING")
-                        .query_async::<_, String>(&mut conn)
-                        .await
-                        .is_ok()
-                }
-                Err(_) => false,
-            }
-        }
-        Err(_) => false,
-    };
-
+    let redis_ok = probes::redis(&state.redis).await == "ok";
+    let runtime = probes::runtime(&state.config_manager, &state.language).await;
+    let runtime_ok = runtime == "ok" || runtime == "skipped";
+    if !runtime_ok {
+        warn!("Container runtime for {} unavailable: {}", state.language, runtime);
+    }
     let executing = *state.is_executing.read().await;
     
-    // Worker is ready if Redis is reachable AND not currently executing
+    // Worker is ready if Redis is reachable, its runtime exists AND it is not currently executing
     // This allows KEDA to scale down idle workers safely
-    let is_ready = redis_ok && !executing;
+    let is_ready = redis_ok && runtime_ok && !executing;
     
     let status_code = if is_ready {
         StatusCode::OK
@@ -87,11 +92,60 @@ async fn ready_handler(State(state): State<WorkerState>) -> impl IntoResponse {
     )
 }
 
+/// Dependency probes (see `probes`), 503 with the failed checks if any fails
+async fn probe_response(state: &WorkerState, checks: probes::Checks, passed: &'static str) -> impl IntoResponse {
+    let executing = *state.is_executing.read().await;
+    let ok = probes::all_passed(&checks);
+    if !ok {
+        warn!(checks = ?checks, "Worker probe failed");
+    }
+    (
+        if ok { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE },
+        Json(probes::ProbeResponse {
+            status: if ok { passed } else { "unhealthy" },
+            executing_job: executing,
+            checks,
+        }),
+    )
+}
+
+/// Liveness with dependencies - Redis and the container engine answer
+async fn healthz_handler(State(state): State<WorkerState>) -> impl IntoResponse {
+    let (redis, engine) = tokio::join!(probes::redis(&state.redis), probes::container_engine());
+    let checks = probes::Checks::from([("redis", redis), ("container_engine", engine)]);
+    probe_response(&state, checks, "healthy").await
+}
+
+/// Readiness with dependencies - also the language's image and runtime
+async fn readyz_handler(State(state): State<WorkerState>) -> impl IntoResponse {
+    let (redis, engine, image, runtime) = tokio::join!(
+        probes::redis(&state.redis),
+        probes::container_engine(),
+        probes::image(&state.config_manager, &state.language),
+        probes::runtime(&state.config_manager, &state.language),
+    );
+    let checks = probes::Checks::from([
+        ("redis", redis),
+        ("container_engine", engine),
+        ("image", image),
+        ("runtime", runtime),
+    ]);
+    probe_response(&state, checks, "ready").await
+}
+
+/// Prometheus metrics for this worker process
+async fn metrics_handler() -> impl IntoResponse {
+    metrics::render_metrics()
+}
+
 /// Start health check HTTP server
 async fn start_health_server(state: WorkerState) -> anyhow::Result<()> {
     let app = Router::new()
         .route("/health", get(health_handler))
         .route("/ready", get(ready_handler))
+        .route("/healthz", get(healthz_handler))
+        .route("/readyz", get(readyz_handler))
+        .route("/metrics", get(metrics_handler))
         .with_state(state);
 
     let port = std::env::var("HEALTH_PORT")
@@ -108,7 +162,7 @@ async fn start_health_server(state: WorkerState) -> anyhow::Result<()> {
 /// Pre-pull a Docker image (best-effort)
 /// Returns Ok(true) if image was pulled, Ok(false) if already present
 async fn prepull_image(image: &str) -> anyhow::Result<bool> {
-    let docker = Docker::connect_with_local_defaults()?;
+    let docker = container_runtime::connect()?;
     
     // Check if image exists locally
     if docker.inspect_image(image).await.is_ok() {
@@ -148,139 +202,191 @@ async fn main() -> anyhow::Result<()> {
     info!("Optimus Worker booting...");
 
     // Load worker concurrency configuration
-    let worker_config = WorkerConfig::from_env();
+    let mut worker_config = WorkerConfig::from_env();
+    if let Err(e) = worker_config.apply_args(std::env::args().skip(1)) {
+        error!("❌ FATAL: {}", e);
+        error!("Usage: optimus-worker [--run-once] [--max-jobs N] [--max-idle-seconds S]");
+        std::process::exit(2);
+    }
     info!(
-        "Worker concurrency config: max_parallel_jobs={}, max_parallel_tests={}",
+        "Worker concurrency config: max_parallel_jobs={}, max_parallel_tests={}, execution_mode={}",
         worker_config.max_parallel_jobs,
-        worker_config.max_parallel_tests
+        worker_config.max_parallel_tests,
+        if worker_config.per_job_container { "per_job" } else { "per_test" }
     );
 
-    // Load language configurations
-    let config_manager = LanguageConfigManager::load_default()
-        .map_err(|e| {
-            error!("Failed to load language configurations: {}", e);
-            error!("Make sure config/languages.json exists");
-            e
-        })?;
-    
-    info!("Loaded language configurations for: {:?}", config_manager.list_languages());
-
-    // Pre-pull all language images (best-effort, async, non-blocking)
-    info!("Pre-pulling language images to warm cache...");
-    let prepull_config_manager = config_manager.clone();
-    tokio::spawn(async move {
-        for lang_name in prepull_config_manager.list_languages() {
-            if let Some(lang) = Language::from_str(&lang_name) {
-                if let Ok(image) = prepull_config_manager.get_image(&lang) {
-                    info!("Pre-pulling image: {}", image);
-                    match prepull_image(&image).await {
-                        Ok(true) => info!("✓ Image cached: {}", image),
-                        Ok(false) => info!("✓ Image already present: {}", image),
-                        Err(e) => warn!("⚠ Failed to pre-pull {}: {} (will retry during execution)", image, e),
-                    }
-                }
-            }
-        }
-        info!("✓ Image pre-pull complete");
+    metrics::init_metrics();
+    docker_api::init(worker_config.docker_max_concurrent_calls);
+    info!(
+        "Docker API limiter initialized with {} concurrent calls",
+        worker_config.docker_max_concurrent_calls
+    );
+    let runtime = container_runtime::ContainerRuntime::from_env().unwrap_or_else(|e| {
+        error!("❌ FATAL: {}", e);
+        std::process::exit(2);
     });
-
-    // ===== LANGUAGE BINDING ENFORCEMENT =====
-    // Worker MUST be bound to exactly one language via environment variables
-    // This is non-negotiable for proper scaling and isolation
-    
-    // 1. Validate OPTIMUS_LANGUAGE is set (REQUIRED)
-    let language_str = std::env::var("OPTIMUS_LANGUAGE")
-        .unwrap_or_else(|_| {
-            error!("❌ FATAL: OPTIMUS_LANGUAGE environment variable not set");
-            error!("Worker must be bound to a specific language (python, java, rust)");
-            error!("This worker cannot start without language specification");
+    container_runtime::init(runtime);
+    if runtime == container_runtime::ContainerRuntime::Podman {
+        // Docker may come up after the worker; Podman's socket has no such excuse
+        let checked = match container_runtime::connect() {
+            Ok(podman) => runtime.check(&podman).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = checked {
+            error!("❌ FATAL: {:#}", e);
+            error!("Start the API socket with `podman system service --time=0` or set CONTAINER_HOST");
             std::process::exit(1);
-        });
-    
-    let language = match Language::from_str(&language_str) {
-        Some(lang) => lang,
-        None => {
-            error!("❌ FATAL: Invalid language: {}", language_str);
-            let valid_languages: Vec<String> = Language::all_variants()
-                .iter()
-                .map(|l| l.to_string())
-                .collect();
-            error!("Valid options: {}", valid_languages.join(", "));
+        }
+    } else if runtime.is_daemonless() {
+        let checked = match runtime {
+            container_runtime::ContainerRuntime::Firecracker => firecracker::check(),
+            container_runtime::ContainerRuntime::Kubernetes => kubernetes::check().await,
+            container_runtime::ContainerRuntime::Containerd => containerd::check().await,
+            _ => sandbox::check(runtime),
+        };
+        if let Err(e) = checked {
+            error!("❌ FATAL: {:#}", e);
+            error!("Set up {} for this worker (see the README) or pick a container runtime", runtime);
             std::process::exit(1);
         }
+    }
+    info!("Container engine: {}", runtime);
+    let capture = output_budget::CaptureLimits {
+        stdout_bytes: worker_config.max_captured_output_kb * 1024,
+        stderr_bytes: worker_config.max_captured_stderr_kb.unwrap_or(worker_config.max_captured_output_kb) * 1024,
+        stderr_tail: worker_config.stderr_keep_tail,
     };
-
-    // 2. Validate language configuration exists
-    if let Err(e) = config_manager.get_config(&language) {
-        error!("❌ FATAL: Language '{}' is not configured: {}", language, e);
-        error!("Available languages: {:?}", config_manager.list_languages());
-        std::process::exit(1);
+    let output_budget_bytes = output_budget::init(
+        worker_config.max_output_buffer_mb,
+        worker_config.max_log_collectors,
+        capture,
+    );
+    info!(
+        "Output budget: {} MB across at most {} log collectors",
+        output_budget_bytes / (1024 * 1024),
+        worker_config.max_log_collectors
+    );
+    info!(
+        "Per-test capture: {} KB stdout, {} KB stderr ({})",
+        capture.stdout_bytes / 1024,
+        capture.stderr_bytes / 1024,
+        if capture.stderr_tail { "tail" } else { "head" }
+    );
+    info!("Job runtime watchdog ceiling: {}s", worker_config.max_job_runtime_secs);
+    if let Some(max_jobs) = worker_config.max_jobs {
+        info!("Run-once mode: exiting after {} job(s)", max_jobs);
+    }
+    if let Some(max_idle) = worker_config.max_idle_secs {
+        info!("Run-once mode: exiting after {}s without a job", max_idle);
     }
 
-    // 3. Validate OPTIMUS_QUEUE matches language (REQUIRED)
-    let expected_queue = config_manager.get_queue_name(&language)?;
-    let queue_name = std::env::var("OPTIMUS_QUEUE")
-        .unwrap_or_else(|_| {
-            error!("❌ FATAL: OPTIMUS_QUEUE environment variable not set");
-            error!("Expected queue for {}: {}", language, expected_queue);
-            error!("Worker cannot start without queue specification");
-            std::process::exit(1);
+    // Redis client (connections are opened later; config sync may need it first)
+    let redis_url = std::env::var("REDIS_URL")
+        .unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string());
+    let client = ::redis::Client::open(redis_url.as_str())?;
+
+    // Stable identity for this worker process (pod name in Kubernetes)
+    let worker_id = std::env::var("OPTIMUS_WORKER_ID")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .unwrap_or_else(|_| format!("worker-{}", uuid::Uuid::new_v4()));
+
+    // ===== LANGUAGE BINDING ENFORCEMENT =====
+    // Load language configurations (central source first, local file as
+    // fallback) and validate this worker's binding against them; see `startup`
+    let config_source = config_sync::ConfigSource::from_env();
+    let mut config_fetcher = config_sync::ConfigFetcher::new(config_source.clone(), client.clone());
+    let (config_manager, binding) = startup::run(
+        startup::ValidationMode::from_env(),
+        &mut config_fetcher,
+        &config_source,
+        &client,
+        &worker_id,
+    )
+    .await;
+    let startup::Binding { language, pool, queue_name, image } = binding;
+
+    info!("Loaded language configurations for: {:?}", config_manager.list_languages());
+
+    // Pre-pull all language images (best-effort, async, non-blocking);
+    // sandboxes and microVMs don't run images, Kubernetes nodes pull their own
+    // and containerd images are pulled into the worker's namespace by hand
+    if !runtime.is_daemonless() {
+        info!("Pre-pulling language images to warm cache...");
+        let prepull_config_manager = config_manager.clone();
+        tokio::spawn(async move {
+            for lang_name in prepull_config_manager.list_languages() {
+                if let Some(lang) = Language::from_str(&lang_name) {
+                    if let Ok(image) = prepull_config_manager.get_image(&lang) {
+                        info!("Pre-pulling image: {}", image);
+                        match prepull_image(&image).await {
+                            Ok(true) => info!("✓ Image cached: {}", image),
+                            Ok(false) => info!("✓ Image already present: {}", image),
+                            Err(e) => warn!("⚠ Failed to pre-pull {}: {} (will retry during execution)", image, e),
+                        }
+                    }
+                }
+            }
+            info!("✓ Image pre-pull complete");
         });
-    
-    if queue_name != expected_queue {
-        error!("❌ FATAL: Queue mismatch detected");
-        error!("  Configured language: {}", language);
-        error!("  Expected queue: {}", expected_queue);
-        error!("  Actual queue: {}", queue_name);
-        error!("This configuration would cause routing bugs. Refusing to start.");
-        std::process::exit(1);
     }
 
-    // 4. Validate OPTIMUS_IMAGE matches language (REQUIRED)
-    let expected_image = config_manager.get_image(&language)?;
-    let image = std::env::var("OPTIMUS_IMAGE")
-        .unwrap_or_else(|_| {
-            error!("❌ FATAL: OPTIMUS_IMAGE environment variable not set");
-            error!("Expected image for {}: {}", language, expected_image);
-            error!("Worker cannot start without image specification");
-            std::process::exit(1);
-        });
-    
-    if image != expected_image {
-        error!("❌ FATAL: Image mismatch detected");
-        error!("  Configured language: {}", language);
-        error!("  Expected image: {}", expected_image);
-        error!("  Actual image: {}", image);
-        error!("This configuration would cause execution bugs. Refusing to start.");
-        std::process::exit(1);
+    // Prune rotated and dangling language images (same engines as the pre-pull)
+    if !runtime.is_daemonless() {
+        if let Some(policy) = image_gc::ImageGcPolicy::from_env() {
+            tokio::spawn(image_gc::gc_loop(config_manager.clone(), policy));
+        }
     }
 
     // ===== ALL VALIDATIONS PASSED =====
-    
+
+    info!("Worker id: {}", worker_id);
+    container_labels::init(&worker_id);
     info!("Worker configured for language: {}", language);
     info!("Docker image: {}", image);
     info!("Queue: {}", queue_name);
+    if let Some(ref pool) = pool {
+        info!("Worker pool: {}", pool);
+    }
 
     // Connect to Redis
-    let redis_url = std::env::var("REDIS_URL")
-        .unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string());
-    
-    let client = ::redis::Client::open(redis_url.as_str())?;
-    let mut redis_conn = ::redis::aio::ConnectionManager::new(client).await?;
+    let mut redis_conn = ::redis::aio::ConnectionManager::new(client.clone()).await?;
     
     info!("Connected to Redis: {}", redis_url);
+
+    // Per-node latency baseline before taking any jobs
+    warmup::run(&mut redis_conn, &config_manager, &language, &worker_id, warmup::runs_from_env()).await;
+
     info!("Worker is READY - waiting for jobs from queue: {}", queue_name);
 
     // Create semaphore for concurrency control
     // This guarantees at most max_parallel_jobs jobs execute simultaneously
     let semaphore = Arc::new(Semaphore::new(worker_config.max_parallel_jobs));
     info!("Concurrency semaphore initialized with {} permits", worker_config.max_parallel_jobs);
+    if let Some(limit) = worker_config.global_max_concurrent_jobs {
+        info!("Cluster-wide concurrency limit for {}: {} jobs", language, limit);
+    }
+
+    // Announce this worker to the API (dedicated connection, never blocked by job handling)
+    let heartbeat_conn = ::redis::aio::ConnectionManager::new(client.clone()).await?;
+    let registration = WorkerInfo {
+        worker_id: worker_id.clone(),
+        language,
+        pool: pool.clone(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        max_parallel_jobs: worker_config.max_parallel_jobs,
+        active_jobs: 0,
+        started_at: chrono::Utc::now().to_rfc3339(),
+        last_heartbeat: String::new(),
+    };
+    let heartbeat = tokio::spawn(heartbeat::heartbeat_loop(heartbeat_conn, registration, semaphore.clone()));
 
     // Create shared state for health checks
     let is_executing = Arc::new(RwLock::new(false));
     let health_state = WorkerState {
-        redis_url: redis_url.clone(),
+        redis: client.clone(),
         is_executing: is_executing.clone(),
+        config_manager: config_manager.clone(),
+        language,
     };
 
     // Start health check server in background
@@ -290,6 +396,19 @@ async fn main() -> anyhow::Result<()> {
         }
     });
 
+    // Keep language config fresh from the central source
+    if config_source != config_sync::ConfigSource::File {
+        tokio::spawn(config_sync::refresh_loop(config_fetcher, config_manager.clone(), language));
+    }
+
+    // Start admin dry-run consumer in background (separate queue, no scoring)
+    let dry_run_config_manager = config_manager.clone();
+    tokio::spawn(async move {
+        if let Err(e) = dry_run::dry_run_loop(client, language, dry_run_config_manager).await {
+            error!("Dry-run consumer error: {}", e);
+        }
+    });
+
     // Setup graceful shutdown - handles both SIGTERM (Kubernetes) and SIGINT (CTRL+C)
     let shutdown = async {
         #[cfg(unix)]
@@ -314,35 +433,157 @@ async fn main() -> anyhow::Result<()> {
             signal::ctrl_c().await.expect("failed to install CTRL+C signal handler");
             warn!("⚠️  Received CTRL+C - initiating graceful shutdown");
         }
-        warn!("Worker will finish current job and exit cleanly");
+        warn!("Worker will stop taking jobs and finish the ones in flight");
     };
 
-    tokio::select! {
-        _ = worker_loop(&mut redis_conn, &language, &config_manager, semaphore, is_executing) => {},
-        _ = shutdown => {},
+    // Drain: stop leasing, then wait (up to SHUTDOWN_DRAIN_SECS) for every
+    // permit to come back before touching what is left in the in-flight list
+    let (stop_tx, stop_rx) = tokio::sync::watch::channel(false);
+    let drain = std::time::Duration::from_secs(worker_config.shutdown_drain_secs);
+    let mut drained = true;
+    {
+        let worker = worker_loop(&mut redis_conn, &language, pool.as_deref(), &config_manager, &worker_config, &worker_id, semaphore.clone(), is_executing, stop_rx);
+        tokio::pin!(worker);
+        tokio::select! {
+            _ = &mut worker => {},
+            _ = shutdown => {
+                let _ = stop_tx.send(true);
+                info!("Draining in-flight jobs (up to {}s)", drain.as_secs());
+                let finished = tokio::time::timeout(drain, async {
+                    let _ = (&mut worker).await;
+                    let _ = semaphore.acquire_many(worker_config.max_parallel_jobs as u32).await;
+                })
+                .await;
+                drained = finished.is_ok();
+                if drained {
+                    info!("✓ In-flight jobs drained");
+                } else {
+                    warn!("Drain deadline of {}s passed - handing back unfinished jobs", drain.as_secs());
+                }
+            }
+        }
+        // Dropping an unfinished loop here cancels its job; container guards start removing the containers
+    }
+
+    // Let those removals finish before the runtime shuts down and cancels them
+    let leftover = engine::finish_cleanups(CLEANUP_TIMEOUT).await;
+    if leftover > 0 {
+        warn!("{} container removals still running after {}s - they may be left behind", leftover, CLEANUP_TIMEOUT.as_secs());
     }
 
-    info!("✓ Worker shutdown complete - all jobs processed");
+    // Whatever did not finish goes straight back instead of waiting for the orphan reaper
+    lease::hand_back_interrupted(&mut redis_conn, &worker_id).await;
+
+    // Stop advertising before exit so the API notices right away
+    heartbeat.abort();
+    if let Err(e) = redis::remove_worker_heartbeat(&mut redis_conn, &language, &worker_id).await {
+        warn!("Failed to remove worker heartbeat: {}", e);
+    }
+
+    if drained {
+        info!("✓ Worker shutdown complete - all jobs processed");
+    } else {
+        info!("✓ Worker shutdown complete - unfinished jobs handed back");
+    }
     Ok(())
 }
 
-#[instrument(skip(redis_conn, config_manager, semaphore, is_executing), fields(language = %language))]
+/// Wait for a cluster-wide execution slot (distributed semaphore in Redis)
+///
+/// Polls with capped backoff. Redis errors fail open - the per-worker
+/// semaphore still bounds local load, and a Redis outage should not
+/// wedge every worker in the cluster.
+async fn acquire_global_slot(
+    redis_conn: &mut ::redis::aio::ConnectionManager,
+    language: &Language,
+    holder: &str,
+    limit: usize,
+    lease_ms: u64,
+) {
+    let mut backoff_ms = 250;
+    let wait_start = std::time::Instant::now();
+    
+    loop {
+        match redis::try_acquire_global_slot(redis_conn, language, holder, limit, lease_ms).await {
+            Ok(true) => {
+                if backoff_ms > 250 {
+                    info!(
+                        holder = %holder,
+                        waited_ms = wait_start.elapsed().as_millis(),
+                        "Acquired cluster-wide execution slot"
+                    );
+                }
+                return;
+            }
+            Ok(false) => {
+                if backoff_ms == 250 {
+                    info!(holder = %holder, limit = limit, "Cluster-wide concurrency limit reached - waiting for a slot");
+                }
+            }
+            Err(e) => {
+                warn!(holder = %holder, error = %e, "Failed to acquire cluster-wide slot - proceeding without it");
+                return;
+            }
+        }
+        
+        tokio::time::sleep(tokio::time::Duration::from_millis(backoff_ms)).await;
+        backoff_ms = (backoff_ms * 2).min(2000);
+    }
+}
+
+#[instrument(skip(redis_conn, config_manager, worker_config, semaphore, is_executing, stop), fields(language = %language))]
+#[allow(clippy::too_many_arguments)]
 async fn worker_loop(
     redis_conn: &mut ::redis::aio::ConnectionManager,
     language: &Language,
+    pool: Option<&str>,
     config_manager: &LanguageConfigManager,
+    worker_config: &WorkerConfig,
+    worker_id: &str,
     semaphore: Arc<Semaphore>,
     is_executing: Arc<RwLock<bool>>,
+    mut stop: tokio::sync::watch::Receiver<bool>,
 ) -> anyhow::Result<()> {
+    // Run-once limits (Kubernetes Jobs / scale-to-zero); None = run until stopped
+    let max_idle = worker_config.max_idle_secs.map(std::time::Duration::from_secs);
+    // Poll no longer than the idle limit so the exit isn't late by a whole poll
+    let poll_secs = max_idle.map_or(5.0, |idle| idle.as_secs_f64().min(5.0));
+    let mut jobs_processed: u64 = 0;
+    let mut idle_since: Option<std::time::Instant> = None;
+    
     loop {
+        if *stop.borrow() {
+            info!(jobs_processed = jobs_processed, "Shutdown requested - no longer taking jobs");
+            return Ok(());
+        }
+        if worker_config.max_jobs.is_some_and(|max| jobs_processed >= max) {
+            info!(jobs_processed = jobs_processed, "Job limit reached - exiting");
+            return Ok(());
+        }
+        let idle_start = *idle_since.get_or_insert_with(std::time::Instant::now);
+        
         // Log idle state (waiting for jobs)
         debug!("Worker IDLE - waiting for job from queue");
         
-        // BLPOP with 5 second timeout for graceful shutdown
-        // Consumes from both main queue and retry queue (main has priority)
-        match redis::pop_job_with_retry(redis_conn, language, 5.0).await {
-            Ok(Some(mut job)) => {
+        // Take a job into this worker's in-flight list, waiting up to 5 seconds
+        // for graceful shutdown; it is acked once fully handled (see `lease`)
+        // Consumes priority queues and the retry queue (high → normal → retry → low)
+        // A shutdown abandons the wait; a job its pending BLMOVE still takes
+        // is handed back with the rest of the in-flight list
+        let leased = tokio::select! {
+            leased = redis::lease_job(redis_conn, language, pool, worker_id, poll_secs) => leased,
+            _ = stop.changed() => continue,
+        };
+        match leased {
+            Ok(Some(leased)) => {
+                let mut job = leased.job.clone();
                 let job_id = job.id;
+                // Keeps the job's processing heartbeat alive until this iteration ends
+                let _processing = lease::JobHeartbeat::start(redis_conn.clone(), worker_id, job_id);
+                jobs_processed += 1;
+                idle_since = None;
+                let picked_up_at = chrono::Utc::now();
+                let dequeued = std::time::Instant::now();
                 
                 // ===== CRITICAL: Language Mismatch Check =====
                 // Workers MUST only process jobs for their configured language
@@ -367,7 +608,7 @@ async fn worker_loop(
                         language, job.language
                     ));
                     
-                    if let Err(dlq_err) = redis::push_to_dlq(redis_conn, &job).await {
+                    if let Err(dlq_err) = redis::push_to_dlq(redis_conn, &mut job).await {
                         error!(
                             job_id = %job_id,
                             error = %dlq_err,
@@ -376,6 +617,7 @@ async fn worker_loop(
                     } else {
                         warn!(job_id = %job_id, "Misrouted job sent to DLQ");
                     }
+                    lease::ack(redis_conn, worker_id, &leased).await;
                     
                     continue;
                 }
@@ -405,6 +647,8 @@ async fn worker_loop(
                         image = %config.image,
                         memory_mb = config.memory_limit_mb,
                         cpu_limit = config.cpu_limit,
+                        memory_override_mb = ?job.memory_limit_mb,
+                        cpu_override = ?job.cpu_limit,
                         "Job configuration"
                     );
                 }
@@ -425,17 +669,26 @@ async fn worker_loop(
                             score: 0,
                             max_score: job.test_cases.iter().map(|tc| tc.weight).sum(),
                             results: vec![],
+                            scoring_formula: None,
+                            reason: None,
+                            feedback: None,
+                            timing_summary: None,
+                            suspicious: false,
+                            cached: false,
+                            verdict_summary: None,
                         };
                         
-                        if let Err(store_err) = redis::store_result_with_metrics(redis_conn, &cancelled_result, &job.language).await {
-                            error!(
+                        match redis::store_result_with_metrics(redis_conn, &cancelled_result, &job.language).await {
+                            Ok(true) => info!(job_id = %job_id, "Cancelled result stored"),
+                            Ok(false) => info!(job_id = %job_id, "Job was force-completed - keeping the operator's result"),
+                            Err(store_err) => error!(
                                 job_id = %job_id,
                                 error = %store_err,
                                 "Failed to store cancelled result"
-                            );
-                        } else {
-                            info!(job_id = %job_id, "Cancelled result stored");
+                            ),
                         }
+                        batch::on_job_finished(redis_conn, &job).await;
+                        lease::ack(redis_conn, worker_id, &leased).await;
                         
                         // MARK: Worker as idle (job was cancelled)
                         *is_executing.write().await = false;
@@ -466,8 +719,55 @@ async fn worker_loop(
                     max_attempts = job.metadata.max_attempts,
                     "Starting execution"
                 );
+                // Cluster-wide slot (optional) - held only for the execution itself
+                let slot_holder = format!("{}:{}", worker_id, job_id);
+                if let Some(limit) = worker_config.global_max_concurrent_jobs {
+                    // Lease outlives the worst-case job so a crashed worker's slot expires on its own
+                    let lease_ms = job.timeout_ms * job.test_cases.len().max(1) as u64 + 60_000;
+                    acquire_global_slot(redis_conn, &job.language, &slot_holder, limit, lease_ms).await;
+                }
+                let mut phases = phases::PhaseTimings::default();
+                phases.add(phases::PERMIT_WAIT, dequeued.elapsed());
+                
                 let start = std::time::Instant::now();
-                let result = match executor::execute_docker(&job, config_manager, redis_conn).await {
+                let ceiling = std::time::Duration::from_secs(worker_config.max_job_runtime_secs);
+                let execution = watchdog::supervise(
+                    &job,
+                    ceiling,
+                    executor::execute_docker(&job, config_manager, redis_conn, &mut phases, worker_config.max_parallel_tests, worker_config.per_job_container),
+                )
+                .await;
+                
+                if worker_config.global_max_concurrent_jobs.is_some() {
+                    if let Err(e) = redis::release_global_slot(redis_conn, &job.language, &slot_holder).await {
+                        warn!(job_id = %job_id, error = %e, "Failed to release cluster-wide slot (lease will expire)");
+                    }
+                }
+                
+                let execution = match execution {
+                    Ok(execution) => execution,
+                    Err(exceeded) => {
+                        // Containers are already gone (guards dropped with the execution)
+                        let timed_out = watchdog::timed_out_result(&job, exceeded);
+                        if let Err(store_err) = redis::store_result_with_metrics(redis_conn, &timed_out, &job.language).await {
+                            error!(
+                                job_id = %job_id,
+                                error = %store_err,
+                                "Failed to store watchdog timeout result"
+                            );
+                        }
+                        batch::on_job_finished(redis_conn, &job).await;
+                        lease::ack(redis_conn, worker_id, &leased).await;
+                        
+                        // MARK: Worker as idle (job killed by watchdog)
+                        *is_executing.write().await = false;
+                        drop(permit);
+                        
+                        continue;
+                    }
+                };
+                
+                let mut result = match execution {
                     Ok(result) => result,
                     Err(e) => {
                         error!(
@@ -482,8 +782,9 @@ async fn worker_loop(
                         job.metadata.attempts += 1;
                         job.metadata.last_failure_reason = Some(format!("Execution error: {}", e));
                         
-                        // Retry logic
-                        if job.metadata.attempts < job.metadata.max_attempts {
+                        // Retry logic (bounded - a full retry queue sheds to the DLQ)
+                        let mut dead_letter = job.metadata.attempts >= job.metadata.max_attempts;
+                        if !dead_letter {
                             warn!(
                                 job_id = %job_id,
                                 attempt = job.metadata.attempts,
@@ -491,14 +792,31 @@ async fn worker_loop(
                                 "Job failed, sending to retry queue"
                             );
                             
-                            if let Err(retry_err) = redis::push_to_retry_queue(redis_conn, &job).await {
-                                error!(
-                                    job_id = %job_id,
-                                    error = %retry_err,
-                                    "Failed to push job to retry queue"
-                                );
-                            } else {
-                                info!(job_id = %job_id, "Job pushed to retry queue");
+                            match redis::push_to_retry_queue_bounded(redis_conn, &mut job, worker_config.max_retry_queue_depth).await {
+                                Ok(true) => info!(job_id = %job_id, "Job pushed to retry queue"),
+                                Ok(false) => {
+                                    error!(
+                                        job_id = %job_id,
+                                        max_depth = worker_config.max_retry_queue_depth,
+                                        phase = "retry_shed",
+                                        "Retry queue full - sending job to DLQ"
+                                    );
+                                    metrics::RETRY_JOBS_SHED
+                                        .with_label_values(&[&job.language.to_string()])
+                                        .inc();
+                                    job.metadata.last_failure_reason = Some(format!(
+                                        "retry_queue_full: {}",
+                                        job.metadata.last_failure_reason.as_deref().unwrap_or_default()
+                                    ));
+                                    dead_letter = true;
+                                }
+                                Err(retry_err) => {
+                                    error!(
+                                        job_id = %job_id,
+                                        error = %retry_err,
+                                        "Failed to push job to retry queue"
+                                    );
+                                }
                             }
                         } else {
                             error!(
@@ -506,8 +824,11 @@ async fn worker_loop(
                                 attempts = job.metadata.attempts,
                                 "Job exceeded max attempts, sending to DLQ"
                             );
-                            
-                            if let Err(dlq_err) = redis::push_to_dlq(redis_conn, &job).await {
+                        }
+                        
+                        if dead_letter {
+
+                            if let Err(dlq_err) = redis::push_to_dlq(redis_conn, &mut job).await {
                                 error!(
                                     job_id = %job_id,
                                     error = %dlq_err,
@@ -524,6 +845,13 @@ async fn worker_loop(
                                 score: 0,
                                 max_score: job.test_cases.iter().map(|tc| tc.weight).sum(),
                                 results: vec![],
+                                scoring_formula: None,
+                                reason: None,
+                                feedback: None,
+                                timing_summary: None,
+                                suspicious: false,
+                                cached: false,
+                                verdict_summary: None,
                             };
                             
                             if let Err(store_err) = redis::store_result_with_metrics(redis_conn, &failed_result, &job.language).await {
@@ -533,7 +861,9 @@ async fn worker_loop(
                                     "Failed to store failed result"
                                 );
                             }
+                            batch::on_job_finished(redis_conn, &job).await;
                         }
+                        lease::ack(redis_conn, worker_id, &leased).await;
                         
                         // MARK: Worker as idle (execution failed)
                         *is_executing.write().await = false;
@@ -544,6 +874,23 @@ async fn worker_loop(
                 };
                 let execution_time = start.elapsed();
                 
+                // Jobs queued by an older API carry no submission time
+                let queue_wait_ms = job
+                    .metadata
+                    .submitted_at
+                    .as_deref()
+                    .and_then(|at| chrono::DateTime::parse_from_rfc3339(at).ok())
+                    .map(|at| (picked_up_at - at.with_timezone(&chrono::Utc)).num_milliseconds().max(0) as u64);
+                let mut timing_summary = evaluator::timing_summary(
+                    &result.results,
+                    queue_wait_ms,
+                    execution_time.as_millis() as u64,
+                );
+                // Everything up to evaluation: `persist` is timed around the write
+                // below, so it only reaches the histogram and the phase log
+                timing_summary.phases_ms = phases.to_millis();
+                result.timing_summary = Some(timing_summary);
+                
                 info!(
                     job_id = %job_id,
                     phase = "evaluated",
@@ -567,15 +914,50 @@ async fn worker_loop(
                 
                 // Persist result to Redis with metrics
                 info!(job_id = %job_id, phase = "persisting", "Storing result to Redis");
-                match redis::store_result_with_metrics(redis_conn, &result, &job.language).await {
-                    Ok(_) => {
+                let persist_start = std::time::Instant::now();
+                let persisted = redis::store_result_with_metrics(redis_conn, &result, &job.language).await;
+                phases.add(phases::PERSIST, persist_start.elapsed());
+                phases.observe(&job.language.to_string());
+                info!(job_id = %job_id, phases_ms = ?phases.to_millis(), "Phase timings");
+                match persisted {
+                    Ok(true) => {
                         info!(job_id = %job_id, phase = "completed", "Result persisted to Redis");
                     }
+                    Ok(false) => {
+                        warn!(job_id = %job_id, phase = "completed", "Job was force-completed while running - keeping the operator's result");
+                    }
                     Err(e) => {
                         error!(job_id = %job_id, phase = "persist_failed", error = %e, "Failed to persist result");
                         // Non-fatal - worker continues
                     }
                 }
+                // Opt-in result cache - identical resubmissions skip execution (best-effort)
+                if let Some(fingerprint) = job.cache_fingerprint.as_deref().filter(|_| result.is_cacheable()) {
+                    if let Err(e) = redis::store_cached_result(redis_conn, fingerprint, &result).await {
+                        warn!(job_id = %job_id, error = %e, "Failed to cache result");
+                    }
+                }
+                batch::on_job_finished(redis_conn, &job).await;
+                lease::ack(redis_conn, worker_id, &leased).await;
+                
+                // Usage analytics (best-effort) - feeds GET /admin/analytics/languages
+                if let Ok(config) = config_manager.get_config(&job.language) {
+                    let usage = optimus_common::types::LanguageUsage {
+                        language: job.language,
+                        version: config.version,
+                        tier: format!(
+                            "{}mb/{}cpu",
+                            job.memory_limit_mb.unwrap_or(config.memory_limit_mb),
+                            job.cpu_limit.map_or(config.cpu_limit.to_string(), |cpu| cpu.to_string()),
+                        ),
+                        source_bytes: (job.source_code.len()
+                            + job.archive.as_ref().map_or(0, |a| a.content_base64.len() / 4 * 3)) as u64,
+                        execution_ms: result.results.iter().map(|r| r.execution_time_ms).sum(),
+                    };
+                    if let Err(e) = redis::record_language_usage(redis_conn, &usage).await {
+                        warn!(job_id = %job_id, error = %e, "Failed to record usage analytics");
+                    }
+                }
                 
                 info!(
                     job_id = %job_id, 
@@ -592,6 +974,14 @@ async fn worker_loop(
             }
             Ok(None) => {
                 // Timeout - check for shutdown (idle continues)
+                if max_idle.is_some_and(|max| idle_start.elapsed() >= max) {
+                    info!(
+                        jobs_processed = jobs_processed,
+                        idle_secs = idle_start.elapsed().as_secs(),
+                        "Idle limit reached - exiting"
+                    );
+                    return Ok(());
+                }
                 continue;
             }
             Err(e) => {
@@ -0,0 +1,79 @@
+//! Worker Metrics - Prometheus Registry
+//!
+//! Exposed on the health server at `/metrics`. Job-level metrics are
+//! published through Redis and recorded by the API; this registry only
+//! holds signals local to a single worker process. The execution layer's
+//! metrics live in `optimus_exec::metrics`, whose registry these join.
+
+use lazy_static::lazy_static;
+use optimus_exec::metrics::REGISTRY;
+use prometheus::{Counter, CounterVec, IntGauge, IntGaugeVec, Opts};
+
+pub use optimus_exec::metrics::render_metrics;
+
+lazy_static! {
+    // Failed jobs dead-lettered because the retry queue was at its cap
+    pub static ref RETRY_JOBS_SHED: CounterVec = CounterVec::new(
+        Opts::new("optimus_worker_retry_jobs_shed_total", "Jobs sent to the DLQ because the retry queue was full"),
+        &["language"]
+    )
+    .expect("metric can be created");
+
+    // Boot-time hello-world latency baseline (phase = cold | warm)
+    pub static ref WARMUP_LATENCY_MS: IntGaugeVec = IntGaugeVec::new(
+        Opts::new("optimus_worker_warmup_latency_ms", "Hello-world latency measured at worker boot"),
+        &["language", "phase"]
+    )
+    .expect("metric can be created");
+
+    // Jobs terminated by the runtime watchdog
+    pub static ref WATCHDOG_KILLS: CounterVec = CounterVec::new(
+        Opts::new("optimus_worker_watchdog_kills_total", "Jobs killed for exceeding the worker's maximum runtime"),
+        &["language"]
+    )
+    .expect("metric can be created");
+
+    // Unused images removed by the image GC (reason = age | size)
+    pub static ref IMAGE_GC_REMOVED: CounterVec = CounterVec::new(
+        Opts::new("optimus_worker_image_gc_removed_total", "Unused Optimus images removed by the image GC"),
+        &["reason"]
+    )
+    .expect("metric can be created");
+
+    // Reported size of the images the GC removed
+    pub static ref IMAGE_GC_RECLAIMED_BYTES: Counter = Counter::new(
+        "optimus_worker_image_gc_reclaimed_bytes_total",
+        "Bytes of images removed by the image GC (as reported by Docker)"
+    )
+    .expect("metric can be created");
+
+    // Optimus images on this node as of the last GC sweep
+    pub static ref OPTIMUS_IMAGE_BYTES: IntGauge = IntGauge::new(
+        "optimus_worker_image_bytes",
+        "Total reported size of Optimus images on the node at the last image GC sweep"
+    )
+    .expect("metric can be created");
+}
+
+/// Initialize metrics registry
+pub fn init_metrics() {
+    optimus_exec::metrics::init_metrics();
+    REGISTRY
+        .register(Box::new(RETRY_JOBS_SHED.clone()))
+        .expect("collector can be registered");
+    REGISTRY
+        .register(Box::new(WARMUP_LATENCY_MS.clone()))
+        .expect("collector can be registered");
+    REGISTRY
+        .register(Box::new(WATCHDOG_KILLS.clone()))
+        .expect("collector can be registered");
+    REGISTRY
+        .register(Box::new(IMAGE_GC_REMOVED.clone()))
+        .expect("collector can be registered");
+    REGISTRY
+        .register(Box::new(IMAGE_GC_RECLAIMED_BYTES.clone()))
+        .expect("collector can be registered");
+    REGISTRY
+        .register(Box::new(OPTIMUS_IMAGE_BYTES.clone()))
+        .expect("collector can be registered");
+}
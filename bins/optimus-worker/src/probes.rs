@@ -0,0 +1,143 @@
+//! Probes - Dependency Checks Behind `/healthz` and `/readyz`
+//!
+//! **Responsibility:**
+//! Tell Kubernetes whether this worker can actually run jobs, so a wedged
+//! worker is restarted instead of silently idling.
+//!
+//! **Checks (each bounded by `PROBE_TIMEOUT`):**
+//! - `redis`: a fresh connection answers PING
+//! - `container_engine`: the Docker/Podman daemon (or containerd) answers a ping
+//! - `image`: the bound language's image is present on the node (in
+//!   containerd, in the worker's namespace)
+//! - `runtime`: the language's container runtime (e.g. kata) is registered
+//!
+//! `/healthz` (liveness) needs Redis and the engine; `/readyz` needs all
+//! four. Daemonless runtimes (sandboxes, microVMs, Kubernetes Jobs) have no
+//! engine or local images, so those checks report `skipped`.
+
+use optimus_common::types::Language;
+use optimus_exec::config::LanguageConfigManager;
+use optimus_exec::container_runtime::{self, ContainerRuntime};
+use optimus_exec::{containerd, docker_api, engine};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::time::Duration;
+
+/// A probe answering slower than this counts as failed (a hung daemon)
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Outcome of one check: "ok", "skipped" or the failure
+pub type Checks = BTreeMap<&'static str, String>;
+
+pub fn all_passed(checks: &Checks) -> bool {
+    checks.values().all(|outcome| outcome == "ok" || outcome == "skipped")
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProbeResponse {
+    pub status: &'static str,
+    pub executing_job: bool,
+    pub checks: Checks,
+}
+
+/// Run a check under `PROBE_TIMEOUT`
+async fn bounded<F: Future<Output = Result<(), String>>>(check: F) -> String {
+    match tokio::time::timeout(PROBE_TIMEOUT, check).await {
+        Ok(Ok(())) => "ok".to_string(),
+        Ok(Err(e)) => e,
+        Err(_) => format!("no answer within {}s", PROBE_TIMEOUT.as_secs()),
+    }
+}
+
+pub async fn redis(client: &::redis::Client) -> String {
+    bounded(async {
+        let mut conn = client.get_async_connection().await.map_err(|e| e.to_string())?;
+        ::redis::cmd("PING")
+            .query_async::<_, String>(&mut conn)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    })
+    .await
+}
+
+pub async fn container_engine() -> String {
+    if container_runtime::selected() == ContainerRuntime::Containerd {
+        return bounded(async { containerd::check().await.map_err(|e| format!("{:#}", e)) }).await;
+    }
+    if container_runtime::selected().is_daemonless() {
+        return "skipped".to_string();
+    }
+    bounded(async {
+        let docker = container_runtime::connect().map_err(|e| e.to_string())?;
+        docker_api::call("ping", || docker.ping()).await.map(|_| ()).map_err(|e| e.to_string())
+    })
+    .await
+}
+
+pub async fn image(config_manager: &LanguageConfigManager, language: &Language) -> String {
+    if container_runtime::selected() == ContainerRuntime::Containerd {
+        return bounded(async {
+            let image = config_manager.get_image(language).map_err(|e| e.to_string())?;
+            match containerd::image_present(&image).await {
+                Ok(true) => Ok(()),
+                Ok(false) => Err(format!("{} not pulled into containerd", image)),
+                Err(e) => Err(format!("{:#}", e)),
+            }
+        })
+        .await;
+    }
+    if container_runtime::selected().is_daemonless() {
+        return "skipped".to_string();
+    }
+    bounded(async {
+        let image = config_manager.get_image(language).map_err(|e| e.to_string())?;
+        let docker = container_runtime::connect().map_err(|e| e.to_string())?;
+        docker_api::call("inspect_image", || docker.inspect_image(&image))
+            .await
+            .map(|_| ())
+            .map_err(|e| format!("{} not available: {}", image, e))
+    })
+    .await
+}
+
+/// Whether the language's configured container runtime (if any) is registered
+/// with dockerd (or Podman) on this node
+///
+/// Under Kubernetes it names a RuntimeClass, which the API server checks
+/// when the execution pod is created; under containerd a runtime (e.g.
+/// `io.containerd.runsc.v1`), which containerd checks when the task starts.
+pub async fn runtime(config_manager: &LanguageConfigManager, language: &Language) -> String {
+    let Some(runtime) = config_manager.get_runtime(language) else {
+        return "skipped".to_string();
+    };
+    if matches!(container_runtime::selected(), ContainerRuntime::Kubernetes | ContainerRuntime::Containerd) {
+        return "skipped".to_string();
+    }
+    bounded(async {
+        let docker = container_runtime::connect().map_err(|e| e.to_string())?;
+        match engine::runtime_available(&docker, &runtime).await {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(format!("'{}' is not registered with {}", runtime, container_runtime::selected())),
+            Err(e) => Err(e.to_string()),
+        }
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_all_passed() {
+        let mut checks = Checks::new();
+        checks.insert("redis", "ok".to_string());
+        checks.insert("image", "skipped".to_string());
+        assert!(all_passed(&checks));
+
+        checks.insert("container_engine", "no answer within 2s".to_string());
+        assert!(!all_passed(&checks));
+    }
+}
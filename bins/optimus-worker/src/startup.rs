@@ -0,0 +1,401 @@
+//! Startup Validation - Language Binding Checks
+//!
+//! **Responsibility:**
+//! Resolve languages.json and check that this worker's environment
+//! (`OPTIMUS_LANGUAGE`, `OPTIMUS_POOL`, `OPTIMUS_QUEUE`, `OPTIMUS_IMAGE`) agrees
+//! with it and that the node can run the language, before any job is consumed.
+//!
+//! **Modes (`STARTUP_VALIDATION`):**
+//! - `strict` (default): a failed check is fatal - the process exits
+//! - `supervised`: the worker stays up, reports the failed check on `/health`
+//!   and `/healthz`, on `/ready` and `/readyz` (503) and in
+//!   `optimus:worker_status:{worker_id}`, and re-resolves
+//!   the config with backoff until every check passes. In Kubernetes this
+//!   shows the mismatch instead of a crash loop hiding it.
+
+use optimus_exec::config::LanguageConfigManager;
+use crate::config_sync::{ConfigFetcher, ConfigSource};
+use crate::metrics;
+use optimus_exec::{container_runtime, gpu, seccomp};
+use axum::{extract::State, http::StatusCode, response::IntoResponse, routing::get, Json, Router};
+use optimus_common::redis;
+use optimus_common::types::{Language, WorkerPhase, WorkerStatus};
+use serde::Serialize;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+/// First retry delay in supervised mode; doubles up to `MAX_RETRY_DELAY`
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(1);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationMode {
+    Strict,
+    Supervised,
+}
+
+impl ValidationMode {
+    pub fn from_env() -> Self {
+        Self::parse(std::env::var("STARTUP_VALIDATION").ok().as_deref())
+    }
+
+    /// Anything but "supervised" keeps the strict default
+    fn parse(value: Option<&str>) -> Self {
+        match value {
+            Some(v) if v.trim().eq_ignore_ascii_case("supervised") => ValidationMode::Supervised,
+            _ => ValidationMode::Strict,
+        }
+    }
+}
+
+/// What this worker consumes, once validated
+#[derive(Debug, Clone)]
+pub struct Binding {
+    pub language: Language,
+    pub pool: Option<String>,
+    pub queue_name: String,
+    pub image: String,
+}
+
+/// A failed startup check
+#[derive(Debug, Clone, Serialize)]
+pub struct StartupError {
+    /// Stable identifier of the check (e.g. "QUEUE_MISMATCH")
+    pub code: &'static str,
+    pub message: String,
+    /// How to fix it
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub hints: Vec<String>,
+}
+
+impl StartupError {
+    fn new(code: &'static str, message: String) -> Self {
+        Self { code, message, hints: Vec::new() }
+    }
+
+    fn hint(mut self, hint: String) -> Self {
+        self.hints.push(hint);
+        self
+    }
+}
+
+impl fmt::Display for StartupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.code, self.message)
+    }
+}
+
+/// Resolve config and validate the binding according to `mode`
+///
+/// Returns only once everything passed; in strict mode a failure exits the
+/// process instead.
+pub async fn run(
+    mode: ValidationMode,
+    fetcher: &mut ConfigFetcher,
+    source: &ConfigSource,
+    client: &::redis::Client,
+    worker_id: &str,
+) -> (LanguageConfigManager, Binding) {
+    if mode == ValidationMode::Strict {
+        let resolved = match resolve_config(fetcher, source, None).await {
+            Ok(config_manager) => validate(&config_manager).await.map(|binding| (config_manager, binding)),
+            Err(e) => Err(e),
+        };
+        return resolved.unwrap_or_else(|e| {
+            error!("❌ FATAL: {}", e.message);
+            for hint in &e.hints {
+                error!("{}", hint);
+            }
+            std::process::exit(1);
+        });
+    }
+
+    info!("Supervised startup validation - jobs are consumed once the language binding checks pass");
+    let status = Arc::new(Mutex::new(None::<ValidationStatus>));
+    let status_server = tokio::spawn(serve_status(status.clone()));
+
+    let mut config_manager = None;
+    let mut attempts = 0;
+    let mut delay = INITIAL_RETRY_DELAY;
+    let resolved = loop {
+        attempts += 1;
+        let result = match resolve_config(fetcher, source, config_manager.as_ref()).await {
+            Ok(resolved) => {
+                let validated = validate(&resolved).await;
+                config_manager = Some(resolved);
+                validated
+            }
+            Err(e) => Err(e),
+        };
+        let error = match result {
+            Ok(binding) => break (config_manager.expect("config resolved before validation"), binding),
+            Err(e) => e,
+        };
+
+        warn!(code = error.code, attempt = attempts, retry_in_secs = delay.as_secs(), "Startup validation failed: {}", error.message);
+        for hint in &error.hints {
+            warn!("  {}", hint);
+        }
+        publish_status(client, worker_id, WorkerPhase::Validating, Some(&error), attempts).await;
+        *status.lock().expect("startup status lock poisoned") = Some(ValidationStatus { attempts, error });
+
+        tokio::time::sleep(delay).await;
+        delay = (delay * 2).min(MAX_RETRY_DELAY);
+    };
+
+    // Frees the health port for the regular server
+    status_server.abort();
+    let _ = status_server.await;
+    publish_status(client, worker_id, WorkerPhase::Ready, None, attempts).await;
+    info!(attempts = attempts, "Startup validation passed");
+    resolved
+}
+
+/// Load languages.json: the central source first, the local file as fallback
+///
+/// `previous` is kept when the central source reports no change since it was
+/// fetched; the local file is re-read on every call.
+async fn resolve_config(
+    fetcher: &mut ConfigFetcher,
+    source: &ConfigSource,
+    previous: Option<&LanguageConfigManager>,
+) -> Result<LanguageConfigManager, StartupError> {
+    match fetcher.fetch_if_changed().await {
+        Ok(Some(manager)) => {
+            info!("Loaded language configurations from {:?}", source);
+            return Ok(manager);
+        }
+        Ok(None) if *source != ConfigSource::File => {
+            if let Some(previous) = previous {
+                return Ok(previous.clone());
+            }
+        }
+        Ok(None) => {}
+        Err(e) => warn!("⚠ Failed to load language config from {:?}: {} (falling back to local file)", source, e),
+    }
+
+    let path = LanguageConfigManager::default_path();
+    if !path.exists() && optimus_common::config::allow_default_config() {
+        warn!(
+            "{} not found - using the built-in default configuration (python only, ALLOW_DEFAULT_CONFIG=true)",
+            path.display()
+        );
+        return LanguageConfigManager::from_json(optimus_common::config::DEFAULT_LANGUAGES_JSON)
+            .map_err(|e| StartupError::new("CONFIG_UNAVAILABLE", format!("Built-in default configuration is invalid: {:#}", e)));
+    }
+    LanguageConfigManager::load_default().map_err(|e| {
+        StartupError::new("CONFIG_UNAVAILABLE", format!("Failed to load language configurations: {:#}", e))
+            .hint("Make sure config/languages.json exists (or set ALLOW_DEFAULT_CONFIG=true for a python-only default)".to_string())
+    })
+}
+
+/// Worker MUST be bound to exactly one language via environment variables,
+/// matching languages.json - non-negotiable for proper scaling and isolation
+async fn validate(config_manager: &LanguageConfigManager) -> Result<Binding, StartupError> {
+    // 1. OPTIMUS_LANGUAGE is set and known (REQUIRED)
+    let language_str = std::env::var("OPTIMUS_LANGUAGE").map_err(|_| {
+        StartupError::new("LANGUAGE_NOT_SET", "OPTIMUS_LANGUAGE environment variable not set".to_string())
+            .hint("Worker must be bound to a specific language (python, java, rust)".to_string())
+    })?;
+    let language = Language::from_str(&language_str).ok_or_else(|| {
+        let valid: Vec<String> = Language::all_variants().iter().map(|l| l.to_string()).collect();
+        StartupError::new("INVALID_LANGUAGE", format!("Invalid language: {}", language_str))
+            .hint(format!("Valid options: {}", valid.join(", ")))
+    })?;
+
+    // 2. The language is configured
+    if let Err(e) = config_manager.get_config(&language) {
+        return Err(StartupError::new("LANGUAGE_NOT_CONFIGURED", format!("Language '{}' is not configured: {}", language, e))
+            .hint(format!("Available languages: {:?}", config_manager.list_languages())));
+    }
+
+    // 3. OPTIMUS_POOL (optional) is a configured placement pool, and
+    // OPTIMUS_QUEUE matches language and pool (REQUIRED)
+    let pool = std::env::var("OPTIMUS_POOL").ok().filter(|p| !p.is_empty());
+    if let Some(ref pool) = pool {
+        let pools = config_manager.get_pools(&language);
+        if !pools.contains(pool) {
+            return Err(StartupError::new("POOL_NOT_CONFIGURED", format!("Pool '{}' is not configured for {}", pool, language))
+                .hint(format!("Configured pools: {:?}", pools)));
+        }
+    }
+    let expected_queue = match pool {
+        Some(ref pool) => redis::pool_queue_name(&language, Some(pool)),
+        None => config_manager
+            .get_queue_name(&language)
+            .map_err(|e| StartupError::new("LANGUAGE_NOT_CONFIGURED", e.to_string()))?,
+    };
+    let queue_name = std::env::var("OPTIMUS_QUEUE").map_err(|_| {
+        StartupError::new("QUEUE_NOT_SET", "OPTIMUS_QUEUE environment variable not set".to_string())
+            .hint(format!("Expected queue for {}: {}", language, expected_queue))
+    })?;
+    if queue_name != expected_queue {
+        return Err(StartupError::new(
+            "QUEUE_MISMATCH",
+            format!("Queue mismatch: {} workers consume {}, but OPTIMUS_QUEUE is {}", language, expected_queue, queue_name),
+        )
+        .hint("This configuration would cause routing bugs".to_string()));
+    }
+
+    // 4. OPTIMUS_IMAGE matches the language (REQUIRED)
+    let expected_image = config_manager
+        .get_image(&language)
+        .map_err(|e| StartupError::new("LANGUAGE_NOT_CONFIGURED", e.to_string()))?;
+    let image = std::env::var("OPTIMUS_IMAGE").map_err(|_| {
+        StartupError::new("IMAGE_NOT_SET", "OPTIMUS_IMAGE environment variable not set".to_string())
+            .hint(format!("Expected image for {}: {}", language, expected_image))
+    })?;
+    if image != expected_image {
+        return Err(StartupError::new(
+            "IMAGE_MISMATCH",
+            format!("Image mismatch: {} runs {}, but OPTIMUS_IMAGE is {}", language, expected_image, image),
+        )
+        .hint("This configuration would cause execution bugs".to_string()));
+    }
+
+    // 5. The configured container runtime exists on this node
+    if let Some(runtime) = config_manager.get_runtime(&language) {
+        let check = crate::probes::runtime(config_manager, &language).await;
+        if check != "ok" && check != "skipped" {
+            return Err(StartupError::new("RUNTIME_UNAVAILABLE", format!("Container runtime '{}' is not available on this node: {}", runtime, check))
+                .hint(format!(
+                    "Register it with dockerd (daemon.json \"runtimes\") or Podman (containers.conf [engine.runtimes]), or remove \"runtime\" from {}'s config",
+                    language
+                )));
+        }
+        info!("Container runtime: {}", runtime);
+    }
+
+    // 6. GPU languages run on nodes that can attach GPUs (under Kubernetes
+    // the scheduler places execution pods on GPU nodes instead)
+    let schedules_gpus = container_runtime::selected() == container_runtime::ContainerRuntime::Kubernetes;
+    if let Some(gpu_config) = config_manager.get_gpu(&language).filter(|_| !schedules_gpus) {
+        let available = match container_runtime::connect() {
+            Ok(docker) => gpu::available(&docker).await,
+            Err(e) => Err(e),
+        };
+        match available {
+            Ok(true) => {}
+            Ok(false) => {
+                return Err(StartupError::new("GPU_UNAVAILABLE", format!("{} is configured with GPUs but this node has no NVIDIA runtime", language))
+                    .hint(format!("Install the NVIDIA Container Toolkit (nvidia-ctk runtime configure) or remove \"gpu\" from {}'s config", language)));
+            }
+            Err(e) => return Err(StartupError::new("GPU_UNAVAILABLE", format!("Failed to detect GPU support: {:#}", e))),
+        }
+        match gpu::pinned_devices() {
+            Some(devices) => info!("GPUs: devices {} ({})", devices.join(","), gpu_config.capabilities.join(",")),
+            None => info!("GPUs: {} per container ({})", gpu_config.count, gpu_config.capabilities.join(",")),
+        }
+    }
+
+    // 7. The seccomp profile exists and Docker would accept it (only the
+    // Docker-API engines apply it)
+    if let Some(profile) = config_manager.get_seccomp_profile(&language) {
+        if let Err(e) = seccomp::load_profile(&profile) {
+            return Err(StartupError::new("SECCOMP_PROFILE_INVALID", format!("{:#}", e))
+                .hint(format!("Use \"default\" for the built-in profile, or fix or remove \"seccomp_profile\" from {}'s config", language)));
+        }
+        match container_runtime::selected() {
+            container_runtime::ContainerRuntime::Docker | container_runtime::ContainerRuntime::Podman => {
+                info!("Seccomp profile: {}", profile);
+            }
+            runtime => warn!("Seccomp profile {} is ignored by the {} engine", profile, runtime),
+        }
+    }
+
+    Ok(Binding { language, pool, queue_name, image })
+}
+
+/// Publish the startup status to Redis (best-effort: Redis may be the problem)
+async fn publish_status(client: &::redis::Client, worker_id: &str, phase: WorkerPhase, error: Option<&StartupError>, attempts: u32) {
+    let status = WorkerStatus {
+        worker_id: worker_id.to_string(),
+        phase,
+        code: error.map(|e| e.code.to_string()),
+        message: error.map(|e| e.message.clone()),
+        attempts,
+        updated_at: chrono::Utc::now().to_rfc3339(),
+    };
+    let published = match client.get_connection_manager().await {
+        Ok(mut conn) => redis::set_worker_status(&mut conn, &status).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = published {
+        warn!(error = %e, "Failed to publish worker status");
+    }
+}
+
+/// Last failed validation, served while the worker is validating
+#[derive(Debug, Clone, Serialize)]
+struct ValidationStatus {
+    attempts: u32,
+    error: StartupError,
+}
+
+#[derive(Serialize)]
+struct ValidatingResponse {
+    status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    validation: Option<ValidationStatus>,
+}
+
+type SharedStatus = Arc<Mutex<Option<ValidationStatus>>>;
+
+fn validating(status: &SharedStatus) -> Json<ValidatingResponse> {
+    Json(ValidatingResponse {
+        status: "validating",
+        validation: status.lock().expect("startup status lock poisoned").clone(),
+    })
+}
+
+/// Liveness: the process is up (restarting it would not fix the config)
+async fn health_handler(State(status): State<SharedStatus>) -> impl IntoResponse {
+    (StatusCode::OK, validating(&status))
+}
+
+/// Readiness: never ready before validation passes
+async fn ready_handler(State(status): State<SharedStatus>) -> impl IntoResponse {
+    (StatusCode::SERVICE_UNAVAILABLE, validating(&status))
+}
+
+async fn metrics_handler() -> impl IntoResponse {
+    metrics::render_metrics()
+}
+
+/// Health endpoints on `HEALTH_PORT` while validating (the regular server
+/// takes the port over afterwards)
+async fn serve_status(status: SharedStatus) {
+    let app = Router::new()
+        .route("/health", get(health_handler))
+        .route("/ready", get(ready_handler))
+        .route("/healthz", get(health_handler))
+        .route("/readyz", get(ready_handler))
+        .route("/metrics", get(metrics_handler))
+        .with_state(status);
+
+    let port = std::env::var("HEALTH_PORT").unwrap_or_else(|_| "8080".to_string());
+    let addr = format!("0.0.0.0:{}", port);
+    match tokio::net::TcpListener::bind(&addr).await {
+        Ok(listener) => {
+            if let Err(e) = axum::serve(listener, app).await {
+                error!("Startup status server error: {}", e);
+            }
+        }
+        Err(e) => error!("Failed to bind startup status server on {}: {}", addr, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validation_mode() {
+        assert_eq!(ValidationMode::parse(None), ValidationMode::Strict);
+        assert_eq!(ValidationMode::parse(Some("strict")), ValidationMode::Strict);
+        assert_eq!(ValidationMode::parse(Some("Supervised")), ValidationMode::Supervised);
+        assert_eq!(ValidationMode::parse(Some("bogus")), ValidationMode::Strict);
+    }
+}
@@ -0,0 +1,141 @@
+//! Warmup Benchmark - Per-Node Latency Baseline
+//!
+//! **Responsibility:**
+//! On boot, run a standard hello-world program for the worker's language a
+//! few times and publish the resulting latencies.
+//!
+//! **Measurements (wall clock, container create → exit):**
+//! - `cold_ms`: first run - includes image cache effects and first container start
+//! - `warm_ms`: median of the remaining runs
+//!
+//! **Published to:**
+//! - Redis `optimus:warmup:{language}` (hash keyed by worker id)
+//! - `optimus_worker_warmup_latency_ms{language, phase}` on `/metrics`
+//!
+//! Runs before the worker starts consuming jobs so the numbers aren't skewed
+//! by concurrent executions. Failures are logged and never block startup.
+
+use optimus_exec::config::LanguageConfigManager;
+use optimus_exec::engine;
+use crate::metrics;
+use optimus_common::redis;
+use optimus_common::types::{Language, TestCase, WarmupBaseline};
+use std::time::Instant;
+use tracing::{info, warn};
+
+const RUN_TIMEOUT_MS: u64 = 10_000;
+
+/// Number of benchmark runs from `WARMUP_RUNS` (default: 3, 0 disables)
+pub fn runs_from_env() -> u32 {
+    std::env::var("WARMUP_RUNS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(3)
+}
+
+/// Standard hello-world program for a language
+fn hello_world(language: &Language) -> &'static str {
+    match language {
+        Language::Python => "print(\"hello\")\n",
+        Language::Java => "public class Main {\n    public static void main(String[] args) {\n        System.out.println(\"hello\");\n    }\n}\n",
+        Language::Rust => "fn main() {\n    println!(\"hello\");\n}\n",
+    }
+}
+
+/// Median of a non-empty set of samples
+fn median(samples: &mut [u64]) -> Option<u64> {
+    if samples.is_empty() {
+        return None;
+    }
+    samples.sort_unstable();
+    Some(samples[samples.len() / 2])
+}
+
+/// Run the benchmark and publish the baseline (best-effort)
+pub async fn run(
+    conn: &mut ::redis::aio::ConnectionManager,
+    config_manager: &LanguageConfigManager,
+    language: &Language,
+    worker_id: &str,
+    runs: u32,
+) {
+    if runs == 0 {
+        return;
+    }
+
+    let engine = match engine::connect(config_manager) {
+        Ok(engine) => engine,
+        Err(e) => {
+            warn!(error = %e, "Warmup benchmark skipped - engine unavailable");
+            return;
+        }
+    };
+
+    info!(language = %language, runs = runs, "Running warmup benchmark");
+
+    let mut samples = Vec::with_capacity(runs as usize);
+    for attempt in 1..=runs {
+        let started = Instant::now();
+        match engine.execute_in_container(language, hello_world(language), &TestCase::default(), None, RUN_TIMEOUT_MS, Default::default(), Default::default()).await {
+            Ok(output) if output.exit_code == Some(0) && !output.timed_out => {
+                samples.push(started.elapsed().as_millis() as u64);
+            }
+            Ok(output) => {
+                warn!(attempt = attempt, exit_code = ?output.exit_code, timed_out = output.timed_out, stderr = %output.stderr, "Warmup run did not succeed");
+            }
+            Err(e) => {
+                warn!(attempt = attempt, error = %e, "Warmup run failed");
+            }
+        }
+    }
+
+    let Some((&cold_ms, rest)) = samples.split_first() else {
+        warn!(language = %language, "Warmup benchmark produced no samples - no baseline published");
+        return;
+    };
+    let warm_ms = median(&mut rest.to_vec());
+
+    metrics::WARMUP_LATENCY_MS
+        .with_label_values(&[&language.to_string(), "cold"])
+        .set(cold_ms as i64);
+    if let Some(warm_ms) = warm_ms {
+        metrics::WARMUP_LATENCY_MS
+            .with_label_values(&[&language.to_string(), "warm"])
+            .set(warm_ms as i64);
+    }
+
+    let baseline = WarmupBaseline {
+        worker_id: worker_id.to_string(),
+        node: std::env::var("NODE_NAME").ok(),
+        language: *language,
+        image: config_manager.get_image(language).unwrap_or_default(),
+        cold_ms,
+        warm_ms,
+        runs: samples.len() as u32,
+        measured_at: chrono::Utc::now().to_rfc3339(),
+    };
+
+    info!(
+        language = %language,
+        cold_ms = cold_ms,
+        warm_ms = ?warm_ms,
+        phase = "warmup",
+        "✓ Warmup baseline measured"
+    );
+
+    if let Err(e) = redis::publish_warmup_baseline(conn, &baseline).await {
+        warn!(error = %e, "Failed to publish warmup baseline");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_median_of_warm_runs() {
+        assert_eq!(median(&mut []), None);
+        assert_eq!(median(&mut [40]), Some(40));
+        assert_eq!(median(&mut [90, 30, 50]), Some(50));
+    }
+}
@@ -0,0 +1,61 @@
+//! Watchdog - Hard Ceiling on a Job's Total Runtime
+//!
+//! **Responsibility:**
+//! Per-test timeouts bound each container, but not the job as a whole - a
+//! wedged Docker call, a slow image pull or a stuck log stream can keep a job
+//! (and its concurrency permit) busy forever. The watchdog races the whole
+//! execution against `MAX_JOB_RUNTIME_SECS` and abandons it when the ceiling
+//! is hit.
+//!
+//! **Termination:**
+//! - The execution future is dropped, so every live `ContainerGuard`
+//!   force-removes its container
+//! - The job is recorded as `TimedOut` (no retry - it would hit the same
+//!   ceiling again) and counted in `optimus_worker_watchdog_kills_total`
+
+use crate::metrics;
+use optimus_common::types::{ExecutionResult, JobRequest, JobStatus};
+use std::future::Future;
+use std::time::Duration;
+use tracing::error;
+
+/// The execution ran past the worker's runtime ceiling
+#[derive(Debug, Clone, Copy)]
+pub struct CeilingExceeded(pub Duration);
+
+/// Run `execution`, giving up once it has taken longer than `ceiling`
+pub async fn supervise<F: Future>(job: &JobRequest, ceiling: Duration, execution: F) -> Result<F::Output, CeilingExceeded> {
+    match tokio::time::timeout(ceiling, execution).await {
+        Ok(output) => Ok(output),
+        Err(_) => {
+            metrics::WATCHDOG_KILLS.with_label_values(&[&job.language.to_string()]).inc();
+            error!(
+                job_id = %job.id,
+                ceiling_secs = ceiling.as_secs(),
+                "Watchdog: job exceeded the worker runtime ceiling - containers killed"
+            );
+            Err(CeilingExceeded(ceiling))
+        }
+    }
+}
+
+/// Terminal result recorded for a job the watchdog terminated
+pub fn timed_out_result(job: &JobRequest, exceeded: CeilingExceeded) -> ExecutionResult {
+    ExecutionResult {
+        job_id: job.id,
+        overall_status: JobStatus::TimedOut,
+        score: 0,
+        max_score: job.test_cases.iter().map(|tc| tc.weight).sum(),
+        results: vec![],
+        scoring_formula: None,
+        reason: Some(format!(
+            "Job exceeded the worker's maximum runtime of {}s and was terminated",
+            exceeded.0.as_secs()
+        )),
+        feedback: None,
+        timing_summary: None,
+        suspicious: false,
+        cached: false,
+        verdict_summary: None,
+    }
+}
@@ -35,6 +35,12 @@ Write-Host "--------------------------------------------" -ForegroundColor Green
 $pythonTag = "optimus-python:3.11-v1"
 $javaTag = "optimus-java:17-v1"
 
+Write-Host "`nBuilding shared base image (optimus-base:latest)..." -ForegroundColor Yellow
+docker build -f dockerfiles/Dockerfile.base -t optimus-base:latest . -q
+if ($LASTEXITCODE -ne 0) {
+    Write-Host "[FAIL] Base image build failed" -ForegroundColor Red
+}
+
 Write-Host "`nBuilding Python image ($pythonTag)..." -ForegroundColor Yellow
 docker build -f dockerfiles/python/Dockerfile -t $pythonTag . -q
 if ($LASTEXITCODE -eq 0) {
@@ -1,28 +1,116 @@
 #!/bin/bash
 # Universal Optimus Code Runner
 # This script detects the language and executes code appropriately
+# Arguments passed to the container (the test's args) are forwarded to the program
 # Supports: Python, Java, Rust, C++, Go, Node.js, and more
 
 set -e
 
+# Exit-code contract with the worker (see engine.rs RUNNER_EXIT_*):
+#   0          program succeeded
+#   201        compilation failed
+#   202        program exceeded WALL_TIMEOUT_MS (runner-enforced)
+#   203        harness error - bad environment, unsupported language, undecodable input
+#   any other  the program's own non-zero exit status (runtime failure)
+EXIT_COMPILE_ERROR=201
+EXIT_WALL_TIMEOUT=202
+EXIT_HARNESS_ERROR=203
+
+harness_error() {
+    echo "Error: $1" >&2
+    exit $EXIT_HARNESS_ERROR
+}
+
+compile_error() {
+    echo "Compilation failed" >&2
+    exit $EXIT_COMPILE_ERROR
+}
+
+# Compiled languages build into BUILD_DIR and stamp it when the build succeeds.
+# BUILD_ONLY=1: compile, then exit (the worker caches the build by source hash).
+# PRECOMPILED=1: the worker uploaded a cached build, so skip compilation.
+BUILD_DIR=/code/.build
+BUILD_STAMP="$BUILD_DIR/.complete"
+
+# Run a compile command unless a cached build is present
+build() {
+    if [ -n "$PRECOMPILED" ] && [ -f "$BUILD_STAMP" ]; then
+        return 0
+    fi
+    mkdir -p "$BUILD_DIR"
+    "$@" 2>&1 </dev/null || compile_error
+    touch "$BUILD_STAMP"
+}
+
+# The program's CPU time in microseconds, left for the worker to read after
+# the container stops (written by the runner, after the program has exited)
+CPU_TIME_FILE=/code/.cpu_usec
+
+# CPU usage of the container's cgroup so far (v2, then v1), in microseconds
+cpu_usage_usec() {
+    if [ -r /sys/fs/cgroup/cpu.stat ]; then
+        awk '/^usage_usec/ { print $2 }' /sys/fs/cgroup/cpu.stat
+    elif [ -r /sys/fs/cgroup/cpuacct/cpuacct.usage ]; then
+        echo $(( $(cat /sys/fs/cgroup/cpuacct/cpuacct.usage) / 1000 ))
+    fi
+}
+
+record_cpu_time() {
+    local after
+    after=$(cpu_usage_usec)
+    if [ -n "$1" ] && [ -n "$after" ]; then
+        echo $((after - $1)) > "$CPU_TIME_FILE" 2>/dev/null || true
+    fi
+}
+
+# Run the program under the optional runner-side wall clock limit
+# `timeout` exits 124 when the limit is hit; report that as EXIT_WALL_TIMEOUT
+run_program() {
+    local status=0 cpu_before
+    rm -rf "$CPU_TIME_FILE"
+    cpu_before=$(cpu_usage_usec)
+    if [ -n "$WALL_TIMEOUT_MS" ] && command -v timeout >/dev/null 2>&1; then
+        timeout "$(awk "BEGIN { printf \"%.3f\", $WALL_TIMEOUT_MS / 1000 }")" "$@" || status=$?
+        record_cpu_time "$cpu_before"
+        if [ $status -eq 124 ]; then
+            echo "[Wall clock limit of ${WALL_TIMEOUT_MS}ms exceeded]" >&2
+            exit $EXIT_WALL_TIMEOUT
+        fi
+        return $status
+    fi
+    "$@" || status=$?
+    record_cpu_time "$cpu_before"
+    return $status
+}
+
+# The worker uploads the source into JOB_DIR before the runner starts (a tar
+# copied into the container - nothing travels through the environment).
+# The test's input arrives on the runner's own stdin, which the program inherits.
+JOB_DIR=/tmp/optimus
+SOURCE_FILE="$JOB_DIR/source"
+
 # Read environment variables
-SOURCE_CODE_B64="${SOURCE_CODE:-}"
-TEST_INPUT_B64="${TEST_INPUT:-}"
 LANGUAGE="${LANGUAGE:-}"
+ENTRY_COMMAND="${ENTRY_COMMAND:-}"
+WALL_TIMEOUT_MS="${WALL_TIMEOUT_MS:-}"
+BUILD_ONLY="${BUILD_ONLY:-}"
+PRECOMPILED="${PRECOMPILED:-}"
 
-if [ -z "$SOURCE_CODE_B64" ]; then
-    echo "Error: SOURCE_CODE environment variable not set" >&2
-    exit 1
+# Project archives: files are already unpacked into /code by the worker,
+# so run the entry command there instead of writing a single source file
+if [ -n "$ENTRY_COMMAND" ]; then
+    cd /code
+    run_program bash -c "$ENTRY_COMMAND" optimus "$@"
+    exit $?
 fi
 
-if [ -z "$LANGUAGE" ]; then
-    echo "Error: LANGUAGE environment variable not set" >&2
-    exit 1
+if [ ! -f "$SOURCE_FILE" ]; then
+    harness_error "No source code at $SOURCE_FILE"
 fi
 
-# Decode source code and input
-SOURCE_CODE=$(echo "$SOURCE_CODE_B64" | base64 -d)
-TEST_INPUT=$(echo "$TEST_INPUT_B64" | base64 -d 2>/dev/null || echo "")
+if [ -z "$LANGUAGE" ]; then
+    harness_error "LANGUAGE environment variable not set"
+fi
 
 # Create code directory if it doesn't exist
 mkdir -p /code
@@ -32,15 +120,15 @@ cd /code
 case "$LANGUAGE" in
     python)
         # Write Python code
-        echo "$SOURCE_CODE" > /code/main.py
+        cp "$SOURCE_FILE" /code/main.py
         
         # Execute Python code with test input
-        echo "$TEST_INPUT" | python3 -u /code/main.py
+        run_program python3 -u /code/main.py "$@"
         ;;
         
     java)
         # Write Java code
-        echo "$SOURCE_CODE" > /code/Main.java
+        cp "$SOURCE_FILE" /code/Main.java
 
         unset JAVA_TOOL_OPTIONS
         
@@ -48,172 +136,133 @@ case "$LANGUAGE" in
         unset JAVA_TOOL_OPTIONS
         
         # Compile Java code
-        javac /code/Main.java 2>&1
-        
-        if [ $? -ne 0 ]; then
-            echo "Compilation failed" >&2
-            exit 1
-        fi
+        build javac -d "$BUILD_DIR" /code/Main.java
+        [ -n "$BUILD_ONLY" ] && exit 0
         
         # Execute Java code with test input
-        echo "$TEST_INPUT" | java -cp /code Main
+        run_program java -cp "$BUILD_DIR" Main "$@"
         ;;
         
     rust)
         # Write Rust code
-        echo "$SOURCE_CODE" > /code/main.rs
+        cp "$SOURCE_FILE" /code/main.rs
         
         # Compile Rust code
-        rustc /code/main.rs -o /code/main 2>&1
-        
-        if [ $? -ne 0 ]; then
-            echo "Compilation failed" >&2
-            exit 1
-        fi
+        build rustc /code/main.rs -o "$BUILD_DIR/main"
+        [ -n "$BUILD_ONLY" ] && exit 0
         
         # Execute Rust binary with test input
-        echo "$TEST_INPUT" | /code/main
+        run_program "$BUILD_DIR/main" "$@"
         ;;
         
     cpp|c++)
         # Write C++ code
-        echo "$SOURCE_CODE" > /code/main.cpp
+        cp "$SOURCE_FILE" /code/main.cpp
         
         # Compile C++ code
-        g++ -std=c++17 -O2 /code/main.cpp -o /code/main 2>&1
-        
-        if [ $? -ne 0 ]; then
-            echo "Compilation failed" >&2
-            exit 1
-        fi
+        g++ -std=c++17 -O2 /code/main.cpp -o /code/main 2>&1 || compile_error
         
         # Execute C++ binary with test input
-        echo "$TEST_INPUT" | /code/main
+        run_program /code/main "$@"
         ;;
         
     c)
         # Write C code
-        echo "$SOURCE_CODE" > /code/main.c
+        cp "$SOURCE_FILE" /code/main.c
         
         # Compile C code
-        gcc -std=c11 -O2 /code/main.c -o /code/main 2>&1
-        
-        if [ $? -ne 0 ]; then
-            echo "Compilation failed" >&2
-            exit 1
-        fi
+        gcc -std=c11 -O2 /code/main.c -o /code/main 2>&1 || compile_error
         
         # Execute C binary with test input
-        echo "$TEST_INPUT" | /code/main
+        run_program /code/main "$@"
         ;;
         
     go)
         # Write Go code
-        echo "$SOURCE_CODE" > /code/main.go
+        cp "$SOURCE_FILE" /code/main.go
         
         # Execute Go code with test input (compile and run)
-        echo "$TEST_INPUT" | go run /code/main.go
+        run_program go run /code/main.go "$@"
         ;;
         
     javascript|node|nodejs)
         # Write JavaScript code
-        echo "$SOURCE_CODE" > /code/main.js
+        cp "$SOURCE_FILE" /code/main.js
         
         # Execute Node.js code with test input
-        echo "$TEST_INPUT" | node /code/main.js
+        run_program node /code/main.js "$@"
         ;;
         
     typescript|ts)
         # Write TypeScript code
-        echo "$SOURCE_CODE" > /code/main.ts
+        cp "$SOURCE_FILE" /code/main.ts
         
         # Compile TypeScript to JavaScript
-        tsc /code/main.ts 2>&1
-        
-        if [ $? -ne 0 ]; then
-            echo "Compilation failed" >&2
-            exit 1
-        fi
+        tsc /code/main.ts 2>&1 || compile_error
         
         # Execute compiled JavaScript with test input
-        echo "$TEST_INPUT" | node /code/main.js
+        run_program node /code/main.js "$@"
         ;;
         
     ruby)
         # Write Ruby code
-        echo "$SOURCE_CODE" > /code/main.rb
+        cp "$SOURCE_FILE" /code/main.rb
         
         # Execute Ruby code with test input
-        echo "$TEST_INPUT" | ruby /code/main.rb
+        run_program ruby /code/main.rb "$@"
         ;;
         
     php)
         # Write PHP code
-        echo "$SOURCE_CODE" > /code/main.php
+        cp "$SOURCE_FILE" /code/main.php
         
         # Execute PHP code with test input
-        echo "$TEST_INPUT" | php /code/main.php
+        run_program php /code/main.php "$@"
         ;;
         
     kotlin)
         # Write Kotlin code
-        echo "$SOURCE_CODE" > /code/Main.kt
+        cp "$SOURCE_FILE" /code/Main.kt
         
         # Compile Kotlin code
-        kotlinc /code/Main.kt -include-runtime -d /code/main.jar 2>&1
-        
-        if [ $? -ne 0 ]; then
-            echo "Compilation failed" >&2
-            exit 1
-        fi
+        kotlinc /code/Main.kt -include-runtime -d /code/main.jar 2>&1 || compile_error
         
         # Execute Kotlin JAR with test input
-        echo "$TEST_INPUT" | java -jar /code/main.jar
+        run_program java -jar /code/main.jar "$@"
         ;;
         
     scala)
         # Write Scala code
-        echo "$SOURCE_CODE" > /code/Main.scala
+        cp "$SOURCE_FILE" /code/Main.scala
         
         # Compile and execute Scala code with test input
-        echo "$TEST_INPUT" | scala /code/Main.scala
+        run_program scala /code/Main.scala "$@"
         ;;
         
     csharp|cs)
         # Write C# code
-        echo "$SOURCE_CODE" > /code/Main.cs
+        cp "$SOURCE_FILE" /code/Main.cs
         
         # Compile C# code
-        csc /code/Main.cs /out:/code/main.exe 2>&1
-        
-        if [ $? -ne 0 ]; then
-            echo "Compilation failed" >&2
-            exit 1
-        fi
+        csc /code/Main.cs /out:/code/main.exe 2>&1 || compile_error
         
         # Execute C# binary with test input
-        echo "$TEST_INPUT" | mono /code/main.exe
+        run_program mono /code/main.exe "$@"
         ;;
         
     swift)
         # Write Swift code
-        echo "$SOURCE_CODE" > /code/main.swift
+        cp "$SOURCE_FILE" /code/main.swift
         
         # Compile Swift code
-        swiftc /code/main.swift -o /code/main 2>&1
-        
-        if [ $? -ne 0 ]; then
-            echo "Compilation failed" >&2
-            exit 1
-        fi
+        swiftc /code/main.swift -o /code/main 2>&1 || compile_error
         
         # Execute Swift binary with test input
-        echo "$TEST_INPUT" | /code/main
+        run_program /code/main "$@"
         ;;
         
     *)
-        echo "Error: Unsupported language '$LANGUAGE'" >&2
         echo "Supported languages: python, java, rust, cpp, c, go, javascript, typescript, ruby, php, kotlin, scala, csharp, swift" >&2
-        exit 1
+        harness_error "Unsupported language '$LANGUAGE'"
         ;;
 esac
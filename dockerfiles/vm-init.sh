@@ -0,0 +1,84 @@
+#!/bin/bash
+# Optimus Firecracker microVM init (PID 1)
+# Runs one test with the universal runner, reports back over the serial
+# console and powers off. The worker's side lives in
+# libs/optimus-exec/src/firecracker.rs - keep the two in sync.
+#
+# The job drive (/dev/vdb) carries what a container would have received:
+#   code/            project files, fixtures, cached build (becomes /code)
+#   tmp/optimus/     the source file (becomes /tmp/optimus)
+#   .input           the test's stdin
+#   .env             the runner's environment (NAME='value' lines)
+#   .args            the program's arguments (NUL-separated)
+#   .nonce           marker secret, deleted before the program starts
+#
+# Results are console lines "@@optimus:<nonce> <field> [value]"; stdout,
+# stderr and build sections are followed by base64 lines. The program runs as
+# the unprivileged optimus user, which can neither read the nonce nor write
+# to the console, so it cannot forge a result.
+
+JOB=/tmp/job
+# Per-stream cap on what is sent back (the worker applies its own limits)
+OUTPUT_LIMIT=8388608
+
+# Reboot ends the VM (the kernel runs with reboot=k); if sysrq is
+# unavailable, PID 1 exiting panics the kernel, which reboots (panic=1)
+power_off() {
+    sync
+    echo b > /proc/sysrq-trigger 2>/dev/null
+    exit 0
+}
+
+mount -t proc proc /proc
+mount -t sysfs sysfs /sys
+mount -t devtmpfs devtmpfs /dev 2>/dev/null
+mount -t tmpfs tmpfs /tmp
+mount -t cgroup2 cgroup2 /sys/fs/cgroup 2>/dev/null
+
+mkdir -p "$JOB" /tmp/optimus
+mount /dev/vdb "$JOB" || { echo "optimus: job drive missing" >&2; power_off; }
+mount --bind "$JOB/code" /code
+cp -a "$JOB/tmp/optimus/." /tmp/optimus/
+chown -R optimus: /code /tmp/optimus
+
+NONCE=$(cat "$JOB/.nonce")
+rm -f "$JOB/.nonce"
+report() { echo "@@optimus:$NONCE $*"; }
+section() {
+    report "$1"
+    head -c "$OUTPUT_LIMIT" "$2" | base64
+}
+
+# The image's ENV (exported with the rootfs), then the job's
+if [ -f /opt/optimus/vm-env ]; then
+    while IFS= read -r line; do
+        [ -n "$line" ] && export "$line"
+    done < /opt/optimus/vm-env
+fi
+set -a
+. "$JOB/.env"
+set +a
+mapfile -d '' ARGS < "$JOB/.args"
+
+start=$(date +%s%N)
+cd /code
+setpriv --reuid=optimus --regid=optimus --init-groups -- \
+    /opt/optimus/runner.sh "${ARGS[@]}" < "$JOB/.input" > /tmp/stdout 2> /tmp/stderr
+status=$?
+end=$(date +%s%N)
+
+section stdout /tmp/stdout
+section stderr /tmp/stderr
+if [ -n "$BUILD_ONLY" ] && [ $status -eq 0 ] && [ -d /code/.build ]; then
+    report build
+    tar -C /code -cf - .build | base64
+fi
+if [ -f /code/.cpu_usec ]; then
+    report cpu_usec "$(cat /code/.cpu_usec)"
+fi
+if dmesg 2>/dev/null | grep -q "Out of memory"; then
+    report oom
+fi
+report time_ms $(( (end - start) / 1000000 ))
+report exit "$status"
+power_off
@@ -1,5 +1,14 @@
 use std::env;
 
+/// Built-in languages.json (python only, conservative limits) for first runs
+/// without a `config/` directory; used only with `ALLOW_DEFAULT_CONFIG=true`
+pub const DEFAULT_LANGUAGES_JSON: &str = include_str!("default_languages.json");
+
+/// Whether a missing languages.json may fall back to `DEFAULT_LANGUAGES_JSON`
+pub fn allow_default_config() -> bool {
+    env::var("ALLOW_DEFAULT_CONFIG").is_ok_and(|v| matches!(v.as_str(), "1" | "true"))
+}
+
 /// Application configuration
 /// Provides defaults with environment variable overrides
 #[derive(Debug, Clone)]
@@ -20,6 +29,66 @@ pub struct WorkerConfig {
     /// Maximum test cases executing in parallel within a single job
     /// Default: 1 (strict isolation - sequential execution within job)
     pub max_parallel_tests: usize,
+    
+    /// Run a job's tests in one shared container (`EXECUTION_MODE=per_job`)
+    /// instead of one container per test
+    /// Default: false (per_test)
+    pub per_job_container: bool,
+    
+    /// Cluster-wide cap on concurrent jobs for this worker's language,
+    /// shared by all workers through a Redis semaphore
+    /// Default: None (only per-worker limits apply)
+    pub global_max_concurrent_jobs: Option<usize>,
+    
+    /// Maximum Docker API calls in flight from this worker (create/start/kill/...)
+    /// Separate from job concurrency - protects the daemon under load
+    /// Default: 16
+    pub docker_max_concurrent_calls: usize,
+    
+    /// Maximum depth of this language's retry queue
+    /// Failed jobs beyond it go straight to the DLQ (`retry_queue_full`)
+    /// Default: 10000
+    pub max_retry_queue_depth: usize,
+    
+    /// Memory budget for container output buffered by this worker, in MB
+    /// Default: None (25% of the worker's cgroup memory limit, else 256 MB)
+    pub max_output_buffer_mb: Option<u64>,
+    
+    /// Maximum containers whose logs are collected simultaneously
+    /// Default: 8
+    pub max_log_collectors: usize,
+    
+    /// Bytes of stdout (the judged stream) kept per test, in KB
+    /// Output past it is drained and dropped (`stdout_truncated`)
+    /// Default: 64
+    pub max_captured_output_kb: usize,
+    
+    /// Bytes of stderr (diagnostics) kept per test, in KB (`stderr_truncated`)
+    /// Default: None (same as `max_captured_output_kb`)
+    pub max_captured_stderr_kb: Option<usize>,
+    
+    /// Keep the end of stderr instead of the start (`STDERR_RETENTION=tail`)
+    /// Default: false (head)
+    pub stderr_keep_tail: bool,
+    
+    /// Hard ceiling on one job's total execution time, in seconds
+    /// Jobs still running past it are killed and recorded as timed out
+    /// Default: 3600
+    pub max_job_runtime_secs: u64,
+    
+    /// How long a stopping worker waits for in-flight jobs to finish, in seconds
+    /// Jobs still running after it are handed back to the retry queue
+    /// Default: 25 (inside Kubernetes' default 30s grace period)
+    pub shutdown_drain_secs: u64,
+    
+    /// Exit after processing this many jobs (Kubernetes Jobs, KEDA ScaledJobs)
+    /// `OPTIMUS_RUN_ONCE=true` is shorthand for 1
+    /// Default: None (run until stopped)
+    pub max_jobs: Option<u64>,
+    
+    /// Exit after this many seconds without a job (scale-to-zero)
+    /// Default: None (wait for jobs forever)
+    pub max_idle_secs: Option<u64>,
 }
 
 impl Config {
@@ -60,12 +129,101 @@ impl WorkerConfig {
                 .ok()
                 .and_then(|v| v.parse().ok())
                 .unwrap_or(1),
+            per_job_container: env::var("EXECUTION_MODE")
+                .is_ok_and(|v| v.eq_ignore_ascii_case("per_job")),
+            global_max_concurrent_jobs: env::var("GLOBAL_MAX_CONCURRENT_JOBS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|&n: &usize| n > 0),
+            docker_max_concurrent_calls: env::var("DOCKER_MAX_CONCURRENT_CALLS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|&n: &usize| n > 0)
+                .unwrap_or(16),
+            max_retry_queue_depth: env::var("MAX_RETRY_QUEUE_DEPTH")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|&n: &usize| n > 0)
+                .unwrap_or(10_000),
+            max_output_buffer_mb: env::var("MAX_OUTPUT_BUFFER_MB")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|&n: &u64| n > 0),
+            max_log_collectors: env::var("MAX_LOG_COLLECTORS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|&n: &usize| n > 0)
+                .unwrap_or(8),
+            max_captured_output_kb: env::var("MAX_CAPTURED_OUTPUT_KB")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|&n: &usize| n > 0)
+                .unwrap_or(64),
+            max_captured_stderr_kb: env::var("MAX_CAPTURED_STDERR_KB")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|&n: &usize| n > 0),
+            stderr_keep_tail: env::var("STDERR_RETENTION")
+                .is_ok_and(|v| v.eq_ignore_ascii_case("tail")),
+            max_job_runtime_secs: env::var("MAX_JOB_RUNTIME_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|&n: &u64| n > 0)
+                .unwrap_or(3600),
+            shutdown_drain_secs: env::var("SHUTDOWN_DRAIN_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(25),
+            max_jobs: env::var("OPTIMUS_MAX_JOBS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|&n: &u64| n > 0)
+                .or_else(|| {
+                    env::var("OPTIMUS_RUN_ONCE")
+                        .is_ok_and(|v| matches!(v.as_str(), "1" | "true"))
+                        .then_some(1)
+                }),
+            max_idle_secs: env::var("OPTIMUS_MAX_IDLE_SECONDS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|&n: &u64| n > 0),
         }
     }
     
     pub fn new() -> Self {
         Self::from_env()
     }
+    
+    /// Apply command-line overrides for the run-once limits
+    /// Accepts `--run-once`, `--max-jobs N` and `--max-idle-seconds S` (also `--flag=value`)
+    pub fn apply_args<I: IntoIterator<Item = String>>(&mut self, args: I) -> Result<(), String> {
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                None => (arg, None),
+            };
+            if flag == "--run-once" {
+                self.max_jobs = Some(1);
+                continue;
+            }
+            let target = match flag.as_str() {
+                "--max-jobs" => &mut self.max_jobs,
+                "--max-idle-seconds" => &mut self.max_idle_secs,
+                _ => return Err(format!("unknown argument '{}'", flag)),
+            };
+            let value = inline
+                .or_else(|| args.next())
+                .ok_or_else(|| format!("{} requires a value", flag))?;
+            let parsed = value
+                .parse::<u64>()
+                .ok()
+                .filter(|&n| n > 0)
+                .ok_or_else(|| format!("{} must be a positive integer, got '{}'", flag, value))?;
+            *target = Some(parsed);
+        }
+        Ok(())
+    }
 }
 
 impl Default for WorkerConfig {
@@ -90,5 +248,34 @@ mod tests {
         let config = WorkerConfig::default();
         assert_eq!(config.max_parallel_jobs, 1);
         assert_eq!(config.max_parallel_tests, 1);
+        assert!(!config.per_job_container);
+        assert_eq!(config.global_max_concurrent_jobs, None);
+        assert_eq!(config.docker_max_concurrent_calls, 16);
+        assert_eq!(config.max_retry_queue_depth, 10_000);
+        assert_eq!(config.max_output_buffer_mb, None);
+        assert_eq!(config.max_log_collectors, 8);
+        assert_eq!(config.max_captured_output_kb, 64);
+        assert_eq!(config.max_captured_stderr_kb, None);
+        assert!(!config.stderr_keep_tail);
+        assert_eq!(config.max_job_runtime_secs, 3600);
+        assert_eq!(config.shutdown_drain_secs, 25);
+        assert_eq!(config.max_jobs, None);
+        assert_eq!(config.max_idle_secs, None);
+    }
+    
+    #[test]
+    fn test_worker_config_run_once_args() {
+        let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        
+        let mut config = WorkerConfig::default();
+        config.apply_args(args(&["--max-jobs", "5", "--max-idle-seconds=30"])).unwrap();
+        assert_eq!((config.max_jobs, config.max_idle_secs), (Some(5), Some(30)));
+        
+        config.apply_args(args(&["--run-once"])).unwrap();
+        assert_eq!(config.max_jobs, Some(1));
+        
+        assert!(config.apply_args(args(&["--max-jobs"])).is_err());
+        assert!(config.apply_args(args(&["--max-jobs", "0"])).is_err());
+        assert!(config.apply_args(args(&["--verbose"])).is_err());
     }
 }
@@ -1,210 +1,2391 @@
-use crate::types::{Language, JobRequest};
+use crate::types::{
+    BatchCompletion, BatchJobSummary, BatchRecord, DryRunRequest, DryRunResult, FeedbackRevision, HopLog, IdempotencyRecord,
+    JobFeedback, JobRequest, Language, LanguageUsage, LanguageUsageDay, Priority, Problem, ProblemAsset, SubmissionForecast, UploadSession,
+    WarmupBaseline, WebhookDelivery,
+};
 use redis::{AsyncCommands, RedisResult};
 
-/// Redis queue semantics - defines only semantics, not runtime logic
-/// Ensures API and worker never drift, Redis keys are deterministic,
-/// and KEDA scaling remains predictable
+// Redis queue semantics - defines only semantics, not runtime logic
+// Ensures API and worker never drift, Redis keys are deterministic,
+// and KEDA scaling remains predictable
 
 pub const QUEUE_PREFIX: &str = "optimus:queue";
 pub const RESULT_PREFIX: &str = "optimus:result";
+pub const RESULT_RESET_PREFIX: &str = "optimus:result_reset";
 pub const STATUS_PREFIX: &str = "optimus:status";
 pub const METRICS_PREFIX: &str = "optimus:metrics";
 pub const CONTROL_PREFIX: &str = "optimus:control";
+pub const DRY_RUN_PREFIX: &str = "optimus:dryrun";
+pub const CONCURRENCY_PREFIX: &str = "optimus:concurrency";
+pub const RATE_LIMIT_PREFIX: &str = "optimus:ratelimit";
+pub const IDEMPOTENCY_PREFIX: &str = "optimus:idempotency";
+pub const BATCH_PREFIX: &str = "optimus:batch";
+pub const EVENTS_PREFIX: &str = "optimus:events";
+pub const WEBHOOK_QUEUE: &str = "optimus:webhooks";
+pub const CONFIG_PREFIX: &str = "optimus:config";
+pub const WARMUP_PREFIX: &str = "optimus:warmup";
+pub const FEEDBACK_PREFIX: &str = "optimus:feedback";
+pub const ANALYTICS_PREFIX: &str = "optimus:analytics";
+pub const SUBMISSIONS_PREFIX: &str = "optimus:submissions";
+pub const FORECAST_PREFIX: &str = "optimus:forecast";
+pub const PROBLEM_PREFIX: &str = "optimus:problem";
+pub const PROBLEM_ASSETS_PREFIX: &str = "optimus:problem_assets";
+pub const ARTIFACT_PREFIX: &str = "optimus:artifact";
+pub const WORKERS_PREFIX: &str = "optimus:workers";
+pub const PARKED_PREFIX: &str = "optimus:parked";
+pub const RESULT_CACHE_PREFIX: &str = "optimus:resultcache";
+pub const DIAGNOSTICS_PREFIX: &str = "optimus:diagnostics";
+pub const POOLS_PREFIX: &str = "optimus:pools";
+pub const WORKER_STATUS_PREFIX: &str = "optimus:worker_status";
+pub const WORKER_PREFIX: &str = "optimus:worker";
+pub const UPLOAD_PREFIX: &str = "optimus:upload";
+pub const HOPS_PREFIX: &str = "optimus:hops";
+pub const DELAYED_PREFIX: &str = "optimus:delayed";
+pub const PROCESSING_PREFIX: &str = "optimus:processing";
+pub const INFLIGHT_PREFIX: &str = "optimus:inflight";
+pub const PARTIAL_PREFIX: &str = "optimus:partial";
+/// Set of worker ids that may hold jobs in an in-flight list
+pub const INFLIGHT_WORKERS_KEY: &str = "optimus:inflight_workers";
+
+/// Daily usage buckets are kept for this long
+pub const ANALYTICS_TTL_SECS: i64 = 90 * 86400;
+/// Per-day, per-language cap on samples kept for percentiles
+pub const ANALYTICS_MAX_SAMPLES: isize = 1000;
+
+/// Per-minute submission counters are kept this long (well past any EWMA window)
+pub const SUBMISSIONS_TTL_SECS: i64 = 3600;
+/// A published forecast expires if the API stops refreshing it
+pub const FORECAST_TTL_SECS: u64 = 300;
+
+/// Cached compile output lives this long after it was built
+pub const ARTIFACT_TTL_SECS: u64 = 3600;
+
+/// How long a worker's warmup baseline is kept after its last boot
+pub const WARMUP_TTL_SECS: i64 = 7 * 86400;
+
+/// A cached result answers identical submissions for this long after it was judged
+pub const RESULT_CACHE_TTL_SECS: u64 = 86400;
+
+/// Kill diagnostics are kept as long as the job's result
+pub const DIAGNOSTICS_TTL_SECS: i64 = 86400;
+
+/// A job's hop log copy outlives its last queue transition by this long
+pub const HOP_LOG_TTL_SECS: u64 = 86400;
+
+/// Workers refresh their heartbeat this often
+pub const HEARTBEAT_INTERVAL_SECS: u64 = 10;
+/// A worker without a heartbeat for this long counts as gone
+pub const HEARTBEAT_STALE_SECS: i64 = 30;
+
+/// A job's processing heartbeat lapses this long after its last refresh
+pub const PROCESSING_TTL_SECS: u64 = 30;
+/// Workers refresh the heartbeat of the job they hold this often
+pub const PROCESSING_HEARTBEAT_SECS: u64 = 10;
+/// How long an idle worker blocks on its highest-priority queue before
+/// checking the others again (see `lease_job`)
+pub const LEASE_BLOCK_SECS: f64 = 0.5;
+
+/// A worker's startup status outlives its last update by this long
+pub const WORKER_STATUS_TTL_SECS: u64 = 300;
+
+/// How long an Idempotency-Key keeps mapping to its job (matches result TTL)
+pub const IDEMPOTENCY_TTL_SECS: u64 = 86400;
+
+/// An upload session expires this long after its last chunk or finalize
+pub const UPLOAD_TTL_SECS: u64 = 86400;
 
 /// Generate deterministic queue name for a language
 pub fn queue_name(language: &Language) -> String {
     format!("{}:{}", QUEUE_PREFIX, language)
 }
 
-/// Generate retry queue name for a language
-pub fn retry_queue_name(language: &Language) -> String {
-    format!("{}:{}:retry", QUEUE_PREFIX, language)
+/// Generate the normal-priority queue name for a language's worker pool
+/// (see `Placement::pool`); the default pool (None) is the plain queue name
+pub fn pool_queue_name(language: &Language, pool: Option<&str>) -> String {
+    match pool {
+        None => queue_name(language),
+        Some(pool) => format!("{}:{}@{}", QUEUE_PREFIX, language, pool),
+    }
+}
+
+/// Generate queue name for a language at a given priority
+/// Normal priority keeps the plain queue name so existing consumers and
+/// KEDA triggers are unaffected
+pub fn priority_queue_name(language: &Language, priority: Priority) -> String {
+    pool_priority_queue_name(language, None, priority)
+}
+
+/// Generate queue name for a language's worker pool at a given priority
+pub fn pool_priority_queue_name(language: &Language, pool: Option<&str>, priority: Priority) -> String {
+    let base = pool_queue_name(language, pool);
+    match priority {
+        Priority::Normal => base,
+        Priority::High => format!("{}:high", base),
+        Priority::Low => format!("{}:low", base),
+    }
+}
+
+/// All queues a worker consumes, in dequeue order:
+/// high → normal → retry → low
+pub fn dequeue_order(language: &Language) -> [String; 4] {
+    pool_dequeue_order(language, None)
+}
+
+/// All queues a worker of a pool consumes, in dequeue order
+pub fn pool_dequeue_order(language: &Language, pool: Option<&str>) -> [String; 4] {
+    [
+        pool_priority_queue_name(language, pool, Priority::High),
+        pool_priority_queue_name(language, pool, Priority::Normal),
+        pool_retry_queue_name(language, pool),
+        pool_priority_queue_name(language, pool, Priority::Low),
+    ]
+}
+
+/// Every list a job of this language can sit in (consumed queues + DLQ)
+pub fn job_queues(language: &Language) -> Vec<String> {
+    let mut queues = dequeue_order(language).to_vec();
+    queues.push(dlq_name(language));
+    queues
+}
+
+/// Generate retry queue name for a language
+pub fn retry_queue_name(language: &Language) -> String {
+    pool_retry_queue_name(language, None)
+}
+
+/// Generate retry queue name for a language's worker pool
+/// (retries go back to workers that satisfy the job's placement)
+pub fn pool_retry_queue_name(language: &Language, pool: Option<&str>) -> String {
+    format!("{}:retry", pool_queue_name(language, pool))
+}
+
+/// Generate the key of the set of pools that have had jobs of a language queued
+pub fn pools_key(language: &Language) -> String {
+    format!("{}:{}", POOLS_PREFIX, language)
+}
+
+/// Every list a job of this language can sit in, across the default pool,
+/// every pool jobs were ever queued for, and the (shared) DLQ
+pub async fn all_job_queues(
+    conn: &mut redis::aio::ConnectionManager,
+    language: &Language,
+) -> RedisResult<Vec<String>> {
+    let mut queues = job_queues(language);
+    let mut pools: Vec<String> = conn.smembers(pools_key(language)).await?;
+    pools.sort();
+    for pool in pools {
+        queues.extend(pool_dequeue_order(language, Some(&pool)));
+    }
+    Ok(queues)
+}
+
+/// Generate dead letter queue name for a language
+pub fn dlq_name(language: &Language) -> String {
+    format!("{}:{}:dlq", QUEUE_PREFIX, language)
+}
+
+/// Generate admin dry-run queue name for a language
+/// Kept separate from the job queues so KEDA scaling and retries ignore it
+pub fn dry_run_queue_name(language: &Language) -> String {
+    format!("{}:{}:dryrun", QUEUE_PREFIX, language)
+}
+
+/// Generate result key for a dry-run request
+pub fn dry_run_result_key(request_id: &uuid::Uuid) -> String {
+    format!("{}:{}", DRY_RUN_PREFIX, request_id)
+}
+
+/// Generate cluster-wide concurrency slot key for a language
+/// Sorted set of holder ids scored by lease expiry (unix ms)
+pub fn global_slots_key(language: &Language) -> String {
+    format!("{}:{}", CONCURRENCY_PREFIX, language)
+}
+
+/// Generate token-bucket key for a rate-limited caller
+/// `scope` separates identity kinds (e.g. "key", "ip") so they never collide
+pub fn rate_limit_key(scope: &str, identity: &str) -> String {
+    format!("{}:{}:{}", RATE_LIMIT_PREFIX, scope, identity)
+}
+
+/// Generate idempotency key for a client-supplied `Idempotency-Key`
+/// Authenticated callers get their own namespace so keys never collide across callers
+pub fn idempotency_key(caller: Option<&str>, key: &str) -> String {
+    match caller {
+        Some(caller) => format!("{}:{}:{}", IDEMPOTENCY_PREFIX, caller, key),
+        None => format!("{}:{}", IDEMPOTENCY_PREFIX, key),
+    }
+}
+
+/// Generate key holding a batch definition
+pub fn batch_key(batch_id: &uuid::Uuid) -> String {
+    format!("{}:{}", BATCH_PREFIX, batch_id)
+}
+
+/// Generate counter key for jobs submitted into a batch
+pub fn batch_submitted_key(batch_id: &uuid::Uuid) -> String {
+    format!("{}:{}:submitted", BATCH_PREFIX, batch_id)
+}
+
+/// Generate set key of batch jobs that reached a terminal state
+pub fn batch_finished_key(batch_id: &uuid::Uuid) -> String {
+    format!("{}:{}:finished", BATCH_PREFIX, batch_id)
+}
+
+/// Generate key holding a batch's completion event once emitted
+pub fn batch_completion_key(batch_id: &uuid::Uuid) -> String {
+    format!("{}:{}:completion", BATCH_PREFIX, batch_id)
+}
+
+/// Generate pub/sub channel for batch events (consumed by SSE streams)
+pub fn batch_events_channel(batch_id: &uuid::Uuid) -> String {
+    format!("{}:batch:{}", EVENTS_PREFIX, batch_id)
+}
+
+/// Generate pub/sub channel notified when a job's result is stored
+/// Used by long-polling `GET /job/{id}?wait=...`
+pub fn job_events_channel(job_id: &uuid::Uuid) -> String {
+    format!("{}:job:{}", EVENTS_PREFIX, job_id)
+}
+
+/// Generate key holding the published languages.json
+/// Hash with `body` (raw JSON) and `version` (bumped on every publish)
+pub fn language_config_key() -> String {
+    format!("{}:languages", CONFIG_PREFIX)
+}
+
+/// Generate key holding warmup baselines for a language
+/// Hash of worker id -> WarmupBaseline JSON
+pub fn warmup_key(language: &Language) -> String {
+    format!("{}:{}", WARMUP_PREFIX, language)
+}
+
+/// Generate result key for a job
+pub fn result_key(job_id: &uuid::Uuid) -> String {
+    format!("{}:{}", RESULT_PREFIX, job_id)
+}
+
+/// Generate key marking a job whose result was cleared for a re-run
+/// Copies of the old result kept outside Redis must not be served while it exists
+pub fn result_reset_key(job_id: &uuid::Uuid) -> String {
+    format!("{}:{}", RESULT_RESET_PREFIX, job_id)
+}
+
+/// Generate key holding one day of usage counters for a language
+/// Hash of `jobs`, `version:{v}` and `tier:{t}` counts; `day` is YYYY-MM-DD (UTC)
+pub fn analytics_key(day: &str, language: &Language) -> String {
+    format!("{}:{}:{}", ANALYTICS_PREFIX, day, language)
+}
+
+/// Generate key holding one day of samples for a usage metric (capped list)
+pub fn analytics_samples_key(day: &str, language: &Language, metric: &str) -> String {
+    format!("{}:{}", analytics_key(day, language), metric)
+}
+
+/// Generate counter key for a language's submissions in one minute
+/// `minute` is minutes since the unix epoch (UTC)
+pub fn submissions_key(language: &Language, minute: i64) -> String {
+    format!("{}:{}:{}", SUBMISSIONS_PREFIX, language, minute)
+}
+
+/// Generate key holding a language's latest SubmissionForecast (JSON)
+/// Read by external autoscalers
+pub fn forecast_key(language: &Language) -> String {
+    format!("{}:{}", FORECAST_PREFIX, language)
+}
+
+/// Generate key holding a problem definition (Problem JSON, no expiry)
+pub fn problem_key(problem_id: &str) -> String {
+    format!("{}:{}", PROBLEM_PREFIX, problem_id)
+}
+
+/// Generate hash of a problem's assets (name -> ProblemAsset JSON, no expiry)
+pub fn problem_assets_key(problem_id: &str) -> String {
+    format!("{}:{}", PROBLEM_ASSETS_PREFIX, problem_id)
+}
+
+/// Generate key holding a chunked upload session (UploadSession JSON)
+pub fn upload_key(upload_id: &uuid::Uuid) -> String {
+    format!("{}:{}", UPLOAD_PREFIX, upload_id)
+}
+
+/// Generate key holding a copy of a job's hop log (HopLog JSON)
+/// Written with every queue transition, so the timeline survives the job
+/// leaving the queues
+pub fn hop_log_key(job_id: &uuid::Uuid) -> String {
+    format!("{}:{}", HOPS_PREFIX, job_id)
+}
+
+/// Generate key of delayed jobs waiting for release
+/// Sorted set of job id scored by release time (unix ms); the payloads and
+/// target queues live in `delayed_jobs_key` / `delayed_queues_key`
+pub fn delayed_key() -> String {
+    DELAYED_PREFIX.to_string()
+}
+
+/// Generate hash of delayed job id -> JobRequest JSON
+pub fn delayed_jobs_key() -> String {
+    format!("{}:jobs", DELAYED_PREFIX)
+}
+
+/// Generate hash of delayed job id -> queue it is released into
+pub fn delayed_queues_key() -> String {
+    format!("{}:queues", DELAYED_PREFIX)
+}
+
+/// Generate key of a worker's in-flight list
+/// Jobs the worker has taken off the queues but not yet acknowledged
+pub fn inflight_key(worker_id: &str) -> String {
+    format!("{}:{}", INFLIGHT_PREFIX, worker_id)
+}
+
+/// Generate processing heartbeat key for a job (holds the worker id)
+/// Exists while a live worker holds the job
+pub fn processing_key(job_id: &uuid::Uuid) -> String {
+    format!("{}:{}", PROCESSING_PREFIX, job_id)
+}
+
+/// Generate key holding a compiled build (tar), keyed by source hash
+pub fn artifact_key(source_hash: &str) -> String {
+    format!("{}:{}", ARTIFACT_PREFIX, source_hash)
+}
+
+/// Generate key holding the cached ExecutionResult (JSON) for a submission fingerprint
+pub fn result_cache_key(fingerprint: &str) -> String {
+    format!("{}:{}", RESULT_CACHE_PREFIX, fingerprint)
+}
+
+/// Generate key holding kill diagnostics for a job
+/// Hash of test id to DiagnosticBundle JSON
+pub fn diagnostics_key(job_id: &uuid::Uuid) -> String {
+    format!("{}:{}", DIAGNOSTICS_PREFIX, job_id)
+}
+
+/// Generate key holding live workers for a language
+/// Sorted set of worker id scored by last heartbeat (unix seconds)
+pub fn workers_key(language: &Language) -> String {
+    format!("{}:{}", WORKERS_PREFIX, language)
+}
+
+/// Generate key holding a worker's startup status (WorkerStatus JSON)
+pub fn worker_status_key(worker_id: &str) -> String {
+    format!("{}:{}", WORKER_STATUS_PREFIX, worker_id)
+}
+
+/// Generate key holding a worker's registration (WorkerInfo JSON)
+pub fn worker_key(worker_id: &str) -> String {
+    format!("{}:{}", WORKER_PREFIX, worker_id)
+}
+
+/// Generate counter of jobs accepted while a language had no workers
+pub fn parked_key(language: &Language) -> String {
+    format!("{}:{}", PARKED_PREFIX, language)
+}
+
+/// Generate key holding grader feedback for a job (current JobFeedback JSON)
+pub fn feedback_key(job_id: &uuid::Uuid) -> String {
+    format!("{}:{}", FEEDBACK_PREFIX, job_id)
+}
+
+/// Generate key holding the feedback audit trail (list of FeedbackRevision JSON)
+pub fn feedback_audit_key(job_id: &uuid::Uuid) -> String {
+    format!("{}:audit", feedback_key(job_id))
+}
+
+/// Generate status key for a job
+pub fn status_key(job_id: &uuid::Uuid) -> String {
+    format!("{}:{}", STATUS_PREFIX, job_id)
+}
+
+/// Generate key of a running job's finished tests (list of TestResult JSON,
+/// in completion order)
+pub fn partial_results_key(job_id: &uuid::Uuid) -> String {
+    format!("{}:{}", PARTIAL_PREFIX, job_id)
+}
+
+/// Generate control key for a job (cancellation flag)
+pub fn control_key(job_id: &uuid::Uuid) -> String {
+    format!("{}:{}", CONTROL_PREFIX, job_id)
+}
+
+/// Serialized job and hop log, for writing a queue transition
+fn queue_entry(job: &JobRequest) -> RedisResult<(String, String)> {
+    let serialize = |value: serde_json::Result<String>| {
+        value.map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "serialization error", e.to_string())))
+    };
+    Ok((serialize(serde_json::to_string(job))?, serialize(serde_json::to_string(&job.metadata.hops))?))
+}
+
+/// Stamp a dequeue on a popped job and save its hop log
+async fn record_dequeue(
+    conn: &mut redis::aio::ConnectionManager,
+    payload: &str,
+) -> RedisResult<JobRequest> {
+    let mut job: JobRequest = serde_json::from_str(payload)
+        .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "deserialization error", e.to_string())))?;
+    job.metadata.hops.record_dequeue(job.metadata.attempts, chrono::Utc::now().to_rfc3339());
+    
+    let (_, hops) = queue_entry(&job)?;
+    let _: () = conn.set_ex(hop_log_key(&job.id), hops, HOP_LOG_TTL_SECS).await?;
+    Ok(job)
+}
+
+/// Push a job to the language-specific queue for its priority (in its
+/// placement's worker pool)
+/// Uses RPUSH for FIFO semantics within a priority
+/// Stamps `enqueued_at` in the job's hop log (kept if already set)
+pub async fn push_job(
+    conn: &mut redis::aio::ConnectionManager,
+    job: &mut JobRequest,
+) -> RedisResult<()> {
+    let pool = job.pool();
+    let queue = pool_priority_queue_name(&job.language, pool.as_deref(), job.priority);
+    job.metadata.hops.enqueued_at.get_or_insert_with(|| chrono::Utc::now().to_rfc3339());
+    let (payload, hops) = queue_entry(job)?;
+    
+    // Remember the pool so queue scans (cancel, force-complete, fsck) look there too
+    if let Some(pool) = pool {
+        let _: () = conn.sadd(pools_key(&job.language), pool).await?;
+    }
+    redis::pipe()
+        .atomic()
+        .rpush(&queue, payload).ignore()
+        .set_ex(hop_log_key(&job.id), hops, HOP_LOG_TTL_SECS).ignore()
+        .query_async(conn)
+        .await
+}
+
+/// Hold a job back until `release_at`, then queue it like `push_job`
+/// (see `release_delayed_jobs`)
+/// Stamps `enqueued_at` and `release_at` in the job's hop log
+pub async fn push_job_delayed(
+    conn: &mut redis::aio::ConnectionManager,
+    job: &mut JobRequest,
+    release_at: chrono::DateTime<chrono::Utc>,
+) -> RedisResult<()> {
+    let pool = job.pool();
+    let queue = pool_priority_queue_name(&job.language, pool.as_deref(), job.priority);
+    job.metadata.hops.enqueued_at.get_or_insert_with(|| chrono::Utc::now().to_rfc3339());
+    job.metadata.hops.release_at = Some(release_at.to_rfc3339());
+    let (payload, hops) = queue_entry(job)?;
+    let id = job.id.to_string();
+    
+    if let Some(pool) = pool {
+        let _: () = conn.sadd(pools_key(&job.language), pool).await?;
+    }
+    redis::pipe()
+        .atomic()
+        .hset(delayed_jobs_key(), &id, payload).ignore()
+        .hset(delayed_queues_key(), &id, queue).ignore()
+        .zadd(delayed_key(), &id, release_at.timestamp_millis()).ignore()
+        .set_ex(hop_log_key(&job.id), hops, HOP_LOG_TTL_SECS).ignore()
+        .query_async(conn)
+        .await
+}
+
+/// Move up to `limit` delayed jobs whose release time has passed onto their queues
+/// Atomic per call, so any number of API replicas can release concurrently;
+/// returns how many jobs were released
+pub async fn release_delayed_jobs(
+    conn: &mut redis::aio::ConnectionManager,
+    limit: usize,
+) -> RedisResult<usize> {
+    let script = redis::Script::new(
+        r#"
+        local ids = redis.call('ZRANGEBYSCORE', KEYS[1], '-inf', ARGV[1], 'LIMIT', 0, tonumber(ARGV[2]))
+        for _, id in ipairs(ids) do
+            local payload = redis.call('HGET', KEYS[2], id)
+            local queue = redis.call('HGET', KEYS[3], id)
+            if payload and queue then
+                redis.call('RPUSH', queue, payload)
+            end
+            redis.call('HDEL', KEYS[2], id)
+            redis.call('HDEL', KEYS[3], id)
+            redis.call('ZREM', KEYS[1], id)
+        end
+        return #ids
+        "#,
+    );
+    
+    script
+        .key(delayed_key())
+        .key(delayed_jobs_key())
+        .key(delayed_queues_key())
+        .arg(chrono::Utc::now().timestamp_millis())
+        .arg(limit)
+        .invoke_async(conn)
+        .await
+}
+
+/// Push a job to the retry queue
+/// Stamps `retry_enqueued_at` in the job's hop log
+pub async fn push_to_retry_queue(
+    conn: &mut redis::aio::ConnectionManager,
+    job: &mut JobRequest,
+) -> RedisResult<()> {
+    let queue = pool_retry_queue_name(&job.language, job.pool().as_deref());
+    job.metadata.hops.retry_enqueued_at.push(chrono::Utc::now().to_rfc3339());
+    let (payload, hops) = queue_entry(job)?;
+    
+    redis::pipe()
+        .atomic()
+        .rpush(&queue, payload).ignore()
+        .set_ex(hop_log_key(&job.id), hops, HOP_LOG_TTL_SECS).ignore()
+        .query_async(conn)
+        .await
+}
+
+/// Push a job to the retry queue unless it already holds `max_depth` jobs
+/// Returns false if the job was shed (caller should dead-letter it); the
+/// `retry_enqueued_at` stamp is only kept when the job was queued
+pub async fn push_to_retry_queue_bounded(
+    conn: &mut redis::aio::ConnectionManager,
+    job: &mut JobRequest,
+    max_depth: usize,
+) -> RedisResult<bool> {
+    let script = redis::Script::new(
+        r#"
+        if redis.call('LLEN', KEYS[1]) >= tonumber(ARGV[1]) then
+            return 0
+        end
+        redis.call('RPUSH', KEYS[1], ARGV[2])
+        redis.call('SET', KEYS[2], ARGV[3], 'EX', ARGV[4])
+        return 1
+        "#,
+    );
+    
+    job.metadata.hops.retry_enqueued_at.push(chrono::Utc::now().to_rfc3339());
+    let (payload, hops) = queue_entry(job)?;
+    
+    let pushed: i64 = script
+        .key(pool_retry_queue_name(&job.language, job.pool().as_deref()))
+        .key(hop_log_key(&job.id))
+        .arg(max_depth)
+        .arg(payload)
+        .arg(hops)
+        .arg(HOP_LOG_TTL_SECS)
+        .invoke_async(conn)
+        .await?;
+    
+    if pushed != 1 {
+        job.metadata.hops.retry_enqueued_at.pop();
+    }
+    Ok(pushed == 1)
+}
+
+/// Push a job to the dead letter queue
+/// Stamps `dlq_at` in the job's hop log
+pub async fn push_to_dlq(
+    conn: &mut redis::aio::ConnectionManager,
+    job: &mut JobRequest,
+) -> RedisResult<()> {
+    let queue = dlq_name(&job.language);
+    job.metadata.hops.dlq_at = Some(chrono::Utc::now().to_rfc3339());
+    let (payload, hops) = queue_entry(job)?;
+    
+    redis::pipe()
+        .atomic()
+        .rpush(&queue, payload).ignore()
+        .set_ex(hop_log_key(&job.id), hops, HOP_LOG_TTL_SECS).ignore()
+        .query_async(conn)
+        .await
+}
+
+/// Pop a job from the language-specific queue
+/// Uses BLPOP with timeout for graceful shutdown
+/// Stamps the attempt's first dequeue in the job's hop log
+pub async fn pop_job(
+    conn: &mut redis::aio::ConnectionManager,
+    language: &Language,
+    timeout_seconds: f64,
+) -> RedisResult<Option<JobRequest>> {
+    let queue = queue_name(language);
+    let result: Option<(String, String)> = conn.blpop(&queue, timeout_seconds).await?;
+    
+    match result {
+        Some((_key, payload)) => Ok(Some(record_dequeue(conn, &payload).await?)),
+        None => Ok(None),
+    }
+}
+
+/// A job a worker took off the queues, held in its in-flight list until acked
+#[derive(Debug, Clone)]
+pub struct LeasedJob {
+    pub job: JobRequest,
+    /// Entry in the in-flight list, exactly as it was queued
+    pub payload: String,
+}
+
+/// Take the next job of a worker pool into the worker's in-flight list
+///
+/// Queues are tried in order high → normal → retry → low (see
+/// `pool_dequeue_order`). The move, the job's processing heartbeat and the
+/// worker's registration with the reaper happen in one script, so a taken
+/// job is never left without a heartbeat.
+///
+/// While all queues are empty the worker blocks (BLMOVE) on the
+/// high-priority queue for up to `LEASE_BLOCK_SECS` at a time, so a job
+/// arriving on another queue waits at most that long, until `timeout_seconds`
+/// passes. The worker is registered before it blocks and the heartbeat is
+/// set right after the move; the reaper leaves in-flight entries alone until
+/// they have gone without a heartbeat for `PROCESSING_TTL_SECS`, which covers
+/// that gap.
+///
+/// An entry that does not deserialize as a job is moved to the language's
+/// DLQ instead of being left in the in-flight list.
+/// Stamps the attempt's first dequeue in the job's hop log
+pub async fn lease_job(
+    conn: &mut redis::aio::ConnectionManager,
+    language: &Language,
+    pool: Option<&str>,
+    worker_id: &str,
+    timeout_seconds: f64,
+) -> RedisResult<Option<LeasedJob>> {
+    let script = redis::Script::new(
+        r#"
+        for i = 3, #KEYS do
+            local payload = redis.call('LMOVE', KEYS[i], KEYS[1], 'LEFT', 'RIGHT')
+            if payload then
+                redis.call('SADD', KEYS[2], ARGV[1])
+                local ok, job = pcall(cjson.decode, payload)
+                if ok and type(job) == 'table' and type(job.id) == 'string' then
+                    redis.call('SET', ARGV[2] .. ':' .. job.id, ARGV[1], 'EX', ARGV[3])
+                end
+                return payload
+            end
+        end
+        return false
+        "#,
+    );
+    
+    let inflight = inflight_key(worker_id);
+    let queues = pool_dequeue_order(language, pool);
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs_f64(timeout_seconds.max(0.0));
+    loop {
+        let mut invocation = script.key(&inflight);
+        invocation.key(INFLIGHT_WORKERS_KEY);
+        for queue in &queues {
+            invocation.key(queue);
+        }
+        let mut payload: Option<String> = invocation
+            .arg(worker_id)
+            .arg(PROCESSING_PREFIX)
+            .arg(PROCESSING_TTL_SECS)
+            .invoke_async(conn)
+            .await?;
+        let mut blocked = false;
+        
+        if payload.is_none() {
+            let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+            if remaining.is_zero() {
+                return Ok(None);
+            }
+            // Registered first: a worker dying right after the move is still reaped
+            let _: i64 = conn.sadd(INFLIGHT_WORKERS_KEY, worker_id).await?;
+            // A zero timeout would block forever
+            let block_secs = remaining.as_secs_f64().clamp(0.01, LEASE_BLOCK_SECS);
+            payload = redis::cmd("BLMOVE")
+                .arg(&queues[0])
+                .arg(&inflight)
+                .arg("LEFT")
+                .arg("RIGHT")
+                .arg(block_secs)
+                .query_async(conn)
+                .await?;
+            blocked = true;
+        }
+        
+        let Some(payload) = payload else {
+            continue;
+        };
+        let job_id = match serde_json::from_str::<JobRequest>(&payload) {
+            Ok(job) => job.id,
+            Err(e) => {
+                discard_unreadable_lease(conn, language, worker_id, &payload).await?;
+                return Err(redis::RedisError::from((
+                    redis::ErrorKind::TypeError,
+                    "deserialization error",
+                    format!("unreadable queue entry moved to the DLQ: {}", e),
+                )));
+            }
+        };
+        if blocked {
+            // The reaper may have dropped the registration while this worker
+            // was blocked with an empty list
+            redis::pipe()
+                .atomic()
+                .set_ex(processing_key(&job_id), worker_id, PROCESSING_TTL_SECS).ignore()
+                .sadd(INFLIGHT_WORKERS_KEY, worker_id).ignore()
+                .query_async::<_, ()>(conn)
+                .await?;
+        }
+        let job = record_dequeue(conn, &payload).await?;
+        return Ok(Some(LeasedJob { job, payload }));
+    }
+}
+
+/// Move an in-flight entry that is not a valid job to the language's DLQ
+async fn discard_unreadable_lease(
+    conn: &mut redis::aio::ConnectionManager,
+    language: &Language,
+    worker_id: &str,
+    payload: &str,
+) -> RedisResult<()> {
+    redis::pipe()
+        .atomic()
+        .lrem(inflight_key(worker_id), 1, payload).ignore()
+        .rpush(dlq_name(language), payload).ignore()
+        .query_async(conn)
+        .await
+}
+
+/// Acknowledge a leased job: it has been fully handled (result stored,
+/// retried or dead-lettered) and leaves the worker's in-flight list
+pub async fn ack_job(
+    conn: &mut redis::aio::ConnectionManager,
+    worker_id: &str,
+    leased: &LeasedJob,
+) -> RedisResult<()> {
+    redis::pipe()
+        .atomic()
+        .lrem(inflight_key(worker_id), 1, &leased.payload).ignore()
+        .del(processing_key(&leased.job.id)).ignore()
+        .query_async(conn)
+        .await
+}
+
+/// Refresh the processing heartbeat of a job this worker holds
+pub async fn touch_processing(
+    conn: &mut redis::aio::ConnectionManager,
+    job_id: &uuid::Uuid,
+    worker_id: &str,
+) -> RedisResult<()> {
+    conn.set_ex(processing_key(job_id), worker_id, PROCESSING_TTL_SECS).await
+}
+
+/// Whether an in-flight job's processing heartbeat has lapsed (its worker
+/// crashed or was partitioned away)
+pub async fn processing_expired(
+    conn: &mut redis::aio::ConnectionManager,
+    job_id: &uuid::Uuid,
+) -> RedisResult<bool> {
+    let alive: bool = conn.exists(processing_key(job_id)).await?;
+    Ok(!alive)
+}
+
+/// Workers that may hold unacknowledged jobs
+pub async fn inflight_workers(
+    conn: &mut redis::aio::ConnectionManager,
+) -> RedisResult<Vec<String>> {
+    conn.smembers(INFLIGHT_WORKERS_KEY).await
+}
+
+/// Stop tracking a worker whose in-flight list is empty
+pub async fn forget_idle_inflight_worker(
+    conn: &mut redis::aio::ConnectionManager,
+    worker_id: &str,
+) -> RedisResult<()> {
+    let script = redis::Script::new(
+        r#"
+        if redis.call('LLEN', KEYS[1]) == 0 then
+            redis.call('SREM', KEYS[2], ARGV[1])
+        end
+        return 1
+        "#,
+    );
+    
+    let _: i64 = script
+        .key(inflight_key(worker_id))
+        .key(INFLIGHT_WORKERS_KEY)
+        .arg(worker_id)
+        .invoke_async(conn)
+        .await?;
+    Ok(())
+}
+
+/// Jobs in a worker's in-flight list (oldest first); unreadable entries are skipped
+pub async fn list_leased_jobs(
+    conn: &mut redis::aio::ConnectionManager,
+    worker_id: &str,
+) -> RedisResult<Vec<LeasedJob>> {
+    let items: Vec<String> = conn.lrange(inflight_key(worker_id), 0, -1).await?;
+    
+    Ok(items
+        .into_iter()
+        .filter_map(|payload| {
+            let job = serde_json::from_str(&payload).ok()?;
+            Some(LeasedJob { job, payload })
+        })
+        .collect())
+}
+
+/// Move an unacknowledged job from a worker's in-flight list to its retry
+/// queue (or the DLQ with `dead_letter`) in one atomic step
+///
+/// `job` is the updated copy to queue (attempts, failure reason). With
+/// `only_if_expired`, nothing happens while the job's processing heartbeat
+/// is alive.
+/// Returns false if the entry was already gone (acked, or requeued by
+/// another worker) - the caller must then not act on the job.
+/// Stamps `retry_enqueued_at` or `dlq_at` in the job's hop log
+pub async fn requeue_leased_job(
+    conn: &mut redis::aio::ConnectionManager,
+    worker_id: &str,
+    leased: &LeasedJob,
+    job: &mut JobRequest,
+    dead_letter: bool,
+    only_if_expired: bool,
+) -> RedisResult<bool> {
+    let script = redis::Script::new(
+        r#"
+        if ARGV[1] == '1' and redis.call('EXISTS', KEYS[2]) == 1 then
+            return 0
+        end
+        if redis.call('LREM', KEYS[1], 1, ARGV[2]) == 0 then
+            return 0
+        end
+        redis.call('DEL', KEYS[2])
+        redis.call('RPUSH', KEYS[4], ARGV[3])
+        redis.call('SET', KEYS[5], ARGV[4], 'EX', ARGV[5])
+        if redis.call('LLEN', KEYS[1]) == 0 then
+            redis.call('SREM', KEYS[3], ARGV[6])
+        end
+        return 1
+        "#,
+    );
+    
+    let now = chrono::Utc::now().to_rfc3339();
+    let target = if dead_letter {
+        job.metadata.hops.dlq_at = Some(now);
+        dlq_name(&job.language)
+    } else {
+        job.metadata.hops.retry_enqueued_at.push(now);
+        pool_retry_queue_name(&job.language, job.pool().as_deref())
+    };
+    let (payload, hops) = queue_entry(job)?;
+    
+    let moved: i64 = script
+        .key(inflight_key(worker_id))
+        .key(processing_key(&job.id))
+        .key(INFLIGHT_WORKERS_KEY)
+        .key(target)
+        .key(hop_log_key(&job.id))
+        .arg(if only_if_expired { "1" } else { "0" })
+        .arg(&leased.payload)
+        .arg(payload)
+        .arg(hops)
+        .arg(HOP_LOG_TTL_SECS)
+        .arg(worker_id)
+        .invoke_async(conn)
+        .await?;
+    
+    Ok(moved == 1)
+}
+
+/// A job's hop log as of its last queue transition (None once expired)
+pub async fn get_hop_log(
+    conn: &mut redis::aio::ConnectionManager,
+    job_id: &uuid::Uuid,
+) -> RedisResult<Option<HopLog>> {
+    let payload: Option<String> = conn.get(hop_log_key(job_id)).await?;
+    
+    payload
+        .map(|data| serde_json::from_str(&data))
+        .transpose()
+        .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "deserialization error", e.to_string())))
+}
+
+/// Store execution result in Redis
+/// TTL is optional - set to 24 hours for now (can be configured later)
+///
+/// Refuses (returns false) when the job already has a result and its control
+/// key says cancelled: that result was forced by an operator
+/// (`force_complete_job`) and must not be replaced by a worker that still
+/// held the job. A plain user cancel sets no result, so the worker's
+/// cancelled result is still stored.
+/// 
+/// Also publishes metrics event for distributed tracking
+pub async fn store_result(
+    conn: &mut redis::aio::ConnectionManager,
+    result: &crate::types::ExecutionResult,
+) -> RedisResult<bool> {
+    let script = redis::Script::new(
+        r#"
+        if redis.call('EXISTS', KEYS[1]) == 1 then
+            local control = redis.call('GET', KEYS[3])
+            if control then
+                local ok, decoded = pcall(cjson.decode, control)
+                if ok and type(decoded) == 'table' and decoded.cancelled == true then
+                    return 0
+                end
+            end
+        end
+        redis.call('SET', KEYS[1], ARGV[1], 'EX', ARGV[3])
+        redis.call('SET', KEYS[2], ARGV[2], 'EX', ARGV[3])
+        redis.call('DEL', KEYS[4])
+        return 1
+        "#,
+    );
+    
+    let payload = serde_json::to_string(result)
+        .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "serialization error", e.to_string())))?;
+    // Also store status separately for quick lookup
+    let status = serde_json::to_string(&result.overall_status)
+        .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "serialization error", e.to_string())))?;
+    
+    // Store result with 24-hour TTL; it supersedes the tests published while it ran
+    let stored: i64 = script
+        .key(result_key(&result.job_id))
+        .key(status_key(&result.job_id))
+        .key(control_key(&result.job_id))
+        .key(partial_results_key(&result.job_id))
+        .arg(payload)
+        .arg(status)
+        .arg(86400)
+        .invoke_async(conn)
+        .await?;
+    if stored == 0 {
+        return Ok(false);
+    }
+    
+    // Wake long-polling readers (fire-and-forget, usually no subscribers)
+    let _: i64 = conn.publish(job_events_channel(&result.job_id), "stored").await.unwrap_or(0);
+    
+    Ok(true)
+}
+
+/// Publish a running job's progress in its status key
+/// Never replaces a final status: a result stored first wins
+pub async fn set_job_progress(
+    conn: &mut redis::aio::ConnectionManager,
+    job_id: &uuid::Uuid,
+    progress: &crate::types::JobProgress,
+) -> RedisResult<()> {
+    let script = redis::Script::new(
+        r#"
+        local current = redis.call('GET', KEYS[1])
+        if current and string.sub(current, 1, 1) ~= '{' then
+            return 0
+        end
+        redis.call('SET', KEYS[1], ARGV[1], 'EX', ARGV[2])
+        return 1
+        "#,
+    );
+
+    let payload = serde_json::to_string(progress)
+        .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "serialization error", e.to_string())))?;
+    let _: i64 = script
+        .key(status_key(job_id))
+        .arg(payload)
+        .arg(86400)
+        .invoke_async(conn)
+        .await?;
+    Ok(())
+}
+
+/// A job's progress, if its status key holds one (None before a worker
+/// starts it and once its result is stored)
+pub async fn get_job_progress(
+    conn: &mut redis::aio::ConnectionManager,
+    job_id: &uuid::Uuid,
+) -> RedisResult<Option<crate::types::JobProgress>> {
+    let payload: Option<String> = conn.get(status_key(job_id)).await?;
+    Ok(payload.and_then(|payload| serde_json::from_str(&payload).ok()))
+}
+
+/// Publish tests of a running job as they finish
+pub async fn append_partial_results(
+    conn: &mut redis::aio::ConnectionManager,
+    job_id: &uuid::Uuid,
+    results: &[crate::types::TestResult],
+) -> RedisResult<()> {
+    if results.is_empty() {
+        return Ok(());
+    }
+    let payloads = results
+        .iter()
+        .map(serde_json::to_string)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "serialization error", e.to_string())))?;
+
+    let key = partial_results_key(job_id);
+    let _: () = redis::pipe()
+        .atomic()
+        .rpush(&key, payloads)
+        .ignore()
+        .expire(&key, 86400)
+        .ignore()
+        .query_async(conn)
+        .await?;
+    Ok(())
+}
+
+/// Drop the tests published by an earlier run of a job
+pub async fn clear_partial_results(
+    conn: &mut redis::aio::ConnectionManager,
+    job_id: &uuid::Uuid,
+) -> RedisResult<()> {
+    conn.del(partial_results_key(job_id)).await
+}
+
+/// Tests of a running job published so far, in completion order
+/// (a test re-run after an infrastructure failure appears again)
+pub async fn get_partial_results(
+    conn: &mut redis::aio::ConnectionManager,
+    job_id: &uuid::Uuid,
+) -> RedisResult<Vec<crate::types::TestResult>> {
+    let items: Vec<String> = conn.lrange(partial_results_key(job_id), 0, -1).await?;
+    Ok(items.iter().filter_map(|item| serde_json::from_str(item).ok()).collect())
+}
+
+/// Find a job still sitting in any queue (any language), without removing it
+pub async fn find_queued_job(
+    conn: &mut redis::aio::ConnectionManager,
+    job_id: &uuid::Uuid,
+) -> RedisResult<Option<JobRequest>> {
+    for language in Language::all_variants() {
+        for queue in all_job_queues(conn, language).await? {
+            let items: Vec<String> = conn.lrange(&queue, 0, -1).await?;
+            let found = items
+                .iter()
+                .filter_map(|item| serde_json::from_str::<JobRequest>(item).ok())
+                .find(|job| job.id == *job_id);
+            if found.is_some() {
+                return Ok(found);
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// A job waiting in one of a language's consumed queues
+#[derive(Debug, Clone)]
+pub struct QueuedJob {
+    pub queue: String,
+    /// Index within `queue` (0 is popped next)
+    pub position: usize,
+    /// Exact list entry, for compare-and-swap replacement
+    pub payload: String,
+    pub job: JobRequest,
+}
+
+/// Jobs waiting for a worker of this language, in dequeue order, default
+/// pool first (the DLQ is excluded; undecodable entries are skipped)
+pub async fn list_queued_jobs(
+    conn: &mut redis::aio::ConnectionManager,
+    language: &Language,
+) -> RedisResult<Vec<QueuedJob>> {
+    let mut jobs = Vec::new();
+    let dlq = dlq_name(language);
+    for queue in all_job_queues(conn, language).await?.into_iter().filter(|queue| *queue != dlq) {
+        let items: Vec<String> = conn.lrange(&queue, 0, -1).await?;
+        for (position, payload) in items.into_iter().enumerate() {
+            if let Ok(job) = serde_json::from_str::<JobRequest>(&payload) {
+                jobs.push(QueuedJob {
+                    queue: queue.clone(),
+                    position,
+                    payload,
+                    job,
+                });
+            }
+        }
+    }
+    Ok(jobs)
+}
+
+/// Swap a queued job's entry for `job`, in place
+///
+/// Compare-and-swap on the exact entry read earlier: returns false (and
+/// changes nothing) if a worker popped it or it moved to another queue since.
+pub async fn replace_queued_job(
+    conn: &mut redis::aio::ConnectionManager,
+    queued: &QueuedJob,
+    job: &JobRequest,
+) -> RedisResult<bool> {
+    let script = redis::Script::new(
+        r#"
+        local items = redis.call('LRANGE', KEYS[1], 0, -1)
+        for i, item in ipairs(items) do
+            if item == ARGV[1] then
+                redis.call('LSET', KEYS[1], i - 1, ARGV[2])
+                return 1
+            end
+        end
+        return 0
+        "#,
+    );
+
+    let payload = serde_json::to_string(job)
+        .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "serialization error", e.to_string())))?;
+
+    let replaced: i64 = script
+        .key(&queued.queue)
+        .arg(&queued.payload)
+        .arg(payload)
+        .invoke_async(conn)
+        .await?;
+
+    Ok(replaced == 1)
+}
+
+/// Length of every list a job of this language can sit in (see `all_job_queues`)
+pub async fn queue_depths(
+    conn: &mut redis::aio::ConnectionManager,
+    language: &Language,
+) -> RedisResult<Vec<(String, u64)>> {
+    let queues = all_job_queues(conn, language).await?;
+    let mut pipe = redis::pipe();
+    for queue in &queues {
+        pipe.llen(queue);
+    }
+    let depths: Vec<u64> = pipe.query_async(conn).await?;
+    Ok(queues.into_iter().zip(depths).collect())
+}
+
+/// Empty a language's queues in one step: every consumed queue of every
+/// pool, plus the DLQ with `include_dlq`
+/// Returns the removed entries by queue (jobs already held by workers and
+/// delayed batch jobs are untouched)
+pub async fn drain_queues(
+    conn: &mut redis::aio::ConnectionManager,
+    language: &Language,
+    include_dlq: bool,
+) -> RedisResult<Vec<(String, Vec<String>)>> {
+    let script = redis::Script::new(
+        r#"
+        local drained = {}
+        for i, queue in ipairs(KEYS) do
+            drained[i] = redis.call('LRANGE', queue, 0, -1)
+            redis.call('DEL', queue)
+        end
+        return drained
+        "#,
+    );
+
+    let dlq = dlq_name(language);
+    let queues: Vec<String> = all_job_queues(conn, language)
+        .await?
+        .into_iter()
+        .filter(|queue| include_dlq || *queue != dlq)
+        .collect();
+    let mut invocation = script.prepare_invoke();
+    for queue in &queues {
+        invocation.key(queue);
+    }
+    let drained: Vec<Vec<String>> = invocation.invoke_async(conn).await?;
+    Ok(queues.into_iter().zip(drained).collect())
+}
+
+/// Dead-lettered jobs of a language, oldest first (undecodable entries are skipped)
+pub async fn list_dlq_jobs(
+    conn: &mut redis::aio::ConnectionManager,
+    language: &Language,
+) -> RedisResult<Vec<QueuedJob>> {
+    let queue = dlq_name(language);
+    let items: Vec<String> = conn.lrange(&queue, 0, -1).await?;
+    Ok(items
+        .into_iter()
+        .enumerate()
+        .filter_map(|(position, payload)| {
+            let job = serde_json::from_str::<JobRequest>(&payload).ok()?;
+            Some(QueuedJob { queue: queue.clone(), position, payload, job })
+        })
+        .collect())
+}
+
+/// Whether a job's result was cleared for a re-run that has not been
+/// persisted elsewhere yet (see `requeue_dlq_job`)
+pub async fn result_was_reset(
+    conn: &mut redis::aio::ConnectionManager,
+    job_id: &uuid::Uuid,
+) -> RedisResult<bool> {
+    conn.exists(result_reset_key(job_id)).await
+}
+
+/// Drop a job's reset marker once its current result has been persisted
+pub async fn clear_result_reset(
+    conn: &mut redis::aio::ConnectionManager,
+    job_id: &uuid::Uuid,
+) -> RedisResult<()> {
+    conn.del(result_reset_key(job_id)).await
+}
+
+/// Move a dead-lettered job back to its priority queue for a fresh set of attempts
+///
+/// In one script: removes the exact DLQ entry read earlier, deletes the
+/// failed result and status stored when it was dead-lettered (marking the
+/// result reset, see `result_reset_key`), and queues the job with its
+/// attempt count reset (`last_failure_reason` is kept). Returns
+/// the queue it went to, or None (nothing changed) if the entry left the DLQ
+/// in the meantime.
+pub async fn requeue_dlq_job(
+    conn: &mut redis::aio::ConnectionManager,
+    dead: &QueuedJob,
+) -> RedisResult<Option<String>> {
+    let script = redis::Script::new(
+        r#"
+        if redis.call('LREM', KEYS[1], 1, ARGV[1]) == 0 then
+            return 0
+        end
+        redis.call('DEL', KEYS[3], KEYS[4])
+        redis.call('SET', KEYS[6], '1', 'EX', ARGV[5])
+        redis.call('RPUSH', KEYS[2], ARGV[2])
+        redis.call('SET', KEYS[5], ARGV[3], 'EX', ARGV[4])
+        return 1
+        "#,
+    );
+
+    let mut job = dead.job.clone();
+    job.metadata.attempts = 0;
+    job.metadata.hops.dlq_at = None;
+    let pool = job.pool();
+    let queue = pool_priority_queue_name(&job.language, pool.as_deref(), job.priority);
+    let (payload, hops) = queue_entry(&job)?;
+
+    if let Some(pool) = pool {
+        let _: () = conn.sadd(pools_key(&job.language), pool).await?;
+    }
+    let moved: i64 = script
+        .key(&dead.queue)
+        .key(&queue)
+        .key(result_key(&job.id))
+        .key(status_key(&job.id))
+        .key(hop_log_key(&job.id))
+        .key(result_reset_key(&job.id))
+        .arg(&dead.payload)
+        .arg(payload)
+        .arg(hops)
+        .arg(HOP_LOG_TTL_SECS)
+        .arg(86400)
+        .invoke_async(conn)
+        .await?;
+
+    Ok((moved == 1).then_some(queue))
+}
+
+/// Drop every dead-lettered job of a language, returning how many there were
+pub async fn purge_dlq(
+    conn: &mut redis::aio::ConnectionManager,
+    language: &Language,
+) -> RedisResult<u64> {
+    let queue = dlq_name(language);
+    let (purged, _): (u64, i64) = redis::pipe()
+        .atomic()
+        .llen(&queue)
+        .del(&queue)
+        .query_async(conn)
+        .await?;
+    Ok(purged)
+}
+
+/// Operator override: store a terminal result for a stuck job
+///
+/// In one script: refuses if a result already exists, removes the job from
+/// every queue of every language and pool (including the DLQ), from
+/// workers' in-flight lists and from the delayed set, stores the result and
+/// status, and sets the cancellation flag so a worker that still holds the
+/// job won't start it.
+///
+/// Returns `None` if the job already had a result, otherwise the number of
+/// queue entries removed.
+pub async fn force_complete_job(
+    conn: &mut redis::aio::ConnectionManager,
+    result: &crate::types::ExecutionResult,
+) -> RedisResult<Option<u32>> {
+    let script = redis::Script::new(
+        r#"
+        if redis.call('EXISTS', KEYS[1]) == 1 then
+            return -1
+        end
+        local removed = redis.call('ZREM', KEYS[4], ARGV[1])
+        redis.call('HDEL', KEYS[5], ARGV[1])
+        redis.call('HDEL', KEYS[6], ARGV[1])
+        for i = 7, #KEYS do
+            for _, item in ipairs(redis.call('LRANGE', KEYS[i], 0, -1)) do
+                local ok, job = pcall(cjson.decode, item)
+                if ok and type(job) == 'table' and job.id == ARGV[1] then
+                    removed = removed + redis.call('LREM', KEYS[i], 0, item)
+                end
+            end
+        end
+        redis.call('SET', KEYS[1], ARGV[2], 'EX', ARGV[5])
+        redis.call('SET', KEYS[2], ARGV[3], 'EX', ARGV[5])
+        redis.call('SET', KEYS[3], ARGV[4], 'EX', ARGV[5])
+        return removed
+        "#,
+    );
+    
+    let payload = serde_json::to_string(result)
+        .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "serialization error", e.to_string())))?;
+    let status = serde_json::to_string(&result.overall_status)
+        .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "serialization error", e.to_string())))?;
+    let control = serde_json::to_string(&crate::types::JobControl { cancelled: true })
+        .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "serialization error", e.to_string())))?;
+    
+    let mut invocation = script.prepare_invoke();
+    invocation
+        .key(result_key(&result.job_id))
+        .key(status_key(&result.job_id))
+        .key(control_key(&result.job_id))
+        .key(delayed_key())
+        .key(delayed_jobs_key())
+        .key(delayed_queues_key());
+    for language in Language::all_variants() {
+        for queue in all_job_queues(conn, language).await? {
+            invocation.key(queue);
+        }
+    }
+    // A lost worker's unacknowledged copy would otherwise be requeued by the reaper
+    for worker_id in inflight_workers(conn).await? {
+        invocation.key(inflight_key(&worker_id));
+    }
+    
+    let removed: i64 = invocation
+        .arg(result.job_id.to_string())
+        .arg(payload)
+        .arg(status)
+        .arg(control)
+        .arg(86400)
+        .invoke_async(conn)
+        .await?;
+    
+    if removed < 0 {
+        return Ok(None);
+    }
+    
+    // Wake long-polling readers
+    let _: i64 = conn.publish(job_events_channel(&result.job_id), "stored").await.unwrap_or(0);
+    
+    Ok(Some(removed as u32))
+}
+
+/// Store execution result and publish completion metrics
+/// This is a convenience function that combines store_result with metrics publishing
+/// Returns false if an operator's forced result was kept (see `store_result`)
+pub async fn store_result_with_metrics(
+    conn: &mut redis::aio::ConnectionManager,
+    result: &crate::types::ExecutionResult,
+    language: &crate::types::Language,
+) -> RedisResult<bool> {
+    // Store the result first
+    if !store_result(conn, result).await? {
+        return Ok(false);
+    }
+    
+    // Publish metrics event
+    publish_job_completion(conn, result, language).await?;
+    
+    Ok(true)
+}
+
+/// Publish job completion metrics (for distributed metrics tracking)
+async fn publish_job_completion(
+    conn: &mut redis::aio::ConnectionManager,
+    result: &crate::types::ExecutionResult,
+    language: &crate::types::Language,
+) -> RedisResult<()> {
+    // Calculate total execution time from test results
+    let total_execution_time_ms: u64 = result.results.iter()
+        .map(|r| r.execution_time_ms)
+        .sum();
+    
+    let channel = format!("{}:completions", METRICS_PREFIX);
+    let event = serde_json::json!({
+        "job_id": result.job_id.to_string(),
+        "language": language.to_string(),
+        "status": format!("{:?}", result.overall_status),
+        "execution_time_ms": total_execution_time_ms,
+        "timestamp": chrono::Utc::now().to_rfc3339(),
+    });
+    
+    let payload = serde_json::to_string(&event)
+        .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "serialization error", e.to_string())))?;
+    
+    // Publish event (fire-and-forget, no subscribers required)
+    let _: i64 = conn.publish(&channel, payload).await.unwrap_or(0);
+    Ok(())
+}
+
+/// Retrieve execution result from Redis
+pub async fn get_result(
+    conn: &mut redis::aio::ConnectionManager,
+    job_id: &uuid::Uuid,
+) -> RedisResult<Option<crate::types::ExecutionResult>> {
+    let key = result_key(job_id);
+    let payload: Option<String> = conn.get(&key).await?;
+    
+    match payload {
+        Some(data) => {
+            let result: crate::types::ExecutionResult = serde_json::from_str(&data)
+                .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "deserialization error", e.to_string())))?;
+            Ok(Some(result))
+        }
+        None => Ok(None),
+    }
+}
+
+/// One SCAN step over stored results: the next cursor (0 when the walk is
+/// done) and the job ids found in this step
+pub async fn scan_result_ids(
+    conn: &mut redis::aio::ConnectionManager,
+    cursor: u64,
+    count: usize,
+) -> RedisResult<(u64, Vec<uuid::Uuid>)> {
+    let (next, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+        .arg(cursor)
+        .arg("MATCH")
+        .arg(format!("{}:*", RESULT_PREFIX))
+        .arg("COUNT")
+        .arg(count)
+        .query_async(conn)
+        .await?;
+    let prefix = format!("{}:", RESULT_PREFIX);
+    let ids = keys
+        .iter()
+        .filter_map(|key| key.strip_prefix(&prefix))
+        .filter_map(|id| uuid::Uuid::parse_str(id).ok())
+        .collect();
+    Ok((next, ids))
+}
+
+/// Push a dry-run request to the language's admin queue
+pub async fn push_dry_run(
+    conn: &mut redis::aio::ConnectionManager,
+    request: &DryRunRequest,
+) -> RedisResult<()> {
+    let queue = dry_run_queue_name(&request.language);
+    let payload = serde_json::to_string(request)
+        .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "serialization error", e.to_string())))?;
+    
+    conn.rpush(&queue, payload).await
+}
+
+/// Pop a dry-run request from the language's admin queue
+pub async fn pop_dry_run(
+    conn: &mut redis::aio::ConnectionManager,
+    language: &Language,
+    timeout_seconds: f64,
+) -> RedisResult<Option<DryRunRequest>> {
+    let queue = dry_run_queue_name(language);
+    let result: Option<(String, String)> = conn.blpop(&queue, timeout_seconds).await?;
+    
+    match result {
+        Some((_key, payload)) => {
+            let request: DryRunRequest = serde_json::from_str(&payload)
+                .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "deserialization error", e.to_string())))?;
+            Ok(Some(request))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Store a dry-run result (short 5-minute TTL - the caller is waiting on it)
+pub async fn store_dry_run_result(
+    conn: &mut redis::aio::ConnectionManager,
+    result: &DryRunResult,
+) -> RedisResult<()> {
+    let key = dry_run_result_key(&result.id);
+    let payload = serde_json::to_string(result)
+        .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "serialization error", e.to_string())))?;
+    
+    conn.set_ex(&key, payload, 300).await
+}
+
+/// Retrieve a dry-run result
+pub async fn get_dry_run_result(
+    conn: &mut redis::aio::ConnectionManager,
+    request_id: &uuid::Uuid,
+) -> RedisResult<Option<DryRunResult>> {
+    let key = dry_run_result_key(request_id);
+    let payload: Option<String> = conn.get(&key).await?;
+    
+    match payload {
+        Some(data) => {
+            let result: DryRunResult = serde_json::from_str(&data)
+                .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "deserialization error", e.to_string())))?;
+            Ok(Some(result))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Try to take one cluster-wide execution slot for a language
+///
+/// Distributed semaphore backed by a sorted set:
+/// - Expired leases are evicted first (crashed workers can't leak slots)
+/// - A slot is granted only while fewer than `limit` leases are live
+/// - The check-and-add runs atomically in a Lua script
+///
+/// Returns true if the slot was acquired.
+pub async fn try_acquire_global_slot(
+    conn: &mut redis::aio::ConnectionManager,
+    language: &Language,
+    holder: &str,
+    limit: usize,
+    lease_ms: u64,
+) -> RedisResult<bool> {
+    let script = redis::Script::new(
+        r#"
+        local now = tonumber(ARGV[1])
+        redis.call('ZREMRANGEBYSCORE', KEYS[1], '-inf', now)
+        if redis.call('ZCARD', KEYS[1]) < tonumber(ARGV[2]) then
+            redis.call('ZADD', KEYS[1], now + tonumber(ARGV[3]), ARGV[4])
+            return 1
+        end
+        return 0
+        "#,
+    );
+    
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let acquired: i64 = script
+        .key(global_slots_key(language))
+        .arg(now_ms)
+        .arg(limit)
+        .arg(lease_ms)
+        .arg(holder)
+        .invoke_async(conn)
+        .await?;
+    
+    Ok(acquired == 1)
+}
+
+/// Release a cluster-wide execution slot
+pub async fn release_global_slot(
+    conn: &mut redis::aio::ConnectionManager,
+    language: &Language,
+    holder: &str,
+) -> RedisResult<()> {
+    conn.zrem(global_slots_key(language), holder).await
+}
+
+/// Take one token from a caller's bucket
+///
+/// Token bucket stored as a hash (tokens, last refill ms), refilled
+/// continuously at `per_minute / 60_000` tokens per ms up to `burst`.
+/// Runs atomically in Lua so API replicas share one bucket per caller.
+///
+/// Returns `(allowed, retry_after_ms)`.
+pub async fn take_rate_limit_token(
+    conn: &mut redis::aio::ConnectionManager,
+    key: &str,
+    per_minute: u32,
+    burst: u32,
+) -> RedisResult<(bool, u64)> {
+    let script = redis::Script::new(
+        r#"
+        local capacity = tonumber(ARGV[1])
+        local refill_per_ms = tonumber(ARGV[2])
+        local now = tonumber(ARGV[3])
+        local bucket = redis.call('HMGET', KEYS[1], 'tokens', 'ts')
+        local tokens = tonumber(bucket[1]) or capacity
+        local ts = tonumber(bucket[2]) or now
+        tokens = math.min(capacity, tokens + math.max(0, now - ts) * refill_per_ms)
+        local allowed = 0
+        local retry_after = 0
+        if tokens >= 1 then
+            tokens = tokens - 1
+            allowed = 1
+        else
+            retry_after = math.ceil((1 - tokens) / refill_per_ms)
+        end
+        redis.call('HSET', KEYS[1], 'tokens', tostring(tokens), 'ts', now)
+        redis.call('PEXPIRE', KEYS[1], math.ceil(capacity / refill_per_ms) + 1000)
+        return {allowed, retry_after}
+        "#,
+    );
+    
+    let refill_per_ms = per_minute as f64 / 60_000.0;
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let (allowed, retry_after_ms): (i64, i64) = script
+        .key(key)
+        .arg(burst.max(1))
+        .arg(refill_per_ms)
+        .arg(now_ms)
+        .invoke_async(conn)
+        .await?;
+    
+    Ok((allowed == 1, retry_after_ms.max(0) as u64))
+}
+
+/// Atomically claim an idempotency key for a new job
+///
+/// Uses `SET NX` so two concurrent retries can't both enqueue.
+/// Returns `None` when the key was claimed (caller should enqueue) or
+/// `Some(existing)` when an earlier request already owns it.
+pub async fn claim_idempotency_key(
+    conn: &mut redis::aio::ConnectionManager,
+    key: &str,
+    record: &IdempotencyRecord,
+) -> RedisResult<Option<IdempotencyRecord>> {
+    let payload = serde_json::to_string(record)
+        .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "serialization error", e.to_string())))?;
+    
+    let claimed: Option<String> = redis::cmd("SET")
+        .arg(key)
+        .arg(&payload)
+        .arg("NX")
+        .arg("EX")
+        .arg(IDEMPOTENCY_TTL_SECS)
+        .query_async(conn)
+        .await?;
+    
+    if claimed.is_some() {
+        return Ok(None);
+    }
+    
+    let existing: Option<String> = conn.get(key).await?;
+    match existing {
+        Some(data) => {
+            let existing: IdempotencyRecord = serde_json::from_str(&data)
+                .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "deserialization error", e.to_string())))?;
+            Ok(Some(existing))
+        }
+        None => {
+            // Expired between SET and GET - take it over
+            conn.set_ex::<_, _, ()>(key, payload, IDEMPOTENCY_TTL_SECS).await?;
+            Ok(None)
+        }
+    }
+}
+
+/// Release an idempotency key (used when the claimed job could not be queued)
+pub async fn release_idempotency_key(
+    conn: &mut redis::aio::ConnectionManager,
+    key: &str,
+) -> RedisResult<()> {
+    conn.del::<_, ()>(key).await
+}
+
+/// Store a new batch definition (24-hour TTL, like results)
+pub async fn create_batch(
+    conn: &mut redis::aio::ConnectionManager,
+    batch: &BatchRecord,
+) -> RedisResult<()> {
+    let payload = serde_json::to_string(batch)
+        .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "serialization error", e.to_string())))?;
+    
+    conn.set_ex(batch_key(&batch.id), payload, 86400).await
+}
+
+/// Retrieve a batch definition
+pub async fn get_batch(
+    conn: &mut redis::aio::ConnectionManager,
+    batch_id: &uuid::Uuid,
+) -> RedisResult<Option<BatchRecord>> {
+    let payload: Option<String> = conn.get(batch_key(batch_id)).await?;
+    
+    match payload {
+        Some(data) => {
+            let batch: BatchRecord = serde_json::from_str(&data)
+                .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "deserialization error", e.to_string())))?;
+            Ok(Some(batch))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Store a new problem definition
+/// Problems are write-once: returns false (and changes nothing) if the id is taken
+pub async fn create_problem(
+    conn: &mut redis::aio::ConnectionManager,
+    problem: &Problem,
+) -> RedisResult<bool> {
+    let payload = serde_json::to_string(problem)
+        .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "serialization error", e.to_string())))?;
+    
+    conn.set_nx(problem_key(&problem.id), payload).await
+}
+
+/// Retrieve a problem definition
+pub async fn get_problem(
+    conn: &mut redis::aio::ConnectionManager,
+    problem_id: &str,
+) -> RedisResult<Option<Problem>> {
+    let payload: Option<String> = conn.get(problem_key(problem_id)).await?;
+    
+    match payload {
+        Some(data) => {
+            let problem: Problem = serde_json::from_str(&data)
+                .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "deserialization error", e.to_string())))?;
+            Ok(Some(problem))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Record an uploaded problem asset, replacing an earlier one of the same name
+pub async fn put_problem_asset(
+    conn: &mut redis::aio::ConnectionManager,
+    problem_id: &str,
+    asset: &ProblemAsset,
+) -> RedisResult<()> {
+    let payload = serde_json::to_string(asset)
+        .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "serialization error", e.to_string())))?;
+    
+    conn.hset(problem_assets_key(problem_id), &asset.name, payload).await
+}
+
+/// Metadata of one problem asset
+pub async fn get_problem_asset(
+    conn: &mut redis::aio::ConnectionManager,
+    problem_id: &str,
+    name: &str,
+) -> RedisResult<Option<ProblemAsset>> {
+    let payload: Option<String> = conn.hget(problem_assets_key(problem_id), name).await?;
+    
+    payload
+        .map(|data| serde_json::from_str(&data))
+        .transpose()
+        .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "deserialization error", e.to_string())))
+}
+
+/// All of a problem's assets, ordered by name
+pub async fn list_problem_assets(
+    conn: &mut redis::aio::ConnectionManager,
+    problem_id: &str,
+) -> RedisResult<Vec<ProblemAsset>> {
+    let entries: std::collections::HashMap<String, String> = conn.hgetall(problem_assets_key(problem_id)).await?;
+    
+    let mut assets = entries
+        .values()
+        .map(|data| serde_json::from_str::<ProblemAsset>(data))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "deserialization error", e.to_string())))?;
+    assets.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(assets)
+}
+
+/// Store a new upload session (expires after UPLOAD_TTL_SECS)
+pub async fn create_upload(
+    conn: &mut redis::aio::ConnectionManager,
+    session: &UploadSession,
+) -> RedisResult<()> {
+    let payload = serde_json::to_string(session)
+        .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "serialization error", e.to_string())))?;
+    
+    conn.set_ex(upload_key(&session.id), payload, UPLOAD_TTL_SECS).await
+}
+
+/// Retrieve an upload session
+pub async fn get_upload(
+    conn: &mut redis::aio::ConnectionManager,
+    upload_id: &uuid::Uuid,
+) -> RedisResult<Option<UploadSession>> {
+    let payload: Option<String> = conn.get(upload_key(upload_id)).await?;
+    
+    payload
+        .map(|data| serde_json::from_str(&data))
+        .transpose()
+        .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "deserialization error", e.to_string())))
+}
+
+/// Swap an upload session for `next`, refreshing its expiry
+///
+/// Compare-and-swap on `previous` as read earlier: returns false (and
+/// changes nothing) if a concurrent chunk or finalize updated the session
+/// first, or it expired.
+pub async fn replace_upload(
+    conn: &mut redis::aio::ConnectionManager,
+    previous: &UploadSession,
+    next: &UploadSession,
+) -> RedisResult<bool> {
+    let script = redis::Script::new(
+        r#"
+        if redis.call('GET', KEYS[1]) ~= ARGV[1] then
+            return 0
+        end
+        redis.call('SET', KEYS[1], ARGV[2], 'EX', ARGV[3])
+        return 1
+        "#,
+    );
+
+    let serialize = |session: &UploadSession| {
+        serde_json::to_string(session)
+            .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "serialization error", e.to_string())))
+    };
+
+    let replaced: i64 = script
+        .key(upload_key(&previous.id))
+        .arg(serialize(previous)?)
+        .arg(serialize(next)?)
+        .arg(UPLOAD_TTL_SECS)
+        .invoke_async(conn)
+        .await?;
+
+    Ok(replaced == 1)
+}
+
+/// Fetch a cached compiled build
+pub async fn get_artifact(
+    conn: &mut redis::aio::ConnectionManager,
+    source_hash: &str,
+) -> RedisResult<Option<Vec<u8>>> {
+    conn.get(artifact_key(source_hash)).await
+}
+
+/// Cache a compiled build for ARTIFACT_TTL_SECS
+pub async fn store_artifact(
+    conn: &mut redis::aio::ConnectionManager,
+    source_hash: &str,
+    artifact: &[u8],
+) -> RedisResult<()> {
+    conn.set_ex(artifact_key(source_hash), artifact, ARTIFACT_TTL_SECS).await
 }
 
-/// Generate dead letter queue name for a language
-pub fn dlq_name(language: &Language) -> String {
-    format!("{}:{}:dlq", QUEUE_PREFIX, language)
+/// Reserve one job slot in a batch
+/// Returns the slot's 0-based submission index, or None when the batch
+/// already holds `expected_jobs` submissions
+pub async fn reserve_batch_slot(
+    conn: &mut redis::aio::ConnectionManager,
+    batch: &BatchRecord,
+) -> RedisResult<Option<u32>> {
+    let script = redis::Script::new(
+        r#"
+        local submitted = redis.call('INCR', KEYS[1])
+        if submitted > tonumber(ARGV[1]) then
+            redis.call('DECR', KEYS[1])
+            return 0
+        end
+        redis.call('EXPIRE', KEYS[1], 86400)
+        return submitted
+        "#,
+    );
+    
+    let submitted: i64 = script
+        .key(batch_submitted_key(&batch.id))
+        .arg(batch.expected_jobs)
+        .invoke_async(conn)
+        .await?;
+    
+    Ok((submitted > 0).then(|| submitted as u32 - 1))
 }
 
-/// Generate result key for a job
-pub fn result_key(job_id: &uuid::Uuid) -> String {
-    format!("{}:{}", RESULT_PREFIX, job_id)
+/// Give back a reserved batch slot (job could not be queued)
+pub async fn release_batch_slot(
+    conn: &mut redis::aio::ConnectionManager,
+    batch_id: &uuid::Uuid,
+) -> RedisResult<()> {
+    conn.decr(batch_submitted_key(batch_id), 1).await
 }
 
-/// Generate status key for a job
-pub fn status_key(job_id: &uuid::Uuid) -> String {
-    format!("{}:{}", STATUS_PREFIX, job_id)
+/// Submitted and finished job counts for a batch
+pub async fn get_batch_progress(
+    conn: &mut redis::aio::ConnectionManager,
+    batch_id: &uuid::Uuid,
+) -> RedisResult<(u32, u32)> {
+    let submitted: Option<u32> = conn.get(batch_submitted_key(batch_id)).await?;
+    let finished: u32 = conn.scard(batch_finished_key(batch_id)).await?;
+    Ok((submitted.unwrap_or(0), finished))
 }
 
-/// Generate control key for a job (cancellation flag)
-pub fn control_key(job_id: &uuid::Uuid) -> String {
-    format!("{}:{}", CONTROL_PREFIX, job_id)
+/// Record that a batch job reached a terminal state
+///
+/// Set membership makes this idempotent (a job stored twice counts once).
+/// Returns true for exactly one caller: the one that finished the batch.
+pub async fn mark_batch_job_finished(
+    conn: &mut redis::aio::ConnectionManager,
+    batch: &BatchRecord,
+    job_id: &uuid::Uuid,
+) -> RedisResult<bool> {
+    let script = redis::Script::new(
+        r#"
+        local added = redis.call('SADD', KEYS[1], ARGV[1])
+        redis.call('EXPIRE', KEYS[1], 86400)
+        if added == 1 and redis.call('SCARD', KEYS[1]) == tonumber(ARGV[2]) then
+            return 1
+        end
+        return 0
+        "#,
+    );
+    
+    let completed: i64 = script
+        .key(batch_finished_key(&batch.id))
+        .arg(job_id.to_string())
+        .arg(batch.expected_jobs)
+        .invoke_async(conn)
+        .await?;
+    
+    Ok(completed == 1)
 }
 
-/// Push a job to the language-specific queue
-/// Uses RPUSH for FIFO semantics
-pub async fn push_job(
+/// Job ids of all finished jobs in a batch
+pub async fn get_batch_finished_jobs(
     conn: &mut redis::aio::ConnectionManager,
-    job: &JobRequest,
+    batch_id: &uuid::Uuid,
+) -> RedisResult<Vec<uuid::Uuid>> {
+    let members: Vec<String> = conn.smembers(batch_finished_key(batch_id)).await?;
+    Ok(members.iter().filter_map(|m| m.parse().ok()).collect())
+}
+
+/// Store and broadcast a batch completion event
+///
+/// - Stored so late SSE subscribers and `GET /batches/{id}` can still see it
+/// - Published on the batch events channel for live SSE streams
+/// - Queued for webhook delivery when the batch has a webhook URL, shaped
+///   by its `webhook_payload` template (`results` are the jobs' results)
+pub async fn publish_batch_completion(
+    conn: &mut redis::aio::ConnectionManager,
+    completion: &BatchCompletion,
+    batch: &BatchRecord,
+    results: &[crate::types::ExecutionResult],
 ) -> RedisResult<()> {
-    let queue = queue_name(&job.language);
-    let payload = serde_json::to_string(job)
-        .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "serialization error", e.to_string())))?;
+    let serialization_error = |e: serde_json::Error| {
+        redis::RedisError::from((redis::ErrorKind::TypeError, "serialization error", e.to_string()))
+    };
+    let payload = serde_json::to_string(completion).map_err(serialization_error)?;
     
-    conn.rpush(&queue, payload).await
+    let _: () = conn.set_ex(batch_completion_key(&completion.batch_id), &payload, 86400).await?;
+    let _: () = conn.publish(batch_events_channel(&completion.batch_id), &payload).await?;
+    
+    if let Some(url) = &batch.webhook_url {
+        let body = match batch.webhook_payload {
+            crate::types::WebhookPayload::Summary => payload,
+            ref template => template.render(completion, results).map_err(serialization_error)?,
+        };
+        let delivery = WebhookDelivery {
+            url: url.clone(),
+            event: "batch.completed".to_string(),
+            body,
+        };
+        push_webhook(conn, &delivery).await?;
+    }
+    
+    Ok(())
 }
 
-/// Push a job to the retry queue
-pub async fn push_to_retry_queue(
+/// Build per-job summaries for a finished batch and publish the completion
+///
+/// Called by whoever `mark_batch_job_finished` returned true for.
+/// Jobs whose result has already expired are left out of the summary.
+pub async fn complete_batch(
     conn: &mut redis::aio::ConnectionManager,
-    job: &JobRequest,
-) -> RedisResult<()> {
-    let queue = retry_queue_name(&job.language);
-    let payload = serde_json::to_string(job)
-        .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "serialization error", e.to_string())))?;
+    batch: &BatchRecord,
+) -> RedisResult<BatchCompletion> {
+    let mut jobs = Vec::new();
+    let mut results = Vec::new();
     
-    conn.rpush(&queue, payload).await
+    for job_id in get_batch_finished_jobs(conn, &batch.id).await? {
+        if let Some(result) = get_result(conn, &job_id).await? {
+            jobs.push(BatchJobSummary {
+                job_id,
+                status: result.overall_status,
+                score: result.score,
+                max_score: result.max_score,
+            });
+            // Only templates beyond the summary need the full results
+            if batch.webhook_url.is_some() && !batch.webhook_payload.is_summary() {
+                results.push(result);
+            }
+        }
+    }
+    
+    let completion = BatchCompletion {
+        batch_id: batch.id,
+        total_jobs: batch.expected_jobs,
+        completed_at: chrono::Utc::now().to_rfc3339(),
+        jobs,
+    };
+    
+    publish_batch_completion(conn, &completion, batch, &results).await?;
+    Ok(completion)
 }
 
-/// Push a job to the dead letter queue
-pub async fn push_to_dlq(
+/// Retrieve a batch completion event, if the batch has finished
+pub async fn get_batch_completion(
     conn: &mut redis::aio::ConnectionManager,
-    job: &JobRequest,
+    batch_id: &uuid::Uuid,
+) -> RedisResult<Option<BatchCompletion>> {
+    let payload: Option<String> = conn.get(batch_completion_key(batch_id)).await?;
+    
+    match payload {
+        Some(data) => {
+            let completion: BatchCompletion = serde_json::from_str(&data)
+                .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "deserialization error", e.to_string())))?;
+            Ok(Some(completion))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Queue a webhook for delivery
+pub async fn push_webhook(
+    conn: &mut redis::aio::ConnectionManager,
+    delivery: &WebhookDelivery,
 ) -> RedisResult<()> {
-    let queue = dlq_name(&job.language);
-    let payload = serde_json::to_string(job)
+    let payload = serde_json::to_string(delivery)
         .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "serialization error", e.to_string())))?;
     
-    conn.rpush(&queue, payload).await
+    conn.rpush(WEBHOOK_QUEUE, payload).await
 }
 
-/// Pop a job from the language-specific queue
-/// Uses BLPOP with timeout for graceful shutdown
-pub async fn pop_job(
+/// Pop the next webhook to deliver (BLPOP with timeout)
+pub async fn pop_webhook(
     conn: &mut redis::aio::ConnectionManager,
-    language: &Language,
     timeout_seconds: f64,
-) -> RedisResult<Option<JobRequest>> {
-    let queue = queue_name(language);
-    let result: Option<(String, String)> = conn.blpop(&queue, timeout_seconds).await?;
+) -> RedisResult<Option<WebhookDelivery>> {
+    let result: Option<(String, String)> = conn.blpop(WEBHOOK_QUEUE, timeout_seconds).await?;
     
     match result {
         Some((_key, payload)) => {
-            let job: JobRequest = serde_json::from_str(&payload)
+            let delivery: WebhookDelivery = serde_json::from_str(&payload)
                 .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "deserialization error", e.to_string())))?;
-            Ok(Some(job))
+            Ok(Some(delivery))
         }
         None => Ok(None),
     }
 }
 
-/// Pop a job from either the main queue or retry queue (priority: main first)
-/// Uses BLPOP with multiple keys - Redis pops from first non-empty queue
-pub async fn pop_job_with_retry(
+/// Publish languages.json for workers to pick up
+/// Body and version are written atomically; returns the new version
+pub async fn publish_language_config(
     conn: &mut redis::aio::ConnectionManager,
-    language: &Language,
-    timeout_seconds: f64,
-) -> RedisResult<Option<JobRequest>> {
-    let main_queue = queue_name(language);
-    let retry_queue = retry_queue_name(language);
+    body: &str,
+) -> RedisResult<u64> {
+    let script = redis::Script::new(
+        r#"
+        redis.call('HSET', KEYS[1], 'body', ARGV[1])
+        return redis.call('HINCRBY', KEYS[1], 'version', 1)
+        "#,
+    );
+    
+    script.key(language_config_key()).arg(body).invoke_async(conn).await
+}
+
+/// Current published languages.json version (cheap change check)
+pub async fn get_language_config_version(
+    conn: &mut redis::aio::ConnectionManager,
+) -> RedisResult<Option<u64>> {
+    conn.hget(language_config_key(), "version").await
+}
+
+/// Published languages.json as `(version, body)`
+pub async fn get_language_config(
+    conn: &mut redis::aio::ConnectionManager,
+) -> RedisResult<Option<(u64, String)>> {
+    let (version, body): (Option<u64>, Option<String>) = redis::cmd("HMGET")
+        .arg(language_config_key())
+        .arg("version")
+        .arg("body")
+        .query_async(conn)
+        .await?;
+    
+    Ok(version.zip(body))
+}
+
+/// Record a worker's warmup baseline (replaces its previous one)
+pub async fn publish_warmup_baseline(
+    conn: &mut redis::aio::ConnectionManager,
+    baseline: &WarmupBaseline,
+) -> RedisResult<()> {
+    let key = warmup_key(&baseline.language);
+    let payload = serde_json::to_string(baseline)
+        .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "serialization error", e.to_string())))?;
     
-    // BLPOP checks keys in order - main queue has priority
-    let result: Option<(String, String)> = conn.blpop(&[main_queue, retry_queue], timeout_seconds).await?;
+    let _: () = redis::pipe()
+        .hset(&key, &baseline.worker_id, payload)
+        .ignore()
+        .expire(&key, WARMUP_TTL_SECS)
+        .ignore()
+        .query_async(conn)
+        .await?;
     
-    match result {
-        Some((_key, payload)) => {
-            let job: JobRequest = serde_json::from_str(&payload)
+    Ok(())
+}
+
+/// Result of an identical earlier submission, if one is cached
+pub async fn get_cached_result(
+    conn: &mut redis::aio::ConnectionManager,
+    fingerprint: &str,
+) -> RedisResult<Option<crate::types::ExecutionResult>> {
+    let payload: Option<String> = conn.get(result_cache_key(fingerprint)).await?;
+    
+    match payload {
+        Some(data) => {
+            let result = serde_json::from_str(&data)
                 .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "deserialization error", e.to_string())))?;
-            Ok(Some(job))
+            Ok(Some(result))
         }
         None => Ok(None),
     }
 }
 
-/// Store execution result in Redis
-/// TTL is optional - set to 24 hours for now (can be configured later)
-/// 
-/// Also publishes metrics event for distributed tracking
-pub async fn store_result(
+/// Cache a judged result for RESULT_CACHE_TTL_SECS
+pub async fn store_cached_result(
     conn: &mut redis::aio::ConnectionManager,
+    fingerprint: &str,
     result: &crate::types::ExecutionResult,
 ) -> RedisResult<()> {
-    let key = result_key(&result.job_id);
     let payload = serde_json::to_string(result)
         .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "serialization error", e.to_string())))?;
+    conn.set_ex(result_cache_key(fingerprint), payload, RESULT_CACHE_TTL_SECS).await
+}
+
+/// Attach a timed-out test's diagnostic bundle to its job
+pub async fn store_diagnostics(
+    conn: &mut redis::aio::ConnectionManager,
+    job_id: &uuid::Uuid,
+    bundle: &crate::types::DiagnosticBundle,
+) -> RedisResult<()> {
+    let key = diagnostics_key(job_id);
+    let payload = serde_json::to_string(bundle)
+        .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "serialization error", e.to_string())))?;
     
-    // Store result with 24-hour TTL
-    let _: () = conn.set_ex(&key, payload, 86400).await?;
+    redis::pipe()
+        .hset(&key, bundle.test_id, payload).ignore()
+        .expire(&key, DIAGNOSTICS_TTL_SECS).ignore()
+        .query_async(conn)
+        .await
+}
+
+/// All diagnostic bundles captured for a job, ordered by test id
+pub async fn get_diagnostics(
+    conn: &mut redis::aio::ConnectionManager,
+    job_id: &uuid::Uuid,
+) -> RedisResult<Vec<crate::types::DiagnosticBundle>> {
+    let entries: std::collections::HashMap<String, String> = conn.hgetall(diagnostics_key(job_id)).await?;
     
-    // Also store status separately for quick lookup
-    let status_key_str = status_key(&result.job_id);
-    let status_str = serde_json::to_string(&result.overall_status)
+    let mut bundles = entries
+        .values()
+        .map(|data| serde_json::from_str::<crate::types::DiagnosticBundle>(data))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "deserialization error", e.to_string())))?;
+    bundles.sort_by_key(|bundle| bundle.test_id);
+    Ok(bundles)
+}
+
+/// Publish a worker's startup status (expires WORKER_STATUS_TTL_SECS after the last update)
+pub async fn set_worker_status(
+    conn: &mut redis::aio::ConnectionManager,
+    status: &crate::types::WorkerStatus,
+) -> RedisResult<()> {
+    let payload = serde_json::to_string(status)
         .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "serialization error", e.to_string())))?;
-    let _: () = conn.set_ex(&status_key_str, status_str, 86400).await?;
-    
-    Ok(())
+    conn.set_ex(worker_status_key(&status.worker_id), payload, WORKER_STATUS_TTL_SECS).await
 }
 
-/// Store execution result and publish completion metrics
-/// This is a convenience function that combines store_result with metrics publishing
-pub async fn store_result_with_metrics(
+/// Mark a worker as alive for its language and refresh its registration
+/// (called every HEARTBEAT_INTERVAL_SECS)
+/// Entries that went stale are pruned on the way; the registration expires
+/// with the heartbeat
+pub async fn record_worker_heartbeat(
     conn: &mut redis::aio::ConnectionManager,
-    result: &crate::types::ExecutionResult,
-    language: &crate::types::Language,
+    info: &crate::types::WorkerInfo,
 ) -> RedisResult<()> {
-    // Store the result first
-    store_result(conn, result).await?;
-    
-    // Publish metrics event
-    publish_job_completion(conn, result, language).await?;
-    
-    Ok(())
+    let payload = serde_json::to_string(info)
+        .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "serialization error", e.to_string())))?;
+    let key = workers_key(&info.language);
+    let now = chrono::Utc::now().timestamp();
+    redis::pipe()
+        .zadd(&key, &info.worker_id, now).ignore()
+        .zrembyscore(&key, "-inf", now - HEARTBEAT_STALE_SECS).ignore()
+        .expire(&key, HEARTBEAT_STALE_SECS * 2).ignore()
+        .set_ex(worker_key(&info.worker_id), payload, HEARTBEAT_STALE_SECS as u64).ignore()
+        .query_async(conn)
+        .await
 }
 
-/// Publish job completion metrics (for distributed metrics tracking)
-async fn publish_job_completion(
+/// Drop a worker's heartbeat and registration on clean shutdown
+pub async fn remove_worker_heartbeat(
     conn: &mut redis::aio::ConnectionManager,
-    result: &crate::types::ExecutionResult,
-    language: &crate::types::Language,
+    language: &Language,
+    worker_id: &str,
 ) -> RedisResult<()> {
-    // Calculate total execution time from test results
-    let total_execution_time_ms: u64 = result.results.iter()
-        .map(|r| r.execution_time_ms)
-        .sum();
+    redis::pipe()
+        .zrem(workers_key(language), worker_id).ignore()
+        .del(worker_key(worker_id)).ignore()
+        .query_async(conn)
+        .await
+}
+
+/// Registrations of a language's live workers, by worker id
+/// Workers whose registration is missing or unreadable are skipped
+pub async fn list_live_workers(
+    conn: &mut redis::aio::ConnectionManager,
+    language: &Language,
+) -> RedisResult<Vec<crate::types::WorkerInfo>> {
+    let since = chrono::Utc::now().timestamp() - HEARTBEAT_STALE_SECS;
+    let mut ids: Vec<String> = conn.zrangebyscore(workers_key(language), since, "+inf").await?;
+    if ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    ids.sort();
+    let keys: Vec<String> = ids.iter().map(|id| worker_key(id)).collect();
+    let payloads: Vec<Option<String>> = redis::cmd("MGET").arg(&keys).query_async(conn).await?;
+    Ok(payloads
+        .into_iter()
+        .flatten()
+        .filter_map(|payload| serde_json::from_str(&payload).ok())
+        .collect())
+}
+
+/// Workers with a heartbeat in the last HEARTBEAT_STALE_SECS
+pub async fn count_live_workers(
+    conn: &mut redis::aio::ConnectionManager,
+    language: &Language,
+) -> RedisResult<u64> {
+    let since = chrono::Utc::now().timestamp() - HEARTBEAT_STALE_SECS;
+    conn.zcount(workers_key(language), since, "+inf").await
+}
+
+/// Count a job accepted while its language had no workers
+pub async fn record_parked_job(
+    conn: &mut redis::aio::ConnectionManager,
+    language: &Language,
+) -> RedisResult<u64> {
+    conn.incr(parked_key(language), 1).await
+}
+
+/// Take the parked-job count for a language, resetting it
+/// Only one caller sees a non-zero count, so only one API replica announces recovery
+pub async fn take_parked_jobs(
+    conn: &mut redis::aio::ConnectionManager,
+    language: &Language,
+) -> RedisResult<u64> {
+    let count: Option<u64> = redis::cmd("GETDEL").arg(parked_key(language)).query_async(conn).await?;
+    Ok(count.unwrap_or(0))
+}
+
+/// Record one finished job in today's usage bucket for its language
+pub async fn record_language_usage(
+    conn: &mut redis::aio::ConnectionManager,
+    usage: &LanguageUsage,
+) -> RedisResult<()> {
+    let day = chrono::Utc::now().format("%Y-%m-%d").to_string();
+    let key = analytics_key(&day, &usage.language);
+    let source_key = analytics_samples_key(&day, &usage.language, "source_bytes");
+    let execution_key = analytics_samples_key(&day, &usage.language, "execution_ms");
     
-    let channel = format!("{}:completions", METRICS_PREFIX);
-    let event = serde_json::json!({
-        "job_id": result.job_id.to_string(),
-        "language": language.to_string(),
-        "status": format!("{:?}", result.overall_status),
-        "execution_time_ms": total_execution_time_ms,
-        "timestamp": chrono::Utc::now().to_rfc3339(),
-    });
+    let mut pipe = redis::pipe();
+    pipe.hincr(&key, "jobs", 1).ignore()
+        .hincr(&key, format!("version:{}", usage.version), 1).ignore()
+        .hincr(&key, format!("tier:{}", usage.tier), 1).ignore()
+        .expire(&key, ANALYTICS_TTL_SECS).ignore();
+    for (samples_key, value) in [(&source_key, usage.source_bytes), (&execution_key, usage.execution_ms)] {
+        pipe.lpush(samples_key, value).ignore()
+            .ltrim(samples_key, 0, ANALYTICS_MAX_SAMPLES - 1).ignore()
+            .expire(samples_key, ANALYTICS_TTL_SECS).ignore();
+    }
+    pipe.query_async(conn).await
+}
+
+/// Read one day of usage for a language (empty if nothing was recorded)
+pub async fn get_language_usage_day(
+    conn: &mut redis::aio::ConnectionManager,
+    day: &str,
+    language: &Language,
+) -> RedisResult<LanguageUsageDay> {
+    let (counts, source_bytes, execution_ms): (std::collections::HashMap<String, u64>, Vec<u64>, Vec<u64>) = redis::pipe()
+        .hgetall(analytics_key(day, language))
+        .lrange(analytics_samples_key(day, language, "source_bytes"), 0, -1)
+        .lrange(analytics_samples_key(day, language, "execution_ms"), 0, -1)
+        .query_async(conn)
+        .await?;
     
-    let payload = serde_json::to_string(&event)
+    let mut usage = LanguageUsageDay { source_bytes, execution_ms, ..Default::default() };
+    for (field, count) in counts {
+        if field == "jobs" {
+            usage.jobs = count;
+        } else if let Some(version) = field.strip_prefix("version:") {
+            usage.versions.insert(version.to_string(), count);
+        } else if let Some(tier) = field.strip_prefix("tier:") {
+            usage.tiers.insert(tier.to_string(), count);
+        }
+    }
+    Ok(usage)
+}
+
+/// Count one accepted submission in the current minute's bucket
+pub async fn record_submission(
+    conn: &mut redis::aio::ConnectionManager,
+    language: &Language,
+) -> RedisResult<()> {
+    let key = submissions_key(language, chrono::Utc::now().timestamp() / 60);
+    redis::pipe()
+        .incr(&key, 1).ignore()
+        .expire(&key, SUBMISSIONS_TTL_SECS).ignore()
+        .query_async(conn)
+        .await
+}
+
+/// Submission counts for `minutes` consecutive minutes starting at `from_minute`
+/// Oldest first; minutes with no submissions count as 0
+pub async fn get_submission_counts(
+    conn: &mut redis::aio::ConnectionManager,
+    language: &Language,
+    from_minute: i64,
+    minutes: usize,
+) -> RedisResult<Vec<u64>> {
+    if minutes == 0 {
+        return Ok(Vec::new());
+    }
+    let keys: Vec<String> = (0..minutes as i64)
+        .map(|offset| submissions_key(language, from_minute + offset))
+        .collect();
+    // MGET with a single key returns a scalar, so always go through a Vec
+    let counts: Vec<Option<u64>> = redis::cmd("MGET").arg(&keys).query_async(conn).await?;
+    Ok(counts.into_iter().map(|c| c.unwrap_or(0)).collect())
+}
+
+/// Publish a language's submission forecast for autoscalers
+pub async fn store_forecast(
+    conn: &mut redis::aio::ConnectionManager,
+    forecast: &SubmissionForecast,
+) -> RedisResult<()> {
+    let json = serde_json::to_string(forecast)
+        .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "serialization error", e.to_string())))?;
+    conn.set_ex(forecast_key(&forecast.language), json, FORECAST_TTL_SECS).await
+}
+
+/// Replace a job's grader feedback and append the change to its audit trail
+/// TTL of 24 hours to match result expiry
+pub async fn store_feedback(
+    conn: &mut redis::aio::ConnectionManager,
+    job_id: &uuid::Uuid,
+    feedback: &JobFeedback,
+    revision: &FeedbackRevision,
+) -> RedisResult<()> {
+    let current = JobFeedback { history: Vec::new(), ..feedback.clone() };
+    let payload = serde_json::to_string(&current)
+        .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "serialization error", e.to_string())))?;
+    let entry = serde_json::to_string(revision)
         .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "serialization error", e.to_string())))?;
     
-    // Publish event (fire-and-forget, no subscribers required)
-    let _: i64 = conn.publish(&channel, payload).await.unwrap_or(0);
+    let audit_key = feedback_audit_key(job_id);
+    let _: () = redis::pipe()
+        .atomic()
+        .set_ex(feedback_key(job_id), payload, 86400)
+        .ignore()
+        .rpush(&audit_key, entry)
+        .ignore()
+        .expire(&audit_key, 86400)
+        .ignore()
+        .query_async(conn)
+        .await?;
+    
     Ok(())
 }
 
-/// Retrieve execution result from Redis
-pub async fn get_result(
+/// Fetch a job's grader feedback with its full audit trail
+pub async fn get_feedback(
     conn: &mut redis::aio::ConnectionManager,
     job_id: &uuid::Uuid,
-) -> RedisResult<Option<crate::types::ExecutionResult>> {
-    let key = result_key(job_id);
-    let payload: Option<String> = conn.get(&key).await?;
+) -> RedisResult<Option<JobFeedback>> {
+    let (payload, entries): (Option<String>, Vec<String>) = redis::pipe()
+        .get(feedback_key(job_id))
+        .lrange(feedback_audit_key(job_id), 0, -1)
+        .query_async(conn)
+        .await?;
     
-    match payload {
-        Some(data) => {
-            let result: crate::types::ExecutionResult = serde_json::from_str(&data)
-                .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "deserialization error", e.to_string())))?;
-            Ok(Some(result))
-        }
-        None => Ok(None),
-    }
+    let Some(data) = payload else {
+        return Ok(None);
+    };
+    let mut feedback: JobFeedback = serde_json::from_str(&data)
+        .map_err(|e| redis::RedisError::from((redis::ErrorKind::TypeError, "deserialization error", e.to_string())))?;
+    feedback.history = entries
+        .iter()
+        .filter_map(|entry| serde_json::from_str(entry).ok())
+        .collect();
+    
+    Ok(Some(feedback))
 }
 
 /// Set cancellation flag for a job
@@ -257,6 +2438,108 @@ mod tests {
         
         assert_eq!(dlq_name(&Language::Python), "optimus:queue:python:dlq");
         assert_eq!(dlq_name(&Language::Rust), "optimus:queue:rust:dlq");
+        
+        let queues = job_queues(&Language::Java);
+        assert_eq!(queues.len(), 5);
+        assert_eq!(queues.last().unwrap(), "optimus:queue:java:dlq");
+        
+        assert_eq!(dry_run_queue_name(&Language::Java), "optimus:queue:java:dryrun");
+        
+        assert_eq!(priority_queue_name(&Language::Python, Priority::Normal), "optimus:queue:python");
+        assert_eq!(priority_queue_name(&Language::Python, Priority::High), "optimus:queue:python:high");
+        assert_eq!(priority_queue_name(&Language::Python, Priority::Low), "optimus:queue:python:low");
+        assert_eq!(
+            dequeue_order(&Language::Rust),
+            [
+                "optimus:queue:rust:high",
+                "optimus:queue:rust",
+                "optimus:queue:rust:retry",
+                "optimus:queue:rust:low",
+            ]
+        );
+        assert_eq!(global_slots_key(&Language::Rust), "optimus:concurrency:rust");
+        assert_eq!(rate_limit_key("key", "alice"), "optimus:ratelimit:key:alice");
+        assert_eq!(idempotency_key(None, "k1"), "optimus:idempotency:k1");
+        assert_eq!(idempotency_key(Some("alice"), "k1"), "optimus:idempotency:alice:k1");
+        assert_eq!(language_config_key(), "optimus:config:languages");
+        assert_eq!(warmup_key(&Language::Java), "optimus:warmup:java");
+    }
+
+    #[test]
+    fn test_pool_queue_naming() {
+        assert_eq!(pool_queue_name(&Language::Java, None), queue_name(&Language::Java));
+        assert_eq!(pool_dequeue_order(&Language::Rust, None), dequeue_order(&Language::Rust));
+        assert_eq!(
+            pool_dequeue_order(&Language::Rust, Some("arch-arm64")),
+            [
+                "optimus:queue:rust@arch-arm64:high",
+                "optimus:queue:rust@arch-arm64",
+                "optimus:queue:rust@arch-arm64:retry",
+                "optimus:queue:rust@arch-arm64:low",
+            ]
+        );
+        assert_eq!(pools_key(&Language::Rust), "optimus:pools:rust");
+    }
+
+    #[test]
+    fn test_problem_key_format() {
+        assert_eq!(problem_key("two-sum"), "optimus:problem:two-sum");
+        assert_eq!(problem_assets_key("two-sum"), "optimus:problem_assets:two-sum");
+    }
+
+    #[test]
+    fn test_artifact_key_format() {
+        assert_eq!(artifact_key("ab12"), "optimus:artifact:ab12");
+    }
+
+    #[test]
+    fn test_submission_forecast_key_format() {
+        assert_eq!(submissions_key(&Language::Java, 29_000_000), "optimus:submissions:java:29000000");
+        assert_eq!(forecast_key(&Language::Python), "optimus:forecast:python");
+    }
+
+    #[test]
+    fn test_analytics_key_format() {
+        assert_eq!(analytics_key("2026-10-16", &Language::Python), "optimus:analytics:2026-10-16:python");
+        assert_eq!(
+            analytics_samples_key("2026-10-16", &Language::Rust, "execution_ms"),
+            "optimus:analytics:2026-10-16:rust:execution_ms"
+        );
+    }
+
+    #[test]
+    fn test_feedback_key_format() {
+        let id = Uuid::new_v4();
+        assert_eq!(feedback_key(&id), format!("optimus:feedback:{}", id));
+        assert_eq!(feedback_audit_key(&id), format!("optimus:feedback:{}:audit", id));
+    }
+
+    #[test]
+    fn test_batch_key_format() {
+        let id = Uuid::new_v4();
+        assert_eq!(batch_key(&id), format!("optimus:batch:{}", id));
+        assert_eq!(batch_submitted_key(&id), format!("optimus:batch:{}:submitted", id));
+        assert_eq!(batch_finished_key(&id), format!("optimus:batch:{}:finished", id));
+        assert_eq!(batch_completion_key(&id), format!("optimus:batch:{}:completion", id));
+        assert_eq!(batch_events_channel(&id), format!("optimus:events:batch:{}", id));
+        assert_eq!(job_events_channel(&id), format!("optimus:events:job:{}", id));
+    }
+
+    #[test]
+    fn test_result_cache_key_format() {
+        assert_eq!(result_cache_key("ab12"), "optimus:resultcache:ab12");
+    }
+
+    #[test]
+    fn test_diagnostics_key_format() {
+        let job_id = uuid::Uuid::nil();
+        assert_eq!(diagnostics_key(&job_id), format!("optimus:diagnostics:{}", job_id));
+    }
+
+    #[test]
+    fn test_worker_presence_key_format() {
+        assert_eq!(workers_key(&Language::Rust), "optimus:workers:rust");
+        assert_eq!(parked_key(&Language::Rust), "optimus:parked:rust");
     }
 
     #[test]
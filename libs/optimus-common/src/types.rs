@@ -1,10 +1,12 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 use uuid::Uuid;
 
 /// Strongly-typed language enum
 /// Start strict - will extend dynamically later
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum Language {
     Python,
@@ -12,6 +14,28 @@ pub enum Language {
     Rust,
 }
 
+/// Job Priority
+/// Selects which per-language queue a job is pushed to.
+/// Interactive submissions use High to jump ahead of bulk rejudge traffic (Low).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    Low,
+    #[default]
+    Normal,
+    High,
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Priority::Low => write!(f, "low"),
+            Priority::Normal => write!(f, "normal"),
+            Priority::High => write!(f, "high"),
+        }
+    }
+}
+
 /// Job Metadata for Retry and Failure Handling
 /// Tracks retry attempts and failure information
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,6 +44,15 @@ pub struct JobMetadata {
     pub max_attempts: u8,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub last_failure_reason: Option<String>,
+    /// RFC 3339 time the API accepted the job (kept across retries)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub submitted_at: Option<String>,
+    /// Notes attached by the API's validation plugins (e.g. course or cohort)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub annotations: BTreeMap<String, String>,
+    /// Queue timeline, appended by the Redis queue helpers
+    #[serde(default, skip_serializing_if = "HopLog::is_empty")]
+    pub hops: HopLog,
 }
 
 impl Default for JobMetadata {
@@ -28,32 +61,503 @@ impl Default for JobMetadata {
             attempts: 0,
             max_attempts: 3,
             last_failure_reason: None,
+            submitted_at: None,
+            annotations: BTreeMap::new(),
+            hops: HopLog::default(),
+        }
+    }
+}
+
+/// Queue-Level Timeline of a Job
+/// Every queue transition stamps an RFC 3339 time here, so a job's path
+/// through the queues can be read without digging through API and worker logs
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct HopLog {
+    /// When the API queued the job
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enqueued_at: Option<String>,
+    /// When a delayed job (spread batch) is released to its queue
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub release_at: Option<String>,
+    /// When a worker first dequeued each attempt (index 0 is the first attempt)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub first_dequeue_at: Vec<String>,
+    /// When each failed attempt went back on the retry queue
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub retry_enqueued_at: Vec<String>,
+    /// When the job was dead-lettered
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dlq_at: Option<String>,
+}
+
+impl HopLog {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Record a dequeue of attempt `attempt` (0-based); later dequeues of
+    /// an attempt that already has one are ignored
+    pub fn record_dequeue(&mut self, attempt: u8, at: String) {
+        if self.first_dequeue_at.len() <= attempt as usize {
+            self.first_dequeue_at.push(at);
         }
     }
 }
 
 /// Job Cancellation Control
 /// Tracks cancellation state for cooperative shutdown
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct JobControl {
     pub cancelled: bool,
 }
 
-impl Default for JobControl {
-    fn default() -> Self {
-        Self { cancelled: false }
+/// Fixture File (Immutable Input)
+/// A small file written into the container working directory (/code)
+/// before the test runs. Exactly one of `content` / `content_base64` is set.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+pub struct FixtureFile {
+    /// Relative path inside the working directory (e.g. "data/input.csv")
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_base64: Option<String>,
+}
+
+impl FixtureFile {
+    /// Validate the fixture and return its decoded bytes
+    ///
+    /// Rejects absolute paths, `..` components, and ambiguous content so a
+    /// fixture can never escape the working directory.
+    pub fn validate(&self) -> Result<Vec<u8>, String> {
+        let name = self.name.as_str();
+        if name.is_empty() || name.len() > 255 {
+            return Err("fixture name must be 1-255 characters".to_string());
+        }
+        if name.starts_with('/') || name.contains('\\') || name.contains('\0') {
+            return Err(format!("fixture name '{}' must be a relative path", name));
+        }
+        if name.split('/').any(|part| part.is_empty() || part == "." || part == "..") {
+            return Err(format!("fixture name '{}' contains an invalid path component", name));
+        }
+        
+        match (&self.content, &self.content_base64) {
+            (Some(text), None) => Ok(text.as_bytes().to_vec()),
+            (None, Some(encoded)) => {
+                use base64::Engine as _;
+                base64::engine::general_purpose::STANDARD
+                    .decode(encoded)
+                    .map_err(|e| format!("fixture '{}' has invalid base64: {}", name, e))
+            }
+            _ => Err(format!(
+                "fixture '{}' must set exactly one of content or content_base64",
+                name
+            )),
+        }
+    }
+}
+
+/// Project Archive Format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum ArchiveFormat {
+    #[serde(rename = "zip")]
+    Zip,
+    #[serde(rename = "tar.gz")]
+    TarGz,
+}
+
+/// Project Archive (Immutable Input)
+/// A multi-file project unpacked into the working directory (/code) in
+/// place of the single source file. `entry_command` runs from /code with
+/// the test input on stdin; the language's `project_command` is used when
+/// it is omitted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ProjectArchive {
+    pub format: ArchiveFormat,
+    pub content_base64: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entry_command: Option<String>,
+}
+
+/// Time-Based Scoring (Optional)
+/// Awards bonus points to passing tests for speed:
+/// full `bonus_weight` at or under `full_bonus_ms`, decaying linearly to
+/// zero at `zero_bonus_ms`. Failing tests never earn a bonus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct TimeScoring {
+    pub full_bonus_ms: u64,
+    pub zero_bonus_ms: u64,
+    pub bonus_weight: u32,
+}
+
+impl TimeScoring {
+    /// Check thresholds are ordered and a bonus is actually possible
+    pub fn validate(&self) -> Result<(), String> {
+        if self.full_bonus_ms >= self.zero_bonus_ms {
+            return Err(format!(
+                "full_bonus_ms ({}) must be less than zero_bonus_ms ({})",
+                self.full_bonus_ms, self.zero_bonus_ms
+            ));
+        }
+        if self.bonus_weight == 0 {
+            return Err("bonus_weight must be greater than 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Job Scoring Policy
+/// `weighted`: partial credit, each passed test earns its weight.
+/// `all_or_nothing`: `max_score` only when every test passes, 0 otherwise
+/// (ICPC-style judging).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoringPolicy {
+    #[default]
+    Weighted,
+    AllOrNothing,
+}
+
+impl ScoringPolicy {
+    pub fn is_weighted(&self) -> bool {
+        *self == ScoringPolicy::Weighted
+    }
+}
+
+/// Time Limit Mode
+/// Which clock `timeout_ms` limits. `cpu` judges on CPU time measured from
+/// the container's cgroup, so a program waiting on sleep or I/O isn't timed
+/// out while a multi-threaded spinner is; the wall clock still gets a
+/// generous backstop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeLimitMode {
+    #[default]
+    Wall,
+    Cpu,
+}
+
+impl TimeLimitMode {
+    pub fn is_wall(&self) -> bool {
+        *self == TimeLimitMode::Wall
+    }
+}
+
+/// Placement constraints: where a job must run
+///
+/// A job with constraints is routed to the queues of the worker pool serving
+/// exactly that combination (see `pool`), e.g. ARM-only or regional workers.
+/// Pools are declared per language in languages.json (`placements`).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Placement {
+    /// Region or zone label, e.g. "eu-west-1"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    /// CPU architecture, e.g. "arm64"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arch: Option<String>,
+}
+
+impl Placement {
+    const MAX_VALUE_LEN: usize = 32;
+
+    /// No constraints: any default worker may run the job
+    pub fn is_any(&self) -> bool {
+        self.region.is_none() && self.arch.is_none()
+    }
+
+    /// Values are lowercase labels (`[a-z0-9-]`, at most 32 characters)
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, value) in self.constraints() {
+            let valid = !value.is_empty()
+                && value.len() <= Self::MAX_VALUE_LEN
+                && value.bytes().all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-');
+            if !valid {
+                return Err(format!(
+                    "{} must be 1-{} characters of a-z, 0-9 and '-'",
+                    name,
+                    Self::MAX_VALUE_LEN
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Worker pool serving this placement, e.g. "arch-arm64.region-eu-west-1"
+    /// (None for the default pool); part of the pool's queue names
+    pub fn pool(&self) -> Option<String> {
+        if self.is_any() {
+            return None;
+        }
+        let parts: Vec<String> = self
+            .constraints()
+            .map(|(name, value)| format!("{}-{}", name, value))
+            .collect();
+        Some(parts.join("."))
     }
+
+    /// Set constraints as (name, value), in pool-name order
+    fn constraints(&self) -> impl Iterator<Item = (&'static str, &str)> {
+        [("arch", &self.arch), ("region", &self.region)]
+            .into_iter()
+            .filter_map(|(name, value)| Some((name, value.as_deref()?)))
+    }
+}
+
+/// Problem Definition (Server-Side, Write-Once)
+/// Submissions that reference a problem run under its limits, whatever the
+/// request itself asks for, so clients can't loosen constraints
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Problem {
+    pub id: String,
+    pub limits: ProblemLimits,
+    /// Hidden tests run for submissions that send no `test_cases`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub test_suite: Option<TestSuiteRef>,
+    /// RFC 3339 creation time
+    pub created_at: String,
+}
+
+/// Test suite assembled from a finalized chunked upload (`POST /uploads`)
+/// The JSON array of test cases lives in object storage; jobs that send no
+/// `test_cases` of their own are filled from it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct TestSuiteRef {
+    /// Upload the suite was assembled from
+    pub upload_id: Uuid,
+    pub test_cases: u32,
+    pub size: u64,
+    /// Hex SHA-256 of the suite JSON
+    pub sha256: String,
+}
+
+/// Chunked upload session for test suites above the request body limit
+/// Chunks are appended in order at `received_bytes`, so an interrupted
+/// upload resumes from wherever the session says it stopped
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct UploadSession {
+    pub id: Uuid,
+    /// Declared size of the complete upload
+    pub total_bytes: u64,
+    /// Bytes stored so far - the offset the next chunk must start at
+    pub received_bytes: u64,
+    /// Hex SHA-256 of each stored chunk, in order (chunks are stored by digest)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub chunks: Vec<String>,
+    /// Hex SHA-256 the complete upload must match, checked on finalize
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    /// Token subject that started the upload (only it may use it)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    /// RFC 3339 creation time
+    pub created_at: String,
+    /// Set once the upload is finalized
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub test_suite: Option<TestSuiteRef>,
+}
+
+/// Static file attached to a problem (statement, sample data), served at
+/// `GET /problems/{id}/assets/{name}`; the bytes live in object storage
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ProblemAsset {
+    pub name: String,
+    pub content_type: String,
+    pub size: u64,
+    /// Hex SHA-256 of the content (served as the ETag)
+    pub sha256: String,
+    /// RFC 3339 upload time
+    pub uploaded_at: String,
+}
+
+/// Limits a problem imposes on every submission
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ProblemLimits {
+    /// Per-test timeout
+    pub timeout_ms: u64,
+    /// Container memory limit (language default when absent)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_limit_mb: Option<u32>,
+    /// Container CPU cores, replacing the request's `cpu_limit` (language
+    /// default when absent)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_limit: Option<f64>,
+    /// Comparison applied to every test, replacing per-test settings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comparison: Option<Comparison>,
+    /// Whether presentation errors pass, replacing the request's setting
+    /// (language default when absent)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accept_presentation_errors: Option<bool>,
+    /// What `timeout_ms` limits, replacing the request's setting (default:
+    /// wall time, since `cpu` stretches the wall-clock backstop)
+    #[serde(default, skip_serializing_if = "TimeLimitMode::is_wall")]
+    pub time_limit_mode: TimeLimitMode,
+    /// Submissions need a GPU (CUDA); only languages with `gpu` configured accept them
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub gpu: bool,
+    /// Test concurrency forced on submissions (1 for problems that must run sequentially)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_parallel_tests: Option<u32>,
 }
 
 /// Test Case Definition (Immutable Input)
 /// Test cases are immutable - workers must not mutate them
 /// Ordering matters - execution is sequential
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct TestCase {
     pub id: u32,
     pub input: String,
     pub expected_output: String,
+    /// Additional acceptable outputs - the test passes if any one matches
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub expected_outputs: Vec<String>,
     pub weight: u32, // for scoring
+    /// Files written into the working directory before execution
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fixtures: Vec<FixtureFile>,
+    /// Command-line arguments passed to the program
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    /// Extra environment variables for the program
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub env: HashMap<String, String>,
+    /// How stdout is compared against the expected output (default: exact)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comparison: Option<Comparison>,
+    /// Compare letters case-insensitively (applied after trimming)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub ignore_case: bool,
+    /// Whether the expected outputs are literal text or regex patterns
+    #[serde(default, skip_serializing_if = "ExpectedKind::is_literal")]
+    pub expected_kind: ExpectedKind,
+}
+
+/// How `expected_output` / `expected_outputs` are interpreted
+/// `regex` patterns must match the whole trimmed stdout; `comparison` is
+/// ignored for them, `ignore_case` still applies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ExpectedKind {
+    #[default]
+    Literal,
+    Regex,
+}
+
+impl ExpectedKind {
+    pub fn is_literal(&self) -> bool {
+        *self == ExpectedKind::Literal
+    }
+}
+
+/// Upper bound on a compiled expected-output regex (the regex crate's own
+/// size limit, so huge repetition counts fail to compile instead of eating memory)
+pub const MAX_EXPECTED_REGEX_SIZE: usize = 1024 * 1024;
+
+/// Compile an expected-output pattern, anchored to match the whole output
+pub fn compile_expected_regex(pattern: &str, ignore_case: bool) -> Result<regex::Regex, regex::Error> {
+    regex::RegexBuilder::new(&format!("^(?:{})$", pattern.trim()))
+        .case_insensitive(ignore_case)
+        .size_limit(MAX_EXPECTED_REGEX_SIZE)
+        .build()
+}
+
+/// Output Comparison Mode
+/// `exact`: trimmed string equality. `float`: outputs are split on whitespace
+/// and token counts must match; numeric token pairs pass when within
+/// `abs_tol` or `rel_tol` (relative to the larger magnitude), all other
+/// tokens must match exactly.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "mode", rename_all = "lowercase")]
+pub enum Comparison {
+    #[default]
+    Exact,
+    Float {
+        #[serde(default)]
+        abs_tol: f64,
+        #[serde(default)]
+        rel_tol: f64,
+    },
+}
+
+impl Comparison {
+    pub fn validate(&self) -> Result<(), String> {
+        match *self {
+            Comparison::Exact => Ok(()),
+            Comparison::Float { abs_tol, rel_tol } => {
+                if !(abs_tol.is_finite() && abs_tol >= 0.0 && rel_tol.is_finite() && rel_tol >= 0.0) {
+                    return Err("abs_tol and rel_tol must be finite and non-negative".to_string());
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Environment variables the runner reads itself - tests may not set them
+pub const RESERVED_ENV_VARS: &[&str] = &[
+    "SOURCE_CODE",
+    "TEST_INPUT",
+    "LANGUAGE",
+    "ENTRY_COMMAND",
+    "BUILD_ONLY",
+    "PRECOMPILED",
+    // GPU visibility is decided by the worker (see the language's `gpu` settings)
+    "NVIDIA_VISIBLE_DEVICES",
+    "NVIDIA_DRIVER_CAPABILITIES",
+];
+/// Largest test case input (stdin) the API accepts and workers stream to the program
+pub const MAX_TEST_INPUT_BYTES: usize = 10 * 1024 * 1024;
+pub const MAX_TEST_ARGS: usize = 64;
+pub const MAX_TEST_ENV_VARS: usize = 64;
+/// Combined size of all args, env names and env values of one test
+pub const MAX_TEST_ARGS_ENV_BYTES: usize = 64 * 1024;
+
+/// Validate per-test program arguments and environment variables
+///
+/// Env names must be plain identifiers (`[A-Za-z_][A-Za-z0-9_]*`) and may
+/// not shadow the runner's own variables; nothing may contain NUL bytes.
+pub fn validate_args_env(args: &[String], env: &HashMap<String, String>) -> Result<(), String> {
+    if args.len() > MAX_TEST_ARGS {
+        return Err(format!("at most {} args allowed", MAX_TEST_ARGS));
+    }
+    if env.len() > MAX_TEST_ENV_VARS {
+        return Err(format!("at most {} env variables allowed", MAX_TEST_ENV_VARS));
+    }
+    if args.iter().any(|arg| arg.contains('\0')) {
+        return Err("args must not contain NUL bytes".to_string());
+    }
+
+    for (name, value) in env {
+        let mut chars = name.chars();
+        let valid_name = chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid_name {
+            return Err(format!("env name '{}' is not a valid identifier", name));
+        }
+        if RESERVED_ENV_VARS.contains(&name.as_str()) {
+            return Err(format!("env name '{}' is reserved", name));
+        }
+        if value.contains('\0') {
+            return Err(format!("env value for '{}' must not contain NUL bytes", name));
+        }
+    }
+
+    let total: usize = args.iter().map(String::len).sum::<usize>()
+        + env.iter().map(|(k, v)| k.len() + v.len()).sum::<usize>();
+    if total > MAX_TEST_ARGS_ENV_BYTES {
+        return Err(format!("args and env exceed {} bytes", MAX_TEST_ARGS_ENV_BYTES));
+    }
+    Ok(())
+}
+
+impl TestCase {
+    /// Every output this test accepts: `expected_output` first, then the alternatives
+    pub fn accepted_outputs(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.expected_output.as_str())
+            .chain(self.expected_outputs.iter().map(String::as_str))
+    }
 }
 
 impl Language {
@@ -64,7 +568,14 @@ impl Language {
         &[Language::Python, Language::Java, Language::Rust]
     }
     
+    /// Whether the runner compiles submissions before running them
+    /// (their build can be cached and reused across tests)
+    pub fn is_compiled(&self) -> bool {
+        matches!(self, Language::Java | Language::Rust)
+    }
+    
     /// Parse a language from string (case-insensitive)
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<Language> {
         match s.to_lowercase().as_str() {
             "python" => Some(Language::Python),
@@ -102,12 +613,60 @@ pub struct JobRequest {
     pub timeout_ms: u64,
     #[serde(default)]
     pub metadata: JobMetadata,
+    #[serde(default)]
+    pub priority: Priority,
+    /// Batch this job belongs to (drives the batch-completion event)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub batch_id: Option<Uuid>,
+    /// Optional speed bonus applied by the evaluator
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_scoring: Option<TimeScoring>,
+    /// Clock that `timeout_ms` applies to (default: wall)
+    #[serde(default, skip_serializing_if = "TimeLimitMode::is_wall")]
+    pub time_limit_mode: TimeLimitMode,
+    /// Multi-file project submission (replaces `source_code`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archive: Option<ProjectArchive>,
+    /// Score presentation errors as passes instead of reporting them
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub accept_presentation_errors: bool,
+    /// How passed tests turn into a score (default: weighted partial credit)
+    #[serde(default, skip_serializing_if = "ScoringPolicy::is_weighted")]
+    pub scoring: ScoringPolicy,
+    /// Server-side problem whose limits were applied to this job
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub problem_id: Option<String>,
+    /// Container memory limit overriding the language default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_limit_mb: Option<u32>,
+    /// Container CPU limit (cores) overriding the language default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_limit: Option<f64>,
+    /// Result cache entry the worker fills on completion (opt-in `cache` submissions)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_fingerprint: Option<String>,
+    /// Worker pool constraints (default: any worker of the language)
+    #[serde(default, skip_serializing_if = "Placement::is_any")]
+    pub placement: Placement,
+    /// Attach GPU devices to this job's containers (requested or set by its problem)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub gpu: bool,
+    /// Test containers run at once, capped by the worker's MAX_PARALLEL_TESTS (1 = sequential)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_parallel_tests: Option<u32>,
+}
+
+impl JobRequest {
+    /// Worker pool this job is queued for (None for the default pool)
+    pub fn pool(&self) -> Option<String> {
+        self.placement.pool()
+    }
 }
 
 /// Job State Machine
 /// Explicitly models lifecycle states
 /// Backs: GET /job/{id}, retry logic, metrics
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum JobStatus {
     Queued,
@@ -118,43 +677,579 @@ pub enum JobStatus {
     Cancelled,
 }
 
+/// Live progress of a running job, kept in `optimus:status:{job_id}` until
+/// the result's final status replaces it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct JobProgress {
+    /// Always `running`
+    pub state: JobStatus,
+    /// Tests with an output so far (duplicates count with the test they reuse)
+    pub tests_completed: usize,
+    pub tests_total: usize,
+    /// RFC 3339 start of the most recently started test
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current_test_started_at: Option<String>,
+}
+
 /// Per-Test Status
 /// Distinguishes different failure modes for individual test cases
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum TestStatus {
     Passed,
     Failed,
     RuntimeError,
     TimeLimitExceeded,
+    /// Killed by the kernel OOM killer for exceeding the container memory limit
+    MemoryLimitExceeded,
+    /// Output is right once trailing whitespace and blank lines are ignored
+    PresentationError,
+    /// Submission failed to compile
+    CompileError,
+    /// The runner itself failed (bad environment, unsupported language)
+    InternalError,
 }
 
 /// Per-Test Result
 /// Captures individual test case execution outcome
 /// Enables partial success and detailed feedback
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct TestResult {
     pub test_id: u32,
     pub status: TestStatus,
     pub stdout: String,
     pub stderr: String,
     pub execution_time_ms: u64,
+    /// True when this result was fanned out from an identical earlier test
+    /// instead of running in its own container
+    #[serde(default)]
+    pub deduplicated: bool,
+    /// Speed bonus earned by this test (0 without time-based scoring)
+    #[serde(default)]
+    pub time_bonus: u32,
+    /// Unified diff of expected vs actual output, set on output mismatches
+    /// (first 50 changed lines)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff: Option<String>,
+    /// Sandbox escape heuristics that fired for this test (e.g.
+    /// `write_outside_workdir: /etc/passwd`); informational, verdict unchanged
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sandbox_flags: Vec<String>,
+    /// `stdout` was cut off at the worker's capture limit
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub stdout_truncated: bool,
+    /// `stderr` was cut off at the worker's capture limit
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub stderr_truncated: bool,
+    /// CPU time (user + system) the program used, from the container's cgroup
+    /// Absent when it couldn't be measured (e.g. killed on timeout)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_time_ms: Option<u64>,
+    /// Container exit code; 128+N means killed by signal N (137 SIGKILL,
+    /// 139 SIGSEGV). Absent when the test was killed on timeout or never ran
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i64>,
+    /// One-line explanation of the verdict for display, e.g. "Wrong answer on
+    /// test 3: expected 42, got 41 at line 1"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
 }
 
 /// Execution Output
 /// Written by workers, read by API, stored in Redis/object storage
 /// 
 /// ## Scoring Semantics:
-/// - score: sum of weights for passed tests
-/// - max_score: sum of all test case weights
+/// - score: sum of weights for passed tests, plus any time bonus
+/// - max_score: sum of all test case weights, plus the maximum time bonus
 /// - overall_status: Completed if all tests passed, Failed otherwise
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct ExecutionResult {
     pub job_id: Uuid,
     pub overall_status: JobStatus,
     pub score: u32,
     pub max_score: u32,
     pub results: Vec<TestResult>,
+    /// Human-readable time bonus formula, present when time-based scoring was used
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scoring_formula: Option<String>,
+    /// Why the job ended without running (set for operator force-completes)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// Manual grader review, attached when the result is read
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub feedback: Option<JobFeedback>,
+    /// Per-test timing statistics and end-to-end latency (set by the worker)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timing_summary: Option<TimingSummary>,
+    /// Some test tripped a sandbox escape heuristic - review `sandbox_flags`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub suspicious: bool,
+    /// Copied from an identical earlier submission instead of executed
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub cached: bool,
+    /// One-line explanation of the overall verdict for display, e.g. "Time
+    /// limit exceeded on 4/10 tests" (set by the evaluator)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verdict_summary: Option<String>,
+}
+
+/// Timing Summary
+/// Statistics over the per-test execution times plus queue wait and worker
+/// wall time, so clients don't recompute them from `results`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct TimingSummary {
+    /// Sum of per-test execution times
+    pub total_ms: u64,
+    pub min_ms: u64,
+    pub max_ms: u64,
+    pub p50_ms: u64,
+    pub p95_ms: u64,
+    /// Submission to worker pickup (includes earlier attempts of a retried job)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub queue_wait_ms: Option<u64>,
+    /// Worker time for the whole job, including container setup and evaluation
+    pub wall_time_ms: u64,
+    /// Worker time per phase (permit_wait, image_check, container_create,
+    /// execute, evaluate), summed over the job's containers. `persist` is
+    /// not included: it is measured while this result is being written
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub phases_ms: BTreeMap<String, u64>,
+}
+
+/// Result Summary
+/// Per-status test counts and the score - enough for dashboards without
+/// shipping every test's stdout/stderr
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ResultSummary {
+    pub total_tests: u32,
+    pub passed: u32,
+    pub failed: u32,
+    pub runtime_error: u32,
+    pub time_limit_exceeded: u32,
+    #[serde(default)]
+    pub memory_limit_exceeded: u32,
+    pub presentation_error: u32,
+    pub compile_error: u32,
+    pub internal_error: u32,
+    pub score: u32,
+    pub max_score: u32,
+}
+
+impl ExecutionResult {
+    /// Whether an identical submission may reuse this result
+    /// Only judged outcomes qualify - infrastructure failures must be retried
+    pub fn is_cacheable(&self) -> bool {
+        matches!(self.overall_status, JobStatus::Completed | JobStatus::Failed)
+            && self.reason.is_none()
+            && !self.results.is_empty()
+            && self.results.iter().all(|r| r.status != TestStatus::InternalError)
+    }
+
+    pub fn summary(&self) -> ResultSummary {
+        let mut summary = ResultSummary {
+            total_tests: self.results.len() as u32,
+            score: self.score,
+            max_score: self.max_score,
+            ..Default::default()
+        };
+        for result in &self.results {
+            match result.status {
+                TestStatus::Passed => summary.passed += 1,
+                TestStatus::Failed => summary.failed += 1,
+                TestStatus::RuntimeError => summary.runtime_error += 1,
+                TestStatus::TimeLimitExceeded => summary.time_limit_exceeded += 1,
+                TestStatus::MemoryLimitExceeded => summary.memory_limit_exceeded += 1,
+                TestStatus::PresentationError => summary.presentation_error += 1,
+                TestStatus::CompileError => summary.compile_error += 1,
+                TestStatus::InternalError => summary.internal_error += 1,
+            }
+        }
+        summary
+    }
+}
+
+/// Grader Feedback (Manual Review)
+/// Comments and a score adjustment layered on top of automatic judging.
+/// `score` on the result is never rewritten - `adjusted_score` carries the
+/// reviewed value.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct JobFeedback {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    /// Points added to (or, if negative, removed from) the automatic score
+    pub score_adjustment: i64,
+    pub adjusted_score: u32,
+    /// Token subject of the grader who made the latest change
+    pub updated_by: String,
+    pub updated_at: String,
+    /// Every change, oldest first
+    #[serde(default)]
+    pub history: Vec<FeedbackRevision>,
+}
+
+/// One entry in the feedback audit trail
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct FeedbackRevision {
+    pub grader: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    pub score_adjustment: i64,
+    pub at: String,
+}
+
+/// Idempotency Record
+/// Stored under a client's `Idempotency-Key` so retried submissions map
+/// back to the job they originally created
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IdempotencyRecord {
+    pub job_id: Uuid,
+    /// SHA-256 of the submitted payload - detects key reuse with a different body
+    pub payload_hash: String,
+    pub created_at: String,
+}
+
+/// Batch Definition
+/// Groups many jobs (e.g. one assignment run) so integrations receive a
+/// single completion event instead of one callback per job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchRecord {
+    pub id: Uuid,
+    /// Number of jobs the caller will submit into this batch
+    pub expected_jobs: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook_url: Option<String>,
+    /// Shape of the webhook body (SSE and `GET /batches/{id}` always get the summary)
+    #[serde(default, skip_serializing_if = "WebhookPayload::is_summary")]
+    pub webhook_payload: WebhookPayload,
+    /// Token subject that created the batch (only it may add jobs)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    /// Tests for jobs in the batch that send no `test_cases`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub test_suite: Option<TestSuiteRef>,
+    /// Window (from `created_at`) over which the batch's jobs are released
+    /// to the queue, evenly by submission order
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub spread_ms: u64,
+    /// Random extra delay of up to this much per job
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub jitter_ms: u64,
+    pub created_at: String,
+}
+
+fn is_zero(value: &u64) -> bool {
+    *value == 0
+}
+
+impl BatchRecord {
+    /// Delay after `created_at` before the `slot`th (0-based) job of the batch
+    /// is queued; `random` picks the jitter
+    pub fn release_offset_ms(&self, slot: u32, random: u64) -> u64 {
+        let spread = self.spread_ms * u64::from(slot.min(self.expected_jobs)) / u64::from(self.expected_jobs.max(1));
+        spread + random % (self.jitter_ms + 1)
+    }
+}
+
+/// Per-job line in a batch completion event
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BatchJobSummary {
+    pub job_id: Uuid,
+    pub status: JobStatus,
+    pub score: u32,
+    pub max_score: u32,
+}
+
+/// Batch Completion Event
+/// Emitted exactly once, when the last job of a batch reaches a terminal state
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BatchCompletion {
+    pub batch_id: Uuid,
+    pub total_jobs: u32,
+    pub completed_at: String,
+    pub jobs: Vec<BatchJobSummary>,
+}
+
+/// Webhook Payload Template
+/// Chosen per batch so lightweight consumers don't receive every test's output
+/// - `summary` (default): the `BatchCompletion` event
+/// - `full`: the event plus every job's complete `ExecutionResult` in `results`
+/// - `projection`: the event with each job reduced to the listed dot paths of
+///   its `ExecutionResult` (e.g. `score`, `results.status`; paths through
+///   arrays apply to every element)
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookPayload {
+    #[default]
+    Summary,
+    Full,
+    Projection(Vec<String>),
+}
+
+impl WebhookPayload {
+    pub const MAX_PROJECTION_FIELDS: usize = 32;
+    pub const MAX_PROJECTION_PATH_LEN: usize = 128;
+
+    pub fn is_summary(&self) -> bool {
+        *self == WebhookPayload::Summary
+    }
+
+    /// Check a projection is non-empty and its paths are well-formed
+    pub fn validate(&self) -> Result<(), String> {
+        let WebhookPayload::Projection(fields) = self else {
+            return Ok(());
+        };
+        if fields.is_empty() || fields.len() > Self::MAX_PROJECTION_FIELDS {
+            return Err(format!("projection must list 1-{} fields", Self::MAX_PROJECTION_FIELDS));
+        }
+        for field in fields {
+            if field.len() > Self::MAX_PROJECTION_PATH_LEN || field.split('.').any(str::is_empty) {
+                return Err(format!("projection field '{}' is not a valid dot path", field));
+            }
+        }
+        Ok(())
+    }
+
+    /// Webhook body for a finished batch (`results` are the jobs' results, in any order)
+    pub fn render(&self, completion: &BatchCompletion, results: &[ExecutionResult]) -> Result<String, serde_json::Error> {
+        match self {
+            WebhookPayload::Summary => serde_json::to_string(completion),
+            WebhookPayload::Full => {
+                let mut body = serde_json::to_value(completion)?;
+                body["results"] = serde_json::to_value(results)?;
+                serde_json::to_string(&body)
+            }
+            WebhookPayload::Projection(fields) => {
+                let mut body = serde_json::to_value(completion)?;
+                let jobs = results
+                    .iter()
+                    .map(|result| {
+                        let value = serde_json::to_value(result)?;
+                        let mut projected = serde_json::json!({ "job_id": result.job_id });
+                        for field in fields {
+                            let segments: Vec<&str> = field.split('.').collect();
+                            if let Some(part) = project_path(&value, &segments) {
+                                merge_projection(&mut projected, part);
+                            }
+                        }
+                        Ok(projected)
+                    })
+                    .collect::<Result<Vec<_>, serde_json::Error>>()?;
+                body["jobs"] = serde_json::Value::Array(jobs);
+                serde_json::to_string(&body)
+            }
+        }
+    }
+}
+
+/// The part of `value` at `path`, keeping the enclosing keys
+/// (arrays are mapped element by element)
+fn project_path(value: &serde_json::Value, path: &[&str]) -> Option<serde_json::Value> {
+    let Some((head, rest)) = path.split_first() else {
+        return Some(value.clone());
+    };
+    match value {
+        serde_json::Value::Array(items) => Some(serde_json::Value::Array(
+            items
+                .iter()
+                .map(|item| project_path(item, path).unwrap_or(serde_json::Value::Null))
+                .collect(),
+        )),
+        serde_json::Value::Object(map) => {
+            let inner = project_path(map.get(*head)?, rest)?;
+            Some(serde_json::json!({ *head: inner }))
+        }
+        _ => None,
+    }
+}
+
+/// Deep-merge one projected path into the accumulated projection
+fn merge_projection(into: &mut serde_json::Value, part: serde_json::Value) {
+    match (into, part) {
+        (serde_json::Value::Object(into), serde_json::Value::Object(part)) => {
+            for (key, value) in part {
+                match into.get_mut(&key) {
+                    Some(existing) => merge_projection(existing, value),
+                    None => {
+                        into.insert(key, value);
+                    }
+                }
+            }
+        }
+        (serde_json::Value::Array(into), serde_json::Value::Array(part)) => {
+            for (existing, value) in into.iter_mut().zip(part) {
+                merge_projection(existing, value);
+            }
+        }
+        (into, part) => *into = part,
+    }
+}
+
+/// Outbound webhook waiting for delivery by the API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    pub url: String,
+    /// Event name sent as `X-Optimus-Event` (e.g. "batch.completed")
+    pub event: String,
+    /// JSON request body
+    pub body: String,
+}
+
+/// Dry-Run Request (Admin)
+/// Raw execution of a snippet through a worker's engine, bypassing scoring
+/// Used by language config authors to debug runner/image issues
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DryRunRequest {
+    pub id: Uuid,
+    pub language: Language,
+    pub source_code: String,
+    #[serde(default)]
+    pub input: String,
+    pub timeout_ms: u64,
+}
+
+/// Dry-Run Result (Admin)
+/// Raw engine output - no expected output, no score
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DryRunResult {
+    pub id: Uuid,
+    pub language: Language,
+    pub stdout: String,
+    pub stderr: String,
+    pub exit_code: Option<i64>,
+    pub execution_time_ms: u64,
+    pub timed_out: bool,
+    /// Set when the engine itself failed (image pull, container create, ...)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub engine_error: Option<String>,
+}
+
+/// Where a worker is in its startup validation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkerPhase {
+    /// Environment or node doesn't match languages.json yet - no jobs consumed
+    Validating,
+    /// Validation passed; the worker consumes its queue
+    Ready,
+}
+
+/// Startup status a worker publishes under `optimus:worker_status:{worker_id}`
+/// (`STARTUP_VALIDATION=supervised`), so a mismatch is visible without logs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkerStatus {
+    pub worker_id: String,
+    pub phase: WorkerPhase,
+    /// Failed check while validating (e.g. "QUEUE_MISMATCH")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Validation attempts so far
+    pub attempts: u32,
+    /// RFC 3339
+    pub updated_at: String,
+}
+
+/// Registration a worker refreshes with every heartbeat under
+/// `optimus:worker:{worker_id}` (listed by GET /workers)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct WorkerInfo {
+    pub worker_id: String,
+    pub language: Language,
+    /// Placement pool the worker serves (None: the language's default queues)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pool: Option<String>,
+    /// optimus-worker version
+    pub version: String,
+    pub max_parallel_jobs: usize,
+    /// Jobs executing as of the last heartbeat
+    pub active_jobs: usize,
+    /// RFC 3339
+    pub started_at: String,
+    /// RFC 3339
+    pub last_heartbeat: String,
+}
+
+/// Kill Diagnostics (Admin)
+/// Captured from a timed-out container between the kill and its removal,
+/// when the language opts in with `kill_diagnostics` in languages.json
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct DiagnosticBundle {
+    pub test_id: u32,
+    /// Last `log_tail_kb` of combined stdout/stderr, in arrival order
+    pub log_tail: String,
+    pub exit_code: Option<i64>,
+    pub oom_killed: bool,
+    /// Docker's `State.FinishedAt`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<String>,
+    /// Requested files that existed, base64-encoded, keyed by path
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub files: BTreeMap<String, String>,
+    /// Set if the grace window ran out before everything was collected
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub incomplete: bool,
+    pub captured_at: String,
+}
+
+/// Warmup Baseline
+/// Boot-time hello-world latencies for one worker, published per language
+/// so live job latencies can be compared against a known-good node baseline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WarmupBaseline {
+    pub worker_id: String,
+    /// Kubernetes node (NODE_NAME), if known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node: Option<String>,
+    pub language: Language,
+    pub image: String,
+    /// First run: container create + start + hello-world on a fresh worker
+    pub cold_ms: u64,
+    /// Median of the remaining runs
+    pub warm_ms: Option<u64>,
+    pub runs: u32,
+    pub measured_at: String,
+}
+
+/// Language Usage Sample (Analytics)
+/// One finished job, recorded by the worker into daily per-language buckets
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LanguageUsage {
+    pub language: Language,
+    /// Runtime version from languages.json (e.g. "3.11-slim")
+    pub version: String,
+    /// Resource limits the job ran under (e.g. "512mb/1cpu")
+    pub tier: String,
+    pub source_bytes: u64,
+    /// Sum of the per-test program run times
+    pub execution_ms: u64,
+}
+
+/// One day of recorded usage for a language
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LanguageUsageDay {
+    pub jobs: u64,
+    pub versions: HashMap<String, u64>,
+    pub tiers: HashMap<String, u64>,
+    /// Most recent samples of the day (capped), for percentiles
+    pub source_bytes: Vec<u64>,
+    pub execution_ms: Vec<u64>,
+}
+
+/// Submission Rate Forecast
+/// Leading signal for autoscalers: reacts to a burst within a minute or two,
+/// before queue depth builds up
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct SubmissionForecast {
+    pub language: Language,
+    /// Exponentially weighted submissions per minute over the completed minutes
+    pub ewma_per_minute: f64,
+    /// Submissions so far in the current (partial) minute
+    pub current_minute: u64,
+    /// Completed minutes the EWMA covers
+    pub window_minutes: u32,
+    /// RFC 3339 timestamp of the computation
+    pub updated_at: String,
 }
 
 #[cfg(test)]
@@ -179,12 +1274,14 @@ mod tests {
                 input: "5\n".to_string(),
                 expected_output: "120\n".to_string(),
                 weight: 10,
+                ..Default::default()
             },
             TestCase {
                 id: 2,
                 input: "3\n".to_string(),
                 expected_output: "6\n".to_string(),
                 weight: 10,
+                ..Default::default()
             },
         ];
         
@@ -195,6 +1292,20 @@ mod tests {
             test_cases,
             timeout_ms: 5000,
             metadata: JobMetadata::default(),
+            priority: Default::default(),
+            batch_id: None,
+            time_scoring: None,
+            time_limit_mode: Default::default(),
+            archive: None,
+            accept_presentation_errors: false,
+            scoring: ScoringPolicy::Weighted,
+            problem_id: None,
+            memory_limit_mb: None,
+            cpu_limit: None,
+            cache_fingerprint: None,
+            placement: Default::default(),
+            gpu: false,
+            max_parallel_tests: None,
         };
         
         let json = serde_json::to_string(&job).unwrap();
@@ -222,6 +1333,15 @@ mod tests {
                 stdout: "120\n".to_string(),
                 stderr: String::new(),
                 execution_time_ms: 45,
+                deduplicated: false,
+                time_bonus: 0,
+                diff: None,
+                sandbox_flags: Vec::new(),
+                stdout_truncated: false,
+                stderr_truncated: false,
+                cpu_time_ms: None,
+                exit_code: Some(0),
+                summary: None,
             },
             TestResult {
                 test_id: 2,
@@ -229,6 +1349,15 @@ mod tests {
                 stdout: "5\n".to_string(),
                 stderr: String::new(),
                 execution_time_ms: 42,
+                deduplicated: false,
+                time_bonus: 0,
+                diff: None,
+                sandbox_flags: Vec::new(),
+                stdout_truncated: false,
+                stderr_truncated: false,
+                cpu_time_ms: None,
+                exit_code: Some(0),
+                summary: None,
             },
         ];
         
@@ -238,6 +1367,13 @@ mod tests {
             score: 10,
             max_score: 20,
             results: test_results,
+            scoring_formula: None,
+            reason: None,
+            feedback: None,
+            timing_summary: None,
+            suspicious: false,
+            cached: false,
+            verdict_summary: None,
         };
         
         assert_eq!(result.overall_status, JobStatus::Completed);
@@ -246,6 +1382,19 @@ mod tests {
         assert_eq!(result.results.len(), 2);
         assert_eq!(result.results[0].status, TestStatus::Passed);
         assert_eq!(result.results[1].status, TestStatus::Failed);
+        
+        let summary = result.summary();
+        assert_eq!(summary.total_tests, 2);
+        assert_eq!((summary.passed, summary.failed, summary.runtime_error), (1, 1, 0));
+        assert_eq!((summary.score, summary.max_score), (10, 20));
+        assert!(result.is_cacheable());
+        
+        let mut broken = result.clone();
+        broken.results[1].status = TestStatus::InternalError;
+        assert!(!broken.is_cacheable());
+        let mut force_completed = result;
+        force_completed.reason = Some("stuck".to_string());
+        assert!(!force_completed.is_cacheable());
     }
     
     #[test]
@@ -255,6 +1404,7 @@ mod tests {
             input: "input".to_string(),
             expected_output: "output".to_string(),
             weight: 5,
+            ..Default::default()
         };
         
         // Test case can be cloned but original is immutable
@@ -272,6 +1422,9 @@ mod tests {
         let failed = TestStatus::Failed;
         let json = serde_json::to_string(&failed).unwrap();
         assert_eq!(json, "\"failed\"");
+
+        let json = serde_json::to_string(&TestStatus::MemoryLimitExceeded).unwrap();
+        assert_eq!(json, "\"memorylimitexceeded\"");
     }
     
     #[test]
@@ -295,4 +1448,179 @@ mod tests {
         assert_eq!(Language::from_str("javascript"), None);
         assert_eq!(Language::from_str(""), None);
     }
+    
+    #[test]
+    fn test_fixture_validation() {
+        let fixture = |name: &str, content: Option<&str>, b64: Option<&str>| FixtureFile {
+            name: name.to_string(),
+            content: content.map(str::to_string),
+            content_base64: b64.map(str::to_string),
+        };
+        
+        assert_eq!(fixture("data/in.txt", Some("1 2"), None).validate().unwrap(), b"1 2");
+        assert_eq!(fixture("blob.bin", None, Some("AAE=")).validate().unwrap(), vec![0u8, 1]);
+        
+        assert!(fixture("/etc/passwd", Some("x"), None).validate().is_err());
+        assert!(fixture("../escape", Some("x"), None).validate().is_err());
+        assert!(fixture("a//b", Some("x"), None).validate().is_err());
+        assert!(fixture("", Some("x"), None).validate().is_err());
+        assert!(fixture("both", Some("x"), Some("eA==")).validate().is_err());
+        assert!(fixture("neither", None, None).validate().is_err());
+        assert!(fixture("bad.bin", None, Some("!!")).validate().is_err());
+    }
+    
+    #[test]
+    fn test_args_env_validation() {
+        let env = |pairs: &[(&str, &str)]| -> HashMap<String, String> {
+            pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+        };
+        let args = vec!["--size".to_string(), "10".to_string()];
+        
+        assert!(validate_args_env(&args, &env(&[("MODE", "fast"), ("_DEBUG2", "1")])).is_ok());
+        assert!(validate_args_env(&[], &env(&[("2FAST", "x")])).is_err());
+        assert!(validate_args_env(&[], &env(&[("A-B", "x")])).is_err());
+        assert!(validate_args_env(&[], &env(&[("TEST_INPUT", "x")])).is_err());
+        assert!(validate_args_env(&["a\0b".to_string()], &HashMap::new()).is_err());
+        assert!(validate_args_env(&vec!["x".to_string(); MAX_TEST_ARGS + 1], &HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn test_placement_pool() {
+        assert_eq!(Placement::default().pool(), None);
+
+        let arm = Placement { arch: Some("arm64".to_string()), ..Default::default() };
+        assert_eq!(arm.pool().as_deref(), Some("arch-arm64"));
+        let regional_arm = Placement { region: Some("eu-west-1".to_string()), arch: Some("arm64".to_string()) };
+        assert_eq!(regional_arm.pool().as_deref(), Some("arch-arm64.region-eu-west-1"));
+        assert!(regional_arm.validate().is_ok());
+
+        assert!(Placement { arch: Some("ARM64".to_string()), ..Default::default() }.validate().is_err());
+        assert!(Placement { region: Some("eu:west".to_string()), ..Default::default() }.validate().is_err());
+        assert!(Placement { region: Some(String::new()), ..Default::default() }.validate().is_err());
+        assert!(serde_json::from_str::<Placement>(r#"{"gpu": "a100"}"#).is_err());
+    }
+
+    #[test]
+    fn test_webhook_payload_projection() {
+        let result: ExecutionResult = serde_json::from_value(serde_json::json!({
+            "job_id": Uuid::nil(),
+            "overall_status": "completed",
+            "score": 10,
+            "max_score": 20,
+            "results": [
+                {"test_id": 1, "status": "passed", "stdout": "big", "stderr": "", "execution_time_ms": 5},
+                {"test_id": 2, "status": "failed", "stdout": "dump", "stderr": "", "execution_time_ms": 7},
+            ],
+        }))
+        .unwrap();
+        let completion = BatchCompletion {
+            batch_id: Uuid::nil(),
+            total_jobs: 1,
+            completed_at: "2026-10-16T00:00:00Z".to_string(),
+            jobs: vec![],
+        };
+
+        let template = WebhookPayload::Projection(vec![
+            "score".to_string(),
+            "results.test_id".to_string(),
+            "results.status".to_string(),
+            "missing.field".to_string(),
+        ]);
+        assert!(template.validate().is_ok());
+        let body: serde_json::Value = serde_json::from_str(&template.render(&completion, std::slice::from_ref(&result)).unwrap()).unwrap();
+        assert_eq!(
+            body["jobs"][0],
+            serde_json::json!({
+                "job_id": Uuid::nil(),
+                "score": 10,
+                "results": [{"test_id": 1, "status": "passed"}, {"test_id": 2, "status": "failed"}],
+            })
+        );
+
+        let full: serde_json::Value = serde_json::from_str(&WebhookPayload::Full.render(&completion, &[result]).unwrap()).unwrap();
+        assert_eq!(full["results"][0]["results"][1]["stdout"], "dump");
+
+        assert!(WebhookPayload::Projection(vec![]).validate().is_err());
+        assert!(WebhookPayload::Projection(vec!["results..status".to_string()]).validate().is_err());
+        let parsed: WebhookPayload = serde_json::from_str(r#"{"projection": ["score"]}"#).unwrap();
+        assert_eq!(parsed, WebhookPayload::Projection(vec!["score".to_string()]));
+    }
+
+    #[test]
+    fn test_upload_session_round_trip() {
+        // replace_upload compares re-serialized sessions with the stored JSON
+        let session = UploadSession {
+            id: Uuid::nil(),
+            total_bytes: 10,
+            received_bytes: 4,
+            chunks: vec!["ab".repeat(32)],
+            sha256: None,
+            owner: Some("grader".to_string()),
+            created_at: "2026-01-01T00:00:00+00:00".to_string(),
+            test_suite: None,
+        };
+        let stored = serde_json::to_string(&session).unwrap();
+        let reread: UploadSession = serde_json::from_str(&stored).unwrap();
+        assert_eq!(serde_json::to_string(&reread).unwrap(), stored);
+
+        let problem: Problem = serde_json::from_str(
+            r#"{"id": "p1", "limits": {"timeout_ms": 1000}, "created_at": "2026-01-01T00:00:00+00:00"}"#,
+        ).unwrap();
+        assert_eq!(problem.test_suite, None);
+        assert_eq!(problem.limits.accept_presentation_errors, None);
+        assert_eq!(problem.limits.time_limit_mode, TimeLimitMode::Wall);
+        assert_eq!(problem.limits.cpu_limit, None);
+    }
+
+    #[test]
+    fn test_hop_log_records_first_dequeue_per_attempt() {
+        let mut hops = HopLog::default();
+        assert!(hops.is_empty());
+
+        hops.record_dequeue(0, "t1".to_string());
+        hops.record_dequeue(0, "t2".to_string());
+        hops.record_dequeue(1, "t3".to_string());
+        assert_eq!(hops.first_dequeue_at, vec!["t1", "t3"]);
+
+        // Jobs queued before the hop log existed deserialize with an empty one
+        let metadata: JobMetadata = serde_json::from_str(r#"{"attempts": 1, "max_attempts": 3}"#).unwrap();
+        assert!(metadata.hops.is_empty());
+    }
+
+    #[test]
+    fn test_batch_release_offsets() {
+        let mut batch = BatchRecord {
+            id: Uuid::nil(),
+            expected_jobs: 500,
+            webhook_url: None,
+            webhook_payload: WebhookPayload::Summary,
+            owner: None,
+            test_suite: None,
+            spread_ms: 60_000,
+            jitter_ms: 0,
+            created_at: "2026-01-01T00:00:00+00:00".to_string(),
+        };
+        assert_eq!(batch.release_offset_ms(0, 7), 0);
+        assert_eq!(batch.release_offset_ms(250, 7), 30_000);
+        assert_eq!(batch.release_offset_ms(499, 7), 59_880);
+
+        batch.jitter_ms = 1000;
+        assert_eq!(batch.release_offset_ms(250, 1500), 30_499);
+        assert!((0..5000).all(|random| batch.release_offset_ms(0, random) <= 1000));
+    }
+
+    #[test]
+    fn test_job_progress_shares_status_key() {
+        let progress = JobProgress {
+            state: JobStatus::Running,
+            tests_completed: 3,
+            tests_total: 10,
+            current_test_started_at: None,
+        };
+        // `set_job_progress` tells progress from a final status by the leading brace
+        let payload = serde_json::to_string(&progress).unwrap();
+        assert!(payload.starts_with('{'));
+        assert!(payload.contains("\"state\":\"running\""));
+        assert!(serde_json::from_str::<JobProgress>(&serde_json::to_string(&JobStatus::Completed).unwrap()).is_err());
+    }
 }
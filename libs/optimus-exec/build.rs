@@ -0,0 +1,38 @@
+// Generates the containerd API clients from the protos vendored under `proto/`
+// (containerd 1.7's `api/` tree, import paths as upstream)
+//
+// The descriptors come from protobuf-parse's pure Rust parser, so building
+// needs no `protoc`.
+
+use prost::Message as _;
+use protobuf::Message as _;
+
+const PROTO_ROOT: &str = "proto";
+
+const PROTOS: &[&str] = &[
+    "github.com/containerd/containerd/api/services/containers/v1/containers.proto",
+    "github.com/containerd/containerd/api/services/content/v1/content.proto",
+    "github.com/containerd/containerd/api/services/images/v1/images.proto",
+    "github.com/containerd/containerd/api/services/snapshots/v1/snapshots.proto",
+    "github.com/containerd/containerd/api/services/tasks/v1/tasks.proto",
+    "github.com/containerd/containerd/api/services/version/v1/version.proto",
+];
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    println!("cargo:rerun-if-changed={}", PROTO_ROOT);
+
+    let inputs: Vec<_> = PROTOS.iter().map(|proto| std::path::Path::new(PROTO_ROOT).join(proto)).collect();
+    // Every file the inputs import too: prost resolves types across all of them
+    let parsed = protobuf_parse::Parser::new()
+        .pure()
+        .include(PROTO_ROOT)
+        .inputs(&inputs)
+        .parse_and_typecheck()?;
+    let mut descriptors = prost_types::FileDescriptorSet::default();
+    for file in parsed.file_descriptors {
+        descriptors.file.push(prost_types::FileDescriptorProto::decode(file.write_to_bytes()?.as_slice())?);
+    }
+
+    tonic_build::configure().build_server(false).compile_fds(descriptors)?;
+    Ok(())
+}
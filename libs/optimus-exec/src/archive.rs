@@ -0,0 +1,293 @@
+//! Project Archives - Safe Unpacking of Multi-File Submissions
+//!
+//! **Responsibility:**
+//! Turn a submitted zip / tar.gz project into a tar stream rooted at the
+//! runner's working directory, ready for `upload_to_container`.
+//!
+//! **Safety:**
+//! - Entry paths must be relative with no `..` components (no escaping `/code`)
+//! - Only regular files and directories - symlinks, hardlinks and device
+//!   nodes are rejected outright
+//! - Caps on compressed size, entry count and total unpacked bytes; unpacked
+//!   sizes are counted while reading, never trusted from headers
+//!
+//! Unpacking happens in the worker's memory, not on its filesystem - the only
+//! place the files ever land is inside the job's container.
+
+use anyhow::{Context, Result, bail};
+use base64::{Engine as _, engine::general_purpose};
+use flate2::read::GzDecoder;
+use optimus_common::types::{ArchiveFormat, ProjectArchive};
+use std::io::{Cursor, Read};
+
+/// Directory the runner executes from; project files are placed here
+const PROJECT_DIR: &str = "code";
+
+pub const MAX_ARCHIVE_BYTES: usize = 10 * 1024 * 1024; // 10MB compressed
+pub const MAX_UNPACKED_BYTES: u64 = 50 * 1024 * 1024; // 50MB
+pub const MAX_ENTRIES: usize = 1000;
+const MAX_PATH_LEN: usize = 1024;
+
+/// Files are owned by the runner's non-root user so builds can write next to them
+const RUNNER_UID: u64 = 1000;
+
+/// A validated project, ready to copy into a container
+#[derive(Debug, Clone)]
+pub struct ProjectBundle {
+    /// Tar stream rooted at `/`, entries under `code/`
+    pub tar: Vec<u8>,
+    /// Shell command the runner executes from /code
+    pub entry_command: String,
+}
+
+/// Decode and validate a project archive
+///
+/// `default_command` (the language's `project_command`) is used when the
+/// submission didn't name an entry command.
+pub fn prepare(archive: &ProjectArchive, default_command: Option<String>) -> Result<ProjectBundle> {
+    let entry_command = archive
+        .entry_command
+        .clone()
+        .filter(|c| !c.trim().is_empty())
+        .or(default_command)
+        .context("No entry command given and the language has no project_command configured")?;
+
+    let compressed = general_purpose::STANDARD
+        .decode(&archive.content_base64)
+        .context("Archive is not valid base64")?;
+    if compressed.len() > MAX_ARCHIVE_BYTES {
+        bail!("Archive exceeds maximum size of {} bytes", MAX_ARCHIVE_BYTES);
+    }
+
+    let mut out = Repacker::new();
+    match archive.format {
+        ArchiveFormat::Zip => unpack_zip(&compressed, &mut out)?,
+        ArchiveFormat::TarGz => unpack_tar_gz(&compressed, &mut out)?,
+    }
+    if out.files == 0 {
+        bail!("Archive contains no files");
+    }
+
+    Ok(ProjectBundle {
+        tar: out.finish()?,
+        entry_command,
+    })
+}
+
+/// Normalize an archive entry path, rejecting anything that could leave /code
+///
+/// Leading `./` and `.` components are dropped; directory entries may end in `/`.
+fn sanitize_path(raw: &str) -> Result<String> {
+    if raw.len() > MAX_PATH_LEN {
+        bail!("Archive entry path is longer than {} bytes", MAX_PATH_LEN);
+    }
+    if raw.starts_with('/') || raw.contains('\\') || raw.contains('\0') {
+        bail!("Archive entry '{}' must be a relative path", raw);
+    }
+
+    let mut parts = Vec::new();
+    for part in raw.split('/') {
+        match part {
+            "" | "." => continue,
+            ".." => bail!("Archive entry '{}' contains '..'", raw),
+            part => parts.push(part),
+        }
+    }
+    if parts.is_empty() {
+        bail!("Archive entry '{}' has an empty path", raw);
+    }
+
+    Ok(parts.join("/"))
+}
+
+/// Collects validated entries into the output tar while enforcing the caps
+struct Repacker {
+    builder: tar::Builder<Vec<u8>>,
+    entries: usize,
+    files: usize,
+    unpacked_bytes: u64,
+}
+
+impl Repacker {
+    fn new() -> Self {
+        Self {
+            builder: tar::Builder::new(Vec::new()),
+            entries: 0,
+            files: 0,
+            unpacked_bytes: 0,
+        }
+    }
+
+    fn count_entry(&mut self) -> Result<()> {
+        self.entries += 1;
+        if self.entries > MAX_ENTRIES {
+            bail!("Archive has more than {} entries", MAX_ENTRIES);
+        }
+        Ok(())
+    }
+
+    fn add_dir(&mut self, path: &str) -> Result<()> {
+        self.count_entry()?;
+        let mut header = header(tar::EntryType::Directory, 0, 0o755);
+        self.builder
+            .append_data(&mut header, format!("{}/{}", PROJECT_DIR, path), std::io::empty())
+            .context(format!("Failed to add directory '{}'", path))
+    }
+
+    /// Read a file from `reader`, counting its real size against the budget
+    fn add_file(&mut self, path: &str, reader: impl Read, executable: bool) -> Result<()> {
+        self.count_entry()?;
+
+        let remaining = MAX_UNPACKED_BYTES - self.unpacked_bytes;
+        let mut content = Vec::new();
+        reader
+            .take(remaining + 1)
+            .read_to_end(&mut content)
+            .context(format!("Failed to read '{}' from archive", path))?;
+        if content.len() as u64 > remaining {
+            bail!("Archive unpacks to more than {} bytes", MAX_UNPACKED_BYTES);
+        }
+        self.unpacked_bytes += content.len() as u64;
+        self.files += 1;
+
+        let mode = if executable { 0o755 } else { 0o644 };
+        let mut header = header(tar::EntryType::Regular, content.len() as u64, mode);
+        self.builder
+            .append_data(&mut header, format!("{}/{}", PROJECT_DIR, path), content.as_slice())
+            .context(format!("Failed to add file '{}'", path))
+    }
+
+    fn finish(self) -> Result<Vec<u8>> {
+        self.builder.into_inner().context("Failed to finalize project archive")
+    }
+}
+
+fn header(entry_type: tar::EntryType, size: u64, mode: u32) -> tar::Header {
+    let mut header = tar::Header::new_gnu();
+    header.set_entry_type(entry_type);
+    header.set_size(size);
+    header.set_mode(mode);
+    header.set_uid(RUNNER_UID);
+    header.set_gid(RUNNER_UID);
+    header.set_cksum();
+    header
+}
+
+fn unpack_zip(bytes: &[u8], out: &mut Repacker) -> Result<()> {
+    let mut zip = zip::ZipArchive::new(Cursor::new(bytes)).context("Invalid zip archive")?;
+    if zip.len() > MAX_ENTRIES {
+        bail!("Archive has more than {} entries", MAX_ENTRIES);
+    }
+
+    for index in 0..zip.len() {
+        let entry = zip.by_index(index).context("Invalid zip entry")?;
+        let path = sanitize_path(entry.name())?;
+        let unix_mode = entry.unix_mode().unwrap_or(0);
+
+        // S_IFLNK - zip has no entry type, symlinks are flagged in the mode bits
+        if unix_mode & 0o170000 == 0o120000 {
+            bail!("Archive entry '{}' is a symlink", path);
+        }
+
+        if entry.is_dir() {
+            out.add_dir(&path)?;
+        } else {
+            out.add_file(&path, entry, unix_mode & 0o111 != 0)?;
+        }
+    }
+    Ok(())
+}
+
+fn unpack_tar_gz(bytes: &[u8], out: &mut Repacker) -> Result<()> {
+    let mut archive = tar::Archive::new(GzDecoder::new(bytes));
+
+    for entry in archive.entries().context("Invalid tar.gz archive")? {
+        let entry = entry.context("Invalid tar.gz entry")?;
+        let raw_path = entry.path().context("Invalid tar.gz entry path")?;
+        let path = sanitize_path(&raw_path.to_string_lossy())?;
+
+        match entry.header().entry_type() {
+            tar::EntryType::Directory => out.add_dir(&path)?,
+            tar::EntryType::Regular | tar::EntryType::Continuous => {
+                let executable = entry.header().mode().map(|m| m & 0o111 != 0).unwrap_or(false);
+                out.add_file(&path, entry, executable)?;
+            }
+            // PAX / GNU long-name records are consumed by the tar reader itself
+            tar::EntryType::XGlobalHeader => continue,
+            other => bail!("Archive entry '{}' has unsupported type {:?}", path, other),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn tar_gz(build: impl FnOnce(&mut tar::Builder<Vec<u8>>)) -> String {
+        let mut builder = tar::Builder::new(Vec::new());
+        build(&mut builder);
+        let tar = builder.into_inner().unwrap();
+        let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+        gz.write_all(&tar).unwrap();
+        general_purpose::STANDARD.encode(gz.finish().unwrap())
+    }
+
+    fn project(content_base64: String) -> ProjectArchive {
+        ProjectArchive { format: ArchiveFormat::TarGz, content_base64, entry_command: Some("make run".to_string()) }
+    }
+
+    #[test]
+    fn test_sanitize_path_rejects_escapes() {
+        assert_eq!(sanitize_path("./src/main.py").unwrap(), "src/main.py");
+        assert_eq!(sanitize_path("pkg/").unwrap(), "pkg");
+        assert!(sanitize_path("../etc/passwd").is_err());
+        assert!(sanitize_path("src/../../x").is_err());
+        assert!(sanitize_path("/etc/passwd").is_err());
+        assert!(sanitize_path("src\\..\\x").is_err());
+        assert!(sanitize_path("./").is_err());
+    }
+
+    #[test]
+    fn test_tar_gz_is_repacked_under_code() {
+        let content = tar_gz(|b| {
+            let mut header = header(tar::EntryType::Regular, 5, 0o644);
+            b.append_data(&mut header, "src/lib.py", "x = 1".as_bytes()).unwrap();
+        });
+        let bundle = prepare(&project(content), None).unwrap();
+        assert_eq!(bundle.entry_command, "make run");
+
+        let mut repacked = tar::Archive::new(bundle.tar.as_slice());
+        let paths: Vec<String> = repacked
+            .entries()
+            .unwrap()
+            .map(|e| e.unwrap().path().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(paths, vec!["code/src/lib.py"]);
+    }
+
+    #[test]
+    fn test_tar_gz_symlinks_rejected() {
+        let content = tar_gz(|b| {
+            let mut header = header(tar::EntryType::Symlink, 0, 0o777);
+            b.append_link(&mut header, "escape", "/etc/passwd").unwrap();
+        });
+        let err = prepare(&project(content), None).unwrap_err();
+        assert!(err.to_string().contains("unsupported type"));
+    }
+
+    #[test]
+    fn test_entry_command_falls_back_to_language_default() {
+        let content = tar_gz(|b| {
+            let mut header = header(tar::EntryType::Regular, 0, 0o644);
+            b.append_data(&mut header, "main.py", std::io::empty()).unwrap();
+        });
+        let mut archive = project(content);
+        archive.entry_command = None;
+
+        assert!(prepare(&archive, None).is_err());
+        let bundle = prepare(&archive, Some("python3 main.py".to_string())).unwrap();
+        assert_eq!(bundle.entry_command, "python3 main.py");
+    }
+}
@@ -0,0 +1,523 @@
+// Language configuration management for Optimus Worker
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+use optimus_common::types::{Language, Placement};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LanguageExecution {
+    pub command: String,
+    pub args: Vec<String>,
+    pub file_extension: String,
+    /// Default entry command for project archive submissions (run from /code)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_command: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LanguageConfig {
+    pub name: String,
+    pub version: String,
+    pub image: String,
+    pub dockerfile_path: String,
+    pub execution: LanguageExecution,
+    pub queue_name: String,
+    pub memory_limit_mb: u32,
+    pub cpu_limit: f32,
+    /// Cap on live containers of this language on the Docker host (all workers),
+    /// counted by label before each container is created
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_containers: Option<u32>,
+    /// Capture a diagnostic bundle from timed-out containers before removal
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kill_diagnostics: Option<KillDiagnostics>,
+    /// OCI runtime for this language's containers, as registered with dockerd
+    /// (e.g. "kata" for VM-isolated containers); None uses the daemon default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub runtime: Option<String>,
+    /// Placement-constrained worker pools with their own queues and deployments
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub placements: Vec<Placement>,
+    /// GPU devices attached to this language's containers (NVIDIA); workers
+    /// refuse to start on nodes without them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gpu: Option<GpuConfig>,
+    /// Seccomp profile for Docker/Podman containers: "default" for the built-in
+    /// profile or a path to a profile JSON; None keeps the daemon's default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seccomp_profile: Option<String>,
+    /// Process, file-descriptor and file-size limits for Docker/Podman containers
+    #[serde(default, skip_serializing_if = "ProcessLimits::is_default")]
+    pub process_limits: ProcessLimits,
+    /// Root filesystem of Docker/Podman containers: read-only with size-capped
+    /// tmpfs mounts unless the language opts out
+    #[serde(default, skip_serializing_if = "FilesystemConfig::is_default")]
+    pub filesystem: FilesystemConfig,
+    /// Capability and privilege hardening of Docker/Podman containers
+    #[serde(default, skip_serializing_if = "SecurityConfig::is_default")]
+    pub security: SecurityConfig,
+}
+
+/// How GPU devices are requested for a language's containers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GpuConfig {
+    /// GPUs per container (ignored when the worker pins devices with `OPTIMUS_GPU_DEVICES`)
+    #[serde(default = "GpuConfig::default_count")]
+    pub count: u32,
+    /// NVIDIA driver capabilities exposed to the container
+    #[serde(default = "GpuConfig::default_capabilities")]
+    pub capabilities: Vec<String>,
+    /// Attach GPUs to every job, not only to jobs that ask for one
+    /// (`gpu: true` on the submission or its problem)
+    #[serde(default)]
+    pub all_jobs: bool,
+}
+
+impl GpuConfig {
+    fn default_count() -> u32 {
+        1
+    }
+
+    fn default_capabilities() -> Vec<String> {
+        vec!["compute".to_string(), "utility".to_string()]
+    }
+}
+
+/// What to collect from a container killed on timeout, and for how long
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KillDiagnostics {
+    /// Time allowed for collection before the container is removed anyway
+    #[serde(default = "KillDiagnostics::default_grace_ms")]
+    pub grace_ms: u64,
+    /// How much of the end of the combined output to keep
+    #[serde(default = "KillDiagnostics::default_log_tail_kb")]
+    pub log_tail_kb: usize,
+    /// Files to keep, relative to the runner's working directory (e.g. "core")
+    /// Each is capped at `MAX_DIAGNOSTIC_FILE_BYTES`; missing ones are skipped
+    #[serde(default)]
+    pub files: Vec<String>,
+}
+
+impl KillDiagnostics {
+    /// Per-file cap - bundles are meant to be small
+    pub const MAX_DIAGNOSTIC_FILE_BYTES: usize = 64 * 1024;
+
+    fn default_grace_ms() -> u64 {
+        2000
+    }
+
+    fn default_log_tail_kb() -> usize {
+        16
+    }
+}
+
+/// Limits that keep one container from exhausting the node's process table,
+/// file descriptors or disk - none of which the memory/CPU limits cover
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ProcessLimits {
+    /// Processes and threads in the container (`HostConfig.PidsLimit`)
+    #[serde(default = "ProcessLimits::default_pids_limit")]
+    pub pids_limit: i64,
+    /// Open files per process (`nofile` ulimit)
+    #[serde(default = "ProcessLimits::default_nofile")]
+    pub nofile: i64,
+    /// Largest file a process may write, in MB (`fsize` ulimit)
+    #[serde(default = "ProcessLimits::default_fsize_mb")]
+    pub fsize_mb: i64,
+    /// Processes per user (`nproc` ulimit); unset by default because the
+    /// kernel counts every process of the UID on the host, not per container
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nproc: Option<i64>,
+}
+
+impl ProcessLimits {
+    fn default_pids_limit() -> i64 {
+        256
+    }
+
+    fn default_nofile() -> i64 {
+        256
+    }
+
+    fn default_fsize_mb() -> i64 {
+        64
+    }
+
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl Default for ProcessLimits {
+    fn default() -> Self {
+        Self {
+            pids_limit: Self::default_pids_limit(),
+            nofile: Self::default_nofile(),
+            fsize_mb: Self::default_fsize_mb(),
+            nproc: None,
+        }
+    }
+}
+
+/// Where a container may write: with a read-only root filesystem, only
+/// `/tmp` and the runner's working directory (`/code`), each a tmpfs of
+/// bounded size, so a submission can't fill the node's disk
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FilesystemConfig {
+    /// `false` keeps the image's writable layer (and no tmpfs mounts)
+    #[serde(default = "FilesystemConfig::default_readonly_rootfs")]
+    pub readonly_rootfs: bool,
+    /// Size of the `/tmp` tmpfs, in MB
+    #[serde(default = "FilesystemConfig::default_tmp_mb")]
+    pub tmp_mb: u64,
+    /// Size of the `/code` tmpfs, in MB (source, fixtures, build output)
+    #[serde(default = "FilesystemConfig::default_work_dir_mb")]
+    pub work_dir_mb: u64,
+}
+
+impl FilesystemConfig {
+    fn default_readonly_rootfs() -> bool {
+        true
+    }
+
+    fn default_tmp_mb() -> u64 {
+        64
+    }
+
+    fn default_work_dir_mb() -> u64 {
+        256
+    }
+
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl Default for FilesystemConfig {
+    fn default() -> Self {
+        Self {
+            readonly_rootfs: Self::default_readonly_rootfs(),
+            tmp_mb: Self::default_tmp_mb(),
+            work_dir_mb: Self::default_work_dir_mb(),
+        }
+    }
+}
+
+/// Privileges of a language's containers: by default every capability is
+/// dropped and `no-new-privileges` is set, so setuid binaries and file
+/// capabilities in the image can't hand the program more than it started with
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SecurityConfig {
+    /// Keep the daemon's default capabilities and allow privilege gains, as
+    /// before hardening (for images that rely on setuid helpers)
+    #[serde(default)]
+    pub legacy_privileges: bool,
+    /// `HostConfig.UsernsMode`: "host" opts out of dockerd's `userns-remap`,
+    /// Podman also takes "auto", "keep-id", ...; None keeps the daemon's mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub userns_mode: Option<String>,
+}
+
+impl SecurityConfig {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct LanguagesJson {
+    languages: Vec<LanguageConfig>,
+}
+
+/// Language configuration manager
+///
+/// Clones share the same underlying map, so a refresh (see `config_sync`)
+/// is seen by every engine and background task holding a clone.
+#[derive(Clone)]
+pub struct LanguageConfigManager {
+    configs: Arc<RwLock<HashMap<String, LanguageConfig>>>,
+}
+
+impl LanguageConfigManager {
+    /// Load language configurations from languages.json
+    pub fn load(config_path: &Path) -> Result<Self> {
+        if !config_path.exists() {
+            bail!("Language config file not found: {}", config_path.display());
+        }
+
+        let content = fs::read_to_string(config_path)
+            .context("Failed to read languages.json")?;
+        
+        Self::from_json(&content)
+    }
+
+    /// Parse language configurations from languages.json content
+    pub fn from_json(content: &str) -> Result<Self> {
+        Ok(Self {
+            configs: Arc::new(RwLock::new(Self::parse(content)?)),
+        })
+    }
+
+    fn parse(content: &str) -> Result<HashMap<String, LanguageConfig>> {
+        let languages_json: LanguagesJson = serde_json::from_str(content)
+            .context("Failed to parse languages.json")?;
+
+        let mut configs = HashMap::new();
+        for lang in languages_json.languages {
+            configs.insert(lang.name.clone(), lang);
+        }
+
+        Ok(configs)
+    }
+
+    /// Swap in the configurations from another manager
+    /// Returns true if anything changed
+    pub fn replace_with(&self, other: &LanguageConfigManager) -> bool {
+        let incoming = other.configs.read().expect("config lock poisoned").clone();
+        let mut configs = self.configs.write().expect("config lock poisoned");
+        if *configs == incoming {
+            return false;
+        }
+        *configs = incoming;
+        true
+    }
+
+    /// Where `load_default` reads from
+    pub fn default_path() -> &'static Path {
+        Path::new("config/languages.json")
+    }
+
+    /// Load with default path (config/languages.json)
+    pub fn load_default() -> Result<Self> {
+        Self::load(Self::default_path())
+    }
+
+    /// Get configuration for a specific language (snapshot)
+    pub fn get_config(&self, language: &Language) -> Result<LanguageConfig> {
+        let lang_name = language.to_string();
+        self.configs
+            .read()
+            .expect("config lock poisoned")
+            .get(&lang_name)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("No configuration found for language: {}", lang_name))
+    }
+
+    /// Get Docker image for a language
+    pub fn get_image(&self, language: &Language) -> Result<String> {
+        Ok(self.get_config(language)?.image)
+    }
+
+    /// Get queue name for a language
+    pub fn get_queue_name(&self, language: &Language) -> Result<String> {
+        Ok(self.get_config(language)?.queue_name)
+    }
+
+    /// Get memory limit for a language
+    pub fn get_memory_limit_mb(&self, language: &Language) -> Result<u32> {
+        Ok(self.get_config(language)?.memory_limit_mb)
+    }
+
+    /// Get CPU limit for a language
+    pub fn get_cpu_limit(&self, language: &Language) -> Result<f32> {
+        Ok(self.get_config(language)?.cpu_limit)
+    }
+
+    /// Get the default project archive entry command for a language
+    pub fn get_project_command(&self, language: &Language) -> Result<Option<String>> {
+        Ok(self.get_config(language)?.execution.project_command)
+    }
+
+    /// Per-language cap on live containers (None = uncapped)
+    pub fn get_max_containers(&self, language: &Language) -> Option<u32> {
+        self.get_config(language).ok()?.max_containers
+    }
+
+    /// Kill diagnostics settings for a language (None if it didn't opt in)
+    pub fn get_kill_diagnostics(&self, language: &Language) -> Option<KillDiagnostics> {
+        self.get_config(language).ok()?.kill_diagnostics
+    }
+
+    /// Names of the language's placement pools (see `Placement::pool`)
+    pub fn get_pools(&self, language: &Language) -> Vec<String> {
+        self.get_config(language)
+            .map(|config| config.placements.iter().filter_map(Placement::pool).collect())
+            .unwrap_or_default()
+    }
+
+    /// Container runtime for a language (None for the Docker default)
+    pub fn get_runtime(&self, language: &Language) -> Option<String> {
+        self.get_config(language).ok()?.runtime
+    }
+
+    /// GPU settings for a language (None if it has no GPUs)
+    pub fn get_gpu(&self, language: &Language) -> Option<GpuConfig> {
+        self.get_config(language).ok()?.gpu
+    }
+
+    /// Seccomp profile configured for a language (see `seccomp::load_profile`)
+    pub fn get_seccomp_profile(&self, language: &Language) -> Option<String> {
+        self.get_config(language).ok()?.seccomp_profile
+    }
+
+    /// Process limits for a language (defaults when it isn't configured)
+    pub fn get_process_limits(&self, language: &Language) -> ProcessLimits {
+        self.get_config(language).map(|config| config.process_limits).unwrap_or_default()
+    }
+
+    /// Root filesystem settings for a language (defaults when it isn't configured)
+    pub fn get_filesystem(&self, language: &Language) -> FilesystemConfig {
+        self.get_config(language).map(|config| config.filesystem).unwrap_or_default()
+    }
+
+    /// Privilege settings for a language (hardened defaults when it isn't configured)
+    pub fn get_security(&self, language: &Language) -> SecurityConfig {
+        self.get_config(language).map(|config| config.security).unwrap_or_default()
+    }
+
+    /// List all supported languages
+    pub fn list_languages(&self) -> Vec<String> {
+        self.configs.read().expect("config lock poisoned").keys().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_config() {
+        // This test will only work if config/languages.json exists
+        let result = LanguageConfigManager::load_default();
+        match result {
+            Ok(manager) => {
+                println!("Loaded languages: {:?}", manager.list_languages());
+            }
+            Err(e) => {
+                println!("Config not found (expected in test environment): {}", e);
+            }
+        }
+    }
+
+    #[test]
+    fn test_default_config_parses() {
+        let manager = LanguageConfigManager::from_json(optimus_common::config::DEFAULT_LANGUAGES_JSON).unwrap();
+        assert_eq!(manager.list_languages(), vec!["python".to_string()]);
+        assert_eq!(manager.get_memory_limit_mb(&Language::Python).unwrap(), 128);
+        assert_eq!(manager.get_max_containers(&Language::Python), Some(4));
+    }
+
+        fn sample_json(memory_mb: u32) -> String {
+        format!(
+            r#"{{"languages": [{{
+                "name": "python", "version": "3.11", "image": "optimus-python:3.11-slim",
+                "dockerfile_path": "dockerfiles/python/Dockerfile",
+                "execution": {{"command": "python", "args": [], "file_extension": "py"}},
+                "queue_name": "optimus:queue:python", "memory_limit_mb": {}, "cpu_limit": 0.5
+            }}]}}"#,
+            memory_mb
+        )
+    }
+
+    #[test]
+    fn test_replace_is_shared_across_clones() {
+        let manager = LanguageConfigManager::from_json(&sample_json(256)).unwrap();
+        let engine_copy = manager.clone();
+
+        let updated = LanguageConfigManager::from_json(&sample_json(512)).unwrap();
+        assert!(manager.replace_with(&updated));
+        assert!(!manager.replace_with(&updated));
+
+        assert_eq!(engine_copy.get_memory_limit_mb(&Language::Python).unwrap(), 512);
+    }
+
+    #[test]
+    fn test_kill_diagnostics_defaults() {
+        let manager = LanguageConfigManager::from_json(&sample_json(256)).unwrap();
+        assert_eq!(manager.get_kill_diagnostics(&Language::Python), None);
+
+        let json = sample_json(256).replace(r#""cpu_limit": 0.5"#, r#""cpu_limit": 0.5, "kill_diagnostics": {"files": ["core"]}"#);
+        let manager = LanguageConfigManager::from_json(&json).unwrap();
+        let settings = manager.get_kill_diagnostics(&Language::Python).unwrap();
+        assert_eq!(settings.grace_ms, 2000);
+        assert_eq!(settings.log_tail_kb, 16);
+        assert_eq!(settings.files, vec!["core".to_string()]);
+    }
+
+    #[test]
+    fn test_runtime_is_optional() {
+        let manager = LanguageConfigManager::from_json(&sample_json(256)).unwrap();
+        assert_eq!(manager.get_runtime(&Language::Python), None);
+
+        let json = sample_json(256).replace(r#""cpu_limit": 0.5"#, r#""cpu_limit": 0.5, "runtime": "kata""#);
+        let manager = LanguageConfigManager::from_json(&json).unwrap();
+        assert_eq!(manager.get_runtime(&Language::Python), Some("kata".to_string()));
+    }
+
+    #[test]
+    fn test_gpu_defaults() {
+        let manager = LanguageConfigManager::from_json(&sample_json(256)).unwrap();
+        assert_eq!(manager.get_gpu(&Language::Python), None);
+
+        let json = sample_json(256).replace(r#""cpu_limit": 0.5"#, r#""cpu_limit": 0.5, "gpu": {}"#);
+        let gpu = LanguageConfigManager::from_json(&json).unwrap().get_gpu(&Language::Python).unwrap();
+        assert_eq!(gpu.count, 1);
+        assert_eq!(gpu.capabilities, vec!["compute", "utility"]);
+        assert!(!gpu.all_jobs);
+    }
+
+    #[test]
+    fn test_process_limits_defaults() {
+        let manager = LanguageConfigManager::from_json(&sample_json(256)).unwrap();
+        assert_eq!(manager.get_process_limits(&Language::Python), ProcessLimits::default());
+
+        let json = sample_json(256).replace(r#""cpu_limit": 0.5"#, r#""cpu_limit": 0.5, "process_limits": {"pids_limit": 64, "nproc": 512}"#);
+        let limits = LanguageConfigManager::from_json(&json).unwrap().get_process_limits(&Language::Python);
+        assert_eq!(limits.pids_limit, 64);
+        assert_eq!(limits.nofile, 256);
+        assert_eq!(limits.fsize_mb, 64);
+        assert_eq!(limits.nproc, Some(512));
+    }
+
+    #[test]
+    fn test_filesystem_defaults_to_readonly() {
+        let manager = LanguageConfigManager::from_json(&sample_json(256)).unwrap();
+        let filesystem = manager.get_filesystem(&Language::Python);
+        assert!(filesystem.readonly_rootfs);
+        assert_eq!(filesystem.tmp_mb, 64);
+        assert_eq!(filesystem.work_dir_mb, 256);
+
+        let json = sample_json(256).replace(r#""cpu_limit": 0.5"#, r#""cpu_limit": 0.5, "filesystem": {"work_dir_mb": 1024}"#);
+        let filesystem = LanguageConfigManager::from_json(&json).unwrap().get_filesystem(&Language::Python);
+        assert!(filesystem.readonly_rootfs);
+        assert_eq!(filesystem.work_dir_mb, 1024);
+    }
+
+    #[test]
+    fn test_security_defaults_to_hardened() {
+        let manager = LanguageConfigManager::from_json(&sample_json(256)).unwrap();
+        assert_eq!(manager.get_security(&Language::Python), SecurityConfig::default());
+        assert!(!manager.get_security(&Language::Python).legacy_privileges);
+
+        let json = sample_json(256).replace(
+            r#""cpu_limit": 0.5"#,
+            r#""cpu_limit": 0.5, "security": {"legacy_privileges": true, "userns_mode": "host"}"#,
+        );
+        let security = LanguageConfigManager::from_json(&json).unwrap().get_security(&Language::Python);
+        assert!(security.legacy_privileges);
+        assert_eq!(security.userns_mode.as_deref(), Some("host"));
+    }
+
+    #[test]
+    fn test_seccomp_profile_is_optional() {
+        let manager = LanguageConfigManager::from_json(&sample_json(256)).unwrap();
+        assert_eq!(manager.get_seccomp_profile(&Language::Python), None);
+
+        let json = sample_json(256).replace(r#""cpu_limit": 0.5"#, r#""cpu_limit": 0.5, "seccomp_profile": "default""#);
+        let manager = LanguageConfigManager::from_json(&json).unwrap();
+        assert_eq!(manager.get_seccomp_profile(&Language::Python), Some("default".to_string()));
+    }
+}
@@ -0,0 +1,108 @@
+//! Container Labels - What Is Actually Running on the Node
+//!
+//! **Responsibility:**
+//! Tag every container the engine creates with its language and worker, and
+//! enforce a per-language cap on live containers by counting those labels.
+//!
+//! **Why Labels:**
+//! The job and test semaphores only know about this worker's own futures.
+//! Docker knows what is really running - including containers leaked by a
+//! crashed worker or started by other workers sharing the daemon - so the
+//! cap (`max_containers` in languages.json) is checked against Docker.
+//!
+//! **Limits:**
+//! Count-then-create is not atomic: concurrent creators can overshoot the
+//! cap by a few containers. It is a backstop, not the primary limiter.
+
+use crate::docker_api;
+use crate::metrics;
+use anyhow::{bail, Context, Result};
+use bollard::container::ListContainersOptions;
+use bollard::Docker;
+use optimus_common::types::Language;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+pub const LABEL_MANAGED: &str = "optimus.managed";
+pub const LABEL_LANGUAGE: &str = "optimus.language";
+pub const LABEL_WORKER: &str = "optimus.worker";
+
+/// How often a capped language is re-counted while waiting for a slot
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+/// Give up (Docker execution error) after waiting this long
+const MAX_WAIT: Duration = Duration::from_secs(30);
+
+static WORKER_ID: OnceLock<String> = OnceLock::new();
+
+/// Record this worker's id for the `optimus.worker` label (call once at startup)
+pub fn init(worker_id: &str) {
+    let _ = WORKER_ID.set(worker_id.to_string());
+}
+
+/// Labels for a new container of `language`
+pub fn labels(language: &Language) -> HashMap<String, String> {
+    let worker = WORKER_ID.get().map_or("unknown", String::as_str);
+    HashMap::from([
+        (LABEL_MANAGED.to_string(), "true".to_string()),
+        (LABEL_LANGUAGE.to_string(), language.to_string()),
+        (LABEL_WORKER.to_string(), worker.to_string()),
+    ])
+}
+
+/// Labeled containers of `language` that are created or running (any worker)
+pub async fn count_live(docker: &Docker, language: &Language) -> Result<usize> {
+    let filters = HashMap::from([
+        ("label".to_string(), vec![format!("{}={}", LABEL_LANGUAGE, language)]),
+        ("status".to_string(), vec!["created".to_string(), "running".to_string()]),
+    ]);
+    let options = ListContainersOptions::<String> {
+        all: true,
+        filters,
+        ..Default::default()
+    };
+    let containers = docker_api::call("list_containers", || docker.list_containers(Some(options.clone())))
+        .await
+        .context("Failed to list labeled containers")?;
+    Ok(containers.len())
+}
+
+/// Wait until fewer than `max` containers of `language` are live
+pub async fn wait_for_slot(docker: &Docker, language: &Language, max: usize) -> Result<()> {
+    let started = Instant::now();
+    let mut waited = false;
+    loop {
+        let live = count_live(docker, language).await?;
+        if live < max {
+            return Ok(());
+        }
+        if !waited {
+            waited = true;
+            metrics::CONTAINER_CAP_WAITS.with_label_values(&[&language.to_string()]).inc();
+        }
+        if started.elapsed() >= MAX_WAIT {
+            bail!(
+                "{} containers of '{}' already running (max_containers {}) after waiting {}s",
+                live,
+                language,
+                max,
+                MAX_WAIT.as_secs()
+            );
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_labels() {
+        init("worker-a");
+        let labels = labels(&Language::Python);
+        assert_eq!(labels.get(LABEL_MANAGED).map(String::as_str), Some("true"));
+        assert_eq!(labels.get(LABEL_LANGUAGE).map(String::as_str), Some("python"));
+        assert_eq!(labels.get(LABEL_WORKER).map(String::as_str), Some("worker-a"));
+    }
+}
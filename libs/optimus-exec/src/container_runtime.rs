@@ -0,0 +1,175 @@
+//! Container Runtime - Docker, Podman, a Local Sandbox, Firecracker, Kubernetes or containerd
+//!
+//! **Responsibility:**
+//! Pick the container daemon the worker talks to and open a client for it.
+//!
+//! **Selection:**
+//! - `OPTIMUS_CONTAINER_RUNTIME=docker` (default): the local dockerd socket
+//!   (`DOCKER_HOST` is honoured)
+//! - `OPTIMUS_CONTAINER_RUNTIME=podman`: Podman's Docker-compatible API
+//!   socket, from `CONTAINER_HOST`, else the rootless socket under
+//!   `$XDG_RUNTIME_DIR`, else the rootful `/run/podman/podman.sock`
+//!   (start it with `podman system service`)
+//! - `OPTIMUS_CONTAINER_RUNTIME=nsjail` or `bwrap`: no daemon at all - each
+//!   test runs in an nsjail/bubblewrap sandbox on the host (`sandbox.rs`)
+//! - `OPTIMUS_CONTAINER_RUNTIME=firecracker`: no daemon either - each test
+//!   boots a Firecracker microVM from the language's rootfs (`firecracker.rs`)
+//! - `OPTIMUS_CONTAINER_RUNTIME=kubernetes` (or `k8s`): each test runs as a
+//!   Kubernetes Job in the worker's namespace (`kubernetes.rs`)
+//! - `OPTIMUS_CONTAINER_RUNTIME=containerd`: each test runs as a containerd
+//!   task, driven through containerd's gRPC socket (`containerd.rs`)
+//!
+//! Docker and Podman speak the Docker Engine API, so one bollard client
+//! serves either; the Podman-specific container settings live in `podman.rs`.
+//! The sandboxes and Firecracker have no API to connect to; Kubernetes and
+//! containerd have their own clients.
+
+use anyhow::{bail, Context, Result};
+use bollard::models::SystemInfoCgroupVersionEnum;
+use bollard::{Docker, API_DEFAULT_VERSION};
+use std::fmt;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Seconds before a request to the daemon is abandoned (bollard's default)
+const REQUEST_TIMEOUT_SECS: u64 = 120;
+
+const ROOTFUL_PODMAN_SOCKET: &str = "/run/podman/podman.sock";
+
+static SELECTED: OnceLock<ContainerRuntime> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContainerRuntime {
+    Docker,
+    Podman,
+    Nsjail,
+    Bubblewrap,
+    Firecracker,
+    Kubernetes,
+    Containerd,
+}
+
+impl fmt::Display for ContainerRuntime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContainerRuntime::Docker => write!(f, "docker"),
+            ContainerRuntime::Podman => write!(f, "podman"),
+            ContainerRuntime::Nsjail => write!(f, "nsjail"),
+            ContainerRuntime::Bubblewrap => write!(f, "bwrap"),
+            ContainerRuntime::Firecracker => write!(f, "firecracker"),
+            ContainerRuntime::Kubernetes => write!(f, "kubernetes"),
+            ContainerRuntime::Containerd => write!(f, "containerd"),
+        }
+    }
+}
+
+impl ContainerRuntime {
+    /// Parse `OPTIMUS_CONTAINER_RUNTIME` (unset means Docker)
+    pub fn from_env() -> Result<Self> {
+        match std::env::var("OPTIMUS_CONTAINER_RUNTIME") {
+            Ok(value) => Self::parse(&value),
+            Err(_) => Ok(ContainerRuntime::Docker),
+        }
+    }
+
+    fn parse(value: &str) -> Result<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "" | "docker" => Ok(ContainerRuntime::Docker),
+            "podman" => Ok(ContainerRuntime::Podman),
+            "nsjail" => Ok(ContainerRuntime::Nsjail),
+            "bwrap" | "bubblewrap" => Ok(ContainerRuntime::Bubblewrap),
+            "firecracker" => Ok(ContainerRuntime::Firecracker),
+            "kubernetes" | "k8s" => Ok(ContainerRuntime::Kubernetes),
+            "containerd" => Ok(ContainerRuntime::Containerd),
+            other => bail!(
+                "Unknown OPTIMUS_CONTAINER_RUNTIME '{}' (expected docker, podman, nsjail, bwrap, firecracker, kubernetes or containerd)",
+                other
+            ),
+        }
+    }
+
+    /// Open a client for this runtime's API socket
+    ///
+    /// Connecting is lazy: a missing daemon only shows up on the first call.
+    pub fn connect(self) -> Result<Docker> {
+        match self {
+            ContainerRuntime::Docker => Docker::connect_with_local_defaults().context("Failed to connect to Docker daemon"),
+            ContainerRuntime::Podman => {
+                let socket = podman_socket();
+                Docker::connect_with_socket(&socket, REQUEST_TIMEOUT_SECS, API_DEFAULT_VERSION)
+                    .with_context(|| format!("Failed to connect to Podman socket {}", socket))
+            }
+            ContainerRuntime::Nsjail
+            | ContainerRuntime::Bubblewrap
+            | ContainerRuntime::Firecracker
+            | ContainerRuntime::Kubernetes => {
+                bail!("{} runs without a container daemon - there is no API to connect to", self)
+            }
+            ContainerRuntime::Containerd => bail!("containerd is driven through its own gRPC API, not the Docker API"),
+        }
+    }
+
+    /// Whether this runtime works without a Docker API daemon (host
+    /// sandboxes, microVMs, Kubernetes Jobs and containerd): no images for
+    /// the Docker engines to pull or prune here
+    pub fn is_daemonless(self) -> bool {
+        !matches!(self, ContainerRuntime::Docker | ContainerRuntime::Podman)
+    }
+
+    /// Confirm the daemon answers and can enforce container limits
+    ///
+    /// Podman on cgroups v1 accepts memory/CPU limits it cannot apply
+    /// rootless, so it is refused rather than running submissions unbounded.
+    pub async fn check(self, docker: &Docker) -> Result<()> {
+        let info = docker.info().await.with_context(|| format!("Failed to reach the {} API", self))?;
+        if self == ContainerRuntime::Podman && info.cgroup_version == Some(SystemInfoCgroupVersionEnum::_1) {
+            bail!("Podman is running on cgroups v1 - container memory and CPU limits need cgroups v2");
+        }
+        Ok(())
+    }
+}
+
+/// Record the runtime chosen at startup (later calls are ignored)
+pub fn init(runtime: ContainerRuntime) {
+    let _ = SELECTED.set(runtime);
+}
+
+/// The runtime chosen at startup (Docker if `init` never ran)
+pub fn selected() -> ContainerRuntime {
+    *SELECTED.get_or_init(|| ContainerRuntime::Docker)
+}
+
+/// Client for the selected runtime
+pub fn connect() -> Result<Docker> {
+    selected().connect()
+}
+
+/// Podman API socket path, following Podman's own lookup
+fn podman_socket() -> String {
+    if let Ok(host) = std::env::var("CONTAINER_HOST") {
+        return host.strip_prefix("unix://").unwrap_or(&host).to_string();
+    }
+    if let Ok(runtime_dir) = std::env::var("XDG_RUNTIME_DIR") {
+        let rootless = Path::new(&runtime_dir).join("podman/podman.sock");
+        if rootless.exists() {
+            return rootless.display().to_string();
+        }
+    }
+    ROOTFUL_PODMAN_SOCKET.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_runtime() {
+        assert_eq!(ContainerRuntime::parse("docker").unwrap(), ContainerRuntime::Docker);
+        assert_eq!(ContainerRuntime::parse("").unwrap(), ContainerRuntime::Docker);
+        assert_eq!(ContainerRuntime::parse(" Podman ").unwrap(), ContainerRuntime::Podman);
+        assert_eq!(ContainerRuntime::parse("bubblewrap").unwrap(), ContainerRuntime::Bubblewrap);
+        assert_eq!(ContainerRuntime::parse("k8s").unwrap(), ContainerRuntime::Kubernetes);
+        assert_eq!(ContainerRuntime::parse("containerd").unwrap(), ContainerRuntime::Containerd);
+        assert!(ContainerRuntime::parse("cri").is_err());
+    }
+}
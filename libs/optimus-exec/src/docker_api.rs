@@ -0,0 +1,121 @@
+//! Docker API Limiter - Bounded Concurrency for Daemon Calls
+//!
+//! **Responsibility:**
+//! Keep the worker from flooding the Docker daemon under high concurrency.
+//!
+//! **Behaviour:**
+//! - At most `DOCKER_MAX_CONCURRENT_CALLS` request/response calls in flight
+//!   (process-wide, independent of the job semaphore)
+//! - Daemon 5xx responses are retried with exponential backoff; the permit
+//!   is released while backing off
+//! - Calls that have to wait for a permit are counted as saturation
+//!
+//! Long-lived streams (log following) are not gated - they would hold a
+//! permit for the whole execution and starve short calls.
+
+use crate::metrics;
+use bollard::errors::Error as DockerError;
+use std::future::Future;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::sync::{Semaphore, SemaphorePermit};
+use tracing::warn;
+
+const DEFAULT_MAX_CONCURRENT_CALLS: usize = 16;
+const MAX_RETRIES: u32 = 3;
+const BASE_BACKOFF_MS: u64 = 100;
+
+static LIMITER: OnceLock<Semaphore> = OnceLock::new();
+
+/// Size the limiter (call once at startup; later calls are ignored)
+pub fn init(max_concurrent_calls: usize) {
+    let _ = LIMITER.set(Semaphore::new(max_concurrent_calls.max(1)));
+}
+
+fn limiter() -> &'static Semaphore {
+    LIMITER.get_or_init(|| Semaphore::new(DEFAULT_MAX_CONCURRENT_CALLS))
+}
+
+/// Permit for one in-flight Docker call; keeps the in-flight gauge accurate
+pub struct CallPermit {
+    _permit: SemaphorePermit<'static>,
+}
+
+impl Drop for CallPermit {
+    fn drop(&mut self) {
+        metrics::DOCKER_CALLS_IN_FLIGHT.dec();
+    }
+}
+
+/// Wait for a free Docker call slot
+pub async fn acquire(op: &'static str) -> CallPermit {
+    let permit = match limiter().try_acquire() {
+        Ok(permit) => permit,
+        Err(_) => {
+            metrics::DOCKER_CALLS_SATURATED.with_label_values(&[op]).inc();
+            limiter().acquire().await.expect("docker limiter is never closed")
+        }
+    };
+    metrics::DOCKER_CALLS_IN_FLIGHT.inc();
+    CallPermit { _permit: permit }
+}
+
+/// Daemon-side failures worth retrying
+fn is_retryable(error: &DockerError) -> bool {
+    matches!(error, DockerError::DockerResponseServerError { status_code, .. } if *status_code >= 500)
+}
+
+/// Backoff before retry `attempt` (1-based): 100ms, 200ms, 400ms
+fn backoff(attempt: u32) -> Duration {
+    Duration::from_millis(BASE_BACKOFF_MS << attempt.saturating_sub(1))
+}
+
+/// Run a Docker API call under the limiter, retrying daemon 5xx responses
+pub async fn call<T, F, Fut>(op: &'static str, mut f: F) -> Result<T, DockerError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, DockerError>>,
+{
+    let mut attempt = 0;
+    loop {
+        let result = {
+            let _permit = acquire(op).await;
+            f().await
+        };
+
+        match result {
+            Err(e) if is_retryable(&e) && attempt < MAX_RETRIES => {
+                attempt += 1;
+                metrics::DOCKER_CALL_RETRIES.with_label_values(&[op]).inc();
+                warn!(op = op, attempt = attempt, error = %e, "Docker daemon error - retrying");
+                tokio::time::sleep(backoff(attempt)).await;
+            }
+            Err(e) => {
+                metrics::DOCKER_CALL_FAILURES.with_label_values(&[op]).inc();
+                return Err(e);
+            }
+            ok => return ok,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_server_errors_are_retried() {
+        let server = DockerError::DockerResponseServerError { status_code: 500, message: "busy".to_string() };
+        let not_found = DockerError::DockerResponseServerError { status_code: 404, message: "no such container".to_string() };
+
+        assert!(is_retryable(&server));
+        assert!(!is_retryable(&not_found));
+    }
+
+    #[test]
+    fn test_backoff_doubles() {
+        assert_eq!(backoff(1), Duration::from_millis(100));
+        assert_eq!(backoff(2), Duration::from_millis(200));
+        assert_eq!(backoff(3), Duration::from_millis(400));
+    }
+}
@@ -0,0 +1,2284 @@
+//! Execution Engine - Abstraction for Code Execution
+//!
+//! **Core Responsibility:**
+//! Execute source code with test inputs and capture raw outputs.
+//!
+//! **Critical Architectural Boundary:**
+//! - Engine knows HOW to execute (Docker, local, sandbox, etc.)
+//! - Engine does NOT know scoring rules
+//! - Engine does NOT evaluate correctness
+//! - Engine returns raw outputs for Evaluator to judge
+//!
+//! **Why This Exists:**
+//! Enables swappable execution backends without touching scoring logic.
+//! Production uses DockerEngine with language-aware configuration, or
+//! PodmanEngine (`podman.rs`) on Docker-less nodes; SandboxEngine
+//! (`sandbox.rs`) and FirecrackerEngine (`firecracker.rs`) run without any
+//! container daemon; ContainerdEngine (`containerd.rs`) drives containerd
+//! directly.
+
+use crate::archive::{self, ProjectBundle};
+use crate::evaluator::TestExecutionOutput;
+use crate::firecracker::FirecrackerEngine;
+use crate::kubernetes::KubernetesEngine;
+use crate::config::{FilesystemConfig, GpuConfig, KillDiagnostics, LanguageConfigManager, ProcessLimits, SecurityConfig};
+use crate::container_labels;
+use crate::containerd::ContainerdEngine;
+use crate::container_runtime::{self, ContainerRuntime};
+use crate::docker_api;
+use crate::gpu;
+use crate::metrics;
+use crate::output_budget;
+use crate::phases::{self, PhaseTimings};
+use crate::podman::PodmanEngine;
+use crate::sandbox::SandboxEngine;
+use crate::sandbox_audit;
+use crate::seccomp;
+use optimus_common::types::{validate_args_env, DiagnosticBundle, FixtureFile, JobProgress, JobRequest, JobStatus, Language, ProjectArchive, TestCase, TimeLimitMode};
+use bollard::{Docker, container::Config, image::CreateImageOptions, container::{CreateContainerOptions, DownloadFromContainerOptions, StartContainerOptions, WaitContainerOptions, RemoveContainerOptions}};
+use bollard::container::{AttachContainerOptions, LogOutput};
+use futures_util::stream::StreamExt;
+use bollard::exec::{CreateExecOptions, StartExecResults};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tokio::sync::Semaphore;
+use anyhow::{Context, Result, bail};
+use async_trait::async_trait;
+use base64::{Engine as _, engine::general_purpose};
+use tracing::{debug, info, warn};
+
+/// Safety limits to prevent pathological inputs from reaching Docker
+const MAX_SOURCE_CODE_BYTES: usize = 1024 * 1024; // 1MB
+const MAX_TEST_INPUT_BYTES: usize = optimus_common::types::MAX_TEST_INPUT_BYTES; // 10MB, shared with the API
+
+/// Directory the runner executes from; fixtures are placed here
+const FIXTURE_DIR: &str = "code";
+
+/// Compiled builds larger than this aren't cached (they still run normally)
+pub const MAX_BUILD_ARTIFACT_BYTES: usize = 16 * 1024 * 1024;
+
+/// Success stamp of the runner's build directory (`/code/.build`) inside a build tar
+pub const BUILD_STAMP_ENTRY: &str = ".build/.complete";
+
+/// Wall-clock limit for a compile-only run (compilation isn't billed to a test)
+const BUILD_TIMEOUT_MS: u64 = 30_000;
+
+/// File the runner leaves the program's CPU time in (microseconds)
+const CPU_TIME_FILE: &str = "/code/.cpu_usec";
+
+/// Where the source is copied in for the runner (see dockerfiles/runner.sh);
+/// tar entry name, relative to `/`. Test input is streamed over stdin instead.
+pub const SOURCE_ENTRY: &str = "tmp/optimus/source";
+
+/// Payload entry holding the test's input (see `payload_archive`)
+pub const STDIN_ENTRY: &str = "tmp/optimus/.stdin";
+
+/// tar reads whole records: a padded payload unpacks without waiting for
+/// stdin to close
+pub const TAR_RECORD_BYTES: usize = 10240;
+
+/// Per-test containers leave the CPU time and a compile-only run's build
+/// here for the worker: an anonymous volume, which unlike the tmpfs mounts
+/// is still readable once the container has stopped
+const RESULTS_DIR: &str = "/opt/optimus/results";
+const RESULT_CPU_TIME: &str = "/opt/optimus/results/.cpu_usec";
+const RESULT_BUILD: &str = "/opt/optimus/results/.build";
+
+/// Entry of per-test containers, run as root: unpack the payload from stdin,
+/// run the runner as the images' `optimus` user on the test's input, then
+/// copy what the worker reads back into `RESULTS_DIR`. A payload that doesn't
+/// unpack is a harness error (`RUNNER_EXIT_HARNESS_ERROR`).
+const CONTAINER_SCRIPT: &str = r#"tar -xf - -C / || exit 203
+chown -R optimus: /code /tmp/optimus
+setpriv --reuid=optimus --regid=optimus --init-groups -- /opt/optimus/runner.sh "$@" < /tmp/optimus/.stdin
+status=$?
+if [ -f /code/.cpu_usec ] && [ ! -L /code/.cpu_usec ]; then
+    head -c 64 /code/.cpu_usec > /opt/optimus/results/.cpu_usec
+fi
+if [ -n "$BUILD_ONLY" ] && [ $status -eq 0 ]; then
+    cp -a /code/.build /opt/optimus/results/
+fi
+exit $status"#;
+
+/// What the per-test entry script needs as root (unpack into directories
+/// `optimus` owns, hand them over, switch users); the runner and program
+/// run as `optimus` and keep none of them
+const ENTRY_CAPABILITIES: &[&str] = &["CHOWN", "DAC_OVERRIDE", "FOWNER", "SETUID", "SETGID"];
+
+/// Wall-clock limit for unpacking the source into a job container
+const STAGE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Universal runner and init inside every language image (see dockerfiles/Dockerfile.base)
+pub const RUNNER_PATH: &str = "/opt/optimus/runner.sh";
+pub const JOB_CONTAINER_INIT: &str = "/opt/optimus/tini";
+
+/// With CPU-time limits, the wall clock only stops programs that hang:
+/// they get this many times `timeout_ms` before being killed
+const CPU_MODE_WALL_FACTOR: u64 = 3;
+
+/// How long a killed container gets to stop before it is force-removed
+const KILL_GRACE: Duration = Duration::from_secs(10);
+
+/// Runner exit-code contract (see dockerfiles/runner.sh)
+/// Any other non-zero code is the program's own exit status (runtime failure)
+pub const RUNNER_EXIT_COMPILE_ERROR: i64 = 201;
+pub const RUNNER_EXIT_WALL_TIMEOUT: i64 = 202;
+pub const RUNNER_EXIT_HARNESS_ERROR: i64 = 203;
+
+/// Memoization key for a test case execution
+///
+/// Two test cases with the same key produce the same container run, so only
+/// the first one is executed. Expected output is deliberately excluded -
+/// comparison happens later in the evaluator. Fixtures, args and env are
+/// part of the key since they change what the program sees.
+fn dedup_key(test_case: &TestCase) -> String {
+    if test_case.fixtures.is_empty() && test_case.args.is_empty() && test_case.env.is_empty() {
+        return test_case.input.clone();
+    }
+    // Sorted so HashMap iteration order can't split identical tests
+    let env: BTreeMap<_, _> = test_case.env.iter().collect();
+    let extras = serde_json::to_string(&(&test_case.fixtures, &test_case.args, env)).unwrap_or_default();
+    format!("{}\0{}", test_case.input, extras)
+}
+
+/// Compiled build of a job's source, from the Redis cache or a compile-only run
+///
+/// Ok(None) for interpreted languages, project archives and infrastructure
+/// failures - tests then compile in their own containers exactly as without
+/// the cache. Err carries the compile-only run's output when the source
+/// doesn't compile, so every test reports that without running.
+async fn prepare_build(
+    job: &JobRequest,
+    engine: &dyn ExecutionEngine,
+    redis_conn: &mut redis::aio::ConnectionManager,
+    phases: &mut PhaseTimings,
+) -> Result<Option<Vec<u8>>, TestExecutionOutput> {
+    if !job.language.is_compiled() || job.archive.is_some() {
+        return Ok(None);
+    }
+    let Some(version) = engine.language_version(&job.language) else {
+        return Ok(None);
+    };
+    let hash = build_hash(&job.language, &version, &job.source_code);
+    let language = job.language.to_string();
+
+    match optimus_common::redis::get_artifact(redis_conn, &hash).await {
+        Ok(Some(artifact)) => {
+            println!("  ✓ Build cache hit ({} bytes)", artifact.len());
+            metrics::BUILD_CACHE.with_label_values(&[&language, "hit"]).inc();
+            return Ok(Some(artifact));
+        }
+        Ok(None) => {}
+        Err(e) => eprintln!("  ⚠ Failed to read build cache: {}", e),
+    }
+    metrics::BUILD_CACHE.with_label_values(&[&language, "miss"]).inc();
+
+    let mut output = match engine.build(&job.language, &job.source_code, ResourceOverrides::from_job(job)).await {
+        Ok(output) => {
+            phases.merge(&output.phases);
+            output
+        }
+        Err(e) => {
+            eprintln!("  ⚠ Compile-only run failed: {} (compiling per test)", e);
+            return Ok(None);
+        }
+    };
+    if output.compile_error {
+        return Err(output);
+    }
+    // Timeouts, OOMs and oversized builds: let each test compile for itself
+    let Some(artifact) = output.build_artifact.take() else {
+        return Ok(None);
+    };
+    println!("  ✓ Compiled once for all tests ({} bytes)", artifact.len());
+    if let Err(e) = optimus_common::redis::store_artifact(redis_conn, &hash, &artifact).await {
+        eprintln!("  ⚠ Failed to cache build: {}", e);
+    }
+    Ok(Some(artifact))
+}
+
+/// Publishes a running job's progress to its status key (see `JobProgress`)
+/// and each finished test to its partial results list
+///
+/// Partial results are judged with the Evaluator's per-test rules, so they
+/// match what the final result will say. Writes happen under the lock so a
+/// slow write can't replace a newer count; failures are only logged since
+/// both are informational.
+struct ProgressReporter {
+    job_id: uuid::Uuid,
+    /// Tests each unique test provides output for (itself, then its duplicates)
+    groups: HashMap<u32, Vec<u32>>,
+    progress: tokio::sync::Mutex<JobProgress>,
+}
+
+impl ProgressReporter {
+    fn new(job: &JobRequest) -> Self {
+        let mut first: HashMap<String, u32> = HashMap::new();
+        let mut groups: HashMap<u32, Vec<u32>> = HashMap::new();
+        for test_case in &job.test_cases {
+            let id = *first.entry(dedup_key(test_case)).or_insert(test_case.id);
+            groups.entry(id).or_default().push(test_case.id);
+        }
+        Self {
+            job_id: job.id,
+            groups,
+            progress: tokio::sync::Mutex::new(JobProgress {
+                state: JobStatus::Running,
+                tests_completed: 0,
+                tests_total: job.test_cases.len(),
+                current_test_started_at: None,
+            }),
+        }
+    }
+
+    async fn publish(&self, progress: &JobProgress, redis_conn: &mut redis::aio::ConnectionManager) {
+        if let Err(e) = optimus_common::redis::set_job_progress(redis_conn, &self.job_id, progress).await {
+            eprintln!("  ⚠ Failed to publish job progress: {}", e);
+        }
+    }
+
+    /// Reset (a retried job starts over)
+    async fn start(&self, redis_conn: &mut redis::aio::ConnectionManager) {
+        let progress = self.progress.lock().await;
+        if let Err(e) = optimus_common::redis::clear_partial_results(redis_conn, &self.job_id).await {
+            eprintln!("  ⚠ Failed to clear partial results: {}", e);
+        }
+        self.publish(&progress, redis_conn).await;
+    }
+
+    async fn test_started(&self, redis_conn: &mut redis::aio::ConnectionManager) {
+        let mut progress = self.progress.lock().await;
+        progress.current_test_started_at = Some(chrono::Utc::now().to_rfc3339());
+        self.publish(&progress, redis_conn).await;
+    }
+
+    /// Publish the results `output` stands for (the test and its duplicates)
+    async fn test_finished(&self, job: &JobRequest, output: &TestExecutionOutput, redis_conn: &mut redis::aio::ConnectionManager) {
+        let mut progress = self.progress.lock().await;
+        // A duplicate re-run after an infrastructure failure replaces its
+        // earlier result but was already counted
+        let (ids, counted) = match self.groups.get(&output.test_id) {
+            Some(ids) => (ids.as_slice(), true),
+            None => (std::slice::from_ref(&output.test_id), false),
+        };
+        let results: Vec<_> = ids
+            .iter()
+            .filter_map(|&id| {
+                let test_case = job.test_cases.iter().find(|test_case| test_case.id == id)?;
+                let mut output = output.clone();
+                output.deduplicated = id != output.test_id;
+                output.test_id = id;
+                Some(crate::evaluator::evaluate_job_test(job, &output, test_case))
+            })
+            .collect();
+        if let Err(e) = optimus_common::redis::append_partial_results(redis_conn, &self.job_id, &results).await {
+            eprintln!("  ⚠ Failed to publish partial results: {}", e);
+        }
+        if counted {
+            progress.tests_completed = (progress.tests_completed + ids.len()).min(progress.tests_total);
+            self.publish(&progress, redis_conn).await;
+        }
+    }
+}
+
+/// Execute a complete job using an ExecutionEngine (async version)
+///
+/// This function:
+/// 1. Picks the first test case of every distinct input
+/// 2. Runs those with up to `max_parallel_tests` containers at once,
+///    checking for cancellation before each one starts, publishing
+///    progress to the job's status key as tests start and finish, and
+///    appending each finished test's result to the job's partial results
+/// 3. Fans out outputs to duplicate test cases (marked `deduplicated`)
+/// 4. Collects raw outputs in test order (stopping at the first test
+///    skipped by cancellation)
+/// 5. Returns outputs for Evaluator
+///
+/// ## Arguments
+/// * `job` - The job to execute
+/// * `engine` - The execution engine to use (Docker, Podman, a local sandbox, Firecracker, Kubernetes or containerd)
+/// * `redis_conn` - Redis connection for cancellation checks and progress
+/// * `phases` - Accumulates the container phases of every run (deduplicated tests add nothing)
+/// * `max_parallel_tests` - Containers this job may run at once (`MAX_PARALLEL_TESTS`)
+/// * `per_job_container` - Run the tests one by one in a single shared
+///   container (`EXECUTION_MODE=per_job`) when the job has no project
+///   archive or fixtures; otherwise, and once that container is lost,
+///   tests get their own containers
+///
+/// ## Returns
+/// Vector of raw execution outputs (one per test case)
+pub async fn execute_job_async(
+    job: &JobRequest,
+    engine: &dyn ExecutionEngine,
+    redis_conn: &mut redis::aio::ConnectionManager,
+    phases: &mut PhaseTimings,
+    max_parallel_tests: usize,
+    per_job_container: bool,
+) -> Vec<TestExecutionOutput> {
+    let mut outputs = Vec::new();
+    let mut memo: HashMap<String, TestExecutionOutput> = HashMap::new();
+
+    println!("→ Executing {} test cases with Docker", job.test_cases.len());
+    println!("  Language: {}", job.language);
+    println!("  Timeout per test: {}ms", job.timeout_ms);
+    println!("  Parallel tests: {}", max_parallel_tests.max(1));
+    println!();
+
+    let progress = ProgressReporter::new(job);
+    progress.start(redis_conn).await;
+
+    // Project archives are unpacked and validated once per job
+    let project = match job.archive.as_ref().map(|a| engine.prepare_project(&job.language, a)).transpose() {
+        Ok(project) => project,
+        Err(e) => {
+            eprintln!("  ✗ Invalid project archive: {:#}", e);
+            return job
+                .test_cases
+                .iter()
+                .map(|test_case| TestExecutionOutput {
+                    test_id: test_case.id,
+                    stderr: format!("Invalid project archive: {:#}", e),
+                    runtime_error: true,
+                    ..Default::default()
+                })
+                .collect();
+        }
+    };
+
+    // Compiled languages build once per job (or not at all on a cache hit)
+    let build = match prepare_build(job, engine, redis_conn, phases).await {
+        Ok(build) => build,
+        Err(compile_failure) => {
+            println!("  ✗ Compilation error - skipping test runs");
+            return job
+                .test_cases
+                .iter()
+                .map(|test_case| TestExecutionOutput {
+                    test_id: test_case.id,
+                    ..compile_failure.clone()
+                })
+                .collect();
+        }
+    };
+    let build_cache = build.as_deref().map_or(BuildCache::Off, BuildCache::Reuse);
+
+    // First occurrence of each distinct test runs; duplicates wait for its output
+    let mut seen = HashSet::new();
+    let unique: Vec<&TestCase> = job
+        .test_cases
+        .iter()
+        .filter(|test_case| seen.insert(dedup_key(test_case)))
+        .collect();
+
+    // Fixtures and project files are per-test uploads, so those jobs keep per-test containers
+    let shared_eligible = per_job_container
+        && project.is_none()
+        && unique.iter().all(|test_case| test_case.fixtures.is_empty());
+    let job_container = if shared_eligible {
+        match engine.start_job_container(&job.language, &job.source_code, ResourceOverrides::from_job(job), build_cache).await {
+            Ok(container) => {
+                println!("  Mode: one container for the job");
+                phases.merge(&container.phases);
+                Some(container)
+            }
+            Err(e) => {
+                eprintln!("  ⚠ Failed to start job container: {:#} (one container per test)", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let runs = if let Some(container) = &job_container {
+        // Sequential: the tests share one container's CPU and memory limits
+        let mut runs = Vec::with_capacity(unique.len());
+        for test_case in &unique {
+            runs.push(run_test(job, engine, test_case, None, build_cache, Some(container), &progress, redis_conn).await);
+        }
+        runs
+    } else {
+        // Up to `max_parallel_tests` containers at once; join_all keeps test order
+        let permits = Semaphore::new(max_parallel_tests.max(1));
+        futures_util::future::join_all(unique.iter().map(|test_case| {
+            let permits = &permits;
+            let progress = &progress;
+            let project = project.as_ref();
+            let mut redis_conn = redis_conn.clone();
+            async move {
+                let _permit = permits.acquire().await.expect("test semaphore is never closed");
+                run_test(job, engine, test_case, project, build_cache, None, progress, &mut redis_conn).await
+            }
+        }))
+        .await
+    };
+    drop(job_container);
+    let mut runs: HashMap<u32, Option<(TestExecutionOutput, bool)>> = unique
+        .iter()
+        .map(|test_case| test_case.id)
+        .zip(runs)
+        .collect();
+
+    // Assemble in test order, stopping at the first test skipped by cancellation
+    for test_case in &job.test_cases {
+        // Reuse output from an identical earlier test instead of spawning a container
+        if let Some(previous) = memo.get(&dedup_key(test_case)) {
+            println!("  Test {} duplicates test {} - reusing output", test_case.id, previous.test_id);
+            let mut output = previous.clone();
+            output.test_id = test_case.id;
+            output.deduplicated = true;
+            outputs.push(output);
+            continue;
+        }
+
+        // Duplicates of a test that hit an infrastructure failure get their own attempt
+        let run = match runs.remove(&test_case.id) {
+            Some(run) => run,
+            None => run_test(job, engine, test_case, project.as_ref(), build_cache, None, &progress, redis_conn).await,
+        };
+        let Some((output, memoizable)) = run else {
+            println!("  ⚠ Job cancelled - stopping execution");
+            println!("    Completed {} of {} tests before cancellation", outputs.len(), job.test_cases.len());
+            break;
+        };
+
+        phases.merge(&output.phases);
+        if memoizable {
+            memo.insert(dedup_key(test_case), output.clone());
+        }
+        outputs.push(output);
+    }
+
+    println!();
+    println!("→ All test cases executed");
+
+    outputs
+}
+
+/// Run one test case in its own container, or in `job_container` while it is alive
+///
+/// Returns the output and whether duplicates may reuse it (infrastructure
+/// failures are not memoized), or None if the job was cancelled first.
+#[allow(clippy::too_many_arguments)]
+async fn run_test(
+    job: &JobRequest,
+    engine: &dyn ExecutionEngine,
+    test_case: &TestCase,
+    project: Option<&ProjectBundle>,
+    build_cache: BuildCache<'_>,
+    job_container: Option<&JobContainer<'_>>,
+    progress: &ProgressReporter,
+    redis_conn: &mut redis::aio::ConnectionManager,
+) -> Option<(TestExecutionOutput, bool)> {
+    // Check for cancellation before starting the container
+    match optimus_common::redis::is_job_cancelled(redis_conn, &job.id).await {
+        Ok(true) => return None,
+        Ok(false) => {
+            // Not cancelled, continue
+        }
+        Err(e) => {
+            eprintln!("  ⚠ Failed to check cancellation status: {}", e);
+            // Continue execution on error to avoid false cancellations
+        }
+    }
+
+    println!("  Executing test {}", test_case.id);
+    progress.test_started(redis_conn).await;
+
+    // Execute with Docker engine
+    let result = match job_container.filter(|container| container.is_alive()) {
+        Some(container) => {
+            engine.execute_in_job_container(container, &job.language, test_case, wall_timeout_ms(job)).await
+        }
+        None => engine.execute_in_container(
+            &job.language,
+            &job.source_code,
+            test_case,
+            project,
+            wall_timeout_ms(job),
+            ResourceOverrides::from_job(job),
+            build_cache,
+        ).await,
+    };
+
+    // Infrastructure failures are not memoized - duplicates get their own attempt
+    let memoizable = result.is_ok();
+
+    let mut output = match result {
+        Ok(output) => output,
+        Err(e) => {
+            eprintln!("    ✗ Test {}: Docker execution error: {}", test_case.id, e);
+            TestExecutionOutput {
+                test_id: test_case.id,
+                stdout: String::new(),
+                stderr: format!("Docker execution error: {}", e),
+                execution_time_ms: 0,
+                timed_out: false,
+                runtime_error: true,
+                memory_limit_exceeded: false,
+                compile_error: false,
+                internal_error: false,
+                exit_code: None,
+                cpu_time_ms: None,
+                deduplicated: false,
+                sandbox_flags: Vec::new(),
+                stdout_truncated: false,
+                stderr_truncated: false,
+                build_artifact: None,
+                diagnostics: None,
+                phases: PhaseTimings::default(),
+            }
+        }
+    };
+
+    // Set correct test_id
+    output.test_id = test_case.id;
+    if let Some(bundle) = output.diagnostics.take() {
+        println!("    Test {}: kill diagnostics captured ({} bytes of logs, {} files)", test_case.id, bundle.log_tail.len(), bundle.files.len());
+        if let Err(e) = optimus_common::redis::store_diagnostics(redis_conn, &job.id, &bundle).await {
+            eprintln!("    ⚠ Failed to store kill diagnostics: {}", e);
+        }
+    }
+    if job.time_limit_mode == TimeLimitMode::Cpu {
+        apply_cpu_time_limit(&mut output, job.timeout_ms);
+    }
+    progress.test_finished(job, &output, redis_conn).await;
+
+    // Tests may finish out of order, so every line names its test
+    let id = test_case.id;
+    println!("    Test {}: execution time {}ms", id, output.execution_time_ms);
+    if let Some(cpu_time_ms) = output.cpu_time_ms {
+        println!("    Test {}: CPU time {}ms", id, cpu_time_ms);
+    }
+    if output.timed_out {
+        println!("    ⚠ Test {}: timed out", id);
+    }
+    if output.runtime_error {
+        println!("    ✗ Test {}: runtime error", id);
+    }
+    if output.memory_limit_exceeded {
+        println!("    ✗ Test {}: memory limit exceeded (OOM killed)", id);
+    }
+    if output.compile_error {
+        println!("    ✗ Test {}: compilation error", id);
+    }
+    if output.internal_error {
+        println!("    ✗ Test {}: runner harness error", id);
+    }
+    if !output.stderr.is_empty() {
+        println!("    Test {} stderr: {}", id, output.stderr.lines().next().unwrap_or(""));
+    }
+
+    Some((output, memoizable))
+}
+
+/// Container cleanup guard - guarantees container removal on drop
+/// This ensures containers are cleaned up even if execution panics or is cancelled
+struct ContainerGuard<'a> {
+    docker: &'a Docker,
+    container_id: String,
+}
+
+impl<'a> ContainerGuard<'a> {
+    fn new(docker: &'a Docker, container_id: String) -> Self {
+        Self { docker, container_id }
+    }
+}
+
+impl<'a> Drop for ContainerGuard<'a> {
+    fn drop(&mut self) {
+        // Best-effort cleanup - cannot be async in Drop
+        // Log if cleanup fails but don't panic
+        let container_id = self.container_id.clone();
+        let docker = self.docker.clone();
+        
+        spawn_cleanup(async move {
+            // `v` takes the results volume with it
+            let remove_options = RemoveContainerOptions {
+                force: true,
+                v: true,
+                ..Default::default()
+            };
+            
+            let removed = docker_api::call("remove_container", || {
+                docker.remove_container(&container_id, Some(remove_options))
+            })
+            .await;
+            if let Err(e) = removed {
+                eprintln!("⚠ Failed to cleanup container {}: {}", container_id, e);
+            }
+        });
+    }
+}
+
+/// Removals started by dropped guards that may still be running
+static PENDING_CLEANUPS: Mutex<Vec<tokio::task::JoinHandle<()>>> = Mutex::new(Vec::new());
+
+/// Run a guard's cleanup in the background, tracked so shutdown can wait
+/// for it (see `finish_cleanups`)
+pub fn spawn_cleanup<F>(cleanup: F)
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    let handle = tokio::spawn(cleanup);
+    let mut pending = PENDING_CLEANUPS.lock().unwrap_or_else(|e| e.into_inner());
+    pending.retain(|handle| !handle.is_finished());
+    pending.push(handle);
+}
+
+/// Wait up to `timeout` for the cleanups started so far
+///
+/// Call before the process exits: shutting down the runtime cancels spawned
+/// removals, leaving their containers behind. Returns how many were still
+/// running at the deadline.
+pub async fn finish_cleanups(timeout: Duration) -> usize {
+    let handles = std::mem::take(&mut *PENDING_CLEANUPS.lock().unwrap_or_else(|e| e.into_inner()));
+    let mut remaining = handles.len();
+    let _ = tokio::time::timeout(timeout, async {
+        for handle in handles {
+            let _ = handle.await;
+            remaining -= 1;
+        }
+    })
+    .await;
+    remaining
+}
+
+/// One container shared by all of a job's tests (`EXECUTION_MODE=per_job`)
+///
+/// Started once with an idle entrypoint; every test then runs the universal
+/// runner through `docker exec`. The container is removed when this drops.
+pub struct JobContainer<'a> {
+    guard: ContainerGuard<'a>,
+    /// A cached or in-container build is present, so tests skip compilation
+    precompiled: bool,
+    /// Cleared once a hard timeout killed the container - remaining tests
+    /// fall back to their own containers
+    alive: AtomicBool,
+    /// Sandbox flags already reported, so each test only gets what it added
+    reported_flags: Mutex<HashSet<String>>,
+    /// Image check, create and in-container compile
+    pub phases: PhaseTimings,
+}
+
+impl JobContainer<'_> {
+    fn id(&self) -> &str {
+        &self.guard.container_id
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.alive.load(Ordering::Relaxed)
+    }
+}
+
+/// Output of one exec that finished within its time limit
+struct ExecRun {
+    stdout: String,
+    stderr: String,
+    stdout_truncated: bool,
+    stderr_truncated: bool,
+    exit_code: Option<i64>,
+}
+
+/// A backend that runs submissions in sandboxed containers
+///
+/// Implementations only produce raw outputs; scoring stays in the evaluator.
+/// Pick one with `connect` (`OPTIMUS_CONTAINER_RUNTIME`).
+#[async_trait]
+pub trait ExecutionEngine: Send + Sync {
+    /// Toolchain version of a language (part of the build cache key)
+    fn language_version(&self, language: &Language) -> Option<String>;
+
+    /// Validate a project archive and resolve its entry command
+    fn prepare_project(&self, language: &Language, project: &ProjectArchive) -> Result<ProjectBundle>;
+
+    /// Run one test case in a fresh container
+    #[allow(clippy::too_many_arguments)]
+    async fn execute_in_container(
+        &self,
+        language: &Language,
+        source_code: &str,
+        test_case: &TestCase,
+        project: Option<&ProjectBundle>,
+        timeout_ms: u64,
+        overrides: ResourceOverrides,
+        build: BuildCache<'_>,
+    ) -> Result<TestExecutionOutput>;
+
+    /// Compile `source_code` in a throwaway container
+    /// On success the build directory comes back as a tar in `build_artifact`
+    /// (unless it's too large to cache)
+    async fn build(&self, language: &Language, source_code: &str, overrides: ResourceOverrides) -> Result<TestExecutionOutput> {
+        self.execute_in_container(language, source_code, &TestCase::default(), None, BUILD_TIMEOUT_MS, overrides, BuildCache::Build)
+            .await
+    }
+
+    /// Start a container shared by all of a job's tests (`EXECUTION_MODE=per_job`)
+    async fn start_job_container<'a>(
+        &'a self,
+        language: &Language,
+        source_code: &str,
+        overrides: ResourceOverrides,
+        build: BuildCache<'_>,
+    ) -> Result<JobContainer<'a>>;
+
+    /// Run one test case inside a job's shared container
+    async fn execute_in_job_container(
+        &self,
+        container: &JobContainer<'_>,
+        language: &Language,
+        test_case: &TestCase,
+        timeout_ms: u64,
+    ) -> Result<TestExecutionOutput>;
+}
+
+/// Engine for the container runtime selected at startup
+pub fn connect(config_manager: &LanguageConfigManager) -> Result<Box<dyn ExecutionEngine>> {
+    Ok(match container_runtime::selected() {
+        ContainerRuntime::Docker => Box::new(DockerEngine::new_with_config(config_manager)?),
+        ContainerRuntime::Podman => Box::new(PodmanEngine::new_with_config(config_manager)?),
+        runtime @ (ContainerRuntime::Nsjail | ContainerRuntime::Bubblewrap) => {
+            Box::new(SandboxEngine::new_with_config(runtime, config_manager)?)
+        }
+        ContainerRuntime::Firecracker => Box::new(FirecrackerEngine::new_with_config(config_manager)?),
+        ContainerRuntime::Kubernetes => Box::new(KubernetesEngine::new_with_config(config_manager)),
+        ContainerRuntime::Containerd => Box::new(ContainerdEngine::new_with_config(config_manager)?),
+    })
+}
+
+/// Docker-based execution engine for real sandboxed code execution
+///
+/// **Docker Execution Rules:**
+/// 1. Pulls language-specific Docker image if not present
+/// 2. Creates container with security constraints:
+///    - Network disabled
+///    - CPU/memory limits enforced
+///    - Read-only filesystem (where possible)
+/// 3. Copies source code in as a file (tar upload) and streams test input to stdin
+/// 4. Captures stdout/stderr streams
+/// 5. Measures execution time
+/// 6. Handles timeouts and runtime errors
+/// 7. Cleans up container after execution
+///
+/// **Purpose:**
+/// Production-grade sandboxed execution with resource isolation
+pub struct DockerEngine {
+    docker: Docker,
+    config_manager: Option<LanguageConfigManager>,
+    /// Explicit network mode for every container, for daemons that don't
+    /// honour `NetworkDisabled` alone
+    network_mode: Option<&'static str>,
+}
+
+impl DockerEngine {
+    /// Create a new Docker engine with language config manager
+    pub fn new_with_config(config_manager: &LanguageConfigManager) -> Result<Self> {
+        let docker = ContainerRuntime::Docker.connect()?;
+        Ok(DockerEngine::with_client(docker, config_manager))
+    }
+
+    /// Engine over any Docker Engine API client (e.g. Podman's compatible socket)
+    pub fn with_client(docker: Docker, config_manager: &LanguageConfigManager) -> Self {
+        DockerEngine {
+            docker,
+            config_manager: Some(config_manager.clone()),
+            network_mode: None,
+        }
+    }
+
+    /// Set `HostConfig.NetworkMode` on every container this engine creates
+    pub fn with_network_mode(mut self, network_mode: &'static str) -> Self {
+        self.network_mode = Some(network_mode);
+        self
+    }
+
+    /// Get the Docker image name for a language
+    fn get_image_name(&self, language: &Language) -> String {
+        // Try config manager first, fallback to hardcoded values
+        if let Some(ref config) = self.config_manager {
+            if let Ok(image) = config.get_image(language) {
+                return image;
+            }
+        }
+        
+        // Fallback to hardcoded defaults
+        match language {
+            Language::Python => "optimus-python:latest".to_string(),
+            Language::Java => "optimus-java:latest".to_string(),
+            Language::Rust => "optimus-rust:latest".to_string(),
+        }
+    }
+
+    /// Get memory limit for a language (a job-level override wins)
+    fn get_memory_limit(&self, language: &Language, override_mb: Option<u32>) -> i64 {
+        if let Some(limit_mb) = override_mb {
+            return (limit_mb as i64) * 1024 * 1024;
+        }
+        if let Some(ref config) = self.config_manager {
+            if let Ok(limit_mb) = config.get_memory_limit_mb(language) {
+                return (limit_mb as i64) * 1024 * 1024;
+            }
+        }
+        256 * 1024 * 1024 // Default: 256MB
+    }
+
+    /// Get CPU limit for a language (a job-level override wins)
+    fn get_cpu_limit(&self, language: &Language, override_cores: Option<f64>) -> i64 {
+        if let Some(cores) = override_cores {
+            return (cores * 1_000_000_000.0) as i64;
+        }
+        if let Some(ref config) = self.config_manager {
+            if let Ok(limit) = config.get_cpu_limit(language) {
+                return (limit * 1_000_000_000.0) as i64;
+            }
+        }
+        500_000_000 // Default: 0.5 CPU
+    }
+
+    /// Container runtime configured for a language (None for the daemon default)
+    fn get_runtime(&self, language: &Language) -> Option<String> {
+        self.config_manager.as_ref()?.get_runtime(language)
+    }
+
+    /// `HostConfig.SecurityOpt`: the language's seccomp profile and
+    /// `no-new-privileges` (None keeps the daemon defaults)
+    fn get_security_opt(&self, language: &Language, security: &SecurityConfig) -> Result<Option<Vec<String>>> {
+        let mut opts = Vec::new();
+        if let Some(profile) = self.config_manager.as_ref().and_then(|c| c.get_seccomp_profile(language)) {
+            opts.push(seccomp::security_opt(&profile)?);
+        }
+        if !security.legacy_privileges {
+            opts.push("no-new-privileges:true".to_string());
+        }
+        Ok((!opts.is_empty()).then_some(opts))
+    }
+
+    fn security(&self, language: &Language) -> SecurityConfig {
+        self.config_manager
+            .as_ref()
+            .map(|config| config.get_security(language))
+            .unwrap_or_default()
+    }
+
+    /// GPU settings when this container gets GPUs: the language has them and
+    /// the job asked for one, or the language gives them to every job
+    fn get_gpu(&self, language: &Language, requested: bool) -> Option<GpuConfig> {
+        self.config_manager
+            .as_ref()?
+            .get_gpu(language)
+            .filter(|gpu| requested || gpu.all_jobs)
+    }
+
+    /// Ensure Docker image is available (pull if needed)
+    /// 
+    /// **Image Cache Health Check:**
+    /// - Verifies image exists locally before execution
+    /// - Pulls synchronously if missing (prevents execution failure)
+    /// - Logs cache hits/misses for observability
+    async fn ensure_image(&self, image: &str) -> Result<()> {
+        // Image cache health check
+        let inspect_result = docker_api::call("inspect_image", || self.docker.inspect_image(image)).await;
+        
+        if inspect_result.is_ok() {
+            // Cache hit - image is already present
+            debug!("✓ Image cache hit: {}", image);
+            return Ok(());
+        }
+
+        // Cache miss - need to pull the image
+        warn!("⚠ Image cache miss: {} (pulling now)", image);
+        
+        let options = Some(CreateImageOptions {
+            from_image: image,
+            ..Default::default()
+        });
+
+        let mut stream = self.docker.create_image(options, None, None);
+        
+        while let Some(result) = stream.next().await {
+            result.context("Failed to pull Docker image")?;
+        }
+
+        info!("✓ Image pulled successfully: {}", image);
+        Ok(())
+    }
+
+    /// Whether Docker recorded an OOM kill in the container's cgroup
+    /// An inspect failure counts as no: the exit stays a runtime error
+    async fn was_oom_killed(&self, container_id: &str) -> bool {
+        match docker_api::call("inspect_container", || self.docker.inspect_container(container_id, None)).await {
+            Ok(info) => info.state.and_then(|state| state.oom_killed).unwrap_or(false),
+            Err(e) => {
+                warn!(container_id = %container_id, error = %e, "Failed to inspect container for OOM kill");
+                false
+            }
+        }
+    }
+
+    /// Kill a container whose hard timeout fired and wait (up to `KILL_GRACE`)
+    /// for `stopped` to resolve, recording the kill latency and outcome
+    ///
+    /// A failed kill or a container that outlives the grace window is
+    /// force-removed. Returns `stopped`'s output if the container went down.
+    async fn kill_after_timeout<F: Future>(
+        &self,
+        container_id: &str,
+        language: &Language,
+        fired: Instant,
+        stopped: F,
+    ) -> Option<F::Output> {
+        let language = language.to_string();
+        let killed = docker_api::call("kill_container", || {
+            self.docker.kill_container(container_id, None::<bollard::container::KillContainerOptions<String>>)
+        })
+        .await;
+        let result = match killed {
+            // 409: it already exited on its own
+            Ok(()) | Err(bollard::errors::Error::DockerResponseServerError { status_code: 409, .. }) => {
+                match tokio::time::timeout(KILL_GRACE, stopped).await {
+                    Ok(output) => {
+                        metrics::TIMEOUT_KILLS.with_label_values(&[&language, "killed"]).inc();
+                        metrics::KILL_LATENCY_MS
+                            .with_label_values(&[&language])
+                            .observe(fired.elapsed().as_millis() as f64);
+                        return Some(output);
+                    }
+                    Err(_) => {
+                        warn!(container_id = %container_id, grace_ms = KILL_GRACE.as_millis() as u64, "Killed container did not stop - force-removing");
+                        None
+                    }
+                }
+            }
+            Err(e) => {
+                eprintln!("    ⚠ Failed to kill timed-out container: {}", e);
+                None
+            }
+        };
+
+        let remove_options = RemoveContainerOptions { force: true, ..Default::default() };
+        let removed = docker_api::call("remove_container", || {
+            self.docker.remove_container(container_id, Some(remove_options))
+        })
+        .await;
+        match removed {
+            Ok(()) => {
+                metrics::TIMEOUT_KILLS.with_label_values(&[&language, "force_removed"]).inc();
+                metrics::KILL_LATENCY_MS
+                    .with_label_values(&[&language])
+                    .observe(fired.elapsed().as_millis() as f64);
+            }
+            Err(e) => {
+                warn!(container_id = %container_id, error = %e, "Failed to force-remove timed-out container");
+                metrics::TIMEOUT_KILLS.with_label_values(&[&language, "failed"]).inc();
+            }
+        }
+        result
+    }
+
+    /// Download the build a compile-only run left in the results volume
+    /// (a stopped container is fine)
+    async fn collect_build(&self, container_id: &str) -> Option<Vec<u8>> {
+        let mut tar = Vec::new();
+        let mut stream = {
+            let _permit = docker_api::acquire("download_from_container").await;
+            self.docker.download_from_container(container_id, Some(DownloadFromContainerOptions { path: RESULT_BUILD }))
+        };
+        while let Some(chunk) = stream.next().await {
+            match chunk {
+                Ok(bytes) if tar.len() + bytes.len() <= MAX_BUILD_ARTIFACT_BYTES => tar.extend_from_slice(&bytes),
+                Ok(_) => {
+                    debug!(container_id = %container_id, "Build exceeds {} bytes - not caching", MAX_BUILD_ARTIFACT_BYTES);
+                    return None;
+                }
+                Err(e) => {
+                    warn!(container_id = %container_id, error = %e, "Failed to download build");
+                    return None;
+                }
+            }
+        }
+        has_build_stamp(&tar).then_some(tar)
+    }
+
+    /// Exec `cmd` in a running container with `stdin` as its input and capture its output
+    /// Ok(None) when `timeout` ran out first (the exec is still running)
+    async fn run_exec(
+        &self,
+        container_id: &str,
+        cmd: Vec<String>,
+        env: Vec<String>,
+        stdin: &[u8],
+        timeout: Duration,
+    ) -> Result<Option<ExecRun>> {
+        let mut output_buffer = output_budget::collector().await;
+
+        let options = CreateExecOptions {
+            attach_stdin: Some(true),
+            attach_stdout: Some(true),
+            attach_stderr: Some(true),
+            cmd: Some(cmd),
+            env: Some(env),
+            ..Default::default()
+        };
+        let exec = docker_api::call("create_exec", || self.docker.create_exec(container_id, options.clone()))
+            .await
+            .context("Failed to create exec")?;
+        let started = docker_api::call("start_exec", || self.docker.start_exec(&exec.id, None))
+            .await
+            .context("Failed to start exec")?;
+        let StartExecResults::Attached { mut output, input } = started else {
+            bail!("Exec started detached");
+        };
+        let stdin_feed = feed_stdin(input, stdin.to_vec());
+
+        let execution_future = async {
+            let mut stdout = output_budget::CapturedStream::stdout();
+            let mut stderr = output_budget::CapturedStream::stderr();
+            while let Some(chunk) = output.next().await {
+                match chunk {
+                    Ok(LogOutput::StdOut { message }) => stdout.push(&message, &mut output_buffer),
+                    Ok(LogOutput::StdErr { message }) => stderr.push(&message, &mut output_buffer),
+                    Err(e) => {
+                        eprintln!("⚠ Error reading exec output: {}", e);
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+            (stdout.finish(), stderr.finish())
+        };
+
+        let completed = tokio::time::timeout(timeout, execution_future).await;
+        stdin_feed.abort();
+        let Ok(((stdout, stdout_truncated), (mut stderr, stderr_truncated))) = completed else {
+            return Ok(None);
+        };
+        if output_buffer.truncated() {
+            stderr.push_str(output_budget::TRUNCATION_MARKER);
+        }
+
+        let exit_code = docker_api::call("inspect_exec", || self.docker.inspect_exec(&exec.id))
+            .await
+            .context("Failed to inspect exec")?
+            .exit_code;
+
+        Ok(Some(ExecRun {
+            stdout,
+            stderr,
+            stdout_truncated,
+            stderr_truncated,
+            exit_code,
+        }))
+    }
+
+    fn process_limits(&self, language: &Language) -> ProcessLimits {
+        self.config_manager
+            .as_ref()
+            .map(|config| config.get_process_limits(language))
+            .unwrap_or_default()
+    }
+
+    fn filesystem(&self, language: &Language) -> FilesystemConfig {
+        self.config_manager
+            .as_ref()
+            .map(|config| config.get_filesystem(language))
+            .unwrap_or_default()
+    }
+
+    fn max_containers(&self, language: &Language) -> Option<u32> {
+        self.config_manager.as_ref()?.get_max_containers(language)
+    }
+
+    fn kill_diagnostics(&self, language: &Language) -> Option<KillDiagnostics> {
+        self.config_manager.as_ref()?.get_kill_diagnostics(language)
+    }
+
+    /// Collect a diagnostic bundle from a killed container within its grace window
+    /// Whatever was gathered when the window closes is kept and flagged `incomplete`
+    async fn capture_diagnostics(&self, container_id: &str, test_id: u32, settings: &KillDiagnostics) -> DiagnosticBundle {
+        let mut bundle = DiagnosticBundle {
+            test_id,
+            log_tail: String::new(),
+            exit_code: None,
+            oom_killed: false,
+            finished_at: None,
+            files: BTreeMap::new(),
+            incomplete: false,
+            captured_at: chrono::Utc::now().to_rfc3339(),
+        };
+
+        let grace = Duration::from_millis(settings.grace_ms);
+        let collect = self.collect_diagnostics(container_id, settings, &mut bundle);
+        if tokio::time::timeout(grace, collect).await.is_err() {
+            warn!(container_id = %container_id, grace_ms = settings.grace_ms, "Kill diagnostics grace window ran out");
+            bundle.incomplete = true;
+        }
+        bundle
+    }
+
+    async fn collect_diagnostics(&self, container_id: &str, settings: &KillDiagnostics, bundle: &mut DiagnosticBundle) {
+        // Exit info (the kill has been sent; wait for it to land so the state is final)
+        {
+            let _permit = docker_api::acquire("wait_container").await;
+            let wait_options = WaitContainerOptions { condition: "not-running" };
+            let _ = self.docker.wait_container(container_id, Some(wait_options)).next().await;
+        }
+        match docker_api::call("inspect_container", || self.docker.inspect_container(container_id, None)).await {
+            Ok(info) => {
+                if let Some(state) = info.state {
+                    bundle.exit_code = state.exit_code;
+                    bundle.oom_killed = state.oom_killed.unwrap_or(false);
+                    bundle.finished_at = state.finished_at;
+                }
+            }
+            Err(e) => warn!(container_id = %container_id, error = %e, "Failed to inspect killed container"),
+        }
+
+        // Log tail (the follow stream was dropped with the timed-out future, so read it again)
+        let limit = settings.log_tail_kb * 1024;
+        let mut tail = Vec::new();
+        let logs_options = Some(bollard::container::LogsOptions::<String> {
+            stdout: true,
+            stderr: true,
+            ..Default::default()
+        });
+        let mut logs_stream = {
+            let _permit = docker_api::acquire("logs").await;
+            self.docker.logs(container_id, logs_options)
+        };
+        while let Some(output) = logs_stream.next().await {
+            match output {
+                Ok(LogOutput::StdOut { message }) | Ok(LogOutput::StdErr { message }) => keep_tail(&mut tail, &message, limit),
+                Ok(_) => {}
+                Err(e) => {
+                    warn!(container_id = %container_id, error = %e, "Failed to read killed container logs");
+                    break;
+                }
+            }
+        }
+        bundle.log_tail = String::from_utf8_lossy(&tail).into_owned();
+
+        // Requested files, skipping anything that would leave the working directory
+        for name in &settings.files {
+            if name.starts_with('/') || name.split('/').any(|part| part == "..") {
+                warn!(file = %name, "Ignoring kill diagnostics file outside the working directory");
+                continue;
+            }
+            let path = format!("/{}/{}", FIXTURE_DIR, name);
+            if let Some(content) = self.download_file(container_id, &path, KillDiagnostics::MAX_DIAGNOSTIC_FILE_BYTES).await {
+                bundle.files.insert(name.clone(), general_purpose::STANDARD.encode(content));
+            }
+        }
+    }
+
+    /// Contents of one regular file in the container, cut to `limit` bytes
+    async fn download_file(&self, container_id: &str, path: &str, limit: usize) -> Option<Vec<u8>> {
+        let mut tar = Vec::new();
+        let mut stream = {
+            let _permit = docker_api::acquire("download_from_container").await;
+            self.docker.download_from_container(container_id, Some(DownloadFromContainerOptions { path }))
+        };
+        // Headers and padding on top of the capped content
+        let tar_limit = limit + 64 * 1024;
+        while let Some(chunk) = stream.next().await {
+            let bytes = chunk.ok()?;
+            let room = tar_limit.saturating_sub(tar.len());
+            tar.extend_from_slice(&bytes[..bytes.len().min(room)]);
+        }
+        read_tar_file(&tar, limit)
+    }
+
+    /// Program CPU time recorded by the runner, from the results volume
+    /// (a stopped container is fine)
+    async fn read_cpu_time(&self, container_id: &str) -> Option<u64> {
+        let mut tar = Vec::new();
+        let mut stream = {
+            let _permit = docker_api::acquire("download_from_container").await;
+            self.docker.download_from_container(container_id, Some(DownloadFromContainerOptions { path: RESULT_CPU_TIME }))
+        };
+        while let Some(chunk) = stream.next().await {
+            tar.extend_from_slice(&chunk.ok()?);
+            if tar.len() > 64 * 1024 {
+                return None;
+            }
+        }
+        parse_cpu_time(&tar)
+    }
+
+    /// Program CPU time in a running job container, read with an exec
+    /// (the archive API can't see into its tmpfs mounts)
+    async fn read_job_cpu_time(&self, container_id: &str) -> Option<u64> {
+        let cmd = vec!["cat".to_string(), CPU_TIME_FILE.to_string()];
+        let run = self.run_exec(container_id, cmd, Vec::new(), b"", STAGE_TIMEOUT).await.ok()??;
+        if run.exit_code != Some(0) {
+            return None;
+        }
+        let usec: u64 = run.stdout.trim().parse().ok()?;
+        Some(usec / 1000)
+    }
+}
+
+#[async_trait]
+impl ExecutionEngine for DockerEngine {
+    /// Toolchain version of a language (part of the build cache key)
+    fn language_version(&self, language: &Language) -> Option<String> {
+        let config = self.config_manager.as_ref()?.get_config(language).ok()?;
+        Some(config.version)
+    }
+
+    /// Validate a project archive and resolve its entry command
+    fn prepare_project(&self, language: &Language, project: &ProjectArchive) -> Result<ProjectBundle> {
+        let default_command = self
+            .config_manager
+            .as_ref()
+            .and_then(|config| config.get_project_command(language).ok().flatten());
+        archive::prepare(project, default_command)
+    }
+
+    /// Execute code in Docker container with hardened safety guarantees
+    /// 
+    /// **Safety Guarantees:**
+    /// - Input validation: Rejects oversized source code or test inputs
+    /// - Hard timeout: Enforced via tokio::time::timeout, kills container on timeout
+    /// - Guaranteed cleanup: Container removed even on panic/cancellation via Drop guard
+    /// - Error classification: Distinguishes timeout, runtime error, and infrastructure failure
+    /// - Partial output capture: Captures stdout/stderr even on timeout
+    /// - Fixtures: Validated and unpacked into the working directory before
+    ///   the runner starts
+    /// - Projects: Unpacked into the working directory; the runner executes
+    ///   the entry command instead of `source_code`
+    /// - Filesystem: read-only root with size-capped tmpfs at `/tmp` and
+    ///   `/code` unless the language opts out; files arrive on stdin ahead of
+    ///   the input (`payload_archive`), results leave through a volume
+    /// - Args/env: Passed through the runner to the program (stdin still
+    ///   carries `input`); env may not shadow the runner's own variables
+    /// - Resources: `overrides` (per-job request or problem limits, already
+    ///   clamped by the API) replace the language's memory/CPU defaults
+    /// - GPUs: attached per the language's `gpu` settings when the job asked
+    ///   for them; every other container has GPUs masked
+    /// - Builds: `build` either compiles only and returns the build, or
+    ///   uploads a cached build so the runner skips compilation
+    #[allow(clippy::too_many_arguments)]
+    async fn execute_in_container(
+        &self,
+        language: &Language,
+        source_code: &str,
+        test_case: &TestCase,
+        project: Option<&ProjectBundle>,
+        timeout_ms: u64,
+        overrides: ResourceOverrides,
+        build: BuildCache<'_>,
+    ) -> Result<TestExecutionOutput> {
+        // GUARDRAIL 1: Validate input sizes
+        validate_test_payload(source_code, test_case)?;
+        let payload = payload_archive(source_code, test_case, project, build)?;
+
+        // GUARDRAIL 2: Reserve a log-collection slot before creating anything,
+        // so a burst of output-heavy jobs queues here instead of OOMing the worker
+        let mut output_buffer = output_budget::collector().await;
+
+        let image = self.get_image_name(language);
+        let container_name = format!("optimus-{}", uuid::Uuid::new_v4());
+
+        let mut phases = PhaseTimings::default();
+
+        // Ensure image is available
+        let image_check = Instant::now();
+        self.ensure_image(&image).await
+            .context(format!("Failed to ensure Docker image '{}' is available", image))?;
+        phases.add(phases::IMAGE_CHECK, image_check.elapsed());
+
+        // The image's entrypoint is the universal runner; the command becomes
+        // its arguments, which it forwards to the program
+        let cmd = test_case.args.clone();
+        
+        // Create container configuration with LANGUAGE env var for universal runner
+        let mut env = vec![
+            format!("LANGUAGE={}", format!("{}", language).to_lowercase()),
+            // Runner-side limit on the program itself; the hard timeout below stays the backstop
+            format!("WALL_TIMEOUT_MS={}", timeout_ms),
+            // The entry script starts as root; home is wherever the program may write
+            "HOME=/code".to_string(),
+        ];
+        if let Some(project) = project {
+            env.push(format!("ENTRY_COMMAND={}", project.entry_command));
+        }
+        match build {
+            BuildCache::Off => {}
+            BuildCache::Build => env.push("BUILD_ONLY=1".to_string()),
+            BuildCache::Reuse(_) => env.push("PRECOMPILED=1".to_string()),
+        }
+        env.extend(test_case.env.iter().map(|(name, value)| format!("{}={}", name, value)));
+
+        // Get resource limits from config
+        let memory_limit = self.get_memory_limit(language, overrides.memory_limit_mb);
+        let cpu_limit = self.get_cpu_limit(language, overrides.cpu_limit);
+        let gpu = self.get_gpu(language, overrides.gpu);
+        if gpu.is_none() {
+            env.push(gpu::MASK_ENV.to_string());
+        }
+        let security = self.security(language);
+        let security_opt = self.get_security_opt(language, &security)?;
+        let (cap_drop, cap_add) = capabilities(&security, ENTRY_CAPABILITIES);
+        let process_limits = self.process_limits(language);
+        let filesystem = self.filesystem(language);
+
+        let config = Config {
+            image: Some(image.clone()),
+            // Root only until the payload is unpacked; the runner drops to `optimus`
+            user: Some("0".to_string()),
+            entrypoint: Some(
+                [JOB_CONTAINER_INIT, "--", "/bin/sh", "-c", CONTAINER_SCRIPT, "optimus"]
+                    .map(String::from)
+                    .to_vec(),
+            ),
+            cmd: Some(cmd),
+            env: Some(env),
+            volumes: Some(HashMap::from([(RESULTS_DIR.to_string(), HashMap::new())])),
+            attach_stdin: Some(true),
+            attach_stdout: Some(true),
+            attach_stderr: Some(true),
+            // Input is written to stdin; Docker closes it once we do
+            open_stdin: Some(true),
+            stdin_once: Some(true),
+            labels: Some(container_labels::labels(language)),
+            network_disabled: Some(true), // SECURITY: No network access
+            host_config: Some(bollard::models::HostConfig {
+                memory: Some(memory_limit),
+                nano_cpus: Some(cpu_limit),
+                readonly_rootfs: Some(filesystem.readonly_rootfs),
+                tmpfs: tmpfs(&filesystem),
+                runtime: self.get_runtime(language),
+                security_opt,
+                cap_drop,
+                cap_add,
+                userns_mode: security.userns_mode.clone(),
+                pids_limit: Some(process_limits.pids_limit),
+                ulimits: Some(ulimits(&process_limits)),
+                network_mode: self.network_mode.map(str::to_string),
+                device_requests: gpu.map(|gpu| vec![gpu::device_request(&gpu, gpu::pinned_devices())]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        // Create container (once the language is under its cap on this Docker host)
+        let create_start = Instant::now();
+        if let Some(max) = self.max_containers(language) {
+            container_labels::wait_for_slot(&self.docker, language, max as usize).await?;
+        }
+        let create_options = CreateContainerOptions {
+            name: container_name.as_str(),
+            platform: None,
+        };
+
+        let container = docker_api::call("create_container", || {
+            self.docker.create_container(Some(create_options.clone()), config.clone())
+        })
+        .await
+        .context("Failed to create Docker container")?;
+
+        let container_id = container.id.clone();
+        
+        // CRITICAL: Set up cleanup guard immediately after container creation
+        // This guarantees cleanup even if we panic or get cancelled
+        let _guard = ContainerGuard::new(&self.docker, container_id.clone());
+
+        // Attach to stdin before start: the payload (source, project files,
+        // fixtures, cached build, then the test's input) goes in that way, so
+        // nothing has to be copied onto a read-only root filesystem
+        let attached = docker_api::call("attach_container", || {
+            self.docker.attach_container(
+                &container_id,
+                Some(AttachContainerOptions::<String> {
+                    stdin: Some(true),
+                    stream: Some(true),
+                    ..Default::default()
+                }),
+            )
+        })
+        .await
+        .context("Failed to attach to container stdin")?;
+
+        phases.add(phases::CONTAINER_CREATE, create_start.elapsed());
+
+        // Start container
+        let execute_start = Instant::now();
+        docker_api::call("start_container", || {
+            self.docker.start_container(&container_id, None::<StartContainerOptions<String>>)
+        })
+        .await
+        .context("Failed to start Docker container")?;
+        let stdin_feed = feed_stdin(attached.input, payload);
+
+        // Start execution timer (after start, so limiter queueing isn't billed to the job)
+        let start_time = Instant::now();
+
+        let mut timed_out = false;
+        let mut runtime_error = false;
+        let mut memory_limit_exceeded = false;
+        let mut compile_error = false;
+        let mut internal_error = false;
+        let mut sandbox_flags = Vec::new();
+        let mut diagnostics = None;
+
+        // HARD TIMEOUT: Wrap execution in tokio::time::timeout
+        let timeout_duration = Duration::from_millis(timeout_ms);
+        // Output seen so far, to measure how much kept streaming past a timeout
+        let streamed_bytes = AtomicU64::new(0);
+        
+        let execution_future = async {
+            let mut stdout = output_budget::CapturedStream::stdout();
+            let mut stderr = output_budget::CapturedStream::stderr();
+            let mut exit_code: Option<i64> = None;
+            
+            // Collect logs and wait for completion in parallel
+            let logs_options = Some(bollard::container::LogsOptions::<String> {
+                stdout: true,
+                stderr: true,
+                follow: true,
+                ..Default::default()
+            });
+            
+            // Not limited: the follow stream lives as long as the program runs
+            let mut logs_stream = self.docker.logs(&container_id, logs_options);
+            
+            // Collect all output (drained but discarded past the capture limit or output budget)
+            while let Some(output) = logs_stream.next().await {
+                match output {
+                    Ok(LogOutput::StdOut { message }) => {
+                        streamed_bytes.fetch_add(message.len() as u64, Ordering::Relaxed);
+                        stdout.push(&message, &mut output_buffer);
+                    }
+                    Ok(LogOutput::StdErr { message }) => {
+                        streamed_bytes.fetch_add(message.len() as u64, Ordering::Relaxed);
+                        stderr.push(&message, &mut output_buffer);
+                    }
+                    Err(e) => {
+                        eprintln!("⚠ Error reading container logs: {}", e);
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+            
+            // Get exit code
+            let wait_options = WaitContainerOptions {
+                condition: "not-running",
+            };
+            
+            let _permit = docker_api::acquire("wait_container").await;
+            let mut wait_stream = self.docker.wait_container(&container_id, Some(wait_options));
+            // bollard reports non-zero exits as an error carrying the code
+            match wait_stream.next().await {
+                Some(Ok(response)) => exit_code = Some(response.status_code),
+                Some(Err(bollard::errors::Error::DockerContainerWaitError { code, .. })) => exit_code = Some(code),
+                _ => {}
+            }
+            
+            let (stdout, stdout_truncated) = stdout.finish();
+            let (stderr, stderr_truncated) = stderr.finish();
+            (stdout, stderr, exit_code, stdout_truncated, stderr_truncated)
+        };
+
+        // Execute with hard timeout
+        let mut execution = Box::pin(execution_future);
+        let timeout_result = tokio::time::timeout(timeout_duration, &mut execution).await;
+        stdin_feed.abort();
+
+        let (stdout, mut stderr, exit_code, stdout_truncated, stderr_truncated) = match timeout_result {
+            Ok((out, mut err, code, out_truncated, err_truncated)) => {
+                // Execution completed within timeout
+                // Classify error type based on exit code
+                match code {
+                    None | Some(0) => {}
+                    Some(RUNNER_EXIT_COMPILE_ERROR) => compile_error = true,
+                    Some(RUNNER_EXIT_WALL_TIMEOUT) => timed_out = true,
+                    Some(RUNNER_EXIT_HARNESS_ERROR) => internal_error = true,
+                    Some(code) => {
+                        // SIGKILL is only an OOM if Docker saw the cgroup's OOM killer fire
+                        if code == 137 && self.was_oom_killed(&container_id).await {
+                            memory_limit_exceeded = true;
+                        } else {
+                            runtime_error = true;
+
+                            // Special handling for common signals
+                            if code == 137 {
+                                err.push_str("\n[Container killed: SIGKILL]");
+                            } else if code == 139 {
+                                err.push_str("\n[Container killed: segmentation fault]");
+                            }
+                        }
+                    }
+                }
+                
+                (out, err, code, out_truncated, err_truncated)
+            }
+            Err(_) => {
+                // TIMEOUT: Kill container immediately and capture partial output
+                timed_out = true;
+                let fired = Instant::now();
+                let bytes_at_timeout = streamed_bytes.load(Ordering::Relaxed);
+                
+                println!("    ⚠ Execution timed out after {}ms - killing container", timeout_ms);
+                
+                // Last chance to see what the program left running
+                sandbox_flags.extend(sandbox_audit::audit_running(&self.docker, &container_id).await);
+                
+                // Keep draining the logs until the container is down, so what it
+                // still wrote after the timeout is measured
+                if self.kill_after_timeout(&container_id, language, fired, &mut execution).await.is_some() {
+                    metrics::POST_TIMEOUT_OUTPUT_BYTES
+                        .with_label_values(&[&language.to_string()])
+                        .observe((streamed_bytes.load(Ordering::Relaxed) - bytes_at_timeout) as f64);
+                }
+                
+                // Opted-in languages get a grace window to salvage what the program left behind
+                if let Some(settings) = self.kill_diagnostics(language) {
+                    diagnostics = Some(self.capture_diagnostics(&container_id, test_case.id, &settings).await);
+                }
+                
+                // Return empty output with timeout message
+                (String::new(), String::from("\n[Execution timed out]"), None, false, false)
+            }
+        };
+
+        drop(execution);
+        let execution_time_ms = start_time.elapsed().as_millis() as u64;
+        phases.add(phases::EXECUTE, execute_start.elapsed());
+
+        // Container has stopped but isn't removed yet - its filesystem diff is still readable
+        sandbox_flags.extend(sandbox_audit::audit_filesystem(&self.docker, &container_id).await);
+        for flag in &sandbox_flags {
+            warn!(container = %container_name, language = %language, flag = %flag, "Sandbox escape heuristic fired");
+            metrics::SANDBOX_FLAGS
+                .with_label_values(&[&language.to_string(), flag.check.as_str()])
+                .inc();
+        }
+
+        if output_buffer.truncated() {
+            stderr.push_str(output_budget::TRUNCATION_MARKER);
+        }
+
+        // Only a clean compile-only run yields a build worth caching
+        let build_artifact = if matches!(build, BuildCache::Build) && exit_code == Some(0) {
+            self.collect_build(&container_id).await
+        } else {
+            None
+        };
+
+        // The runner writes it once the program exits - killed containers have none
+        let cpu_time_ms = match (build, exit_code) {
+            (BuildCache::Build, _) | (_, None) => None,
+            _ => self.read_cpu_time(&container_id).await,
+        };
+
+        // Container cleanup happens automatically via Drop guard
+        // No need for explicit cleanup here
+
+        Ok(TestExecutionOutput {
+            test_id: 0, // Will be set by executor
+            stdout,
+            stderr,
+            execution_time_ms,
+            timed_out,
+            runtime_error,
+            memory_limit_exceeded,
+            compile_error,
+            internal_error,
+            exit_code,
+            cpu_time_ms,
+            deduplicated: false,
+            sandbox_flags: sandbox_flags.iter().map(ToString::to_string).collect(),
+            stdout_truncated,
+            stderr_truncated,
+            build_artifact,
+            diagnostics,
+            phases,
+        })
+    }
+
+    /// Start a job's shared container and compile the source in it once
+    ///
+    /// A cached build is uploaded instead of compiling. A failed or timed-out
+    /// in-container compile is not an error: tests then compile for themselves
+    /// (and report the compile error) exactly as in per-test mode.
+    async fn start_job_container<'a>(
+        &'a self,
+        language: &Language,
+        source_code: &str,
+        overrides: ResourceOverrides,
+        build: BuildCache<'_>,
+    ) -> Result<JobContainer<'a>> {
+        if source_code.len() > MAX_SOURCE_CODE_BYTES {
+            bail!("Source code exceeds maximum size of {} bytes", MAX_SOURCE_CODE_BYTES);
+        }
+
+        let image = self.get_image_name(language);
+        let mut phases = PhaseTimings::default();
+
+        let image_check = Instant::now();
+        self.ensure_image(&image).await
+            .context(format!("Failed to ensure Docker image '{}' is available", image))?;
+        phases.add(phases::IMAGE_CHECK, image_check.elapsed());
+
+        // Idle under tini instead of running the runner; tests exec it one by one
+        let gpu = self.get_gpu(language, overrides.gpu);
+        let process_limits = self.process_limits(language);
+        let filesystem = self.filesystem(language);
+        let security = self.security(language);
+        // Runs as `optimus` throughout, so it needs no capabilities at all
+        let (cap_drop, cap_add) = capabilities(&security, &[]);
+        let mut env = vec!["HOME=/code".to_string()];
+        if gpu.is_none() {
+            env.push(gpu::MASK_ENV.to_string());
+        }
+        let config = Config {
+            image: Some(image.clone()),
+            entrypoint: Some(vec![
+                JOB_CONTAINER_INIT.to_string(),
+                "--".to_string(),
+                "sleep".to_string(),
+                "infinity".to_string(),
+            ]),
+            // GPU visibility is fixed at create time, so it can't go on the execs
+            env: Some(env),
+            labels: Some(container_labels::labels(language)),
+            network_disabled: Some(true), // SECURITY: No network access
+            host_config: Some(bollard::models::HostConfig {
+                memory: Some(self.get_memory_limit(language, overrides.memory_limit_mb)),
+                nano_cpus: Some(self.get_cpu_limit(language, overrides.cpu_limit)),
+                readonly_rootfs: Some(filesystem.readonly_rootfs),
+                tmpfs: tmpfs(&filesystem),
+                runtime: self.get_runtime(language),
+                security_opt: self.get_security_opt(language, &security)?,
+                cap_drop,
+                cap_add,
+                userns_mode: security.userns_mode.clone(),
+                pids_limit: Some(process_limits.pids_limit),
+                ulimits: Some(ulimits(&process_limits)),
+                network_mode: self.network_mode.map(str::to_string),
+                device_requests: gpu.map(|gpu| vec![gpu::device_request(&gpu, gpu::pinned_devices())]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let create_start = Instant::now();
+        if let Some(max) = self.max_containers(language) {
+            container_labels::wait_for_slot(&self.docker, language, max as usize).await?;
+        }
+        let container_name = format!("optimus-{}", uuid::Uuid::new_v4());
+        let create_options = CreateContainerOptions {
+            name: container_name.as_str(),
+            platform: None,
+        };
+        let container = docker_api::call("create_container", || {
+            self.docker.create_container(Some(create_options.clone()), config.clone())
+        })
+        .await
+        .context("Failed to create Docker container")?;
+        let guard = ContainerGuard::new(&self.docker, container.id);
+
+        docker_api::call("start_container", || {
+            self.docker.start_container(&guard.container_id, None::<StartContainerOptions<String>>)
+        })
+        .await
+        .context("Failed to start Docker container")?;
+
+        // Tests exec against this source; each streams its own input to stdin.
+        // Unpacked from inside: the archive API can't reach the tmpfs mounts.
+        let payload = payload_archive(source_code, &TestCase::default(), None, build)?;
+        let cmd = ["tar", "-xf", "-", "-C", "/"].map(String::from).to_vec();
+        match self.run_exec(&guard.container_id, cmd, Vec::new(), &payload, STAGE_TIMEOUT).await? {
+            Some(run) if run.exit_code == Some(0) => {}
+            Some(run) => bail!("Failed to copy source into container: {}", run.stderr.trim()),
+            None => bail!("Copying source into container timed out after {}s", STAGE_TIMEOUT.as_secs()),
+        }
+        phases.add(phases::CONTAINER_CREATE, create_start.elapsed());
+
+        let mut precompiled = matches!(build, BuildCache::Reuse(_));
+        if !precompiled && language.is_compiled() {
+            let compile_start = Instant::now();
+            let env = vec![
+                format!("LANGUAGE={}", language.to_string().to_lowercase()),
+                "BUILD_ONLY=1".to_string(),
+            ];
+            let run = self
+                .run_exec(&guard.container_id, vec![RUNNER_PATH.to_string()], env, b"", Duration::from_millis(BUILD_TIMEOUT_MS))
+                .await?;
+            phases.add(phases::EXECUTE, compile_start.elapsed());
+            match run {
+                Some(run) if run.exit_code == Some(0) => precompiled = true,
+                Some(_) => println!("  ⚠ In-container compile failed - tests compile for themselves"),
+                None => bail!("In-container compile timed out after {}ms", BUILD_TIMEOUT_MS),
+            }
+        }
+
+        Ok(JobContainer {
+            guard,
+            precompiled,
+            alive: AtomicBool::new(true),
+            reported_flags: Mutex::new(HashSet::new()),
+            phases,
+        })
+    }
+
+    /// Run one test inside a job's shared container
+    ///
+    /// Same guardrails and classification as `execute_in_container`, except:
+    /// - The source is already there; input goes to the exec's stdin
+    /// - A hard timeout kills the whole container (Docker cannot kill a
+    ///   single exec); the caller runs the remaining tests per container
+    /// - Kill diagnostics are not captured
+    /// - Sandbox flags are the ones this test added to the shared container
+    async fn execute_in_job_container(
+        &self,
+        container: &JobContainer<'_>,
+        language: &Language,
+        test_case: &TestCase,
+        timeout_ms: u64,
+    ) -> Result<TestExecutionOutput> {
+        if test_case.input.len() > MAX_TEST_INPUT_BYTES {
+            bail!("Test input exceeds maximum size of {} bytes", MAX_TEST_INPUT_BYTES);
+        }
+        validate_args_env(&test_case.args, &test_case.env)
+            .map_err(|e| anyhow::anyhow!("Invalid test parameters: {}", e))?;
+
+        let mut cmd = vec![RUNNER_PATH.to_string()];
+        cmd.extend(test_case.args.iter().cloned());
+
+        let mut env = vec![
+            format!("LANGUAGE={}", language.to_string().to_lowercase()),
+            format!("WALL_TIMEOUT_MS={}", timeout_ms),
+        ];
+        if container.precompiled {
+            env.push("PRECOMPILED=1".to_string());
+        }
+        env.extend(test_case.env.iter().map(|(name, value)| format!("{}={}", name, value)));
+
+        let mut phases = PhaseTimings::default();
+        let mut sandbox_flags = Vec::new();
+        let mut output = TestExecutionOutput::default();
+
+        let execute_start = Instant::now();
+        let run = self
+            .run_exec(container.id(), cmd, env, test_case.input.as_bytes(), Duration::from_millis(timeout_ms))
+            .await?;
+        output.execution_time_ms = execute_start.elapsed().as_millis() as u64;
+
+        match run {
+            Some(run) => {
+                output.stdout = run.stdout;
+                output.stderr = run.stderr;
+                output.stdout_truncated = run.stdout_truncated;
+                output.stderr_truncated = run.stderr_truncated;
+                output.exit_code = run.exit_code;
+                match run.exit_code {
+                    None | Some(0) => {}
+                    Some(RUNNER_EXIT_COMPILE_ERROR) => output.compile_error = true,
+                    Some(RUNNER_EXIT_WALL_TIMEOUT) => output.timed_out = true,
+                    Some(RUNNER_EXIT_HARNESS_ERROR) => output.internal_error = true,
+                    Some(code) => {
+                        if code == 137 && self.was_oom_killed(container.id()).await {
+                            output.memory_limit_exceeded = true;
+                        } else {
+                            output.runtime_error = true;
+                            if code == 137 {
+                                output.stderr.push_str("\n[Container killed: SIGKILL]");
+                            } else if code == 139 {
+                                output.stderr.push_str("\n[Container killed: segmentation fault]");
+                            }
+                        }
+                    }
+                }
+                if run.exit_code.is_some() {
+                    output.cpu_time_ms = self.read_job_cpu_time(container.id()).await;
+                }
+            }
+            None => {
+                let fired = Instant::now();
+                output.timed_out = true;
+                output.stderr = String::from("\n[Execution timed out]");
+                println!("    ⚠ Execution timed out after {}ms - killing job container", timeout_ms);
+
+                sandbox_flags.extend(sandbox_audit::audit_running(&self.docker, container.id()).await);
+                container.alive.store(false, Ordering::Relaxed);
+                let stopped = async {
+                    let _permit = docker_api::acquire("wait_container").await;
+                    let wait_options = WaitContainerOptions { condition: "not-running" };
+                    self.docker.wait_container(container.id(), Some(wait_options)).next().await
+                };
+                self.kill_after_timeout(container.id(), language, fired, stopped).await;
+            }
+        }
+        phases.add(phases::EXECUTE, execute_start.elapsed());
+
+        // The filesystem diff is cumulative - only flag what is new since the last test
+        sandbox_flags.extend(sandbox_audit::audit_filesystem(&self.docker, container.id()).await);
+        let mut reported = container.reported_flags.lock().expect("sandbox flag set is never poisoned");
+        for flag in &sandbox_flags {
+            if !reported.insert(flag.to_string()) {
+                continue;
+            }
+            warn!(container = %container.id(), language = %language, flag = %flag, "Sandbox escape heuristic fired");
+            metrics::SANDBOX_FLAGS
+                .with_label_values(&[&language.to_string(), flag.check.as_str()])
+                .inc();
+            output.sandbox_flags.push(flag.to_string());
+        }
+
+        output.phases = phases;
+        Ok(output)
+    }
+}
+
+
+/// How a container run uses the compile cache (compiled languages only)
+#[derive(Debug, Clone, Copy, Default)]
+pub enum BuildCache<'a> {
+    /// Compile as part of the run
+    #[default]
+    Off,
+    /// Compile only, then return the build in `TestExecutionOutput::build_artifact`
+    Build,
+    /// Upload this build (from `Build`) and skip compilation
+    Reuse(&'a [u8]),
+}
+
+/// Wall-clock limit for each test container of a job
+fn wall_timeout_ms(job: &JobRequest) -> u64 {
+    match job.time_limit_mode {
+        TimeLimitMode::Wall => job.timeout_ms,
+        TimeLimitMode::Cpu => job.timeout_ms * CPU_MODE_WALL_FACTOR,
+    }
+}
+
+/// Judge a CPU-time-limited test on the CPU time the runner measured
+/// Without a measurement the (extended) wall clock is all there is
+fn apply_cpu_time_limit(output: &mut TestExecutionOutput, limit_ms: u64) {
+    let over_limit = output.cpu_time_ms.is_some_and(|cpu_ms| cpu_ms > limit_ms);
+    if over_limit && !output.timed_out && !output.compile_error && !output.internal_error {
+        output.timed_out = true;
+        output.stderr.push_str(&format!("\n[CPU time limit of {}ms exceeded]", limit_ms));
+    }
+}
+
+/// CPU time from the runner's file in a downloaded tar (regular files only)
+fn parse_cpu_time(tar: &[u8]) -> Option<u64> {
+    use std::io::Read;
+
+    let mut archive = tar::Archive::new(tar);
+    let mut entry = archive.entries().ok()?.next()?.ok()?;
+    if !entry.header().entry_type().is_file() {
+        return None;
+    }
+    let mut contents = String::new();
+    entry.by_ref().take(64).read_to_string(&mut contents).ok()?;
+    let usec: u64 = contents.trim().parse().ok()?;
+    Some(usec / 1000)
+}
+
+/// First entry of a downloaded tar, if it is a regular file, cut to `limit` bytes
+/// (tolerates a tar truncated part-way through the content)
+fn read_tar_file(tar: &[u8], limit: usize) -> Option<Vec<u8>> {
+    use std::io::Read;
+
+    let mut archive = tar::Archive::new(tar);
+    let mut entry = archive.entries().ok()?.next()?.ok()?;
+    if !entry.header().entry_type().is_file() {
+        return None;
+    }
+    let mut contents = Vec::new();
+    let _ = entry.by_ref().take(limit as u64).read_to_end(&mut contents);
+    Some(contents)
+}
+
+/// Append `chunk`, keeping only the last `limit` bytes
+fn keep_tail(tail: &mut Vec<u8>, chunk: &[u8], limit: usize) {
+    tail.extend_from_slice(chunk);
+    if tail.len() > limit {
+        tail.drain(..tail.len() - limit);
+    }
+}
+
+/// Whether a build tar contains the runner's success stamp
+pub fn has_build_stamp(tar: &[u8]) -> bool {
+    let mut archive = tar::Archive::new(tar);
+    let Ok(entries) = archive.entries() else {
+        return false;
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .any(|entry| entry.path().is_ok_and(|path| path.as_os_str() == BUILD_STAMP_ENTRY))
+}
+
+/// Cache key for a compiled build: sha256 of language, toolchain version and source
+pub fn build_hash(language: &Language, version: &str, source_code: &str) -> String {
+    use sha2::{Digest, Sha256};
+
+    let mut hasher = Sha256::new();
+    for part in [language.to_string().as_str(), version, source_code] {
+        hasher.update(part.as_bytes());
+        hasher.update([0]);
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// Per-job container resources replacing the language defaults
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ResourceOverrides {
+    pub memory_limit_mb: Option<u32>,
+    pub cpu_limit: Option<f64>,
+    /// The job asked for GPUs (see `DockerEngine::get_gpu`)
+    pub gpu: bool,
+}
+
+impl ResourceOverrides {
+    pub fn from_job(job: &JobRequest) -> Self {
+        Self {
+            memory_limit_mb: job.memory_limit_mb,
+            cpu_limit: job.cpu_limit,
+            gpu: job.gpu,
+        }
+    }
+}
+
+/// `nofile`/`fsize`/`nproc` ulimits for a container (soft = hard, so the
+/// program can't raise them)
+fn ulimits(limits: &ProcessLimits) -> Vec<bollard::models::ResourcesUlimits> {
+    let ulimit = |name: &str, value: i64| bollard::models::ResourcesUlimits {
+        name: Some(name.to_string()),
+        soft: Some(value),
+        hard: Some(value),
+    };
+    let mut ulimits = vec![
+        ulimit("nofile", limits.nofile),
+        ulimit("fsize", limits.fsize_mb * 1024 * 1024),
+    ];
+    ulimits.extend(limits.nproc.map(|nproc| ulimit("nproc", nproc)));
+    ulimits
+}
+
+/// `HostConfig.CapDrop` / `CapAdd`: everything dropped except `needed`
+/// (both None in legacy mode, keeping the daemon's default set)
+fn capabilities(security: &SecurityConfig, needed: &[&str]) -> (Option<Vec<String>>, Option<Vec<String>>) {
+    if security.legacy_privileges {
+        return (None, None);
+    }
+    let cap_add = (!needed.is_empty()).then(|| needed.iter().map(|cap| cap.to_string()).collect());
+    (Some(vec!["ALL".to_string()]), cap_add)
+}
+
+/// `HostConfig.Tmpfs` for a read-only root filesystem (None keeps the
+/// writable layer). Docker mounts tmpfs `noexec` unless told otherwise, and
+/// compiled programs run from `/code`; tmpfs pages count towards the
+/// container's memory limit.
+fn tmpfs(filesystem: &FilesystemConfig) -> Option<HashMap<String, String>> {
+    if !filesystem.readonly_rootfs {
+        return None;
+    }
+    // size=0 would mean no limit at all
+    let options = |mb: u64| format!("rw,exec,nosuid,nodev,mode=1777,size={}m", mb.max(1));
+    Some(HashMap::from([
+        ("/tmp".to_string(), options(filesystem.tmp_mb)),
+        ("/code".to_string(), options(filesystem.work_dir_mb)),
+    ]))
+}
+
+/// Whether dockerd on this node has `runtime` registered (e.g. "kata" in
+/// daemon.json `runtimes`); containers asking for a missing one fail to create
+pub async fn runtime_available(docker: &Docker, runtime: &str) -> Result<bool> {
+    let info = docker_api::call("info", || docker.info())
+        .await
+        .context("Failed to query Docker info")?;
+    Ok(info.runtimes.is_some_and(|runtimes| runtimes.contains_key(runtime)))
+}
+
+/// Write `input` to an attached stdin, then close it so the program sees EOF
+///
+/// Runs as its own task: a program that never reads its input must not stall
+/// output collection. The caller aborts it once the run is over.
+pub fn feed_stdin(mut stdin: Pin<Box<dyn AsyncWrite + Send>>, input: Vec<u8>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        // Fails when the program exits without reading everything - not an error
+        if let Err(e) = stdin.write_all(&input).await {
+            debug!("stdin closed after part of the input: {}", e);
+            return;
+        }
+        if let Err(e) = stdin.shutdown().await {
+            debug!("failed to close stdin: {}", e);
+        }
+    })
+}
+
+/// Reject oversized source or input and args/env the runner can't take
+pub fn validate_test_payload(source_code: &str, test_case: &TestCase) -> Result<()> {
+    if source_code.len() > MAX_SOURCE_CODE_BYTES {
+        bail!("Source code exceeds maximum size of {} bytes", MAX_SOURCE_CODE_BYTES);
+    }
+    if test_case.input.len() > MAX_TEST_INPUT_BYTES {
+        bail!("Test input exceeds maximum size of {} bytes", MAX_TEST_INPUT_BYTES);
+    }
+    validate_args_env(&test_case.args, &test_case.env).map_err(|e| anyhow::anyhow!("Invalid test parameters: {}", e))
+}
+
+/// Tar of the runner's input files (entry name, content), extracted at `/`
+pub fn build_submission_archive(files: &[(&str, &str)]) -> Result<Vec<u8>> {
+    let mut builder = tar::Builder::new(Vec::new());
+
+    for (name, content) in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+
+        builder
+            .append_data(&mut header, name, content.as_bytes())
+            .context(format!("Failed to archive '{}'", name))?;
+    }
+
+    builder.into_inner().context("Failed to finalize submission archive")
+}
+
+/// Pack fixture files into a tar archive rooted at the runner's working directory
+///
+/// Each fixture is re-validated here so a request that bypassed the API
+/// still can't escape `/code`.
+pub fn build_fixture_archive(fixtures: &[FixtureFile]) -> Result<Vec<u8>> {
+    let mut builder = tar::Builder::new(Vec::new());
+
+    for fixture in fixtures {
+        let content = fixture
+            .validate()
+            .map_err(|e| anyhow::anyhow!("Invalid fixture: {}", e))?;
+
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+
+        builder
+            .append_data(&mut header, format!("{}/{}", FIXTURE_DIR, fixture.name), content.as_slice())
+            .context(format!("Failed to archive fixture '{}'", fixture.name))?;
+    }
+
+    builder.into_inner().context("Failed to finalize fixture archive")
+}
+
+/// Everything a test's container unpacks at `/` before the runner starts
+/// (source, project files, fixtures, cached build), then the test's input;
+/// padded to whole tar records
+pub fn payload_archive(
+    source_code: &str,
+    test_case: &TestCase,
+    project: Option<&ProjectBundle>,
+    build: BuildCache<'_>,
+) -> Result<Vec<u8>> {
+    let mut builder = tar::Builder::new(Vec::new());
+    append_archive(&mut builder, &build_submission_archive(&[(SOURCE_ENTRY, source_code)])?, "")?;
+    if let Some(project) = project {
+        append_archive(&mut builder, &project.tar, "").context("Failed to pack project files")?;
+    }
+    if !test_case.fixtures.is_empty() {
+        append_archive(&mut builder, &build_fixture_archive(&test_case.fixtures)?, "")
+            .context("Failed to pack fixture files")?;
+    }
+    if let BuildCache::Reuse(artifact) = build {
+        append_archive(&mut builder, artifact, "code/").context("Failed to pack cached build")?;
+    }
+    let mut header = tar::Header::new_gnu();
+    header.set_size(test_case.input.len() as u64);
+    header.set_mode(0o644);
+    builder
+        .append_data(&mut header, STDIN_ENTRY, test_case.input.as_bytes())
+        .context("Failed to pack test input")?;
+
+    let mut payload = builder.into_inner().context("Failed to finalize payload")?;
+    payload.resize(payload.len().div_ceil(TAR_RECORD_BYTES) * TAR_RECORD_BYTES, 0);
+    Ok(payload)
+}
+
+/// Copy the entries of `tar` under `prefix`, except `/code` and `/tmp`
+/// themselves: an unprivileged user can't change those directories
+fn append_archive(builder: &mut tar::Builder<Vec<u8>>, tar: &[u8], prefix: &str) -> Result<()> {
+    let mut archive = tar::Archive::new(tar);
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let path = format!("{}{}", prefix, entry.path()?.display());
+        let path = path.trim_start_matches("./").trim_end_matches('/');
+        if matches!(path, "" | "." | "code" | "tmp") {
+            continue;
+        }
+        let mut header = entry.header().clone();
+        builder.append_data(&mut header, path, &mut entry)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tar_of(paths: &[&str]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for path in paths {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(0);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, path, std::io::empty()).unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+
+    #[test]
+    fn test_build_hash_separates_fields() {
+        let hash = build_hash(&Language::Rust, "1.75", "fn main() {}");
+        assert_eq!(hash.len(), 64);
+        assert_eq!(hash, build_hash(&Language::Rust, "1.75", "fn main() {}"));
+        assert_ne!(hash, build_hash(&Language::Rust, "1.76", "fn main() {}"));
+        assert_ne!(hash, build_hash(&Language::Java, "1.75", "fn main() {}"));
+        // Field boundaries are part of the hash
+        assert_ne!(build_hash(&Language::Rust, "1.7", "5fn"), build_hash(&Language::Rust, "1.75", "fn"));
+    }
+
+    #[test]
+    fn test_submission_archive_carries_exact_bytes() {
+        use std::io::Read;
+
+        let tar = build_submission_archive(&[(SOURCE_ENTRY, "print(input())  \n\n")]).unwrap();
+        let mut archive = tar::Archive::new(tar.as_slice());
+        let files: Vec<(String, String)> = archive
+            .entries()
+            .unwrap()
+            .map(|entry| {
+                let mut entry = entry.unwrap();
+                let mut content = String::new();
+                entry.read_to_string(&mut content).unwrap();
+                (entry.path().unwrap().display().to_string(), content)
+            })
+            .collect();
+        assert_eq!(
+            files,
+            vec![("tmp/optimus/source".to_string(), "print(input())  \n\n".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_build_needs_stamp() {
+        assert!(has_build_stamp(&tar_of(&[".build/main", ".build/.complete"])));
+        assert!(!has_build_stamp(&tar_of(&[".build/main"])));
+        assert!(!has_build_stamp(b"not a tar"));
+    }
+
+    #[test]
+    fn test_cpu_time_limit() {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(7);
+        header.set_mode(0o644);
+        header.set_cksum();
+        let mut builder = tar::Builder::new(Vec::new());
+        builder.append_data(&mut header, ".cpu_usec", &b"250400\n"[..]).unwrap();
+        let tar = builder.into_inner().unwrap();
+        assert_eq!(parse_cpu_time(&tar), Some(250));
+        assert_eq!(parse_cpu_time(&tar_of(&[".cpu_usec"])), None);
+
+        // A sleeper well under its CPU budget passes; a spinner over it times out
+        let mut sleeper = TestExecutionOutput { execution_time_ms: 900, cpu_time_ms: Some(20), exit_code: Some(0), ..Default::default() };
+        apply_cpu_time_limit(&mut sleeper, 500);
+        assert!(!sleeper.timed_out);
+        let mut spinner = TestExecutionOutput { execution_time_ms: 300, cpu_time_ms: Some(1100), exit_code: Some(0), ..Default::default() };
+        apply_cpu_time_limit(&mut spinner, 500);
+        assert!(spinner.timed_out);
+        assert!(spinner.stderr.ends_with("[CPU time limit of 500ms exceeded]"));
+    }
+
+    fn entries(tar: &[u8]) -> Vec<String> {
+        tar::Archive::new(tar)
+            .entries()
+            .unwrap()
+            .map(|entry| entry.unwrap().path().unwrap().display().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_payload_archive() {
+        let test_case = TestCase { input: "1 2\n".to_string(), ..Default::default() };
+        let mut build = tar::Builder::new(Vec::new());
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(tar::EntryType::Directory);
+        header.set_size(0);
+        header.set_mode(0o755);
+        build.append_data(&mut header, ".build", std::io::empty()).unwrap();
+        let build = build.into_inner().unwrap();
+
+        let payload = payload_archive("print(1)", &test_case, None, BuildCache::Reuse(&build)).unwrap();
+        assert_eq!(payload.len() % TAR_RECORD_BYTES, 0);
+        assert_eq!(entries(&payload), vec![SOURCE_ENTRY, "code/.build", STDIN_ENTRY]);
+    }
+
+    #[test]
+    fn test_capabilities() {
+        let hardened = SecurityConfig::default();
+        assert_eq!(capabilities(&hardened, &[]), (Some(vec!["ALL".to_string()]), None));
+        let (drop, add) = capabilities(&hardened, ENTRY_CAPABILITIES);
+        assert_eq!(drop, Some(vec!["ALL".to_string()]));
+        assert_eq!(add.unwrap().len(), ENTRY_CAPABILITIES.len());
+
+        let legacy = SecurityConfig { legacy_privileges: true, ..Default::default() };
+        assert_eq!(capabilities(&legacy, ENTRY_CAPABILITIES), (None, None));
+    }
+
+    #[test]
+    fn test_tmpfs() {
+        let filesystem = FilesystemConfig::default();
+        let mounts = tmpfs(&filesystem).unwrap();
+        assert_eq!(mounts["/tmp"], "rw,exec,nosuid,nodev,mode=1777,size=64m");
+        assert_eq!(mounts["/code"], "rw,exec,nosuid,nodev,mode=1777,size=256m");
+
+        // Never an unlimited tmpfs
+        let mounts = tmpfs(&FilesystemConfig { tmp_mb: 0, ..filesystem }).unwrap();
+        assert!(mounts["/tmp"].ends_with("size=1m"));
+
+        assert_eq!(tmpfs(&FilesystemConfig { readonly_rootfs: false, ..filesystem }), None);
+    }
+
+    #[test]
+    fn test_ulimits() {
+        let limits = ProcessLimits::default();
+        let set: Vec<(String, i64)> = ulimits(&limits)
+            .into_iter()
+            .map(|u| {
+                assert_eq!(u.soft, u.hard);
+                (u.name.unwrap(), u.hard.unwrap())
+            })
+            .collect();
+        assert_eq!(set, vec![("nofile".to_string(), 256), ("fsize".to_string(), 64 * 1024 * 1024)]);
+
+        let limits = ProcessLimits { nproc: Some(512), ..limits };
+        assert_eq!(ulimits(&limits).last().unwrap().name.as_deref(), Some("nproc"));
+    }
+
+    #[test]
+    fn test_kill_diagnostics_capture_helpers() {
+        let mut tail = Vec::new();
+        keep_tail(&mut tail, b"hello ", 8);
+        keep_tail(&mut tail, b"world", 8);
+        assert_eq!(tail, b"lo world");
+
+        let mut header = tar::Header::new_gnu();
+        header.set_size(10);
+        header.set_mode(0o644);
+        header.set_cksum();
+        let mut builder = tar::Builder::new(Vec::new());
+        builder.append_data(&mut header, "core", &b"0123456789"[..]).unwrap();
+        let tar = builder.into_inner().unwrap();
+        assert_eq!(read_tar_file(&tar, 4), Some(b"0123".to_vec()));
+        assert_eq!(read_tar_file(&tar, 64), Some(b"0123456789".to_vec()));
+    }
+}
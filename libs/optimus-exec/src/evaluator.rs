@@ -0,0 +1,1727 @@
+//! Test Evaluator - Language-Agnostic Scoring Logic
+//!
+//! **Core Responsibility:**
+//! Compare raw execution outputs against expected outputs and assign scores.
+//!
+//! **Critical Properties:**
+//! - Knows nothing about Docker
+//! - Knows nothing about language runtimes
+//! - Knows nothing about Redis
+//! - Pure function: (execution outputs, expected outputs) → scores
+//!
+//! **Scoring Rules:**
+//! - Each test case has a weight
+//! - score = sum of weights for Passed tests
+//! - max_score = sum of all test case weights
+//! - overall_status: Completed if any test passed, Failed if all failed
+//! - Presentation errors score 0 unless the job sets `accept_presentation_errors`,
+//!   in which case they count as passes
+//! - Optional time bonus: passed tests earn up to `bonus_weight` extra points,
+//!   full at `full_bonus_ms`, decaying linearly to zero at `zero_bonus_ms`
+//! - `scoring: all_or_nothing`: score is max_score if every test passed, else 0
+//!
+//! **Normalization Rules (Applied to All Languages):**
+//! - Trim trailing whitespace: YES
+//! - Trim leading whitespace: YES
+//! - Ignore newline differences (\n vs \r\n): YES (via trim)
+//! - Case sensitivity: YES, unless the test sets `ignore_case`
+//! - Per-line trailing whitespace / blank lines: reported as PresentationError
+//!   (exact mode only)
+//! - Floating-point tolerance: opt-in per test (`comparison: {mode: "float"}`)
+//! - Multiple expected outputs: any-of (`expected_output` or any `expected_outputs`)
+//! - Regex expected outputs: opt-in per test (`expected_kind: "regex"`),
+//!   anchored to the whole trimmed output
+//!
+//! **Verdict Summaries:**
+//! Every test result and the job result carry a one-line `summary` /
+//! `verdict_summary` built from the structured verdict (first mismatching
+//! line, exit signal, failing-test counts), so frontends can show feedback
+//! without rendering results themselves.
+//!
+//! **Why This Exists:**
+//! Separates correctness evaluation from execution mechanism.
+//! Guarantees deterministic scoring regardless of execution engine.
+
+use optimus_common::types::{
+    compile_expected_regex, Comparison, DiagnosticBundle, ExecutionResult, ExpectedKind, JobRequest, JobStatus,
+    ScoringPolicy, TestCase, TestResult, TestStatus, TimeScoring, TimingSummary,
+};
+
+use crate::phases::PhaseTimings;
+
+/// Raw execution output for a single test case
+/// Produced by ExecutionEngine, consumed by Evaluator
+#[derive(Debug, Clone, Default)]
+pub struct TestExecutionOutput {
+    pub test_id: u32,
+    pub stdout: String,
+    pub stderr: String,
+    pub execution_time_ms: u64,
+    pub timed_out: bool,
+    pub runtime_error: bool,
+    /// Program was OOM-killed (exit 137 with Docker's OOMKilled flag set)
+    pub memory_limit_exceeded: bool,
+    /// Runner reported a compilation failure
+    pub compile_error: bool,
+    /// Runner reported a harness error (not the submission's fault)
+    pub internal_error: bool,
+    /// Container exit code (None if killed on timeout or never started)
+    pub exit_code: Option<i64>,
+    /// Program CPU time measured by the runner from the container's cgroup
+    pub cpu_time_ms: Option<u64>,
+    /// Output was reused from an earlier test with identical input
+    pub deduplicated: bool,
+    /// Sandbox escape heuristics that fired (see `sandbox_audit`)
+    pub sandbox_flags: Vec<String>,
+    /// Output past the per-test capture limit (or the worker budget) was dropped
+    pub stdout_truncated: bool,
+    pub stderr_truncated: bool,
+    /// Tar of the compiled build from a compile-only run (`BuildCache::Build`)
+    pub build_artifact: Option<Vec<u8>>,
+    /// Collected from the container after a timeout kill (see `KillDiagnostics`)
+    pub diagnostics: Option<DiagnosticBundle>,
+    /// Time this container spent in each phase (see `phases`)
+    pub phases: PhaseTimings,
+}
+
+/// Normalize output string for comparison
+///
+/// **Normalization Rules:**
+/// - Trim leading whitespace
+/// - Trim trailing whitespace
+/// - Removes differences in line endings (\r\n vs \n)
+///
+/// **Preserves:**
+/// - Internal whitespace
+/// - Case sensitivity (see `text_eq` for `ignore_case`)
+/// - Empty lines within content
+fn normalize_output(output: &str) -> &str {
+    output.trim()
+}
+
+/// Per-line normalization for presentation-error detection
+///
+/// Drops trailing whitespace on every line and removes blank lines entirely.
+fn normalize_lines(output: &str) -> Vec<&str> {
+    output
+        .lines()
+        .map(str::trim_end)
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+/// Right answer, wrong layout: an exact-mode mismatch that goes away under
+/// per-line normalization
+fn presentation_matches(test_case: &TestCase, actual: &str) -> bool {
+    if test_case.expected_kind != ExpectedKind::Literal || test_case.comparison.unwrap_or_default() != Comparison::Exact {
+        return false;
+    }
+
+    let actual = normalize_lines(actual);
+    test_case.accepted_outputs().any(|expected| {
+        let expected = normalize_lines(expected);
+        expected.len() == actual.len()
+            && expected.iter().zip(&actual).all(|(e, a)| text_eq(e, a, test_case.ignore_case))
+    })
+}
+
+/// String equality, optionally ignoring case (Unicode-aware)
+fn text_eq(expected: &str, actual: &str, ignore_case: bool) -> bool {
+    if ignore_case {
+        expected == actual || expected.to_lowercase() == actual.to_lowercase()
+    } else {
+        expected == actual
+    }
+}
+
+/// Token-wise comparison with numeric tolerance
+///
+/// Both outputs are split on whitespace (so line layout doesn't matter) and
+/// must have the same number of tokens. Token pairs that both parse as
+/// numbers pass within `abs_tol` or `rel_tol`; anything else must be equal
+/// (ignoring case if requested).
+fn floats_match(expected: &str, actual: &str, abs_tol: f64, rel_tol: f64, ignore_case: bool) -> bool {
+    let mut expected_tokens = expected.split_whitespace();
+    let mut actual_tokens = actual.split_whitespace();
+
+    loop {
+        match (expected_tokens.next(), actual_tokens.next()) {
+            (None, None) => return true,
+            (Some(e), Some(a)) => {
+                if text_eq(e, a, ignore_case) {
+                    continue;
+                }
+                let (Ok(e), Ok(a)) = (e.parse::<f64>(), a.parse::<f64>()) else {
+                    return false;
+                };
+                let diff = (e - a).abs();
+                if !(diff <= abs_tol || diff <= rel_tol * e.abs().max(a.abs())) {
+                    return false;
+                }
+            }
+            _ => return false,
+        }
+    }
+}
+
+/// Does `actual` match one expected output under the test's comparison mode
+fn output_matches(comparison: Comparison, ignore_case: bool, expected: &str, actual: &str) -> bool {
+    match comparison {
+        Comparison::Exact => text_eq(normalize_output(expected), normalize_output(actual), ignore_case),
+        Comparison::Float { abs_tol, rel_tol } => floats_match(expected, actual, abs_tol, rel_tol, ignore_case),
+    }
+}
+
+/// Does `actual` match any of the test's accepted outputs
+///
+/// Patterns are validated at submission; one that somehow fails to compile
+/// here simply doesn't match.
+fn accepted(test_case: &TestCase, actual: &str) -> bool {
+    match test_case.expected_kind {
+        ExpectedKind::Literal => {
+            let comparison = test_case.comparison.unwrap_or_default();
+            test_case
+                .accepted_outputs()
+                .any(|expected| output_matches(comparison, test_case.ignore_case, expected, actual))
+        }
+        ExpectedKind::Regex => test_case.accepted_outputs().any(|pattern| {
+            compile_expected_regex(pattern, test_case.ignore_case)
+                .map(|re| re.is_match(normalize_output(actual)))
+                .unwrap_or(false)
+        }),
+    }
+}
+
+/// Evaluate a single test case execution output
+///
+/// This function determines the TestStatus based on:
+/// 1. Harness and compilation errors (highest priority)
+/// 2. Memory limit (OOM kills)
+/// 3. Runtime errors
+/// 4. Timeouts
+/// 5. Output comparison (if execution succeeded)
+///
+/// ## Arguments
+/// * `output` - Raw execution output from the engine
+/// * `test_case` - Expected test case definition
+///
+/// ## Returns
+/// TestResult with status and execution details
+pub fn evaluate_test(output: &TestExecutionOutput, test_case: &TestCase) -> TestResult {
+    let status = if output.internal_error {
+        TestStatus::InternalError
+    } else if output.compile_error {
+        TestStatus::CompileError
+    } else if output.memory_limit_exceeded {
+        TestStatus::MemoryLimitExceeded
+    } else if output.runtime_error {
+        TestStatus::RuntimeError
+    } else if output.timed_out {
+        TestStatus::TimeLimitExceeded
+    } else {
+        // Compare outputs - any accepted output is a pass
+        if accepted(test_case, &output.stdout) {
+            TestStatus::Passed
+        } else if presentation_matches(test_case, &output.stdout) {
+            TestStatus::PresentationError
+        } else {
+            TestStatus::Failed
+        }
+    };
+
+    // Show where the output diverged (patterns have nothing meaningful to diff against)
+    let diff = match status {
+        TestStatus::Failed | TestStatus::PresentationError if test_case.expected_kind == ExpectedKind::Literal => {
+            output_diff(&test_case.expected_output, &output.stdout)
+        }
+        _ => None,
+    };
+
+    let mut result = TestResult {
+        test_id: output.test_id,
+        status,
+        stdout: output.stdout.clone(),
+        stderr: output.stderr.clone(),
+        execution_time_ms: output.execution_time_ms,
+        deduplicated: output.deduplicated,
+        time_bonus: 0,
+        diff,
+        sandbox_flags: output.sandbox_flags.clone(),
+        stdout_truncated: output.stdout_truncated,
+        stderr_truncated: output.stderr_truncated,
+        exit_code: output.exit_code,
+        cpu_time_ms: output.cpu_time_ms,
+        summary: None,
+    };
+    result.summary = Some(test_summary(&result, test_case));
+    result
+}
+
+/// Characters of a mismatching line quoted in a summary
+const MAX_SUMMARY_QUOTE_CHARS: usize = 40;
+
+/// Quote a line for a summary, shortened to `MAX_SUMMARY_QUOTE_CHARS`
+fn quote(line: &str) -> String {
+    let mut chars = line.chars();
+    let mut quoted: String = chars.by_ref().take(MAX_SUMMARY_QUOTE_CHARS).collect();
+    if chars.next().is_some() {
+        quoted.push('…');
+    }
+    format!("\"{}\"", quoted)
+}
+
+/// Where a wrong answer first departs from the test's expected output
+fn mismatch_detail(test_case: &TestCase, actual: &str) -> String {
+    if test_case.expected_kind == ExpectedKind::Regex {
+        return "output does not match the expected pattern".to_string();
+    }
+
+    let mut expected_lines = normalize_output(&test_case.expected_output).lines();
+    let mut actual_lines = normalize_output(actual).lines();
+    let mut line = 1;
+    loop {
+        match (expected_lines.next(), actual_lines.next()) {
+            (Some(e), Some(a)) if text_eq(e.trim_end(), a.trim_end(), test_case.ignore_case) => line += 1,
+            (Some(e), Some(a)) => return format!("expected {}, got {} at line {}", quote(e), quote(a), line),
+            (Some(e), None) if line == 1 && e.is_empty() => return "expected no output".to_string(),
+            (Some(e), None) if line == 1 => return format!("expected {}, got no output", quote(e)),
+            (Some(e), None) => return format!("expected {} at line {}, got end of output", quote(e), line),
+            (None, Some(a)) => return format!("unexpected extra output {} at line {}", quote(a), line),
+            // Same lines under a tolerant comparison mode, or an alternative output
+            (None, None) => return "output does not match any accepted output".to_string(),
+        }
+    }
+}
+
+/// Name of the signal behind a 128+N exit code, if it is a common one
+fn signal_name(exit_code: i64) -> Option<&'static str> {
+    match exit_code - 128 {
+        6 => Some("SIGABRT"),
+        8 => Some("SIGFPE"),
+        9 => Some("SIGKILL"),
+        11 => Some("SIGSEGV"),
+        15 => Some("SIGTERM"),
+        _ => None,
+    }
+}
+
+/// One-line explanation of a test's verdict
+pub fn test_summary(result: &TestResult, test_case: &TestCase) -> String {
+    let id = result.test_id;
+    match result.status {
+        TestStatus::Passed if result.time_bonus > 0 => format!("Passed test {} (+{} time bonus)", id, result.time_bonus),
+        TestStatus::Passed => format!("Passed test {}", id),
+        TestStatus::Failed => format!("Wrong answer on test {}: {}", id, mismatch_detail(test_case, &result.stdout)),
+        TestStatus::PresentationError => {
+            format!("Presentation error on test {}: whitespace or blank lines differ", id)
+        }
+        TestStatus::RuntimeError => match result.exit_code {
+            Some(code) => match signal_name(code) {
+                Some(signal) => format!("Runtime error on test {} (exit code {}, {})", id, code, signal),
+                None => format!("Runtime error on test {} (exit code {})", id, code),
+            },
+            None => format!("Runtime error on test {}", id),
+        },
+        TestStatus::TimeLimitExceeded => format!("Time limit exceeded on test {}", id),
+        TestStatus::MemoryLimitExceeded => format!("Memory limit exceeded on test {}", id),
+        TestStatus::CompileError => "Compilation error".to_string(),
+        TestStatus::InternalError => format!("Internal error on test {} (not caused by the submission)", id),
+    }
+}
+
+/// One-line explanation of the job's verdict, from its test results
+///
+/// A single failing test is described in full; several are counted per
+/// failure kind in order of severity ("Wrong answer on 2/10 tests, time limit
+/// exceeded on 1/10 tests").
+pub fn job_summary(results: &[TestResult]) -> Option<String> {
+    let total = results.len();
+    if total == 0 {
+        return None;
+    }
+    if results.iter().any(|r| r.status == TestStatus::CompileError) {
+        return Some("Compilation error".to_string());
+    }
+
+    let failing: Vec<&TestResult> = results.iter().filter(|r| r.status != TestStatus::Passed).collect();
+    match failing.as_slice() {
+        [] if total == 1 => return Some("Passed the test".to_string()),
+        [] => return Some(format!("All {} tests passed", total)),
+        [only] => return only.summary.clone(),
+        _ => {}
+    }
+
+    let kinds = [
+        (TestStatus::InternalError, "internal error"),
+        (TestStatus::Failed, "wrong answer"),
+        (TestStatus::PresentationError, "presentation error"),
+        (TestStatus::RuntimeError, "runtime error"),
+        (TestStatus::MemoryLimitExceeded, "memory limit exceeded"),
+        (TestStatus::TimeLimitExceeded, "time limit exceeded"),
+    ];
+    let parts: Vec<String> = kinds
+        .iter()
+        .filter_map(|(status, label)| {
+            let count = failing.iter().filter(|r| r.status == *status).count();
+            (count > 0).then(|| format!("{} on {}/{} tests", label, count, total))
+        })
+        .collect();
+
+    let mut summary = parts.join(", ");
+    if let Some(first) = summary.get(..1) {
+        summary.replace_range(..1, &first.to_uppercase());
+    }
+    Some(summary)
+}
+
+/// Changed (`+`/`-`) lines kept in a result's diff
+pub const MAX_DIFF_CHANGED_LINES: usize = 50;
+/// Lines of each side fed to the diff - keeps the work bounded for huge outputs
+const MAX_DIFF_INPUT_LINES: usize = 10_000;
+
+/// Bounded unified diff of the (trimmed) expected and actual output
+///
+/// Stops after `MAX_DIFF_CHANGED_LINES` changed lines with a truncation
+/// marker. Returns None if the trimmed outputs are identical.
+fn output_diff(expected: &str, actual: &str) -> Option<String> {
+    let prepare = |text: &str| {
+        let mut lines: String = normalize_output(text)
+            .lines()
+            .take(MAX_DIFF_INPUT_LINES)
+            .flat_map(|line| [line, "\n"])
+            .collect();
+        if lines.is_empty() {
+            lines.push('\n');
+        }
+        lines
+    };
+    let (expected, actual) = (prepare(expected), prepare(actual));
+    if expected == actual {
+        return None;
+    }
+
+    let patch = diffy::DiffOptions::new()
+        .set_original_filename("expected")
+        .set_modified_filename("actual")
+        .create_patch(&expected, &actual)
+        .to_string();
+
+    let mut diff = String::new();
+    let mut changed = 0;
+    for (idx, line) in patch.lines().enumerate() {
+        // The first two lines are the ---/+++ file headers
+        if idx >= 2 && (line.starts_with('+') || line.starts_with('-')) {
+            if changed == MAX_DIFF_CHANGED_LINES {
+                diff.push_str("... diff truncated\n");
+                break;
+            }
+            changed += 1;
+        }
+        diff.push_str(line);
+        diff.push('\n');
+    }
+    Some(diff)
+}
+
+/// Speed bonus for a passed test
+///
+/// Uses the execution time measured by the engine. Integer arithmetic keeps
+/// the result deterministic: bonus = floor(weight * (T2 - t) / (T2 - T1)),
+/// clamped to [0, weight].
+pub fn time_bonus(scoring: &TimeScoring, execution_time_ms: u64) -> u32 {
+    if execution_time_ms <= scoring.full_bonus_ms {
+        return scoring.bonus_weight;
+    }
+    if execution_time_ms >= scoring.zero_bonus_ms {
+        return 0;
+    }
+
+    let remaining = scoring.zero_bonus_ms - execution_time_ms;
+    let window = scoring.zero_bonus_ms - scoring.full_bonus_ms;
+    (scoring.bonus_weight as u64 * remaining / window) as u32
+}
+
+/// Summarize per-test execution times (nearest-rank percentiles)
+/// `queue_wait_ms` and `wall_time_ms` are measured by the worker around the job
+pub fn timing_summary(results: &[TestResult], queue_wait_ms: Option<u64>, wall_time_ms: u64) -> TimingSummary {
+    let mut times: Vec<u64> = results.iter().map(|r| r.execution_time_ms).collect();
+    times.sort_unstable();
+    let rank = |p: usize| match times.len() {
+        0 => 0,
+        n => times[(n * p).div_ceil(100).max(1) - 1],
+    };
+
+    TimingSummary {
+        total_ms: times.iter().sum(),
+        min_ms: times.first().copied().unwrap_or(0),
+        max_ms: times.last().copied().unwrap_or(0),
+        p50_ms: rank(50),
+        p95_ms: rank(95),
+        queue_wait_ms,
+        wall_time_ms,
+        phases_ms: Default::default(),
+    }
+}
+
+/// Formula recorded in the result so scores can be audited later
+fn time_scoring_formula(scoring: &TimeScoring) -> String {
+    format!(
+        "bonus = floor({} * clamp(({} - t_ms) / ({} - {}), 0, 1)) per passed test",
+        scoring.bonus_weight, scoring.zero_bonus_ms, scoring.zero_bonus_ms, scoring.full_bonus_ms
+    )
+}
+
+/// Evaluate one test under the job's rules: presentation errors pass when the
+/// job accepts them, and passing tests earn the job's time bonus
+///
+/// Also what the engine publishes as a partial result when the test finishes.
+pub fn evaluate_job_test(job: &JobRequest, output: &TestExecutionOutput, test_case: &TestCase) -> TestResult {
+    let mut test_result = evaluate_test(output, test_case);
+    if test_result.status == TestStatus::PresentationError && job.accept_presentation_errors {
+        test_result.status = TestStatus::Passed;
+    }
+    if test_result.status == TestStatus::Passed {
+        if let Some(scoring) = &job.time_scoring {
+            test_result.time_bonus = time_bonus(scoring, output.execution_time_ms);
+        }
+    }
+    // Status or bonus may have changed above
+    test_result.summary = Some(test_summary(&test_result, test_case));
+    test_result
+}
+
+/// Aggregate multiple test results into final execution result
+///
+/// This function:
+/// 1. Calculates total score (sum of passed test weights)
+/// 2. Calculates max possible score (sum of all weights)
+/// 3. Determines overall status (Completed if any passed, Failed otherwise)
+///
+/// ## Arguments
+/// * `outputs` - Raw execution outputs from engine
+/// * `job` - Original job request with test cases
+///
+/// ## Returns
+/// Complete ExecutionResult with aggregated scores and status
+pub fn aggregate_results(
+    outputs: &[TestExecutionOutput],
+    job: &JobRequest,
+) -> ExecutionResult {
+    let mut test_results = Vec::new();
+    let mut total_score = 0u32;
+    let mut max_score: u32 = job.test_cases.iter().map(|tc| tc.weight).sum();
+    if let Some(scoring) = &job.time_scoring {
+        max_score += scoring.bonus_weight * job.test_cases.len() as u32;
+    }
+
+    println!("→ Evaluating {} test outputs", outputs.len());
+    println!("  Max possible score: {}", max_score);
+    println!();
+
+    for output in outputs {
+        // Find corresponding test case
+        let test_case = job
+            .test_cases
+            .iter()
+            .find(|tc| tc.id == output.test_id)
+            .expect("Test case not found for output");
+
+        // Evaluate single test
+        let test_result = evaluate_job_test(job, output, test_case);
+
+        // Update score if passed
+        if test_result.status == TestStatus::Passed {
+            total_score += test_case.weight + test_result.time_bonus;
+        }
+
+        // Log evaluation result
+        println!(
+            "  Test {} (id: {}, weight: {}) → {:?}",
+            test_results.len() + 1,
+            test_case.id,
+            test_case.weight,
+            test_result.status
+        );
+
+        match test_result.status {
+            TestStatus::Passed if test_result.time_bonus > 0 => {
+                println!("    ✓ Output matched (+{} time bonus)", test_result.time_bonus)
+            }
+            TestStatus::Passed => println!("    ✓ Output matched"),
+            TestStatus::RuntimeError => println!("    ✗ Runtime error"),
+            TestStatus::TimeLimitExceeded => println!("    ✗ Timeout"),
+            TestStatus::MemoryLimitExceeded => println!("    ✗ Memory limit exceeded"),
+            TestStatus::CompileError => println!("    ✗ Compilation error"),
+            TestStatus::InternalError => println!("    ✗ Internal error (runner harness)"),
+            TestStatus::PresentationError => println!("    ✗ Presentation error (whitespace / blank lines differ)"),
+            TestStatus::Failed => {
+                println!("    ✗ Output mismatch");
+                for expected in test_case.accepted_outputs() {
+                    println!("    Expected: \"{}\"", normalize_output(expected));
+                }
+                println!("    Got:      \"{}\"", normalize_output(&output.stdout));
+            }
+        }
+
+        test_results.push(test_result);
+    }
+
+    // All-or-nothing: partial credit collapses to zero
+    if job.scoring == ScoringPolicy::AllOrNothing {
+        let all_passed = test_results.iter().all(|r| r.status == TestStatus::Passed);
+        total_score = if all_passed { max_score } else { 0 };
+    }
+
+    // Determine overall status
+    let suspicious = test_results.iter().any(|r| !r.sandbox_flags.is_empty());
+    let overall_status = if total_score > 0 {
+        JobStatus::Completed
+    } else {
+        JobStatus::Failed
+    };
+
+    let verdict_summary = job_summary(&test_results);
+
+    println!();
+    println!("→ Evaluation complete");
+    println!("  Score: {} / {}", total_score, max_score);
+    println!("  Status: {:?}", overall_status);
+    if let Some(summary) = &verdict_summary {
+        println!("  Summary: {}", summary);
+    }
+
+    ExecutionResult {
+        job_id: job.id,
+        overall_status,
+        score: total_score,
+        max_score,
+        results: test_results,
+        scoring_formula: job.time_scoring.as_ref().map(time_scoring_formula),
+        reason: None,
+        feedback: None,
+        timing_summary: None,
+        suspicious,
+        cached: false,
+        verdict_summary,
+    }
+}
+
+/// Evaluate all test cases and produce final execution result
+///
+/// This is the main entry point for evaluation. It delegates to:
+/// - `evaluate_test` for individual test evaluation
+/// - `aggregate_results` for score calculation and status determination
+///
+/// ## Arguments
+/// * `job` - The original job request (for test cases and expected outputs)
+/// * `outputs` - Raw execution outputs from the execution engine
+///
+/// ## Returns
+/// Complete ExecutionResult with scores and aggregated status
+pub fn evaluate(job: &JobRequest, outputs: Vec<TestExecutionOutput>) -> ExecutionResult {
+    aggregate_results(&outputs, job)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use optimus_common::types::{Language, TestCase};
+    use uuid::Uuid;
+
+    /// Helper to create a test case
+    fn make_test_case(id: u32, expected_output: &str, weight: u32) -> TestCase {
+        TestCase {
+            id,
+            input: "input".to_string(),
+            expected_output: expected_output.to_string(),
+            weight,
+            ..Default::default()
+        }
+    }
+
+    /// Helper to create a passing output
+    fn make_output(test_id: u32, stdout: &str, exec_time: u64) -> TestExecutionOutput {
+        TestExecutionOutput {
+            test_id,
+            stdout: stdout.to_string(),
+            stderr: String::new(),
+            execution_time_ms: exec_time,
+            timed_out: false,
+            runtime_error: false,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_normalize_output() {
+        assert_eq!(normalize_output("hello"), "hello");
+        assert_eq!(normalize_output("  hello  "), "hello");
+        assert_eq!(normalize_output("hello\n"), "hello");
+        assert_eq!(normalize_output("\nhello\n"), "hello");
+        assert_eq!(normalize_output("  hello world  \n"), "hello world");
+        assert_eq!(normalize_output(""), "");
+        assert_eq!(normalize_output("   "), "");
+    }
+
+    #[test]
+    fn test_evaluate_test_exact_match() {
+        let test_case = make_test_case(1, "120", 10);
+        let output = make_output(1, "120", 42);
+
+        let result = evaluate_test(&output, &test_case);
+
+        assert_eq!(result.status, TestStatus::Passed);
+        assert_eq!(result.test_id, 1);
+        assert_eq!(result.execution_time_ms, 42);
+    }
+
+    #[test]
+    fn test_evaluate_test_with_whitespace() {
+        let test_case = make_test_case(1, "hello", 10);
+        let output = make_output(1, "  hello  \n", 5);
+
+        let result = evaluate_test(&output, &test_case);
+
+        assert_eq!(result.status, TestStatus::Passed);
+    }
+
+    #[test]
+    fn test_evaluate_test_mismatch() {
+        let test_case = make_test_case(1, "expected", 10);
+        let output = make_output(1, "actual", 5);
+
+        let result = evaluate_test(&output, &test_case);
+
+        assert_eq!(result.status, TestStatus::Failed);
+    }
+
+    #[test]
+    fn test_evaluate_test_any_of_expected_outputs() {
+        let test_case = TestCase {
+            expected_outputs: vec!["YES".to_string(), "yes".to_string()],
+            ..make_test_case(1, "Yes", 10)
+        };
+
+        assert_eq!(evaluate_test(&make_output(1, "Yes\n", 5), &test_case).status, TestStatus::Passed);
+        assert_eq!(evaluate_test(&make_output(1, " yes ", 5), &test_case).status, TestStatus::Passed);
+        assert_eq!(evaluate_test(&make_output(1, "no", 5), &test_case).status, TestStatus::Failed);
+    }
+
+    #[test]
+    fn test_evaluate_test_float_tolerance() {
+        let mut test_case = make_test_case(1, "3.141592653 2\nok", 10);
+        let output = make_output(1, "3.1415926535 2.0000000001\nok\n", 100);
+
+        // Exact comparison rejects the 9th-decimal difference
+        assert_eq!(evaluate_test(&output, &test_case).status, TestStatus::Failed);
+
+        test_case.comparison = Some(Comparison::Float { abs_tol: 1e-6, rel_tol: 0.0 });
+        assert_eq!(evaluate_test(&output, &test_case).status, TestStatus::Passed);
+
+        // Relative tolerance scales with magnitude
+        test_case.comparison = Some(Comparison::Float { abs_tol: 0.0, rel_tol: 1e-6 });
+        assert_eq!(evaluate_test(&make_output(1, "1000000.5 2 ok", 100), &make_test_case(1, "1000000 2 ok", 10)).status, TestStatus::Failed);
+        let mut large = make_test_case(1, "1000000 2 ok", 10);
+        large.comparison = test_case.comparison;
+        assert_eq!(evaluate_test(&make_output(1, "1000000.5 2 ok", 100), &large).status, TestStatus::Passed);
+
+        // Non-numeric tokens and token counts still have to match
+        assert_eq!(evaluate_test(&make_output(1, "3.141592653 2 OK", 100), &test_case).status, TestStatus::Failed);
+        assert_eq!(evaluate_test(&make_output(1, "3.141592653 2", 100), &test_case).status, TestStatus::Failed);
+    }
+
+    #[test]
+    fn test_evaluate_test_ignore_case() {
+        let mut test_case = make_test_case(1, "YES", 10);
+        let output = make_output(1, "  Yes\n", 100);
+        assert_eq!(evaluate_test(&output, &test_case).status, TestStatus::Failed);
+
+        // Composes with trimming
+        test_case.ignore_case = true;
+        assert_eq!(evaluate_test(&output, &test_case).status, TestStatus::Passed);
+        assert_eq!(evaluate_test(&make_output(1, "No", 100), &test_case).status, TestStatus::Failed);
+
+        // ... and with float mode's non-numeric tokens
+        let mut floats = make_test_case(1, "0.5 YES", 10);
+        floats.comparison = Some(Comparison::Float { abs_tol: 1e-3, rel_tol: 0.0 });
+        floats.ignore_case = true;
+        assert_eq!(evaluate_test(&make_output(1, "0.5001 yes", 100), &floats).status, TestStatus::Passed);
+    }
+
+    #[test]
+    fn test_evaluate_test_regex_expected_output() {
+        let mut test_case = make_test_case(1, r"started at \d{2}:\d{2}:\d{2}\s+done", 10);
+        test_case.expected_kind = ExpectedKind::Regex;
+
+        let output = make_output(1, "started at 12:04:59\ndone\n", 100);
+        assert_eq!(evaluate_test(&output, &test_case).status, TestStatus::Passed);
+
+        // Anchored: extra output around the match fails
+        let output = make_output(1, "started at 12:04:59\ndone\nextra", 100);
+        assert_eq!(evaluate_test(&output, &test_case).status, TestStatus::Failed);
+
+        // Alternatives are patterns too, and ignore_case applies
+        test_case.expected_outputs = vec!["(yes|no)".to_string()];
+        test_case.ignore_case = true;
+        assert_eq!(evaluate_test(&make_output(1, "NO", 100), &test_case).status, TestStatus::Passed);
+
+        // Invalid patterns never match
+        test_case.expected_output = "(".to_string();
+        test_case.expected_outputs.clear();
+        assert_eq!(evaluate_test(&make_output(1, "(", 100), &test_case).status, TestStatus::Failed);
+    }
+
+    #[test]
+    fn test_evaluate_test_runtime_error() {
+        let test_case = make_test_case(1, "output", 10);
+        let output = TestExecutionOutput {
+            test_id: 1,
+            stdout: String::new(),
+            stderr: "RuntimeError: crash".to_string(),
+            execution_time_ms: 5,
+            timed_out: false,
+            runtime_error: true,
+            ..Default::default()
+        };
+
+        let result = evaluate_test(&output, &test_case);
+
+        assert_eq!(result.status, TestStatus::RuntimeError);
+    }
+
+    #[test]
+    fn test_evaluate_test_runner_classifications() {
+        let test_case = make_test_case(1, "output", 10);
+        let compile = TestExecutionOutput {
+            test_id: 1,
+            stderr: "Compilation failed".to_string(),
+            compile_error: true,
+            exit_code: Some(201),
+            ..Default::default()
+        };
+        assert_eq!(evaluate_test(&compile, &test_case).status, TestStatus::CompileError);
+
+        let harness = TestExecutionOutput {
+            test_id: 1,
+            internal_error: true,
+            exit_code: Some(203),
+            ..Default::default()
+        };
+        assert_eq!(evaluate_test(&harness, &test_case).status, TestStatus::InternalError);
+
+        let oom = TestExecutionOutput {
+            test_id: 1,
+            memory_limit_exceeded: true,
+            exit_code: Some(137),
+            ..Default::default()
+        };
+        let oom_result = evaluate_test(&oom, &test_case);
+        assert_eq!(oom_result.status, TestStatus::MemoryLimitExceeded);
+        assert_eq!(oom_result.exit_code, Some(137));
+    }
+
+    #[test]
+    fn test_failed_tests_carry_bounded_diff() {
+        let test_case = make_test_case(1, "a\nb\nc", 10);
+        let result = evaluate_test(&make_output(1, "a\nX\nc\n", 10), &test_case);
+        assert_eq!(result.status, TestStatus::Failed);
+        let diff = result.diff.unwrap();
+        assert!(diff.starts_with("--- expected\n+++ actual\n"));
+        assert!(diff.contains("-b\n+X\n"));
+
+        // Passing tests have no diff
+        assert!(evaluate_test(&make_output(1, "a\nb\nc", 10), &test_case).diff.is_none());
+
+        // Only the first MAX_DIFF_CHANGED_LINES changed lines are kept
+        let expected: String = (0..200).map(|i| format!("{}\n", i)).collect();
+        let actual: String = (0..200).map(|i| format!("x{}\n", i)).collect();
+        let diff = output_diff(&expected, &actual).unwrap();
+        let changed = diff.lines().skip(2).filter(|l| l.starts_with('+') || l.starts_with('-')).count();
+        assert_eq!(changed, MAX_DIFF_CHANGED_LINES);
+        assert!(diff.ends_with("... diff truncated\n"));
+    }
+
+    #[test]
+    fn test_evaluate_test_timeout() {
+        let test_case = make_test_case(1, "output", 10);
+        let output = TestExecutionOutput {
+            test_id: 1,
+            stdout: String::new(),
+            stderr: String::new(),
+            execution_time_ms: 1001,
+            timed_out: true,
+            runtime_error: false,
+            ..Default::default()
+        };
+
+        let result = evaluate_test(&output, &test_case);
+
+        assert_eq!(result.status, TestStatus::TimeLimitExceeded);
+    }
+
+    #[test]
+    fn test_all_pass() {
+        let job = JobRequest {
+            id: Uuid::new_v4(),
+            language: Language::Python,
+            source_code: String::new(),
+            test_cases: vec![
+                TestCase {
+                    id: 1,
+                    input: "5".to_string(),
+                    expected_output: "120".to_string(),
+                    weight: 10,
+                    ..Default::default()
+                },
+                TestCase {
+                    id: 2,
+                    input: "3".to_string(),
+                    expected_output: "6".to_string(),
+                    weight: 15,
+                    ..Default::default()
+                },
+            ],
+            timeout_ms: 5000,
+            metadata: optimus_common::types::JobMetadata::default(),
+            priority: Default::default(),
+            batch_id: None,
+            time_scoring: None,
+            time_limit_mode: Default::default(),
+            archive: None,
+            accept_presentation_errors: false,
+            scoring: ScoringPolicy::Weighted,
+            problem_id: None,
+            memory_limit_mb: None,
+            cpu_limit: None,
+            cache_fingerprint: None,
+            placement: Default::default(),
+            gpu: false,
+            max_parallel_tests: None,
+        };
+
+        let outputs = vec![
+            TestExecutionOutput {
+                test_id: 1,
+                stdout: "120".to_string(),
+                stderr: String::new(),
+                execution_time_ms: 42,
+                timed_out: false,
+                runtime_error: false,
+                ..Default::default()
+            },
+            TestExecutionOutput {
+                test_id: 2,
+                stdout: "6".to_string(),
+                stderr: String::new(),
+                execution_time_ms: 38,
+                timed_out: false,
+                runtime_error: false,
+                ..Default::default()
+            },
+        ];
+
+        let result = evaluate(&job, outputs);
+
+        assert_eq!(result.overall_status, JobStatus::Completed);
+        assert_eq!(result.score, 25);
+        assert_eq!(result.max_score, 25);
+        assert_eq!(result.results[0].status, TestStatus::Passed);
+        assert_eq!(result.results[1].status, TestStatus::Passed);
+    }
+
+    #[test]
+    fn test_partial_pass() {
+        let job = JobRequest {
+            id: Uuid::new_v4(),
+            language: Language::Java,
+            source_code: String::new(),
+            test_cases: vec![
+                TestCase {
+                    id: 1,
+                    input: "input".to_string(),
+                    expected_output: "correct".to_string(),
+                    weight: 20,
+                    ..Default::default()
+                },
+                TestCase {
+                    id: 2,
+                    input: "input".to_string(),
+                    expected_output: "wrong".to_string(),
+                    weight: 30,
+                    ..Default::default()
+                },
+            ],
+            timeout_ms: 5000,
+            metadata: optimus_common::types::JobMetadata::default(),
+            priority: Default::default(),
+            batch_id: None,
+            time_scoring: None,
+            time_limit_mode: Default::default(),
+            archive: None,
+            accept_presentation_errors: false,
+            scoring: ScoringPolicy::Weighted,
+            problem_id: None,
+            memory_limit_mb: None,
+            cpu_limit: None,
+            cache_fingerprint: None,
+            placement: Default::default(),
+            gpu: false,
+            max_parallel_tests: None,
+        };
+
+        let outputs = vec![
+            TestExecutionOutput {
+                test_id: 1,
+                stdout: "correct".to_string(),
+                stderr: String::new(),
+                execution_time_ms: 10,
+                timed_out: false,
+                runtime_error: false,
+                ..Default::default()
+            },
+            TestExecutionOutput {
+                test_id: 2,
+                stdout: "incorrect".to_string(),
+                stderr: String::new(),
+                execution_time_ms: 10,
+                timed_out: false,
+                runtime_error: false,
+                ..Default::default()
+            },
+        ];
+
+        let result = evaluate(&job, outputs);
+
+        assert_eq!(result.overall_status, JobStatus::Completed);
+        assert_eq!(result.score, 20);
+        assert_eq!(result.max_score, 50);
+        assert_eq!(result.results[0].status, TestStatus::Passed);
+        assert_eq!(result.results[1].status, TestStatus::Failed);
+    }
+
+    #[test]
+    fn test_all_fail() {
+        let job = JobRequest {
+            id: Uuid::new_v4(),
+            language: Language::Python,
+            source_code: String::new(),
+            test_cases: vec![
+                make_test_case(1, "expected1", 10),
+                make_test_case(2, "expected2", 10),
+            ],
+            timeout_ms: 5000,
+            metadata: optimus_common::types::JobMetadata::default(),
+            priority: Default::default(),
+            batch_id: None,
+            time_scoring: None,
+            time_limit_mode: Default::default(),
+            archive: None,
+            accept_presentation_errors: false,
+            scoring: ScoringPolicy::Weighted,
+            problem_id: None,
+            memory_limit_mb: None,
+            cpu_limit: None,
+            cache_fingerprint: None,
+            placement: Default::default(),
+            gpu: false,
+            max_parallel_tests: None,
+        };
+
+        let outputs = vec![
+            make_output(1, "wrong1", 10),
+            make_output(2, "wrong2", 10),
+        ];
+
+        let result = evaluate(&job, outputs);
+
+        assert_eq!(result.overall_status, JobStatus::Failed);
+        assert_eq!(result.score, 0);
+        assert_eq!(result.max_score, 20);
+        assert_eq!(result.results[0].status, TestStatus::Failed);
+        assert_eq!(result.results[1].status, TestStatus::Failed);
+    }
+
+    #[test]
+    fn test_runtime_error() {
+        let job = JobRequest {
+            id: Uuid::new_v4(),
+            language: Language::Python,
+            source_code: String::new(),
+            test_cases: vec![TestCase {
+                id: 1,
+                input: "input".to_string(),
+                expected_output: "output".to_string(),
+                weight: 10,
+                ..Default::default()
+            }],
+            timeout_ms: 5000,
+            metadata: optimus_common::types::JobMetadata::default(),
+            priority: Default::default(),
+            batch_id: None,
+            time_scoring: None,
+            time_limit_mode: Default::default(),
+            archive: None,
+            accept_presentation_errors: false,
+            scoring: ScoringPolicy::Weighted,
+            problem_id: None,
+            memory_limit_mb: None,
+            cpu_limit: None,
+            cache_fingerprint: None,
+            placement: Default::default(),
+            gpu: false,
+            max_parallel_tests: None,
+        };
+
+        let outputs = vec![TestExecutionOutput {
+            test_id: 1,
+            stdout: String::new(),
+            stderr: "RuntimeError: division by zero".to_string(),
+            execution_time_ms: 5,
+            timed_out: false,
+            runtime_error: true,
+            ..Default::default()
+        }];
+
+        let result = evaluate(&job, outputs);
+
+        assert_eq!(result.overall_status, JobStatus::Failed);
+        assert_eq!(result.score, 0);
+        assert_eq!(result.results[0].status, TestStatus::RuntimeError);
+    }
+
+    #[test]
+    fn test_timeout() {
+        let job = JobRequest {
+            id: Uuid::new_v4(),
+            language: Language::Rust,
+            source_code: String::new(),
+            test_cases: vec![TestCase {
+                id: 1,
+                input: "input".to_string(),
+                expected_output: "output".to_string(),
+                weight: 5,
+                ..Default::default()
+            }],
+            timeout_ms: 1000,
+            metadata: optimus_common::types::JobMetadata::default(),
+            priority: Default::default(),
+            batch_id: None,
+            time_scoring: None,
+            time_limit_mode: Default::default(),
+            archive: None,
+            accept_presentation_errors: false,
+            scoring: ScoringPolicy::Weighted,
+            problem_id: None,
+            memory_limit_mb: None,
+            cpu_limit: None,
+            cache_fingerprint: None,
+            placement: Default::default(),
+            gpu: false,
+            max_parallel_tests: None,
+        };
+
+        let outputs = vec![TestExecutionOutput {
+            test_id: 1,
+            stdout: String::new(),
+            stderr: String::new(),
+            execution_time_ms: 1001,
+            timed_out: true,
+            runtime_error: false,
+            ..Default::default()
+        }];
+
+        let result = evaluate(&job, outputs);
+
+        assert_eq!(result.overall_status, JobStatus::Failed);
+        assert_eq!(result.score, 0);
+        assert_eq!(result.results[0].status, TestStatus::TimeLimitExceeded);
+    }
+
+    #[test]
+    fn test_whitespace_trimming() {
+        let job = JobRequest {
+            id: Uuid::new_v4(),
+            language: Language::Python,
+            source_code: String::new(),
+            test_cases: vec![TestCase {
+                id: 1,
+                input: "input".to_string(),
+                expected_output: "hello".to_string(),
+                weight: 10,
+                ..Default::default()
+            }],
+            timeout_ms: 5000,
+            metadata: optimus_common::types::JobMetadata::default(),
+            priority: Default::default(),
+            batch_id: None,
+            time_scoring: None,
+            time_limit_mode: Default::default(),
+            archive: None,
+            accept_presentation_errors: false,
+            scoring: ScoringPolicy::Weighted,
+            problem_id: None,
+            memory_limit_mb: None,
+            cpu_limit: None,
+            cache_fingerprint: None,
+            placement: Default::default(),
+            gpu: false,
+            max_parallel_tests: None,
+        };
+
+        let outputs = vec![TestExecutionOutput {
+            test_id: 1,
+            stdout: "  hello  \n".to_string(),
+            stderr: String::new(),
+            execution_time_ms: 5,
+            timed_out: false,
+            runtime_error: false,
+            ..Default::default()
+        }];
+
+        let result = evaluate(&job, outputs);
+
+        assert_eq!(result.overall_status, JobStatus::Completed);
+        assert_eq!(result.score, 10);
+        assert_eq!(result.results[0].status, TestStatus::Passed);
+    }
+
+    #[test]
+    fn test_newline_handling() {
+        let job = JobRequest {
+            id: Uuid::new_v4(),
+            language: Language::Java,
+            source_code: String::new(),
+            test_cases: vec![make_test_case(1, "line1\nline2\nline3", 10)],
+            timeout_ms: 5000,
+            metadata: optimus_common::types::JobMetadata::default(),
+            priority: Default::default(),
+            batch_id: None,
+            time_scoring: None,
+            time_limit_mode: Default::default(),
+            archive: None,
+            accept_presentation_errors: false,
+            scoring: ScoringPolicy::Weighted,
+            problem_id: None,
+            memory_limit_mb: None,
+            cpu_limit: None,
+            cache_fingerprint: None,
+            placement: Default::default(),
+            gpu: false,
+            max_parallel_tests: None,
+        };
+
+        // Different newline styles should match after normalization
+        let outputs = vec![make_output(1, "line1\nline2\nline3\n", 10)];
+
+        let result = evaluate(&job, outputs);
+
+        assert_eq!(result.results[0].status, TestStatus::Passed);
+        assert_eq!(result.score, 10);
+    }
+
+    #[test]
+    fn test_empty_output() {
+        let job = JobRequest {
+            id: Uuid::new_v4(),
+            language: Language::Python,
+            source_code: String::new(),
+            test_cases: vec![make_test_case(1, "", 5)],
+            timeout_ms: 5000,
+            metadata: optimus_common::types::JobMetadata::default(),
+            priority: Default::default(),
+            batch_id: None,
+            time_scoring: None,
+            time_limit_mode: Default::default(),
+            archive: None,
+            accept_presentation_errors: false,
+            scoring: ScoringPolicy::Weighted,
+            problem_id: None,
+            memory_limit_mb: None,
+            cpu_limit: None,
+            cache_fingerprint: None,
+            placement: Default::default(),
+            gpu: false,
+            max_parallel_tests: None,
+        };
+
+        let outputs = vec![make_output(1, "   \n", 5)];
+
+        let result = evaluate(&job, outputs);
+
+        assert_eq!(result.results[0].status, TestStatus::Passed);
+        assert_eq!(result.score, 5);
+    }
+
+    #[test]
+    fn test_case_sensitivity() {
+        let job = JobRequest {
+            id: Uuid::new_v4(),
+            language: Language::Python,
+            source_code: String::new(),
+            test_cases: vec![make_test_case(1, "Hello", 10)],
+            timeout_ms: 5000,
+            metadata: optimus_common::types::JobMetadata::default(),
+            priority: Default::default(),
+            batch_id: None,
+            time_scoring: None,
+            time_limit_mode: Default::default(),
+            archive: None,
+            accept_presentation_errors: false,
+            scoring: ScoringPolicy::Weighted,
+            problem_id: None,
+            memory_limit_mb: None,
+            cpu_limit: None,
+            cache_fingerprint: None,
+            placement: Default::default(),
+            gpu: false,
+            max_parallel_tests: None,
+        };
+
+        let outputs = vec![make_output(1, "hello", 10)];
+
+        let result = evaluate(&job, outputs);
+
+        // Case should matter - this should fail
+        assert_eq!(result.results[0].status, TestStatus::Failed);
+        assert_eq!(result.score, 0);
+    }
+
+    #[test]
+    fn test_mixed_statuses() {
+        let job = JobRequest {
+            id: Uuid::new_v4(),
+            language: Language::Rust,
+            source_code: String::new(),
+            test_cases: vec![
+                make_test_case(1, "pass", 10),
+                make_test_case(2, "fail", 10),
+                make_test_case(3, "timeout", 10),
+                make_test_case(4, "error", 10),
+            ],
+            timeout_ms: 1000,
+            metadata: optimus_common::types::JobMetadata::default(),
+            priority: Default::default(),
+            batch_id: None,
+            time_scoring: None,
+            time_limit_mode: Default::default(),
+            archive: None,
+            accept_presentation_errors: false,
+            scoring: ScoringPolicy::Weighted,
+            problem_id: None,
+            memory_limit_mb: None,
+            cpu_limit: None,
+            cache_fingerprint: None,
+            placement: Default::default(),
+            gpu: false,
+            max_parallel_tests: None,
+        };
+
+        let outputs = vec![
+            make_output(1, "pass", 100),
+            make_output(2, "wrong", 100),
+            TestExecutionOutput {
+                test_id: 3,
+                stdout: String::new(),
+                stderr: String::new(),
+                execution_time_ms: 1001,
+                timed_out: true,
+                runtime_error: false,
+                ..Default::default()
+            },
+            TestExecutionOutput {
+                test_id: 4,
+                stdout: String::new(),
+                stderr: "Error".to_string(),
+                execution_time_ms: 50,
+                timed_out: false,
+                runtime_error: true,
+                ..Default::default()
+            },
+        ];
+
+        let result = evaluate(&job, outputs);
+
+        assert_eq!(result.overall_status, JobStatus::Completed); // At least one passed
+        assert_eq!(result.score, 10); // Only first test passed
+        assert_eq!(result.max_score, 40);
+        assert_eq!(result.results[0].status, TestStatus::Passed);
+        assert_eq!(result.results[1].status, TestStatus::Failed);
+        assert_eq!(result.results[2].status, TestStatus::TimeLimitExceeded);
+        assert_eq!(result.results[3].status, TestStatus::RuntimeError);
+    }
+
+    #[test]
+    fn test_zero_weight_tests() {
+        let job = JobRequest {
+            id: Uuid::new_v4(),
+            language: Language::Python,
+            source_code: String::new(),
+            test_cases: vec![
+                TestCase {
+                    id: 1,
+                    input: "input".to_string(),
+                    expected_output: "output".to_string(),
+                    weight: 0,
+                    ..Default::default()
+                },
+            ],
+            timeout_ms: 5000,
+            metadata: optimus_common::types::JobMetadata::default(),
+            priority: Default::default(),
+            batch_id: None,
+            time_scoring: None,
+            time_limit_mode: Default::default(),
+            archive: None,
+            accept_presentation_errors: false,
+            scoring: ScoringPolicy::Weighted,
+            problem_id: None,
+            memory_limit_mb: None,
+            cpu_limit: None,
+            cache_fingerprint: None,
+            placement: Default::default(),
+            gpu: false,
+            max_parallel_tests: None,
+        };
+
+        let outputs = vec![make_output(1, "output", 10)];
+
+        let result = evaluate(&job, outputs);
+
+        // Even though test passed, score is 0
+        assert_eq!(result.score, 0);
+        assert_eq!(result.max_score, 0);
+        // Status is Failed because total_score is 0 (no points earned)
+        assert_eq!(result.overall_status, JobStatus::Failed);
+    }
+
+    #[test]
+    fn test_deduplicated_marker_propagates() {
+        let test_case = make_test_case(2, "42", 10);
+        let output = TestExecutionOutput {
+            deduplicated: true,
+            ..make_output(2, "42", 7)
+        };
+
+        let result = evaluate_test(&output, &test_case);
+
+        assert_eq!(result.status, TestStatus::Passed);
+        assert!(result.deduplicated);
+        assert!(!evaluate_test(&make_output(2, "42", 7), &test_case).deduplicated);
+    }
+
+    #[test]
+    fn test_aggregate_results_directly() {
+        let job = JobRequest {
+            id: Uuid::new_v4(),
+            language: Language::Python,
+            source_code: String::new(),
+            test_cases: vec![
+                make_test_case(1, "hello", 15),
+                make_test_case(2, "world", 25),
+            ],
+            timeout_ms: 5000,
+            metadata: optimus_common::types::JobMetadata::default(),
+            priority: Default::default(),
+            batch_id: None,
+            time_scoring: None,
+            time_limit_mode: Default::default(),
+            archive: None,
+            accept_presentation_errors: false,
+            scoring: ScoringPolicy::Weighted,
+            problem_id: None,
+            memory_limit_mb: None,
+            cpu_limit: None,
+            cache_fingerprint: None,
+            placement: Default::default(),
+            gpu: false,
+            max_parallel_tests: None,
+        };
+
+        let outputs = vec![
+            make_output(1, "hello", 50),
+            make_output(2, "world", 75),
+        ];
+
+        let result = aggregate_results(&outputs, &job);
+
+        assert_eq!(result.score, 40);
+        assert_eq!(result.max_score, 40);
+        assert_eq!(result.overall_status, JobStatus::Completed);
+        assert_eq!(result.job_id, job.id);
+    }
+
+    #[test]
+    fn test_presentation_errors_reported_or_accepted() {
+        let mut job = JobRequest {
+            id: Uuid::new_v4(),
+            language: Language::Python,
+            source_code: String::new(),
+            test_cases: vec![make_test_case(1, "1 2\n3 4", 10), make_test_case(2, "5", 10)],
+            timeout_ms: 5000,
+            metadata: optimus_common::types::JobMetadata::default(),
+            priority: Default::default(),
+            batch_id: None,
+            time_scoring: None,
+            time_limit_mode: Default::default(),
+            archive: None,
+            accept_presentation_errors: false,
+            scoring: ScoringPolicy::Weighted,
+            problem_id: None,
+            memory_limit_mb: None,
+            cpu_limit: None,
+            cache_fingerprint: None,
+            placement: Default::default(),
+            gpu: false,
+            max_parallel_tests: None,
+        };
+        // Trailing spaces and a blank line between the rows
+        let outputs = vec![make_output(1, "1 2  \n\n3 4\n", 50), make_output(2, "6", 50)];
+
+        let result = aggregate_results(&outputs, &job);
+        assert_eq!(result.results[0].status, TestStatus::PresentationError);
+        assert_eq!(result.results[1].status, TestStatus::Failed);
+        assert_eq!(result.score, 0);
+        assert_eq!(result.summary().presentation_error, 1);
+
+        job.accept_presentation_errors = true;
+        let result = aggregate_results(&outputs, &job);
+        assert_eq!(result.results[0].status, TestStatus::Passed);
+        assert_eq!(result.score, 10);
+
+        // Internal whitespace still matters
+        assert_eq!(evaluate_test(&make_output(1, "1  2\n3 4", 50), &job.test_cases[0]).status, TestStatus::Failed);
+    }
+
+    #[test]
+    fn test_all_or_nothing_scoring() {
+        let mut job = JobRequest {
+            id: Uuid::new_v4(),
+            language: Language::Python,
+            source_code: String::new(),
+            test_cases: vec![make_test_case(1, "a", 10), make_test_case(2, "b", 30)],
+            timeout_ms: 5000,
+            metadata: optimus_common::types::JobMetadata::default(),
+            priority: Default::default(),
+            batch_id: None,
+            time_scoring: None,
+            time_limit_mode: Default::default(),
+            archive: None,
+            accept_presentation_errors: false,
+            scoring: ScoringPolicy::AllOrNothing,
+            problem_id: None,
+            memory_limit_mb: None,
+            cpu_limit: None,
+            cache_fingerprint: None,
+            placement: Default::default(),
+            gpu: false,
+            max_parallel_tests: None,
+        };
+
+        let all_pass = vec![make_output(1, "a", 50), make_output(2, "b", 50)];
+        let result = aggregate_results(&all_pass, &job);
+        assert_eq!((result.score, result.max_score), (40, 40));
+        assert_eq!(result.overall_status, JobStatus::Completed);
+
+        // One failure forfeits the partial credit weighted scoring would give
+        let one_fails = vec![make_output(1, "a", 50), make_output(2, "x", 50)];
+        let result = aggregate_results(&one_fails, &job);
+        assert_eq!((result.score, result.max_score), (0, 40));
+        assert_eq!(result.overall_status, JobStatus::Failed);
+        assert_eq!(result.results[0].status, TestStatus::Passed);
+
+        job.scoring = ScoringPolicy::Weighted;
+        assert_eq!(aggregate_results(&one_fails, &job).score, 10);
+    }
+
+    #[test]
+    fn test_timing_summary_percentiles() {
+        let results: Vec<TestResult> = (1..=20)
+            .map(|ms| evaluate_test(&make_output(ms, "x", ms as u64 * 10), &make_test_case(ms, "x", 1)))
+            .collect();
+
+        let summary = timing_summary(&results, Some(1500), 4000);
+        assert_eq!(summary.total_ms, 2100);
+        assert_eq!((summary.min_ms, summary.max_ms), (10, 200));
+        assert_eq!((summary.p50_ms, summary.p95_ms), (100, 190));
+        assert_eq!(summary.queue_wait_ms, Some(1500));
+        assert_eq!(summary.wall_time_ms, 4000);
+
+        // No tests ran (e.g. compile failure short-circuit) - all zeros, no panic
+        let empty = timing_summary(&[], None, 30);
+        assert_eq!((empty.total_ms, empty.p95_ms, empty.wall_time_ms), (0, 0, 30));
+    }
+
+    #[test]
+    fn test_sandbox_flags_mark_job_suspicious() {
+        let job = JobRequest {
+            id: Uuid::new_v4(),
+            language: Language::Python,
+            source_code: String::new(),
+            test_cases: vec![make_test_case(1, "ok", 10), make_test_case(2, "ok", 10)],
+            timeout_ms: 5000,
+            metadata: optimus_common::types::JobMetadata::default(),
+            priority: Default::default(),
+            batch_id: None,
+            time_scoring: None,
+            time_limit_mode: Default::default(),
+            archive: None,
+            accept_presentation_errors: false,
+            scoring: ScoringPolicy::Weighted,
+            problem_id: None,
+            memory_limit_mb: None,
+            cpu_limit: None,
+            cache_fingerprint: None,
+            placement: Default::default(),
+            gpu: false,
+            max_parallel_tests: None,
+        };
+        let mut flagged = make_output(2, "ok", 50);
+        flagged.sandbox_flags = vec!["write_outside_workdir: /etc/passwd".to_string()];
+
+        let result = aggregate_results(&[make_output(1, "ok", 50), flagged], &job);
+        assert!(result.suspicious);
+        assert_eq!(result.results[1].sandbox_flags, vec!["write_outside_workdir: /etc/passwd"]);
+        // Flags are informational - the verdict stands
+        assert_eq!(result.results[1].status, TestStatus::Passed);
+        assert_eq!(result.score, 20);
+
+        assert!(!aggregate_results(&[make_output(1, "ok", 50), make_output(2, "ok", 50)], &job).suspicious);
+    }
+
+    #[test]
+    fn test_time_bonus_linear_decay() {
+        let scoring = TimeScoring { full_bonus_ms: 100, zero_bonus_ms: 300, bonus_weight: 10 };
+
+        assert_eq!(time_bonus(&scoring, 0), 10);
+        assert_eq!(time_bonus(&scoring, 100), 10);
+        assert_eq!(time_bonus(&scoring, 200), 5);
+        assert_eq!(time_bonus(&scoring, 299), 0); // floor(10 * 1 / 200)
+        assert_eq!(time_bonus(&scoring, 300), 0);
+        assert_eq!(time_bonus(&scoring, 10_000), 0);
+    }
+
+    #[test]
+    fn test_time_scoring_only_rewards_passed_tests() {
+        let job = JobRequest {
+            id: Uuid::new_v4(),
+            language: Language::Python,
+            source_code: String::new(),
+            test_cases: vec![
+                make_test_case(1, "fast", 10),
+                make_test_case(2, "slow", 10),
+                make_test_case(3, "wrong", 10),
+            ],
+            timeout_ms: 5000,
+            metadata: optimus_common::types::JobMetadata::default(),
+            priority: Default::default(),
+            batch_id: None,
+            time_scoring: Some(TimeScoring { full_bonus_ms: 100, zero_bonus_ms: 300, bonus_weight: 4 }),
+            time_limit_mode: Default::default(),
+            archive: None,
+            accept_presentation_errors: false,
+            scoring: ScoringPolicy::Weighted,
+            problem_id: None,
+            memory_limit_mb: None,
+            cpu_limit: None,
+            cache_fingerprint: None,
+            placement: Default::default(),
+            gpu: false,
+            max_parallel_tests: None,
+        };
+
+        let outputs = vec![
+            make_output(1, "fast", 50),
+            make_output(2, "slow", 200),
+            make_output(3, "nope", 10),
+        ];
+
+        let result = evaluate(&job, outputs);
+
+        assert_eq!(result.results[0].time_bonus, 4);
+        assert_eq!(result.results[1].time_bonus, 2);
+        assert_eq!(result.results[2].time_bonus, 0);
+        assert_eq!(result.score, 10 + 4 + 10 + 2);
+        assert_eq!(result.max_score, 30 + 3 * 4);
+        assert!(result.scoring_formula.unwrap().contains("300 - 100"));
+    }
+
+    #[test]
+    fn test_job_test_matches_aggregated_result() {
+        let job = JobRequest {
+            id: Uuid::new_v4(),
+            language: Language::Python,
+            source_code: String::new(),
+            test_cases: vec![make_test_case(1, "1 2", 10), make_test_case(2, "fast", 10)],
+            timeout_ms: 5000,
+            metadata: optimus_common::types::JobMetadata::default(),
+            priority: Default::default(),
+            batch_id: None,
+            time_scoring: Some(TimeScoring { full_bonus_ms: 100, zero_bonus_ms: 300, bonus_weight: 4 }),
+            time_limit_mode: Default::default(),
+            archive: None,
+            accept_presentation_errors: true,
+            scoring: ScoringPolicy::Weighted,
+            problem_id: None,
+            memory_limit_mb: None,
+            cpu_limit: None,
+            cache_fingerprint: None,
+            placement: Default::default(),
+            gpu: false,
+            max_parallel_tests: None,
+        };
+        let outputs = vec![make_output(1, "1 2  \n", 50), make_output(2, "fast", 200)];
+
+        // What a worker publishes per test is what the final result keeps
+        let result = aggregate_results(&outputs, &job);
+        for (output, aggregated) in outputs.iter().zip(&result.results) {
+            let test_case = job.test_cases.iter().find(|tc| tc.id == output.test_id).unwrap();
+            let single = evaluate_job_test(&job, output, test_case);
+            assert_eq!(single.status, aggregated.status);
+            assert_eq!(single.time_bonus, aggregated.time_bonus);
+            assert_eq!(single.summary, aggregated.summary);
+        }
+        assert_eq!(result.results[0].status, TestStatus::Passed);
+        assert_eq!(result.results[1].time_bonus, 2);
+    }
+
+    #[test]
+    fn test_verdict_summaries() {
+        let test_case = make_test_case(3, "1\n42", 10);
+        let summary = |output: TestExecutionOutput| evaluate_test(&output, &test_case).summary.unwrap();
+
+        assert_eq!(summary(make_output(3, "1\n41\n", 5)), "Wrong answer on test 3: expected \"42\", got \"41\" at line 2");
+        assert_eq!(summary(make_output(3, "1", 5)), "Wrong answer on test 3: expected \"42\" at line 2, got end of output");
+        assert_eq!(summary(make_output(3, "", 5)), "Wrong answer on test 3: expected \"1\", got no output");
+        assert_eq!(summary(make_output(3, "1\n42\n7", 5)), "Wrong answer on test 3: unexpected extra output \"7\" at line 3");
+        assert_eq!(
+            summary(TestExecutionOutput { runtime_error: true, exit_code: Some(139), ..make_output(3, "", 5) }),
+            "Runtime error on test 3 (exit code 139, SIGSEGV)"
+        );
+        assert_eq!(summary(make_output(3, "1\n42", 5)), "Passed test 3");
+
+        // Long lines are shortened
+        let long = "x".repeat(100);
+        assert!(summary(make_output(3, &format!("1\n{}", long), 5)).ends_with("…\" at line 2"));
+
+        let result = |id: u32, status: TestStatus| TestResult {
+            summary: Some(format!("test {} summary", id)),
+            status,
+            ..evaluate_test(&make_output(id, "", 5), &make_test_case(id, "", 10))
+        };
+        assert_eq!(job_summary(&[]), None);
+        assert_eq!(job_summary(&[result(1, TestStatus::Passed), result(2, TestStatus::Passed)]).unwrap(), "All 2 tests passed");
+        assert_eq!(
+            job_summary(&[result(1, TestStatus::Passed), result(2, TestStatus::Failed)]).unwrap(),
+            "test 2 summary"
+        );
+        let mixed: Vec<TestResult> = (1..=10)
+            .map(|id| match id {
+                1..=4 => result(id, TestStatus::TimeLimitExceeded),
+                5 | 6 => result(id, TestStatus::Failed),
+                _ => result(id, TestStatus::Passed),
+            })
+            .collect();
+        assert_eq!(job_summary(&mixed).unwrap(), "Wrong answer on 2/10 tests, time limit exceeded on 4/10 tests");
+        assert_eq!(job_summary(&[result(1, TestStatus::CompileError), result(2, TestStatus::CompileError)]).unwrap(), "Compilation error");
+    }
+}
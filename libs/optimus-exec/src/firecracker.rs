@@ -0,0 +1,523 @@
+//! Firecracker Engine - MicroVM Isolation
+//!
+//! **Responsibility:**
+//! Run submissions in Firecracker microVMs (`OPTIMUS_CONTAINER_RUNTIME=firecracker`)
+//! for deployments that need a VM boundary between untrusted submissions.
+//!
+//! **How:**
+//! Every test boots its own VM (`firecracker --no-api`) from the language's
+//! read-only rootfs (`<OPTIMUS_FIRECRACKER_ROOTFS_DIR>/<language>.ext4`, an
+//! exported language image) plus a writable job drive built with
+//! `mkfs.ext4 -d` from what a container would have received. The guest init
+//! (`dockerfiles/vm-init.sh`, in every image's base layer) runs the universal
+//! runner and reports stdout, stderr, exit status, CPU time and the build back
+//! over the serial console, framed with a per-run secret; then the VM powers
+//! off.
+//!
+//! **Isolation:**
+//! - A KVM guest with its own kernel: `vcpu_count` and `mem_size_mib` from
+//!   the language limits (vCPUs rounded up to whole cores)
+//! - No network device at all
+//! - The program runs as the image's unprivileged `optimus` user
+//!
+//! **Differences from containers:**
+//! - No per-job containers (`EXECUTION_MODE=per_job` falls back to one VM
+//!   per test); boot time is not billed to the test
+//! - Languages with a `runtime` or `gpu` need a container runtime
+
+use crate::archive::{self, ProjectBundle};
+use crate::config::LanguageConfigManager;
+use crate::engine::{self, BuildCache, ExecutionEngine, JobContainer, ResourceOverrides};
+use crate::evaluator::TestExecutionOutput;
+use crate::output_budget;
+use crate::phases::{self, PhaseTimings};
+use crate::sandbox::{self, Scratch};
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use base64::{engine::general_purpose, Engine as _};
+use optimus_common::types::{Language, ProjectArchive, TestCase};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
+use tokio::process::Command;
+use tracing::debug;
+
+const DEFAULT_ASSETS_DIR: &str = "/var/lib/optimus/firecracker";
+
+/// Kernel command line: serial console, exit on reboot, guest init
+const BOOT_ARGS: &str = "console=ttyS0 reboot=k panic=1 pci=off quiet loglevel=0 init=/opt/optimus/vm-init";
+
+/// Guest memory on top of the program's limit (kernel, init, page cache)
+const GUEST_OVERHEAD_MB: u64 = 64;
+
+/// Extra wall clock for booting and shutting down the VM
+const BOOT_ALLOWANCE_MS: u64 = 5_000;
+
+/// Free space on the job drive for the program's files and the build
+const JOB_DRIVE_HEADROOM_MB: u64 = 256;
+
+/// Console output kept for error messages when the guest reports nothing
+const CONSOLE_TAIL_BYTES: usize = 4096;
+
+pub struct FirecrackerEngine {
+    binary: PathBuf,
+    kernel: PathBuf,
+    rootfs_dir: PathBuf,
+    /// Parent of every run's scratch directories
+    work_dir: PathBuf,
+    config_manager: LanguageConfigManager,
+}
+
+impl FirecrackerEngine {
+    pub fn new_with_config(config_manager: &LanguageConfigManager) -> Result<Self> {
+        let work_dir = sandbox::work_dir();
+        std::fs::create_dir_all(&work_dir)
+            .with_context(|| format!("Failed to create scratch directory {}", work_dir.display()))?;
+        Ok(FirecrackerEngine {
+            binary: find_binary()?,
+            kernel: kernel_path(),
+            rootfs_dir: assets_dir("OPTIMUS_FIRECRACKER_ROOTFS_DIR"),
+            work_dir,
+            config_manager: config_manager.clone(),
+        })
+    }
+
+    /// Guest resources: a job-level override wins over the language default
+    fn machine(&self, language: &Language, overrides: ResourceOverrides) -> Machine {
+        let memory_mb = overrides
+            .memory_limit_mb
+            .or_else(|| self.config_manager.get_memory_limit_mb(language).ok())
+            .unwrap_or(256) as u64;
+        let cpus = overrides
+            .cpu_limit
+            .or_else(|| self.config_manager.get_cpu_limit(language).ok().map(f64::from))
+            .unwrap_or(0.5);
+        Machine {
+            mem_size_mib: memory_mb + GUEST_OVERHEAD_MB,
+            vcpu_count: (cpus.ceil() as u64).max(1),
+        }
+    }
+
+    fn rootfs(&self, language: &Language) -> Result<PathBuf> {
+        let rootfs = self.rootfs_dir.join(format!("{}.ext4", language.to_string().to_lowercase()));
+        if !rootfs.is_file() {
+            bail!("No Firecracker rootfs for {} at {} (see the README)", language, rootfs.display());
+        }
+        Ok(rootfs)
+    }
+}
+
+/// Confirm KVM, firecracker, the guest kernel and mkfs.ext4 are available
+pub fn check() -> Result<()> {
+    find_binary()?;
+    if !Path::new("/dev/kvm").exists() {
+        bail!("/dev/kvm not found - Firecracker needs KVM (bare metal or nested virtualization)");
+    }
+    let kernel = kernel_path();
+    if !kernel.is_file() {
+        bail!("Guest kernel {} not found (set OPTIMUS_FIRECRACKER_KERNEL)", kernel.display());
+    }
+    sandbox::find_on_path("mkfs.ext4").context("mkfs.ext4 not found on PATH (install e2fsprogs)")?;
+    Ok(())
+}
+
+fn find_binary() -> Result<PathBuf> {
+    if let Ok(binary) = std::env::var("OPTIMUS_FIRECRACKER_BIN") {
+        return Ok(PathBuf::from(binary));
+    }
+    sandbox::find_on_path("firecracker").context("firecracker not found on PATH (or set OPTIMUS_FIRECRACKER_BIN)")
+}
+
+fn kernel_path() -> PathBuf {
+    std::env::var_os("OPTIMUS_FIRECRACKER_KERNEL")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| Path::new(DEFAULT_ASSETS_DIR).join("vmlinux"))
+}
+
+fn assets_dir(var: &str) -> PathBuf {
+    std::env::var_os(var).map(PathBuf::from).unwrap_or_else(|| PathBuf::from(DEFAULT_ASSETS_DIR))
+}
+
+/// Guest size
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Machine {
+    mem_size_mib: u64,
+    vcpu_count: u64,
+}
+
+/// `--config-file` contents: read-only rootfs, writable job drive, no network
+fn vm_config(kernel: &Path, rootfs: &Path, job_drive: &Path, machine: Machine) -> serde_json::Value {
+    serde_json::json!({
+        "boot-source": {
+            "kernel_image_path": kernel,
+            "boot_args": BOOT_ARGS,
+        },
+        "drives": [
+            {
+                "drive_id": "rootfs",
+                "path_on_host": rootfs,
+                "is_root_device": true,
+                "is_read_only": true,
+            },
+            {
+                "drive_id": "job",
+                "path_on_host": job_drive,
+                "is_root_device": false,
+                "is_read_only": false,
+            },
+        ],
+        "machine-config": {
+            "vcpu_count": machine.vcpu_count,
+            "mem_size_mib": machine.mem_size_mib,
+            "smt": false,
+        },
+    })
+}
+
+/// Environment as `NAME='value'` lines the guest init sources
+fn env_file(env: &[String]) -> String {
+    env.iter()
+        .filter_map(|var| var.split_once('='))
+        .map(|(name, value)| format!("{}='{}'\n", name, value.replace('\'', "'\\''")))
+        .collect()
+}
+
+/// Write the guest's control files next to the payload and pack it all into
+/// an ext4 image at `drive`
+async fn build_job_drive(
+    scratch: &Scratch,
+    drive: &Path,
+    test_case: &TestCase,
+    env: &[String],
+    nonce: &str,
+) -> Result<()> {
+    let root = &scratch.root;
+    std::fs::write(root.join(".input"), &test_case.input)?;
+    std::fs::write(root.join(".env"), env_file(env))?;
+    let args: Vec<u8> = test_case.args.iter().flat_map(|arg| arg.bytes().chain([0])).collect();
+    std::fs::write(root.join(".args"), args)?;
+    std::fs::write(root.join(".nonce"), nonce)?;
+
+    let payload_bytes: u64 = test_case.input.len() as u64 + scratch_size(root);
+    let size = payload_bytes + JOB_DRIVE_HEADROOM_MB * 1024 * 1024;
+    std::fs::File::create(drive)
+        .and_then(|file| file.set_len(size))
+        .with_context(|| format!("Failed to create job drive {}", drive.display()))?;
+
+    let mkfs = Command::new("mkfs.ext4")
+        .args(["-q", "-F", "-d"])
+        .arg(root)
+        .arg(drive)
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .context("Failed to run mkfs.ext4")?;
+    if !mkfs.status.success() {
+        bail!("mkfs.ext4 failed: {}", String::from_utf8_lossy(&mkfs.stderr).trim());
+    }
+    Ok(())
+}
+
+/// Bytes of regular files under `dir`
+fn scratch_size(dir: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| match entry.file_type() {
+            Ok(kind) if kind.is_dir() => scratch_size(&entry.path()),
+            Ok(kind) if kind.is_file() => entry.metadata().map(|m| m.len()).unwrap_or(0),
+            _ => 0,
+        })
+        .sum()
+}
+
+/// One line of the serial console
+#[derive(Debug, PartialEq)]
+enum ConsoleLine<'a> {
+    /// `@@optimus:<nonce> <field> [value]` from the guest init
+    Field(&'a str, Option<&'a str>),
+    /// Base64 payload of the current section
+    Data(Vec<u8>),
+    /// Kernel and firecracker output
+    Other,
+}
+
+fn parse_console_line<'a>(line: &'a str, marker: &str) -> ConsoleLine<'a> {
+    let line = line.trim_end_matches('\r');
+    if let Some(rest) = line.strip_prefix(marker).and_then(|rest| rest.strip_prefix(' ')) {
+        let mut parts = rest.splitn(2, ' ');
+        let field = parts.next().unwrap_or_default();
+        return ConsoleLine::Field(field, parts.next());
+    }
+    let is_base64 = !line.is_empty()
+        && line.bytes().all(|b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'/' | b'='));
+    match is_base64.then(|| general_purpose::STANDARD.decode(line)) {
+        Some(Ok(bytes)) => ConsoleLine::Data(bytes),
+        _ => ConsoleLine::Other,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Section {
+    Stdout,
+    Stderr,
+    Build,
+}
+
+/// What the guest reported about its run
+#[derive(Debug, Default)]
+struct GuestReport {
+    exit_code: Option<i64>,
+    cpu_usec: Option<u64>,
+    time_ms: Option<u64>,
+    oom: bool,
+    build: Vec<u8>,
+    build_too_large: bool,
+}
+
+fn keep_console_tail(tail: &mut Vec<u8>, chunk: &[u8]) {
+    tail.extend_from_slice(chunk);
+    if tail.len() > CONSOLE_TAIL_BYTES {
+        tail.drain(..tail.len() - CONSOLE_TAIL_BYTES);
+    }
+}
+
+#[async_trait]
+impl ExecutionEngine for FirecrackerEngine {
+    fn language_version(&self, language: &Language) -> Option<String> {
+        Some(self.config_manager.get_config(language).ok()?.version)
+    }
+
+    fn prepare_project(&self, language: &Language, project: &ProjectArchive) -> Result<ProjectBundle> {
+        archive::prepare(project, self.config_manager.get_project_command(language).ok().flatten())
+    }
+
+    /// Run one test case in a fresh microVM
+    ///
+    /// Same guardrails and runner contract as the container engines; the hard
+    /// timeout (plus boot time) kills the VM, and the scratch directories are
+    /// removed whatever happens.
+    #[allow(clippy::too_many_arguments)]
+    async fn execute_in_container(
+        &self,
+        language: &Language,
+        source_code: &str,
+        test_case: &TestCase,
+        project: Option<&ProjectBundle>,
+        timeout_ms: u64,
+        overrides: ResourceOverrides,
+        build: BuildCache<'_>,
+    ) -> Result<TestExecutionOutput> {
+        engine::validate_test_payload(source_code, test_case)?;
+        let rootfs = self.rootfs(language)?;
+
+        let mut output_buffer = output_budget::collector().await;
+        let mut phases = PhaseTimings::default();
+
+        // Payload and VM files live apart: mkfs.ext4 packs the whole payload directory
+        let create_start = Instant::now();
+        let payload = Scratch::create(&self.work_dir)?;
+        payload.populate(source_code, test_case, project, build)?;
+        let vm_files = Scratch::create(&self.work_dir)?;
+        let job_drive = vm_files.root.join("job.ext4");
+        let nonce = uuid::Uuid::new_v4().simple().to_string();
+        let env = sandbox::runner_env(language, timeout_ms, project, build, test_case);
+        build_job_drive(&payload, &job_drive, test_case, &env, &nonce).await?;
+        drop(payload);
+
+        let config_path = vm_files.root.join("vm.json");
+        let config = vm_config(&self.kernel, &rootfs, &job_drive, self.machine(language, overrides));
+        std::fs::write(&config_path, serde_json::to_vec(&config)?).context("Failed to write VM config")?;
+
+        let mut child = Command::new(&self.binary)
+            .arg("--no-api")
+            .arg("--config-file")
+            .arg(&config_path)
+            .arg("--id")
+            .arg(format!("optimus-{}", nonce))
+            .env_clear()
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("Failed to start {}", self.binary.display()))?;
+        phases.add(phases::CONTAINER_CREATE, create_start.elapsed());
+
+        let execute_start = Instant::now();
+        let mut console = BufReader::new(child.stdout.take().context("firecracker console not captured")?).lines();
+        let mut vmm_stderr = child.stderr.take().context("firecracker stderr not captured")?;
+        let marker = format!("@@optimus:{}", nonce);
+
+        let mut stdout = output_budget::CapturedStream::stdout();
+        let mut stderr = output_budget::CapturedStream::stderr();
+        let mut report = GuestReport::default();
+        let mut console_tail = Vec::new();
+        let execution_future = async {
+            let mut section = None;
+            let (mut console_open, mut stderr_open) = (true, true);
+            let mut chunk = vec![0u8; 4096];
+            while console_open || stderr_open {
+                tokio::select! {
+                    line = console.next_line(), if console_open => {
+                        let Ok(Some(line)) = line else {
+                            console_open = false;
+                            continue;
+                        };
+                        match parse_console_line(&line, &marker) {
+                            ConsoleLine::Field(field, value) => {
+                                section = None;
+                                let number = value.and_then(|v| v.trim().parse::<i64>().ok());
+                                match field {
+                                    "stdout" => section = Some(Section::Stdout),
+                                    "stderr" => section = Some(Section::Stderr),
+                                    "build" => section = Some(Section::Build),
+                                    "exit" => report.exit_code = number,
+                                    "cpu_usec" => report.cpu_usec = number.map(|n| n as u64),
+                                    "time_ms" => report.time_ms = number.map(|n| n as u64),
+                                    "oom" => report.oom = true,
+                                    other => debug!("Unknown guest report field '{}'", other),
+                                }
+                            }
+                            ConsoleLine::Data(bytes) => match section {
+                                Some(Section::Stdout) => stdout.push(&bytes, &mut output_buffer),
+                                Some(Section::Stderr) => stderr.push(&bytes, &mut output_buffer),
+                                Some(Section::Build) if report.build.len() + bytes.len() <= engine::MAX_BUILD_ARTIFACT_BYTES => {
+                                    report.build.extend_from_slice(&bytes)
+                                }
+                                Some(Section::Build) => report.build_too_large = true,
+                                None => keep_console_tail(&mut console_tail, line.as_bytes()),
+                            },
+                            ConsoleLine::Other => {
+                                keep_console_tail(&mut console_tail, line.as_bytes());
+                                keep_console_tail(&mut console_tail, b"\n");
+                            }
+                        }
+                    }
+                    read = vmm_stderr.read(&mut chunk), if stderr_open => match read {
+                        Ok(0) | Err(_) => stderr_open = false,
+                        Ok(n) => keep_console_tail(&mut console_tail, &chunk[..n]),
+                    },
+                }
+            }
+            child.wait().await
+        };
+        let waited = tokio::time::timeout(Duration::from_millis(timeout_ms + BOOT_ALLOWANCE_MS), execution_future).await;
+
+        let mut output = TestExecutionOutput::default();
+        match waited {
+            Ok(status) => {
+                status.context("Failed to wait for firecracker")?;
+                let Some(code) = report.exit_code else {
+                    bail!(
+                        "microVM exited without a result: {}",
+                        String::from_utf8_lossy(&console_tail).trim()
+                    );
+                };
+                let (out, out_truncated) = stdout.finish();
+                let (mut err, err_truncated) = stderr.finish();
+                match code {
+                    0 => {}
+                    engine::RUNNER_EXIT_COMPILE_ERROR => output.compile_error = true,
+                    engine::RUNNER_EXIT_WALL_TIMEOUT => output.timed_out = true,
+                    engine::RUNNER_EXIT_HARNESS_ERROR => output.internal_error = true,
+                    // The guest kernel's OOM killer is the only reliable memory signal
+                    _ if report.oom => output.memory_limit_exceeded = true,
+                    code => {
+                        output.runtime_error = true;
+                        if code == 137 {
+                            err.push_str("\n[Process killed: SIGKILL]");
+                        } else if code == 139 {
+                            err.push_str("\n[Process killed: segmentation fault]");
+                        }
+                    }
+                }
+                output.exit_code = Some(code);
+                output.stdout = out;
+                output.stderr = err;
+                output.stdout_truncated = out_truncated;
+                output.stderr_truncated = err_truncated;
+            }
+            Err(_) => {
+                println!("    ⚠ Execution timed out after {}ms - killing microVM", timeout_ms);
+                output.timed_out = true;
+                if let Err(e) = child.kill().await {
+                    eprintln!("    ⚠ Failed to kill timed-out microVM: {}", e);
+                }
+                output.stderr = String::from("\n[Execution timed out]");
+            }
+        }
+        // Boot and shutdown are not the program's time
+        output.execution_time_ms = report.time_ms.unwrap_or_else(|| execute_start.elapsed().as_millis() as u64);
+        phases.add(phases::EXECUTE, execute_start.elapsed());
+
+        if output_buffer.truncated() {
+            output.stderr.push_str(output_budget::TRUNCATION_MARKER);
+        }
+        if matches!(build, BuildCache::Build) {
+            if output.exit_code == Some(0) && !report.build_too_large && engine::has_build_stamp(&report.build) {
+                output.build_artifact = Some(std::mem::take(&mut report.build));
+            }
+        } else if output.exit_code.is_some() {
+            output.cpu_time_ms = report.cpu_usec.map(|usec| usec / 1000);
+        }
+        output.phases = phases;
+        Ok(output)
+    }
+
+    /// Not available: every test boots its own VM
+    async fn start_job_container<'a>(
+        &'a self,
+        _language: &Language,
+        _source_code: &str,
+        _overrides: ResourceOverrides,
+        _build: BuildCache<'_>,
+    ) -> Result<JobContainer<'a>> {
+        bail!("firecracker has no per-job containers")
+    }
+
+    async fn execute_in_job_container(
+        &self,
+        _container: &JobContainer<'_>,
+        _language: &Language,
+        _test_case: &TestCase,
+        _timeout_ms: u64,
+    ) -> Result<TestExecutionOutput> {
+        bail!("firecracker has no per-job containers")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_console_line() {
+        let marker = "@@optimus:abc";
+        assert_eq!(parse_console_line("@@optimus:abc exit 201\r", marker), ConsoleLine::Field("exit", Some("201")));
+        assert_eq!(parse_console_line("@@optimus:abc stdout", marker), ConsoleLine::Field("stdout", None));
+        assert_eq!(parse_console_line("aGVsbG8K", marker), ConsoleLine::Data(b"hello\n".to_vec()));
+        // A forged marker without the run's secret is just noise
+        assert_eq!(parse_console_line("@@optimus:xyz exit 0", marker), ConsoleLine::Other);
+        assert_eq!(parse_console_line("[    0.000000] Linux version 6.1", marker), ConsoleLine::Other);
+    }
+
+    #[test]
+    fn test_env_file_quotes_values() {
+        let env = vec!["LANGUAGE=python".to_string(), "GREETING=it's a=b".to_string()];
+        assert_eq!(env_file(&env), "LANGUAGE='python'\nGREETING='it'\\''s a=b'\n");
+    }
+
+    #[test]
+    fn test_vm_config() {
+        let machine = Machine { mem_size_mib: 320, vcpu_count: 1 };
+        let config = vm_config(Path::new("/k"), Path::new("/r.ext4"), Path::new("/j.ext4"), machine);
+        assert_eq!(config["drives"][0]["is_read_only"], true);
+        assert_eq!(config["drives"][1]["path_on_host"], "/j.ext4");
+        assert_eq!(config["machine-config"]["mem_size_mib"], 320);
+        assert!(config.get("network-interfaces").is_none());
+    }
+}
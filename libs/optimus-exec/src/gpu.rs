@@ -0,0 +1,86 @@
+//! GPU Devices - CUDA-Capable Containers
+//!
+//! **Responsibility:**
+//! Translate a language's `gpu` settings into Docker device requests, and
+//! detect whether this node can serve them.
+//!
+//! **Behaviour:**
+//! - GPU containers get an NVIDIA device request (`--gpus`): `count` devices,
+//!   or exactly the ones listed in `OPTIMUS_GPU_DEVICES` (e.g. "0,2") so
+//!   workers sharing a node can be given disjoint GPUs
+//! - Every other container gets `NVIDIA_VISIBLE_DEVICES=void`, so CUDA base
+//!   images (which default it to `all`) see no GPUs on hosts whose default
+//!   runtime is nvidia
+//! - A node can serve GPUs when dockerd has the `nvidia` runtime registered
+//!   (`nvidia-ctk runtime configure` does this)
+
+use crate::config::GpuConfig;
+use crate::docker_api;
+use anyhow::{Context, Result};
+use bollard::models::DeviceRequest;
+use bollard::Docker;
+
+/// Runtime the NVIDIA Container Toolkit registers with dockerd
+const NVIDIA_RUNTIME: &str = "nvidia";
+
+/// Devices this worker may hand out (`OPTIMUS_GPU_DEVICES`, comma-separated
+/// indexes or UUIDs); None leaves the choice to the driver
+pub fn pinned_devices() -> Option<Vec<String>> {
+    let devices: Vec<String> = std::env::var("OPTIMUS_GPU_DEVICES")
+        .ok()?
+        .split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(str::to_string)
+        .collect();
+    (!devices.is_empty()).then_some(devices)
+}
+
+/// Device request for a GPU container (`--gpus` equivalent)
+pub fn device_request(config: &GpuConfig, pinned: Option<Vec<String>>) -> DeviceRequest {
+    let mut capabilities = vec!["gpu".to_string()];
+    capabilities.extend(config.capabilities.iter().cloned());
+    DeviceRequest {
+        driver: Some(NVIDIA_RUNTIME.to_string()),
+        count: pinned.is_none().then_some(config.count as i64),
+        device_ids: pinned,
+        capabilities: Some(vec![capabilities]),
+        options: None,
+    }
+}
+
+/// Environment masking GPUs from containers that weren't given any
+pub const MASK_ENV: &str = "NVIDIA_VISIBLE_DEVICES=void";
+
+/// Whether this node's Docker can attach NVIDIA GPUs
+pub async fn available(docker: &Docker) -> Result<bool> {
+    let info = docker_api::call("info", || docker.info())
+        .await
+        .context("Failed to query Docker info")?;
+    Ok(info.runtimes.is_some_and(|runtimes| runtimes.contains_key(NVIDIA_RUNTIME)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> GpuConfig {
+        serde_json::from_str(r#"{"count": 2}"#).unwrap()
+    }
+
+    #[test]
+    fn test_device_request_by_count() {
+        let request = device_request(&config(), None);
+        assert_eq!(request.driver.as_deref(), Some("nvidia"));
+        assert_eq!(request.count, Some(2));
+        assert_eq!(request.device_ids, None);
+        assert_eq!(request.capabilities, Some(vec![vec!["gpu".to_string(), "compute".to_string(), "utility".to_string()]]));
+    }
+
+    #[test]
+    fn test_pinned_devices_replace_count() {
+        let request = device_request(&config(), Some(vec!["0".to_string(), "2".to_string()]));
+        assert_eq!(request.count, None);
+        assert_eq!(request.device_ids, Some(vec!["0".to_string(), "2".to_string()]));
+    }
+}
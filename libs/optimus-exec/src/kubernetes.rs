@@ -0,0 +1,553 @@
+//! Kubernetes Engine - One Job per Execution
+//!
+//! **Responsibility:**
+//! Run submissions as Kubernetes Jobs (`OPTIMUS_CONTAINER_RUNTIME=kubernetes`),
+//! so workers only need API access to their namespace instead of a
+//! privileged Docker socket.
+//!
+//! **How:**
+//! Every test creates a Job whose single pod runs the language image with
+//! the language limits as both requests and limits. The pod starts with its
+//! stdin open; once it runs, the worker attaches and sends one tar holding
+//! what a container would have received (source, project files, fixtures,
+//! cached build) plus the test's input, which the pod unpacks before the
+//! universal runner starts. stdout and stderr stream back over the attach
+//! connection; the exit code and OOM kills come from the pod status and the
+//! CPU time from the container's termination message. The Job is deleted
+//! afterwards - its TTL cleans up after a worker that died mid-run.
+//!
+//! **Isolation:**
+//! - The images' unprivileged `optimus` user, no privilege escalation, all
+//!   capabilities dropped, the runtime's default seccomp profile
+//! - No service account token and no service links
+//! - A language's `runtime` becomes the pod's RuntimeClass (gVisor, Kata, ...)
+//!   and its `gpu` an `nvidia.com/gpu` limit
+//! - Kubernetes cannot switch networking off per pod: apply
+//!   `k8s/execution-network-policy.yaml`, which denies all traffic to and
+//!   from execution pods
+//!
+//! **Differences from containers:**
+//! - No per-job containers (`EXECUTION_MODE=per_job` falls back to one Job
+//!   per test); scheduling and image pulls are not billed to the test
+//! - Compiles are not cached by this engine (builds cached by other workers
+//!   are reused)
+
+use crate::archive::{self, ProjectBundle};
+use crate::config::LanguageConfigManager;
+use crate::container_labels;
+use crate::engine::{self, BuildCache, ExecutionEngine, JobContainer, ResourceOverrides};
+use crate::evaluator::TestExecutionOutput;
+use crate::gpu;
+use crate::output_budget;
+use crate::phases::{self, PhaseTimings};
+use crate::sandbox;
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use k8s_openapi::api::batch::v1::{Job, JobSpec};
+use k8s_openapi::api::core::v1::{
+    Capabilities, Container, ContainerStateTerminated, EnvVar, Pod, PodSecurityContext, PodSpec, PodTemplateSpec,
+    ResourceRequirements, SeccompProfile, SecurityContext,
+};
+use k8s_openapi::apimachinery::pkg::api::resource::Quantity;
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta;
+use kube::api::{Api, AttachParams, DeleteParams, ListParams, PostParams};
+use kube::Client;
+use optimus_common::types::{Language, ProjectArchive, TestCase};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::sync::OnceCell;
+use tracing::debug;
+
+/// Name of the pod's only container
+const CONTAINER: &str = "run";
+
+/// Pod command: unpack the payload from stdin, run the runner on the test's
+/// input, then leave the CPU time as the termination message. A payload that
+/// doesn't unpack is a harness error (`engine::RUNNER_EXIT_HARNESS_ERROR`).
+const POD_SCRIPT: &str = r#"tar -xf - -C / || exit 203
+/opt/optimus/runner.sh "$@" < /tmp/optimus/.stdin
+status=$?
+cat /code/.cpu_usec > /dev/termination-log 2>/dev/null
+exit $status"#;
+
+/// The images' `optimus` user
+const RUN_AS_USER: i64 = 1000;
+
+/// Scheduling and image pull allowance before a pod counts as failed
+const POD_START_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// How long the kubelet gets to report a finished container's status
+const STATUS_TIMEOUT: Duration = Duration::from_secs(30);
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Finished Jobs left behind by a crashed worker are garbage-collected after this
+const JOB_TTL_SECS: i32 = 300;
+
+/// Waiting reasons that won't resolve by waiting longer
+const FATAL_WAITING_REASONS: &[&str] = &[
+    "ErrImagePull",
+    "ImagePullBackOff",
+    "InvalidImageName",
+    "ErrImageNeverPull",
+    "CreateContainerConfigError",
+    "CreateContainerError",
+    "RunContainerError",
+];
+
+pub struct KubernetesEngine {
+    /// Client and namespace, created on first use
+    client: OnceCell<(Client, String)>,
+    config_manager: LanguageConfigManager,
+}
+
+impl KubernetesEngine {
+    pub fn new_with_config(config_manager: &LanguageConfigManager) -> Self {
+        KubernetesEngine {
+            client: OnceCell::new(),
+            config_manager: config_manager.clone(),
+        }
+    }
+
+    async fn apis(&self) -> Result<(Api<Job>, Api<Pod>)> {
+        let (client, namespace) = self.client.get_or_try_init(connect).await?;
+        Ok((Api::namespaced(client.clone(), namespace), Api::namespaced(client.clone(), namespace)))
+    }
+
+    /// Pod resources: a job-level override wins over the language default
+    fn limits(&self, language: &Language, overrides: ResourceOverrides) -> PodLimits {
+        let memory_mb = overrides
+            .memory_limit_mb
+            .or_else(|| self.config_manager.get_memory_limit_mb(language).ok())
+            .unwrap_or(256) as u64;
+        let cpus = overrides
+            .cpu_limit
+            .or_else(|| self.config_manager.get_cpu_limit(language).ok().map(f64::from))
+            .unwrap_or(0.5);
+        let gpus = self
+            .config_manager
+            .get_gpu(language)
+            .filter(|gpu| overrides.gpu || gpu.all_jobs)
+            .map(|gpu| gpu.count);
+        PodLimits {
+            memory_mb,
+            cpu_millis: ((cpus * 1000.0).round() as u64).max(1),
+            gpus,
+        }
+    }
+}
+
+/// Client from the in-cluster service account (or a kubeconfig outside a
+/// cluster), for `OPTIMUS_K8S_NAMESPACE` or the client's default namespace
+async fn connect() -> Result<(Client, String)> {
+    let client = Client::try_default().await.context("Failed to create a Kubernetes client")?;
+    let namespace = std::env::var("OPTIMUS_K8S_NAMESPACE").unwrap_or_else(|_| client.default_namespace().to_string());
+    Ok((client, namespace))
+}
+
+/// Confirm the API server answers and the worker may manage Jobs
+pub async fn check() -> Result<()> {
+    let (client, namespace) = connect().await?;
+    let jobs: Api<Job> = Api::namespaced(client, &namespace);
+    jobs.list(&ListParams::default().limit(1))
+        .await
+        .with_context(|| format!("Failed to list Jobs in namespace '{}' (check the worker's RBAC role)", namespace))?;
+    Ok(())
+}
+
+/// Resources of one execution pod
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct PodLimits {
+    memory_mb: u64,
+    cpu_millis: u64,
+    gpus: Option<u32>,
+}
+
+/// Label values must be at most 63 characters and end alphanumerically
+fn label_value(value: &str) -> String {
+    let value: String = value.chars().take(63).collect();
+    value.trim_end_matches(|c: char| !c.is_ascii_alphanumeric()).to_string()
+}
+
+/// The Job running one test: `env` is the runner's environment, `args` the
+/// program's arguments
+#[allow(clippy::too_many_arguments)]
+fn job_manifest(
+    name: &str,
+    language: &Language,
+    image: &str,
+    env: &[String],
+    args: &[String],
+    limits: PodLimits,
+    runtime_class: Option<String>,
+    deadline_secs: i64,
+) -> Job {
+    let labels: BTreeMap<String, String> = container_labels::labels(language)
+        .into_iter()
+        .map(|(key, value)| (key, label_value(&value)))
+        .collect();
+
+    let mut quantities = BTreeMap::from([
+        ("memory".to_string(), Quantity(format!("{}Mi", limits.memory_mb))),
+        ("cpu".to_string(), Quantity(format!("{}m", limits.cpu_millis))),
+    ]);
+    if let Some(gpus) = limits.gpus {
+        quantities.insert("nvidia.com/gpu".to_string(), Quantity(gpus.to_string()));
+    }
+
+    let command = [engine::JOB_CONTAINER_INIT, "--", "/bin/sh", "-c", POD_SCRIPT, "optimus"];
+    let container = Container {
+        name: CONTAINER.to_string(),
+        image: Some(image.to_string()),
+        command: Some(command.into_iter().map(String::from).collect()),
+        args: Some(args.to_vec()),
+        env: Some(
+            env.iter()
+                .map(|var| {
+                    let (name, value) = var.split_once('=').unwrap_or((var, ""));
+                    EnvVar { name: name.to_string(), value: Some(value.to_string()), ..Default::default() }
+                })
+                .collect(),
+        ),
+        // The payload arrives on stdin once the worker attaches
+        stdin: Some(true),
+        stdin_once: Some(true),
+        resources: Some(ResourceRequirements {
+            limits: Some(quantities.clone()),
+            requests: Some(quantities),
+            ..Default::default()
+        }),
+        security_context: Some(SecurityContext {
+            allow_privilege_escalation: Some(false),
+            capabilities: Some(Capabilities { drop: Some(vec!["ALL".to_string()]), ..Default::default() }),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    Job {
+        metadata: ObjectMeta {
+            name: Some(name.to_string()),
+            labels: Some(labels.clone()),
+            ..Default::default()
+        },
+        spec: Some(JobSpec {
+            backoff_limit: Some(0),
+            active_deadline_seconds: Some(deadline_secs),
+            ttl_seconds_after_finished: Some(JOB_TTL_SECS),
+            template: PodTemplateSpec {
+                metadata: Some(ObjectMeta { labels: Some(labels), ..Default::default() }),
+                spec: Some(PodSpec {
+                    containers: vec![container],
+                    restart_policy: Some("Never".to_string()),
+                    automount_service_account_token: Some(false),
+                    enable_service_links: Some(false),
+                    runtime_class_name: runtime_class,
+                    security_context: Some(PodSecurityContext {
+                        run_as_non_root: Some(true),
+                        run_as_user: Some(RUN_AS_USER),
+                        run_as_group: Some(RUN_AS_USER),
+                        seccomp_profile: Some(SeccompProfile { type_: "RuntimeDefault".to_string(), ..Default::default() }),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }),
+            },
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+/// Wait for the Job's pod to start its container and return the pod's name
+async fn wait_for_pod(pods: &Api<Pod>, job_name: &str) -> Result<String> {
+    let selector = ListParams::default().labels(&format!("job-name={}", job_name));
+    let deadline = Instant::now() + POD_START_TIMEOUT;
+    let mut last_state = String::from("no pod yet");
+    loop {
+        let list = pods.list(&selector).await.context("Failed to list execution pods")?;
+        if let Some(pod) = list.items.first() {
+            let name = pod.metadata.name.clone().unwrap_or_default();
+            let status = pod.status.as_ref();
+            let container = status
+                .and_then(|status| status.container_statuses.as_ref())
+                .and_then(|statuses| statuses.iter().find(|c| c.name == CONTAINER));
+            let state = container.and_then(|c| c.state.as_ref());
+            if state.is_some_and(|state| state.running.is_some()) {
+                return Ok(name);
+            }
+            if let Some(terminated) = state.and_then(|state| state.terminated.as_ref()) {
+                bail!(
+                    "Execution pod {} exited before receiving its payload ({}: {})",
+                    name,
+                    terminated.reason.as_deref().unwrap_or("exit"),
+                    terminated.message.as_deref().unwrap_or(&terminated.exit_code.to_string())
+                );
+            }
+            if let Some(waiting) = state.and_then(|state| state.waiting.as_ref()) {
+                let reason = waiting.reason.as_deref().unwrap_or_default();
+                let message = waiting.message.as_deref().unwrap_or_default();
+                if FATAL_WAITING_REASONS.contains(&reason) {
+                    bail!("Execution pod {} cannot start: {} {}", name, reason, message);
+                }
+                last_state = format!("{} {}", reason, message);
+            } else if let Some(condition) = status
+                .and_then(|status| status.conditions.as_ref())
+                .and_then(|conditions| conditions.iter().find(|c| c.status == "False"))
+            {
+                last_state = format!("{} {}", condition.type_, condition.message.as_deref().unwrap_or_default());
+            }
+            if status.and_then(|status| status.phase.as_deref()) == Some("Failed") {
+                bail!("Execution pod {} failed to start: {}", name, last_state.trim());
+            }
+        }
+        if Instant::now() >= deadline {
+            bail!("Execution pod for {} did not start within {}s ({})", job_name, POD_START_TIMEOUT.as_secs(), last_state.trim());
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// The run container's final state, once the kubelet reports it
+async fn wait_for_exit(pods: &Api<Pod>, pod_name: &str) -> Result<ContainerStateTerminated> {
+    let deadline = Instant::now() + STATUS_TIMEOUT;
+    loop {
+        let pod = pods.get(pod_name).await.context("Failed to read execution pod status")?;
+        let terminated = pod
+            .status
+            .and_then(|status| status.container_statuses)
+            .and_then(|statuses| statuses.into_iter().find(|c| c.name == CONTAINER))
+            .and_then(|container| container.state)
+            .and_then(|state| state.terminated);
+        if let Some(terminated) = terminated {
+            return Ok(terminated);
+        }
+        if Instant::now() >= deadline {
+            bail!("Execution pod {} has no exit status after {}s", pod_name, STATUS_TIMEOUT.as_secs());
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Deletes a Job (and its pod) when dropped, whatever happened to the run
+struct JobGuard {
+    jobs: Api<Job>,
+    name: String,
+}
+
+impl Drop for JobGuard {
+    fn drop(&mut self) {
+        let jobs = self.jobs.clone();
+        let name = std::mem::take(&mut self.name);
+        engine::spawn_cleanup(async move {
+            if let Err(e) = jobs.delete(&name, &DeleteParams::background().grace_period(0)).await {
+                eprintln!("⚠ Failed to delete Job {}: {}", name, e);
+            }
+        });
+    }
+}
+
+#[async_trait]
+impl ExecutionEngine for KubernetesEngine {
+    fn language_version(&self, language: &Language) -> Option<String> {
+        Some(self.config_manager.get_config(language).ok()?.version)
+    }
+
+    fn prepare_project(&self, language: &Language, project: &ProjectArchive) -> Result<ProjectBundle> {
+        archive::prepare(project, self.config_manager.get_project_command(language).ok().flatten())
+    }
+
+    /// Run one test case in its own Job
+    ///
+    /// Same guardrails and runner contract as the container engines; the hard
+    /// timeout starts once the payload is delivered, and the Job is deleted
+    /// whatever happens.
+    #[allow(clippy::too_many_arguments)]
+    async fn execute_in_container(
+        &self,
+        language: &Language,
+        source_code: &str,
+        test_case: &TestCase,
+        project: Option<&ProjectBundle>,
+        timeout_ms: u64,
+        overrides: ResourceOverrides,
+        build: BuildCache<'_>,
+    ) -> Result<TestExecutionOutput> {
+        engine::validate_test_payload(source_code, test_case)?;
+        let payload = engine::payload_archive(source_code, test_case, project, build)?;
+        let image = self.config_manager.get_image(language)?;
+
+        let mut output_buffer = output_budget::collector().await;
+        let mut phases = PhaseTimings::default();
+        let (jobs, pods) = self.apis().await?;
+
+        let mut env = sandbox::runner_env(language, timeout_ms, project, build, test_case);
+        let limits = self.limits(language, overrides);
+        if limits.gpus.is_none() {
+            env.push(gpu::MASK_ENV.to_string());
+        }
+        let name = format!("optimus-{}", uuid::Uuid::new_v4().simple());
+        let deadline_secs = (POD_START_TIMEOUT.as_secs() + timeout_ms / 1000 + STATUS_TIMEOUT.as_secs()) as i64;
+        let manifest = job_manifest(
+            &name,
+            language,
+            &image,
+            &env,
+            &test_case.args,
+            limits,
+            self.config_manager.get_runtime(language),
+            deadline_secs,
+        );
+
+        let create_start = Instant::now();
+        jobs.create(&PostParams::default(), &manifest)
+            .await
+            .context("Failed to create Kubernetes Job")?;
+        let _guard = JobGuard { jobs: jobs.clone(), name: name.clone() };
+        let pod_name = wait_for_pod(&pods, &name).await?;
+        let attach = AttachParams::default().container(CONTAINER).stdin(true).stdout(true).stderr(true);
+        let mut attached = pods.attach(&pod_name, &attach).await.context("Failed to attach to execution pod")?;
+        phases.add(phases::CONTAINER_CREATE, create_start.elapsed());
+
+        let execute_start = Instant::now();
+        // The writer stays open until the run ends: closing it may hang up the whole connection
+        let mut pod_stdin = attached.stdin().context("execution pod stdin not attached")?;
+        let mut pod_stdout = attached.stdout().context("execution pod stdout not attached")?;
+        let mut pod_stderr = attached.stderr().context("execution pod stderr not attached")?;
+        tokio::time::timeout(POD_START_TIMEOUT, async {
+            pod_stdin.write_all(&payload).await?;
+            pod_stdin.flush().await
+        })
+        .await
+        .context("Timed out sending the payload")?
+        .context("Failed to send the payload")?;
+        drop(payload);
+        let start_time = Instant::now();
+
+        let mut stdout = output_budget::CapturedStream::stdout();
+        let mut stderr = output_budget::CapturedStream::stderr();
+        let execution_future = async {
+            let (mut out_open, mut err_open) = (true, true);
+            let mut out_chunk = vec![0u8; 8192];
+            let mut err_chunk = vec![0u8; 8192];
+            // Drained but discarded past the capture limit or output budget
+            while out_open || err_open {
+                tokio::select! {
+                    read = pod_stdout.read(&mut out_chunk), if out_open => match read {
+                        Ok(0) | Err(_) => out_open = false,
+                        Ok(n) => stdout.push(&out_chunk[..n], &mut output_buffer),
+                    },
+                    read = pod_stderr.read(&mut err_chunk), if err_open => match read {
+                        Ok(0) | Err(_) => err_open = false,
+                        Ok(n) => stderr.push(&err_chunk[..n], &mut output_buffer),
+                    },
+                }
+            }
+        };
+        let waited = tokio::time::timeout(Duration::from_millis(timeout_ms), execution_future).await;
+        let execution_time_ms = start_time.elapsed().as_millis() as u64;
+        drop(pod_stdin);
+        attached.abort();
+
+        let mut output = TestExecutionOutput { execution_time_ms, ..Default::default() };
+        match waited {
+            Ok(()) => {
+                let terminated = wait_for_exit(&pods, &pod_name).await?;
+                let code = i64::from(terminated.exit_code);
+                let (out, out_truncated) = stdout.finish();
+                let (mut err, err_truncated) = stderr.finish();
+                match code {
+                    0 => {}
+                    engine::RUNNER_EXIT_COMPILE_ERROR => output.compile_error = true,
+                    engine::RUNNER_EXIT_WALL_TIMEOUT => output.timed_out = true,
+                    engine::RUNNER_EXIT_HARNESS_ERROR => output.internal_error = true,
+                    _ if terminated.reason.as_deref() == Some("OOMKilled") => output.memory_limit_exceeded = true,
+                    code => {
+                        output.runtime_error = true;
+                        if code == 137 {
+                            err.push_str("\n[Container killed: SIGKILL]");
+                        } else if code == 139 {
+                            err.push_str("\n[Container killed: segmentation fault]");
+                        }
+                    }
+                }
+                if !matches!(build, BuildCache::Build) {
+                    output.cpu_time_ms = terminated
+                        .message
+                        .as_deref()
+                        .and_then(|message| message.trim().parse::<u64>().ok())
+                        .map(|usec| usec / 1000);
+                }
+                output.exit_code = Some(code);
+                output.stdout = out;
+                output.stderr = err;
+                output.stdout_truncated = out_truncated;
+                output.stderr_truncated = err_truncated;
+            }
+            Err(_) => {
+                println!("    ⚠ Execution timed out after {}ms - deleting Job", timeout_ms);
+                output.timed_out = true;
+                output.stderr = String::from("\n[Execution timed out]");
+            }
+        }
+        phases.add(phases::EXECUTE, execute_start.elapsed());
+        debug!(job = %name, pod = %pod_name, "Execution pod finished");
+
+        if output_buffer.truncated() {
+            output.stderr.push_str(output_budget::TRUNCATION_MARKER);
+        }
+        output.phases = phases;
+        Ok(output)
+    }
+
+    /// Not available: every test gets its own Job
+    async fn start_job_container<'a>(
+        &'a self,
+        _language: &Language,
+        _source_code: &str,
+        _overrides: ResourceOverrides,
+        _build: BuildCache<'_>,
+    ) -> Result<JobContainer<'a>> {
+        bail!("kubernetes has no per-job containers")
+    }
+
+    async fn execute_in_job_container(
+        &self,
+        _container: &JobContainer<'_>,
+        _language: &Language,
+        _test_case: &TestCase,
+        _timeout_ms: u64,
+    ) -> Result<TestExecutionOutput> {
+        bail!("kubernetes has no per-job containers")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_job_manifest() {
+        let limits = PodLimits { memory_mb: 512, cpu_millis: 1500, gpus: Some(1) };
+        let env = vec!["LANGUAGE=python".to_string(), "GREETING=a=b".to_string()];
+        let job = job_manifest("optimus-x", &Language::Python, "optimus-python:3.11", &env, &[], limits, Some("gvisor".to_string()), 400);
+        let pod = job.spec.as_ref().unwrap().template.spec.as_ref().unwrap();
+        let container = &pod.containers[0];
+        let limits = container.resources.as_ref().unwrap().limits.as_ref().unwrap();
+        assert_eq!(limits["memory"].0, "512Mi");
+        assert_eq!(limits["cpu"].0, "1500m");
+        assert_eq!(limits["nvidia.com/gpu"].0, "1");
+        assert_eq!(container.env.as_ref().unwrap()[1].value.as_deref(), Some("a=b"));
+        assert_eq!(container.stdin, Some(true));
+        assert_eq!(pod.runtime_class_name.as_deref(), Some("gvisor"));
+        assert_eq!(pod.automount_service_account_token, Some(false));
+        assert_eq!(pod.security_context.as_ref().unwrap().run_as_non_root, Some(true));
+    }
+
+    #[test]
+    fn test_label_value() {
+        assert_eq!(label_value("optimus-worker-7d9f-"), "optimus-worker-7d9f");
+        assert_eq!(label_value(&"a".repeat(80)).len(), 63);
+    }
+}
@@ -0,0 +1,46 @@
+//! Optimus Exec - Execution and Scoring Library
+//!
+//! **Responsibility:**
+//! Run submissions against test cases in an isolated engine and score the
+//! results - the logic behind `optimus-worker`, for any tool that needs it
+//! (local runs, smoke tests, external graders) without a Redis queue.
+//!
+//! **Entry points:**
+//! - `engine::connect` picks the engine for the container runtime recorded
+//!   with `container_runtime::init` (Docker unless told otherwise);
+//!   `DockerEngine` can also be built directly
+//! - `ExecutionEngine::execute_in_container` runs one test case;
+//!   `engine::execute_job_async` runs a whole job (deduplication, build
+//!   cache, cancellation through Redis)
+//! - `evaluator::evaluate` turns raw outputs into a scored `ExecutionResult`
+//! - `config::LanguageConfigManager` loads images and limits from
+//!   `languages.json`
+//!
+//! **Process-wide settings:**
+//! Call once before running anything: `docker_api::init` (concurrent Docker
+//! calls), `output_budget::init` (output memory and capture limits) and
+//! `metrics::init_metrics` if the metrics are exported. Without them the
+//! defaults apply.
+
+pub mod archive;
+pub mod config;
+pub mod container_labels;
+pub mod container_runtime;
+pub mod containerd;
+pub mod docker_api;
+pub mod engine;
+pub mod evaluator;
+pub mod firecracker;
+pub mod gpu;
+pub mod kubernetes;
+pub mod metrics;
+pub mod output_budget;
+pub mod phases;
+pub mod podman;
+pub mod sandbox;
+pub mod sandbox_audit;
+pub mod seccomp;
+
+pub use config::LanguageConfigManager;
+pub use engine::{DockerEngine, ExecutionEngine, ResourceOverrides};
+pub use evaluator::{evaluate, TestExecutionOutput};
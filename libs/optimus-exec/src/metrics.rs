@@ -0,0 +1,159 @@
+//! Execution Metrics - Prometheus Registry
+//!
+//! Signals of the execution layer (container API calls, output capture,
+//! sandbox heuristics, timeout kills, build cache, phase timings), under the
+//! `optimus_worker_` names dashboards already use. Embedders register their
+//! own collectors in `REGISTRY` and expose `render_metrics()`; the worker
+//! serves it on its health server at `/metrics`.
+
+use lazy_static::lazy_static;
+use prometheus::{CounterVec, Encoder, HistogramOpts, HistogramVec, IntCounter, IntGauge, Opts, Registry, TextEncoder};
+
+lazy_static! {
+    pub static ref REGISTRY: Registry = Registry::new();
+
+    // Docker API calls currently holding a limiter permit
+    pub static ref DOCKER_CALLS_IN_FLIGHT: IntGauge = IntGauge::new(
+        "optimus_worker_docker_calls_in_flight",
+        "Docker API calls currently in flight"
+    )
+    .expect("metric can be created");
+
+    // Calls that had to wait for a permit (limiter saturated)
+    pub static ref DOCKER_CALLS_SATURATED: CounterVec = CounterVec::new(
+        Opts::new("optimus_worker_docker_calls_saturated_total", "Docker API calls that waited for a free slot"),
+        &["op"]
+    )
+    .expect("metric can be created");
+
+    // Retries after a daemon 5xx
+    pub static ref DOCKER_CALL_RETRIES: CounterVec = CounterVec::new(
+        Opts::new("optimus_worker_docker_call_retries_total", "Docker API calls retried after a daemon error"),
+        &["op"]
+    )
+    .expect("metric can be created");
+
+    // Calls that failed after exhausting retries (or with a non-retryable error)
+    pub static ref DOCKER_CALL_FAILURES: CounterVec = CounterVec::new(
+        Opts::new("optimus_worker_docker_call_failures_total", "Docker API calls that failed"),
+        &["op"]
+    )
+    .expect("metric can be created");
+
+    // Output bytes currently reserved by in-flight log collection
+    pub static ref OUTPUT_BUFFER_BYTES: IntGauge = IntGauge::new(
+        "optimus_worker_output_buffer_bytes",
+        "Container output bytes currently buffered by log collection"
+    )
+    .expect("metric can be created");
+
+    // Executions whose output was cut off by the output budget
+    pub static ref OUTPUT_TRUNCATIONS: IntCounter = IntCounter::new(
+        "optimus_worker_output_truncations_total",
+        "Executions whose output was truncated by the worker output budget"
+    )
+    .expect("metric can be created");
+
+    // Sandbox escape heuristics that fired (check = stray_processes | excessive_pids | write_outside_workdir)
+    pub static ref SANDBOX_FLAGS: CounterVec = CounterVec::new(
+        Opts::new("optimus_worker_sandbox_flags_total", "Tests flagged by sandbox escape heuristics"),
+        &["language", "check"]
+    )
+    .expect("metric can be created");
+
+    // Compiled-language jobs by build cache outcome (result = hit | miss)
+    pub static ref BUILD_CACHE: CounterVec = CounterVec::new(
+        Opts::new("optimus_worker_build_cache_total", "Compiled-language jobs by build cache outcome"),
+        &["language", "result"]
+    )
+    .expect("metric can be created");
+
+    // Container creations that had to wait for the language's max_containers cap
+    pub static ref CONTAINER_CAP_WAITS: CounterVec = CounterVec::new(
+        Opts::new("optimus_worker_container_cap_waits_total", "Container creations delayed by the per-language container cap"),
+        &["language"]
+    )
+    .expect("metric can be created");
+
+    // Timeout firing -> container stopped (or force-removed), per timed-out test
+    pub static ref KILL_LATENCY_MS: HistogramVec = HistogramVec::new(
+        HistogramOpts::new("optimus_worker_kill_latency_ms", "Time from a hard timeout firing until the container stopped, in milliseconds")
+            .buckets(vec![5.0, 25.0, 100.0, 250.0, 500.0, 1000.0, 2500.0, 5000.0, 10000.0, 30000.0]),
+        &["language"]
+    )
+    .expect("metric can be created");
+
+    // Timed-out containers by how they went down (outcome = killed | force_removed | failed)
+    pub static ref TIMEOUT_KILLS: CounterVec = CounterVec::new(
+        Opts::new("optimus_worker_timeout_kills_total", "Timed-out containers by kill outcome"),
+        &["language", "outcome"]
+    )
+    .expect("metric can be created");
+
+    // Output the program was still producing between the timeout and its death
+    pub static ref POST_TIMEOUT_OUTPUT_BYTES: HistogramVec = HistogramVec::new(
+        HistogramOpts::new("optimus_worker_post_timeout_output_bytes", "Output bytes streamed after a hard timeout fired")
+            .buckets(vec![0.0, 1024.0, 16384.0, 65536.0, 262144.0, 1048576.0, 4194304.0, 16777216.0]),
+        &["language"]
+    )
+    .expect("metric can be created");
+
+    // Worker time per job phase (phase = permit_wait | image_check | container_create | execute | evaluate | persist)
+    pub static ref PHASE_DURATION_MS: HistogramVec = HistogramVec::new(
+        HistogramOpts::new("optimus_worker_phase_duration_ms", "Time a job spent in each worker phase, in milliseconds")
+            .buckets(vec![1.0, 5.0, 25.0, 100.0, 250.0, 500.0, 1000.0, 2500.0, 5000.0, 10000.0, 30000.0]),
+        &["language", "phase"]
+    )
+    .expect("metric can be created");
+}
+
+/// Register the execution metrics (call once at startup)
+pub fn init_metrics() {
+    REGISTRY
+        .register(Box::new(DOCKER_CALLS_IN_FLIGHT.clone()))
+        .expect("collector can be registered");
+    REGISTRY
+        .register(Box::new(DOCKER_CALLS_SATURATED.clone()))
+        .expect("collector can be registered");
+    REGISTRY
+        .register(Box::new(DOCKER_CALL_RETRIES.clone()))
+        .expect("collector can be registered");
+    REGISTRY
+        .register(Box::new(DOCKER_CALL_FAILURES.clone()))
+        .expect("collector can be registered");
+    REGISTRY
+        .register(Box::new(OUTPUT_BUFFER_BYTES.clone()))
+        .expect("collector can be registered");
+    REGISTRY
+        .register(Box::new(OUTPUT_TRUNCATIONS.clone()))
+        .expect("collector can be registered");
+    REGISTRY
+        .register(Box::new(SANDBOX_FLAGS.clone()))
+        .expect("collector can be registered");
+    REGISTRY
+        .register(Box::new(BUILD_CACHE.clone()))
+        .expect("collector can be registered");
+    REGISTRY
+        .register(Box::new(CONTAINER_CAP_WAITS.clone()))
+        .expect("collector can be registered");
+    REGISTRY
+        .register(Box::new(KILL_LATENCY_MS.clone()))
+        .expect("collector can be registered");
+    REGISTRY
+        .register(Box::new(TIMEOUT_KILLS.clone()))
+        .expect("collector can be registered");
+    REGISTRY
+        .register(Box::new(POST_TIMEOUT_OUTPUT_BYTES.clone()))
+        .expect("collector can be registered");
+    REGISTRY
+        .register(Box::new(PHASE_DURATION_MS.clone()))
+        .expect("collector can be registered");
+}
+
+/// Render metrics in Prometheus text format
+pub fn render_metrics() -> String {
+    let encoder = TextEncoder::new();
+    let mut buffer = Vec::new();
+    encoder.encode(&REGISTRY.gather(), &mut buffer).unwrap();
+    String::from_utf8(buffer).unwrap()
+}
@@ -0,0 +1,291 @@
+//! Output Budget - Worker Self-Limits for Log Collection
+//!
+//! **Responsibility:**
+//! Keep a burst of output-heavy jobs from OOM-killing the worker pod (and
+//! every unrelated in-flight job with it).
+//!
+//! **Limits:**
+//! - Byte budget shared by all in-flight log collection
+//!   (`MAX_OUTPUT_BUFFER_MB`, default: 25% of the cgroup memory limit,
+//!   256 MB when no limit is visible)
+//! - At most `MAX_LOG_COLLECTORS` containers have their logs followed at once;
+//!   further executions wait before their container is created
+//! - Each test keeps at most `MAX_CAPTURED_OUTPUT_KB` of stdout (default
+//!   64 KB), the judged stream, and `MAX_CAPTURED_STDERR_KB` of stderr
+//!   (default: same as stdout), which also bounds the stored result
+//! - `STDERR_RETENTION=tail` keeps the end of stderr instead of the start -
+//!   where the stack trace usually is after pages of debug prints
+//!
+//! Output that doesn't fit is dropped and the stream is flagged truncated
+//! (`stdout_truncated` / `stderr_truncated`); running out of the shared budget
+//! also marks the test's stderr. The job still completes (and most likely fails
+//! its comparison) instead of taking the worker down.
+
+use crate::metrics;
+use std::sync::OnceLock;
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+/// Budget accounting granularity
+const CHUNK_BYTES: usize = 1024;
+const DEFAULT_BUDGET_BYTES: u64 = 256 * 1024 * 1024;
+const DEFAULT_MAX_COLLECTORS: usize = 8;
+/// Share of the cgroup memory limit given to buffered output
+const CGROUP_SHARE_PERCENT: u64 = 25;
+
+pub const TRUNCATION_MARKER: &str = "\n[Output truncated: worker output buffer limit reached]";
+
+struct OutputBudget {
+    /// One permit per KiB of buffered output
+    chunks: Semaphore,
+    collectors: Semaphore,
+    capture: CaptureLimits,
+}
+
+/// Per-test capture limits for each stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CaptureLimits {
+    pub stdout_bytes: usize,
+    pub stderr_bytes: usize,
+    /// Keep the end of stderr rather than the start
+    pub stderr_tail: bool,
+}
+
+impl Default for CaptureLimits {
+    fn default() -> Self {
+        Self {
+            stdout_bytes: 64 * 1024,
+            stderr_bytes: 64 * 1024,
+            stderr_tail: false,
+        }
+    }
+}
+
+static BUDGET: OnceLock<OutputBudget> = OnceLock::new();
+
+/// Size the budget (call once at startup; later calls are ignored)
+/// Returns the effective byte budget
+pub fn init(configured_mb: Option<u64>, max_collectors: usize, capture: CaptureLimits) -> u64 {
+    let budget_bytes = resolve_budget_bytes(configured_mb, cgroup_memory_limit());
+    let _ = BUDGET.set(OutputBudget::new(budget_bytes, max_collectors, capture));
+    budget_bytes
+}
+
+fn budget() -> &'static OutputBudget {
+    BUDGET.get_or_init(|| OutputBudget::new(DEFAULT_BUDGET_BYTES, DEFAULT_MAX_COLLECTORS, CaptureLimits::default()))
+}
+
+impl OutputBudget {
+    fn new(budget_bytes: u64, max_collectors: usize, capture: CaptureLimits) -> Self {
+        let chunks = (budget_bytes / CHUNK_BYTES as u64).clamp(1, Semaphore::MAX_PERMITS as u64) as usize;
+        Self {
+            chunks: Semaphore::new(chunks),
+            collectors: Semaphore::new(max_collectors.max(1)),
+            capture,
+        }
+    }
+}
+
+/// Explicit setting wins, then a share of the cgroup limit, then the default
+fn resolve_budget_bytes(configured_mb: Option<u64>, cgroup_limit: Option<u64>) -> u64 {
+    match (configured_mb, cgroup_limit) {
+        (Some(mb), _) => mb * 1024 * 1024,
+        (None, Some(limit)) => limit * CGROUP_SHARE_PERCENT / 100,
+        (None, None) => DEFAULT_BUDGET_BYTES,
+    }
+}
+
+/// Memory limit of the worker's own cgroup (v2, then v1), if one is set
+fn cgroup_memory_limit() -> Option<u64> {
+    if let Ok(value) = std::fs::read_to_string("/sys/fs/cgroup/memory.max") {
+        return value.trim().parse().ok(); // "max" = unlimited
+    }
+    std::fs::read_to_string("/sys/fs/cgroup/memory/memory.limit_in_bytes")
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .filter(|&limit| limit < (1 << 60)) // v1 reports "unlimited" as a huge value
+}
+
+/// One execution's share of the budget
+///
+/// Holds a collector slot plus the bytes reserved so far; both are returned
+/// on drop.
+pub struct OutputBuffer {
+    _slot: SemaphorePermit<'static>,
+    used_bytes: usize,
+    reserved_chunks: usize,
+    truncated: bool,
+}
+
+/// Wait for a free log-collection slot
+pub async fn collector() -> OutputBuffer {
+    let slot = budget()
+        .collectors
+        .acquire()
+        .await
+        .expect("output budget is never closed");
+    OutputBuffer {
+        _slot: slot,
+        used_bytes: 0,
+        reserved_chunks: 0,
+        truncated: false,
+    }
+}
+
+impl OutputBuffer {
+    /// Reserve room for `bytes` more output
+    /// Returns false (and marks the buffer truncated) when the budget is exhausted
+    pub fn try_grow(&mut self, bytes: usize) -> bool {
+        if self.truncated {
+            return false;
+        }
+
+        let needed = (self.used_bytes + bytes).div_ceil(CHUNK_BYTES);
+        let extra = needed.saturating_sub(self.reserved_chunks);
+        if extra > 0 {
+            let acquired = u32::try_from(extra)
+                .ok()
+                .and_then(|n| budget().chunks.try_acquire_many(n).ok());
+            match acquired {
+                Some(permit) => {
+                    permit.forget();
+                    self.reserved_chunks += extra;
+                    metrics::OUTPUT_BUFFER_BYTES.add((extra * CHUNK_BYTES) as i64);
+                }
+                None => {
+                    self.truncated = true;
+                    metrics::OUTPUT_TRUNCATIONS.inc();
+                    return false;
+                }
+            }
+        }
+
+        self.used_bytes += bytes;
+        true
+    }
+
+    pub fn truncated(&self) -> bool {
+        self.truncated
+    }
+}
+
+/// One stream (stdout or stderr) of a test, capped at its capture limit
+#[derive(Debug)]
+pub struct CapturedStream {
+    bytes: Vec<u8>,
+    truncated: bool,
+    limit: usize,
+    keep_tail: bool,
+}
+
+impl CapturedStream {
+    fn new(limit: usize, keep_tail: bool) -> Self {
+        Self {
+            bytes: Vec::new(),
+            truncated: false,
+            limit: limit.max(1),
+            keep_tail,
+        }
+    }
+
+    /// The judged stream - always keeps its start
+    pub fn stdout() -> Self {
+        Self::new(budget().capture.stdout_bytes, false)
+    }
+
+    /// The diagnostics stream - start or end, per `STDERR_RETENTION`
+    pub fn stderr() -> Self {
+        let capture = budget().capture;
+        Self::new(capture.stderr_bytes, capture.stderr_tail)
+    }
+
+    /// Buffer a log chunk, charging new bytes to the execution's budget share
+    pub fn push(&mut self, message: &[u8], buffer: &mut OutputBuffer) {
+        self.append(message, |bytes| buffer.try_grow(bytes));
+    }
+
+    /// Keep the head (or tail) of the stream within the limit, flagging anything dropped
+    /// `reserve` is asked before the buffer grows; a refusal drops the chunk
+    fn append(&mut self, message: &[u8], mut reserve: impl FnMut(usize) -> bool) {
+        let kept = if self.keep_tail {
+            &message[message.len().saturating_sub(self.limit)..]
+        } else {
+            &message[..message.len().min(self.limit.saturating_sub(self.bytes.len()))]
+        };
+        if kept.len() < message.len() {
+            self.truncated = true;
+        }
+
+        // A full tail buffer rotates in place - only growth is charged
+        let growth = (self.bytes.len() + kept.len()).min(self.limit) - self.bytes.len();
+        if growth > 0 && !reserve(growth) {
+            self.truncated = true;
+            return;
+        }
+
+        self.bytes.extend_from_slice(kept);
+        if self.bytes.len() > self.limit {
+            let excess = self.bytes.len() - self.limit;
+            self.bytes.drain(..excess);
+            self.truncated = true;
+        }
+    }
+
+    /// Captured text (lossy UTF-8) and whether anything was dropped
+    pub fn finish(self) -> (String, bool) {
+        (String::from_utf8_lossy(&self.bytes).into_owned(), self.truncated)
+    }
+}
+
+impl Drop for OutputBuffer {
+    fn drop(&mut self) {
+        if self.reserved_chunks > 0 {
+            budget().chunks.add_permits(self.reserved_chunks);
+            metrics::OUTPUT_BUFFER_BYTES.sub((self.reserved_chunks * CHUNK_BYTES) as i64);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_resolution_order() {
+        assert_eq!(resolve_budget_bytes(Some(64), Some(4 << 30)), 64 * 1024 * 1024);
+        assert_eq!(resolve_budget_bytes(None, Some(4 << 30)), 1 << 30);
+        assert_eq!(resolve_budget_bytes(None, None), DEFAULT_BUDGET_BYTES);
+    }
+
+    #[test]
+    fn test_capture_stops_at_limit() {
+        let mut charged = 0;
+        let mut stream = CapturedStream::new(8, false);
+        stream.append(b"hello ", |n| { charged += n; true });
+        assert!(!stream.truncated);
+        stream.append(b"world", |n| { charged += n; true });
+        stream.append(b"!", |n| { charged += n; true });
+
+        assert_eq!(charged, 8);
+        assert_eq!(stream.finish(), ("hello wo".to_string(), true));
+    }
+
+    #[test]
+    fn test_capture_keeps_tail() {
+        let mut charged = 0;
+        let mut stream = CapturedStream::new(8, true);
+        stream.append(b"debug 1\n", |n| { charged += n; true });
+        stream.append(b"debug 2\n", |n| { charged += n; true });
+        stream.append(b"panic!", |n| { charged += n; true });
+
+        assert_eq!(charged, 8);
+        assert_eq!(stream.finish(), ("2\npanic!".to_string(), true));
+    }
+
+    #[test]
+    fn test_capture_refused_by_budget() {
+        let mut stream = CapturedStream::new(64, false);
+        stream.append(b"ok", |_| true);
+        stream.append(b"lost", |_| false);
+        assert_eq!(stream.finish(), ("ok".to_string(), true));
+    }
+}
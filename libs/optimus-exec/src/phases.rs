@@ -0,0 +1,92 @@
+//! Phase Timing - Where a Job's Worker Time Goes
+//!
+//! **Responsibility:**
+//! Accumulate wall time per phase for one job so latency regressions can be
+//! attributed to the right step. Container phases are summed over every
+//! container the job started (compile-only run included).
+//!
+//! **Phases:**
+//! - `permit_wait`: dequeue until the local (and cluster-wide) permit is held
+//! - `image_check`: making sure the language image is present (pulls included)
+//! - `container_create`: create plus project/fixture/build uploads
+//! - `execute`: container start until exit (or kill on timeout)
+//! - `evaluate`: judging outputs and scoring
+//! - `persist`: storing the result (histogram and logs only - the stored
+//!   result can't contain its own write time)
+//!
+//! Every phase is exported as `optimus_worker_phase_duration_ms`.
+
+use crate::metrics;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+pub const PERMIT_WAIT: &str = "permit_wait";
+pub const IMAGE_CHECK: &str = "image_check";
+pub const CONTAINER_CREATE: &str = "container_create";
+pub const EXECUTE: &str = "execute";
+pub const EVALUATE: &str = "evaluate";
+pub const PERSIST: &str = "persist";
+
+/// Per-phase durations of one job (or one container run)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PhaseTimings {
+    phases: BTreeMap<&'static str, Duration>,
+}
+
+impl PhaseTimings {
+    /// Add time spent in a phase
+    pub fn add(&mut self, phase: &'static str, elapsed: Duration) {
+        *self.phases.entry(phase).or_default() += elapsed;
+    }
+
+    /// Fold another run's phases into these
+    pub fn merge(&mut self, other: &PhaseTimings) {
+        for (&phase, &elapsed) in &other.phases {
+            self.add(phase, elapsed);
+        }
+    }
+
+    /// Milliseconds per phase, as stored in the result's timing summary
+    pub fn to_millis(&self) -> BTreeMap<String, u64> {
+        self.phases
+            .iter()
+            .map(|(phase, elapsed)| (phase.to_string(), elapsed.as_millis() as u64))
+            .collect()
+    }
+
+    /// Export every recorded phase to the phase histogram
+    pub fn observe(&self, language: &str) {
+        for (phase, elapsed) in &self.phases {
+            metrics::PHASE_DURATION_MS
+                .with_label_values(&[language, phase])
+                .observe(elapsed.as_secs_f64() * 1000.0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_phases_accumulate_across_runs() {
+        let mut first = PhaseTimings::default();
+        first.add(CONTAINER_CREATE, Duration::from_millis(40));
+        first.add(EXECUTE, Duration::from_millis(300));
+
+        let mut second = PhaseTimings::default();
+        second.add(CONTAINER_CREATE, Duration::from_millis(35));
+        second.add(EXECUTE, Duration::from_micros(120_900));
+
+        let mut job = PhaseTimings::default();
+        job.add(PERMIT_WAIT, Duration::from_millis(2));
+        job.merge(&first);
+        job.merge(&second);
+
+        let millis = job.to_millis();
+        assert_eq!(millis.len(), 3);
+        assert_eq!(millis["container_create"], 75);
+        assert_eq!(millis["execute"], 420);
+        assert_eq!(millis["permit_wait"], 2);
+    }
+}
@@ -0,0 +1,87 @@
+//! Podman Engine - Execution on Docker-less Nodes
+//!
+//! **Responsibility:**
+//! Run submissions through Podman (`OPTIMUS_CONTAINER_RUNTIME=podman`).
+//!
+//! **How:**
+//! Podman serves the Docker Engine API on its own socket
+//! (`podman system service`), so this engine drives the same container
+//! lifecycle as DockerEngine over that socket - payload on attached stdin,
+//! exec for per-job containers, OOM detection from inspect.
+//!
+//! **Differences from Docker:**
+//! - Networking is pinned to `none` explicitly rather than relying on
+//!   `NetworkDisabled`, which Podman's compatibility layer treats loosely
+//! - Language `runtime`s name OCI runtimes from containers.conf
+//!   (`[engine.runtimes]`) instead of daemon.json
+//! - Images built with `podman build` are stored as `localhost/<name>`;
+//!   the short names in languages.json resolve to them
+
+use crate::archive::ProjectBundle;
+use crate::config::LanguageConfigManager;
+use crate::container_runtime::ContainerRuntime;
+use crate::engine::{BuildCache, DockerEngine, ExecutionEngine, JobContainer, ResourceOverrides};
+use crate::evaluator::TestExecutionOutput;
+use anyhow::Result;
+use async_trait::async_trait;
+use optimus_common::types::{Language, ProjectArchive, TestCase};
+
+pub struct PodmanEngine {
+    inner: DockerEngine,
+}
+
+impl PodmanEngine {
+    /// Connect to the Podman API socket (see `container_runtime`)
+    pub fn new_with_config(config_manager: &LanguageConfigManager) -> Result<Self> {
+        let podman = ContainerRuntime::Podman.connect()?;
+        Ok(PodmanEngine {
+            inner: DockerEngine::with_client(podman, config_manager).with_network_mode("none"),
+        })
+    }
+}
+
+#[async_trait]
+impl ExecutionEngine for PodmanEngine {
+    fn language_version(&self, language: &Language) -> Option<String> {
+        self.inner.language_version(language)
+    }
+
+    fn prepare_project(&self, language: &Language, project: &ProjectArchive) -> Result<ProjectBundle> {
+        self.inner.prepare_project(language, project)
+    }
+
+    async fn execute_in_container(
+        &self,
+        language: &Language,
+        source_code: &str,
+        test_case: &TestCase,
+        project: Option<&ProjectBundle>,
+        timeout_ms: u64,
+        overrides: ResourceOverrides,
+        build: BuildCache<'_>,
+    ) -> Result<TestExecutionOutput> {
+        self.inner
+            .execute_in_container(language, source_code, test_case, project, timeout_ms, overrides, build)
+            .await
+    }
+
+    async fn start_job_container<'a>(
+        &'a self,
+        language: &Language,
+        source_code: &str,
+        overrides: ResourceOverrides,
+        build: BuildCache<'_>,
+    ) -> Result<JobContainer<'a>> {
+        self.inner.start_job_container(language, source_code, overrides, build).await
+    }
+
+    async fn execute_in_job_container(
+        &self,
+        container: &JobContainer<'_>,
+        language: &Language,
+        test_case: &TestCase,
+        timeout_ms: u64,
+    ) -> Result<TestExecutionOutput> {
+        self.inner.execute_in_job_container(container, language, test_case, timeout_ms).await
+    }
+}
@@ -0,0 +1,624 @@
+//! Sandbox Engine - Execution without a Container Daemon
+//!
+//! **Responsibility:**
+//! Run submissions on the host under nsjail or bubblewrap
+//! (`OPTIMUS_CONTAINER_RUNTIME=nsjail` / `bwrap`), for single machines and
+//! CI runners that have no Docker daemon.
+//!
+//! **How:**
+//! Each test gets a scratch directory under `OPTIMUS_SANDBOX_DIR` holding
+//! what a container would have received (source, project files, fixtures,
+//! cached build). The sandbox sees only the host's toolchain paths read-only
+//! (`/usr`, `/bin`, `/lib*`, `/etc/alternatives`, ... plus
+//! `OPTIMUS_SANDBOX_BINDS`), with that directory bound at `/code` and
+//! `/tmp/optimus`, and runs the universal runner (`OPTIMUS_SANDBOX_RUNNER`,
+//! by default the images' path) - so the language toolchains must be
+//! installed on the host. Home directories, `/root`, `/var` (and with it
+//! `/var/run/secrets`) and the rest of `/etc` stay hidden, and startup fails
+//! if `OPTIMUS_SANDBOX_DIR` lies under a bound path.
+//!
+//! **Isolation:**
+//! - nsjail: fresh user/pid/mount/net/ipc/uts namespaces, cgroup v2 memory,
+//!   CPU and pid limits, rlimits, and a seccomp policy denying kernel-level
+//!   syscalls (mount, ptrace, bpf, module loading, ...)
+//! - bubblewrap: fresh namespaces (`--unshare-all`, so no network) and
+//!   rlimits via `prlimit`; no cgroup limits and no seccomp filter
+//!
+//! **Differences from containers:**
+//! - No per-job containers (`EXECUTION_MODE=per_job` falls back to one
+//!   sandbox per test)
+//! - No CPU-time measurement: CPU-time limits use the extended wall clock
+//! - A memory-limit kill is reported as a SIGKILL runtime error
+//! - Languages with a `runtime` or `gpu` need a container runtime
+
+use crate::archive::{self, ProjectBundle};
+use crate::config::LanguageConfigManager;
+use crate::container_runtime::ContainerRuntime;
+use crate::engine::{self, BuildCache, ExecutionEngine, JobContainer, ResourceOverrides};
+use crate::evaluator::TestExecutionOutput;
+use crate::output_budget;
+use crate::phases::{self, PhaseTimings};
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use optimus_common::types::{Language, ProjectArchive, TestCase};
+use std::os::unix::process::ExitStatusExt;
+use std::path::{Path, PathBuf};
+use std::process::{ExitStatus, Stdio};
+use std::time::{Duration, Instant};
+use tokio::io::AsyncReadExt;
+use tokio::process::Command;
+use tracing::{debug, warn};
+
+/// PATH inside the sandbox (the host's toolchains)
+const SANDBOX_PATH: &str = "/usr/local/sbin:/usr/local/bin:/usr/sbin:/usr/bin:/sbin:/bin";
+
+/// Host paths bound read-only into every sandbox (skipped if missing)
+const TOOLCHAIN_PATHS: &[&str] = &[
+    "/usr",
+    "/bin",
+    "/sbin",
+    "/lib",
+    "/lib32",
+    "/lib64",
+    "/libx32",
+    "/etc/alternatives",
+    "/etc/ld.so.cache",
+    "/etc/ld.so.conf",
+    "/etc/ld.so.conf.d",
+];
+
+/// Device nodes bound into nsjail sandboxes (bubblewrap creates its own `/dev`)
+const DEVICES: &[&str] = &["/dev/null", "/dev/zero", "/dev/random", "/dev/urandom"];
+
+/// Largest file a program may write, in MB
+const MAX_FILE_SIZE_MB: u64 = 64;
+const MAX_OPEN_FILES: u64 = 256;
+/// Processes and threads per sandbox (nsjail only)
+const MAX_PIDS: u64 = 128;
+
+/// Syscalls refused with EPERM under nsjail (kafel policy); everything else is allowed
+const SECCOMP_POLICY: &str = "POLICY optimus { ERRNO(1) { \
+    ptrace, process_vm_readv, process_vm_writev, mount, umount2, pivot_root, chroot, \
+    unshare, setns, kexec_load, kexec_file_load, init_module, finit_module, delete_module, \
+    reboot, swapon, swapoff, bpf, perf_event_open, userfaultfd, keyctl, add_key, request_key, \
+    acct, quotactl, syslog, settimeofday, clock_settime, sethostname, setdomainname \
+} } USE optimus DEFAULT ALLOW";
+
+/// Where the runner reads the source and runs from, inside the sandbox
+const JOB_DIR: &str = "/tmp/optimus";
+const CODE_DIR: &str = "/code";
+
+pub struct SandboxEngine {
+    runtime: ContainerRuntime,
+    /// nsjail or bwrap executable
+    binary: PathBuf,
+    /// Universal runner on the host
+    runner: String,
+    /// Host paths the sandbox sees read-only
+    binds: Vec<PathBuf>,
+    /// Parent of every test's scratch directory
+    work_dir: PathBuf,
+    config_manager: LanguageConfigManager,
+}
+
+impl SandboxEngine {
+    pub fn new_with_config(runtime: ContainerRuntime, config_manager: &LanguageConfigManager) -> Result<Self> {
+        let binary = find_binary(runtime)?;
+        let runner = runner_path();
+        let binds = bind_paths(&runner);
+        let work_dir = work_dir();
+        std::fs::create_dir_all(&work_dir)
+            .with_context(|| format!("Failed to create sandbox directory {}", work_dir.display()))?;
+        check_hidden(&work_dir, &binds)?;
+        Ok(SandboxEngine {
+            runtime,
+            binary,
+            runner,
+            binds,
+            work_dir,
+            config_manager: config_manager.clone(),
+        })
+    }
+
+    /// Limits for one run: a job-level override wins over the language default
+    fn limits(&self, language: &Language, overrides: ResourceOverrides, timeout_ms: u64) -> Limits {
+        Limits {
+            memory_mb: overrides
+                .memory_limit_mb
+                .or_else(|| self.config_manager.get_memory_limit_mb(language).ok())
+                .unwrap_or(256) as u64,
+            cpus: overrides
+                .cpu_limit
+                .or_else(|| self.config_manager.get_cpu_limit(language).ok().map(f64::from))
+                .unwrap_or(0.5),
+            // The sandbox's own backstop, past the worker's hard timeout
+            time_limit_secs: timeout_ms.div_ceil(1000) + 1,
+        }
+    }
+}
+
+/// Confirm the sandbox tool, the runner and the scratch directory are usable
+pub fn check(runtime: ContainerRuntime) -> Result<()> {
+    find_binary(runtime)?;
+    let runner = runner_path();
+    if !Path::new(&runner).is_file() {
+        bail!("Runner {} not found - install dockerfiles/runner.sh there or set OPTIMUS_SANDBOX_RUNNER", runner);
+    }
+    let work_dir = work_dir();
+    std::fs::create_dir_all(&work_dir)
+        .with_context(|| format!("Failed to create sandbox directory {}", work_dir.display()))?;
+    check_hidden(&work_dir, &bind_paths(&runner))
+}
+
+/// Host paths bound read-only into the sandbox: the existing
+/// `TOOLCHAIN_PATHS`, `OPTIMUS_SANDBOX_BINDS` (a PATH-style list, e.g. for a
+/// JDK under /opt) and the runner itself
+fn bind_paths(runner: &str) -> Vec<PathBuf> {
+    let mut binds: Vec<PathBuf> = TOOLCHAIN_PATHS
+        .iter()
+        .map(PathBuf::from)
+        .filter(|path| path.exists())
+        .collect();
+    if let Some(extra) = std::env::var_os("OPTIMUS_SANDBOX_BINDS") {
+        binds.extend(std::env::split_paths(&extra).filter(|path| !path.as_os_str().is_empty()));
+    }
+    binds.push(PathBuf::from(runner));
+    binds
+}
+
+/// Refuse a scratch directory the sandbox can see: every run could read the
+/// other runs' sources and tests through the bound path
+fn check_hidden(work_dir: &Path, binds: &[PathBuf]) -> Result<()> {
+    let resolve = |path: &Path| path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let work_dir = resolve(work_dir);
+    if let Some(bind) = binds.iter().find(|bind| work_dir.starts_with(resolve(bind))) {
+        bail!(
+            "OPTIMUS_SANDBOX_DIR {} is inside {}, which the sandbox sees - move it elsewhere (e.g. under /tmp or /var)",
+            work_dir.display(),
+            bind.display()
+        );
+    }
+    Ok(())
+}
+
+/// `OPTIMUS_SANDBOX_BIN`, else the tool's usual name looked up on PATH
+fn find_binary(runtime: ContainerRuntime) -> Result<PathBuf> {
+    if let Ok(binary) = std::env::var("OPTIMUS_SANDBOX_BIN") {
+        return Ok(PathBuf::from(binary));
+    }
+    let name = runtime.to_string();
+    find_on_path(&name).with_context(|| format!("{} not found on PATH (or set OPTIMUS_SANDBOX_BIN)", name))
+}
+
+/// First executable called `name` on the worker's PATH
+pub fn find_on_path(name: &str) -> Option<PathBuf> {
+    std::env::var_os("PATH")
+        .iter()
+        .flat_map(std::env::split_paths)
+        .map(|dir| dir.join(name))
+        .find(|candidate| candidate.is_file())
+}
+
+fn runner_path() -> String {
+    std::env::var("OPTIMUS_SANDBOX_RUNNER").unwrap_or_else(|_| engine::RUNNER_PATH.to_string())
+}
+
+/// Parent of every run's scratch directory (`OPTIMUS_SANDBOX_DIR`)
+pub fn work_dir() -> PathBuf {
+    std::env::var_os("OPTIMUS_SANDBOX_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| std::env::temp_dir().join("optimus-sandbox"))
+}
+
+/// Resources of one sandboxed run
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Limits {
+    memory_mb: u64,
+    cpus: f64,
+    time_limit_secs: u64,
+}
+
+/// Command line running `runner args...` under the sandbox tool
+///
+/// `scratch` holds `code/` and `tmp/optimus/`, bound at `/code` and
+/// `/tmp/optimus`; `binds` are the host paths visible read-only and `env` is
+/// the runner's whole environment.
+#[allow(clippy::too_many_arguments)]
+fn sandbox_args(
+    runtime: ContainerRuntime,
+    scratch: &Path,
+    binds: &[PathBuf],
+    limits: Limits,
+    env: &[String],
+    runner: &str,
+    args: &[String],
+) -> Vec<String> {
+    let code = scratch.join("code").display().to_string();
+    let job = scratch.join("tmp/optimus").display().to_string();
+    let mut cmd: Vec<String> = Vec::new();
+    match runtime {
+        ContainerRuntime::Nsjail => {
+            // No --chroot: the root is an empty tmpfs holding only these mounts
+            cmd.extend(["--mode", "o", "--quiet"].map(String::from));
+            for bind in binds {
+                cmd.extend(["--bindmount_ro".to_string(), bind.display().to_string()]);
+            }
+            for device in DEVICES {
+                cmd.extend(["--bindmount", device].map(String::from));
+            }
+            cmd.extend(["--tmpfsmount", "/tmp"].map(String::from));
+            cmd.extend(["--bindmount".to_string(), format!("{}:{}", code, CODE_DIR)]);
+            cmd.extend(["--bindmount".to_string(), format!("{}:{}", job, JOB_DIR)]);
+            cmd.extend(["--cwd", CODE_DIR, "--user", "65534", "--group", "65534"].map(String::from));
+            cmd.extend(["--time_limit".to_string(), limits.time_limit_secs.to_string()]);
+            // Memory is capped by the cgroup; address-space limits break JVMs
+            cmd.extend(["--rlimit_as", "max", "--rlimit_core", "0"].map(String::from));
+            cmd.extend(["--rlimit_fsize".to_string(), MAX_FILE_SIZE_MB.to_string()]);
+            cmd.extend(["--rlimit_nofile".to_string(), MAX_OPEN_FILES.to_string()]);
+            cmd.push("--use_cgroupv2".to_string());
+            cmd.extend(["--cgroup_mem_max".to_string(), (limits.memory_mb * 1024 * 1024).to_string()]);
+            cmd.extend(["--cgroup_pids_max".to_string(), MAX_PIDS.to_string()]);
+            cmd.extend(["--cgroup_cpu_ms_per_sec".to_string(), ((limits.cpus * 1000.0) as u64).max(1).to_string()]);
+            cmd.extend(["--seccomp_string".to_string(), SECCOMP_POLICY.to_string()]);
+            for var in env {
+                cmd.extend(["--env".to_string(), var.clone()]);
+            }
+            cmd.extend(["--", runner].map(String::from));
+        }
+        _ => {
+            for bind in binds {
+                let bind = bind.display().to_string();
+                cmd.extend(["--ro-bind".to_string(), bind.clone(), bind]);
+            }
+            cmd.extend(["--dev", "/dev", "--proc", "/proc", "--tmpfs", "/tmp"].map(String::from));
+            cmd.extend(["--bind".to_string(), code, CODE_DIR.to_string()]);
+            cmd.extend(["--bind".to_string(), job, JOB_DIR.to_string()]);
+            cmd.extend(["--unshare-all", "--die-with-parent", "--new-session", "--cap-drop", "ALL"].map(String::from));
+            cmd.extend(["--chdir", CODE_DIR, "--clearenv"].map(String::from));
+            for var in env {
+                let (name, value) = var.split_once('=').unwrap_or((var, ""));
+                cmd.extend(["--setenv", name, value].map(String::from));
+            }
+            // bubblewrap sets no limits of its own; RLIMIT_DATA rather than AS for JVMs
+            cmd.extend(["--", "prlimit"].map(String::from));
+            cmd.push(format!("--data={}", limits.memory_mb * 1024 * 1024));
+            cmd.push(format!("--fsize={}", MAX_FILE_SIZE_MB * 1024 * 1024));
+            cmd.push(format!("--nofile={}", MAX_OPEN_FILES));
+            cmd.push("--core=0".to_string());
+            cmd.extend(["--", runner].map(String::from));
+        }
+    }
+    cmd.extend(args.iter().cloned());
+    cmd
+}
+
+/// The runner's environment for one run, as the container engines set it
+pub fn runner_env(
+    language: &Language,
+    timeout_ms: u64,
+    project: Option<&ProjectBundle>,
+    build: BuildCache<'_>,
+    test_case: &TestCase,
+) -> Vec<String> {
+    let mut env = vec![
+        format!("LANGUAGE={}", language.to_string().to_lowercase()),
+        format!("WALL_TIMEOUT_MS={}", timeout_ms),
+    ];
+    if let Some(project) = project {
+        env.push(format!("ENTRY_COMMAND={}", project.entry_command));
+    }
+    match build {
+        BuildCache::Off => {}
+        BuildCache::Build => env.push("BUILD_ONLY=1".to_string()),
+        BuildCache::Reuse(_) => env.push("PRECOMPILED=1".to_string()),
+    }
+    env.extend(test_case.env.iter().map(|(name, value)| format!("{}={}", name, value)));
+    env
+}
+
+/// A run's scratch directory on the host, removed on drop
+pub struct Scratch {
+    pub root: PathBuf,
+}
+
+impl Scratch {
+    pub fn create(parent: &Path) -> Result<Self> {
+        let root = parent.join(uuid::Uuid::new_v4().to_string());
+        std::fs::create_dir_all(&root).with_context(|| format!("Failed to create {}", root.display()))?;
+        Ok(Scratch { root })
+    }
+
+    /// Lay out what a container would have received, in the same order:
+    /// source, project files, fixtures, then the cached build in `code/`
+    pub fn populate(
+        &self,
+        source_code: &str,
+        test_case: &TestCase,
+        project: Option<&ProjectBundle>,
+        build: BuildCache<'_>,
+    ) -> Result<()> {
+        for dir in ["code", "tmp/optimus"] {
+            std::fs::create_dir_all(self.root.join(dir))
+                .with_context(|| format!("Failed to create {}", self.root.join(dir).display()))?;
+        }
+        self.unpack(&engine::build_submission_archive(&[(engine::SOURCE_ENTRY, source_code)])?, "")?;
+        if let Some(project) = project {
+            self.unpack(&project.tar, "").context("Failed to copy project files")?;
+        }
+        if !test_case.fixtures.is_empty() {
+            self.unpack(&engine::build_fixture_archive(&test_case.fixtures)?, "")
+                .context("Failed to copy fixture files")?;
+        }
+        if let BuildCache::Reuse(artifact) = build {
+            self.unpack(artifact, "code").context("Failed to copy cached build")?;
+        }
+        Ok(())
+    }
+
+    /// Extract a tar the container engines upload at `dir` (relative to the sandbox root)
+    fn unpack(&self, tar: &[u8], dir: &str) -> Result<()> {
+        tar::Archive::new(tar)
+            .unpack(self.root.join(dir))
+            .with_context(|| format!("Failed to unpack into {}", self.root.join(dir).display()))
+    }
+
+    /// The runner's build directory as a tar (as downloaded from a container),
+    /// if the build succeeded and is small enough to cache
+    pub fn collect_build(&self) -> Option<Vec<u8>> {
+        let build_dir = self.root.join("code/.build");
+        let mut builder = tar::Builder::new(Vec::new());
+        if let Err(e) = builder.append_dir_all(".build", &build_dir) {
+            warn!(error = %e, "Failed to archive build");
+            return None;
+        }
+        let tar = builder.into_inner().ok()?;
+        if tar.len() > engine::MAX_BUILD_ARTIFACT_BYTES {
+            debug!("Build exceeds {} bytes - not caching", engine::MAX_BUILD_ARTIFACT_BYTES);
+            return None;
+        }
+        engine::has_build_stamp(&tar).then_some(tar)
+    }
+}
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_dir_all(&self.root) {
+            eprintln!("⚠ Failed to remove scratch directory {}: {}", self.root.display(), e);
+        }
+    }
+}
+
+/// Exit code as a container would report it (signals as 128 + signal)
+fn exit_code(status: ExitStatus) -> Option<i64> {
+    status
+        .code()
+        .map(i64::from)
+        .or_else(|| status.signal().map(|signal| 128 + i64::from(signal)))
+}
+
+#[async_trait]
+impl ExecutionEngine for SandboxEngine {
+    fn language_version(&self, language: &Language) -> Option<String> {
+        Some(self.config_manager.get_config(language).ok()?.version)
+    }
+
+    fn prepare_project(&self, language: &Language, project: &ProjectArchive) -> Result<ProjectBundle> {
+        archive::prepare(project, self.config_manager.get_project_command(language).ok().flatten())
+    }
+
+    /// Run one test case in a fresh sandbox
+    ///
+    /// Same guardrails and runner contract as the container engines: input
+    /// size checks, hard timeout (the sandbox process is killed), guaranteed
+    /// cleanup of the scratch directory, output capped by the output budget.
+    #[allow(clippy::too_many_arguments)]
+    async fn execute_in_container(
+        &self,
+        language: &Language,
+        source_code: &str,
+        test_case: &TestCase,
+        project: Option<&ProjectBundle>,
+        timeout_ms: u64,
+        overrides: ResourceOverrides,
+        build: BuildCache<'_>,
+    ) -> Result<TestExecutionOutput> {
+        engine::validate_test_payload(source_code, test_case)?;
+
+        let mut output_buffer = output_budget::collector().await;
+        let mut phases = PhaseTimings::default();
+
+        let create_start = Instant::now();
+        let scratch = Scratch::create(&self.work_dir)?;
+        scratch.populate(source_code, test_case, project, build)?;
+
+        let mut env = vec![format!("PATH={}", SANDBOX_PATH), format!("HOME={}", CODE_DIR)];
+        env.extend(runner_env(language, timeout_ms, project, build, test_case));
+
+        let limits = self.limits(language, overrides, timeout_ms);
+        let args = sandbox_args(
+            self.runtime,
+            &scratch.root,
+            &self.binds,
+            limits,
+            &env,
+            &self.runner,
+            &test_case.args,
+        );
+        let mut child = Command::new(&self.binary)
+            .args(&args)
+            .env_clear()
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("Failed to start {}", self.binary.display()))?;
+        phases.add(phases::CONTAINER_CREATE, create_start.elapsed());
+
+        let execute_start = Instant::now();
+        let stdin_feed = child
+            .stdin
+            .take()
+            .map(|stdin| engine::feed_stdin(Box::pin(stdin), test_case.input.as_bytes().to_vec()));
+        let mut child_stdout = child.stdout.take().context("sandbox stdout not captured")?;
+        let mut child_stderr = child.stderr.take().context("sandbox stderr not captured")?;
+
+        let mut stdout = output_budget::CapturedStream::stdout();
+        let mut stderr = output_budget::CapturedStream::stderr();
+        let execution_future = async {
+            let (mut out_open, mut err_open) = (true, true);
+            let mut out_chunk = vec![0u8; 8192];
+            let mut err_chunk = vec![0u8; 8192];
+            // Drained but discarded past the capture limit or output budget
+            while out_open || err_open {
+                tokio::select! {
+                    read = child_stdout.read(&mut out_chunk), if out_open => match read {
+                        Ok(0) | Err(_) => out_open = false,
+                        Ok(n) => stdout.push(&out_chunk[..n], &mut output_buffer),
+                    },
+                    read = child_stderr.read(&mut err_chunk), if err_open => match read {
+                        Ok(0) | Err(_) => err_open = false,
+                        Ok(n) => stderr.push(&err_chunk[..n], &mut output_buffer),
+                    },
+                }
+            }
+            child.wait().await
+        };
+        let waited = tokio::time::timeout(Duration::from_millis(timeout_ms), execution_future).await;
+        if let Some(feed) = stdin_feed {
+            feed.abort();
+        }
+
+        let mut output = TestExecutionOutput::default();
+        let (exit_code, out, err, out_truncated, err_truncated) = match waited {
+            Ok(status) => {
+                let status = status.context("Failed to wait for sandbox")?;
+                let code = exit_code(status);
+                let (out, out_truncated) = stdout.finish();
+                let (mut err, err_truncated) = stderr.finish();
+                match code {
+                    None | Some(0) => {}
+                    Some(engine::RUNNER_EXIT_COMPILE_ERROR) => output.compile_error = true,
+                    Some(engine::RUNNER_EXIT_WALL_TIMEOUT) => output.timed_out = true,
+                    Some(engine::RUNNER_EXIT_HARNESS_ERROR) => output.internal_error = true,
+                    Some(code) => {
+                        output.runtime_error = true;
+                        if code == 137 {
+                            err.push_str("\n[Process killed: SIGKILL]");
+                        } else if code == 139 {
+                            err.push_str("\n[Process killed: segmentation fault]");
+                        }
+                    }
+                }
+                (code, out, err, out_truncated, err_truncated)
+            }
+            Err(_) => {
+                println!("    ⚠ Execution timed out after {}ms - killing sandbox", timeout_ms);
+                output.timed_out = true;
+                if let Err(e) = child.kill().await {
+                    eprintln!("    ⚠ Failed to kill timed-out sandbox: {}", e);
+                }
+                (None, String::new(), String::from("\n[Execution timed out]"), false, false)
+            }
+        };
+        output.execution_time_ms = execute_start.elapsed().as_millis() as u64;
+        phases.add(phases::EXECUTE, execute_start.elapsed());
+
+        output.stdout = out;
+        output.stderr = err;
+        if output_buffer.truncated() {
+            output.stderr.push_str(output_budget::TRUNCATION_MARKER);
+        }
+        output.stdout_truncated = out_truncated;
+        output.stderr_truncated = err_truncated;
+        output.exit_code = exit_code;
+        // Only a clean compile-only run yields a build worth caching
+        if matches!(build, BuildCache::Build) && exit_code == Some(0) {
+            output.build_artifact = scratch.collect_build();
+        }
+        output.phases = phases;
+        Ok(output)
+    }
+
+    /// Not available: every test gets its own sandbox
+    async fn start_job_container<'a>(
+        &'a self,
+        _language: &Language,
+        _source_code: &str,
+        _overrides: ResourceOverrides,
+        _build: BuildCache<'_>,
+    ) -> Result<JobContainer<'a>> {
+        bail!("{} has no per-job containers", self.runtime)
+    }
+
+    async fn execute_in_job_container(
+        &self,
+        _container: &JobContainer<'_>,
+        _language: &Language,
+        _test_case: &TestCase,
+        _timeout_ms: u64,
+    ) -> Result<TestExecutionOutput> {
+        bail!("{} has no per-job containers", self.runtime)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIMITS: Limits = Limits { memory_mb: 128, cpus: 0.5, time_limit_secs: 3 };
+
+    fn args(runtime: ContainerRuntime) -> Vec<String> {
+        let env = vec!["LANGUAGE=python".to_string(), "WALL_TIMEOUT_MS=2000".to_string()];
+        let binds = [PathBuf::from("/usr"), PathBuf::from("/opt/optimus/runner.sh")];
+        sandbox_args(
+            runtime,
+            Path::new("/scratch/1"),
+            &binds,
+            LIMITS,
+            &env,
+            "/opt/optimus/runner.sh",
+            &["--flag".to_string()],
+        )
+    }
+
+    fn has_pair(args: &[String], flag: &str, value: &str) -> bool {
+        args.windows(2).any(|pair| pair[0] == flag && pair[1] == value)
+    }
+
+    #[test]
+    fn test_nsjail_args() {
+        let args = args(ContainerRuntime::Nsjail);
+        assert!(!args.contains(&"--chroot".to_string()));
+        assert!(has_pair(&args, "--bindmount_ro", "/usr"));
+        assert!(has_pair(&args, "--bindmount_ro", "/opt/optimus/runner.sh"));
+        assert!(has_pair(&args, "--bindmount", "/dev/null"));
+        assert!(has_pair(&args, "--bindmount", "/scratch/1/code:/code"));
+        assert!(has_pair(&args, "--bindmount", "/scratch/1/tmp/optimus:/tmp/optimus"));
+        assert!(has_pair(&args, "--cgroup_mem_max", "134217728"));
+        assert!(has_pair(&args, "--cgroup_cpu_ms_per_sec", "500"));
+        assert!(has_pair(&args, "--env", "LANGUAGE=python"));
+        assert!(args.contains(&"--seccomp_string".to_string()));
+        assert_eq!(&args[args.len() - 3..], ["--", "/opt/optimus/runner.sh", "--flag"]);
+    }
+
+    #[test]
+    fn test_bwrap_args() {
+        let args = args(ContainerRuntime::Bubblewrap);
+        assert!(args.windows(3).any(|w| w == ["--ro-bind", "/usr", "/usr"]));
+        assert!(!args.windows(3).any(|w| w == ["--ro-bind", "/", "/"]));
+        assert!(args.contains(&"--unshare-all".to_string()));
+        assert!(args.contains(&"--clearenv".to_string()));
+        assert!(args.windows(3).any(|w| w == ["--setenv", "WALL_TIMEOUT_MS", "2000"]));
+        assert!(args.contains(&"--data=134217728".to_string()));
+        assert_eq!(&args[args.len() - 3..], ["--", "/opt/optimus/runner.sh", "--flag"]);
+    }
+
+    #[test]
+    fn test_check_hidden() {
+        let binds = [PathBuf::from("/usr"), PathBuf::from("/opt/optimus/runner.sh")];
+        assert!(check_hidden(Path::new("/tmp/optimus-sandbox"), &binds).is_ok());
+        assert!(check_hidden(Path::new("/usr/local/optimus-sandbox"), &binds).is_err());
+        assert!(check_hidden(Path::new("/usr"), &binds).is_err());
+    }
+}
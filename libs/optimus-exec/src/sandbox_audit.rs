@@ -0,0 +1,208 @@
+//! Sandbox Audit - Post-Execution Escape Heuristics
+//!
+//! **Responsibility:**
+//! Look at a finished (or about-to-be-killed) container for signs that the
+//! submission tried to break out of its sandbox, so the job can be flagged
+//! for review. Heuristics only: nothing here changes a verdict.
+//!
+//! **Checks:**
+//! - Stray processes: more processes than a runner tree needs when the
+//!   container is killed on timeout (daemons, fork loops)
+//! - Excessive pids: task count (threads included) at kill time
+//! - Writes outside the workdir: filesystem diff against the image, ignoring
+//!   the runner's scratch locations
+//!
+//! Audit failures (Docker errors) are logged and produce no flags.
+
+use crate::docker_api;
+use bollard::container::{StatsOptions, TopOptions};
+use bollard::models::{ChangeType, FilesystemChange};
+use bollard::Docker;
+use futures_util::StreamExt;
+use std::fmt;
+use tracing::warn;
+
+/// Processes a runner tree legitimately has (tini, entry script, shell,
+/// timeout, compiler driver + children, program)
+pub const MAX_EXPECTED_PROCESSES: usize = 9;
+
+/// Tasks (processes + threads) at kill time; JVM and Go runtimes use dozens
+pub const MAX_EXPECTED_PIDS: u64 = 256;
+
+/// Paths the runner and language toolchains write to, and the mountpoint
+/// of the per-test results volume
+const ALLOWED_WRITE_PREFIXES: &[&str] = &["/code", "/tmp", "/var/tmp", "/root/.cache", "/opt/optimus/results"];
+
+/// Paths listed per flag before the detail is cut short
+const MAX_LISTED_PATHS: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SandboxCheck {
+    StrayProcesses,
+    ExcessivePids,
+    WriteOutsideWorkdir,
+}
+
+impl SandboxCheck {
+    /// Metric label / flag prefix
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SandboxCheck::StrayProcesses => "stray_processes",
+            SandboxCheck::ExcessivePids => "excessive_pids",
+            SandboxCheck::WriteOutsideWorkdir => "write_outside_workdir",
+        }
+    }
+}
+
+/// One suspicious observation about a container
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SandboxFlag {
+    pub check: SandboxCheck,
+    pub detail: String,
+}
+
+impl fmt::Display for SandboxFlag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.check.as_str(), self.detail)
+    }
+}
+
+/// Inspect a still-running container (call before killing it)
+pub async fn audit_running(docker: &Docker, container_id: &str) -> Vec<SandboxFlag> {
+    let mut flags = Vec::new();
+
+    match docker_api::call("top_processes", || {
+        docker.top_processes(container_id, Some(TopOptions { ps_args: "-eo pid,comm" }))
+    })
+    .await
+    {
+        Ok(top) => flags.extend(process_flag(&top.processes.unwrap_or_default())),
+        Err(e) => warn!(container_id = %container_id, error = %e, "Sandbox audit: failed to list processes"),
+    }
+
+    let stats = {
+        let _permit = docker_api::acquire("stats").await;
+        docker
+            .stats(container_id, Some(StatsOptions { stream: false, one_shot: true }))
+            .next()
+            .await
+    };
+    match stats {
+        Some(Ok(stats)) => flags.extend(pids_flag(stats.pids_stats.current)),
+        Some(Err(e)) => warn!(container_id = %container_id, error = %e, "Sandbox audit: failed to read stats"),
+        None => {}
+    }
+
+    flags
+}
+
+/// Inspect a container's filesystem changes (works after it exited)
+pub async fn audit_filesystem(docker: &Docker, container_id: &str) -> Vec<SandboxFlag> {
+    match docker_api::call("container_changes", || docker.container_changes(container_id)).await {
+        Ok(changes) => write_flag(&changes.unwrap_or_default()).into_iter().collect(),
+        Err(e) => {
+            warn!(container_id = %container_id, error = %e, "Sandbox audit: failed to diff filesystem");
+            Vec::new()
+        }
+    }
+}
+
+/// Flag a process table (one row per process, command last) that's larger than a runner needs
+fn process_flag(processes: &[Vec<String>]) -> Option<SandboxFlag> {
+    if processes.len() <= MAX_EXPECTED_PROCESSES {
+        return None;
+    }
+    let commands: Vec<&str> = processes
+        .iter()
+        .filter_map(|row| row.last().map(String::as_str))
+        .take(MAX_LISTED_PATHS)
+        .collect();
+    Some(SandboxFlag {
+        check: SandboxCheck::StrayProcesses,
+        detail: format!("{} processes at kill time ({}, ...)", processes.len(), commands.join(", ")),
+    })
+}
+
+fn pids_flag(current: Option<u64>) -> Option<SandboxFlag> {
+    let current = current.filter(|&n| n > MAX_EXPECTED_PIDS)?;
+    Some(SandboxFlag {
+        check: SandboxCheck::ExcessivePids,
+        detail: format!("{} tasks at kill time (limit {})", current, MAX_EXPECTED_PIDS),
+    })
+}
+
+fn is_allowed_write(path: &str) -> bool {
+    ALLOWED_WRITE_PREFIXES
+        .iter()
+        .any(|prefix| path == *prefix || path.strip_prefix(prefix).is_some_and(|rest| rest.starts_with('/')))
+}
+
+/// Flag created, deleted or modified paths outside the allowed locations
+/// Docker reports the parent directories of every change as modified, so a
+/// modified path that merely contains another change is ignored.
+fn write_flag(changes: &[FilesystemChange]) -> Option<SandboxFlag> {
+    let is_ancestor = |path: &str| {
+        let dir = format!("{}/", path.trim_end_matches('/'));
+        changes.iter().any(|other| other.path.starts_with(&dir))
+    };
+
+    let offending: Vec<&str> = changes
+        .iter()
+        .filter(|change| !is_allowed_write(&change.path))
+        .filter(|change| change.kind != ChangeType::_0 || !is_ancestor(&change.path))
+        .map(|change| change.path.as_str())
+        .collect();
+
+    if offending.is_empty() {
+        return None;
+    }
+    let mut detail = offending.iter().take(MAX_LISTED_PATHS).copied().collect::<Vec<_>>().join(", ");
+    if offending.len() > MAX_LISTED_PATHS {
+        detail.push_str(&format!(" (+{} more)", offending.len() - MAX_LISTED_PATHS));
+    }
+    Some(SandboxFlag { check: SandboxCheck::WriteOutsideWorkdir, detail })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(path: &str, kind: ChangeType) -> FilesystemChange {
+        FilesystemChange { path: path.to_string(), kind }
+    }
+
+    #[test]
+    fn test_write_flag_ignores_workdir_and_parent_dirs() {
+        let changes = vec![
+            change("/code", ChangeType::_1),
+            change("/code/main", ChangeType::_1),
+            change("/root", ChangeType::_0),
+            change("/root/.cache", ChangeType::_1),
+            change("/root/.cache/go-build/ab", ChangeType::_1),
+            change("/tmp/hsperfdata_root", ChangeType::_1),
+        ];
+        assert_eq!(write_flag(&changes), None);
+
+        let mut escaped = changes.clone();
+        escaped.push(change("/etc/passwd", ChangeType::_0));
+        escaped.push(change("/usr/bin/evil", ChangeType::_1));
+        escaped.push(change("/codex", ChangeType::_1));
+        let flag = write_flag(&escaped).unwrap();
+        assert_eq!(flag.check, SandboxCheck::WriteOutsideWorkdir);
+        assert_eq!(flag.detail, "/etc/passwd, /usr/bin/evil, /codex");
+    }
+
+    #[test]
+    fn test_process_and_pid_thresholds() {
+        let tree: Vec<Vec<String>> = ["tini", "sh", "timeout", "main"].iter().map(|c| vec!["1".to_string(), c.to_string()]).collect();
+        assert_eq!(process_flag(&tree), None);
+
+        let bomb = vec![vec!["7".to_string(), "sh".to_string()]; 40];
+        let flag = process_flag(&bomb).unwrap();
+        assert_eq!(flag.to_string(), "stray_processes: 40 processes at kill time (sh, sh, sh, sh, sh, ...)");
+
+        assert_eq!(pids_flag(Some(MAX_EXPECTED_PIDS)), None);
+        assert_eq!(pids_flag(None), None);
+        assert_eq!(pids_flag(Some(4096)).unwrap().check, SandboxCheck::ExcessivePids);
+    }
+}
@@ -0,0 +1,178 @@
+//! Seccomp Profiles - Per-Language Syscall Filters
+//!
+//! **Responsibility:**
+//! Resolve a language's `seccomp_profile` into the `HostConfig.SecurityOpt`
+//! entry Docker applies to its containers, and reject profiles that would
+//! be refused by the daemon or break every container.
+//!
+//! **Profiles:**
+//! - `"default"`: the profile built into the worker (`seccomp_default.json`),
+//!   an allowlist modelled on Docker's own default that additionally blocks
+//!   ptrace, namespace creation (`unshare`, `setns`, namespaced `clone`),
+//!   mounts, keyrings, BPF, perf, io_uring and the kernel-admin syscalls
+//! - anything else: path to a profile JSON in Docker's format, read when
+//!   each container is created (relative paths resolve from the worker's
+//!   working directory)
+//!
+//! Docker takes the profile content, not a path, so it is sent inline as
+//! `seccomp=<json>`. Languages without `seccomp_profile` keep the daemon's
+//! default profile.
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use std::path::Path;
+
+/// `seccomp_profile` value selecting the built-in profile
+pub const DEFAULT_PROFILE: &str = "default";
+
+const DEFAULT_PROFILE_JSON: &str = include_str!("seccomp_default.json");
+
+/// Actions libseccomp (and so runc/crun) understands
+const ACTIONS: &[&str] = &[
+    "SCMP_ACT_KILL",
+    "SCMP_ACT_KILL_PROCESS",
+    "SCMP_ACT_KILL_THREAD",
+    "SCMP_ACT_TRAP",
+    "SCMP_ACT_ERRNO",
+    "SCMP_ACT_TRACE",
+    "SCMP_ACT_ALLOW",
+    "SCMP_ACT_LOG",
+    "SCMP_ACT_NOTIFY",
+];
+
+const OPERATORS: &[&str] = &[
+    "SCMP_CMP_NE",
+    "SCMP_CMP_LT",
+    "SCMP_CMP_LE",
+    "SCMP_CMP_EQ",
+    "SCMP_CMP_GE",
+    "SCMP_CMP_GT",
+    "SCMP_CMP_MASKED_EQ",
+];
+
+/// Syscall arguments are numbered 0-5
+const MAX_ARG_INDEX: u32 = 5;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Profile {
+    default_action: String,
+    #[serde(default)]
+    syscalls: Vec<SyscallRule>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SyscallRule {
+    #[serde(default)]
+    names: Vec<String>,
+    /// Pre-`names` profiles list one syscall per rule
+    #[serde(default)]
+    name: Option<String>,
+    action: String,
+    #[serde(default)]
+    args: Vec<SyscallArg>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SyscallArg {
+    index: u32,
+    op: String,
+}
+
+/// Profile JSON for a `seccomp_profile` value, validated and compacted
+pub fn load_profile(profile: &str) -> Result<String> {
+    let content = if profile == DEFAULT_PROFILE {
+        DEFAULT_PROFILE_JSON.to_string()
+    } else {
+        std::fs::read_to_string(Path::new(profile))
+            .with_context(|| format!("Failed to read seccomp profile {}", profile))?
+    };
+    let value: serde_json::Value = serde_json::from_str(&content)
+        .with_context(|| format!("Seccomp profile {} is not valid JSON", profile))?;
+    validate(&value).with_context(|| format!("Invalid seccomp profile {}", profile))?;
+    // Re-serialized from the parsed value, so fields we don't check (archMap,
+    // includes, errnoRet, ...) reach Docker untouched
+    Ok(value.to_string())
+}
+
+/// `HostConfig.SecurityOpt` entry applying a `seccomp_profile`
+pub fn security_opt(profile: &str) -> Result<String> {
+    Ok(format!("seccomp={}", load_profile(profile)?))
+}
+
+fn validate(value: &serde_json::Value) -> Result<()> {
+    let profile: Profile = serde_json::from_value(value.clone())?;
+    if !ACTIONS.contains(&profile.default_action.as_str()) {
+        bail!("unknown defaultAction '{}'", profile.default_action);
+    }
+
+    let mut execve_allowed = profile.default_action == "SCMP_ACT_ALLOW";
+    for (i, rule) in profile.syscalls.iter().enumerate() {
+        let names: Vec<&str> = rule.names.iter().map(String::as_str).chain(rule.name.as_deref()).collect();
+        if names.is_empty() || names.iter().any(|name| name.is_empty()) {
+            bail!("syscalls[{}] names no syscall", i);
+        }
+        if !ACTIONS.contains(&rule.action.as_str()) {
+            bail!("syscalls[{}] has unknown action '{}'", i, rule.action);
+        }
+        for arg in &rule.args {
+            if arg.index > MAX_ARG_INDEX {
+                bail!("syscalls[{}] filters argument {} (syscalls have 0-{})", i, arg.index, MAX_ARG_INDEX);
+            }
+            if !OPERATORS.contains(&arg.op.as_str()) {
+                bail!("syscalls[{}] has unknown op '{}'", i, arg.op);
+            }
+        }
+        if rule.action == "SCMP_ACT_ALLOW" && rule.args.is_empty() && names.contains(&"execve") {
+            execve_allowed = true;
+        }
+    }
+
+    // Every container starts by exec'ing tini and the runner
+    if !execve_allowed {
+        bail!("execve is not allowed unconditionally, so no container could start");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_profile_is_valid() {
+        let opt = security_opt(DEFAULT_PROFILE).unwrap();
+        assert!(opt.starts_with("seccomp={"));
+        assert!(!opt.contains('\n'));
+
+        let profile: serde_json::Value = serde_json::from_str(&load_profile(DEFAULT_PROFILE).unwrap()).unwrap();
+        let allowed = profile["syscalls"][0]["names"].as_array().unwrap();
+        for blocked in ["ptrace", "unshare", "setns", "mount", "bpf", "io_uring_setup", "keyctl"] {
+            assert!(!allowed.iter().any(|name| name == blocked), "{} should be blocked", blocked);
+        }
+    }
+
+    #[test]
+    fn test_rejects_broken_profiles() {
+        let check = |json: &str| validate(&serde_json::from_str(json).unwrap());
+
+        assert!(check(r#"{"defaultAction": "SCMP_ACT_ALLOW"}"#).is_ok());
+        assert!(check(r#"{"defaultAction": "SCMP_ACT_ALLOWED"}"#).is_err());
+        assert!(check(r#"{"defaultAction": "SCMP_ACT_ALLOW", "syscalls": [{"names": [], "action": "SCMP_ACT_ERRNO"}]}"#).is_err());
+        assert!(check(r#"{"defaultAction": "SCMP_ACT_ALLOW", "syscalls": [{"names": ["ptrace"], "action": "DENY"}]}"#).is_err());
+        assert!(check(
+            r#"{"defaultAction": "SCMP_ACT_ALLOW", "syscalls": [{"names": ["clone"], "action": "SCMP_ACT_ERRNO",
+                "args": [{"index": 6, "value": 1, "op": "SCMP_CMP_EQ"}]}]}"#
+        ).is_err());
+
+        // An allowlist without execve would fail every container at start
+        assert!(check(r#"{"defaultAction": "SCMP_ACT_ERRNO", "syscalls": [{"names": ["read"], "action": "SCMP_ACT_ALLOW"}]}"#).is_err());
+        assert!(check(r#"{"defaultAction": "SCMP_ACT_ERRNO", "syscalls": [{"name": "execve", "action": "SCMP_ACT_ALLOW"}]}"#).is_ok());
+    }
+
+    #[test]
+    fn test_missing_file() {
+        let err = load_profile("/nonexistent/seccomp.json").unwrap_err();
+        assert!(format!("{:#}", err).contains("Failed to read seccomp profile"));
+    }
+}